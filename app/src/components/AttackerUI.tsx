@@ -5,7 +5,7 @@ import Grid from '@/components/Grid';
 import WalletConnect from '@/components/WalletConnect';
 import { Cell, GameState, Move } from '@/types';
 import { initializeGrid } from '@/lib/utils';
-import { getGameState, getMoves, makeMove, submitTransaction } from '@/lib/stellar';
+import { getFullState, getMoves, submitMove, submitTransaction } from '@/lib/stellar';
 import { useWallet } from 'stellar-wallet-kit';
 import { CONTRACT_CONFIG } from '@/lib/config';
 
@@ -40,10 +40,13 @@ export default function AttackerUI() {
     setIsLoading(true);
     setMessage('Loading game...');
     try {
-      const state = await getGameState(sessionId);
+      const state = await getFullState(sessionId);
       if (state) {
-        setGameState(state);
-        const gameMoves = await getMoves(sessionId);
+        setGameState(state.game);
+        // The recent moves are all of them early on; past that, read them all
+        const gameMoves = state.game.movesMade > state.recentMoves.length
+          ? await getMoves(sessionId)
+          : state.recentMoves;
         setMoves(gameMoves);
         updateGridWithMoves(gameMoves);
         setMessage('Game loaded successfully');
@@ -88,26 +91,8 @@ export default function AttackerUI() {
     try {
       const { x, y } = selectedCell;
 
-      // In a real implementation, this would:
-      // 1. Wait for defender to provide the ZK proof
-      // 2. Verify the proof parameters
-      // Here we're using placeholder values
-
-      const dummyProof = new Uint8Array([0]); // Placeholder
-      const dummyPublicInputs = new Uint8Array([0]); // Placeholder
-
-      // Note: In reality, the defender provides the proof after seeing the move
-      // This is a simplified version for demonstration
-
-      const xdr = await makeMove(
-        sessionId,
-        x,
-        y,
-        false, // isHit - will be determined by defender's proof
-        dummyProof,
-        dummyPublicInputs,
-        publicKey
-      );
+      // The defender answers the submitted cell with a ZK proof through make_move
+      const xdr = await submitMove(sessionId, x, y, publicKey);
 
       const signResult = await signTransaction(xdr);
       const signedXDR = signResult.signedTxXdr;
@@ -13,7 +13,7 @@ import {
   rpc
 } from '@stellar/stellar-sdk';
 import { CONTRACT_CONFIG } from './config';
-import { GameState, Move } from '@/types';
+import { Deadlines, FullState, GameState, Move } from '@/types';
 
 /**
  * Get Stellar Server instance
@@ -73,7 +73,52 @@ export async function startGame(
 }
 
 /**
- * Make a move with ZK proof
+ * Pick the next cell as the attacker, for the defender to answer with makeMove
+ */
+export async function submitMove(
+  sessionId: number,
+  x: number,
+  y: number,
+  signerPublicKey: string
+): Promise<string> {
+  // Validate sessionId is a valid u32
+  if (!Number.isInteger(sessionId) || sessionId < 0 || sessionId > 4294967295) {
+    throw new Error(`Invalid session ID: ${sessionId}. Must be an integer between 0 and 4294967295.`);
+  }
+  // Validate coordinates are valid u32
+  if (!Number.isInteger(x) || x < 0 || x > 4294967295) {
+    throw new Error(`Invalid x coordinate: ${x}. Must be an integer between 0 and 4294967295.`);
+  }
+  if (!Number.isInteger(y) || y < 0 || y > 4294967295) {
+    throw new Error(`Invalid y coordinate: ${y}. Must be an integer between 0 and 4294967295.`);
+  }
+
+  const server = getServer();
+  const account = await loadAccount(signerPublicKey);
+
+  const contract = new Contract(CONTRACT_CONFIG.trapGridContract);
+
+  const transaction = new TransactionBuilder(account, {
+    fee: BASE_FEE,
+    networkPassphrase: CONTRACT_CONFIG.networkPassphrase,
+  })
+    .addOperation(
+      contract.call(
+        'submit_move',
+        nativeToScVal(sessionId, { type: 'u32' }),
+        nativeToScVal(x, { type: 'u32' }),
+        nativeToScVal(y, { type: 'u32' })
+      )
+    )
+    .setTimeout(30)
+    .build();
+
+  const prepared = await server.prepareTransaction(transaction);
+  return prepared.toXDR();
+}
+
+/**
+ * Answer the attacker's submitted move with a ZK proof, as the defender
  */
 export async function makeMove(
   sessionId: number,
@@ -241,6 +286,101 @@ export async function getMoves(sessionId: number): Promise<Move[]> {
   }
 }
 
+/**
+ * Get a game with its pending move, deadlines and last few moves in one read
+ */
+export async function getFullState(sessionId: number): Promise<FullState | null> {
+  try {
+    // Validate sessionId is a valid u32
+    if (!Number.isInteger(sessionId) || sessionId < 0 || sessionId > 4294967295) {
+      console.error('Invalid session ID:', sessionId);
+      return null;
+    }
+
+    const server = getServer();
+    const contract = new Contract(CONTRACT_CONFIG.trapGridContract);
+
+    const sourceKeypair = Keypair.random();
+    const sourceAccount = new Account(sourceKeypair.publicKey(), '0');
+
+    const transaction = new TransactionBuilder(sourceAccount, {
+      fee: BASE_FEE,
+      networkPassphrase: CONTRACT_CONFIG.networkPassphrase,
+    })
+      .addOperation(
+        contract.call('get_full_state', nativeToScVal(sessionId, { type: 'u32' }))
+      )
+      .setTimeout(30)
+      .build();
+
+    const response = await server.simulateTransaction(transaction);
+
+    console.log('getFullState response:', response);
+
+    // @ts-ignore - error property exists at runtime but not in types
+    if (response.error) {
+      // Error(Contract, #1) is GameNotFound
+      // @ts-ignore
+      if (response.error.includes('Error(Contract, #1)')) {
+        return null;
+      }
+      // @ts-ignore
+      throw new Error(`Contract error: ${response.error}`);
+    }
+
+    if (
+      response &&
+      'result' in response &&
+      response.result
+    ) {
+      return parseFullState(response.result.retval, sessionId);
+    }
+
+    console.log('getFullState: No result in response');
+    return null;
+  } catch (error) {
+    console.error('Error getting full state:', error);
+    return null;
+  }
+}
+
+/**
+ * Parse the full state from ScVal
+ */
+function parseFullState(value: xdr.ScVal, sessionId: number): FullState | null {
+  const fields = new Map<string, xdr.ScVal>();
+  for (const entry of value.map() ?? []) {
+    fields.set(entry.key().sym().toString(), entry.val());
+  }
+  const game = fields.get('game');
+  if (!game) {
+    console.log('parseFullState: No game field');
+    return null;
+  }
+  const gameState = parseGameState(game, sessionId);
+  if (!gameState) {
+    return null;
+  }
+
+  const ledger = (field: any) => (field === undefined || field === null ? undefined : Number(field));
+  const deadlines: any = scValToNative(fields.get('deadlines') ?? xdr.ScVal.scvVoid()) || {};
+  // The contract returns the pending move as a vector of at most one
+  const pending: any[] = scValToNative(fields.get('pending_move') ?? xdr.ScVal.scvVoid()) || [];
+  return {
+    game: gameState,
+    pendingMove: pending.length > 0
+      ? { x: Number(pending[0].x), y: Number(pending[0].y) }
+      : undefined,
+    deadlines: {
+      expiresAfter: ledger(deadlines.expires_after),
+      appealUntil: ledger(deadlines.appeal_until),
+      claimableFrom: ledger(deadlines.claimable_from),
+      emergencyWithdrawFrom: ledger(deadlines.emergency_withdraw_from),
+    } as Deadlines,
+    recentMoves: parseMoves(fields.get('recent_moves') ?? xdr.ScVal.scvVec([])),
+  };
+}
+
 /**
  * Parse game state from ScVal
  */
@@ -19,6 +19,28 @@ export interface Move {
   verified: boolean;
 }
 
+export interface PendingMove {
+  x: number;
+  y: number;
+}
+
+/** Ledgers a game's next steps hinge on; undefined where they don't apply */
+export interface Deadlines {
+  expiresAfter?: number;
+  appealUntil?: number;
+  claimableFrom?: number;
+  emergencyWithdrawFrom?: number;
+}
+
+/** A game with its pending move, deadlines and last few moves, read in one call */
+export interface FullState {
+  game: GameState;
+  pendingMove?: PendingMove;
+  deadlines: Deadlines;
+  /** Oldest first; fetch every move with getMoves when movesMade is larger */
+  recentMoves: Move[];
+}
+
 export interface Cell {
   x: number;
   y: number;
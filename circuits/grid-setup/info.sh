@@ -0,0 +1,8 @@
+echo "Show the size of the ZK circuit..."
+bb gates -b target/grid_setup.json | grep "circuit"
+
+# @aztec/bb.js
+#   - Performance and limitations: 
+#     - Max circuit size is 2^19 gates (524,288). This is due to the underlying WASM 4GB memory limit.
+# 
+# Link: https://www.npmjs.com/package/@aztec/bb.js
@@ -4,10 +4,12 @@
  * This script reads the Prover.toml file and extracts the public inputs,
  * then writes them to a binary file in the correct format for the verifier.
  * 
- * Public inputs order (3 fields, each 32 bytes):
+ * Public inputs order (5 fields, each 32 bytes):
  * 1. move_x (u32)
  * 2. move_y (u32)
  * 3. is_hit (u32)
+ * 4. grid_size (u32)
+ * 5. grid_commitment (Field)
  */
 
 import * as fs from 'fs';
@@ -22,6 +24,8 @@ interface PublicInputs {
   move_x: string | number;
   move_y: string | number;
   is_hit: string | number;
+  grid_size: string | number;
+  grid_commitment: string;
 }
 
 function parseToml(content: string): TomlData {
@@ -104,7 +108,7 @@ const data = parseToml(tomlContent);
 // Extract public inputs (they might be at root level or in public_inputs section)
 const publicInputs: PublicInputs = (data.public_inputs || data) as PublicInputs;
 
-// Build the 3 fields (each 32 bytes)
+// Build the 5 fields (each 32 bytes)
 const fields: Buffer[] = [];
 
 // 1. move_x (u32)
@@ -116,6 +120,12 @@ fields.push(u32ToBytes32(publicInputs.move_y));
 // 3. is_hit (u32)
 fields.push(u32ToBytes32(publicInputs.is_hit));
 
+// 4. grid_size (u32)
+fields.push(u32ToBytes32(publicInputs.grid_size));
+
+// 5. grid_commitment (Field)
+fields.push(fieldToBytes32(publicInputs.grid_commitment));
+
 // Combine all fields
 const publicInputsBuffer = Buffer.concat(fields);
 
@@ -129,4 +139,4 @@ if (!fs.existsSync(targetDir)) {
 fs.writeFileSync(outputPath, publicInputsBuffer);
 
 console.log(`✓ Generated public_inputs file: ${publicInputsBuffer.length} bytes`);
-console.log(`  Expected: ${3 * 32} bytes`);
+console.log(`  Expected: ${5 * 32} bytes`);
@@ -2,47 +2,174 @@
 
 /**
  * Helper script to generate valid test data for position-movement Prover.toml
- * 
- * This script creates simple test data for the simplified circuit that verifies:
+ *
+ * This script creates a simple trap grid, commits to it the way grid-setup
+ * does and opens the moved-to cell against that commitment, so the circuit can verify:
  * - Move coordinates are within grid bounds
  * - Trap value matches the claimed result (hit/miss)
- * 
- * Usage: ts-node generate_test_data.ts [move_x] [move_y] [trap_value]
- *        or with tsx: tsx generate_test_data.ts [move_x] [move_y] [trap_value]
- * 
+ * - Trap value is the one at leaf `move_x * grid_size + move_y` of the committed grid
+ *
+ * Usage: ts-node generate_test_data.ts [move_x] [move_y] [grid_size] [salt] [defender_key]
+ *        or with tsx: tsx generate_test_data.ts [move_x] [move_y] [grid_size] [salt] [defender_key]
+ *
+ * `defender_key` is the defender's 32-byte account key or contract id in hex.
+ *
  * Example:
- *   tsx generate_test_data.ts 2 3 1  # Generate test data for hit at (2,3)
- *   tsx generate_test_data.ts 5 1 0  # Generate test data for miss at (5,1)
+ *   tsx generate_test_data.ts 0 0  # Generate test data for a hit at (0,0)
+ *   tsx generate_test_data.ts 5 1  # Generate test data for a miss at (5,1)
  */
 
+import { poseidon2, poseidon3, poseidon4 } from 'poseidon-lite';
+
 // Constants from the circuit
-const GRID_SIZE = 8;
+const MIN_GRID_SIZE = 2;
+const MAX_GRID_SIZE = 16;
+const MAX_MERKLE_TREE_DEPTH = 8;
+
+/**
+ * Depth of the trap Merkle tree of a grid: the fewest levels that hold all its cells
+ */
+function merkleDepth(gridSize: number): number {
+  return Math.ceil(Math.log2(gridSize * gridSize));
+}
+
+// Domain-separation tags from libs/hash/domain.nr: each hash's ASCII name as an integer
+const DOMAIN_TRAP_LEAF = BigInt('0x' + Buffer.from('trap-grid/leaf').toString('hex'));
+const DOMAIN_MERKLE_NODE = BigInt('0x' + Buffer.from('trap-grid/node').toString('hex'));
+const DOMAIN_GRID_COMMITMENT = BigInt('0x' + Buffer.from('trap-grid/grid-commitment').toString('hex'));
+const DOMAIN_DEFENDER = BigInt('0x' + Buffer.from('trap-grid/defender').toString('hex'));
+
+interface TrapPosition {
+  x: number;
+  y: number;
+}
+
+interface MerkleProof {
+  indices: number[];
+  siblings: bigint[];
+}
+
+/**
+ * Generate a simple trap grid (all zeros except specified traps)
+ */
+function generateTrapGrid(gridSize: number, traps: TrapPosition[] = []): number[] {
+  // Cells past the grid pad the last level with empty leaves
+  const grid = new Array(1 << merkleDepth(gridSize)).fill(0);
+  for (const { x, y } of traps) {
+    grid[x * gridSize + y] = 1;
+  }
+  return grid;
+}
+
+/**
+ * Build the trap Merkle tree, leaf level first
+ * Leaves match the circuit's trap_leaf, inner nodes its merkle_node
+ */
+function buildMerkleTree(grid: number[], depth: number): bigint[][] {
+  let currentLevel: bigint[] = grid.map(trap => poseidon2([DOMAIN_TRAP_LEAF, BigInt(trap)]));
+  const tree: bigint[][] = [currentLevel];
+
+  for (let level = 0; level < depth; level++) {
+    const nextLevel: bigint[] = [];
+    for (let i = 0; i < currentLevel.length; i += 2) {
+      nextLevel.push(poseidon3([DOMAIN_MERKLE_NODE, currentLevel[i], currentLevel[i + 1]]));
+    }
+    tree.push(nextLevel);
+    currentLevel = nextLevel;
+  }
+
+  return tree;
+}
+
+/**
+ * Generate Merkle proof for a specific leaf
+ */
+function generateMerkleProof(tree: bigint[][], leafIndex: number, depth: number): MerkleProof {
+  const siblings: bigint[] = [];
+  const indices: number[] = [];
+  let currentIndex = leafIndex;
+
+  for (let level = 0; level < depth; level++) {
+    const isRightChild = currentIndex % 2 === 1;
+    indices.push(isRightChild ? 1 : 0);
+    siblings.push(tree[level][isRightChild ? currentIndex - 1 : currentIndex + 1]);
+    currentIndex = Math.floor(currentIndex / 2);
+  }
+
+  // The circuit takes proofs of the deepest tree; the levels past the depth are zero
+  while (indices.length < MAX_MERKLE_TREE_DEPTH) {
+    indices.push(0);
+    siblings.push(0n);
+  }
+
+  return { indices, siblings };
+}
+
+/**
+ * The defender key's first 31 bytes and its last byte, each as a big-endian integer,
+ * the circuit's `defender` input
+ */
+function defenderFields(keyHex: string): [bigint, bigint] {
+  const key = Buffer.from(keyHex.replace(/^0x/, ''), 'hex');
+  if (key.length !== 32) {
+    console.error('Error: defender_key must be 32 bytes of hex');
+    process.exit(1);
+  }
+  return [BigInt('0x' + key.subarray(0, 31).toString('hex')), BigInt(key[31])];
+}
+
+/**
+ * Convert BigInt to hex string (32 bytes, big-endian)
+ */
+function bigIntToHex(value: bigint | number): string {
+  const bigIntValue = typeof value === 'bigint' ? value : BigInt(value);
+  return '0x' + bigIntValue.toString(16).padStart(64, '0');
+}
 
 /**
  * Main function
  */
 function main(): void {
-  const moveX = parseInt(process.argv[2] || '2');
-  const moveY = parseInt(process.argv[3] || '3');
-  const trapValue = parseInt(process.argv[4] || '1');
+  const moveX = parseInt(process.argv[2] || '0');
+  const moveY = parseInt(process.argv[3] || '0');
+  const gridSize = parseInt(process.argv[4] || '8');
+  const salt = BigInt(process.argv[5] || '12345');
+  const defender = defenderFields(process.argv[6] || '11'.repeat(32));
 
   // Validate inputs
-  if (moveX < 0 || moveX >= GRID_SIZE || moveY < 0 || moveY >= GRID_SIZE) {
-    console.error(`Error: Move coordinates must be in range [0, ${GRID_SIZE - 1}]`);
+  if (gridSize < MIN_GRID_SIZE || gridSize > MAX_GRID_SIZE) {
+    console.error(`Error: grid_size must be in range [${MIN_GRID_SIZE}, ${MAX_GRID_SIZE}]`);
     process.exit(1);
   }
 
-  if (trapValue !== 0 && trapValue !== 1) {
-    console.error('Error: trap_value must be 0 (miss) or 1 (hit)');
+  if (moveX < 0 || moveX >= gridSize || moveY < 0 || moveY >= gridSize) {
+    console.error(`Error: Move coordinates must be in range [0, ${gridSize - 1}]`);
     process.exit(1);
   }
 
+  const depth = merkleDepth(gridSize);
+  const leafIndex = moveX * gridSize + moveY;
+
+  // A simple test grid with traps at (0, 0) and (1, 2)
+  const traps: TrapPosition[] = [
+    { x: 0, y: 0 },
+    { x: 1, y: 2 }
+  ];
+
+  const grid = generateTrapGrid(gridSize, traps);
+  const tree = buildMerkleTree(grid, depth);
+  const root = tree[tree.length - 1][0];
+  const proof = generateMerkleProof(tree, leafIndex, depth);
+  const defenderHash = poseidon4([DOMAIN_DEFENDER, 32n, defender[0], defender[1]]);
+  const gridCommitment = poseidon4([DOMAIN_GRID_COMMITMENT, root, salt, defenderHash]);
+
+  const trapValue = grid[leafIndex];
   const isHit = trapValue;
 
   console.log('\n' + '='.repeat(70));
   console.log('Generated Test Data for Position Movement Circuit');
   console.log('='.repeat(70));
-  console.log(`\nMove: (${moveX}, ${moveY})`);
+  console.log(`\nMove: (${moveX}, ${moveY}) -> Leaf Index: ${leafIndex}`);
   console.log(`Trap Value: ${trapValue} (${trapValue === 1 ? 'HIT' : 'MISS'})`);
   console.log(`Claimed Result (is_hit): ${isHit}`);
   console.log(`\nProver.toml format:\n`);
@@ -50,11 +177,32 @@ function main(): void {
   console.log(`move_x = "${moveX}"`);
   console.log(`move_y = "${moveY}"`);
   console.log(`is_hit = "${isHit}"`);
+  console.log(`grid_size = "${gridSize}"`);
+  console.log(`grid_commitment = "${bigIntToHex(gridCommitment)}"`);
   console.log(`\n[private_inputs]`);
   console.log(`trap_value = "${trapValue}"`);
+  console.log(`salt = "${salt}"`);
+  console.log(`defender = ["${bigIntToHex(defender[0])}", "${bigIntToHex(defender[1])}"]`);
+  console.log(`trap_merkle_proof_indices = [${proof.indices.map(i => `"${i}"`).join(', ')}]`);
+  console.log(`trap_merkle_proof_siblings = [`);
+  proof.siblings.forEach((s, i) => {
+    console.log(`    "${bigIntToHex(s)}"${i < proof.siblings.length - 1 ? ',' : ''}`);
+  });
+  console.log(`]`);
   console.log('\n' + '='.repeat(70));
   console.log('\nCopy the above to position-movement/Prover.toml to use for testing.');
   console.log('='.repeat(70) + '\n');
 }
 
-main();
+// Check if required module is available
+try {
+  main();
+} catch (error) {
+  if (error instanceof Error && error.message.includes('poseidon-lite')) {
+    console.error('\n❌ Error: poseidon-lite not found');
+    console.error('Please run: npm install poseidon-lite@0.3.0');
+    console.error('\nError details:', error.message);
+    process.exit(1);
+  }
+  throw error;
+}
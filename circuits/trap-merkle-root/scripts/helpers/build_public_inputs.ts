@@ -4,14 +4,15 @@
  * This script reads the Prover.toml file and extracts the public inputs,
  * then writes them to a binary file in the correct format for the verifier.
  * 
- * Public inputs order (17 fields, each 32 bytes):
+ * Public inputs order (22 fields, each 32 bytes):
  * 1. trap_merkle_root (Field)
  * 2. move_x (u32)
  * 3. move_y (u32)
  * 4. is_hit (u32)
  * 5. trap_merkle_proof_length (u32)
- * 6-11. trap_merkle_proof_indices (6 x u1)
- * 12-17. trap_merkle_proof_siblings (6 x Field)
+ * 6-13. trap_merkle_proof_indices (8 x u1, zero past the proof length)
+ * 14-21. trap_merkle_proof_siblings (8 x Field, zero past the proof length)
+ * 22. grid_size (u32)
  */
 
 import * as fs from 'fs';
@@ -30,6 +31,7 @@ interface PublicInputs {
   trap_merkle_proof_length: string | number;
   trap_merkle_proof_indices: string[];
   trap_merkle_proof_siblings: string[];
+  grid_size: string | number;
 }
 
 function parseToml(content: string): TomlData {
@@ -112,7 +114,7 @@ const data = parseToml(tomlContent);
 // Extract public inputs (they might be at root level or in public_inputs section)
 const publicInputs: PublicInputs = (data.public_inputs || data) as PublicInputs;
 
-// Build the 17 fields (each 32 bytes)
+// Build the 22 fields (each 32 bytes)
 const fields: Buffer[] = [];
 
 // 1. trap_merkle_root (Field)
@@ -130,24 +132,27 @@ fields.push(u32ToBytes32(publicInputs.is_hit));
 // 5. trap_merkle_proof_length (u32)
 fields.push(u32ToBytes32(publicInputs.trap_merkle_proof_length));
 
-// 6-11. trap_merkle_proof_indices (6 x u1)
+// 6-13. trap_merkle_proof_indices (8 x u1)
 const indices = Array.isArray(publicInputs.trap_merkle_proof_indices)
   ? publicInputs.trap_merkle_proof_indices
   : [];
-for (let i = 0; i < 6; i++) {
+for (let i = 0; i < 8; i++) {
   const value = indices[i] || '0';
   fields.push(u1ToBytes32(value));
 }
 
-// 12-17. trap_merkle_proof_siblings (6 x Field)
+// 14-21. trap_merkle_proof_siblings (8 x Field)
 const siblings = Array.isArray(publicInputs.trap_merkle_proof_siblings)
   ? publicInputs.trap_merkle_proof_siblings
   : [];
-for (let i = 0; i < 6; i++) {
+for (let i = 0; i < 8; i++) {
   const value = siblings[i] || '0x0';
   fields.push(fieldToBytes32(value));
 }
 
+// 22. grid_size (u32)
+fields.push(u32ToBytes32(publicInputs.grid_size));
+
 // Combine all fields
 const publicInputsBuffer = Buffer.concat(fields);
 
@@ -6,16 +6,27 @@
  * This script creates a simple trap grid configuration and computes the
  * Merkle tree with valid proofs that can be used for testing.
  * 
- * Usage: ts-node generate_test_data.ts [move_x] [move_y]
- *        or with tsx: tsx generate_test_data.ts [move_x] [move_y]
+ * Usage: ts-node generate_test_data.ts [move_x] [move_y] [grid_size]
+ *        or with tsx: tsx generate_test_data.ts [move_x] [move_y] [grid_size]
  */
 
-import { poseidon1, poseidon2 } from 'poseidon-lite';
+import { poseidon2, poseidon3 } from 'poseidon-lite';
 
 // Constants from the circuit
-const GRID_SIZE = 8;
-const NUM_CELLS = 64;
-const MERKLE_TREE_DEPTH = 6;
+const MIN_GRID_SIZE = 2;
+const MAX_GRID_SIZE = 16;
+const MAX_MERKLE_TREE_DEPTH = 8;
+
+/**
+ * Depth of the trap Merkle tree of a grid: the fewest levels that hold all its cells
+ */
+function merkleDepth(gridSize: number): number {
+  return Math.ceil(Math.log2(gridSize * gridSize));
+}
+
+// Domain-separation tags from libs/hash/domain.nr: each hash's ASCII name as an integer
+const DOMAIN_TRAP_LEAF = BigInt('0x' + Buffer.from('trap-grid/leaf').toString('hex'));
+const DOMAIN_MERKLE_NODE = BigInt('0x' + Buffer.from('trap-grid/node').toString('hex'));
 
 interface TrapPosition {
   x: number;
@@ -28,8 +39,8 @@ interface MerkleProof {
 }
 
 /**
- * Compute Poseidon hash of two field elements
- * Using poseidon2 from poseidon-lite which matches Noir's poseidon_hash_2
+ * Compute an inner node of the Merkle tree
+ * Using poseidon3 to match the circuit's merkle_node: poseidon_hash_3([DOMAIN_MERKLE_NODE, left, right])
  */
 function poseidonHash2(left: bigint | number, right: bigint | number): bigint {
   // poseidon-lite expects inputs as bigints or numbers
@@ -37,16 +48,17 @@ function poseidonHash2(left: bigint | number, right: bigint | number): bigint {
   const rightBigInt = typeof right === 'bigint' ? right : BigInt(right);
 
   // poseidon2 returns a bigint
-  return poseidon2([leftBigInt, rightBigInt]);
+  return poseidon3([DOMAIN_MERKLE_NODE, leftBigInt, rightBigInt]);
 }
 
 /**
  * Generate a simple trap grid (all zeros except specified traps)
  */
-function generateTrapGrid(traps: TrapPosition[] = []): number[] {
-  const grid = new Array(NUM_CELLS).fill(0);
+function generateTrapGrid(gridSize: number, traps: TrapPosition[] = []): number[] {
+  // Cells past the grid pad the last level with empty leaves
+  const grid = new Array(1 << merkleDepth(gridSize)).fill(0);
   for (const { x, y } of traps) {
-    const index = x * GRID_SIZE + y;
+    const index = x * gridSize + y;
     grid[index] = 1;
   }
   return grid;
@@ -54,17 +66,16 @@ function generateTrapGrid(traps: TrapPosition[] = []): number[] {
 
 /**
  * Compute trap commitment for a given cell
- * Using poseidon1 to match the circuit: poseidon_hash_1([trap_value as Field])
+ * Using poseidon2 to match the circuit's trap_leaf: poseidon_hash_2([DOMAIN_TRAP_LEAF, trap_value as Field])
  */
 function computeTrapCommitment(trapValue: number, leafIndex: number): bigint {
-  // Match the circuit which uses: poseidon_hash_1([trap_value as Field])
-  return poseidon1([BigInt(trapValue)]);
+  return poseidon2([DOMAIN_TRAP_LEAF, BigInt(trapValue)]);
 }
 
 /**
  * Build Merkle tree from trap commitments
  */
-function buildMerkleTree(grid: number[]): bigint[][] {
+function buildMerkleTree(grid: number[], depth: number): bigint[][] {
   // Level 0: Compute commitments for all leaves
   let currentLevel: bigint[] = grid.map((trap, idx) =>
     computeTrapCommitment(trap, idx)
@@ -73,7 +84,7 @@ function buildMerkleTree(grid: number[]): bigint[][] {
   const tree: bigint[][] = [currentLevel];
 
   // Build tree bottom-up
-  for (let level = 0; level < MERKLE_TREE_DEPTH; level++) {
+  for (let level = 0; level < depth; level++) {
     const nextLevel: bigint[] = [];
     for (let i = 0; i < currentLevel.length; i += 2) {
       const left = currentLevel[i];
@@ -90,12 +101,12 @@ function buildMerkleTree(grid: number[]): bigint[][] {
 /**
  * Generate Merkle proof for a specific leaf
  */
-function generateMerkleProof(tree: bigint[][], leafIndex: number): MerkleProof {
+function generateMerkleProof(tree: bigint[][], leafIndex: number, depth: number): MerkleProof {
   const siblings: bigint[] = [];
   const indices: number[] = [];
   let currentIndex = leafIndex;
 
-  for (let level = 0; level < MERKLE_TREE_DEPTH; level++) {
+  for (let level = 0; level < depth; level++) {
     const isRightChild = currentIndex % 2 === 1;
     const siblingIndex = isRightChild ? currentIndex - 1 : currentIndex + 1;
 
@@ -105,6 +116,12 @@ function generateMerkleProof(tree: bigint[][], leafIndex: number): MerkleProof {
     currentIndex = Math.floor(currentIndex / 2);
   }
 
+  // The circuit takes proofs of the deepest tree; the levels past the depth are zero
+  while (indices.length < MAX_MERKLE_TREE_DEPTH) {
+    indices.push(0);
+    siblings.push(0n);
+  }
+
   return { indices, siblings };
 }
 
@@ -124,13 +141,20 @@ function bigIntToHex(value: bigint | number): string {
 function main(): void {
   const moveX = parseInt(process.argv[2] || '0');
   const moveY = parseInt(process.argv[3] || '0');
+  const gridSize = parseInt(process.argv[4] || '8');
+
+  if (gridSize < MIN_GRID_SIZE || gridSize > MAX_GRID_SIZE) {
+    console.error(`Error: grid_size must be in range [${MIN_GRID_SIZE}, ${MAX_GRID_SIZE}]`);
+    process.exit(1);
+  }
 
-  if (moveX < 0 || moveX >= GRID_SIZE || moveY < 0 || moveY >= GRID_SIZE) {
-    console.error(`Error: Move coordinates must be in range [0, ${GRID_SIZE - 1}]`);
+  if (moveX < 0 || moveX >= gridSize || moveY < 0 || moveY >= gridSize) {
+    console.error(`Error: Move coordinates must be in range [0, ${gridSize - 1}]`);
     process.exit(1);
   }
 
-  const leafIndex = moveX * GRID_SIZE + moveY;
+  const depth = merkleDepth(gridSize);
+  const leafIndex = moveX * gridSize + moveY;
 
   // Generate a simple test grid (you can modify this)
   // For testing, let's put a trap at position (0, 0) and (1, 2)
@@ -139,10 +163,10 @@ function main(): void {
     { x: 1, y: 2 }
   ];
 
-  const grid = generateTrapGrid(traps);
-  const tree = buildMerkleTree(grid);
+  const grid = generateTrapGrid(gridSize, traps);
+  const tree = buildMerkleTree(grid, depth);
   const root = tree[tree.length - 1][0];
-  const proof = generateMerkleProof(tree, leafIndex);
+  const proof = generateMerkleProof(tree, leafIndex, depth);
 
   const trapValue = grid[leafIndex];
   const isHit = trapValue;
@@ -159,13 +183,14 @@ function main(): void {
   console.log(`move_x = "${moveX}"`);
   console.log(`move_y = "${moveY}"`);
   console.log(`is_hit = "${isHit}"`);
-  console.log(`trap_merkle_proof_length = "${MERKLE_TREE_DEPTH}"`);
+  console.log(`trap_merkle_proof_length = "${depth}"`);
   console.log(`trap_merkle_proof_indices = [${proof.indices.map(i => `"${i}"`).join(', ')}]`);
   console.log(`trap_merkle_proof_siblings = [`);
   proof.siblings.forEach((s, i) => {
     console.log(`    "${bigIntToHex(s)}"${i < proof.siblings.length - 1 ? ',' : ''}`);
   });
   console.log(`]`);
+  console.log(`grid_size = "${gridSize}"`);
   console.log(`\n[private_inputs]`);
   console.log(`trap_value = "${trapValue}"`);
   console.log('\n' + '='.repeat(70));
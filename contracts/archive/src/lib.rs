@@ -0,0 +1,375 @@
+#![no_std]
+
+//! # Archive
+//!
+//! Permanent history of finished games, kept out of the game contracts:
+//! - The admin allows game contracts (trap-grid) to write
+//! - A game pushes one compact record when a session ends: the players, a
+//!   hash of the configuration it was played under, the result and a replay
+//! - Records are immutable: a session is archived once and never changed,
+//!   so the game contract can let its own records expire
+
+use soroban_sdk::{
+    contract, contracterror, contractevent, contractimpl, contracttype, Address, Bytes, BytesN,
+    Env, Vec,
+};
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum Error {
+    /// The caller is not a game the admin allowed to write
+    NotWriter = 1,
+    /// The game already archived this session
+    AlreadyArchived = 2,
+    RecordNotFound = 3,
+}
+
+// ============================================================================
+// Data Types
+// ============================================================================
+
+/// What a game reports about a finished session
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GameRecord {
+    pub session_id: u32,
+    pub defender: Address,
+    pub attacker: Address,
+    /// SHA-256 of the game's configuration when the session ended
+    pub config_hash: BytesN<32>,
+    /// `None` when the session ended without a winner
+    pub winner: Option<Address>,
+    pub hits: u32,
+    pub misses: u32,
+    /// The moves in play order, in the game's own encoding
+    pub replay: Bytes,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ArchivedGame {
+    pub id: u32,
+    /// Game contract that archived the record
+    pub game: Address,
+    /// Ledger sequence the record was archived in
+    pub ledger: u32,
+    pub record: GameRecord,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum DataKey {
+    Admin,
+    RecordCount,
+    Writer(Address),       // game -> true
+    Record(u32),           // id -> ArchivedGame
+    Session(Address, u32), // (game, session_id) -> id
+    Records(Address),      // player -> Vec<u32> of record ids
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+/// Published by `archive`
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GameArchived {
+    #[topic]
+    pub game: Address,
+    #[topic]
+    pub session_id: u32,
+    pub id: u32,
+}
+
+// ============================================================================
+// Storage TTL Management
+// ============================================================================
+
+const RECORD_TTL_LEDGERS: u32 = 3_110_400; // 180 days, the network's maximum
+
+// ============================================================================
+// Contract Definition
+// ============================================================================
+
+#[contract]
+pub struct ArchiveContract;
+
+#[contractimpl]
+impl ArchiveContract {
+    /// Initialize the contract with the admin who allows games to write
+    pub fn __constructor(env: Env, admin: Address) {
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.storage().instance().set(&DataKey::RecordCount, &0u32);
+    }
+
+    /// Allow or disallow `game` to archive its sessions
+    pub fn set_writer(env: Env, game: Address, allowed: bool) {
+        admin(&env).require_auth();
+        let key = DataKey::Writer(game);
+        if allowed {
+            env.storage().persistent().set(&key, &true);
+            env.storage()
+                .persistent()
+                .extend_ttl(&key, RECORD_TTL_LEDGERS, RECORD_TTL_LEDGERS);
+        } else {
+            env.storage().persistent().remove(&key);
+        }
+    }
+
+    /// Whether `game` may archive its sessions
+    pub fn is_writer(env: Env, game: Address) -> bool {
+        env.storage().persistent().has(&DataKey::Writer(game))
+    }
+
+    /// Archive a finished session of `game`; returns the record's id
+    ///
+    /// # Arguments
+    /// * `game` - Game contract the session was played on; must authorize the call
+    /// * `record` - The session's players, configuration hash, result and replay
+    pub fn archive(env: Env, game: Address, record: GameRecord) -> Result<u32, Error> {
+        game.require_auth();
+        if !Self::is_writer(env.clone(), game.clone()) {
+            return Err(Error::NotWriter);
+        }
+        let session_key = DataKey::Session(game.clone(), record.session_id);
+        if env.storage().persistent().has(&session_key) {
+            return Err(Error::AlreadyArchived);
+        }
+
+        let id: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::RecordCount)
+            .unwrap_or(0);
+        let session_id = record.session_id;
+        let players = [record.defender.clone(), record.attacker.clone()];
+        let archived = ArchivedGame {
+            id,
+            game: game.clone(),
+            ledger: env.ledger().sequence(),
+            record,
+        };
+        let record_key = DataKey::Record(id);
+        env.storage().persistent().set(&record_key, &archived);
+        env.storage()
+            .persistent()
+            .extend_ttl(&record_key, RECORD_TTL_LEDGERS, RECORD_TTL_LEDGERS);
+        env.storage().persistent().set(&session_key, &id);
+        env.storage()
+            .persistent()
+            .extend_ttl(&session_key, RECORD_TTL_LEDGERS, RECORD_TTL_LEDGERS);
+
+        for player in players {
+            let key = DataKey::Records(player);
+            let mut ids: Vec<u32> = env
+                .storage()
+                .persistent()
+                .get(&key)
+                .unwrap_or(Vec::new(&env));
+            ids.push_back(id);
+            env.storage().persistent().set(&key, &ids);
+            env.storage()
+                .persistent()
+                .extend_ttl(&key, RECORD_TTL_LEDGERS, RECORD_TTL_LEDGERS);
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::RecordCount, &(id + 1));
+        env.storage()
+            .instance()
+            .extend_ttl(RECORD_TTL_LEDGERS, RECORD_TTL_LEDGERS);
+
+        GameArchived {
+            game,
+            session_id,
+            id,
+        }
+        .publish(&env);
+        Ok(id)
+    }
+
+    /// Get a record by id
+    pub fn get_record(env: Env, id: u32) -> Result<ArchivedGame, Error> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Record(id))
+            .ok_or(Error::RecordNotFound)
+    }
+
+    /// Get the record of `session_id` of `game`
+    pub fn get_session(env: Env, game: Address, session_id: u32) -> Result<ArchivedGame, Error> {
+        let id: u32 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Session(game, session_id))
+            .ok_or(Error::RecordNotFound)?;
+        Self::get_record(env, id)
+    }
+
+    /// Records of the sessions `player` played, on either side, oldest first
+    pub fn get_records(env: Env, player: Address) -> Vec<ArchivedGame> {
+        let ids: Vec<u32> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Records(player))
+            .unwrap_or(Vec::new(&env));
+        let mut records = Vec::new(&env);
+        for id in ids.iter() {
+            if let Some(record) = env.storage().persistent().get(&DataKey::Record(id)) {
+                records.push_back(record);
+            }
+        }
+        records
+    }
+
+    /// Number of records archived
+    pub fn record_count(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::RecordCount)
+            .unwrap_or(0)
+    }
+}
+
+fn admin(env: &Env) -> Address {
+    env.storage()
+        .instance()
+        .get(&DataKey::Admin)
+        .expect("Admin not set")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use soroban_sdk::testutils::{Address as _, Events as _};
+    use soroban_sdk::Event;
+
+    struct Setup<'a> {
+        env: Env,
+        game: Address,
+        client: ArchiveContractClient<'a>,
+    }
+
+    fn setup(env: &Env) -> Setup<'_> {
+        env.mock_all_auths();
+        let admin = Address::generate(env);
+        let contract = env.register(ArchiveContract, (&admin,));
+        let client = ArchiveContractClient::new(env, &contract);
+        let game = Address::generate(env);
+        client.set_writer(&game, &true);
+        Setup {
+            env: env.clone(),
+            game,
+            client,
+        }
+    }
+
+    fn record(env: &Env, session_id: u32, defender: &Address, attacker: &Address) -> GameRecord {
+        GameRecord {
+            session_id,
+            defender: defender.clone(),
+            attacker: attacker.clone(),
+            config_hash: BytesN::from_array(env, &[7; 32]),
+            winner: Some(attacker.clone()),
+            hits: 1,
+            misses: 1,
+            replay: Bytes::from_array(env, &[0x83, 0x02, 0x04, 0x00]),
+        }
+    }
+
+    #[test]
+    fn test_archive_and_get_records() {
+        let env = Env::default();
+        let setup = setup(&env);
+        let client = &setup.client;
+        let defender = Address::generate(&env);
+        let attacker = Address::generate(&env);
+
+        assert_eq!(client.get_records(&defender).len(), 0);
+        let first = record(&env, 7, &defender, &attacker);
+        assert_eq!(client.archive(&setup.game, &first), 0);
+        let event = GameArchived {
+            game: setup.game.clone(),
+            session_id: 7,
+            id: 0,
+        };
+        assert_eq!(
+            env.events().all().filter_by_contract(&client.address),
+            [event.to_xdr(&env, &client.address)]
+        );
+        let other = Address::generate(&env);
+        assert_eq!(
+            client.archive(&setup.game, &record(&env, 8, &other, &defender)),
+            1
+        );
+
+        let archived = client.get_session(&setup.game, &7);
+        assert_eq!((archived.id, &archived.game), (0, &setup.game));
+        assert_eq!(archived.record, first);
+        assert_eq!(client.get_records(&defender).len(), 2);
+        assert_eq!(client.get_records(&attacker).len(), 1);
+        assert_eq!(client.get_records(&other).get(0).unwrap().id, 1);
+        assert_eq!(client.record_count(), 2);
+        assert_eq!(
+            client.try_get_session(&setup.game, &9),
+            Err(Ok(Error::RecordNotFound))
+        );
+        assert_eq!(client.try_get_record(&2), Err(Ok(Error::RecordNotFound)));
+    }
+
+    #[test]
+    fn test_records_are_immutable() {
+        let env = Env::default();
+        let setup = setup(&env);
+        let client = &setup.client;
+        let defender = Address::generate(&env);
+        let attacker = Address::generate(&env);
+
+        client.archive(&setup.game, &record(&env, 1, &defender, &attacker));
+        let mut rewritten = record(&env, 1, &defender, &attacker);
+        rewritten.winner = Some(defender.clone());
+        assert_eq!(
+            client.try_archive(&setup.game, &rewritten),
+            Err(Ok(Error::AlreadyArchived))
+        );
+        assert_eq!(
+            client.get_session(&setup.game, &1).record.winner,
+            Some(attacker)
+        );
+
+        // Another game's session with the same id is its own record
+        let other_game = Address::generate(&env);
+        client.set_writer(&other_game, &true);
+        assert_eq!(client.archive(&other_game, &rewritten), 1);
+    }
+
+    #[test]
+    fn test_only_writers_archive() {
+        let env = Env::default();
+        let setup = setup(&env);
+        let client = &setup.client;
+        let defender = Address::generate(&env);
+        let attacker = Address::generate(&env);
+
+        let stranger = Address::generate(&env);
+        assert_eq!(
+            client.try_archive(&stranger, &record(&env, 1, &defender, &attacker)),
+            Err(Ok(Error::NotWriter))
+        );
+
+        client.set_writer(&setup.game, &false);
+        assert!(!client.is_writer(&setup.game));
+        assert_eq!(
+            client.try_archive(&setup.game, &record(&env, 1, &defender, &attacker)),
+            Err(Ok(Error::NotWriter))
+        );
+        assert_eq!(setup.env.events().all().events().len(), 0);
+    }
+}
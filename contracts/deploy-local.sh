@@ -37,6 +37,8 @@ fi
 # Circuit paths
 CIRCUIT_DIR="../circuits/position-movement"
 CIRCUIT_TARGET="$CIRCUIT_DIR/target"
+SETUP_CIRCUIT_DIR="../circuits/grid-setup"
+SETUP_CIRCUIT_TARGET="$SETUP_CIRCUIT_DIR/target"
 
 echo ""
 echo -e "${YELLOW}Step 0: Building Position Movement Circuit${NC}"
@@ -70,6 +72,29 @@ echo -e "${GREEN}✓ Circuit built successfully${NC}"
 # Return to deployment directory
 cd - > /dev/null
 
+echo ""
+echo -e "${YELLOW}Step 0b: Building Grid Setup Circuit${NC}"
+echo "======================================"
+
+cd "$SETUP_CIRCUIT_DIR"
+
+if [ ! -d "node_modules" ]; then
+    echo "Installing circuit dependencies..."
+    npm install
+fi
+
+echo "Compiling Noir circuit..."
+nargo compile
+
+echo "Generating verification key..."
+node "$BBJS" write_vk_ultra_keccak_honk \
+  -b ./target/grid_setup.json \
+  -o ./target/vk
+
+echo -e "${GREEN}✓ Grid setup circuit built successfully${NC}"
+
+cd - > /dev/null
+
 echo ""
 echo -e "${YELLOW}Step 1: Building rs-soroban-ultrahonk verifier${NC}"
 echo "======================================"
@@ -157,6 +182,25 @@ VERIFIER_ID=$(stellar contract deploy \
 
 echo -e "${GREEN}✓ Verifier deployed and initialized: $VERIFIER_ID${NC}"
 
+echo ""
+echo "Deploying UltraHonk Verifier Contract with Grid Setup VK..."
+SETUP_VK_FILE="$SETUP_CIRCUIT_TARGET/vk"
+
+if [ ! -f "$SETUP_VK_FILE" ]; then
+    echo "Error: Verification key not found at $SETUP_VK_FILE"
+    echo "Please build the grid setup circuit first (see Step 0b)"
+    exit 1
+fi
+
+SETUP_VERIFIER_ID=$(stellar contract deploy \
+  --wasm $VERIFIER_WASM \
+  --source deployer \
+  --network $NETWORK \
+  -- \
+  --vk_bytes-file-path $SETUP_VK_FILE)
+
+echo -e "${GREEN}✓ Setup verifier deployed and initialized: $SETUP_VERIFIER_ID${NC}"
+
 echo ""
 echo "Deploying Trap Grid Game Contract..."
 TRAP_GRID_WASM="target/wasm32-unknown-unknown/release/trap_grid.wasm"
@@ -175,7 +219,8 @@ stellar contract invoke \
   __constructor \
   --admin $ADMIN \
   --game_hub $GAME_HUB_ID \
-  --verifier $VERIFIER_ID
+  --verifier $VERIFIER_ID \
+  --setup_verifier $SETUP_VERIFIER_ID
 
 echo -e "${GREEN}✓ Trap Grid deployed and initialized: $TRAP_GRID_ID${NC}"
 
@@ -189,14 +234,17 @@ echo "-------------------"
 echo "Game Hub:   $GAME_HUB_ID"
 echo "Trap Grid:  $TRAP_GRID_ID"
 echo "Verifier:   $VERIFIER_ID"
+echo "Setup Verifier: $SETUP_VERIFIER_ID"
 echo ""
 echo "Circuit: trap-grid-position-movement"
 echo "VK Location: $VK_FILE"
+echo "Setup VK Location: $SETUP_VK_FILE"
 echo ""
 echo "Save these addresses to app/.env.local:"
 echo "NEXT_PUBLIC_GAME_HUB_CONTRACT=$GAME_HUB_ID"
 echo "NEXT_PUBLIC_TRAP_GRID_CONTRACT=$TRAP_GRID_ID"
 echo "NEXT_PUBLIC_VERIFIER_CONTRACT=$VERIFIER_ID"
+echo "NEXT_PUBLIC_SETUP_VERIFIER_CONTRACT=$SETUP_VERIFIER_ID"
 echo ""
 
 # Save to a file for easy reference
@@ -205,6 +253,7 @@ cat > app/.env.local << EOF
 NEXT_PUBLIC_GAME_HUB_CONTRACT=$GAME_HUB_ID
 NEXT_PUBLIC_TRAP_GRID_CONTRACT=$TRAP_GRID_ID
 NEXT_PUBLIC_VERIFIER_CONTRACT=$VERIFIER_ID
+NEXT_PUBLIC_SETUP_VERIFIER_CONTRACT=$SETUP_VERIFIER_ID
 NEXT_PUBLIC_NETWORK_PASSPHRASE=Standalone Network ; February 2017
 NEXT_PUBLIC_RPC_URL=http://localhost:8000/soroban/rpc
 EOF
@@ -33,6 +33,8 @@ echo -e "${GREEN}Using official Game Hub: $GAME_HUB_ID${NC}"
 # Circuit paths
 CIRCUIT_DIR="../circuits/position-movement"
 CIRCUIT_TARGET="$CIRCUIT_DIR/target"
+SETUP_CIRCUIT_DIR="../circuits/grid-setup"
+SETUP_CIRCUIT_TARGET="$SETUP_CIRCUIT_DIR/target"
 
 # Load environment variables for testnet account
 if [ -f ".env" ]; then
@@ -88,6 +90,29 @@ echo -e "${GREEN}✓ Circuit built successfully${NC}"
 # Return to deployment directory
 cd - > /dev/null
 
+echo ""
+echo -e "${YELLOW}Step 0b: Building Grid Setup Circuit${NC}"
+echo "======================================"
+
+cd "$SETUP_CIRCUIT_DIR"
+
+if [ ! -d "node_modules" ]; then
+    echo "Installing circuit dependencies..."
+    npm install
+fi
+
+echo "Compiling Noir circuit..."
+nargo compile
+
+echo "Generating verification key..."
+node "$BBJS" write_vk_ultra_keccak_honk \
+  -b ./target/grid_setup.json \
+  -o ./target/vk
+
+echo -e "${GREEN}✓ Grid setup circuit built successfully${NC}"
+
+cd - > /dev/null
+
 echo ""
 echo -e "${YELLOW}Step 1: Building rs-soroban-ultrahonk verifier${NC}"
 echo "======================================"
@@ -149,6 +174,25 @@ VERIFIER_ID=$(stellar contract deploy \
 
 echo -e "${GREEN}✓ Verifier deployed and initialized: $VERIFIER_ID${NC}"
 
+echo ""
+echo "Deploying UltraHonk Verifier Contract with Grid Setup VK..."
+SETUP_VK_FILE="$SETUP_CIRCUIT_TARGET/vk"
+
+if [ ! -f "$SETUP_VK_FILE" ]; then
+    echo -e "${RED}Error: Verification key not found at $SETUP_VK_FILE${NC}"
+    echo "Please build the grid setup circuit first (see Step 0b)"
+    exit 1
+fi
+
+SETUP_VERIFIER_ID=$(stellar contract deploy \
+  --wasm $VERIFIER_WASM \
+  --source-account $SOURCE_ACCOUNT \
+  --network $NETWORK \
+  -- \
+  --vk_bytes-file-path $SETUP_VK_FILE | tail -n1)
+
+echo -e "${GREEN}✓ Setup verifier deployed and initialized: $SETUP_VERIFIER_ID${NC}"
+
 echo ""
 echo "Deploying Trap Grid Game Contract..."
 TRAP_GRID_WASM="target/wasm32-unknown-unknown/release/trap_grid.wasm"
@@ -183,7 +227,8 @@ stellar contract invoke \
   __constructor \
   --admin $ADMIN \
   --game_hub $GAME_HUB_ID \
-  --verifier $VERIFIER_ID > /dev/null
+  --verifier $VERIFIER_ID \
+  --setup_verifier $SETUP_VERIFIER_ID > /dev/null
 
 echo -e "${GREEN}✓ Trap Grid initialized${NC}"
 
@@ -197,19 +242,23 @@ echo "-------------------"
 echo "Game Hub:   $GAME_HUB_ID"
 echo "Trap Grid:  $TRAP_GRID_ID"
 echo "Verifier:   $VERIFIER_ID"
+echo "Setup Verifier: $SETUP_VERIFIER_ID"
 echo ""
 echo "Circuit: trap-grid-position-movement"
 echo "VK Location: $VK_FILE"
+echo "Setup VK Location: $SETUP_VK_FILE"
 echo ""
 echo "🔗 View on Stellar Expert:"
 echo "   Game Hub:   https://stellar.expert/explorer/testnet/contract/$GAME_HUB_ID"
 echo "   Trap Grid:  https://stellar.expert/explorer/testnet/contract/$TRAP_GRID_ID"
 echo "   Verifier:   https://stellar.expert/explorer/testnet/contract/$VERIFIER_ID"
+echo "   Setup Verifier: https://stellar.expert/explorer/testnet/contract/$SETUP_VERIFIER_ID"
 echo ""
 echo "Save these addresses to app/.env.local:"
 echo "NEXT_PUBLIC_GAME_HUB_CONTRACT=$GAME_HUB_ID"
 echo "NEXT_PUBLIC_TRAP_GRID_CONTRACT=$TRAP_GRID_ID"
 echo "NEXT_PUBLIC_VERIFIER_CONTRACT=$VERIFIER_ID"
+echo "NEXT_PUBLIC_SETUP_VERIFIER_CONTRACT=$SETUP_VERIFIER_ID"
 echo ""
 
 # Save to a file for easy reference
@@ -218,6 +267,7 @@ cat > app/.env.local << EOF
 NEXT_PUBLIC_GAME_HUB_CONTRACT=$GAME_HUB_ID
 NEXT_PUBLIC_TRAP_GRID_CONTRACT=$TRAP_GRID_ID
 NEXT_PUBLIC_VERIFIER_CONTRACT=$VERIFIER_ID
+NEXT_PUBLIC_SETUP_VERIFIER_CONTRACT=$SETUP_VERIFIER_ID
 NEXT_PUBLIC_NETWORK_PASSPHRASE=Test SDF Network ; September 2015
 NEXT_PUBLIC_RPC_URL=https://soroban-testnet.stellar.org
 EOF
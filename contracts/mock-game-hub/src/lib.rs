@@ -1,9 +1,10 @@
 #![no_std]
 
 //! Mock Game Hub Contract
-//! 
+//!
 //! A simple mock implementation of a game hub for local development and testing.
-//! This contract provides basic game registration and tracking functionality.
+//! This contract provides basic game registration and tracking functionality,
+//! and implements the `start_game` / `end_game` interface games call.
 
 use soroban_sdk::{contract, contractimpl, contracttype, Address, Env, String, Vec};
 
@@ -16,12 +17,25 @@ pub struct GameInfo {
     pub active: bool,
 }
 
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SessionInfo {
+    pub game_contract: Address,
+    pub player1: Address,
+    pub player2: Address,
+    pub player1_points: i128,
+    pub player2_points: i128,
+    pub ended: bool,
+    pub player1_won: bool,
+}
+
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum DataKey {
     GameCount,
     Game(u64),
     GameContract(Address),
+    Session(u32),
 }
 
 #[contract]
@@ -36,17 +50,14 @@ impl MockGameHub {
     }
 
     /// Register a new game contract
-    pub fn register_game(
-        env: Env,
-        game_contract: Address,
-        name: String,
-    ) -> u64 {
+    pub fn register_game(env: Env, game_contract: Address, name: String) -> u64 {
         // Get and increment game count
-        let mut game_count: u64 = env.storage()
+        let mut game_count: u64 = env
+            .storage()
             .persistent()
             .get(&DataKey::GameCount)
             .unwrap_or(0);
-        
+
         game_count += 1;
 
         let game_info = GameInfo {
@@ -57,9 +68,15 @@ impl MockGameHub {
         };
 
         // Store game info
-        env.storage().persistent().set(&DataKey::Game(game_count), &game_info);
-        env.storage().persistent().set(&DataKey::GameContract(game_contract), &game_count);
-        env.storage().persistent().set(&DataKey::GameCount, &game_count);
+        env.storage()
+            .persistent()
+            .set(&DataKey::Game(game_count), &game_info);
+        env.storage()
+            .persistent()
+            .set(&DataKey::GameContract(game_contract), &game_count);
+        env.storage()
+            .persistent()
+            .set(&DataKey::GameCount, &game_count);
 
         game_count
     }
@@ -71,12 +88,17 @@ impl MockGameHub {
 
     /// Get game ID by contract address
     pub fn get_game_by_contract(env: Env, game_contract: Address) -> Option<u64> {
-        env.storage().persistent().get(&DataKey::GameContract(game_contract))
+        env.storage()
+            .persistent()
+            .get(&DataKey::GameContract(game_contract))
     }
 
     /// Get total number of registered games
     pub fn get_game_count(env: Env) -> u64 {
-        env.storage().persistent().get(&DataKey::GameCount).unwrap_or(0)
+        env.storage()
+            .persistent()
+            .get(&DataKey::GameCount)
+            .unwrap_or(0)
     }
 
     /// Get all games
@@ -93,11 +115,56 @@ impl MockGameHub {
         games
     }
 
+    /// Record the start of a game session (called by the game contract)
+    pub fn start_game(
+        env: Env,
+        game_id: Address,
+        session_id: u32,
+        player1: Address,
+        player2: Address,
+        player1_points: i128,
+        player2_points: i128,
+    ) {
+        let session = SessionInfo {
+            game_contract: game_id,
+            player1,
+            player2,
+            player1_points,
+            player2_points,
+            ended: false,
+            player1_won: false,
+        };
+        env.storage()
+            .temporary()
+            .set(&DataKey::Session(session_id), &session);
+    }
+
+    /// Record the result of a game session (called by the game contract)
+    pub fn end_game(env: Env, session_id: u32, player1_won: bool) {
+        let key = DataKey::Session(session_id);
+        if let Some(mut session) = env.storage().temporary().get::<DataKey, SessionInfo>(&key) {
+            session.ended = true;
+            session.player1_won = player1_won;
+            env.storage().temporary().set(&key, &session);
+        }
+    }
+
+    /// Get a game session by ID
+    pub fn get_session(env: Env, session_id: u32) -> Option<SessionInfo> {
+        env.storage().temporary().get(&DataKey::Session(session_id))
+    }
+
     /// Deactivate a game
     pub fn deactivate_game(env: Env, game_id: u64) -> bool {
-        if let Some(mut game_info) = env.storage().persistent().get::<DataKey, GameInfo>(&DataKey::Game(game_id)) {
+        if let Some(mut game_info) = env
+            .storage()
+            .persistent()
+            .get::<DataKey, GameInfo>(&DataKey::Game(game_id))
+        {
             game_info.active = false;
-            env.storage().persistent().set(&DataKey::Game(game_id), &game_info);
+            env.storage()
+                .persistent()
+                .set(&DataKey::Game(game_id), &game_info);
             true
         } else {
             false
@@ -113,7 +180,7 @@ mod test {
     #[test]
     fn test_initialize_and_register() {
         let env = Env::default();
-        let contract_id = env.register_contract(None, MockGameHub);
+        let contract_id = env.register(MockGameHub, ());
         let client = MockGameHubClient::new(&env, &contract_id);
 
         // Initialize
@@ -138,7 +205,7 @@ mod test {
     #[test]
     fn test_multiple_games() {
         let env = Env::default();
-        let contract_id = env.register_contract(None, MockGameHub);
+        let contract_id = env.register(MockGameHub, ());
         let client = MockGameHubClient::new(&env, &contract_id);
 
         client.initialize();
@@ -155,4 +222,26 @@ mod test {
         let all_games = client.get_all_games();
         assert_eq!(all_games.len(), 2);
     }
+
+    #[test]
+    fn test_session_lifecycle() {
+        let env = Env::default();
+        let contract_id = env.register(MockGameHub, ());
+        let client = MockGameHubClient::new(&env, &contract_id);
+
+        let game = Address::generate(&env);
+        let player1 = Address::generate(&env);
+        let player2 = Address::generate(&env);
+        client.start_game(&game, &7, &player1, &player2, &100, &50);
+
+        let session = client.get_session(&7).unwrap();
+        assert_eq!(session.player1, player1);
+        assert!(!session.ended);
+
+        client.end_game(&7, &true);
+        let session = client.get_session(&7).unwrap();
+        assert!(session.ended);
+        assert!(session.player1_won);
+        assert_eq!(client.get_session(&8), None);
+    }
 }
@@ -0,0 +1,780 @@
+#![no_std]
+
+//! # Puzzle Hunt
+//!
+//! Sequential puzzle stages solved with ZK proofs:
+//! - The admin adds stages, each with a hash commitment to its answer, the
+//!   verifier contract of its circuit and a prize the admin funds up front
+//! - A player solves stage N by proving knowledge of the committed answer,
+//!   which unlocks stage N + 1 for that player only
+//! - The first solver of each stage takes its prize; players who solve every
+//!   stage are ranked on a leaderboard in the order they finished
+//! - The admin can sell encrypted hints for a stage; the revenue goes to the
+//!   admin or into the stage's prize
+//!
+//! **Front-running:** every proof's public inputs bind the solver's address,
+//! so a proof copied from the mempool does not verify for anyone else.
+
+use soroban_sdk::xdr::ToXdr;
+use soroban_sdk::{
+    contract, contractclient, contracterror, contractevent, contractimpl, contracttype, token,
+    Address, Bytes, BytesN, Env, Vec,
+};
+
+// Import ZK Verifier contract interface (rs-soroban-ultrahonk); fails with a
+// contract error when the proof does not verify
+#[contractclient(name = "VerifierClient")]
+pub trait Verifier {
+    fn verify_proof(env: Env, public_inputs: Bytes, proof_bytes: Bytes);
+}
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum Error {
+    StageNotFound = 1,
+    /// The player has not solved the stages before this one
+    StageLocked = 2,
+    StageAlreadySolved = 3,
+    InvalidProof = 4,
+    /// Stages cannot be added once a player has solved one
+    HuntStarted = 5,
+    InvalidPrize = 6,
+    HintNotFound = 7,
+    HintAlreadyUnlocked = 8,
+    InvalidPrice = 9,
+}
+
+// ============================================================================
+// Data Types
+// ============================================================================
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Stage {
+    /// Commitment to the answer, the first public input of the stage's circuit
+    pub commitment: BytesN<32>,
+    pub verifier: Address,
+    pub prize: i128,
+    /// First player to solve the stage, who took its prize
+    pub first_solver: Option<Address>,
+}
+
+/// Where a hint's price goes
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum HintRevenue {
+    /// Paid to the admin
+    Creator,
+    /// Added to the stage's prize, or paid to the admin once it is taken
+    PrizePool,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Hint {
+    /// Encrypted off-chain; the admin hands buyers the key
+    pub ciphertext: Bytes,
+    pub price: i128,
+    pub revenue: HintRevenue,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Finisher {
+    pub player: Address,
+    /// Ledger sequence of the player's last solution
+    pub ledger: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DataKey {
+    Admin,
+    Token,
+    StageCount,
+    Stage(u32),
+    Progress(Address), // player -> stages solved
+    Leaderboard,       // Vec<Finisher>, first finisher first
+    Started,
+    HintCount(u32),                  // stage -> number of hints
+    Hint(u32, u32),                  // (stage, hint) -> Hint
+    HintUnlocked(Address, u32, u32), // (player, stage, hint) -> true
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+/// Published by `add_stage`
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StageAdded {
+    #[topic]
+    pub stage: u32,
+    pub prize: i128,
+}
+
+/// Published when a player's proof for a stage verifies; `prize` is zero
+/// unless they were the first to solve it
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StageSolved {
+    #[topic]
+    pub stage: u32,
+    #[topic]
+    pub player: Address,
+    pub prize: i128,
+}
+
+/// Published by `add_hint`
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HintAdded {
+    #[topic]
+    pub stage: u32,
+    pub hint: u32,
+    pub price: i128,
+}
+
+/// Published by `buy_hint`; the admin answers it with the hint's key
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HintPurchased {
+    #[topic]
+    pub stage: u32,
+    #[topic]
+    pub player: Address,
+    pub hint: u32,
+    pub price: i128,
+}
+
+/// Published when a player solves the last stage
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HuntFinished {
+    #[topic]
+    pub player: Address,
+    /// 1 for the first finisher
+    pub rank: u32,
+}
+
+// ============================================================================
+// Storage TTL Management
+// ============================================================================
+
+const HUNT_TTL_LEDGERS: u32 = 518_400; // 30 days
+
+// ============================================================================
+// Public Inputs
+// ============================================================================
+
+/// The public input binding a proof to `player`: the SHA-256 of the address
+/// XDR with its top byte cleared, so it is below the BN254 modulus
+pub fn player_field(env: &Env, player: &Address) -> BytesN<32> {
+    let mut hash = env.crypto().sha256(&player.clone().to_xdr(env)).to_array();
+    hash[0] = 0;
+    BytesN::from_array(env, &hash)
+}
+
+/// Public inputs a stage's circuit must expose: the commitment, then the
+/// solver's [`player_field`]
+pub fn public_inputs(env: &Env, commitment: &BytesN<32>, player: &Address) -> Bytes {
+    let mut inputs = Bytes::from(commitment.clone());
+    inputs.append(&player_field(env, player).into());
+    inputs
+}
+
+// ============================================================================
+// Contract Definition
+// ============================================================================
+
+#[contract]
+pub struct PuzzleHuntContract;
+
+#[contractimpl]
+impl PuzzleHuntContract {
+    /// Initialize the hunt with its admin and the token prizes are paid in
+    pub fn __constructor(env: Env, admin: Address, token: Address) {
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.storage().instance().set(&DataKey::Token, &token);
+        env.storage().instance().set(&DataKey::StageCount, &0u32);
+    }
+
+    /// Append a stage and transfer its prize from the admin to the contract
+    ///
+    /// # Arguments
+    /// * `commitment` - Hash commitment to the stage's answer
+    /// * `verifier` - Verifier contract of the stage's circuit
+    /// * `prize` - Paid to the first solver; may be zero
+    pub fn add_stage(
+        env: Env,
+        commitment: BytesN<32>,
+        verifier: Address,
+        prize: i128,
+    ) -> Result<u32, Error> {
+        let admin = admin(&env);
+        admin.require_auth();
+
+        if env.storage().instance().has(&DataKey::Started) {
+            return Err(Error::HuntStarted);
+        }
+        if prize < 0 {
+            return Err(Error::InvalidPrize);
+        }
+        if prize > 0 {
+            token_client(&env).transfer(&admin, env.current_contract_address(), &prize);
+        }
+
+        let stage = Self::stage_count(env.clone());
+        let key = DataKey::Stage(stage);
+        env.storage().persistent().set(
+            &key,
+            &Stage {
+                commitment,
+                verifier,
+                prize,
+                first_solver: None,
+            },
+        );
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, HUNT_TTL_LEDGERS, HUNT_TTL_LEDGERS);
+        env.storage()
+            .instance()
+            .set(&DataKey::StageCount, &(stage + 1));
+
+        StageAdded { stage, prize }.publish(&env);
+        Ok(stage)
+    }
+
+    /// Solve the player's next stage
+    ///
+    /// # Arguments
+    /// * `player` - The solver; must sign, and is bound by the public inputs
+    /// * `stage` - Index of the stage, which must be the player's next one
+    /// * `proof` - ZK proof of the stage's answer
+    /// * `public_inputs` - Must be [`public_inputs`] of the stage's commitment
+    ///   and `player`, or the proof is rejected as invalid
+    ///
+    /// Returns the prize paid, zero unless the player solved it first.
+    pub fn solve(
+        env: Env,
+        player: Address,
+        stage: u32,
+        proof: Bytes,
+        public_inputs: Bytes,
+    ) -> Result<i128, Error> {
+        player.require_auth();
+
+        let key = DataKey::Stage(stage);
+        let mut current: Stage = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(Error::StageNotFound)?;
+
+        let progress_key = DataKey::Progress(player.clone());
+        let solved: u32 = env.storage().persistent().get(&progress_key).unwrap_or(0);
+        if stage < solved {
+            return Err(Error::StageAlreadySolved);
+        }
+        if stage > solved {
+            return Err(Error::StageLocked);
+        }
+
+        // The proof must be about this stage and this player
+        if public_inputs != self::public_inputs(&env, &current.commitment, &player) {
+            return Err(Error::InvalidProof);
+        }
+        let verifier = VerifierClient::new(&env, &current.verifier);
+        if verifier.try_verify_proof(&public_inputs, &proof).is_err() {
+            return Err(Error::InvalidProof);
+        }
+
+        env.storage().instance().set(&DataKey::Started, &true);
+        env.storage().persistent().set(&progress_key, &(solved + 1));
+        env.storage()
+            .persistent()
+            .extend_ttl(&progress_key, HUNT_TTL_LEDGERS, HUNT_TTL_LEDGERS);
+
+        let prize = if current.first_solver.is_none() {
+            current.first_solver = Some(player.clone());
+            env.storage().persistent().set(&key, &current);
+            if current.prize > 0 {
+                token_client(&env).transfer(
+                    &env.current_contract_address(),
+                    &player,
+                    &current.prize,
+                );
+            }
+            current.prize
+        } else {
+            0
+        };
+        StageSolved {
+            stage,
+            player: player.clone(),
+            prize,
+        }
+        .publish(&env);
+
+        if stage + 1 == Self::stage_count(env.clone()) {
+            let mut leaderboard = Self::get_leaderboard(env.clone());
+            leaderboard.push_back(Finisher {
+                player: player.clone(),
+                ledger: env.ledger().sequence(),
+            });
+            env.storage()
+                .instance()
+                .set(&DataKey::Leaderboard, &leaderboard);
+            HuntFinished {
+                player,
+                rank: leaderboard.len(),
+            }
+            .publish(&env);
+        }
+
+        Ok(prize)
+    }
+
+    /// Register an encrypted hint for a stage
+    ///
+    /// # Arguments
+    /// * `stage` - The stage the hint is for
+    /// * `ciphertext` - The hint, encrypted; storage is public
+    /// * `price` - What a player pays to unlock it; may be zero
+    /// * `revenue` - Whether the price goes to the admin or the stage's prize
+    pub fn add_hint(
+        env: Env,
+        stage: u32,
+        ciphertext: Bytes,
+        price: i128,
+        revenue: HintRevenue,
+    ) -> Result<u32, Error> {
+        admin(&env).require_auth();
+        Self::get_stage(env.clone(), stage)?;
+        if price < 0 {
+            return Err(Error::InvalidPrice);
+        }
+
+        let hint = Self::hint_count(env.clone(), stage);
+        let key = DataKey::Hint(stage, hint);
+        env.storage().persistent().set(
+            &key,
+            &Hint {
+                ciphertext,
+                price,
+                revenue,
+            },
+        );
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, HUNT_TTL_LEDGERS, HUNT_TTL_LEDGERS);
+        env.storage()
+            .persistent()
+            .set(&DataKey::HintCount(stage), &(hint + 1));
+
+        HintAdded { stage, hint, price }.publish(&env);
+        Ok(hint)
+    }
+
+    /// Pay for a hint and record that the player unlocked it
+    ///
+    /// Returns the hint's ciphertext; the admin sends the key off-chain in
+    /// answer to the `HintPurchased` event.
+    pub fn buy_hint(env: Env, player: Address, stage: u32, hint: u32) -> Result<Bytes, Error> {
+        player.require_auth();
+
+        let current = Self::get_hint(env.clone(), stage, hint)?;
+        let unlocked_key = DataKey::HintUnlocked(player.clone(), stage, hint);
+        if env.storage().persistent().has(&unlocked_key) {
+            return Err(Error::HintAlreadyUnlocked);
+        }
+
+        if current.price > 0 {
+            let stage_key = DataKey::Stage(stage);
+            let mut prize_stage: Stage = env
+                .storage()
+                .persistent()
+                .get(&stage_key)
+                .ok_or(Error::StageNotFound)?;
+            let token = token_client(&env);
+            match current.revenue {
+                HintRevenue::PrizePool if prize_stage.first_solver.is_none() => {
+                    token.transfer(&player, env.current_contract_address(), &current.price);
+                    prize_stage.prize += current.price;
+                    env.storage().persistent().set(&stage_key, &prize_stage);
+                }
+                _ => token.transfer(&player, admin(&env), &current.price),
+            }
+        }
+
+        env.storage().persistent().set(&unlocked_key, &true);
+        env.storage()
+            .persistent()
+            .extend_ttl(&unlocked_key, HUNT_TTL_LEDGERS, HUNT_TTL_LEDGERS);
+
+        HintPurchased {
+            stage,
+            player,
+            hint,
+            price: current.price,
+        }
+        .publish(&env);
+        Ok(current.ciphertext)
+    }
+
+    /// Get a hint
+    pub fn get_hint(env: Env, stage: u32, hint: u32) -> Result<Hint, Error> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Hint(stage, hint))
+            .ok_or(Error::HintNotFound)
+    }
+
+    /// Number of hints for a stage
+    pub fn hint_count(env: Env, stage: u32) -> u32 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::HintCount(stage))
+            .unwrap_or(0)
+    }
+
+    /// Whether the player has unlocked a hint
+    pub fn has_hint(env: Env, player: Address, stage: u32, hint: u32) -> bool {
+        env.storage()
+            .persistent()
+            .has(&DataKey::HintUnlocked(player, stage, hint))
+    }
+
+    /// Get a stage
+    pub fn get_stage(env: Env, stage: u32) -> Result<Stage, Error> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Stage(stage))
+            .ok_or(Error::StageNotFound)
+    }
+
+    /// Number of stages
+    pub fn stage_count(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::StageCount)
+            .unwrap_or(0)
+    }
+
+    /// Number of stages the player has solved, which is also the index of
+    /// the stage they can attempt next
+    pub fn get_progress(env: Env, player: Address) -> u32 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Progress(player))
+            .unwrap_or(0)
+    }
+
+    /// Players who solved every stage, first finisher first
+    pub fn get_leaderboard(env: Env) -> Vec<Finisher> {
+        env.storage()
+            .instance()
+            .get(&DataKey::Leaderboard)
+            .unwrap_or(Vec::new(&env))
+    }
+}
+
+fn admin(env: &Env) -> Address {
+    env.storage()
+        .instance()
+        .get(&DataKey::Admin)
+        .expect("Admin not set")
+}
+
+fn token_client(env: &Env) -> token::Client<'_> {
+    let token: Address = env
+        .storage()
+        .instance()
+        .get(&DataKey::Token)
+        .expect("Token not set");
+    token::Client::new(env, &token)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::token::StellarAssetClient;
+
+    const FUNDS: i128 = 1_000;
+    const PRIZES: [i128; 3] = [100, 200, 0];
+
+    /// Accepts every non-empty proof
+    #[contract]
+    pub struct MockVerifier;
+
+    #[contracterror]
+    #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+    #[repr(u32)]
+    pub enum MockVerifierError {
+        VerificationFailed = 3,
+    }
+
+    #[contractimpl]
+    impl MockVerifier {
+        pub fn verify_proof(
+            _env: Env,
+            _public_inputs: Bytes,
+            proof_bytes: Bytes,
+        ) -> Result<(), MockVerifierError> {
+            if proof_bytes.is_empty() {
+                return Err(MockVerifierError::VerificationFailed);
+            }
+            Ok(())
+        }
+    }
+
+    struct Setup<'a> {
+        env: Env,
+        admin: Address,
+        token: token::Client<'a>,
+        client: PuzzleHuntContractClient<'a>,
+    }
+
+    fn commitment(env: &Env, stage: u32) -> BytesN<32> {
+        let mut bytes = [0u8; 32];
+        bytes[31] = stage as u8 + 1;
+        BytesN::from_array(env, &bytes)
+    }
+
+    fn proof(env: &Env) -> Bytes {
+        Bytes::from_array(env, &[1u8; 4])
+    }
+
+    /// A hunt with three stages, prizes funded from the admin's balance
+    fn setup(env: &Env) -> Setup<'_> {
+        env.mock_all_auths();
+        let admin = Address::generate(env);
+        let token = env.register_stellar_asset_contract_v2(admin.clone());
+        StellarAssetClient::new(env, &token.address()).mint(&admin, &FUNDS);
+        let contract = env.register(PuzzleHuntContract, (&admin, &token.address()));
+        let client = PuzzleHuntContractClient::new(env, &contract);
+
+        let verifier = env.register(MockVerifier, ());
+        for (stage, prize) in PRIZES.iter().enumerate() {
+            let stage = stage as u32;
+            assert_eq!(
+                client.add_stage(&commitment(env, stage), &verifier, prize),
+                stage
+            );
+        }
+        Setup {
+            env: env.clone(),
+            admin,
+            token: token::Client::new(env, &token.address()),
+            client,
+        }
+    }
+
+    impl Setup<'_> {
+        fn try_solve(&self, player: &Address, stage: u32) -> Result<i128, Error> {
+            let inputs = public_inputs(&self.env, &commitment(&self.env, stage), player);
+            match self
+                .client
+                .try_solve(player, &stage, &proof(&self.env), &inputs)
+            {
+                Ok(prize) => Ok(prize.unwrap()),
+                Err(Ok(error)) => Err(error),
+                Err(Err(error)) => panic!("solve aborted: {error:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_stages_unlock_in_order() {
+        let env = Env::default();
+        let setup = setup(&env);
+        let player = Address::generate(&env);
+        let total: i128 = PRIZES.iter().sum();
+        assert_eq!(setup.token.balance(&setup.admin), FUNDS - total);
+        assert_eq!(setup.token.balance(&setup.client.address), total);
+
+        assert_eq!(setup.try_solve(&player, 1), Err(Error::StageLocked));
+        for (stage, prize) in PRIZES.iter().enumerate() {
+            assert_eq!(setup.try_solve(&player, stage as u32), Ok(*prize));
+            assert_eq!(setup.client.get_progress(&player), stage as u32 + 1);
+        }
+        assert_eq!(setup.try_solve(&player, 0), Err(Error::StageAlreadySolved));
+        assert_eq!(setup.try_solve(&player, 3), Err(Error::StageNotFound));
+
+        assert_eq!(setup.token.balance(&player), total);
+        assert_eq!(setup.token.balance(&setup.client.address), 0);
+        assert_eq!(
+            setup.client.get_stage(&0).first_solver,
+            Some(player.clone())
+        );
+        let leaderboard = setup.client.get_leaderboard();
+        assert_eq!(leaderboard.len(), 1);
+        assert_eq!(leaderboard.get(0).unwrap().player, player);
+    }
+
+    #[test]
+    fn test_prizes_go_to_first_solvers() {
+        let env = Env::default();
+        let setup = setup(&env);
+        let first = Address::generate(&env);
+        let second = Address::generate(&env);
+
+        assert_eq!(setup.try_solve(&first, 0), Ok(PRIZES[0]));
+        assert_eq!(setup.try_solve(&second, 0), Ok(0));
+        // The second player overtakes on stage 1 and finishes first
+        assert_eq!(setup.try_solve(&second, 1), Ok(PRIZES[1]));
+        assert_eq!(setup.try_solve(&second, 2), Ok(PRIZES[2]));
+        assert_eq!(setup.try_solve(&first, 1), Ok(0));
+        assert_eq!(setup.try_solve(&first, 2), Ok(0));
+
+        assert_eq!(setup.token.balance(&first), PRIZES[0]);
+        assert_eq!(setup.token.balance(&second), PRIZES[1]);
+        let leaderboard = setup.client.get_leaderboard();
+        assert_eq!(leaderboard.get(0).unwrap().player, second);
+        assert_eq!(leaderboard.get(1).unwrap().player, first);
+    }
+
+    #[test]
+    fn test_proofs_are_bound_to_the_player() {
+        let env = Env::default();
+        let setup = setup(&env);
+        let solver = Address::generate(&env);
+        let thief = Address::generate(&env);
+
+        // Replaying the solver's public inputs as someone else
+        let inputs = public_inputs(&env, &commitment(&env, 0), &solver);
+        assert_eq!(
+            setup.client.try_solve(&thief, &0, &proof(&env), &inputs),
+            Err(Ok(Error::InvalidProof))
+        );
+        // Another stage's commitment, or a proof the verifier rejects
+        let inputs = public_inputs(&env, &commitment(&env, 1), &solver);
+        assert_eq!(
+            setup.client.try_solve(&solver, &0, &proof(&env), &inputs),
+            Err(Ok(Error::InvalidProof))
+        );
+        let inputs = public_inputs(&env, &commitment(&env, 0), &solver);
+        assert_eq!(
+            setup
+                .client
+                .try_solve(&solver, &0, &Bytes::new(&env), &inputs),
+            Err(Ok(Error::InvalidProof))
+        );
+        assert_eq!(setup.client.get_progress(&solver), 0);
+        assert_eq!(setup.try_solve(&solver, 0), Ok(PRIZES[0]));
+    }
+
+    #[test]
+    fn test_add_stage() {
+        let env = Env::default();
+        let setup = setup(&env);
+        let verifier = Address::generate(&env);
+        assert_eq!(
+            setup
+                .client
+                .try_add_stage(&commitment(&env, 3), &verifier, &-1),
+            Err(Ok(Error::InvalidPrize))
+        );
+        assert_eq!(setup.client.stage_count(), 3);
+
+        setup.try_solve(&Address::generate(&env), 0).unwrap();
+        assert_eq!(
+            setup
+                .client
+                .try_add_stage(&commitment(&env, 3), &verifier, &0),
+            Err(Ok(Error::HuntStarted))
+        );
+    }
+
+    #[test]
+    fn test_hints() {
+        let env = Env::default();
+        let setup = setup(&env);
+        let player = Address::generate(&env);
+        let other = Address::generate(&env);
+        StellarAssetClient::new(&env, &setup.token.address).mint(&player, &100);
+        StellarAssetClient::new(&env, &setup.token.address).mint(&other, &100);
+        let ciphertext = Bytes::from_array(&env, &[7u8; 16]);
+        let admin_funds = setup.token.balance(&setup.admin);
+
+        let to_creator = setup
+            .client
+            .add_hint(&0, &ciphertext, &10, &HintRevenue::Creator);
+        let to_pool = setup
+            .client
+            .add_hint(&0, &ciphertext, &25, &HintRevenue::PrizePool);
+        assert_eq!((to_creator, to_pool), (0, 1));
+        assert_eq!(setup.client.hint_count(&0), 2);
+        assert_eq!(setup.client.hint_count(&1), 0);
+
+        assert_eq!(setup.client.buy_hint(&player, &0, &to_creator), ciphertext);
+        assert_eq!(setup.token.balance(&setup.admin), admin_funds + 10);
+        assert_eq!(
+            setup.client.try_buy_hint(&player, &0, &to_creator),
+            Err(Ok(Error::HintAlreadyUnlocked))
+        );
+
+        // Pool revenue raises the stage's prize until it is taken
+        setup.client.buy_hint(&player, &0, &to_pool);
+        assert_eq!(setup.client.get_stage(&0).prize, PRIZES[0] + 25);
+        assert_eq!(setup.try_solve(&player, 0), Ok(PRIZES[0] + 25));
+        setup.client.buy_hint(&other, &0, &to_pool);
+        assert_eq!(setup.token.balance(&setup.admin), admin_funds + 35);
+
+        assert!(setup.client.has_hint(&player, &0, &to_pool));
+        assert!(!setup.client.has_hint(&other, &0, &to_creator));
+        assert_eq!(setup.token.balance(&player), 100 - 35 + PRIZES[0] + 25);
+        assert_eq!(setup.token.balance(&setup.client.address), PRIZES[1]);
+    }
+
+    #[test]
+    fn test_hint_errors() {
+        let env = Env::default();
+        let setup = setup(&env);
+        let player = Address::generate(&env);
+        let ciphertext = Bytes::new(&env);
+        assert_eq!(
+            setup
+                .client
+                .try_add_hint(&3, &ciphertext, &0, &HintRevenue::Creator),
+            Err(Ok(Error::StageNotFound))
+        );
+        assert_eq!(
+            setup
+                .client
+                .try_add_hint(&0, &ciphertext, &-1, &HintRevenue::Creator),
+            Err(Ok(Error::InvalidPrice))
+        );
+        assert_eq!(
+            setup.client.try_buy_hint(&player, &0, &0),
+            Err(Ok(Error::HintNotFound))
+        );
+
+        // Free hints need no balance
+        setup
+            .client
+            .add_hint(&0, &ciphertext, &0, &HintRevenue::PrizePool);
+        setup.client.buy_hint(&player, &0, &0);
+        assert!(setup.client.has_hint(&player, &0, &0));
+        assert_eq!(setup.client.get_stage(&0).prize, PRIZES[0]);
+    }
+
+    #[test]
+    fn test_player_field_is_a_field_element() {
+        let env = Env::default();
+        let player = Address::generate(&env);
+        let field = player_field(&env, &player);
+        assert_eq!(field.get(0), Some(0));
+        assert_ne!(field, player_field(&env, &Address::generate(&env)));
+        // Two field elements
+        assert_eq!(public_inputs(&env, &commitment(&env, 0), &player).len(), 64);
+    }
+}
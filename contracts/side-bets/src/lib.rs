@@ -0,0 +1,741 @@
+#![no_std]
+
+//! # Side Bets
+//!
+//! Spectator pools on the outcome of live trap-grid sessions:
+//! - Anyone opens a market on a session that is being played, in a token and
+//!   with a cutoff: bets close once the game has made that many moves
+//! - Spectators stake on the attacker or the defender until the cutoff
+//! - Once the game ends and its result is final, anyone settles the market
+//!   from the result trap-grid reports; the admin takes a fee and the rest of
+//!   the pool is shared pro-rata among the stakes on the winner. A result
+//!   still open to appeal, or a game its arbiter or the admin froze, waits
+//! - A game that ends without a winner (aborted, expired, voided) or a winner
+//!   nobody backed voids the market, and every stake is returned in full
+
+use soroban_sdk::{
+    contract, contractclient, contracterror, contractevent, contractimpl, contracttype, token,
+    Address, Env,
+};
+
+// Import the trap-grid interface the markets read results from
+#[contractclient(name = "TrapGridClient")]
+pub trait TrapGrid {
+    fn get_game(env: Env, session_id: u32) -> Game;
+    fn get_arbitration(env: Env, session_id: u32) -> Option<Arbitration>;
+    fn get_frozen(env: Env, session_id: u32) -> Option<Address>;
+}
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum Error {
+    MarketNotFound = 1,
+    MarketExists = 2,
+    /// The session is not being played on trap-grid
+    GameNotLive = 3,
+    /// The game already made the market's cutoff number of moves
+    BettingClosed = 4,
+    GameNotEnded = 5,
+    AlreadySettled = 6,
+    NotSettled = 7,
+    NothingToClaim = 8,
+    InvalidAmount = 9,
+    InvalidFee = 10,
+    /// The game ended, but its result can still be appealed or is frozen
+    ResultNotFinal = 11,
+}
+
+// ============================================================================
+// Data Types
+// ============================================================================
+
+/// Mirror of trap-grid's `Game`, as `get_game` returns it
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Game {
+    pub defender: Address,
+    pub attacker: Address,
+    pub defender_points: i128,
+    pub attacker_points: i128,
+    pub moves_made: u32,
+    pub hits: u32,
+    pub misses: u32,
+    pub game_started: bool,
+    pub game_ended: bool,
+    pub winner: Option<Address>,
+    pub schema_version: u32,
+    pub last_active_ledger: u32,
+    pub grid_size: u32,
+    pub trap_count: u32,
+}
+
+/// Mirror of trap-grid's `Arbitration`, as `get_arbitration` returns it
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Arbitration {
+    pub arbiter: Address,
+    pub appeal_until: u32,
+    pub appellant: Option<Address>,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Outcome {
+    /// The market is not settled yet
+    Pending,
+    Attacker,
+    Defender,
+    /// Stakes are returned
+    Void,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Market {
+    pub token: Address,
+    /// Bets close once the game has made this many moves
+    pub cutoff_moves: u32,
+    pub attacker_pool: i128,
+    pub defender_pool: i128,
+    /// `Pending` until settled
+    pub outcome: Outcome,
+    /// What the winning side shares once the fee is taken
+    pub payout_pool: i128,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum DataKey {
+    Admin,
+    Game,
+    FeeBps,
+    Market(u32),               // session_id -> Market
+    Stake(u32, Address, bool), // (session_id, bettor, on_attacker) -> i128
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+/// Published by `open_market`
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MarketOpened {
+    #[topic]
+    pub session_id: u32,
+    pub token: Address,
+    pub cutoff_moves: u32,
+}
+
+/// Published by `bet`
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BetPlaced {
+    #[topic]
+    pub session_id: u32,
+    #[topic]
+    pub bettor: Address,
+    pub on_attacker: bool,
+    pub amount: i128,
+}
+
+/// Published by `settle`
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MarketSettled {
+    #[topic]
+    pub session_id: u32,
+    pub outcome: Outcome,
+    pub fee: i128,
+}
+
+/// Published by `claim`
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WinningsClaimed {
+    #[topic]
+    pub session_id: u32,
+    #[topic]
+    pub bettor: Address,
+    pub amount: i128,
+}
+
+// ============================================================================
+// Storage TTL Management
+// ============================================================================
+
+const MARKET_TTL_LEDGERS: u32 = 518_400; // 30 days
+
+const MAX_BPS: u32 = 10_000;
+
+// ============================================================================
+// Contract Definition
+// ============================================================================
+
+#[contract]
+pub struct SideBetsContract;
+
+#[contractimpl]
+impl SideBetsContract {
+    /// Initialize the contract with the admin who collects fees and the
+    /// trap-grid contract whose sessions are bet on
+    pub fn __constructor(env: Env, admin: Address, game: Address) {
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.storage().instance().set(&DataKey::Game, &game);
+        env.storage().instance().set(&DataKey::FeeBps, &0u32);
+    }
+
+    /// Set the fee taken from each settled pool, in basis points; admin only
+    pub fn set_fee(env: Env, fee_bps: u32) -> Result<(), Error> {
+        admin(&env).require_auth();
+        if fee_bps > MAX_BPS {
+            return Err(Error::InvalidFee);
+        }
+        env.storage().instance().set(&DataKey::FeeBps, &fee_bps);
+        Ok(())
+    }
+
+    pub fn get_fee(env: Env) -> u32 {
+        env.storage().instance().get(&DataKey::FeeBps).unwrap_or(0)
+    }
+
+    /// Open a market on `session_id`, staked in `token`
+    ///
+    /// # Arguments
+    /// * `session_id` - A trap-grid session that has started and not ended
+    /// * `cutoff_moves` - Bets close once the game has made this many moves;
+    ///   must be more than it made so far
+    pub fn open_market(
+        env: Env,
+        session_id: u32,
+        token: Address,
+        cutoff_moves: u32,
+    ) -> Result<(), Error> {
+        let key = DataKey::Market(session_id);
+        if env.storage().persistent().has(&key) {
+            return Err(Error::MarketExists);
+        }
+        let game = live_game(&env, session_id)?;
+        if game.moves_made >= cutoff_moves {
+            return Err(Error::BettingClosed);
+        }
+
+        let market = Market {
+            token: token.clone(),
+            cutoff_moves,
+            attacker_pool: 0,
+            defender_pool: 0,
+            outcome: Outcome::Pending,
+            payout_pool: 0,
+        };
+        save(&env, &key, &market);
+        MarketOpened {
+            session_id,
+            token,
+            cutoff_moves,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Stake `amount` on the attacker or the defender of `session_id`;
+    /// returns the bettor's total stake on that side
+    pub fn bet(
+        env: Env,
+        bettor: Address,
+        session_id: u32,
+        on_attacker: bool,
+        amount: i128,
+    ) -> Result<i128, Error> {
+        bettor.require_auth();
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        let key = DataKey::Market(session_id);
+        let mut market = Self::get_market(env.clone(), session_id)?;
+        let game = live_game(&env, session_id).map_err(|_| Error::BettingClosed)?;
+        if game.moves_made >= market.cutoff_moves {
+            return Err(Error::BettingClosed);
+        }
+
+        token::Client::new(&env, &market.token).transfer(
+            &bettor,
+            env.current_contract_address(),
+            &amount,
+        );
+        if on_attacker {
+            market.attacker_pool += amount;
+        } else {
+            market.defender_pool += amount;
+        }
+        save(&env, &key, &market);
+        let stake_key = DataKey::Stake(session_id, bettor.clone(), on_attacker);
+        let stake = Self::get_stake(env.clone(), session_id, bettor.clone(), on_attacker) + amount;
+        save(&env, &stake_key, &stake);
+
+        BetPlaced {
+            session_id,
+            bettor,
+            on_attacker,
+            amount,
+        }
+        .publish(&env);
+        Ok(stake)
+    }
+
+    /// Settle the market of a session that ended, from the result trap-grid
+    /// reports; anyone can call. A result under arbitration settles once
+    /// trap-grid finalizes or rules on it, and a frozen game once unfrozen.
+    /// A session trap-grid no longer has (its record expired) settles as
+    /// void.
+    pub fn settle(env: Env, session_id: u32) -> Result<Outcome, Error> {
+        let key = DataKey::Market(session_id);
+        let mut market = Self::get_market(env.clone(), session_id)?;
+        if market.outcome != Outcome::Pending {
+            return Err(Error::AlreadySettled);
+        }
+
+        let trap_grid = game_client(&env);
+        let mut outcome = match trap_grid.try_get_game(&session_id) {
+            Ok(Ok(game)) if !game.game_ended => return Err(Error::GameNotEnded),
+            Ok(Ok(_))
+                if trap_grid.get_arbitration(&session_id).is_some()
+                    || trap_grid.get_frozen(&session_id).is_some() =>
+            {
+                return Err(Error::ResultNotFinal)
+            }
+            Ok(Ok(game)) => match game.winner {
+                Some(winner) if winner == game.attacker => Outcome::Attacker,
+                Some(_) => Outcome::Defender,
+                None => Outcome::Void,
+            },
+            _ => Outcome::Void,
+        };
+        let (winning, losing) = match outcome {
+            Outcome::Attacker => (market.attacker_pool, market.defender_pool),
+            Outcome::Defender => (market.defender_pool, market.attacker_pool),
+            Outcome::Void | Outcome::Pending => (0, 0),
+        };
+        if winning == 0 {
+            outcome = Outcome::Void;
+        }
+
+        let mut fee = 0;
+        if outcome != Outcome::Void {
+            let total = winning + losing;
+            fee = total * i128::from(Self::get_fee(env.clone())) / i128::from(MAX_BPS);
+            if fee > 0 {
+                token::Client::new(&env, &market.token).transfer(
+                    &env.current_contract_address(),
+                    admin(&env),
+                    &fee,
+                );
+            }
+            market.payout_pool = total - fee;
+        }
+        market.outcome = outcome;
+        save(&env, &key, &market);
+
+        MarketSettled {
+            session_id,
+            outcome,
+            fee,
+        }
+        .publish(&env);
+        Ok(outcome)
+    }
+
+    /// Pay `bettor` its share of a settled market: its stakes back when the
+    /// market was void, else its pro-rata share of the pool for its stake on
+    /// the winner. Returns the amount paid.
+    pub fn claim(env: Env, bettor: Address, session_id: u32) -> Result<i128, Error> {
+        bettor.require_auth();
+        let market = Self::get_market(env.clone(), session_id)?;
+        let outcome = market.outcome;
+
+        let sides: &[bool] = match outcome {
+            Outcome::Pending => return Err(Error::NotSettled),
+            Outcome::Attacker => &[true],
+            Outcome::Defender => &[false],
+            Outcome::Void => &[true, false],
+        };
+        let mut amount = 0;
+        for &on_attacker in sides {
+            let stake = Self::get_stake(env.clone(), session_id, bettor.clone(), on_attacker);
+            if stake == 0 {
+                continue;
+            }
+            env.storage().persistent().remove(&DataKey::Stake(
+                session_id,
+                bettor.clone(),
+                on_attacker,
+            ));
+            amount += match outcome {
+                Outcome::Attacker => stake * market.payout_pool / market.attacker_pool,
+                Outcome::Defender => stake * market.payout_pool / market.defender_pool,
+                Outcome::Void | Outcome::Pending => stake,
+            };
+        }
+        if amount == 0 {
+            return Err(Error::NothingToClaim);
+        }
+
+        token::Client::new(&env, &market.token).transfer(
+            &env.current_contract_address(),
+            &bettor,
+            &amount,
+        );
+        WinningsClaimed {
+            session_id,
+            bettor,
+            amount,
+        }
+        .publish(&env);
+        Ok(amount)
+    }
+
+    pub fn get_market(env: Env, session_id: u32) -> Result<Market, Error> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Market(session_id))
+            .ok_or(Error::MarketNotFound)
+    }
+
+    /// What `bettor` staked on one side of `session_id`, and has not claimed
+    pub fn get_stake(env: Env, session_id: u32, bettor: Address, on_attacker: bool) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Stake(session_id, bettor, on_attacker))
+            .unwrap_or(0)
+    }
+}
+
+fn admin(env: &Env) -> Address {
+    env.storage()
+        .instance()
+        .get(&DataKey::Admin)
+        .expect("Admin not set")
+}
+
+fn game_client(env: &Env) -> TrapGridClient<'_> {
+    let game: Address = env
+        .storage()
+        .instance()
+        .get(&DataKey::Game)
+        .expect("Game not set");
+    TrapGridClient::new(env, &game)
+}
+
+/// The session's game, while it is being played
+fn live_game(env: &Env, session_id: u32) -> Result<Game, Error> {
+    match game_client(env).try_get_game(&session_id) {
+        Ok(Ok(game)) if game.game_started && !game.game_ended => Ok(game),
+        _ => Err(Error::GameNotLive),
+    }
+}
+
+fn save<V: soroban_sdk::IntoVal<Env, soroban_sdk::Val>>(env: &Env, key: &DataKey, value: &V) {
+    env.storage().persistent().set(key, value);
+    env.storage()
+        .persistent()
+        .extend_ttl(key, MARKET_TTL_LEDGERS, MARKET_TTL_LEDGERS);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use soroban_sdk::symbol_short;
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::token::StellarAssetClient;
+
+    const FUNDS: i128 = 1_000;
+
+    /// Stands in for trap-grid: games are whatever the test stores
+    #[contract]
+    pub struct MockTrapGrid;
+
+    #[contracterror]
+    #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+    #[repr(u32)]
+    pub enum MockTrapGridError {
+        GameNotFound = 1,
+    }
+
+    #[contractimpl]
+    impl MockTrapGrid {
+        pub fn set_game(env: Env, session_id: u32, game: Game) {
+            env.storage().temporary().set(&session_id, &game);
+        }
+
+        pub fn remove_game(env: Env, session_id: u32) {
+            env.storage().temporary().remove(&session_id);
+        }
+
+        pub fn get_game(env: Env, session_id: u32) -> Result<Game, MockTrapGridError> {
+            env.storage()
+                .temporary()
+                .get(&session_id)
+                .ok_or(MockTrapGridError::GameNotFound)
+        }
+
+        pub fn set_arbitration(env: Env, session_id: u32, arbitration: Option<Arbitration>) {
+            let key = (symbol_short!("arbiter"), session_id);
+            match arbitration {
+                Some(arbitration) => env.storage().temporary().set(&key, &arbitration),
+                None => env.storage().temporary().remove(&key),
+            }
+        }
+
+        pub fn get_arbitration(env: Env, session_id: u32) -> Option<Arbitration> {
+            env.storage()
+                .temporary()
+                .get(&(symbol_short!("arbiter"), session_id))
+        }
+
+        pub fn set_frozen(env: Env, session_id: u32, by: Option<Address>) {
+            let key = (symbol_short!("frozen"), session_id);
+            match by {
+                Some(by) => env.storage().temporary().set(&key, &by),
+                None => env.storage().temporary().remove(&key),
+            }
+        }
+
+        pub fn get_frozen(env: Env, session_id: u32) -> Option<Address> {
+            env.storage()
+                .temporary()
+                .get(&(symbol_short!("frozen"), session_id))
+        }
+    }
+
+    struct Setup<'a> {
+        env: Env,
+        admin: Address,
+        game: Game,
+        trap_grid: MockTrapGridClient<'a>,
+        token: token::Client<'a>,
+        client: SideBetsContractClient<'a>,
+    }
+
+    impl Setup<'_> {
+        fn bettor(&self) -> Address {
+            let bettor = Address::generate(&self.env);
+            StellarAssetClient::new(&self.env, &self.token.address).mint(&bettor, &FUNDS);
+            bettor
+        }
+
+        fn play(&self, moves_made: u32) {
+            let game = Game {
+                moves_made,
+                ..self.game.clone()
+            };
+            self.trap_grid.set_game(&1, &game);
+        }
+
+        fn end(&self, winner: Option<&Address>) {
+            let game = Game {
+                game_ended: true,
+                winner: winner.cloned(),
+                ..self.game.clone()
+            };
+            self.trap_grid.set_game(&1, &game);
+        }
+    }
+
+    /// Session 1 being played, with a market closing at move 4
+    fn setup(env: &Env) -> Setup<'_> {
+        env.mock_all_auths();
+        let admin = Address::generate(env);
+        let token = env.register_stellar_asset_contract_v2(admin.clone());
+        let trap_grid = MockTrapGridClient::new(env, &env.register(MockTrapGrid, ()));
+        let contract = env.register(SideBetsContract, (&admin, &trap_grid.address));
+        let client = SideBetsContractClient::new(env, &contract);
+
+        let game = Game {
+            defender: Address::generate(env),
+            attacker: Address::generate(env),
+            defender_points: 100,
+            attacker_points: 100,
+            moves_made: 0,
+            hits: 0,
+            misses: 0,
+            game_started: true,
+            game_ended: false,
+            winner: None,
+            schema_version: 5,
+            last_active_ledger: 0,
+            grid_size: 8,
+            trap_count: 10,
+        };
+        trap_grid.set_game(&1, &game);
+        client.open_market(&1, &token.address(), &4);
+        Setup {
+            env: env.clone(),
+            admin,
+            game,
+            trap_grid,
+            token: token::Client::new(env, &token.address()),
+            client,
+        }
+    }
+
+    #[test]
+    fn test_winners_share_the_pool() {
+        let env = Env::default();
+        let setup = setup(&env);
+        let client = &setup.client;
+        client.set_fee(&1_000);
+        let (a, b, c) = (setup.bettor(), setup.bettor(), setup.bettor());
+
+        assert_eq!(client.bet(&a, &1, &true, &100), 100);
+        assert_eq!(client.bet(&a, &1, &true, &200), 300);
+        client.bet(&b, &1, &true, &100);
+        client.bet(&c, &1, &false, &600);
+        let market = client.get_market(&1);
+        assert_eq!((market.attacker_pool, market.defender_pool), (400, 600));
+
+        assert_eq!(client.try_settle(&1), Err(Ok(Error::GameNotEnded)));
+        assert_eq!(client.try_claim(&a, &1), Err(Ok(Error::NotSettled)));
+        setup.end(Some(&setup.game.attacker));
+        assert_eq!(client.settle(&1), Outcome::Attacker);
+        assert_eq!(client.try_settle(&1), Err(Ok(Error::AlreadySettled)));
+        assert_eq!(setup.token.balance(&setup.admin), 100);
+
+        // 900 left for the 400 staked on the attacker
+        assert_eq!(client.claim(&a, &1), 675);
+        assert_eq!(client.claim(&b, &1), 225);
+        assert_eq!(client.try_claim(&a, &1), Err(Ok(Error::NothingToClaim)));
+        assert_eq!(client.try_claim(&c, &1), Err(Ok(Error::NothingToClaim)));
+        assert_eq!(setup.token.balance(&a), FUNDS + 375);
+        assert_eq!(setup.token.balance(&c), FUNDS - 600);
+        assert_eq!(setup.token.balance(&client.address), 0);
+    }
+
+    #[test]
+    fn test_bets_close_at_the_cutoff() {
+        let env = Env::default();
+        let setup = setup(&env);
+        let client = &setup.client;
+        let bettor = setup.bettor();
+
+        assert_eq!(
+            client.try_open_market(&1, &setup.token.address, &8),
+            Err(Ok(Error::MarketExists))
+        );
+        assert_eq!(
+            client.try_open_market(&2, &setup.token.address, &8),
+            Err(Ok(Error::GameNotLive))
+        );
+        assert_eq!(
+            client.try_bet(&bettor, &1, &true, &0),
+            Err(Ok(Error::InvalidAmount))
+        );
+        setup.play(3);
+        client.bet(&bettor, &1, &true, &10);
+        setup.play(4);
+        assert_eq!(
+            client.try_bet(&bettor, &1, &false, &10),
+            Err(Ok(Error::BettingClosed))
+        );
+        setup.end(None);
+        assert_eq!(
+            client.try_bet(&bettor, &1, &false, &10),
+            Err(Ok(Error::BettingClosed))
+        );
+        assert_eq!(client.try_set_fee(&10_001), Err(Ok(Error::InvalidFee)));
+    }
+
+    #[test]
+    fn test_void_markets_refund_every_stake() {
+        let env = Env::default();
+        let setup = setup(&env);
+        let client = &setup.client;
+        client.set_fee(&1_000);
+        let (a, b) = (setup.bettor(), setup.bettor());
+        client.bet(&a, &1, &true, &100);
+        client.bet(&a, &1, &false, &50);
+        client.bet(&b, &1, &false, &200);
+
+        // Aborted: no winner
+        setup.end(None);
+        assert_eq!(client.settle(&1), Outcome::Void);
+        assert_eq!(client.claim(&a, &1), 150);
+        assert_eq!(client.claim(&b, &1), 200);
+        assert_eq!(setup.token.balance(&setup.admin), 0);
+
+        // A winner nobody backed voids the market too
+        setup.trap_grid.set_game(&2, &setup.game);
+        client.open_market(&2, &setup.token.address, &4);
+        client.bet(&a, &2, &false, &100);
+        setup.trap_grid.set_game(
+            &2,
+            &Game {
+                game_ended: true,
+                winner: Some(setup.game.attacker.clone()),
+                ..setup.game.clone()
+            },
+        );
+        assert_eq!(client.settle(&2), Outcome::Void);
+        assert_eq!(client.claim(&a, &2), 100);
+
+        // So does a session trap-grid no longer has
+        setup.trap_grid.set_game(&3, &setup.game);
+        client.open_market(&3, &setup.token.address, &4);
+        client.bet(&b, &3, &true, &100);
+        setup.trap_grid.remove_game(&3);
+        assert_eq!(client.settle(&3), Outcome::Void);
+        assert_eq!(client.claim(&b, &3), 100);
+        assert_eq!(setup.token.balance(&a), FUNDS);
+        assert_eq!(setup.token.balance(&b), FUNDS);
+    }
+
+    #[test]
+    fn test_settlement_waits_for_a_final_result() {
+        let env = Env::default();
+        let setup = setup(&env);
+        let client = &setup.client;
+        let (a, b) = (setup.bettor(), setup.bettor());
+        client.bet(&a, &1, &true, &100);
+        client.bet(&b, &1, &false, &100);
+
+        // The defender won, but the result is open to appeal
+        setup.end(Some(&setup.game.defender));
+        let arbitration = Arbitration {
+            arbiter: Address::generate(&env),
+            appeal_until: 100,
+            appellant: None,
+        };
+        setup
+            .trap_grid
+            .set_arbitration(&1, &Some(arbitration.clone()));
+        assert_eq!(client.try_settle(&1), Err(Ok(Error::ResultNotFinal)));
+
+        // The attacker appeals and the arbiter overturns it, while the
+        // arbiter also holds the game for a while
+        setup.trap_grid.set_arbitration(
+            &1,
+            &Some(Arbitration {
+                appellant: Some(setup.game.attacker.clone()),
+                ..arbitration.clone()
+            }),
+        );
+        assert_eq!(client.try_settle(&1), Err(Ok(Error::ResultNotFinal)));
+        setup.trap_grid.set_arbitration(&1, &None);
+        setup.end(Some(&setup.game.attacker));
+        setup
+            .trap_grid
+            .set_frozen(&1, &Some(arbitration.arbiter.clone()));
+        assert_eq!(client.try_settle(&1), Err(Ok(Error::ResultNotFinal)));
+        assert_eq!(client.try_claim(&a, &1), Err(Ok(Error::NotSettled)));
+
+        // Only the final result pays
+        setup.trap_grid.set_frozen(&1, &None);
+        assert_eq!(client.settle(&1), Outcome::Attacker);
+        assert_eq!(client.claim(&a, &1), 200);
+        assert_eq!(client.try_claim(&b, &1), Err(Ok(Error::NothingToClaim)));
+    }
+}
@@ -0,0 +1,87 @@
+//! CPU and memory regression tests
+//!
+//! The test environment meters each top-level call, so the cost of
+//! `start_game` and of `make_move` at several points of a game is compared
+//! with a ceiling. A change that makes a call noticeably heavier, such as a
+//! scan over the moves that grows faster than the game, fails here.
+//!
+//! Costs are those of the native contract, below what the Wasm build is
+//! charged on the network; the ceilings leave about a third of headroom over
+//! the measured cost.
+
+extern crate std;
+
+use soroban_sdk::Env;
+
+use crate::testutils::{moves, TestSetup};
+use trap_grid_encoding::DEFAULT_GRID_SIZE;
+
+const SESSION_ID: u32 = 1;
+const MAX_MOVES: u32 = DEFAULT_GRID_SIZE * DEFAULT_GRID_SIZE;
+
+/// CPU instructions and memory bytes of the last top-level call
+fn cost(env: &Env) -> (u64, u64) {
+    let budget = env.cost_estimate().budget();
+    (budget.cpu_instruction_cost(), budget.memory_bytes_cost())
+}
+
+/// Ceilings of (CPU instructions, memory bytes) of `make_move` by the number
+/// of moves already made; the 64th move also settles the game with the hub
+/// and adds its moves to the heatmap
+const MAKE_MOVE_CEILINGS: [(u32, (u64, u64)); 4] = [
+    (0, (505_000, 205_000)),
+    (16, (750_000, 380_000)),
+    (32, (1_000_000, 565_000)),
+    (63, (2_500_000, 1_330_000)),
+];
+
+/// Ceiling of the extra cost of each move over the one before, so the cost of
+/// a move stays linear in the moves already made
+const MAKE_MOVE_GROWTH: (u64, u64) = (27_000, 12_000);
+
+fn assert_within(call: &str, (cpu, mem): (u64, u64), (max_cpu, max_mem): (u64, u64)) {
+    assert!(
+        cpu <= max_cpu,
+        "{call} used {cpu} CPU instructions, over the ceiling of {max_cpu}"
+    );
+    assert!(
+        mem <= max_mem,
+        "{call} used {mem} memory bytes, over the ceiling of {max_mem}"
+    );
+}
+
+#[test]
+fn test_start_game_cost() {
+    let env = Env::default();
+    let setup = TestSetup::new(&env);
+    setup.start_game(SESSION_ID);
+    assert_within("start_game", cost(&env), (540_000, 215_000));
+}
+
+#[test]
+fn test_make_move_cost() {
+    let env = Env::default();
+    let setup = TestSetup::new(&env);
+    setup.start_game(SESSION_ID);
+
+    let mut previous: Option<(u64, u64)> = None;
+    for (made, test_move) in (0u32..).zip(moves(|x, y| x == y)) {
+        setup.make_move(SESSION_ID, &test_move);
+        let (cpu, mem) = cost(&env);
+        let call = std::format!("make_move after {made} moves");
+
+        if let Some((_, ceiling)) = MAKE_MOVE_CEILINGS.iter().find(|(at, _)| *at == made) {
+            assert_within(&call, (cpu, mem), *ceiling);
+        }
+        // The last move also pays for settling, so only the others are compared
+        if let (Some((prev_cpu, prev_mem)), false) = (previous, made == MAX_MOVES - 1) {
+            assert_within(
+                &std::format!("{call}, over the move before,"),
+                (cpu.saturating_sub(prev_cpu), mem.saturating_sub(prev_mem)),
+                MAKE_MOVE_GROWTH,
+            );
+        }
+        previous = Some((cpu, mem));
+    }
+    assert!(setup.client.get_game(&SESSION_ID).game_ended);
+}
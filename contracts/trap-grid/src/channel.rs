@@ -0,0 +1,351 @@
+//! Tests of games played through a channel
+//!
+//! Players sign channel states with ed25519 keys generated here; the
+//! contract verifies them against the keys registered by `open_channel`.
+//! [`Keys`] also signs the states of the channel cases of the error matrix.
+
+extern crate std;
+
+use ed25519_dalek::{Signer, SigningKey};
+use soroban_sdk::testutils::{Events as _, Ledger as _};
+use soroban_sdk::xdr::ToXdr;
+use soroban_sdk::{vec, Address, BytesN, Env, Event, Vec};
+
+use crate::testutils::TestSetup;
+use crate::{
+    transcript_hash, ChannelMove, ChannelState, ChannelStateSubmitted, Error, GameEnded,
+    CHANNEL_CHALLENGE_LEDGERS,
+};
+
+const SESSION_ID: u32 = 1;
+
+/// Known answer of [`transcript_hash`] for [`transcript`]'s first two moves
+const TRANSCRIPT_HASH: &str = "7f7378de2b28cd3717b44292ad3d7168339ed4f7a7dc5045b724a9c1b1013c09";
+
+const CONTRACT: &str = "CABAEAQCAIBAEAQCAIBAEAQCAIBAEAQCAIBAEAQCAIBAEAQCAIBAFNSZ";
+
+/// What the players sign in session 7 of `CONTRACT` after [`transcript`]'s
+/// first two moves; known answer shared with the SDK
+const MESSAGE: &str = "0000001000000001000000030000001200000001020202020202020202020202020202020202020202020202020202020202020200000003000000070000001100000001000000050000000f00000007636c6f73696e670000000000000000000000000f000000046869747300000003000000010000000f000000066d6973736573000000000003000000010000000f0000000a6d6f76655f696e646578000000000003000000020000000f0000000a7472616e73637269707400000000000d000000207f7378de2b28cd3717b44292ad3d7168339ed4f7a7dc5045b724a9c1b1013c09";
+
+/// Both players' channel keys
+pub struct Keys {
+    defender: SigningKey,
+    attacker: SigningKey,
+}
+
+impl Keys {
+    pub fn new() -> Self {
+        Self {
+            defender: SigningKey::from_bytes(&[1; 32]),
+            attacker: SigningKey::from_bytes(&[2; 32]),
+        }
+    }
+
+    /// Open the game's channel with these keys
+    pub fn open(&self, setup: &TestSetup, session_id: u32) {
+        let env = &setup.env;
+        setup.client.open_channel(
+            &session_id,
+            &BytesN::from_array(env, &self.defender.verifying_key().to_bytes()),
+            &BytesN::from_array(env, &self.attacker.verifying_key().to_bytes()),
+        );
+    }
+
+    /// Both players' signatures of `state`
+    pub fn sign(
+        &self,
+        setup: &TestSetup,
+        session_id: u32,
+        state: &ChannelState,
+    ) -> (BytesN<64>, BytesN<64>) {
+        let env = &setup.env;
+        let message: std::vec::Vec<u8> = (setup.contract.clone(), session_id, state.clone())
+            .to_xdr(env)
+            .iter()
+            .collect();
+        (
+            BytesN::from_array(env, &self.defender.sign(&message).to_bytes()),
+            BytesN::from_array(env, &self.attacker.sign(&message).to_bytes()),
+        )
+    }
+
+    /// Submit `state` signed by both players; returns the challenge window's
+    /// last ledger
+    pub fn submit(
+        &self,
+        setup: &TestSetup,
+        session_id: u32,
+        state: &ChannelState,
+    ) -> Result<u32, Error> {
+        let (defender_signature, attacker_signature) = self.sign(setup, session_id, state);
+        match setup.client.try_submit_channel_state(
+            &session_id,
+            state,
+            &defender_signature,
+            &attacker_signature,
+        ) {
+            Ok(Ok(challenge_until)) => Ok(challenge_until),
+            Err(Ok(err)) => Err(err),
+            other => panic!("submit_channel_state failed: {other:?}"),
+        }
+    }
+}
+
+/// The first `len` moves of a channel game: hits on the diagonal
+pub fn transcript(env: &Env, len: u32) -> Vec<ChannelMove> {
+    let mut moves = Vec::new(env);
+    for i in 0..len {
+        let (x, y) = (i % 8, i / 8);
+        moves.push_back(ChannelMove {
+            x,
+            y,
+            is_hit: x == y,
+            proof_hash: BytesN::from_array(env, &[i as u8 + 1; 32]),
+        });
+    }
+    moves
+}
+
+/// The state after `moves`
+pub fn state(env: &Env, moves: &Vec<ChannelMove>, closing: bool) -> ChannelState {
+    let hits = moves.iter().filter(|m| m.is_hit).count() as u32;
+    ChannelState {
+        move_index: moves.len(),
+        hits,
+        misses: moves.len() - hits,
+        transcript: transcript_hash(env, moves),
+        closing,
+    }
+}
+
+#[test]
+fn test_transcript_hash() {
+    let env = Env::default();
+    let hash = transcript_hash(&env, &transcript(&env, 2));
+    assert_eq!(hex(hash.to_array()), TRANSCRIPT_HASH);
+    assert_eq!(
+        transcript_hash(&env, &Vec::new(&env)),
+        BytesN::from_array(&env, &[0; 32])
+    );
+}
+
+/// Lowercase hex of `bytes`
+fn hex(bytes: impl IntoIterator<Item = u8>) -> std::string::String {
+    bytes
+        .into_iter()
+        .map(|byte| std::format!("{byte:02x}"))
+        .collect()
+}
+
+#[test]
+fn test_channel_message() {
+    let env = Env::default();
+    let state = state(&env, &transcript(&env, 2), false);
+    let contract = Address::from_str(&env, CONTRACT);
+    let message = (contract, 7u32, state).to_xdr(&env);
+    assert_eq!(hex(message.iter()), MESSAGE);
+}
+
+#[test]
+fn test_unilateral_exit_and_challenge() {
+    let env = Env::default();
+    let setup = TestSetup::new(&env);
+    let client = &setup.client;
+    let (defender, attacker) = setup.start_game(SESSION_ID);
+    let keys = Keys::new();
+    keys.open(&setup, SESSION_ID);
+    assert_eq!(
+        client.try_submit_move(&SESSION_ID, &0, &0),
+        Err(Ok(Error::ChannelConflict))
+    );
+    assert_eq!(
+        client.try_end_game(&SESSION_ID),
+        Err(Ok(Error::ChannelConflict))
+    );
+    assert_eq!(
+        client.try_settle_channel(&SESSION_ID, &Vec::new(&env)),
+        Err(Ok(Error::NoChannelState))
+    );
+
+    // The defender exits with an old state; the attacker answers with a newer one
+    let moves = transcript(&env, 10);
+    let old = state(&env, &transcript(&env, 4), false);
+    keys.submit(&setup, SESSION_ID, &old).unwrap();
+    let latest = state(&env, &moves, false);
+    let sequence = env.ledger().sequence();
+    assert_eq!(
+        keys.submit(&setup, SESSION_ID, &latest),
+        Ok(sequence + CHANNEL_CHALLENGE_LEDGERS)
+    );
+    assert_eq!(
+        env.events().all().filter_by_contract(&setup.contract),
+        [ChannelStateSubmitted {
+            session_id: SESSION_ID,
+            defender: defender.clone(),
+            attacker: attacker.clone(),
+            move_index: 10,
+            challenge_until: sequence + CHANNEL_CHALLENGE_LEDGERS,
+        }
+        .to_xdr(&env, &setup.contract)]
+    );
+    assert_eq!(
+        keys.submit(&setup, SESSION_ID, &old),
+        Err(Error::StaleChannelState)
+    );
+    assert_eq!(
+        client.try_settle_channel(&SESSION_ID, &moves),
+        Err(Ok(Error::ChallengeWindowOpen))
+    );
+
+    env.ledger()
+        .with_mut(|ledger| ledger.sequence_number += CHANNEL_CHALLENGE_LEDGERS + 1);
+    assert_eq!(
+        client.try_settle_channel(&SESSION_ID, &transcript(&env, 9)),
+        Err(Ok(Error::InvalidTranscript))
+    );
+    client.settle_channel(&SESSION_ID, &moves);
+    assert_eq!(
+        env.events()
+            .all()
+            .filter_by_contract(&setup.contract)
+            .events()
+            .last()
+            .unwrap(),
+        &GameEnded {
+            session_id: SESSION_ID,
+            defender: defender.clone(),
+            attacker: attacker.clone(),
+            winner: defender.clone(),
+            hits: 2,
+            misses: 8,
+        }
+        .to_xdr(&env, &setup.contract)
+    );
+    let game = client.get_game(&SESSION_ID);
+    assert!(game.game_ended);
+    assert_eq!((game.moves_made, game.hits, game.misses), (10, 2, 8));
+    let recorded = client.get_moves(&SESSION_ID);
+    assert_eq!(recorded.len(), 10);
+    assert!(recorded.iter().all(|m| !m.verified));
+    assert_eq!(client.get_channel(&SESSION_ID), None);
+}
+
+#[test]
+fn test_closing_state_settles_at_once() {
+    let env = Env::default();
+    let setup = TestSetup::new(&env);
+    let client = &setup.client;
+    let (_, attacker) = setup.start_game(SESSION_ID);
+    let keys = Keys::new();
+    keys.open(&setup, SESSION_ID);
+
+    let moves: Vec<ChannelMove> = vec![
+        &env,
+        ChannelMove {
+            x: 3,
+            y: 3,
+            is_hit: true,
+            proof_hash: BytesN::from_array(&env, &[9; 32]),
+        },
+    ];
+    let closing = state(&env, &moves, true);
+    keys.submit(&setup, SESSION_ID, &closing).unwrap();
+    assert_eq!(
+        keys.submit(
+            &setup,
+            SESSION_ID,
+            &state(&env, &transcript(&env, 2), false)
+        ),
+        Err(Error::StaleChannelState)
+    );
+    client.settle_channel(&SESSION_ID, &moves);
+    assert_eq!(client.get_game(&SESSION_ID).winner, Some(attacker));
+}
+
+#[test]
+fn test_frozen_channel_waits() {
+    let env = Env::default();
+    let setup = TestSetup::new(&env);
+    let client = &setup.client;
+    setup.start_game(SESSION_ID);
+    let keys = Keys::new();
+    keys.open(&setup, SESSION_ID);
+    let moves = transcript(&env, 2);
+    keys.submit(&setup, SESSION_ID, &state(&env, &moves, true))
+        .unwrap();
+
+    // Not even a closing state settles a frozen game
+    client.freeze_session(&setup.admin, &SESSION_ID);
+    assert_eq!(
+        client.try_settle_channel(&SESSION_ID, &moves),
+        Err(Ok(Error::GameFrozen))
+    );
+    client.unfreeze_session(&setup.admin, &SESSION_ID);
+    client.settle_channel(&SESSION_ID, &moves);
+    assert!(client.get_game(&SESSION_ID).game_ended);
+}
+
+#[test]
+fn test_channel_checks() {
+    let env = Env::default();
+    let setup = TestSetup::new(&env);
+    let client = &setup.client;
+    setup.start_game(SESSION_ID);
+    let keys = Keys::new();
+
+    // Not after an on-chain move
+    client.submit_move(&SESSION_ID, &0, &0);
+    let key = BytesN::from_array(&env, &[0; 32]);
+    assert_eq!(
+        client.try_open_channel(&SESSION_ID, &key, &key),
+        Err(Ok(Error::ChannelConflict))
+    );
+
+    setup.start_game(2);
+    keys.open(&setup, 2);
+    assert_eq!(
+        client.try_open_channel(&2, &key, &key),
+        Err(Ok(Error::ChannelConflict))
+    );
+    // Counts that do not add up
+    let mut bad = state(&env, &transcript(&env, 3), false);
+    bad.misses += 1;
+    assert_eq!(keys.submit(&setup, 2, &bad), Err(Error::InvalidTranscript));
+
+    // A repeated cell, or one off the grid
+    let mut moves = transcript(&env, 2);
+    moves.set(1, moves.get(0).unwrap());
+    keys.submit(&setup, 2, &state(&env, &moves, true)).unwrap();
+    assert_eq!(
+        client.try_settle_channel(&2, &moves),
+        Err(Ok(Error::InvalidTranscript))
+    );
+    let mut off_grid = moves.get(0).unwrap();
+    off_grid.x = 8;
+    assert_eq!(
+        client.try_settle_channel(&2, &vec![&env, off_grid]),
+        Err(Ok(Error::InvalidTranscript))
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_forged_signature() {
+    let env = Env::default();
+    let setup = TestSetup::new(&env);
+    setup.start_game(SESSION_ID);
+    let keys = Keys::new();
+    keys.open(&setup, SESSION_ID);
+
+    // The attacker signs alone, with its key standing in for the defender's
+    let state = state(&env, &transcript(&env, 3), true);
+    let forger = Keys {
+        defender: SigningKey::from_bytes(&[2; 32]),
+        attacker: SigningKey::from_bytes(&[2; 32]),
+    };
+    let (forged, attacker_signature) = forger.sign(&setup, SESSION_ID, &state);
+    setup
+        .client
+        .submit_channel_state(&SESSION_ID, &state, &forged, &attacker_signature);
+}
@@ -0,0 +1,143 @@
+//! Daily puzzles: one hidden grid a day, attacked by every player
+//!
+//! The operator commits to the day's layout as the SHA-256 of its trap cells
+//! (each `x * grid_size + y` as a big-endian u32, in ascending order) followed
+//! by a salt. After the day closes it reveals the cells and the salt, and each
+//! player's probes are scored against the revealed layout rather than the
+//! answers the operator gave during the day. Those answers are kept unverified
+//! until then, and checked against the layout when the player is ranked.
+
+use soroban_sdk::{Bytes, BytesN, Env, Vec};
+
+use crate::{DailyRank, DAILY_LEADERBOARD_SIZE};
+
+/// Seconds in a daily puzzle's day, counted from the Unix epoch
+pub const DAY_SECONDS: u64 = 86_400;
+
+/// The day the ledger closes in
+pub fn today(env: &Env) -> u32 {
+    (env.ledger().timestamp() / DAY_SECONDS) as u32
+}
+
+/// The commitment to a layout of `traps`, in ascending order, and `salt`
+pub fn commitment(env: &Env, traps: &Vec<u32>, salt: &BytesN<32>) -> BytesN<32> {
+    let mut input = Bytes::new(env);
+    for cell in traps.iter() {
+        input.extend_from_array(&cell.to_be_bytes());
+    }
+    input.append(&Bytes::from(salt));
+    env.crypto().sha256(&input).into()
+}
+
+/// Whether `traps` are `trap_count` cells of a `grid_size` grid in strictly
+/// ascending order, the only form a commitment is checked in
+pub fn is_canonical(traps: &Vec<u32>, grid_size: u32, trap_count: u32) -> bool {
+    let cells = grid_size * grid_size;
+    traps.len() == trap_count
+        && traps.iter().all(|cell| cell < cells)
+        && traps
+            .iter()
+            .zip(traps.iter().skip(1))
+            .all(|(cell, next)| cell < next)
+}
+
+/// Traps among `probes`
+pub fn hits(traps: &Vec<u32>, probes: &Vec<u32>) -> u32 {
+    probes.iter().filter(|cell| traps.contains(cell)).count() as u32
+}
+
+/// Hits among the operator's `answers`, which end a player's day once they
+/// reach the trap count
+pub fn answered_hits(answers: &Vec<bool>) -> u32 {
+    answers.iter().filter(|&is_hit| is_hit).count() as u32
+}
+
+/// Answers the layout of `traps` contradicts, each answering the probe at
+/// the same index
+pub fn misanswered(traps: &Vec<u32>, probes: &Vec<u32>, answers: &Vec<bool>) -> u32 {
+    probes
+        .iter()
+        .zip(answers.iter())
+        .filter(|&(cell, is_hit)| traps.contains(cell) != is_hit)
+        .count() as u32
+}
+
+/// Insert `entry` into `board`, ordered by more hits then fewer probes, with
+/// earlier entries ahead on a tie; returns its place, or `None` when the
+/// board is full of better entries
+pub fn rank(board: &mut Vec<DailyRank>, entry: DailyRank) -> Option<u32> {
+    let place = board
+        .iter()
+        .position(|other| {
+            entry.hits > other.hits || (entry.hits == other.hits && entry.probes < other.probes)
+        })
+        .map_or(board.len(), |place| place as u32);
+    if place >= DAILY_LEADERBOARD_SIZE {
+        return None;
+    }
+    board.insert(place, entry);
+    while board.len() > DAILY_LEADERBOARD_SIZE {
+        board.pop_back();
+    }
+    Some(place)
+}
+
+#[cfg(test)]
+mod test {
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::{vec, Address};
+
+    use super::*;
+
+    #[test]
+    fn test_canonical_layouts() {
+        let env = Env::default();
+        assert!(is_canonical(&vec![&env, 0, 5, 63], 8, 3));
+        assert!(!is_canonical(&vec![&env, 5, 0, 63], 8, 3));
+        assert!(!is_canonical(&vec![&env, 0, 5, 5], 8, 3));
+        assert!(!is_canonical(&vec![&env, 0, 5, 64], 8, 3));
+        assert!(!is_canonical(&vec![&env, 0, 5], 8, 3));
+        assert_eq!(hits(&vec![&env, 0, 5, 63], &vec![&env, 1, 5, 63, 7]), 2);
+    }
+
+    #[test]
+    fn test_misanswered() {
+        let env = Env::default();
+        let traps = vec![&env, 0, 5, 63];
+        let probes = vec![&env, 1, 5, 63, 7];
+        assert_eq!(answered_hits(&vec![&env, false, true, true]), 2);
+        assert_eq!(
+            misanswered(&traps, &probes, &vec![&env, false, true, true]),
+            0
+        );
+        // The last probe is unanswered and checks nothing
+        assert_eq!(
+            misanswered(&traps, &probes, &vec![&env, true, true, false]),
+            2
+        );
+    }
+
+    #[test]
+    fn test_rank() {
+        let env = Env::default();
+        let entry = |hits, probes| DailyRank {
+            player: Address::generate(&env),
+            hits,
+            probes,
+        };
+        let mut board = Vec::new(&env);
+        assert_eq!(rank(&mut board, entry(5, 20)), Some(0));
+        assert_eq!(rank(&mut board, entry(5, 12)), Some(0));
+        assert_eq!(rank(&mut board, entry(4, 6)), Some(2));
+        // A tie ranks behind the entry already there
+        assert_eq!(rank(&mut board, entry(5, 12)), Some(1));
+        for _ in 4..DAILY_LEADERBOARD_SIZE {
+            rank(&mut board, entry(9, 9));
+        }
+        assert_eq!(board.len(), DAILY_LEADERBOARD_SIZE);
+        assert_eq!(rank(&mut board, entry(1, 64)), None);
+        assert_eq!(rank(&mut board, entry(10, 10)), Some(0));
+        assert_eq!(board.len(), DAILY_LEADERBOARD_SIZE);
+        assert_eq!(board.last().unwrap().hits, 5);
+    }
+}
@@ -0,0 +1,1094 @@
+//! Every contract error and the shortest way to reach it
+//!
+//! Each row of [`CASES`] starts a fresh contract, makes the fewest calls that
+//! should fail with its error, and checks the code. Variants no call can
+//! return are listed in [`unreachable`] with the reason; its exhaustive match
+//! makes adding a variant without deciding which list it belongs to a build
+//! error. Failures that are not contract errors (missing authorization,
+//! self-play) abort the call and are checked separately.
+
+extern crate std;
+
+use soroban_sdk::testutils::{Address as _, Ledger as _};
+use soroban_sdk::token::StellarAssetClient;
+use soroban_sdk::{vec, Address, Bytes, BytesN, Env};
+
+use crate::channel::{state, transcript, Keys};
+use crate::testutils::{
+    grid_setup, invalid_proof, moves, public_inputs, valid_proof, TestMove, TestSetup, CLAIM_BOND,
+};
+use crate::{
+    daily, AdminAction, ChannelState, ContractConfig, Error, GridSetup, Ruling, Sponsorship,
+    CLAIM_CHALLENGE_LEDGERS, MAX_NOTE_BYTES, PROPOSAL_EXPIRY_LEDGERS,
+};
+
+const SESSION_ID: u32 = 1;
+
+/// A client `try_` result as the contract error it failed with, if any
+fn outcome<T, E: core::fmt::Debug>(result: Result<T, Result<Error, E>>) -> Result<(), Error> {
+    match result {
+        Ok(_) => Ok(()),
+        Err(Ok(error)) => Err(error),
+        Err(Err(error)) => panic!("call aborted: {error:?}"),
+    }
+}
+
+fn hit(x: u32, y: u32) -> TestMove {
+    TestMove { x, y, is_hit: true }
+}
+
+fn started(setup: &TestSetup) {
+    setup.start_game(SESSION_ID);
+}
+
+fn ended(setup: &TestSetup) {
+    started(setup);
+    setup.client.end_game(&SESSION_ID);
+}
+
+/// An ended game with an arbiter, its result in the appeal window
+fn arbitrated(setup: &TestSetup) -> (Address, Address) {
+    let players = setup.start_game(SESSION_ID);
+    let arbiter = Address::generate(&setup.env);
+    setup.client.appoint_arbiter(&SESSION_ID, &arbiter);
+    setup.client.end_game(&SESSION_ID);
+    players
+}
+
+/// A game with an open channel
+fn channel(setup: &TestSetup) -> Keys {
+    started(setup);
+    let keys = Keys::new();
+    keys.open(setup, SESSION_ID);
+    keys
+}
+
+/// Submit `state` without signatures, which fails before they are checked
+fn submit_unsigned(setup: &TestSetup, state: &ChannelState) -> Result<(), Error> {
+    let signature = BytesN::from_array(&setup.env, &[0; 64]);
+    outcome(
+        setup
+            .client
+            .try_submit_channel_state(&SESSION_ID, state, &signature, &signature),
+    )
+}
+
+/// A game in optimistic mode
+fn optimistic(setup: &TestSetup) {
+    started(setup);
+    setup.enable_optimistic(SESSION_ID);
+}
+
+/// A game in optimistic mode with its first move claimed and challenged
+fn challenged(setup: &TestSetup) {
+    optimistic(setup);
+    submit(setup, 0, 0).unwrap();
+    setup.client.claim_move(&SESSION_ID, &true);
+    setup.client.challenge_claim(&SESSION_ID, &0);
+}
+
+fn submit(setup: &TestSetup, x: u32, y: u32) -> Result<(), Error> {
+    outcome(setup.client.try_submit_move(&SESSION_ID, &x, &y))
+}
+
+fn answer(setup: &TestSetup, x: u32, y: u32) -> Result<(), Error> {
+    setup.try_make_move(SESSION_ID, &hit(x, y))
+}
+
+fn propose(setup: &TestSetup, action: AdminAction) -> Result<(), Error> {
+    outcome(setup.client.try_propose(&setup.admin, &action))
+}
+
+struct Case {
+    name: &'static str,
+    error: Error,
+    calls: fn(&TestSetup) -> Result<(), Error>,
+}
+
+const CASES: &[Case] = &[
+    Case {
+        name: "get_game of an unknown session",
+        error: Error::GameNotFound,
+        calls: |setup| outcome(setup.client.try_get_game(&SESSION_ID)),
+    },
+    Case {
+        name: "submit_move in an unknown session",
+        error: Error::GameNotFound,
+        calls: |setup| submit(setup, 0, 0),
+    },
+    Case {
+        name: "make_move in an unknown session",
+        error: Error::GameNotFound,
+        calls: |setup| answer(setup, 0, 0),
+    },
+    Case {
+        name: "end_game of an unknown session",
+        error: Error::GameNotFound,
+        calls: |setup| outcome(setup.client.try_end_game(&SESSION_ID)),
+    },
+    Case {
+        name: "submit_move after end_game",
+        error: Error::GameAlreadyEnded,
+        calls: |setup| {
+            ended(setup);
+            submit(setup, 0, 0)
+        },
+    },
+    Case {
+        name: "make_move after end_game",
+        error: Error::GameAlreadyEnded,
+        calls: |setup| {
+            ended(setup);
+            answer(setup, 0, 0)
+        },
+    },
+    Case {
+        name: "end_game twice",
+        error: Error::GameAlreadyEnded,
+        calls: |setup| {
+            ended(setup);
+            outcome(setup.client.try_end_game(&SESSION_ID))
+        },
+    },
+    Case {
+        name: "end_game after the last move",
+        error: Error::GameAlreadyEnded,
+        calls: |setup| {
+            started(setup);
+            setup.play(SESSION_ID, moves(|_, _| false)).unwrap();
+            outcome(setup.client.try_end_game(&SESSION_ID))
+        },
+    },
+    Case {
+        name: "submit_move off the grid",
+        error: Error::InvalidMove,
+        calls: |setup| {
+            started(setup);
+            submit(setup, 8, 0)
+        },
+    },
+    Case {
+        name: "make_move off the grid",
+        error: Error::InvalidMove,
+        calls: |setup| {
+            started(setup);
+            answer(setup, 0, 8)
+        },
+    },
+    Case {
+        name: "submit_move of a played cell",
+        error: Error::MoveAlreadyMade,
+        calls: |setup| {
+            started(setup);
+            setup.make_move(SESSION_ID, &hit(2, 3));
+            submit(setup, 2, 3)
+        },
+    },
+    Case {
+        name: "make_move of a played cell",
+        error: Error::MoveAlreadyMade,
+        calls: |setup| {
+            started(setup);
+            setup.make_move(SESSION_ID, &hit(2, 3));
+            answer(setup, 2, 3)
+        },
+    },
+    Case {
+        name: "submit_move while a move is pending",
+        error: Error::MovePending,
+        calls: |setup| {
+            started(setup);
+            submit(setup, 2, 3)?;
+            submit(setup, 4, 5)
+        },
+    },
+    Case {
+        name: "make_move of another cell than the pending one",
+        error: Error::MovePending,
+        calls: |setup| {
+            started(setup);
+            submit(setup, 2, 3)?;
+            answer(setup, 4, 5)
+        },
+    },
+    Case {
+        name: "make_move without a submitted move",
+        error: Error::NoPendingMove,
+        calls: |setup| {
+            started(setup);
+            let env = &setup.env;
+            outcome(setup.client.try_make_move(
+                &SESSION_ID,
+                &2,
+                &3,
+                &true,
+                &valid_proof(env),
+                &setup.public_inputs(SESSION_ID, 2, 3, true),
+            ))
+        },
+    },
+    Case {
+        name: "make_move with a proof the verifier rejects",
+        error: Error::InvalidProof,
+        calls: |setup| {
+            started(setup);
+            submit(setup, 2, 3)?;
+            let env = &setup.env;
+            outcome(setup.client.try_make_move(
+                &SESSION_ID,
+                &2,
+                &3,
+                &true,
+                &invalid_proof(env),
+                &setup.public_inputs(SESSION_ID, 2, 3, true),
+            ))
+        },
+    },
+    Case {
+        name: "make_move with public inputs of another cell",
+        error: Error::InvalidProof,
+        calls: |setup| {
+            started(setup);
+            submit(setup, 2, 3)?;
+            let env = &setup.env;
+            outcome(setup.client.try_make_move(
+                &SESSION_ID,
+                &2,
+                &3,
+                &true,
+                &valid_proof(env),
+                &setup.public_inputs(SESSION_ID, 3, 2, true),
+            ))
+        },
+    },
+    Case {
+        name: "make_move with public inputs of another grid",
+        error: Error::InvalidProof,
+        calls: |setup| {
+            started(setup);
+            submit(setup, 2, 3)?;
+            let env = &setup.env;
+            let other_grid = grid_setup(env).grid_commitment;
+            outcome(setup.client.try_make_move(
+                &SESSION_ID,
+                &2,
+                &3,
+                &true,
+                &valid_proof(env),
+                &public_inputs(env, &other_grid, 2, 3, true),
+            ))
+        },
+    },
+    Case {
+        name: "make_move claiming a miss the proof says is a hit",
+        error: Error::InvalidProof,
+        calls: |setup| {
+            started(setup);
+            submit(setup, 2, 3)?;
+            let env = &setup.env;
+            outcome(setup.client.try_make_move(
+                &SESSION_ID,
+                &2,
+                &3,
+                &false,
+                &valid_proof(env),
+                &setup.public_inputs(SESSION_ID, 2, 3, true),
+            ))
+        },
+    },
+    Case {
+        name: "start_sponsored_game staking nothing",
+        error: Error::InvalidSponsorship,
+        calls: |setup| {
+            let env = &setup.env;
+            let defender = Address::generate(env);
+            let sponsorship = Sponsorship {
+                sponsor: Address::generate(env),
+                player: defender.clone(),
+                token: Address::generate(env),
+                amount: 0,
+                player_share_bps: 5_000,
+            };
+            outcome(setup.client.try_start_sponsored_game(
+                &SESSION_ID,
+                &defender,
+                &Address::generate(env),
+                &100,
+                &100,
+                &grid_setup(env),
+                &vec![env, sponsorship],
+            ))
+        },
+    },
+    Case {
+        name: "register_referrer twice",
+        error: Error::ReferrerAlreadySet,
+        calls: |setup| {
+            let env = &setup.env;
+            let player = Address::generate(env);
+            setup
+                .client
+                .register_referrer(&player, &Address::generate(env));
+            outcome(
+                setup
+                    .client
+                    .try_register_referrer(&player, &Address::generate(env)),
+            )
+        },
+    },
+    Case {
+        name: "register_referrer of the player themselves",
+        error: Error::SelfReferral,
+        calls: |setup| {
+            let player = Address::generate(&setup.env);
+            outcome(setup.client.try_register_referrer(&player, &player))
+        },
+    },
+    Case {
+        name: "propose fees over 100%",
+        error: Error::InvalidFees,
+        calls: |setup| propose(setup, AdminAction::SetFees(10_001, 0)),
+    },
+    Case {
+        name: "propose an admin set above its threshold",
+        error: Error::InvalidThreshold,
+        calls: |setup| {
+            let members = vec![&setup.env, setup.admin.clone()];
+            propose(setup, AdminAction::SetAdminSet(members, 2))
+        },
+    },
+    Case {
+        name: "propose as a stranger",
+        error: Error::NotAdminMember,
+        calls: |setup| {
+            let stranger = Address::generate(&setup.env);
+            outcome(
+                setup
+                    .client
+                    .try_propose(&stranger, &AdminAction::SetFees(0, 0)),
+            )
+        },
+    },
+    Case {
+        name: "approve an unknown proposal",
+        error: Error::ProposalNotFound,
+        calls: |setup| outcome(setup.client.try_approve(&setup.admin, &0)),
+    },
+    Case {
+        name: "approve twice",
+        error: Error::AlreadyApproved,
+        calls: |setup| {
+            let second = Address::generate(&setup.env);
+            let members = vec![&setup.env, setup.admin.clone(), second];
+            propose(setup, AdminAction::SetAdminSet(members, 2))?;
+            propose(setup, AdminAction::SetFees(0, 0))?;
+            outcome(setup.client.try_approve(&setup.admin, &1))
+        },
+    },
+    Case {
+        name: "approve after the expiry",
+        error: Error::ProposalExpired,
+        calls: |setup| {
+            let second = Address::generate(&setup.env);
+            let members = vec![&setup.env, setup.admin.clone(), second.clone()];
+            propose(setup, AdminAction::SetAdminSet(members, 2))?;
+            propose(setup, AdminAction::SetFees(0, 0))?;
+            setup
+                .env
+                .ledger()
+                .with_mut(|l| l.sequence_number += PROPOSAL_EXPIRY_LEDGERS + 1);
+            outcome(setup.client.try_approve(&second, &1))
+        },
+    },
+    Case {
+        name: "emergency_withdraw without queueing it",
+        error: Error::WithdrawNotQueued,
+        calls: |setup| {
+            started(setup);
+            outcome(setup.client.try_emergency_withdraw(&SESSION_ID))
+        },
+    },
+    Case {
+        name: "emergency_withdraw right after queueing it",
+        error: Error::WithdrawTimelocked,
+        calls: |setup| {
+            started(setup);
+            setup.client.queue_emergency_withdraw(&SESSION_ID);
+            outcome(setup.client.try_emergency_withdraw(&SESSION_ID))
+        },
+    },
+    Case {
+        name: "set_paused by a stranger",
+        error: Error::NotOperator,
+        calls: |setup| {
+            let stranger = Address::generate(&setup.env);
+            outcome(setup.client.try_set_paused(&stranger, &true))
+        },
+    },
+    Case {
+        name: "start_game while paused",
+        error: Error::Paused,
+        calls: |setup| {
+            setup.client.set_paused(&setup.admin, &true);
+            let env = &setup.env;
+            outcome(setup.client.try_start_game(
+                &SESSION_ID,
+                &Address::generate(env),
+                &Address::generate(env),
+                &100,
+                &100,
+                &grid_setup(env),
+            ))
+        },
+    },
+    Case {
+        name: "set_config with an emergency delay outliving the game",
+        error: Error::InvalidConfig,
+        calls: |setup| {
+            let config = ContractConfig {
+                emergency_delay_ledgers: ContractConfig::DEFAULT.game_ttl_ledgers,
+                ..ContractConfig::DEFAULT
+            };
+            outcome(setup.client.try_set_config(&config))
+        },
+    },
+    Case {
+        name: "start_game beyond max_active_games",
+        error: Error::TooManyGames,
+        calls: |setup| {
+            let config = ContractConfig {
+                max_active_games: 1,
+                ..ContractConfig::DEFAULT
+            };
+            setup.client.set_config(&config);
+            started(setup);
+            let env = &setup.env;
+            outcome(setup.client.try_start_game(
+                &(SESSION_ID + 1),
+                &Address::generate(env),
+                &Address::generate(env),
+                &100,
+                &100,
+                &grid_setup(env),
+            ))
+        },
+    },
+    Case {
+        name: "start_game staking nothing",
+        error: Error::StakeNotPositive,
+        calls: |setup| {
+            let env = &setup.env;
+            outcome(setup.client.try_start_game(
+                &SESSION_ID,
+                &Address::generate(env),
+                &Address::generate(env),
+                &0,
+                &100,
+                &grid_setup(env),
+            ))
+        },
+    },
+    Case {
+        name: "start_game staking more than max_points",
+        error: Error::StakeOutOfBounds,
+        calls: |setup| {
+            let config = ContractConfig {
+                max_points: 99,
+                ..ContractConfig::DEFAULT
+            };
+            setup.client.set_config(&config);
+            let env = &setup.env;
+            outcome(setup.client.try_start_game(
+                &SESSION_ID,
+                &Address::generate(env),
+                &Address::generate(env),
+                &100,
+                &99,
+                &grid_setup(env),
+            ))
+        },
+    },
+    Case {
+        name: "start_game with a player short of min_games_completed",
+        error: Error::TooFewGames,
+        calls: |setup| {
+            let config = ContractConfig {
+                min_games_completed: 1,
+                ..ContractConfig::DEFAULT
+            };
+            setup.client.set_config(&config);
+            let env = &setup.env;
+            outcome(setup.client.try_start_game(
+                &SESSION_ID,
+                &Address::generate(env),
+                &Address::generate(env),
+                &100,
+                &100,
+                &grid_setup(env),
+            ))
+        },
+    },
+    Case {
+        name: "start_game with a setup proof that does not verify",
+        error: Error::InvalidSetup,
+        calls: |setup| {
+            let env = &setup.env;
+            let unverified = GridSetup {
+                proof: invalid_proof(env),
+                ..grid_setup(env)
+            };
+            outcome(setup.client.try_start_game(
+                &SESSION_ID,
+                &Address::generate(env),
+                &Address::generate(env),
+                &100,
+                &100,
+                &unverified,
+            ))
+        },
+    },
+    Case {
+        name: "start_game with the grid commitment of an earlier game",
+        error: Error::SetupReused,
+        calls: |setup| {
+            let env = &setup.env;
+            setup.start_game(SESSION_ID);
+            let replayed = GridSetup {
+                grid_commitment: setup.client.get_grid_commitment(&SESSION_ID).unwrap(),
+                ..grid_setup(env)
+            };
+            outcome(setup.client.try_start_game(
+                &(SESSION_ID + 1),
+                &Address::generate(env),
+                &Address::generate(env),
+                &100,
+                &100,
+                &replayed,
+            ))
+        },
+    },
+    Case {
+        name: "submit_move within the move gap",
+        error: Error::MoveTooSoon,
+        calls: |setup| {
+            setup.client.set_config(&ContractConfig {
+                move_gap_ledgers: 10,
+                ..ContractConfig::DEFAULT
+            });
+            started(setup);
+            answer(setup, 0, 0).unwrap();
+            submit(setup, 1, 0)
+        },
+    },
+    Case {
+        name: "submit_channel_state no newer than the last",
+        error: Error::StaleChannelState,
+        calls: |setup| {
+            channel(setup);
+            submit_unsigned(setup, &state(&setup.env, &transcript(&setup.env, 0), false))
+        },
+    },
+    Case {
+        name: "settle_channel in the challenge window",
+        error: Error::ChallengeWindowOpen,
+        calls: |setup| {
+            let keys = channel(setup);
+            let moves = transcript(&setup.env, 2);
+            keys.submit(setup, SESSION_ID, &state(&setup.env, &moves, false))?;
+            outcome(setup.client.try_settle_channel(&SESSION_ID, &moves))
+        },
+    },
+    Case {
+        name: "submit_channel_state whose counts do not add up",
+        error: Error::InvalidTranscript,
+        calls: |setup| {
+            channel(setup);
+            let mut state = state(&setup.env, &transcript(&setup.env, 2), false);
+            state.hits += 1;
+            submit_unsigned(setup, &state)
+        },
+    },
+    Case {
+        name: "settle_channel without a channel",
+        error: Error::NoChannelState,
+        calls: |setup| {
+            started(setup);
+            outcome(
+                setup
+                    .client
+                    .try_settle_channel(&SESSION_ID, &transcript(&setup.env, 0)),
+            )
+        },
+    },
+    Case {
+        name: "submit_move in a channel game",
+        error: Error::ChannelConflict,
+        calls: |setup| {
+            channel(setup);
+            submit(setup, 0, 0)
+        },
+    },
+    Case {
+        name: "claim_move outside optimistic mode",
+        error: Error::NotOptimistic,
+        calls: |setup| {
+            started(setup);
+            submit(setup, 0, 0)?;
+            outcome(setup.client.try_claim_move(&SESSION_ID, &true))
+        },
+    },
+    Case {
+        name: "claim_move without a submitted move",
+        error: Error::NoPendingMove,
+        calls: |setup| {
+            optimistic(setup);
+            outcome(setup.client.try_claim_move(&SESSION_ID, &true))
+        },
+    },
+    Case {
+        name: "challenge_claim while another claim is challenged",
+        error: Error::ClaimNotChallengeable,
+        calls: |setup| {
+            challenged(setup);
+            submit(setup, 1, 0)?;
+            setup.client.claim_move(&SESSION_ID, &false);
+            outcome(setup.client.try_challenge_claim(&SESSION_ID, &1))
+        },
+    },
+    Case {
+        name: "prove_claim without a challenge",
+        error: Error::NoChallenge,
+        calls: |setup| {
+            optimistic(setup);
+            let inputs = setup.public_inputs(SESSION_ID, 0, 0, true);
+            outcome(
+                setup
+                    .client
+                    .try_prove_claim(&SESSION_ID, &valid_proof(&setup.env), &inputs),
+            )
+        },
+    },
+    Case {
+        name: "prove_claim after its deadline",
+        error: Error::ProofDeadlinePassed,
+        calls: |setup| {
+            challenged(setup);
+            setup
+                .env
+                .ledger()
+                .with_mut(|l| l.sequence_number += CLAIM_CHALLENGE_LEDGERS + 1);
+            let inputs = setup.public_inputs(SESSION_ID, 0, 0, true);
+            outcome(
+                setup
+                    .client
+                    .try_prove_claim(&SESSION_ID, &valid_proof(&setup.env), &inputs),
+            )
+        },
+    },
+    Case {
+        name: "enable_optimistic after the first move",
+        error: Error::OptimisticLocked,
+        calls: |setup| {
+            started(setup);
+            answer(setup, 0, 0)?;
+            let token = Address::generate(&setup.env);
+            outcome(
+                setup
+                    .client
+                    .try_enable_optimistic(&SESSION_ID, &token, &CLAIM_BOND),
+            )
+        },
+    },
+    Case {
+        name: "submit_move_with_note with an over-long note",
+        error: Error::NoteTooLong,
+        calls: |setup| {
+            started(setup);
+            let note = Bytes::from_array(&setup.env, &[0; MAX_NOTE_BYTES as usize + 1]);
+            outcome(
+                setup
+                    .client
+                    .try_submit_move_with_note(&SESSION_ID, &0, &0, &note),
+            )
+        },
+    },
+    Case {
+        name: "appoint_arbiter naming a player",
+        error: Error::InvalidArbiter,
+        calls: |setup| {
+            let (defender, _) = setup.start_game(SESSION_ID);
+            outcome(setup.client.try_appoint_arbiter(&SESSION_ID, &defender))
+        },
+    },
+    Case {
+        name: "freeze_session by a player",
+        error: Error::NotArbiter,
+        calls: |setup| {
+            let (defender, _) = setup.start_game(SESSION_ID);
+            outcome(setup.client.try_freeze_session(&defender, &SESSION_ID))
+        },
+    },
+    Case {
+        name: "submit_move on a frozen game",
+        error: Error::GameFrozen,
+        calls: |setup| {
+            started(setup);
+            setup.client.freeze_session(&setup.admin, &SESSION_ID);
+            submit(setup, 0, 0)
+        },
+    },
+    Case {
+        name: "practice_move without a practice game",
+        error: Error::PracticeNotFound,
+        calls: |setup| {
+            let player = Address::generate(&setup.env);
+            outcome(setup.client.try_practice_move(&player, &0, &0))
+        },
+    },
+    Case {
+        name: "start_practice twice",
+        error: Error::PracticeInProgress,
+        calls: |setup| {
+            let player = Address::generate(&setup.env);
+            let commitment = BytesN::from_array(&setup.env, &[1; 32]);
+            setup.client.start_practice(&player, &commitment);
+            outcome(setup.client.try_start_practice(&player, &commitment))
+        },
+    },
+    Case {
+        name: "finish_practice revealing the commitment itself",
+        error: Error::RevealMismatch,
+        calls: |setup| {
+            let player = Address::generate(&setup.env);
+            let commitment = BytesN::from_array(&setup.env, &[1; 32]);
+            setup.client.start_practice(&player, &commitment);
+            outcome(setup.client.try_finish_practice(&player, &commitment))
+        },
+    },
+    Case {
+        name: "daily_move without a daily grid",
+        error: Error::DailyNotFound,
+        calls: |setup| {
+            let player = Address::generate(&setup.env);
+            outcome(setup.client.try_daily_move(&player, &0, &0))
+        },
+    },
+    Case {
+        name: "commit_daily twice",
+        error: Error::DailyExists,
+        calls: |setup| {
+            let grid_hash = BytesN::from_array(&setup.env, &[1; 32]);
+            setup.client.commit_daily(&setup.admin, &0, &grid_hash);
+            outcome(setup.client.try_commit_daily(&setup.admin, &0, &grid_hash))
+        },
+    },
+    Case {
+        name: "reveal_daily before the day closes",
+        error: Error::DayNotClosed,
+        calls: |setup| {
+            let (traps, salt) = (vec![&setup.env], BytesN::from_array(&setup.env, &[1; 32]));
+            setup.client.commit_daily(&setup.admin, &0, &salt);
+            outcome(
+                setup
+                    .client
+                    .try_reveal_daily(&setup.admin, &0, &traps, &salt),
+            )
+        },
+    },
+    Case {
+        name: "reveal_daily twice",
+        error: Error::DailyRevealed,
+        calls: |setup| {
+            let traps = vec![&setup.env, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9];
+            let salt = BytesN::from_array(&setup.env, &[1; 32]);
+            let grid_hash = daily::commitment(&setup.env, &traps, &salt);
+            setup.client.commit_daily(&setup.admin, &0, &grid_hash);
+            setup
+                .env
+                .ledger()
+                .with_mut(|ledger| ledger.timestamp += daily::DAY_SECONDS);
+            setup.client.reveal_daily(&setup.admin, &0, &traps, &salt);
+            outcome(
+                setup
+                    .client
+                    .try_reveal_daily(&setup.admin, &0, &traps, &salt),
+            )
+        },
+    },
+    Case {
+        name: "rank_daily before the reveal",
+        error: Error::DailyNotRevealed,
+        calls: |setup| {
+            let player = Address::generate(&setup.env);
+            let grid_hash = BytesN::from_array(&setup.env, &[1; 32]);
+            setup.client.commit_daily(&setup.admin, &0, &grid_hash);
+            outcome(setup.client.try_rank_daily(&0, &player))
+        },
+    },
+    Case {
+        name: "finalize_game of a game without an arbiter",
+        error: Error::NotArbitrated,
+        calls: |setup| {
+            ended(setup);
+            outcome(setup.client.try_finalize_game(&SESSION_ID))
+        },
+    },
+    Case {
+        name: "finalize_game in the appeal window",
+        error: Error::AppealWindowOpen,
+        calls: |setup| {
+            arbitrated(setup);
+            outcome(setup.client.try_finalize_game(&SESSION_ID))
+        },
+    },
+    Case {
+        name: "appeal after the appeal window",
+        error: Error::AppealWindowClosed,
+        calls: |setup| {
+            let (_, attacker) = arbitrated(setup);
+            let window = ContractConfig::DEFAULT.appeal_window_ledgers;
+            setup
+                .env
+                .ledger()
+                .with_mut(|l| l.sequence_number += window + 1);
+            outcome(setup.client.try_appeal(&SESSION_ID, &attacker))
+        },
+    },
+    Case {
+        name: "appeal of a result under appeal",
+        error: Error::UnderAppeal,
+        calls: |setup| {
+            let (defender, attacker) = arbitrated(setup);
+            setup.client.appeal(&SESSION_ID, &attacker);
+            outcome(setup.client.try_appeal(&SESSION_ID, &defender))
+        },
+    },
+    Case {
+        name: "resolve_appeal of an unappealed result",
+        error: Error::NotAppealed,
+        calls: |setup| {
+            arbitrated(setup);
+            outcome(
+                setup
+                    .client
+                    .try_resolve_appeal(&SESSION_ID, &Ruling::Confirm),
+            )
+        },
+    },
+    Case {
+        name: "appeal by someone else than the players",
+        error: Error::NotPlayer,
+        calls: |setup| {
+            arbitrated(setup);
+            let stranger = Address::generate(&setup.env);
+            outcome(setup.client.try_appeal(&SESSION_ID, &stranger))
+        },
+    },
+    Case {
+        name: "claim_winnings of a game without sponsorships",
+        error: Error::NothingToClaim,
+        calls: |setup| {
+            ended(setup);
+            outcome(setup.client.try_claim_winnings(&SESSION_ID))
+        },
+    },
+    Case {
+        name: "claim_winnings before the claim delay",
+        error: Error::ClaimNotReady,
+        calls: |setup| {
+            let env = &setup.env;
+            let config = ContractConfig {
+                claim_delay_ledgers: 20_000,
+                ..ContractConfig::DEFAULT
+            };
+            setup.client.set_config(&config);
+            let token = env
+                .register_stellar_asset_contract_v2(Address::generate(env))
+                .address();
+            let (defender, sponsor) = (Address::generate(env), Address::generate(env));
+            StellarAssetClient::new(env, &token).mint(&sponsor, &100);
+            let sponsorship = Sponsorship {
+                sponsor,
+                player: defender.clone(),
+                token,
+                amount: 100,
+                player_share_bps: 0,
+            };
+            setup.client.start_sponsored_game(
+                &SESSION_ID,
+                &defender,
+                &Address::generate(env),
+                &100,
+                &100,
+                &grid_setup(env),
+                &vec![env, sponsorship],
+            );
+            setup.client.end_game(&SESSION_ID);
+            outcome(setup.client.try_claim_winnings(&SESSION_ID))
+        },
+    },
+    Case {
+        name: "commit_grid after the first move",
+        error: Error::GridLocked,
+        calls: |setup| {
+            started(setup);
+            answer(setup, 0, 0)?;
+            let root = BytesN::from_array(&setup.env, &[1; 32]);
+            outcome(setup.client.try_commit_grid(&SESSION_ID, &root))
+        },
+    },
+    Case {
+        name: "commit_grid of a root the defender used before",
+        error: Error::RootReused,
+        calls: |setup| {
+            let (defender, attacker) = setup.start_game(SESSION_ID);
+            let root = BytesN::from_array(&setup.env, &[1; 32]);
+            setup.client.commit_grid(&SESSION_ID, &root);
+            setup.client.start_game(
+                &(SESSION_ID + 1),
+                &defender,
+                &attacker,
+                &100,
+                &100,
+                &grid_setup(&setup.env),
+            );
+            outcome(setup.client.try_commit_grid(&(SESSION_ID + 1), &root))
+        },
+    },
+];
+
+/// Why a variant cannot be returned, or `None` if [`CASES`] must reach it
+fn unreachable(error: Error) -> Option<&'static str> {
+    match error {
+        Error::GameNotFound
+        | Error::GameAlreadyEnded
+        | Error::InvalidMove
+        | Error::MoveAlreadyMade
+        | Error::InvalidProof
+        | Error::MovePending
+        | Error::InvalidSponsorship
+        | Error::ReferrerAlreadySet
+        | Error::SelfReferral
+        | Error::InvalidFees
+        | Error::WithdrawNotQueued
+        | Error::WithdrawTimelocked
+        | Error::NotAdminMember
+        | Error::ProposalNotFound
+        | Error::ProposalExpired
+        | Error::AlreadyApproved
+        | Error::InvalidThreshold
+        | Error::NotOperator
+        | Error::Paused
+        | Error::InvalidConfig
+        | Error::TooManyGames
+        | Error::StakeNotPositive
+        | Error::StakeOutOfBounds
+        | Error::TooFewGames
+        | Error::NotPlayer
+        | Error::InvalidArbiter
+        | Error::NotArbitrated
+        | Error::AppealWindowOpen
+        | Error::AppealWindowClosed
+        | Error::UnderAppeal
+        | Error::NotAppealed
+        | Error::NothingToClaim
+        | Error::ClaimNotReady
+        | Error::GridLocked
+        | Error::RootReused
+        | Error::InvalidSetup
+        | Error::SetupReused
+        | Error::MoveTooSoon
+        | Error::StaleChannelState
+        | Error::ChallengeWindowOpen
+        | Error::InvalidTranscript
+        | Error::NoChannelState
+        | Error::ChannelConflict
+        | Error::NotOptimistic
+        | Error::NoPendingMove
+        | Error::ClaimNotChallengeable
+        | Error::NoChallenge
+        | Error::ProofDeadlinePassed
+        | Error::OptimisticLocked
+        | Error::NoteTooLong
+        | Error::GameFrozen
+        | Error::NotArbiter
+        | Error::PracticeNotFound
+        | Error::PracticeInProgress
+        | Error::RevealMismatch
+        | Error::DailyNotFound
+        | Error::DailyExists
+        | Error::DayNotClosed
+        | Error::DailyRevealed
+        | Error::DailyNotRevealed => None,
+        Error::GameNotStarted => Some("start_game stores games already started"),
+        Error::NotDefender | Error::NotAttacker => {
+            Some("callers are checked with require_auth, which aborts instead")
+        }
+        Error::DefenderMustCommit => Some("committing the grid with commit_grid is optional"),
+        Error::AllMovesCompleted => Some("the last move ends the game: GameAlreadyEnded"),
+        Error::GameNotComplete => Some("end_game settles games at any point"),
+    }
+}
+
+/// Every variant, in code order
+fn variants() -> std::vec::Vec<Error> {
+    (1..)
+        .map_while(|code| Error::try_from(soroban_sdk::Error::from_contract_error(code)).ok())
+        .collect()
+}
+
+#[test]
+fn test_error_matrix() {
+    for case in CASES {
+        let env = Env::default();
+        let setup = TestSetup::new(&env);
+        assert_eq!((case.calls)(&setup), Err(case.error), "{}", case.name);
+    }
+}
+
+#[test]
+fn test_every_error_is_covered() {
+    let variants = variants();
+    assert_eq!(variants.len(), 66);
+    for error in variants {
+        let reached = CASES.iter().any(|case| case.error == error);
+        match unreachable(error) {
+            None => assert!(reached, "no case reaches {error:?}"),
+            Some(reason) => assert!(!reached, "{error:?} is reached, not: {reason}"),
+        }
+    }
+}
+
+#[test]
+fn test_calls_without_auth_abort() {
+    let env = Env::default();
+    let setup = TestSetup::new(&env);
+    let (defender, attacker) = setup.start_game(SESSION_ID);
+
+    // Only the attacker may pick the next cell
+    env.set_auths(&[]);
+    assert!(matches!(
+        setup.client.try_submit_move(&SESSION_ID, &2, &3),
+        Err(Err(_))
+    ));
+
+    // The defender must sign the session, their points and the grid commitment
+    let other = Address::generate(&env);
+    assert!(matches!(
+        setup
+            .client
+            .try_start_game(&2, &defender, &other, &100, &100, &grid_setup(&env)),
+        Err(Err(_))
+    ));
+    assert_eq!(setup.client.get_pending_move(&SESSION_ID), None);
+    assert_eq!(setup.client.try_get_game(&2), Err(Ok(Error::GameNotFound)));
+
+    // Self-play is rejected whoever signs
+    env.mock_all_auths();
+    assert!(matches!(
+        setup
+            .client
+            .try_start_game(&2, &attacker, &attacker, &100, &100, &grid_setup(&env)),
+        Err(Err(_))
+    ));
+}
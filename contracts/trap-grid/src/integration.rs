@@ -0,0 +1,1097 @@
+//! Games played through trap-grid, the mock Game Hub and a verifier
+//!
+//! Unlike the unit tests, the hub here is the `mock-game-hub` contract, which
+//! records each session, so every way a game can end is checked for the
+//! settlement the hub receives: the last move of the grid and `end_game`, won
+//! by either side. Winners are minted trophies by the `trophy` contract,
+//! finished games are recorded by the `archive` contract, and
+//! sponsored games pay out their escrowed stakes, less the protocol fee that
+//! referrers share, at once or when claimed after the claim delay, or partly
+//! move by move with progressive payouts, with a bonus from the pool for
+//! decisive wins and a fee rebate for winning streaks. Stuck games return the stakes by emergency withdrawal.
+//! Arbitrated games reach the hub only after their appeal window, or as the
+//! arbiter rules, and not while their arbiter or the admin freezes them.
+
+use archive::{ArchiveContract, ArchiveContractClient};
+use mock_game_hub::{MockGameHub, MockGameHubClient, SessionInfo};
+use soroban_sdk::testutils::{Address as _, Events as _, Ledger as _};
+use soroban_sdk::token::{StellarAssetClient, TokenClient};
+use soroban_sdk::xdr::ToXdr;
+use soroban_sdk::{vec, Address, Bytes, BytesN, Env, Event};
+use trophy::{TrophyContract, TrophyContractClient};
+
+use crate::testutils::{
+    grid_setup, moves, public_inputs, valid_proof, MockVerifier, TestMove, TestSetup,
+    DEFAULT_POINTS,
+};
+use crate::{replay, APPEAL_WINDOW_LEDGERS, CLAIM_CHALLENGE_LEDGERS, EMERGENCY_DELAY_LEDGERS};
+use crate::{
+    AdminAction, ContractConfig, Error, Game, PayoutCurve, Ruling, Sponsorship, Streak,
+    StreakBonus, StreakBroken,
+};
+
+fn setup(env: &Env) -> (TestSetup<'_>, MockGameHubClient<'_>) {
+    let game_hub = env.register(MockGameHub, ());
+    let verifier = env.register(MockVerifier, ());
+    let setup = TestSetup::with_contracts(env, game_hub.clone(), verifier);
+    (setup, MockGameHubClient::new(env, &game_hub))
+}
+
+/// The session the hub recorded must match the game trap-grid stored
+fn assert_settled(
+    setup: &TestSetup,
+    hub: &MockGameHubClient,
+    session_id: u32,
+) -> (Game, SessionInfo) {
+    let game = setup.client.get_game(&session_id);
+    let session = hub.get_session(&session_id).expect("session registered");
+    assert!(game.game_ended);
+    assert!(session.ended);
+    assert_eq!(session.game_contract, setup.contract);
+    assert_eq!(
+        (&session.player1, &session.player2),
+        (&game.defender, &game.attacker)
+    );
+    assert_eq!(
+        (session.player1_points, session.player2_points),
+        (DEFAULT_POINTS, DEFAULT_POINTS)
+    );
+    let winner = if session.player1_won {
+        &game.defender
+    } else {
+        &game.attacker
+    };
+    assert_eq!(game.winner.as_ref(), Some(winner));
+    (game, session)
+}
+
+#[test]
+fn test_last_move_defender_wins() {
+    let env = Env::default();
+    let (setup, hub) = setup(&env);
+    let (defender, _) = setup.start_game(1);
+
+    setup.play(1, moves(|x, y| x == y)).unwrap();
+    let (game, session) = assert_settled(&setup, &hub, 1);
+    assert_eq!((game.hits, game.misses), (8, 56));
+    assert!(session.player1_won);
+    assert_eq!(game.winner, Some(defender));
+}
+
+#[test]
+fn test_last_move_attacker_wins() {
+    let env = Env::default();
+    let (setup, hub) = setup(&env);
+    let (_, attacker) = setup.start_game(1);
+
+    // 40 traps: more than half the grid
+    setup.play(1, moves(|x, _| x < 5)).unwrap();
+    let (game, session) = assert_settled(&setup, &hub, 1);
+    assert_eq!((game.hits, game.misses), (40, 24));
+    assert!(!session.player1_won);
+    assert_eq!(game.winner, Some(attacker));
+}
+
+#[test]
+fn test_end_game_early() {
+    let env = Env::default();
+    let (setup, hub) = setup(&env);
+
+    // Ended before any move: the defender wins
+    let (defender, _) = setup.start_game(1);
+    assert!(!hub.get_session(&1).unwrap().ended);
+    setup.client.end_game(&1);
+    let (_, session) = assert_settled(&setup, &hub, 1);
+    assert!(session.player1_won);
+    assert_eq!(setup.client.get_game(&1).winner, Some(defender));
+
+    // Ended after three hits out of four: the attacker wins
+    let (_, attacker) = setup.start_game(2);
+    setup
+        .play(2, moves(|x, y| x == 0 && y < 3).take(4))
+        .unwrap();
+    setup.client.end_game(&2);
+    let (game, session) = assert_settled(&setup, &hub, 2);
+    assert_eq!((game.hits, game.misses), (3, 1));
+    assert!(!session.player1_won);
+    assert_eq!(game.winner, Some(attacker));
+
+    // Nothing changes once settled
+    assert_eq!(
+        setup.client.try_end_game(&2),
+        Err(Ok(Error::GameAlreadyEnded))
+    );
+    assert_eq!(
+        setup.try_make_move(
+            2,
+            &TestMove {
+                x: 7,
+                y: 7,
+                is_hit: false
+            }
+        ),
+        Err(Error::GameAlreadyEnded)
+    );
+    assert!(!hub.get_session(&2).unwrap().player1_won);
+}
+
+#[test]
+fn test_sessions_settle_independently() {
+    let env = Env::default();
+    let (setup, hub) = setup(&env);
+    setup.start_game(1);
+    setup.start_game(2);
+
+    // Interleaved moves; session 2 ends early while session 1 plays on
+    let mut first = moves(|_, _| true);
+    let mut second = moves(|_, _| false);
+    for _ in 0..5 {
+        setup.make_move(1, &first.next().unwrap());
+        setup.make_move(2, &second.next().unwrap());
+    }
+    setup.client.end_game(&2);
+    assert!(!hub.get_session(&1).unwrap().ended);
+    assert!(assert_settled(&setup, &hub, 2).1.player1_won);
+
+    setup.play(1, first).unwrap();
+    assert!(!assert_settled(&setup, &hub, 1).1.player1_won);
+    assert_eq!(setup.client.get_moves(&2).len(), 5);
+}
+
+#[test]
+fn test_unknown_session() {
+    let env = Env::default();
+    let (setup, hub) = setup(&env);
+    assert_eq!(setup.client.try_end_game(&9), Err(Ok(Error::GameNotFound)));
+    assert_eq!(hub.get_session(&9), None);
+}
+
+#[test]
+fn test_winners_get_trophies() {
+    let env = Env::default();
+    let (setup, _) = setup(&env);
+
+    // Without a trophy contract games settle as before
+    let (defender, attacker) = setup.start_game(1);
+    setup.client.end_game(&1);
+
+    let trophy = env.register(TrophyContract, (&Address::generate(&env),));
+    let trophies = TrophyContractClient::new(&env, &trophy);
+    setup.client.set_trophy(&trophy);
+
+    // A trophy contract that has not allowed trap-grid does not block settlement
+    setup.start_game(2);
+    setup.client.end_game(&2);
+    assert!(setup.client.get_game(&2).game_ended);
+    assert_eq!(trophies.trophy_count(), 0);
+
+    trophies.set_minter(&setup.contract, &true);
+    let (defender3, attacker3) = setup.start_game(3);
+    setup.play(3, moves(|x, _| x < 5)).unwrap();
+    let won = trophies.get_trophies(&attacker3);
+    assert_eq!(won.len(), 1);
+    let trophy = won.get(0).unwrap();
+    assert_eq!(
+        (trophy.session_id, &trophy.opponent, trophy.score),
+        (3, &defender3, 40)
+    );
+    assert_eq!(trophy.game, setup.contract);
+    assert_eq!(trophies.get_trophies(&defender3).len(), 0);
+    assert_eq!(trophies.get_trophies(&defender).len(), 0);
+    assert_eq!(trophies.get_trophies(&attacker).len(), 0);
+
+    // Ended early after one miss: the defender wins with one miss
+    let (defender4, attacker4) = setup.start_game(4);
+    setup.play(4, moves(|_, _| false).take(1)).unwrap();
+    setup.client.end_game(&4);
+    let trophy = trophies.get_trophies(&defender4).get(0).unwrap();
+    assert_eq!(
+        (trophy.session_id, trophy.opponent, trophy.score),
+        (4, attacker4, 1)
+    );
+}
+
+#[test]
+fn test_finished_games_are_archived() {
+    let env = Env::default();
+    let (setup, _) = setup(&env);
+    let archive = env.register(ArchiveContract, (&Address::generate(&env),));
+    let records = ArchiveContractClient::new(&env, &archive);
+    setup.client.set_archive(&archive);
+
+    // An archive that has not allowed trap-grid does not hold up the game
+    setup.start_game(1);
+    setup.client.end_game(&1);
+    assert!(setup.client.get_game(&1).game_ended);
+    assert_eq!(records.record_count(), 0);
+
+    records.set_writer(&setup.contract, &true);
+    let (defender, attacker) = setup.start_game(2);
+    setup.play(2, moves(|_, y| y == 1).take(3)).unwrap();
+    setup.client.end_game(&2);
+    let archived = records.get_session(&setup.contract, &2);
+    let record = archived.record;
+    assert_eq!((&record.defender, &record.attacker), (&defender, &attacker));
+    assert_eq!(record.winner, Some(defender.clone()));
+    assert_eq!((record.hits, record.misses), (1, 2));
+    let config_hash = env.crypto().sha256(&setup.client.get_config().to_xdr(&env));
+    assert_eq!(record.config_hash, config_hash.to_bytes());
+    let mut replay = Bytes::new(&env);
+    for m in setup.client.get_moves(&2).iter() {
+        replay.push_back(m.x as u8 | if m.is_hit { 0x80 } else { 0 });
+        replay.push_back(m.y as u8);
+    }
+    assert_eq!(record.replay, replay);
+    let played = setup
+        .client
+        .get_moves(&2)
+        .iter()
+        .map(|m| (m.x, m.y, m.is_hit));
+    assert!(replay::decode(&env, &record.replay)
+        .unwrap()
+        .iter()
+        .eq(played));
+    assert_eq!(records.get_records(&defender).len(), 1);
+
+    // Games that end without a winner are archived too
+    setup.start_game(3);
+    let ready = setup.client.queue_emergency_withdraw(&3);
+    env.ledger().with_mut(|l| l.sequence_number = ready);
+    setup.client.emergency_withdraw(&3);
+    assert_eq!(records.get_session(&setup.contract, &3).record.winner, None);
+    assert_eq!(records.record_count(), 2);
+}
+
+#[test]
+fn test_sponsored_stakes_settle() {
+    let env = Env::default();
+    let (setup, _) = setup(&env);
+    let issuer = Address::generate(&env);
+    let token = env.register_stellar_asset_contract_v2(issuer).address();
+    let balance = |address: &Address| TokenClient::new(&env, &token).balance(address);
+    let defender = Address::generate(&env);
+    let attacker = Address::generate(&env);
+    let sponsor = |player: &Address, amount: i128, player_share_bps: u32| {
+        let sponsor = Address::generate(&env);
+        StellarAssetClient::new(&env, &token).mint(&sponsor, &1_000);
+        Sponsorship {
+            sponsor,
+            player: player.clone(),
+            token: token.clone(),
+            amount,
+            player_share_bps,
+        }
+    };
+    let try_start = |session_id: u32, sponsorships: &soroban_sdk::Vec<Sponsorship>| {
+        setup.client.try_start_sponsored_game(
+            &session_id,
+            &defender,
+            &attacker,
+            &DEFAULT_POINTS,
+            &DEFAULT_POINTS,
+            &grid_setup(&env),
+            sponsorships,
+        )
+    };
+
+    // Both sides sponsored; the attacker's side wins the defender's 300
+    let backs_defender = sponsor(&defender, 300, 0);
+    let backs_attacker = sponsor(&attacker, 200, 2_500);
+    let sponsorships = vec![&env, backs_defender.clone(), backs_attacker.clone()];
+    try_start(1, &sponsorships).unwrap().unwrap();
+    assert_eq!(balance(&setup.contract), 500);
+    assert_eq!(setup.client.get_sponsorships(&1), sponsorships);
+    setup.play(1, moves(|x, _| x < 5)).unwrap();
+    assert_eq!(balance(&attacker), 75);
+    assert_eq!(balance(&backs_attacker.sponsor), 800 + 200 + 225);
+    assert_eq!(balance(&backs_defender.sponsor), 700);
+    assert_eq!(balance(&setup.contract), 0);
+    assert_eq!(setup.client.get_sponsorships(&1).len(), 0);
+
+    // Only the attacker sponsored, and the unsponsored defender wins it all
+    try_start(2, &vec![&env, sponsor(&attacker, 100, 5_000)])
+        .unwrap()
+        .unwrap();
+    setup.client.end_game(&2);
+    assert_eq!(balance(&defender), 100);
+    assert_eq!(balance(&setup.contract), 0);
+
+    // No sponsor, a stranger backed, a player backed twice, or two tokens
+    let other_token = Sponsorship {
+        token: Address::generate(&env),
+        ..sponsor(&attacker, 100, 0)
+    };
+    for sponsorships in [
+        vec![&env],
+        vec![&env, sponsor(&Address::generate(&env), 100, 0)],
+        vec![&env, sponsor(&defender, 100, 0), sponsor(&defender, 100, 0)],
+        vec![&env, sponsor(&defender, 100, 0), other_token],
+    ] {
+        assert_eq!(
+            try_start(3, &sponsorships),
+            Err(Ok(Error::InvalidSponsorship))
+        );
+    }
+}
+
+#[test]
+fn test_progressive_payouts_release_per_proven_move() {
+    let env = Env::default();
+    let (setup, _) = setup(&env);
+    let client = &setup.client;
+    let issuer = Address::generate(&env);
+    let token = env.register_stellar_asset_contract_v2(issuer).address();
+    let balance = |address: &Address| TokenClient::new(&env, &token).balance(address);
+    let defender = Address::generate(&env);
+    let attacker = Address::generate(&env);
+    client.set_config(&ContractConfig {
+        progressive_payout_bps: 1_000,
+        ..ContractConfig::DEFAULT
+    });
+    let sponsor = |player: &Address| {
+        let sponsor = Address::generate(&env);
+        StellarAssetClient::new(&env, &token).mint(&sponsor, &1_000);
+        Sponsorship {
+            sponsor,
+            player: player.clone(),
+            token: token.clone(),
+            amount: 1_000,
+            player_share_bps: 0,
+        }
+    };
+    let start = |session_id: u32, backs_defender: &Sponsorship, backs_attacker: &Sponsorship| {
+        client.start_sponsored_game(
+            &session_id,
+            &defender,
+            &attacker,
+            &DEFAULT_POINTS,
+            &DEFAULT_POINTS,
+            &grid_setup(&env),
+            &vec![&env, backs_defender.clone(), backs_attacker.clone()],
+        );
+    };
+    let remaining = |session_id: u32| {
+        let sponsorships = client.get_sponsorships(&session_id);
+        let amount = |player: &Address| {
+            sponsorships
+                .iter()
+                .find(|s| s.player == *player)
+                .map_or(0, |s| s.amount)
+        };
+        (amount(&defender), amount(&attacker))
+    };
+    let play = |session_id: u32, x: u32, is_hit: bool| {
+        setup.make_move(session_id, &TestMove { x, y: 0, is_hit });
+    };
+
+    // Each hit pays the attacker a tenth of what is left of the defender's
+    // side, each miss the defender a tenth of the attacker's
+    let (backs_defender, backs_attacker) = (sponsor(&defender), sponsor(&attacker));
+    start(1, &backs_defender, &backs_attacker);
+    play(1, 0, true);
+    play(1, 1, false);
+    play(1, 2, true);
+    assert_eq!(balance(&attacker), 100 + 90);
+    assert_eq!(balance(&defender), 100);
+    assert_eq!(remaining(1), (810, 900));
+
+    // The attacker wins what is left of the defender's side at settlement
+    client.end_game(&1);
+    assert_eq!(balance(&backs_attacker.sponsor), 900 + 810);
+    assert_eq!(balance(&backs_defender.sponsor), 0);
+    assert_eq!(balance(&setup.contract), 0);
+
+    // An aborted game returns only what was not released
+    let (backs_defender, backs_attacker) = (sponsor(&defender), sponsor(&attacker));
+    start(2, &backs_defender, &backs_attacker);
+    play(2, 0, false);
+    assert!(!client.abort_due_to_verifier(&2, &defender));
+    assert!(client.abort_due_to_verifier(&2, &attacker));
+    assert_eq!(balance(&defender), 100 + 100);
+    assert_eq!(balance(&backs_defender.sponsor), 1_000);
+    assert_eq!(balance(&backs_attacker.sponsor), 900);
+    assert_eq!(balance(&setup.contract), 0);
+}
+
+#[test]
+fn test_progressive_payouts_need_verified_moves() {
+    let env = Env::default();
+    let (setup, _) = setup(&env);
+    let client = &setup.client;
+    let issuer = Address::generate(&env);
+    let token = env.register_stellar_asset_contract_v2(issuer).address();
+    let balance = |address: &Address| TokenClient::new(&env, &token).balance(address);
+    let defender = Address::generate(&env);
+    let attacker = Address::generate(&env);
+    client.set_config(&ContractConfig {
+        progressive_payout_bps: 1_000,
+        ..ContractConfig::DEFAULT
+    });
+    let mut sponsorships = vec![&env];
+    for player in [&defender, &attacker] {
+        let sponsor = Address::generate(&env);
+        StellarAssetClient::new(&env, &token).mint(&sponsor, &1_000);
+        sponsorships.push_back(Sponsorship {
+            sponsor,
+            player: player.clone(),
+            token: token.clone(),
+            amount: 1_000,
+            player_share_bps: 0,
+        });
+    }
+    client.start_sponsored_game(
+        &1,
+        &defender,
+        &attacker,
+        &DEFAULT_POINTS,
+        &DEFAULT_POINTS,
+        &grid_setup(&env),
+        &sponsorships,
+    );
+    setup.enable_optimistic(1);
+    let escrowed = || {
+        client
+            .get_sponsorships(&1)
+            .iter()
+            .map(|s| s.amount)
+            .sum::<i128>()
+    };
+
+    // A hit proven against another grid releases nothing
+    client.submit_move(&1, &0, &0);
+    let forged = public_inputs(&env, &grid_setup(&env).grid_commitment, 0, 0, true);
+    assert_eq!(
+        client.try_make_move(&1, &0, &0, &true, &valid_proof(&env), &forged),
+        Err(Ok(Error::InvalidProof))
+    );
+
+    // Nor does a claim until it is proven
+    client.claim_move(&1, &true);
+    assert_eq!((balance(&attacker), escrowed()), (0, 2_000));
+    client.challenge_claim(&1, &0);
+    client.prove_claim(&1, &valid_proof(&env), &setup.public_inputs(1, 0, 0, true));
+    assert_eq!((balance(&attacker), escrowed()), (100, 1_900));
+
+    // Nor does an answer the defender did not authorize
+    client.submit_move(&1, &1, &0);
+    env.set_auths(&[]);
+    let inputs = setup.public_inputs(1, 1, 0, true);
+    assert!(client
+        .try_make_move(&1, &1, &0, &true, &valid_proof(&env), &inputs)
+        .is_err());
+    assert_eq!((balance(&attacker), escrowed()), (100, 1_900));
+}
+
+#[test]
+fn test_decisive_wins_earn_pool_bonus() {
+    let env = Env::default();
+    let (setup, _) = setup(&env);
+    let client = &setup.client;
+    let issuer = Address::generate(&env);
+    let token = env.register_stellar_asset_contract_v2(issuer).address();
+    let balance = |address: &Address| TokenClient::new(&env, &token).balance(address);
+    let defender = Address::generate(&env);
+    let attacker = Address::generate(&env);
+    let funder = Address::generate(&env);
+    StellarAssetClient::new(&env, &token).mint(&funder, &300);
+    client.set_config(&ContractConfig {
+        bonus_curve: PayoutCurve {
+            max_bonus_bps: 5_000,
+            threshold_bps: 0,
+            exponent: 1,
+        },
+        ..ContractConfig::DEFAULT
+    });
+    assert_eq!(client.fund_bonus_pool(&funder, &token, &300), 300);
+    assert_eq!(
+        client.try_fund_bonus_pool(&funder, &token, &0),
+        Err(Ok(Error::StakeNotPositive))
+    );
+    let sponsor = |player: &Address| {
+        let sponsor = Address::generate(&env);
+        StellarAssetClient::new(&env, &token).mint(&sponsor, &1_000);
+        Sponsorship {
+            sponsor,
+            player: player.clone(),
+            token: token.clone(),
+            amount: 1_000,
+            player_share_bps: 0,
+        }
+    };
+    let (backs_defender, backs_attacker) = (sponsor(&defender), sponsor(&attacker));
+    client.start_sponsored_game(
+        &1,
+        &defender,
+        &attacker,
+        &DEFAULT_POINTS,
+        &DEFAULT_POINTS,
+        &grid_setup(&env),
+        &vec![&env, backs_defender.clone(), backs_attacker.clone()],
+    );
+
+    // Hits on every move are the largest margin, worth half the winnings,
+    // but the pool only holds 300 of those 500
+    for x in 0..2 {
+        setup.make_move(
+            1,
+            &TestMove {
+                x,
+                y: 0,
+                is_hit: true,
+            },
+        );
+    }
+    client.end_game(&1);
+    assert_eq!(balance(&backs_attacker.sponsor), 1_000 + 1_000 + 300);
+    assert_eq!(client.get_bonus_pool(&token), 0);
+    assert_eq!(balance(&setup.contract), 0);
+}
+
+#[test]
+fn test_unproven_claims_earn_no_bonus() {
+    let env = Env::default();
+    let (setup, _) = setup(&env);
+    let client = &setup.client;
+    let issuer = Address::generate(&env);
+    let token = env.register_stellar_asset_contract_v2(issuer).address();
+    let balance = |address: &Address| TokenClient::new(&env, &token).balance(address);
+    let defender = Address::generate(&env);
+    let attacker = Address::generate(&env);
+    let funder = Address::generate(&env);
+    StellarAssetClient::new(&env, &token).mint(&funder, &300);
+    client.set_config(&ContractConfig {
+        bonus_curve: PayoutCurve {
+            max_bonus_bps: 5_000,
+            threshold_bps: 0,
+            exponent: 1,
+        },
+        ..ContractConfig::DEFAULT
+    });
+    client.fund_bonus_pool(&funder, &token, &300);
+    let mut sponsorships = vec![&env];
+    for player in [&defender, &attacker] {
+        let sponsor = Address::generate(&env);
+        StellarAssetClient::new(&env, &token).mint(&sponsor, &1_000);
+        sponsorships.push_back(Sponsorship {
+            sponsor,
+            player: player.clone(),
+            token: token.clone(),
+            amount: 1_000,
+            player_share_bps: 0,
+        });
+    }
+    client.start_sponsored_game(
+        &1,
+        &defender,
+        &attacker,
+        &DEFAULT_POINTS,
+        &DEFAULT_POINTS,
+        &grid_setup(&env),
+        &sponsorships,
+    );
+    setup.enable_optimistic(1);
+
+    // Unchallenged claims decide the game between its players, but the pool
+    // only pays for a margin the proofs show: here none
+    for x in 0..2 {
+        client.submit_move(&1, &x, &0);
+        client.claim_move(&1, &true);
+    }
+    env.ledger()
+        .with_mut(|l| l.sequence_number += CLAIM_CHALLENGE_LEDGERS + 1);
+    client.end_game(&1);
+    let attacker_sponsor = sponsorships.get(1).unwrap().sponsor;
+    assert_eq!(client.get_game(&1).winner, Some(attacker));
+    assert_eq!(balance(&attacker_sponsor), 1_000 + 1_000);
+    assert_eq!(client.get_bonus_pool(&token), 300);
+}
+
+#[test]
+fn test_win_streaks_earn_fee_rebates() {
+    let env = Env::default();
+    let (setup, _) = setup(&env);
+    let client = &setup.client;
+    let issuer = Address::generate(&env);
+    let token = env.register_stellar_asset_contract_v2(issuer).address();
+    let balance = |address: &Address| TokenClient::new(&env, &token).balance(address);
+    let defender = Address::generate(&env);
+    let attacker = Address::generate(&env);
+    client.set_config(&ContractConfig {
+        streak_bonus: StreakBonus {
+            min_streak: 2,
+            fee_rebate_bps: 5_000,
+        },
+        ..ContractConfig::DEFAULT
+    });
+    client.propose(&setup.admin, &AdminAction::SetFees(1_000, 0));
+    let sponsor = |player: &Address| {
+        let sponsor = Address::generate(&env);
+        StellarAssetClient::new(&env, &token).mint(&sponsor, &400);
+        Sponsorship {
+            sponsor,
+            player: player.clone(),
+            token: token.clone(),
+            amount: 400,
+            player_share_bps: 0,
+        }
+    };
+
+    // Ended before any move, twice: the defender wins both
+    client.start_game(
+        &1,
+        &defender,
+        &attacker,
+        &DEFAULT_POINTS,
+        &DEFAULT_POINTS,
+        &grid_setup(&env),
+    );
+    client.end_game(&1);
+    let backs_defender = sponsor(&defender);
+    client.start_sponsored_game(
+        &2,
+        &defender,
+        &attacker,
+        &DEFAULT_POINTS,
+        &DEFAULT_POINTS,
+        &grid_setup(&env),
+        &vec![&env, backs_defender.clone(), sponsor(&attacker)],
+    );
+    client.end_game(&2);
+
+    // The second win is a streak of two: half the fee of 40 is waived
+    assert_eq!(
+        client.get_streak(&defender),
+        Streak {
+            current: 2,
+            best: 2
+        }
+    );
+    assert_eq!(balance(&setup.admin), 20);
+    assert_eq!(balance(&backs_defender.sponsor), 400 + 380);
+
+    // The attacker's first win ends the defender's streak
+    client.start_game(
+        &3,
+        &defender,
+        &attacker,
+        &DEFAULT_POINTS,
+        &DEFAULT_POINTS,
+        &grid_setup(&env),
+    );
+    setup
+        .play(3, moves(|x, y| x == 0 && y < 3).take(4))
+        .unwrap();
+    client.end_game(&3);
+    let broken = StreakBroken {
+        session_id: 3,
+        defender: defender.clone(),
+        attacker: attacker.clone(),
+        player: defender.clone(),
+        streak: 2,
+    };
+    assert!(env
+        .events()
+        .all()
+        .filter_by_contract(&setup.contract)
+        .events()
+        .contains(&broken.to_xdr(&env, &setup.contract)));
+    assert_eq!(
+        client.get_streak(&defender),
+        Streak {
+            current: 0,
+            best: 2
+        }
+    );
+    assert_eq!(
+        client.get_streak(&attacker),
+        Streak {
+            current: 1,
+            best: 1
+        }
+    );
+}
+
+#[test]
+fn test_min_games_completed_gates_new_players() {
+    let env = Env::default();
+    let (setup, _) = setup(&env);
+    let client = &setup.client;
+    let defender = Address::generate(&env);
+    let attacker = Address::generate(&env);
+    let newcomer = Address::generate(&env);
+    let start = |session_id: u32, defender: &Address, attacker: &Address| {
+        client.try_start_game(
+            &session_id,
+            defender,
+            attacker,
+            &DEFAULT_POINTS,
+            &DEFAULT_POINTS,
+            &grid_setup(&env),
+        )
+    };
+
+    // Without a minimum anyone plays, and each completed game counts
+    start(1, &defender, &attacker).unwrap().unwrap();
+    client.end_game(&1);
+    assert_eq!(client.get_games_completed(&defender), 1);
+    assert_eq!(client.get_games_completed(&attacker), 1);
+    assert_eq!(client.get_games_completed(&newcomer), 0);
+
+    client.set_config(&ContractConfig {
+        min_games_completed: 1,
+        ..ContractConfig::DEFAULT
+    });
+    assert_eq!(start(2, &newcomer, &attacker), Err(Ok(Error::TooFewGames)));
+    assert_eq!(start(2, &defender, &newcomer), Err(Ok(Error::TooFewGames)));
+    start(2, &defender, &attacker).unwrap().unwrap();
+    assert_eq!(client.get_rules(&2).min_games_completed, 1);
+    client.end_game(&2);
+    assert_eq!(client.get_games_completed(&defender), 2);
+
+    // Practice games cost nothing to replay, so clearing one does not count
+    let secret = BytesN::from_array(&env, &[9; 32]);
+    let commitment: BytesN<32> = env.crypto().sha256(&secret.clone().into()).into();
+    client.start_practice(&newcomer, &commitment);
+    let grid_size = client.get_config().grid_size;
+    let cells = (0..grid_size).flat_map(|x| (0..grid_size).map(move |y| (x, y)));
+    for (x, y) in cells {
+        client.practice_move(&newcomer, &x, &y);
+        let practice = client.get_practice(&newcomer).unwrap();
+        if practice.found.len() == practice.trap_count {
+            break;
+        }
+    }
+    assert_eq!(client.finish_practice(&newcomer, &secret).cleared, 1);
+    assert_eq!(start(3, &newcomer, &attacker), Err(Ok(Error::TooFewGames)));
+}
+
+#[test]
+fn test_referral_rewards_accrue() {
+    let env = Env::default();
+    let (setup, _) = setup(&env);
+    let client = &setup.client;
+    let issuer = Address::generate(&env);
+    let token = env.register_stellar_asset_contract_v2(issuer).address();
+    let balance = |address: &Address| TokenClient::new(&env, &token).balance(address);
+    let defender = Address::generate(&env);
+    let attacker = Address::generate(&env);
+    let referrer = Address::generate(&env);
+
+    client.register_referrer(&attacker, &referrer);
+    assert_eq!(client.get_referrer(&attacker), Some(referrer.clone()));
+    assert_eq!(client.get_referrer(&defender), None);
+    assert_eq!(
+        client.try_register_referrer(&attacker, &defender),
+        Err(Ok(Error::ReferrerAlreadySet))
+    );
+    assert_eq!(
+        client.try_register_referrer(&referrer, &attacker),
+        Err(Ok(Error::SelfReferral))
+    );
+    assert_eq!(
+        client.try_register_referrer(&defender, &defender),
+        Err(Ok(Error::SelfReferral))
+    );
+
+    // 10% of the winnings is the fee, a quarter of it for each referrer
+    client.propose(&setup.admin, &AdminAction::SetFees(1_000, 2_500));
+    let sponsor = |player: &Address, amount: i128, player_share_bps: u32| {
+        let sponsor = Address::generate(&env);
+        StellarAssetClient::new(&env, &token).mint(&sponsor, &1_000);
+        Sponsorship {
+            sponsor,
+            player: player.clone(),
+            token: token.clone(),
+            amount,
+            player_share_bps,
+        }
+    };
+    let backs_attacker = sponsor(&attacker, 200, 5_000);
+    client.start_sponsored_game(
+        &1,
+        &defender,
+        &attacker,
+        &DEFAULT_POINTS,
+        &DEFAULT_POINTS,
+        &grid_setup(&env),
+        &vec![&env, sponsor(&defender, 400, 0), backs_attacker.clone()],
+    );
+    setup.play(1, moves(|x, _| x < 5)).unwrap();
+
+    // Fee of 40 on the defender's 400: 10 for the attacker's referrer, the
+    // rest to the admin; the remaining 360 split between attacker and sponsor
+    assert_eq!(balance(&setup.admin), 30);
+    assert_eq!(balance(&attacker), 180);
+    assert_eq!(balance(&backs_attacker.sponsor), 800 + 200 + 180);
+    assert_eq!(client.get_referral_rewards(&referrer, &token), 10);
+    assert_eq!(balance(&setup.contract), 10);
+
+    assert_eq!(client.claim_referral_rewards(&referrer, &token), 10);
+    assert_eq!(balance(&referrer), 10);
+    assert_eq!(client.get_referral_rewards(&referrer, &token), 0);
+    assert_eq!(client.claim_referral_rewards(&referrer, &token), 0);
+    assert_eq!(balance(&setup.contract), 0);
+}
+
+#[test]
+fn test_emergency_withdraw_returns_stakes() {
+    let env = Env::default();
+    let (setup, _) = setup(&env);
+    let client = &setup.client;
+    let issuer = Address::generate(&env);
+    let token = env.register_stellar_asset_contract_v2(issuer).address();
+    let balance = |address: &Address| TokenClient::new(&env, &token).balance(address);
+    let defender = Address::generate(&env);
+    let attacker = Address::generate(&env);
+    let sponsor = Address::generate(&env);
+    StellarAssetClient::new(&env, &token).mint(&sponsor, &1_000);
+    client.start_sponsored_game(
+        &1,
+        &defender,
+        &attacker,
+        &DEFAULT_POINTS,
+        &DEFAULT_POINTS,
+        &grid_setup(&env),
+        &vec![
+            &env,
+            Sponsorship {
+                sponsor: sponsor.clone(),
+                player: attacker.clone(),
+                token: token.clone(),
+                amount: 400,
+                player_share_bps: 0,
+            },
+        ],
+    );
+    let advance = |ledgers: u32| env.ledger().with_mut(|l| l.sequence_number += ledgers);
+
+    // A move during the delay cancels the withdrawal
+    let ready = client.queue_emergency_withdraw(&1);
+    assert_eq!(ready, env.ledger().sequence() + EMERGENCY_DELAY_LEDGERS);
+    assert_eq!(client.get_emergency_withdraw(&1), Some(ready));
+    advance(EMERGENCY_DELAY_LEDGERS - 1);
+    assert_eq!(
+        client.try_emergency_withdraw(&1),
+        Err(Ok(Error::WithdrawTimelocked))
+    );
+    client.submit_move(&1, &2, &3);
+    assert_eq!(client.get_emergency_withdraw(&1), None);
+    advance(1);
+    assert_eq!(
+        client.try_emergency_withdraw(&1),
+        Err(Ok(Error::WithdrawNotQueued))
+    );
+
+    // Left alone for the whole delay, the stakes go back to the sponsor
+    client.queue_emergency_withdraw(&1);
+    advance(EMERGENCY_DELAY_LEDGERS);
+    client.emergency_withdraw(&1);
+    assert_eq!(balance(&sponsor), 1_000);
+    assert_eq!(balance(&setup.contract), 0);
+    let game = client.get_game(&1);
+    assert!(game.game_ended);
+    assert_eq!(game.winner, None);
+    assert_eq!(client.get_pending_move(&1), None);
+    assert_eq!(client.try_end_game(&1), Err(Ok(Error::GameAlreadyEnded)));
+    assert_eq!(
+        client.try_queue_emergency_withdraw(&1),
+        Err(Ok(Error::GameAlreadyEnded))
+    );
+}
+
+#[test]
+fn test_arbitrated_results_settle_after_appeal() {
+    let env = Env::default();
+    let (setup, hub) = setup(&env);
+    let client = &setup.client;
+    let arbiter = Address::generate(&env);
+    let advance = |ledgers: u32| env.ledger().with_mut(|l| l.sequence_number += ledgers);
+    // The mock hub's sessions must outlive the appeal window
+    env.ledger()
+        .with_mut(|l| l.min_temp_entry_ttl = 2 * APPEAL_WINDOW_LEDGERS);
+
+    // Unappealed, the result reaches the hub once the window closes
+    let (defender, _) = setup.start_game(1);
+    client.appoint_arbiter(&1, &arbiter);
+    client.end_game(&1);
+    assert!(!hub.get_session(&1).unwrap().ended);
+    advance(APPEAL_WINDOW_LEDGERS);
+    assert_eq!(
+        client.try_finalize_game(&1),
+        Err(Ok(Error::AppealWindowOpen))
+    );
+    advance(1);
+    client.finalize_game(&1);
+    let (game, _) = assert_settled(&setup, &hub, 1);
+    assert_eq!(game.winner, Some(defender));
+    assert_eq!(client.get_arbitration(&1), None);
+
+    // An appeal freezes the result until the arbiter overturns it
+    let (_, attacker) = setup.start_game(2);
+    client.appoint_arbiter(&2, &arbiter);
+    client.end_game(&2);
+    client.appeal(&2, &attacker);
+    advance(APPEAL_WINDOW_LEDGERS + 1);
+    assert_eq!(client.try_finalize_game(&2), Err(Ok(Error::UnderAppeal)));
+    assert!(!hub.get_session(&2).unwrap().ended);
+    client.resolve_appeal(&2, &Ruling::Overturn);
+    let (game, session) = assert_settled(&setup, &hub, 2);
+    assert!(!session.player1_won);
+    assert_eq!(game.winner, Some(attacker));
+}
+
+#[test]
+fn test_frozen_session_holds_one_game() {
+    let env = Env::default();
+    let (setup, hub) = setup(&env);
+    let client = &setup.client;
+    let arbiter = Address::generate(&env);
+    env.ledger()
+        .with_mut(|l| l.min_temp_entry_ttl = 2 * APPEAL_WINDOW_LEDGERS);
+    let (defender, attacker) = setup.start_game(1);
+    setup.start_game(2);
+    client.appoint_arbiter(&1, &arbiter);
+
+    assert_eq!(
+        client.try_freeze_session(&attacker, &1),
+        Err(Ok(Error::NotArbiter))
+    );
+    assert_eq!(
+        client.try_freeze_session(&arbiter, &2),
+        Err(Ok(Error::NotArbiter))
+    );
+    client.freeze_session(&arbiter, &1);
+    assert_eq!(client.get_frozen(&1), Some(arbiter.clone()));
+
+    // Only the frozen game is held
+    assert_eq!(
+        client.try_submit_move(&1, &0, &0),
+        Err(Ok(Error::GameFrozen))
+    );
+    assert_eq!(client.try_end_game(&1), Err(Ok(Error::GameFrozen)));
+    client.submit_move(&2, &0, &0);
+    client.end_game(&2);
+    assert_settled(&setup, &hub, 2);
+
+    // The admin can lift a freeze too, and a submitted move waits it out
+    client.unfreeze_session(&setup.admin, &1);
+    assert_eq!(client.get_frozen(&1), None);
+    client.submit_move(&1, &0, &0);
+    client.freeze_session(&setup.admin, &1);
+    assert_eq!(
+        client.try_simulate_move(&1, &0, &0),
+        Err(Ok(Error::GameFrozen))
+    );
+    client.unfreeze_session(&arbiter, &1);
+    client.end_game(&1);
+    assert!(!hub.get_session(&1).unwrap().ended);
+    client.freeze_session(&setup.admin, &1);
+    env.ledger()
+        .with_mut(|l| l.sequence_number += APPEAL_WINDOW_LEDGERS + 1);
+    assert_eq!(client.try_finalize_game(&1), Err(Ok(Error::GameFrozen)));
+    client.unfreeze_session(&arbiter, &1);
+    client.finalize_game(&1);
+    let (game, _) = assert_settled(&setup, &hub, 1);
+    assert_eq!(game.winner, Some(defender));
+}
+
+#[test]
+fn test_voided_appeal_returns_stakes() {
+    let env = Env::default();
+    let (setup, hub) = setup(&env);
+    let client = &setup.client;
+    let issuer = Address::generate(&env);
+    let token = env.register_stellar_asset_contract_v2(issuer).address();
+    let balance = |address: &Address| TokenClient::new(&env, &token).balance(address);
+    let defender = Address::generate(&env);
+    let attacker = Address::generate(&env);
+    let arbiter = Address::generate(&env);
+    let sponsor = Address::generate(&env);
+    StellarAssetClient::new(&env, &token).mint(&sponsor, &1_000);
+    client.start_sponsored_game(
+        &1,
+        &defender,
+        &attacker,
+        &DEFAULT_POINTS,
+        &DEFAULT_POINTS,
+        &grid_setup(&env),
+        &vec![
+            &env,
+            Sponsorship {
+                sponsor: sponsor.clone(),
+                player: defender.clone(),
+                token: token.clone(),
+                amount: 400,
+                player_share_bps: 0,
+            },
+        ],
+    );
+    client.appoint_arbiter(&1, &arbiter);
+
+    client.end_game(&1);
+    client.appeal(&1, &attacker);
+    client.resolve_appeal(&1, &Ruling::Void);
+    assert_eq!(balance(&sponsor), 1_000);
+    assert_eq!(balance(&setup.contract), 0);
+    assert_eq!(client.get_game(&1).winner, None);
+    assert!(!hub.get_session(&1).unwrap().ended);
+    assert_eq!(client.try_finalize_game(&1), Err(Ok(Error::NotArbitrated)));
+}
+
+#[test]
+fn test_winnings_claimed_after_delay() {
+    let env = Env::default();
+    let (setup, hub) = setup(&env);
+    let client = &setup.client;
+    let issuer = Address::generate(&env);
+    let token = env.register_stellar_asset_contract_v2(issuer).address();
+    let balance = |address: &Address| TokenClient::new(&env, &token).balance(address);
+    let defender = Address::generate(&env);
+    let attacker = Address::generate(&env);
+    let sponsor = Address::generate(&env);
+    StellarAssetClient::new(&env, &token).mint(&sponsor, &1_000);
+    client.set_config(&ContractConfig {
+        claim_delay_ledgers: 20_000,
+        ..ContractConfig::DEFAULT
+    });
+    client.start_sponsored_game(
+        &1,
+        &defender,
+        &attacker,
+        &DEFAULT_POINTS,
+        &DEFAULT_POINTS,
+        &grid_setup(&env),
+        &vec![
+            &env,
+            Sponsorship {
+                sponsor: sponsor.clone(),
+                player: attacker.clone(),
+                token: token.clone(),
+                amount: 400,
+                player_share_bps: 0,
+            },
+        ],
+    );
+
+    // The result settles with the hub, but the stakes stay escrowed
+    client.end_game(&1);
+    assert!(hub.get_session(&1).unwrap().ended);
+    let claimable_from = env.ledger().sequence() + 20_000;
+    assert_eq!(client.get_claim(&1), Some(claimable_from));
+    assert_eq!(balance(&setup.contract), 400);
+    env.ledger()
+        .with_mut(|l| l.sequence_number = claimable_from - 1);
+    assert_eq!(client.try_claim_winnings(&1), Err(Ok(Error::ClaimNotReady)));
+
+    // After the delay the winner pulls the losing side's stake
+    env.ledger()
+        .with_mut(|l| l.sequence_number = claimable_from);
+    client.claim_winnings(&1);
+    assert_eq!(balance(&defender), 400);
+    assert_eq!(balance(&setup.contract), 0);
+    assert_eq!(client.get_claim(&1), None);
+    assert_eq!(
+        client.try_claim_winnings(&1),
+        Err(Ok(Error::NothingToClaim))
+    );
+}
@@ -0,0 +1,228 @@
+//! Property-based tests of the game's state machine
+//!
+//! Random sequences of calls (submitted moves, answered moves with good and
+//! bad proofs, repeated and out-of-range cells, early ends) are run against
+//! the contract and a small model of it. After every call the result must be
+//! the one the model predicts and the stored game must stay consistent.
+
+extern crate std;
+
+use proptest::prelude::*;
+use std::collections::BTreeSet;
+use std::vec::Vec;
+
+use crate::testutils::{invalid_proof, valid_proof, TestSetup};
+use crate::Error;
+use soroban_sdk::Env;
+use trap_grid_encoding::DEFAULT_GRID_SIZE as GRID_SIZE;
+
+const SESSION_ID: u32 = 1;
+const MAX_MOVES: u32 = GRID_SIZE * GRID_SIZE;
+
+#[derive(Clone, Debug)]
+enum Call {
+    SubmitMove {
+        x: u32,
+        y: u32,
+    },
+    MakeMove {
+        x: u32,
+        y: u32,
+        is_hit: bool,
+        valid: bool,
+    },
+    /// Answer the pending move, or submit and answer the first unplayed
+    /// cell, so games can reach their last move
+    MakeNextMove {
+        is_hit: bool,
+    },
+    EndGame,
+}
+
+fn call() -> impl Strategy<Value = Call> {
+    // Coordinates up to 9 so some fall outside the grid
+    let cell = (0..GRID_SIZE + 2, 0..GRID_SIZE + 2);
+    prop_oneof![
+        3 => cell.clone().prop_map(|(x, y)| Call::SubmitMove { x, y }),
+        6 => (cell, any::<bool>(), prop::bool::weighted(0.9))
+            .prop_map(|((x, y), is_hit, valid)| Call::MakeMove { x, y, is_hit, valid }),
+        20 => any::<bool>().prop_map(|is_hit| Call::MakeNextMove { is_hit }),
+        1 => Just(Call::EndGame),
+    ]
+}
+
+/// What the contract should have stored
+#[derive(Default)]
+struct Model {
+    played: BTreeSet<(u32, u32)>,
+    pending: Option<(u32, u32)>,
+    hits: u32,
+    ended: bool,
+}
+
+impl Model {
+    fn submit_move(&mut self, x: u32, y: u32) -> Result<(), Error> {
+        if self.ended {
+            return Err(Error::GameAlreadyEnded);
+        }
+        if x >= GRID_SIZE || y >= GRID_SIZE {
+            return Err(Error::InvalidMove);
+        }
+        if self.pending.is_some() {
+            return Err(Error::MovePending);
+        }
+        if self.played.contains(&(x, y)) {
+            return Err(Error::MoveAlreadyMade);
+        }
+        self.pending = Some((x, y));
+        Ok(())
+    }
+
+    fn make_move(&mut self, x: u32, y: u32, is_hit: bool, valid: bool) -> Result<(), Error> {
+        if self.ended {
+            return Err(Error::GameAlreadyEnded);
+        }
+        if x >= GRID_SIZE || y >= GRID_SIZE {
+            return Err(Error::InvalidMove);
+        }
+        if self.played.contains(&(x, y)) {
+            return Err(Error::MoveAlreadyMade);
+        }
+        match self.pending {
+            Some(pending) if pending == (x, y) => {}
+            Some(_) => return Err(Error::MovePending),
+            None => return Err(Error::NoPendingMove),
+        }
+        if !valid {
+            return Err(Error::InvalidProof);
+        }
+        self.played.insert((x, y));
+        self.pending = None;
+        self.hits += u32::from(is_hit);
+        self.ended = self.played.len() as u32 == MAX_MOVES;
+        Ok(())
+    }
+
+    fn end_game(&mut self) -> Result<(), Error> {
+        if self.ended {
+            return Err(Error::GameAlreadyEnded);
+        }
+        self.ended = true;
+        self.pending = None;
+        Ok(())
+    }
+
+    fn next_cell(&self) -> (u32, u32) {
+        self.pending.unwrap_or_else(|| {
+            (0..GRID_SIZE)
+                .flat_map(|x| (0..GRID_SIZE).map(move |y| (x, y)))
+                .find(|cell| !self.played.contains(cell))
+                .unwrap_or((0, 0))
+        })
+    }
+}
+
+/// A client `try_` result as the contract error it failed with, if any
+fn outcome<T, E: core::fmt::Debug>(result: Result<T, Result<Error, E>>) -> Result<(), Error> {
+    match result {
+        Ok(_) => Ok(()),
+        Err(Ok(error)) => Err(error),
+        Err(Err(error)) => panic!("call aborted: {error:?}"),
+    }
+}
+
+fn make_move(setup: &TestSetup, x: u32, y: u32, is_hit: bool, valid: bool) -> Result<(), Error> {
+    let env = &setup.env;
+    let proof = if valid {
+        valid_proof(env)
+    } else {
+        invalid_proof(env)
+    };
+    let public_inputs = setup.public_inputs(SESSION_ID, x, y, is_hit);
+    outcome(
+        setup
+            .client
+            .try_make_move(&SESSION_ID, &x, &y, &is_hit, &proof, &public_inputs),
+    )
+}
+
+fn apply(
+    setup: &TestSetup,
+    model: &mut Model,
+    call: &Call,
+) -> (Result<(), Error>, Result<(), Error>) {
+    let client = &setup.client;
+    match *call {
+        Call::SubmitMove { x, y } => (
+            outcome(client.try_submit_move(&SESSION_ID, &x, &y)),
+            model.submit_move(x, y),
+        ),
+        Call::MakeMove {
+            x,
+            y,
+            is_hit,
+            valid,
+        } => (
+            make_move(setup, x, y, is_hit, valid),
+            model.make_move(x, y, is_hit, valid),
+        ),
+        Call::MakeNextMove { is_hit } => {
+            let (x, y) = model.next_cell();
+            if model.pending.is_none() {
+                let submitted = (
+                    outcome(client.try_submit_move(&SESSION_ID, &x, &y)),
+                    model.submit_move(x, y),
+                );
+                if submitted != (Ok(()), Ok(())) {
+                    return submitted;
+                }
+            }
+            (
+                make_move(setup, x, y, is_hit, true),
+                model.make_move(x, y, is_hit, true),
+            )
+        }
+        Call::EndGame => (outcome(client.try_end_game(&SESSION_ID)), model.end_game()),
+    }
+}
+
+fn check_invariants(setup: &TestSetup, model: &Model) {
+    let game = setup.client.get_game(&SESSION_ID);
+    let moves = setup.client.get_moves(&SESSION_ID);
+    assert_eq!(game.hits + game.misses, game.moves_made);
+    assert_eq!(game.moves_made, moves.len());
+    assert!(game.moves_made <= MAX_MOVES);
+    assert_eq!(game.game_ended, game.winner.is_some());
+    assert_eq!(game.game_ended, model.ended);
+    assert_eq!(game.hits, model.hits);
+
+    // Every cell at most once, and exactly the ones the model played
+    let cells: Vec<(u32, u32)> = moves.iter().map(|m| (m.x, m.y)).collect();
+    assert_eq!(cells.len(), model.played.len());
+    assert_eq!(cells.iter().copied().collect::<BTreeSet<_>>(), model.played);
+    assert!(moves.iter().all(|m| m.verified));
+
+    let pending = setup.client.get_pending_move(&SESSION_ID);
+    assert_eq!(pending.as_ref().map(|p| (p.x, p.y)), model.pending);
+    if game.game_ended {
+        assert!(pending.is_none());
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn test_calls_keep_game_consistent(calls in prop::collection::vec(call(), 1..160)) {
+        let env = Env::default();
+        let setup = TestSetup::new(&env);
+        setup.start_game(SESSION_ID);
+        let mut model = Model::default();
+
+        for call in &calls {
+            let (actual, expected) = apply(&setup, &mut model, call);
+            prop_assert_eq!(actual, expected, "{:?}", call);
+            check_invariants(&setup, &model);
+        }
+    }
+}
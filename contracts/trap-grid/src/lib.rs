@@ -9,12 +9,41 @@
 //!
 //! **Game Hub Integration:**
 //! This game integrates with the Game Hub contract for session management and scoring.
+//!
+//! Sponsorship, arbitration, channels, optimistic play, practice and the
+//! other modes are described in the contracts README and on the entry points
+//! below.
 
+use soroban_sdk::xdr::ToXdr;
 use soroban_sdk::{
-    Address, Bytes, Env, IntoVal, Vec, contract, contractclient, contracterror,
-    contractimpl, contracttype, vec,
+    contract, contractclient, contracterror, contractevent, contractimpl, contracttype, token, vec,
+    Address, Bytes, BytesN, Env, IntoVal, Map, Symbol, TryFromVal, Val, Vec,
+};
+use trap_grid_encoding::{
+    GridSetup as SetupInputs, PositionMovement, DEFAULT_GRID_SIZE, INTERFACE, INTERFACE_VERSION,
+    MAX_GRID_SIZE, MIN_GRID_SIZE,
 };
 
+#[cfg(test)]
+mod budget;
+#[cfg(test)]
+mod channel;
+mod daily;
+#[cfg(test)]
+mod errors;
+#[cfg(test)]
+mod integration;
+#[cfg(test)]
+mod invariants;
+#[cfg(test)]
+mod optimistic;
+mod payout;
+mod practice;
+pub mod replay;
+#[cfg(test)]
+mod storage_layout;
+pub mod testutils;
+
 // Import GameHub contract interface
 #[contractclient(name = "GameHubClient")]
 pub trait GameHub {
@@ -31,10 +60,30 @@ pub trait GameHub {
     fn end_game(env: Env, session_id: u32, player1_won: bool);
 }
 
-// Import ZK Verifier contract interface
+// Import Trophy contract interface; mints a non-transferable trophy to a winner
+#[contractclient(name = "TrophyClient")]
+pub trait Trophies {
+    fn mint(
+        env: Env,
+        game: Address,
+        winner: Address,
+        session_id: u32,
+        opponent: Address,
+        score: u32,
+    ) -> u32;
+}
+
+// Import Archive contract interface; keeps the record of a finished game
+#[contractclient(name = "ArchiveClient")]
+pub trait Archives {
+    fn archive(env: Env, game: Address, record: ArchiveRecord) -> u32;
+}
+
+// Import ZK Verifier contract interface (rs-soroban-ultrahonk); fails with a
+// contract error when the proof does not verify
 #[contractclient(name = "VerifierClient")]
 pub trait Verifier {
-    fn verify(env: Env, proof: Bytes, public_inputs: Bytes) -> bool;
+    fn verify_proof(env: Env, public_inputs: Bytes, proof_bytes: Bytes);
 }
 
 // ============================================================================
@@ -57,20 +106,131 @@ pub enum Error {
     InvalidProof = 10,
     AllMovesCompleted = 11,
     GameNotComplete = 12,
+    MovePending = 13,
+    /// A sponsorship backs no player of the game or one already backed,
+    /// stakes nothing, splits more than everything, or uses a different token
+    /// than the other side's
+    InvalidSponsorship = 14,
+    /// The player already registered a referrer
+    ReferrerAlreadySet = 15,
+    /// A player cannot refer themselves or the player who referred them
+    SelfReferral = 16,
+    /// A fee over 100%, or a referral share two referrers could push past it
+    InvalidFees = 17,
+    /// No emergency withdrawal is queued for the game
+    WithdrawNotQueued = 18,
+    /// The emergency withdrawal's delay has not passed
+    WithdrawTimelocked = 19,
+    /// The caller is not a member of the admin set
+    NotAdminMember = 20,
+    ProposalNotFound = 21,
+    ProposalExpired = 22,
+    /// The member already approved the proposal
+    AlreadyApproved = 23,
+    /// An admin set with no members, or a threshold of zero or above its size
+    InvalidThreshold = 24,
+    /// The caller is neither an operator nor the admin
+    NotOperator = 25,
+    /// An operator paused new games
+    Paused = 26,
+    /// A configuration value out of its bounds
+    InvalidConfig = 27,
+    /// As many games are in progress as the configuration allows
+    TooManyGames = 28,
+    /// A player staked zero or negative points
+    StakeNotPositive = 29,
+    /// A player staked points outside the configured bounds
+    StakeOutOfBounds = 30,
+    /// The arbiter is a player, or the game has one already or is under way
+    InvalidArbiter = 31,
+    /// The game has no result waiting out an appeal window
+    NotArbitrated = 32,
+    /// The appeal window is still open
+    AppealWindowOpen = 33,
+    /// The appeal window has closed
+    AppealWindowClosed = 34,
+    /// The result is under appeal, frozen until the arbiter rules
+    UnderAppeal = 35,
+    /// Nobody appealed the result
+    NotAppealed = 36,
+    /// The game has no payout waiting to be claimed
+    NothingToClaim = 37,
+    /// The claim delay has not passed yet
+    ClaimNotReady = 38,
+    /// The grid is already committed, or play has started
+    GridLocked = 39,
+    /// The defender committed this grid in an earlier game
+    RootReused = 40,
+    /// The setup proof does not show a well-formed grid with the configured
+    /// number of traps
+    InvalidSetup = 41,
+    /// The grid commitment started an earlier game
+    SetupReused = 42,
+    /// The player moved less than `move_gap_ledgers` ago
+    MoveTooSoon = 43,
+    /// The channel state is not newer than the one submitted, or that one
+    /// closed the channel
+    StaleChannelState = 44,
+    /// A newer channel state can still replace the submitted one
+    ChallengeWindowOpen = 45,
+    /// A channel state's counts, or a transcript, that do not add up: a move
+    /// off the grid or repeated, or a hash or count other than the state's
+    InvalidTranscript = 46,
+    /// The game has no open channel, or no state was submitted to it
+    NoChannelState = 47,
+    /// The game is played both on-chain and through a channel
+    ChannelConflict = 48,
+    /// The game is not in optimistic mode
+    NotOptimistic = 49,
+    /// No move is waiting for the defender's claim
+    NoPendingMove = 50,
+    /// The move is proven, its challenge window has closed, or another claim
+    /// is already challenged
+    ClaimNotChallengeable = 51,
+    /// No claim of the game is challenged
+    NoChallenge = 52,
+    /// The challenged claim was not proven in time
+    ProofDeadlinePassed = 53,
+    /// The game is already in optimistic mode, or play has started
+    OptimisticLocked = 54,
+    /// A move's note is longer than `MAX_NOTE_BYTES`
+    NoteTooLong = 55,
+    /// The game is frozen by its arbiter or the admin
+    GameFrozen = 56,
+    /// The caller is neither the game's arbiter nor the admin
+    NotArbiter = 57,
+    /// The player has no practice game in progress
+    PracticeNotFound = 58,
+    /// The player's last practice game is not finished
+    PracticeInProgress = 59,
+    /// The revealed secret does not hash to the practice game's commitment
+    RevealMismatch = 60,
+    /// No grid is committed for the day
+    DailyNotFound = 61,
+    /// The day's grid is already committed, or the day has passed
+    DailyExists = 62,
+    /// The day's grid cannot be revealed before the day closes
+    DayNotClosed = 63,
+    /// The day's grid is revealed, so it takes no more probes
+    DailyRevealed = 64,
+    /// The day's grid is not revealed yet
+    DailyNotRevealed = 65,
+    /// A player has completed fewer games than the configuration requires
+    TooFewGames = 66,
 }
 
 // ============================================================================
 // Data Types
 // ============================================================================
 
-const GRID_SIZE: u32 = 8;
-const MAX_MOVES: u32 = 64; // 8x8 grid
+/// Moves `get_full_state` returns
+pub const FULL_STATE_MOVES: u32 = 8;
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Game {
-    pub defender: Address,        // Player A
-    pub attacker: Address,        // Player B
+    pub defender: Address, // Player A
+    pub attacker: Address, // Player B
     pub defender_points: i128,
     pub attacker_points: i128,
     pub moves_made: u32,
@@ -78,332 +238,6083 @@ pub struct Game {
     pub misses: u32,
     pub game_started: bool,
     pub game_ended: bool,
+    /// `None` while playing, and after an emergency withdrawal, an expiry
+    /// without a winner or a voided appeal
     pub winner: Option<Address>,
+    /// Layout of the stored record, [`GAME_SCHEMA_VERSION`] once written by
+    /// this contract version; older records are migrated when read
+    pub schema_version: u32,
+    /// Ledger of the last start or move, which expiry counts from
+    pub last_active_ledger: u32,
+    /// Side of the grid, from the configuration the game started under
+    pub grid_size: u32,
+    /// Traps on the grid, from the configuration the game started under
+    pub trap_count: u32,
 }
 
+impl Game {
+    /// Cells on the grid, and so the most moves a game can have
+    pub fn cells(&self) -> u32 {
+        self.grid_size * self.grid_size
+    }
+}
+
+/// Schema of [`Game`] records this contract writes
+pub const GAME_SCHEMA_VERSION: u32 = 5;
+
+/// A game stored before records kept their trap count (schema 4)
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct Move {
-    pub x: u32,
-    pub y: u32,
-    pub is_hit: bool,
-    pub verified: bool,
+pub struct GameV4 {
+    pub defender: Address,
+    pub attacker: Address,
+    pub defender_points: i128,
+    pub attacker_points: i128,
+    pub moves_made: u32,
+    pub hits: u32,
+    pub misses: u32,
+    pub game_started: bool,
+    pub game_ended: bool,
+    pub winner: Option<Address>,
+    pub schema_version: u32,
+    pub last_active_ledger: u32,
+    pub grid_size: u32,
 }
 
+/// A game stored before grids had a configurable size (schema 3), played
+/// on the 8x8 grid
 #[contracttype]
-#[derive(Clone)]
-pub enum DataKey {
-    Game(u32),
-    Moves(u32), // session_id -> Vec<Move>
-    GameHubAddress,
-    VerifierAddress,
-    Admin,
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GameV3 {
+    pub defender: Address,
+    pub attacker: Address,
+    pub defender_points: i128,
+    pub attacker_points: i128,
+    pub moves_made: u32,
+    pub hits: u32,
+    pub misses: u32,
+    pub game_started: bool,
+    pub game_ended: bool,
+    pub winner: Option<Address>,
+    pub schema_version: u32,
+    pub last_active_ledger: u32,
 }
 
-// ============================================================================
-// Storage TTL Management
-// ============================================================================
+/// A game stored before records tracked activity (schema 2)
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GameV2 {
+    pub defender: Address,
+    pub attacker: Address,
+    pub defender_points: i128,
+    pub attacker_points: i128,
+    pub moves_made: u32,
+    pub hits: u32,
+    pub misses: u32,
+    pub game_started: bool,
+    pub game_ended: bool,
+    pub winner: Option<Address>,
+    pub schema_version: u32,
+}
 
-const GAME_TTL_LEDGERS: u32 = 518_400; // 30 days
+/// A game stored before records carried a schema version (schema 1)
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GameV1 {
+    pub defender: Address,
+    pub attacker: Address,
+    pub defender_points: i128,
+    pub attacker_points: i128,
+    pub moves_made: u32,
+    pub hits: u32,
+    pub misses: u32,
+    pub game_started: bool,
+    pub game_ended: bool,
+    pub winner: Option<Address>,
+}
 
-// ============================================================================
-// Contract Definition
-// ============================================================================
+impl From<GameV1> for GameV2 {
+    fn from(game: GameV1) -> Self {
+        GameV2 {
+            defender: game.defender,
+            attacker: game.attacker,
+            defender_points: game.defender_points,
+            attacker_points: game.attacker_points,
+            moves_made: game.moves_made,
+            hits: game.hits,
+            misses: game.misses,
+            game_started: game.game_started,
+            game_ended: game.game_ended,
+            winner: game.winner,
+            schema_version: 2,
+        }
+    }
+}
 
-#[contract]
-pub struct TrapGridContract;
+/// `last_active_ledger` is unknown; `load_game` sets it to the ledger the
+/// record is read in
+impl From<GameV2> for GameV3 {
+    fn from(game: GameV2) -> Self {
+        GameV3 {
+            defender: game.defender,
+            attacker: game.attacker,
+            defender_points: game.defender_points,
+            attacker_points: game.attacker_points,
+            moves_made: game.moves_made,
+            hits: game.hits,
+            misses: game.misses,
+            game_started: game.game_started,
+            game_ended: game.game_ended,
+            winner: game.winner,
+            schema_version: 3,
+            last_active_ledger: 0,
+        }
+    }
+}
 
-#[contractimpl]
-impl TrapGridContract {
-    /// Initialize the contract with GameHub, Verifier addresses and admin
-    pub fn __constructor(
-        env: Env,
-        admin: Address,
-        game_hub: Address,
-        verifier: Address,
-    ) {
-        env.storage().instance().set(&DataKey::Admin, &admin);
-        env.storage()
-            .instance()
-            .set(&DataKey::GameHubAddress, &game_hub);
-        env.storage()
-            .instance()
-            .set(&DataKey::VerifierAddress, &verifier);
+impl From<GameV3> for GameV4 {
+    fn from(game: GameV3) -> Self {
+        GameV4 {
+            defender: game.defender,
+            attacker: game.attacker,
+            defender_points: game.defender_points,
+            attacker_points: game.attacker_points,
+            moves_made: game.moves_made,
+            hits: game.hits,
+            misses: game.misses,
+            game_started: game.game_started,
+            game_ended: game.game_ended,
+            winner: game.winner,
+            schema_version: 4,
+            last_active_ledger: game.last_active_ledger,
+            grid_size: DEFAULT_GRID_SIZE,
+        }
     }
+}
 
-    /// Start a new game between defender and attacker
-    ///
-    /// # Arguments
-    /// * `session_id` - Unique session identifier
-    /// * `defender` - Player A who sets up traps
-    /// * `attacker` - Player B who makes moves
-    /// * `defender_points` - Points committed by defender
-    /// * `attacker_points` - Points committed by attacker
-    pub fn start_game(
-        env: Env,
-        session_id: u32,
-        defender: Address,
-        attacker: Address,
-        defender_points: i128,
-        attacker_points: i128,
-    ) -> Result<(), Error> {
-        // Prevent self-play
-        if defender == attacker {
-            panic!("Cannot play against yourself");
+/// `trap_count` is unknown; `load_game` sets it to the configured count when
+/// the record is read
+impl From<GameV4> for Game {
+    fn from(game: GameV4) -> Self {
+        Game {
+            defender: game.defender,
+            attacker: game.attacker,
+            defender_points: game.defender_points,
+            attacker_points: game.attacker_points,
+            moves_made: game.moves_made,
+            hits: game.hits,
+            misses: game.misses,
+            game_started: game.game_started,
+            game_ended: game.game_ended,
+            winner: game.winner,
+            schema_version: GAME_SCHEMA_VERSION,
+            last_active_ledger: game.last_active_ledger,
+            grid_size: game.grid_size,
+            trap_count: 0,
         }
+    }
+}
 
-        // Only defender needs to authenticate to start the game
-        // Attacker implicitly joins when they make their first move
-        defender.require_auth_for_args(vec![
-            &env,
-            session_id.into_val(&env),
-            defender_points.into_val(&env),
-        ]);
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Move {
+    pub x: u32,
+    pub y: u32,
+    pub is_hit: bool,
+    pub verified: bool,
+}
 
-        // Get GameHub address
-        let game_hub_addr: Address = env
-            .storage()
-            .instance()
-            .get(&DataKey::GameHubAddress)
-            .expect("GameHub address not set");
+/// A cell the attacker has picked and the defender has yet to answer
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PendingMove {
+    pub x: u32,
+    pub y: u32,
+}
 
-        // Create GameHub client and start game
-        let game_hub = GameHubClient::new(&env, &game_hub_addr);
-        game_hub.start_game(
-            &env.current_contract_address(),
-            &session_id,
-            &defender,
-            &attacker,
-            &defender_points,
-            &attacker_points,
-        );
+/// A third party staking tokens on one player
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Sponsorship {
+    pub sponsor: Address,
+    /// The defender or the attacker
+    pub player: Address,
+    pub token: Address,
+    /// Escrowed from the sponsor when the game starts, less what progressive
+    /// payouts have released to the other player since
+    pub amount: i128,
+    /// Share of the winnings (the losing side's stake) paid to the player
+    /// if they win, in basis points; the sponsor takes the rest
+    pub player_share_bps: u32,
+}
 
-        // Create game state
-        let game = Game {
-            defender: defender.clone(),
-            attacker: attacker.clone(),
-            defender_points,
-            attacker_points,
-            moves_made: 0,
-            hits: 0,
-            misses: 0,
-            game_started: true,
-            game_ended: false,
-            winner: None,
-        };
+/// The defender's proof that the hidden grid is well-formed, checked by
+/// `start_game`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GridSetup {
+    /// `poseidon_hash_4([DOMAIN_GRID_COMMITMENT, trap_merkle_root, salt,
+    /// defender])`, `defender` being the hash of the defender's account key
+    /// or contract id, which `start_game` passes to the circuit
+    pub grid_commitment: BytesN<32>,
+    /// UltraHonk proof from the grid-setup circuit
+    pub proof: Bytes,
+}
 
-        // Store game state
-        let game_key = DataKey::Game(session_id);
-        env.storage().temporary().set(&game_key, &game);
-        env.storage()
-            .temporary()
-            .extend_ttl(&game_key, GAME_TTL_LEDGERS, GAME_TTL_LEDGERS);
+/// Deployment settings the admin tunes with `set_config`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ContractConfig {
+    /// Ledgers a game's storage lives after each write
+    pub game_ttl_ledgers: u32,
+    /// Delay before a queued emergency withdrawal can run
+    pub emergency_delay_ledgers: u32,
+    /// Ledgers an admin-set proposal can be approved in
+    pub proposal_expiry_ledgers: u32,
+    /// Games in progress at once; 0 for no limit
+    pub max_active_games: u32,
+    /// Fewest points a player may stake, at least 1
+    pub min_points: i128,
+    /// Most points a player may stake
+    pub max_points: i128,
+    /// Ledgers without a move after which a game has expired; 0 for never
+    pub expiry_ledgers: u32,
+    /// How `end_game` ends an expired game
+    pub expiry_policy: ExpiryPolicy,
+    /// Ledgers a player can appeal an arbitrated game's result in
+    pub appeal_window_ledgers: u32,
+    /// Ledgers between settlement and `claim_winnings` paying the
+    /// sponsorships out; 0 pays them at settlement
+    pub claim_delay_ledgers: u32,
+    /// Whether `commit_grid` rejects a trap Merkle root the defender
+    /// committed in an earlier game
+    pub reject_reused_roots: bool,
+    /// Traps a grid must hold for its setup proof to start a game
+    pub trap_count: u32,
+    /// Ledgers between an attacker's moves, and between the defender's
+    /// answers; 0 for no limit
+    pub move_gap_ledgers: u32,
+    /// Side of the grid new games are played on, 2 to 16; the verifiers
+    /// check it as a public input, so one circuit serves every size
+    pub grid_size: u32,
+    /// Share of what remains of a sponsorship released to the other player
+    /// on each proven move against its side, in basis points: a hit pays the
+    /// attacker from the defender's stake, a miss the defender from the
+    /// attacker's. 0 pays everything at settlement
+    pub progressive_payout_bps: u32,
+    /// Bonus on the winnings of a decisive win, paid from the bonus pool
+    pub bonus_curve: PayoutCurve,
+    /// Fee rebate for winners on a streak of wins
+    pub streak_bonus: StreakBonus,
+    /// Games each player must have completed to start one; 0 for none
+    pub min_games_completed: u32,
+}
 
-        // Initialize empty moves vector
-        let moves_key = DataKey::Moves(session_id);
-        let moves: Vec<Move> = vec![&env];
-        env.storage().temporary().set(&moves_key, &moves);
-        env.storage()
-            .temporary()
-            .extend_ttl(&moves_key, GAME_TTL_LEDGERS, GAME_TTL_LEDGERS);
+/// Outcome of an expired game, whatever its score
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ExpiryPolicy {
+    DefenderWins,
+    AttackerWins,
+    /// Ends without a winner and returns the sponsors' stakes, counted as a
+    /// completed game
+    Draw,
+    /// Ends without a winner and returns the sponsors' stakes, like an
+    /// emergency withdrawal
+    Refund,
+}
 
-        Ok(())
-    }
+/// Bonus on a sponsored win by its margin, the winner's share of the moves
+/// above a bare majority (see the `payout` module)
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PayoutCurve {
+    /// Bonus on the winnings of a win by every move, in basis points; 0
+    /// pays no bonus
+    pub max_bonus_bps: u32,
+    /// Margin, in basis points, a win must exceed to earn a bonus
+    pub threshold_bps: u32,
+    /// 1 for a bonus rising linearly with the margin, 2 or 3 to favour the
+    /// most decisive wins
+    pub exponent: u32,
+}
 
-    /// Attacker makes a move, and Defender responds with ZK proof
-    ///
-    /// # Arguments
-    /// * `session_id` - Game session identifier
-    /// * `x` - X coordinate of move (0-7)
-    /// * `y` - Y coordinate of move (0-7)
-    /// * `is_hit` - Defender's claim: true if trap hit, false if miss
-    /// * `proof` - ZK proof of the claim (UltraHonk proof from position-movement circuit)
-    /// * `public_inputs` - Public inputs for proof verification (trap_commitment, move_x, move_y, is_hit)
-    pub fn make_move(
-        env: Env,
-        session_id: u32,
-        x: u32,
-        y: u32,
-        is_hit: bool,
-        proof: Bytes,
-        public_inputs: Bytes,
-    ) -> Result<bool, Error> {
-        // Load game
-        let game_key = DataKey::Game(session_id);
-        let mut game: Game = env
-            .storage()
-            .temporary()
-            .get(&game_key)
-            .ok_or(Error::GameNotFound)?;
+impl PayoutCurve {
+    /// No bonus
+    pub const NONE: PayoutCurve = PayoutCurve {
+        max_bonus_bps: 0,
+        threshold_bps: 0,
+        exponent: 1,
+    };
+}
 
-        // Validate game state
-        if !game.game_started {
-            return Err(Error::GameNotStarted);
-        }
-        if game.game_ended {
-            return Err(Error::GameAlreadyEnded);
-        }
+/// Rebate of the protocol fee for a winner whose streak, counting the win,
+/// is at least `min_streak` when its sponsorship winnings are paid
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StreakBonus {
+    /// Consecutive wins that earn the rebate; 0 grants none
+    pub min_streak: u32,
+    /// Share of the protocol fee on the winnings waived, in basis points
+    pub fee_rebate_bps: u32,
+}
 
-        // Validate move coordinates
-        if x >= GRID_SIZE || y >= GRID_SIZE {
-            return Err(Error::InvalidMove);
-        }
+impl StreakBonus {
+    /// No rebate
+    pub const NONE: StreakBonus = StreakBonus {
+        min_streak: 0,
+        fee_rebate_bps: 0,
+    };
+}
 
-        // Check if move already made
-        let moves_key = DataKey::Moves(session_id);
-        let mut moves: Vec<Move> = env
-            .storage()
-            .temporary()
-            .get(&moves_key)
-            .unwrap_or(vec![&env]);
+/// A player's consecutive wins of settled games
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Streak {
+    /// Wins since the player's last loss
+    pub current: u32,
+    /// Longest streak the player has had
+    pub best: u32,
+}
 
-        for i in 0..moves.len() {
-            let existing_move = moves.get(i).unwrap();
-            if existing_move.x == x && existing_move.y == y {
-                return Err(Error::MoveAlreadyMade);
-            }
-        }
+impl ContractConfig {
+    /// The configuration until the admin sets one
+    pub const DEFAULT: ContractConfig = ContractConfig {
+        game_ttl_ledgers: GAME_TTL_LEDGERS,
+        emergency_delay_ledgers: EMERGENCY_DELAY_LEDGERS,
+        proposal_expiry_ledgers: PROPOSAL_EXPIRY_LEDGERS,
+        max_active_games: 0,
+        min_points: 1,
+        max_points: i128::MAX,
+        expiry_ledgers: 0,
+        expiry_policy: ExpiryPolicy::Refund,
+        appeal_window_ledgers: APPEAL_WINDOW_LEDGERS,
+        claim_delay_ledgers: 0,
+        reject_reused_roots: true,
+        trap_count: TRAP_COUNT,
+        move_gap_ledgers: 0,
+        grid_size: DEFAULT_GRID_SIZE,
+        progressive_payout_bps: 0,
+        bonus_curve: PayoutCurve::NONE,
+        streak_bonus: StreakBonus::NONE,
+        min_games_completed: 0,
+    };
+}
 
-        // Verify ZK proof using the verifier contract
-        let verifier_addr: Address = env
-            .storage()
-            .instance()
-            .get(&DataKey::VerifierAddress)
-            .expect("Verifier address not set");
+/// Protocol fee taken from sponsorship winnings at settlement
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Fees {
+    /// Share of the winnings taken as the fee, in basis points
+    pub protocol_bps: u32,
+    /// Share of the fee accrued to each player's referrer, in basis points;
+    /// the admin receives what is left
+    pub referral_bps: u32,
+}
 
-        let verifier = VerifierClient::new(&env, &verifier_addr);
-        let proof_valid = verifier.verify(&proof, &public_inputs);
+/// Members who approve sensitive changes, `threshold` of them at a time
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AdminSet {
+    pub members: Vec<Address>,
+    pub threshold: u32,
+}
 
-        if !proof_valid {
-            return Err(Error::InvalidProof);
-        }
+/// A sensitive change the admin set approves
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AdminAction {
+    /// `Fees { protocol_bps, referral_bps }`
+    SetFees(u32, u32),
+    SetVerifier(Address),
+    /// The verifier of the grid-setup circuit's proofs
+    SetSetupVerifier(Address),
+    /// Install the uploaded wasm with this hash
+    Upgrade(BytesN<32>),
+    /// The admin for routine operations (trophies, emergency withdrawals),
+    /// paid the protocol fee
+    SetAdmin(Address),
+    /// Replace the admin set: members and threshold
+    SetAdminSet(Vec<Address>, u32),
+}
 
-        // Record the move
-        let new_move = Move {
-            x,
-            y,
+/// An admin action waiting for approvals
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Proposal {
+    pub action: AdminAction,
+    pub approvals: Vec<Address>,
+    /// Last ledger the proposal can be approved in
+    pub expires_ledger: u32,
+}
+
+/// Totals across every session
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct GlobalStats {
+    pub games_created: u64,
+    /// Games settled with a winner or drawn on expiry; refunds and emergency
+    /// withdrawals don't count
+    pub games_completed: u64,
+    /// Moves and hits of every game no longer in play
+    pub total_moves: u64,
+    pub total_hits: u64,
+    /// Both players' points of every completed game
+    pub points_settled: i128,
+}
+
+/// How often each cell was probed, and hit, in the games that ended on the
+/// configured grid; both have a counter per cell, at `x * grid_size + y`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Heatmap {
+    pub probes: Vec<u32>,
+    pub hits: Vec<u32>,
+}
+
+/// A solo game against a grid the contract draws (see the `practice`
+/// module); no opponent, stake or proof. No layout is stored while it is
+/// played.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Practice {
+    /// SHA-256 of the secret the player reveals at `finish_practice`
+    pub commitment: BytesN<32>,
+    pub grid_size: u32,
+    pub trap_count: u32,
+    /// Cells probed, in play order, at `x * grid_size + y`
+    pub probes: Vec<u32>,
+    /// Probed cells that held a trap
+    pub found: Vec<u32>,
+}
+
+/// A player's practice results, kept apart from ranked play: practice games
+/// never count toward `GlobalStats`, the heatmap, the Game Hub or trophies
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PracticeStats {
+    /// Practice games finished
+    pub games: u32,
+    /// Games finished with every trap found
+    pub cleared: u32,
+    /// Fewest probes a cleared game took; 0 before the first
+    pub best_probes: u32,
+    pub total_probes: u64,
+    pub total_hits: u64,
+}
+
+/// Entries a daily puzzle's leaderboard keeps
+pub const DAILY_LEADERBOARD_SIZE: u32 = 10;
+
+/// A day's shared puzzle grid (see the `daily` module)
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Daily {
+    /// SHA-256 of the trap cells in ascending order and a salt
+    pub grid_hash: BytesN<32>,
+    pub grid_size: u32,
+    pub trap_count: u32,
+    /// Cells holding a trap, at `x * grid_size + y`; empty until revealed
+    pub traps: Vec<u32>,
+    /// Players who probed the grid
+    pub players: u32,
+    /// Ranked players the operator gave an answer the layout contradicts
+    pub misanswered: u32,
+}
+
+/// A player's attack on a day's grid
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DailyEntry {
+    /// Cells probed, in play order
+    pub probes: Vec<u32>,
+    /// The operator's answer to each answered probe, `true` for a hit;
+    /// unverified until `rank_daily` checks them against the layout
+    pub answers: Vec<bool>,
+    /// The probe waiting for the operator's answer
+    pub pending: Option<u32>,
+}
+
+/// A place on a day's leaderboard
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DailyRank {
+    pub player: Address,
+    /// Traps among the probes, by the revealed layout
+    pub hits: u32,
+    pub probes: u32,
+}
+
+/// The arbiter of a game, and the appeal of its result
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Arbitration {
+    pub arbiter: Address,
+    /// Last ledger a player can appeal in; 0 until the result is posted
+    pub appeal_until: u32,
+    /// The player who flagged the result, freezing its settlement
+    pub appellant: Option<Address>,
+}
+
+/// Ledgers of a game's last attacker move and defender answer, which
+/// `move_gap_ledgers` counts from
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct LastMoves {
+    pub attacker_ledger: Option<u32>,
+    pub defender_ledger: Option<u32>,
+}
+
+/// A state of a game played through a channel, signed by both players'
+/// channel keys
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ChannelState {
+    /// Moves played in the channel so far
+    pub move_index: u32,
+    pub hits: u32,
+    pub misses: u32,
+    /// Hash chain of the moves played, as `settle_channel` recomputes it
+    pub transcript: BytesN<32>,
+    /// Both players agree the game ends here, so it settles without a
+    /// challenge window
+    pub closing: bool,
+}
+
+/// A move played in a channel; the defender's proof was checked off-chain
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ChannelMove {
+    pub x: u32,
+    pub y: u32,
+    pub is_hit: bool,
+    /// SHA-256 of the defender's proof of the answer
+    pub proof_hash: BytesN<32>,
+}
+
+/// A game's channel: the keys its states are signed with and the latest
+/// state submitted
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Channel {
+    /// Ed25519 keys the players sign states with
+    pub defender_key: BytesN<32>,
+    pub attacker_key: BytesN<32>,
+    /// Move 0 with an empty transcript until a state is submitted
+    pub state: ChannelState,
+    /// Last ledger a newer state can replace `state` in; 0 until a state is
+    /// submitted
+    pub challenge_until: u32,
+}
+
+/// Claims of a game in optimistic mode and the challenge against them
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Optimistic {
+    /// Ledger each claimed move was answered in, by move index
+    pub claims: Map<u32, u32>,
+    /// Index of the move the attacker challenged, until it is proven
+    pub challenged: Option<u32>,
+    /// Last ledger the challenged move can be proven in
+    pub prove_until: u32,
+    /// Token of the claim bonds
+    pub token: Address,
+    /// Deposit the defender posts with each claim
+    pub bond: i128,
+    /// Deposits held for the claims made so far, until the game ends
+    pub bonded: i128,
+}
+
+/// Ledgers a game's next steps hinge on; `None` where they don't apply
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Deadlines {
+    /// Last ledger before `end_game` treats the game as expired; `None`
+    /// without an expiry or once the game ended
+    pub expires_after: Option<u32>,
+    /// Last ledger a player can appeal the arbitrated result in
+    pub appeal_until: Option<u32>,
+    /// Ledger `claim_winnings` can pay the sponsorships out from
+    pub claimable_from: Option<u32>,
+    /// Ledger a queued emergency withdrawal can run from
+    pub emergency_withdraw_from: Option<u32>,
+}
+
+/// Everything a client polls about a game, read in one call
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FullState {
+    pub game: Game,
+    pub config: ContractConfig,
+    /// The move waiting for the defender's answer, if any; a vector of at
+    /// most one as contract types cannot nest an `Option` of a struct
+    pub pending_move: Vec<PendingMove>,
+    pub deadlines: Deadlines,
+    /// The last `FULL_STATE_MOVES` moves, oldest first
+    pub recent_moves: Vec<Move>,
+}
+
+/// How a game that ends in play is decided; an expired game follows the
+/// configured `ExpiryPolicy` instead
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum WinPolicy {
+    /// The attacker wins with hits on more than half its moves, the
+    /// defender otherwise
+    MajorityHits,
+}
+
+/// The rules a game is played under, for clients to show instead of
+/// assuming them
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Rules {
+    /// Side of the game's grid
+    pub grid_size: u32,
+    /// Traps on the game's grid, as its setup proof showed
+    pub trap_count: u32,
+    pub win_policy: WinPolicy,
+    pub expiry_policy: ExpiryPolicy,
+    /// Ledgers without a move after which the game has expired; 0 for never
+    pub expiry_ledgers: u32,
+    /// Ledgers between an attacker's moves, and between the defender's
+    /// answers; 0 for no limit
+    pub move_gap_ledgers: u32,
+    /// Ledgers a player can appeal an arbitrated result in
+    pub appeal_window_ledgers: u32,
+    /// Ledgers between settlement and `claim_winnings`; 0 pays at settlement
+    pub claim_delay_ledgers: u32,
+    /// Delay before a queued emergency withdrawal can run
+    pub emergency_delay_ledgers: u32,
+    /// Ledgers a submitted channel state can be replaced by a newer one in
+    pub channel_challenge_ledgers: u32,
+    /// Ledgers an optimistic claim can be challenged in, and a challenged
+    /// claim proven in
+    pub claim_challenge_ledgers: u32,
+    pub min_points: i128,
+    pub max_points: i128,
+    /// Share of sponsorship winnings taken as the protocol fee, and the
+    /// referrers' share of it, in basis points; 0 without fees
+    pub protocol_fee_bps: u32,
+    pub referral_fee_bps: u32,
+    /// Share of a sponsorship released per proven move against its side,
+    /// in basis points; 0 pays everything at settlement
+    pub progressive_payout_bps: u32,
+    /// Bonus on the winnings of a decisive win, paid from the bonus pool
+    pub bonus_curve: PayoutCurve,
+    /// Fee rebate for winners on a streak of wins
+    pub streak_bonus: StreakBonus,
+    /// Games each player must have completed to start one
+    pub min_games_completed: u32,
+    /// Verifiers of the position-movement and grid-setup circuits' proofs
+    pub verifier: Address,
+    pub setup_verifier: Address,
+}
+
+/// A finished game as the archive contract stores it
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ArchiveRecord {
+    pub session_id: u32,
+    pub defender: Address,
+    pub attacker: Address,
+    /// SHA-256 of the `ContractConfig` XDR when the game ended
+    pub config_hash: BytesN<32>,
+    pub winner: Option<Address>,
+    pub hits: u32,
+    pub misses: u32,
+    /// Two bytes per move in play order: `x` with the top bit set for a hit,
+    /// then `y` (see the `replay` module)
+    pub replay: Bytes,
+}
+
+/// The arbiter's decision on an appealed result
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Ruling {
+    Confirm,
+    /// The other player wins
+    Overturn,
+    /// Nobody wins and the sponsors' stakes are returned
+    Void,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum DataKey {
+    Game(u32),
+    Moves(u32),       // session_id -> Vec<Move>
+    PendingMove(u32), // session_id -> PendingMove
+    GameHubAddress,
+    VerifierAddress,
+    SetupVerifierAddress,
+    Admin,
+    TrophyAddress,
+    Sponsorships(u32), // session_id -> Vec<Sponsorship>
+    Fees,
+    Referrer(Address),                 // player -> referrer
+    ReferralRewards(Address, Address), // (referrer, token) -> i128 claimable
+    EmergencyWithdraw(u32),            // session_id -> ledger it can run from
+    AdminSet,
+    ProposalCount,
+    Proposal(u32),     // proposal_id -> Proposal
+    Operator(Address), // operator -> true
+    Paused,
+    Config,
+    ActiveGames, // u32, games started and not ended
+    GlobalStats,
+    Heatmap,
+    Arbitration(u32),              // session_id -> Arbitration, until settled
+    Claim(u32),                    // session_id -> ledger the payout can be claimed from
+    GridRoot(u32),                 // session_id -> trap Merkle root the defender committed
+    UsedRoot(Address, BytesN<32>), // (defender, trap Merkle root) -> true
+    GridSetup(u32),                // session_id -> grid commitment of the setup proof
+    UsedSetup(BytesN<32>),         // grid commitment -> true
+    ArchiveAddress,
+    LastMoves(u32),     // session_id -> LastMoves
+    AbortVotes(u32),    // session_id -> Vec<Address> voting to abort, until it aborts
+    Channel(u32),       // session_id -> Channel, until settled
+    Optimistic(u32),    // session_id -> Optimistic
+    Frozen(u32),        // session_id -> arbiter or admin that froze the game
+    BonusPool(Address), // token -> i128 held for decisive-win bonuses
+    Practice(Address),  // player -> Practice, until finished
+    PracticeStats(Address),
+    Daily(u32),               // day -> Daily
+    DailyEntry(u32, Address), // (day, player) -> DailyEntry, until ranked
+    DailyLeaderboard(u32),    // day -> Vec<DailyRank>
+    Streak(Address),          // player -> Streak
+    GamesCompleted(Address),  // player -> u32
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+//
+// Game events carry the session and both players as topics, so RPC filters
+// can select one player's games without reading every trap-grid event.
+
+/// Published by `start_game`
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GameStarted {
+    #[topic]
+    pub session_id: u32,
+    #[topic]
+    pub defender: Address,
+    #[topic]
+    pub attacker: Address,
+}
+
+/// Published by `open_channel`
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ChannelOpened {
+    #[topic]
+    pub session_id: u32,
+    #[topic]
+    pub defender: Address,
+    #[topic]
+    pub attacker: Address,
+}
+
+/// Published by `submit_channel_state`; a newer state can replace it until
+/// `challenge_until`
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ChannelStateSubmitted {
+    #[topic]
+    pub session_id: u32,
+    #[topic]
+    pub defender: Address,
+    #[topic]
+    pub attacker: Address,
+    pub move_index: u32,
+    pub challenge_until: u32,
+}
+
+/// Published by `enable_optimistic`
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OptimisticEnabled {
+    #[topic]
+    pub session_id: u32,
+    #[topic]
+    pub defender: Address,
+    #[topic]
+    pub attacker: Address,
+}
+
+/// Published by `challenge_claim`; the defender must prove the claim by
+/// `prove_until` or lose the game
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ClaimChallenged {
+    #[topic]
+    pub session_id: u32,
+    #[topic]
+    pub defender: Address,
+    #[topic]
+    pub attacker: Address,
+    pub move_index: u32,
+    pub prove_until: u32,
+}
+
+/// Published by `prove_claim`
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ClaimProven {
+    #[topic]
+    pub session_id: u32,
+    #[topic]
+    pub defender: Address,
+    #[topic]
+    pub attacker: Address,
+    pub move_index: u32,
+}
+
+/// Published by `submit_move` when the attacker picks a cell
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MoveSubmitted {
+    #[topic]
+    pub session_id: u32,
+    #[topic]
+    pub defender: Address,
+    #[topic]
+    pub attacker: Address,
+    pub x: u32,
+    pub y: u32,
+    /// The attacker's annotation from `submit_move_with_note`, empty otherwise
+    pub note: Bytes,
+}
+
+/// Published by `make_move` once the defender's proof has been verified
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MoveMade {
+    #[topic]
+    pub session_id: u32,
+    #[topic]
+    pub defender: Address,
+    #[topic]
+    pub attacker: Address,
+    pub x: u32,
+    pub y: u32,
+    pub is_hit: bool,
+    pub verified: bool,
+}
+
+/// Published when a game ends, either after the last move or via `end_game`
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GameEnded {
+    #[topic]
+    pub session_id: u32,
+    #[topic]
+    pub defender: Address,
+    #[topic]
+    pub attacker: Address,
+    pub winner: Address,
+    pub hits: u32,
+    pub misses: u32,
+}
+
+/// Published by `set_config` with the new configuration
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConfigUpdated {
+    pub game_ttl_ledgers: u32,
+    pub emergency_delay_ledgers: u32,
+    pub proposal_expiry_ledgers: u32,
+    pub max_active_games: u32,
+    pub min_points: i128,
+    pub max_points: i128,
+    pub expiry_ledgers: u32,
+    pub expiry_policy: ExpiryPolicy,
+    pub appeal_window_ledgers: u32,
+    pub claim_delay_ledgers: u32,
+    pub reject_reused_roots: bool,
+    pub trap_count: u32,
+    pub move_gap_ledgers: u32,
+    pub grid_size: u32,
+    pub progressive_payout_bps: u32,
+    pub bonus_curve: PayoutCurve,
+    pub streak_bonus: StreakBonus,
+    pub min_games_completed: u32,
+}
+
+/// Published by `end_game` when it ends an expired game by the policy
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GameExpired {
+    #[topic]
+    pub session_id: u32,
+    #[topic]
+    pub defender: Address,
+    #[topic]
+    pub attacker: Address,
+    pub policy: ExpiryPolicy,
+}
+
+/// Published by `queue_emergency_withdraw`; play before `ready_ledger`
+/// cancels the withdrawal
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EmergencyWithdrawQueued {
+    #[topic]
+    pub session_id: u32,
+    #[topic]
+    pub defender: Address,
+    #[topic]
+    pub attacker: Address,
+    pub ready_ledger: u32,
+}
+
+/// Published when a move cancels a queued emergency withdrawal
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EmergencyWithdrawCancelled {
+    #[topic]
+    pub session_id: u32,
+    #[topic]
+    pub defender: Address,
+    #[topic]
+    pub attacker: Address,
+}
+
+/// Published by `appoint_arbiter`
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ArbiterAppointed {
+    #[topic]
+    pub session_id: u32,
+    #[topic]
+    pub defender: Address,
+    #[topic]
+    pub attacker: Address,
+    pub arbiter: Address,
+}
+
+/// Published by `appeal`; settlement waits for the arbiter
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ResultAppealed {
+    #[topic]
+    pub session_id: u32,
+    #[topic]
+    pub defender: Address,
+    #[topic]
+    pub attacker: Address,
+    pub appellant: Address,
+}
+
+/// Published by `resolve_appeal` with the settled winner
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AppealResolved {
+    #[topic]
+    pub session_id: u32,
+    #[topic]
+    pub defender: Address,
+    #[topic]
+    pub attacker: Address,
+    pub ruling: Ruling,
+    pub winner: Option<Address>,
+}
+
+/// Published by `freeze_session`; the game's moves and settlement wait
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SessionFrozen {
+    #[topic]
+    pub session_id: u32,
+    #[topic]
+    pub defender: Address,
+    #[topic]
+    pub attacker: Address,
+    pub by: Address,
+}
+
+/// Published by `unfreeze_session`
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SessionUnfrozen {
+    #[topic]
+    pub session_id: u32,
+    #[topic]
+    pub defender: Address,
+    #[topic]
+    pub attacker: Address,
+    pub by: Address,
+}
+
+/// Published when a proven move releases part of a sponsorship to the
+/// player it went against
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PayoutReleased {
+    #[topic]
+    pub session_id: u32,
+    #[topic]
+    pub defender: Address,
+    #[topic]
+    pub attacker: Address,
+    pub player: Address,
+    /// Paid to `player`, after the protocol fee
+    pub amount: i128,
+}
+
+/// Published at settlement when a decisive win earns a bonus from the pool
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BonusPaid {
+    #[topic]
+    pub session_id: u32,
+    #[topic]
+    pub defender: Address,
+    #[topic]
+    pub attacker: Address,
+    /// The winner's margin, in basis points
+    pub margin_bps: u32,
+    /// Added to the winnings, shared between the winner and its sponsor
+    pub amount: i128,
+}
+
+/// Published by `claim_winnings` once the sponsorships are paid out
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WinningsClaimed {
+    #[topic]
+    pub session_id: u32,
+    #[topic]
+    pub defender: Address,
+    #[topic]
+    pub attacker: Address,
+    pub winner: Address,
+}
+
+/// Published by `commit_grid`
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GridCommitted {
+    #[topic]
+    pub session_id: u32,
+    #[topic]
+    pub defender: Address,
+    #[topic]
+    pub attacker: Address,
+    pub trap_merkle_root: BytesN<32>,
+}
+
+/// Published by `emergency_withdraw` once the stakes are returned
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EmergencyWithdrawn {
+    #[topic]
+    pub session_id: u32,
+    #[topic]
+    pub defender: Address,
+    #[topic]
+    pub attacker: Address,
+}
+
+/// Published by `abort_due_to_verifier` for each vote short of an abort
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AbortRequested {
+    #[topic]
+    pub session_id: u32,
+    #[topic]
+    pub defender: Address,
+    #[topic]
+    pub attacker: Address,
+    pub voter: Address,
+}
+
+/// Published by `abort_due_to_verifier` once the stakes are returned
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GameAborted {
+    #[topic]
+    pub session_id: u32,
+    #[topic]
+    pub defender: Address,
+    #[topic]
+    pub attacker: Address,
+}
+
+/// Published at settlement when the loser's streak of wins ends
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StreakBroken {
+    #[topic]
+    pub session_id: u32,
+    #[topic]
+    pub defender: Address,
+    #[topic]
+    pub attacker: Address,
+    pub player: Address,
+    /// Wins the streak had
+    pub streak: u32,
+}
+
+/// Published by `start_practice`
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PracticeStarted {
+    #[topic]
+    pub player: Address,
+    pub grid_size: u32,
+    pub trap_count: u32,
+}
+
+/// Published by `finish_practice`, revealing the layout the player attacked:
+/// the traps found, then those left hidden
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PracticeFinished {
+    #[topic]
+    pub player: Address,
+    pub probes: u32,
+    pub hits: u32,
+    pub cleared: bool,
+    pub traps: Vec<u32>,
+}
+
+/// Published by `commit_daily`
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DailyCommitted {
+    #[topic]
+    pub day: u32,
+    pub grid_hash: BytesN<32>,
+}
+
+/// Published by `answer_daily`
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DailyAnswered {
+    #[topic]
+    pub day: u32,
+    #[topic]
+    pub player: Address,
+    pub cell: u32,
+    pub is_hit: bool,
+}
+
+/// Published by `reveal_daily`
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DailyRevealed {
+    #[topic]
+    pub day: u32,
+    pub traps: Vec<u32>,
+}
+
+/// Published by `rank_daily`; `place` is `None` off the leaderboard
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DailyRanked {
+    #[topic]
+    pub day: u32,
+    #[topic]
+    pub player: Address,
+    pub hits: u32,
+    pub probes: u32,
+    pub place: Option<u32>,
+    /// Answers the player was given that the layout contradicts
+    pub misanswered: u32,
+}
+
+// ============================================================================
+// Storage TTL Management
+// ============================================================================
+
+// Defaults of `ContractConfig`
+const GAME_TTL_LEDGERS: u32 = 518_400; // 30 days
+const EMERGENCY_DELAY_LEDGERS: u32 = 51_840; // 3 days
+const PROPOSAL_EXPIRY_LEDGERS: u32 = 120_960; // 7 days
+const APPEAL_WINDOW_LEDGERS: u32 = 17_280; // 1 day
+const TRAP_COUNT: u32 = 10;
+
+const REFERRAL_TTL_LEDGERS: u32 = 518_400; // 30 days
+const HEATMAP_TTL_LEDGERS: u32 = 518_400; // 30 days
+const DAILY_TTL_LEDGERS: u32 = 518_400; // 30 days
+const USED_ROOT_TTL_LEDGERS: u32 = 3_110_400; // 180 days
+const USED_SETUP_TTL_LEDGERS: u32 = 3_110_400; // 180 days
+const MIN_DELAY_LEDGERS: u32 = 17_280; // 1 day
+const CHANNEL_CHALLENGE_LEDGERS: u32 = 17_280; // 1 day
+const CLAIM_CHALLENGE_LEDGERS: u32 = 720; // 1 hour
+const MAX_TTL_LEDGERS: u32 = 3_110_400; // 180 days, the network's maximum
+/// Longest note `submit_move_with_note` publishes with a move
+pub const MAX_NOTE_BYTES: u32 = 32;
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+fn config(env: &Env) -> ContractConfig {
+    env.storage()
+        .instance()
+        .get(&DataKey::Config)
+        .unwrap_or(ContractConfig::DEFAULT)
+}
+
+fn game_ttl(env: &Env) -> u32 {
+    config(env).game_ttl_ledgers
+}
+
+/// Timelocks must last at least a day, and end while the game or proposal
+/// they belong to is still stored, as must expiry; stakes must be positive,
+/// grids fit the circuits and hold at least one trap and one safe cell,
+/// progressive payouts release at most the whole stake, and a bonus at most
+/// doubles the winnings
+fn check_config(config: &ContractConfig) -> Result<(), Error> {
+    let ttl = config.game_ttl_ledgers;
+    let in_bounds = |ledgers: u32| (MIN_DELAY_LEDGERS..ttl).contains(&ledgers);
+    if !(MIN_DELAY_LEDGERS..=MAX_TTL_LEDGERS).contains(&ttl)
+        || !in_bounds(config.emergency_delay_ledgers)
+        || !in_bounds(config.proposal_expiry_ledgers)
+        || config.min_points < 1
+        || config.min_points > config.max_points
+        || (config.expiry_ledgers != 0 && !in_bounds(config.expiry_ledgers))
+        || !in_bounds(config.appeal_window_ledgers)
+        || (config.claim_delay_ledgers != 0 && !in_bounds(config.claim_delay_ledgers))
+        || !(MIN_GRID_SIZE..=MAX_GRID_SIZE).contains(&config.grid_size)
+        || !(1..config.grid_size * config.grid_size).contains(&config.trap_count)
+        || config.move_gap_ledgers >= MIN_DELAY_LEDGERS
+        || config.progressive_payout_bps > MAX_BPS
+        || config.bonus_curve.max_bonus_bps > MAX_BPS
+        || config.bonus_curve.threshold_bps >= MAX_BPS
+        || !(1..=3).contains(&config.bonus_curve.exponent)
+        || config.streak_bonus.fee_rebate_bps > MAX_BPS
+    {
+        return Err(Error::InvalidConfig);
+    }
+    Ok(())
+}
+
+/// Count a game out of the games in progress, adding its moves to the
+/// totals and heatmap, and its points too if it was settled with a winner;
+/// its claim bonds are released
+fn game_finished(env: &Env, session_id: u32, game: &Game, moves: &Vec<Move>, completed: bool) {
+    release_bonds(env, session_id, game);
+    let active: u32 = env
+        .storage()
+        .instance()
+        .get(&DataKey::ActiveGames)
+        .unwrap_or(0);
+    env.storage()
+        .instance()
+        .set(&DataKey::ActiveGames, &active.saturating_sub(1));
+
+    let mut stats = TrapGridContract::get_global_stats(env.clone());
+    stats.total_moves += u64::from(game.moves_made);
+    stats.total_hits += u64::from(game.hits);
+    if completed {
+        stats.games_completed += 1;
+        for player in [&game.defender, &game.attacker] {
+            let key = DataKey::GamesCompleted(player.clone());
+            let count: u32 = env.storage().persistent().get(&key).unwrap_or(0);
+            env.storage().persistent().set(&key, &(count + 1));
+            env.storage()
+                .persistent()
+                .extend_ttl(&key, MAX_TTL_LEDGERS, MAX_TTL_LEDGERS);
+        }
+    }
+    if game.winner.is_some() {
+        stats.points_settled = stats
+            .points_settled
+            .saturating_add(game.defender_points)
+            .saturating_add(game.attacker_points);
+    }
+    env.storage().instance().set(&DataKey::GlobalStats, &stats);
+    archive_game(env, session_id, game, moves);
+
+    let mut heatmap = TrapGridContract::get_heatmap(env.clone());
+    // Games on a grid the configuration no longer uses are left out
+    if moves.is_empty() || heatmap.probes.len() != game.cells() {
+        return;
+    }
+    for m in moves.iter() {
+        let cell = m.x * game.grid_size + m.y;
+        heatmap
+            .probes
+            .set(cell, heatmap.probes.get_unchecked(cell) + 1);
+        if m.is_hit {
+            heatmap.hits.set(cell, heatmap.hits.get_unchecked(cell) + 1);
+        }
+    }
+    let key = DataKey::Heatmap;
+    env.storage().persistent().set(&key, &heatmap);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, HEATMAP_TTL_LEDGERS, HEATMAP_TTL_LEDGERS);
+}
+
+// ============================================================================
+// Channels
+// ============================================================================
+
+/// Fails with `ChannelConflict` while the game has an open channel, which
+/// takes the place of on-chain moves
+fn check_no_channel(env: &Env, session_id: u32) -> Result<(), Error> {
+    if env.storage().temporary().has(&DataKey::Channel(session_id)) {
+        return Err(Error::ChannelConflict);
+    }
+    Ok(())
+}
+
+/// Fails with `GameFrozen` while the game's arbiter or the admin holds it
+/// with `freeze_session`
+fn check_not_frozen(env: &Env, session_id: u32) -> Result<(), Error> {
+    if env.storage().temporary().has(&DataKey::Frozen(session_id)) {
+        return Err(Error::GameFrozen);
+    }
+    Ok(())
+}
+
+/// What both channel keys sign: the XDR of `(contract, session_id, state)`,
+/// so a signature counts for one game of one contract
+fn channel_message(env: &Env, session_id: u32, state: &ChannelState) -> Bytes {
+    (env.current_contract_address(), session_id, state.clone()).to_xdr(env)
+}
+
+/// Hash chain of a channel's moves: from 32 zero bytes, each move hashes the
+/// previous hash, its x, y and is_hit as a byte each, and its proof hash
+fn transcript_hash(env: &Env, moves: &Vec<ChannelMove>) -> BytesN<32> {
+    let mut hash = BytesN::from_array(env, &[0; 32]);
+    for m in moves.iter() {
+        let mut bytes = Bytes::from(hash);
+        bytes.extend_from_array(&[m.x as u8, m.y as u8, m.is_hit as u8]);
+        bytes.append(&m.proof_hash.into());
+        hash = env.crypto().sha256(&bytes).into();
+    }
+    hash
+}
+
+// ============================================================================
+// Optimistic Play
+// ============================================================================
+
+/// Fails with `ChallengeWindowOpen` while a claim is challenged or an
+/// unproven claim can still be
+fn check_claims_settled(
+    env: &Env,
+    optimistic: &Optimistic,
+    moves: &Vec<Move>,
+) -> Result<(), Error> {
+    let sequence = env.ledger().sequence();
+    let open = optimistic.claims.iter().any(|(index, ledger)| {
+        sequence <= ledger + CLAIM_CHALLENGE_LEDGERS
+            && moves.get(index).is_some_and(|m| !m.verified)
+    });
+    if optimistic.challenged.is_some() || open {
+        return Err(Error::ChallengeWindowOpen);
+    }
+    Ok(())
+}
+
+/// Whether the defender let a challenge's deadline pass without a proof
+fn claim_forfeited(env: &Env, optimistic: &Option<Optimistic>) -> bool {
+    optimistic.as_ref().is_some_and(|optimistic| {
+        optimistic.challenged.is_some() && env.ledger().sequence() > optimistic.prove_until
+    })
+}
+
+/// Pay an ending optimistic game's claim bonds to the attacker if a
+/// challenge was forfeited, or back to the defender otherwise
+fn release_bonds(env: &Env, session_id: u32, game: &Game) {
+    let key = DataKey::Optimistic(session_id);
+    let optimistic: Option<Optimistic> = env.storage().temporary().get(&key);
+    let forfeited = claim_forfeited(env, &optimistic);
+    let Some(mut optimistic) = optimistic.filter(|optimistic| optimistic.bonded > 0) else {
+        return;
+    };
+    let to = if forfeited {
+        &game.attacker
+    } else {
+        &game.defender
+    };
+    token::Client::new(env, &optimistic.token).transfer(
+        &env.current_contract_address(),
+        to,
+        &optimistic.bonded,
+    );
+    optimistic.bonded = 0;
+    env.storage().temporary().set(&key, &optimistic);
+}
+
+// ============================================================================
+// Game Records
+// ============================================================================
+
+/// Load a game, migrating a record written by an older contract version to
+/// the current [`Game`]. The migrated game is stored the next time it
+/// changes; a record of an unknown schema is a bug and aborts.
+fn load_game(env: &Env, session_id: u32) -> Result<Game, Error> {
+    let record: Val = env
+        .storage()
+        .temporary()
+        .get(&DataKey::Game(session_id))
+        .ok_or(Error::GameNotFound)?;
+    let fields = Map::<Symbol, Val>::try_from_val(env, &record).expect("game record is a map");
+    let schema_version = fields
+        .get(Symbol::new(env, "schema_version"))
+        .map_or(1, |version| {
+            u32::try_from_val(env, &version).expect("schema version is a u32")
+        });
+    let legacy = match schema_version {
+        1 | 2 => {
+            let game = match schema_version {
+                1 => {
+                    GameV2::from(GameV1::try_from_val(env, &record).expect("schema 1 game record"))
+                }
+                _ => GameV2::try_from_val(env, &record).expect("schema 2 game record"),
+            };
+            GameV4 {
+                last_active_ledger: env.ledger().sequence(),
+                ..GameV3::from(game).into()
+            }
+        }
+        3 => GameV3::try_from_val(env, &record)
+            .expect("schema 3 game record")
+            .into(),
+        4 => GameV4::try_from_val(env, &record).expect("schema 4 game record"),
+        GAME_SCHEMA_VERSION => return Ok(Game::try_from_val(env, &record).expect("game record")),
+        _ => panic!("unknown game schema version"),
+    };
+    Ok(Game {
+        trap_count: config(env).trap_count,
+        ..legacy.into()
+    })
+}
+
+/// The checks `make_move` makes before verifying the proof: the game is in
+/// play, the cell is on the grid and still open, and it is the cell the
+/// attacker submitted
+fn check_move(
+    game: &Game,
+    moves: &Vec<Move>,
+    pending: &Option<PendingMove>,
+    x: u32,
+    y: u32,
+) -> Result<(), Error> {
+    if !game.game_started {
+        return Err(Error::GameNotStarted);
+    }
+    if game.game_ended {
+        return Err(Error::GameAlreadyEnded);
+    }
+    if x >= game.grid_size || y >= game.grid_size {
+        return Err(Error::InvalidMove);
+    }
+    if moves.iter().any(|m| m.x == x && m.y == y) {
+        return Err(Error::MoveAlreadyMade);
+    }
+    // Only the attacker picks cells: the defender answers the submitted one
+    match pending {
+        Some(pending) if pending.x == x && pending.y == y => Ok(()),
+        Some(_) => Err(Error::MovePending),
+        None => Err(Error::NoPendingMove),
+    }
+}
+
+/// Fails with `MoveTooSoon` unless `move_gap_ledgers` have passed since the
+/// attacker's last move, when the attacker moves, and since the defender's
+/// last answer, when the defender answers
+fn check_move_gap(env: &Env, session_id: u32, attacker: bool, defender: bool) -> Result<(), Error> {
+    let gap = config(env).move_gap_ledgers;
+    if gap == 0 {
+        return Ok(());
+    }
+    let last: LastMoves = env
+        .storage()
+        .temporary()
+        .get(&DataKey::LastMoves(session_id))
+        .unwrap_or_default();
+    let sequence = env.ledger().sequence();
+    let too_soon =
+        |ledger: Option<u32>| ledger.is_some_and(|ledger| sequence < ledger.saturating_add(gap));
+    if (attacker && too_soon(last.attacker_ledger)) || (defender && too_soon(last.defender_ledger))
+    {
+        return Err(Error::MoveTooSoon);
+    }
+    Ok(())
+}
+
+/// Record the ledger of an attacker's move and of a defender's answer, as
+/// they happen; only while a move gap is configured, so games without one
+/// store nothing
+fn record_move_ledgers(env: &Env, session_id: u32, attacker: bool, defender: bool) {
+    let config = config(env);
+    if config.move_gap_ledgers == 0 {
+        return;
+    }
+    let key = DataKey::LastMoves(session_id);
+    let mut last: LastMoves = env.storage().temporary().get(&key).unwrap_or_default();
+    let sequence = env.ledger().sequence();
+    if attacker {
+        last.attacker_ledger = Some(sequence);
+    }
+    if defender {
+        last.defender_ledger = Some(sequence);
+    }
+    let ttl = config.game_ttl_ledgers;
+    env.storage().temporary().set(&key, &last);
+    env.storage().temporary().extend_ttl(&key, ttl, ttl);
+}
+
+// ============================================================================
+// Public Inputs
+// ============================================================================
+
+/// Whether the verifier stored under `verifier_key` accepts `proof` for
+/// `public_inputs`; `verify_proof` fails with a contract error otherwise
+fn proof_verifies(env: &Env, verifier_key: &DataKey, public_inputs: &Bytes, proof: &Bytes) -> bool {
+    let verifier: Address = env
+        .storage()
+        .instance()
+        .get(verifier_key)
+        .expect("Verifier address not set");
+    VerifierClient::new(env, &verifier)
+        .try_verify_proof(public_inputs, proof)
+        .is_ok()
+}
+
+/// The account key or contract id of `address`, the last 32 bytes of its XDR
+fn address_key(env: &Env, address: &Address) -> [u8; 32] {
+    let xdr = address.to_xdr(env);
+    let mut key = [0u8; 32];
+    xdr.slice(xdr.len() - 32..).copy_into_slice(&mut key);
+    key
+}
+
+/// Whether `public_inputs` are the position-movement circuit's encoding of
+/// (x, y, is_hit) on `game`'s grid, opened against the grid commitment the
+/// game's setup proof bound to its defender
+fn public_inputs_match(
+    env: &Env,
+    session_id: u32,
+    public_inputs: &Bytes,
+    game: &Game,
+    x: u32,
+    y: u32,
+    is_hit: bool,
+) -> bool {
+    let Some(grid_commitment) = env
+        .storage()
+        .temporary()
+        .get::<_, BytesN<32>>(&DataKey::GridSetup(session_id))
+    else {
+        return false;
+    };
+    if public_inputs.len() as usize != PositionMovement::LEN {
+        return false;
+    }
+    let mut buf = [0u8; PositionMovement::LEN];
+    public_inputs.copy_into_slice(&mut buf);
+    PositionMovement::decode(&buf)
+        == Ok(PositionMovement {
+            move_x: x,
+            move_y: y,
+            is_hit,
+            grid_size: game.grid_size,
+            grid_commitment: grid_commitment.to_array(),
+        })
+}
+
+// ============================================================================
+// Sponsorship
+// ============================================================================
+
+const MAX_BPS: u32 = 10_000;
+
+/// Check the sponsorships of a game between `defender` and `attacker`: at
+/// most one per player, all in one token, which is returned
+fn sponsorship_token(
+    sponsorships: &Vec<Sponsorship>,
+    defender: &Address,
+    attacker: &Address,
+) -> Result<Address, Error> {
+    let first = sponsorships.first().ok_or(Error::InvalidSponsorship)?;
+    let (mut defender_backed, mut attacker_backed) = (false, false);
+    for sponsorship in sponsorships.iter() {
+        let backed = if sponsorship.player == *defender {
+            &mut defender_backed
+        } else if sponsorship.player == *attacker {
+            &mut attacker_backed
+        } else {
+            return Err(Error::InvalidSponsorship);
+        };
+        if *backed
+            || sponsorship.amount <= 0
+            || sponsorship.player_share_bps > MAX_BPS
+            || sponsorship.token != first.token
+        {
+            return Err(Error::InvalidSponsorship);
+        }
+        *backed = true;
+    }
+    Ok(first.token)
+}
+
+/// Pay out the escrowed stakes of an ended game: the winning side's sponsor
+/// gets its stake back and the winnings split with its player; an
+/// unsponsored winner takes the winnings alone
+fn settle_sponsorships(env: &Env, session_id: u32, game: &Game) {
+    let key = DataKey::Sponsorships(session_id);
+    let Some(sponsorships) = env
+        .storage()
+        .temporary()
+        .get::<DataKey, Vec<Sponsorship>>(&key)
+    else {
+        return;
+    };
+    env.storage().temporary().remove(&key);
+    let Some(first) = sponsorships.first() else {
+        return;
+    };
+
+    let winner = game.winner.clone().expect("ended game has a winner");
+    let won = sponsorships.iter().find(|s| s.player == winner);
+    let lost = sponsorships.iter().find(|s| s.player != winner);
+    let token = token::Client::new(env, &first.token);
+    let contract = env.current_contract_address();
+    let stake = lost.map_or(0, |s| s.amount);
+    let rebate_bps = streak_rebate_bps(env, &winner);
+    let mut winnings = stake - take_fee(env, &token, game, stake, rebate_bps);
+    winnings += pay_bonus(env, session_id, game, &first.token, winnings);
+    let player_cut = match &won {
+        Some(s) => winnings * i128::from(s.player_share_bps) / i128::from(MAX_BPS),
+        None => winnings,
+    };
+    if player_cut > 0 {
+        token.transfer(&contract, &winner, &player_cut);
+    }
+    if let Some(s) = won {
+        token.transfer(&contract, &s.sponsor, &(s.amount + winnings - player_cut));
+    }
+}
+
+/// Take the bonus a game's margin over its verified moves earns on
+/// `winnings` out of the `token` pool, as far as the pool holds it; returns
+/// the bonus
+fn pay_bonus(env: &Env, session_id: u32, game: &Game, token: &Address, winnings: i128) -> i128 {
+    let moves = TrapGridContract::get_moves(env.clone(), session_id);
+    let margin_bps = payout::margin_bps(game, &moves);
+    let bonus_bps = payout::bonus_bps(&config(env).bonus_curve, margin_bps);
+    let key = DataKey::BonusPool(token.clone());
+    let pool: i128 = env.storage().persistent().get(&key).unwrap_or(0);
+    let bonus = (winnings * i128::from(bonus_bps) / i128::from(MAX_BPS)).min(pool);
+    if bonus <= 0 {
+        return 0;
+    }
+    env.storage().persistent().set(&key, &(pool - bonus));
+    BonusPaid {
+        session_id,
+        defender: game.defender.clone(),
+        attacker: game.attacker.clone(),
+        margin_bps,
+        amount: bonus,
+    }
+    .publish(env);
+    bonus
+}
+
+/// Return every sponsor's stake of a game ended without a winner
+fn refund_sponsorships(env: &Env, session_id: u32) {
+    let sponsorships = TrapGridContract::get_sponsorships(env.clone(), session_id);
+    if let Some(first) = sponsorships.first() {
+        let token = token::Client::new(env, &first.token);
+        let contract = env.current_contract_address();
+        for sponsorship in sponsorships.iter() {
+            token.transfer(&contract, &sponsorship.sponsor, &sponsorship.amount);
+        }
+    }
+    env.storage()
+        .temporary()
+        .remove(&DataKey::Sponsorships(session_id));
+}
+
+/// Release `progressive_payout_bps` of what remains of the sponsorship a
+/// proven move went against to the other player: the defender's on a hit,
+/// the attacker's on a miss. Settlement and refunds see only the rest.
+fn release_progressive(env: &Env, session_id: u32, game: &Game, is_hit: bool) {
+    let bps = config(env).progressive_payout_bps;
+    let key = DataKey::Sponsorships(session_id);
+    let Some(mut sponsorships) = env
+        .storage()
+        .temporary()
+        .get::<DataKey, Vec<Sponsorship>>(&key)
+        .filter(|_| bps != 0)
+    else {
+        return;
+    };
+    let (loser, player) = if is_hit {
+        (&game.defender, &game.attacker)
+    } else {
+        (&game.attacker, &game.defender)
+    };
+    let Some(index) = sponsorships.iter().position(|s| s.player == *loser) else {
+        return;
+    };
+    let index = index as u32;
+    let mut sponsorship = sponsorships.get(index).expect("position is in range");
+    let released = sponsorship.amount * i128::from(bps) / i128::from(MAX_BPS);
+    if released == 0 {
+        return;
+    }
+    sponsorship.amount -= released;
+    let token = token::Client::new(env, &sponsorship.token);
+    sponsorships.set(index, sponsorship);
+    env.storage().temporary().set(&key, &sponsorships);
+
+    let amount = released - take_fee(env, &token, game, released, 0);
+    if amount > 0 {
+        token.transfer(&env.current_contract_address(), player, &amount);
+    }
+    PayoutReleased {
+        session_id,
+        defender: game.defender.clone(),
+        attacker: game.attacker.clone(),
+        player: player.clone(),
+        amount,
+    }
+    .publish(env);
+}
+
+/// Record the attacker's next cell as pending and publish it with `note`
+fn submit(env: &Env, session_id: u32, x: u32, y: u32, note: Bytes) -> Result<(), Error> {
+    let mut game = load_game(env, session_id)?;
+    game.attacker.require_auth();
+
+    if !game.game_started {
+        return Err(Error::GameNotStarted);
+    }
+    if game.game_ended {
+        return Err(Error::GameAlreadyEnded);
+    }
+    if x >= game.grid_size || y >= game.grid_size {
+        return Err(Error::InvalidMove);
+    }
+
+    let pending_key = DataKey::PendingMove(session_id);
+    if env.storage().temporary().has(&pending_key) {
+        return Err(Error::MovePending);
+    }
+    let moves: Vec<Move> = env
+        .storage()
+        .temporary()
+        .get(&DataKey::Moves(session_id))
+        .unwrap_or(vec![env]);
+    if moves.iter().any(|m| m.x == x && m.y == y) {
+        return Err(Error::MoveAlreadyMade);
+    }
+    check_no_channel(env, session_id)?;
+    check_not_frozen(env, session_id)?;
+    check_move_gap(env, session_id, true, false)?;
+
+    let ttl = game_ttl(env);
+    record_move_ledgers(env, session_id, true, false);
+    env.storage()
+        .temporary()
+        .set(&pending_key, &PendingMove { x, y });
+    env.storage().temporary().extend_ttl(&pending_key, ttl, ttl);
+    game.last_active_ledger = env.ledger().sequence();
+    env.storage()
+        .temporary()
+        .set(&DataKey::Game(session_id), &game);
+
+    resume_play(env, session_id, &game);
+    MoveSubmitted {
+        session_id,
+        defender: game.defender.clone(),
+        attacker: game.attacker.clone(),
+        x,
+        y,
+        note,
+    }
+    .publish(env);
+    Ok(())
+}
+
+/// Cancel a queued emergency withdrawal, and the votes to abort, of a game a
+/// player has resumed
+fn resume_play(env: &Env, session_id: u32, game: &Game) {
+    env.storage()
+        .temporary()
+        .remove(&DataKey::AbortVotes(session_id));
+    let key = DataKey::EmergencyWithdraw(session_id);
+    if env.storage().temporary().has(&key) {
+        env.storage().temporary().remove(&key);
+        EmergencyWithdrawCancelled {
+            session_id,
+            defender: game.defender.clone(),
+            attacker: game.attacker.clone(),
+        }
+        .publish(env);
+    }
+}
+
+// ============================================================================
+// Admin Set
+// ============================================================================
+
+fn admin(env: &Env) -> Address {
+    env.storage()
+        .instance()
+        .get(&DataKey::Admin)
+        .expect("Admin not set")
+}
+
+fn admin_set(env: &Env) -> AdminSet {
+    env.storage()
+        .instance()
+        .get(&DataKey::AdminSet)
+        .unwrap_or_else(|| AdminSet {
+            members: vec![env, admin(env)],
+            threshold: 1,
+        })
+}
+
+/// Check `member` belongs to the admin set and authorized the call
+fn require_member(env: &Env, member: &Address) -> Result<AdminSet, Error> {
+    member.require_auth();
+    let set = admin_set(env);
+    if !set.members.contains(member) {
+        return Err(Error::NotAdminMember);
+    }
+    Ok(set)
+}
+
+/// Reject an action that would fail or break the contract when run
+fn check_action(action: &AdminAction) -> Result<(), Error> {
+    match action {
+        AdminAction::SetFees(protocol_bps, referral_bps) => {
+            if *protocol_bps > MAX_BPS || *referral_bps > MAX_BPS / 2 {
+                return Err(Error::InvalidFees);
+            }
+        }
+        AdminAction::SetAdminSet(members, threshold) => {
+            if *threshold == 0 || *threshold > members.len() {
+                return Err(Error::InvalidThreshold);
+            }
+        }
+        AdminAction::SetVerifier(_)
+        | AdminAction::SetSetupVerifier(_)
+        | AdminAction::Upgrade(_)
+        | AdminAction::SetAdmin(_) => {}
+    }
+    Ok(())
+}
+
+fn run_action(env: &Env, action: AdminAction) {
+    let storage = env.storage().instance();
+    match action {
+        AdminAction::SetFees(protocol_bps, referral_bps) => storage.set(
+            &DataKey::Fees,
+            &Fees {
+                protocol_bps,
+                referral_bps,
+            },
+        ),
+        AdminAction::SetVerifier(verifier) => storage.set(&DataKey::VerifierAddress, &verifier),
+        AdminAction::SetSetupVerifier(verifier) => {
+            storage.set(&DataKey::SetupVerifierAddress, &verifier)
+        }
+        AdminAction::Upgrade(wasm_hash) => env.deployer().update_current_contract_wasm(wasm_hash),
+        AdminAction::SetAdmin(admin) => storage.set(&DataKey::Admin, &admin),
+        AdminAction::SetAdminSet(members, threshold) => {
+            storage.set(&DataKey::AdminSet, &AdminSet { members, threshold })
+        }
+    }
+}
+
+/// Run `proposal` if enough current members approved it; returns whether
+/// it ran
+fn try_run_proposal(env: &Env, proposal_id: u32, proposal: &Proposal, set: &AdminSet) -> bool {
+    let approvals = proposal
+        .approvals
+        .iter()
+        .filter(|a| set.members.contains(a))
+        .count() as u32;
+    let key = DataKey::Proposal(proposal_id);
+    if approvals < set.threshold {
+        env.storage().temporary().set(&key, proposal);
+        return false;
+    }
+    env.storage().temporary().remove(&key);
+    run_action(env, proposal.action.clone());
+    true
+}
+
+/// Check `caller` is an operator or the admin and authorized the call; an
+/// operator's role is kept stored for another `MAX_TTL_LEDGERS`
+fn require_operator(env: &Env, caller: &Address) -> Result<(), Error> {
+    caller.require_auth();
+    if *caller == admin(env) {
+        return Ok(());
+    }
+    let key = DataKey::Operator(caller.clone());
+    if !env.storage().persistent().has(&key) {
+        return Err(Error::NotOperator);
+    }
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MAX_TTL_LEDGERS, MAX_TTL_LEDGERS);
+    Ok(())
+}
+
+/// Store a daily puzzle's record for `DAILY_TTL_LEDGERS`
+fn save_daily<V: IntoVal<Env, Val>>(env: &Env, key: &DataKey, value: &V) {
+    env.storage().persistent().set(key, value);
+    env.storage()
+        .persistent()
+        .extend_ttl(key, DAILY_TTL_LEDGERS, DAILY_TTL_LEDGERS);
+}
+
+// ============================================================================
+// Referrals
+// ============================================================================
+
+/// Take the protocol fee out of `winnings`, less `rebate_bps` of it,
+/// accruing each player's referrer its share and paying the rest to the
+/// admin; returns the fee
+fn take_fee(
+    env: &Env,
+    token: &token::Client,
+    game: &Game,
+    winnings: i128,
+    rebate_bps: u32,
+) -> i128 {
+    let Some(fees) = env
+        .storage()
+        .instance()
+        .get::<DataKey, Fees>(&DataKey::Fees)
+    else {
+        return 0;
+    };
+    let fee = winnings * i128::from(fees.protocol_bps) / i128::from(MAX_BPS);
+    let fee = fee - fee * i128::from(rebate_bps) / i128::from(MAX_BPS);
+    let share = fee * i128::from(fees.referral_bps) / i128::from(MAX_BPS);
+    let mut to_admin = fee;
+    for player in [&game.defender, &game.attacker] {
+        let referrer = env
+            .storage()
+            .persistent()
+            .get::<DataKey, Address>(&DataKey::Referrer(player.clone()));
+        if let (Some(referrer), true) = (referrer, share > 0) {
+            let key = DataKey::ReferralRewards(referrer, token.address.clone());
+            let accrued: i128 = env.storage().persistent().get(&key).unwrap_or(0);
+            env.storage().persistent().set(&key, &(accrued + share));
+            env.storage()
+                .persistent()
+                .extend_ttl(&key, REFERRAL_TTL_LEDGERS, REFERRAL_TTL_LEDGERS);
+            to_admin -= share;
+        }
+    }
+    if to_admin > 0 {
+        token.transfer(&env.current_contract_address(), admin(env), &to_admin);
+    }
+    fee
+}
+
+// ============================================================================
+// Settlement
+// ============================================================================
+
+/// Settle an ended game's result: report it to the Game Hub, count it, pay
+/// the sponsorships and mint the trophy
+fn settle(env: &Env, session_id: u32, game: &Game, moves: &Vec<Move>) {
+    let defender_won = game.winner.as_ref() == Some(&game.defender);
+    let game_hub_addr: Address = env
+        .storage()
+        .instance()
+        .get(&DataKey::GameHubAddress)
+        .expect("GameHub address not set");
+    GameHubClient::new(env, &game_hub_addr).end_game(&session_id, &defender_won);
+    game_finished(env, session_id, game, moves, true);
+    mint_trophy(env, session_id, game);
+    record_streaks(env, session_id, game);
+
+    let config = config(env);
+    let sponsorships_key = DataKey::Sponsorships(session_id);
+    if config.claim_delay_ledgers == 0 || !env.storage().temporary().has(&sponsorships_key) {
+        settle_sponsorships(env, session_id, game);
+        return;
+    }
+    // The stakes stay escrowed until the winner claims them
+    let ttl = config.game_ttl_ledgers;
+    let key = DataKey::Claim(session_id);
+    let claimable_from = env.ledger().sequence() + config.claim_delay_ledgers;
+    env.storage().temporary().set(&key, &claimable_from);
+    env.storage().temporary().extend_ttl(&key, ttl, ttl);
+    env.storage()
+        .temporary()
+        .extend_ttl(&sponsorships_key, ttl, ttl);
+}
+
+/// Settle a game's result now, or open its appeal window if it has an
+/// arbiter
+fn post_result(env: &Env, session_id: u32, game: &Game, moves: &Vec<Move>) {
+    let key = DataKey::Arbitration(session_id);
+    let Some(mut arbitration) = env.storage().temporary().get::<DataKey, Arbitration>(&key) else {
+        settle(env, session_id, game, moves);
+        return;
+    };
+    let config = config(env);
+    let ttl = config.game_ttl_ledgers;
+    arbitration.appeal_until = env.ledger().sequence() + config.appeal_window_ledgers;
+    env.storage().temporary().set(&key, &arbitration);
+    env.storage().temporary().extend_ttl(&key, ttl, ttl);
+}
+
+/// Check `caller` is the admin or the game's arbiter and authorized the
+/// call; returns the game
+fn require_freezer(env: &Env, caller: &Address, session_id: u32) -> Result<Game, Error> {
+    caller.require_auth();
+    let game = load_game(env, session_id)?;
+    let arbiter = env
+        .storage()
+        .temporary()
+        .get::<DataKey, Arbitration>(&DataKey::Arbitration(session_id))
+        .map(|arbitration| arbitration.arbiter);
+    if *caller != admin(env) && arbiter.as_ref() != Some(caller) {
+        return Err(Error::NotArbiter);
+    }
+    Ok(game)
+}
+
+/// The arbitration of a game whose result waits to be settled
+fn pending_arbitration(env: &Env, session_id: u32, game: &Game) -> Result<Arbitration, Error> {
+    env.storage()
+        .temporary()
+        .get(&DataKey::Arbitration(session_id))
+        .filter(|_| game.game_ended)
+        .ok_or(Error::NotArbitrated)
+}
+
+// ============================================================================
+// Streaks
+// ============================================================================
+
+/// Extend the winner's streak of a settled game and end the loser's,
+/// publishing `StreakBroken` if the loser had one
+fn record_streaks(env: &Env, session_id: u32, game: &Game) {
+    let winner = game.winner.clone().expect("ended game has a winner");
+    let loser = if winner == game.attacker {
+        &game.defender
+    } else {
+        &game.attacker
+    };
+
+    let mut streak = TrapGridContract::get_streak(env.clone(), winner.clone());
+    streak.current += 1;
+    streak.best = streak.best.max(streak.current);
+    save_streak(env, &winner, &streak);
+
+    let mut streak = TrapGridContract::get_streak(env.clone(), loser.clone());
+    if streak.current == 0 {
+        return;
+    }
+    StreakBroken {
+        session_id,
+        defender: game.defender.clone(),
+        attacker: game.attacker.clone(),
+        player: loser.clone(),
+        streak: streak.current,
+    }
+    .publish(env);
+    streak.current = 0;
+    save_streak(env, loser, &streak);
+}
+
+fn save_streak(env: &Env, player: &Address, streak: &Streak) {
+    let key = DataKey::Streak(player.clone());
+    env.storage().persistent().set(&key, streak);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MAX_TTL_LEDGERS, MAX_TTL_LEDGERS);
+}
+
+/// Share of the protocol fee waived on `winner`'s winnings for its streak
+fn streak_rebate_bps(env: &Env, winner: &Address) -> u32 {
+    let bonus = config(env).streak_bonus;
+    let streak = TrapGridContract::get_streak(env.clone(), winner.clone());
+    if bonus.min_streak == 0 || streak.current < bonus.min_streak {
+        return 0;
+    }
+    bonus.fee_rebate_bps
+}
+
+// ============================================================================
+// Trophies
+// ============================================================================
+
+/// Mint the winner of an ended game a trophy, if a trophy contract is set.
+/// The attacker scores its hits, the defender the attacker's misses. A
+/// trophy contract that refuses to mint does not hold up the settlement.
+fn mint_trophy(env: &Env, session_id: u32, game: &Game) {
+    let Some(trophy) = env
+        .storage()
+        .instance()
+        .get::<DataKey, Address>(&DataKey::TrophyAddress)
+    else {
+        return;
+    };
+    let winner = game.winner.clone().expect("ended game has a winner");
+    let (opponent, score) = if winner == game.attacker {
+        (game.defender.clone(), game.hits)
+    } else {
+        (game.attacker.clone(), game.misses)
+    };
+    let _ = TrophyClient::new(env, &trophy).try_mint(
+        &env.current_contract_address(),
+        &winner,
+        &session_id,
+        &opponent,
+        &score,
+    );
+}
+
+// ============================================================================
+// Archive
+// ============================================================================
+
+/// Push a finished game to the archive contract, if one is set. An archive
+/// that refuses the record does not hold up the game.
+fn archive_game(env: &Env, session_id: u32, game: &Game, moves: &Vec<Move>) {
+    let Some(archive) = env
+        .storage()
+        .instance()
+        .get::<DataKey, Address>(&DataKey::ArchiveAddress)
+    else {
+        return;
+    };
+    let record = ArchiveRecord {
+        session_id,
+        defender: game.defender.clone(),
+        attacker: game.attacker.clone(),
+        config_hash: env.crypto().sha256(&config(env).to_xdr(env)).into(),
+        winner: game.winner.clone(),
+        hits: game.hits,
+        misses: game.misses,
+        replay: replay::encode(env, moves),
+    };
+    let _ = ArchiveClient::new(env, &archive).try_archive(&env.current_contract_address(), &record);
+}
+
+// ============================================================================
+// Contract Definition
+// ============================================================================
+
+#[contract]
+pub struct TrapGridContract;
+
+#[contractimpl]
+impl TrapGridContract {
+    /// Initialize the contract with GameHub, Verifier addresses and admin;
+    /// `setup_verifier` verifies the grid-setup circuit's proofs
+    pub fn __constructor(
+        env: Env,
+        admin: Address,
+        game_hub: Address,
+        verifier: Address,
+        setup_verifier: Address,
+    ) {
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.storage()
+            .instance()
+            .set(&DataKey::GameHubAddress, &game_hub);
+        env.storage()
+            .instance()
+            .set(&DataKey::VerifierAddress, &verifier);
+        env.storage()
+            .instance()
+            .set(&DataKey::SetupVerifierAddress, &setup_verifier);
+    }
+
+    /// Mint trophies to winners through `trophy`, which must allow this
+    /// contract to mint; admin only
+    pub fn set_trophy(env: Env, trophy: Address) {
+        admin(&env).require_auth();
+        env.storage()
+            .instance()
+            .set(&DataKey::TrophyAddress, &trophy);
+    }
+
+    /// Push every game that ends to `archive`, which must allow this
+    /// contract to write; admin only
+    pub fn set_archive(env: Env, archive: Address) {
+        admin(&env).require_auth();
+        env.storage()
+            .instance()
+            .set(&DataKey::ArchiveAddress, &archive);
+    }
+
+    /// Get the deployment configuration
+    pub fn get_config(env: Env) -> ContractConfig {
+        config(&env)
+    }
+
+    /// Tune the deployment; admin only. The game TTL is one day to the
+    /// network's maximum, and the timelocks one day to less than it.
+    /// A new grid size starts a new heatmap; games in progress finish on the
+    /// grid they started with.
+    pub fn set_config(env: Env, config: ContractConfig) -> Result<(), Error> {
+        admin(&env).require_auth();
+        check_config(&config)?;
+        if config.grid_size != Self::get_config(env.clone()).grid_size {
+            env.storage().persistent().remove(&DataKey::Heatmap);
+        }
+        env.storage().instance().set(&DataKey::Config, &config);
+        ConfigUpdated {
+            game_ttl_ledgers: config.game_ttl_ledgers,
+            emergency_delay_ledgers: config.emergency_delay_ledgers,
+            proposal_expiry_ledgers: config.proposal_expiry_ledgers,
+            max_active_games: config.max_active_games,
+            min_points: config.min_points,
+            max_points: config.max_points,
+            expiry_ledgers: config.expiry_ledgers,
+            expiry_policy: config.expiry_policy,
+            appeal_window_ledgers: config.appeal_window_ledgers,
+            claim_delay_ledgers: config.claim_delay_ledgers,
+            reject_reused_roots: config.reject_reused_roots,
+            trap_count: config.trap_count,
+            move_gap_ledgers: config.move_gap_ledgers,
+            grid_size: config.grid_size,
+            progressive_payout_bps: config.progressive_payout_bps,
+            bonus_curve: config.bonus_curve,
+            streak_bonus: config.streak_bonus,
+            min_games_completed: config.min_games_completed,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Number of games started and not ended. Games that expire from
+    /// storage unfinished still count; `end_game` them to free their slot.
+    pub fn get_active_games(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::ActiveGames)
+            .unwrap_or(0)
+    }
+
+    /// Totals across every session, for showing activity without an indexer
+    pub fn get_global_stats(env: Env) -> GlobalStats {
+        env.storage()
+            .instance()
+            .get(&DataKey::GlobalStats)
+            .unwrap_or_default()
+    }
+
+    /// Probes and hits per cell across the games that ended
+    pub fn get_heatmap(env: Env) -> Heatmap {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Heatmap)
+            .unwrap_or_else(|| {
+                let cells = config(&env).grid_size.pow(2);
+                let mut zeros = Vec::new(&env);
+                for _ in 0..cells {
+                    zeros.push_back(0);
+                }
+                Heatmap {
+                    probes: zeros.clone(),
+                    hits: zeros,
+                }
+            })
+    }
+
+    /// Games a player has completed: settled, or drawn by the expiry policy
+    pub fn get_games_completed(env: Env, player: Address) -> u32 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::GamesCompleted(player))
+            .unwrap_or(0)
+    }
+
+    /// A player's current and best streak of wins in settled games
+    pub fn get_streak(env: Env, player: Address) -> Streak {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Streak(player))
+            .unwrap_or_default()
+    }
+
+    /// Start a practice game: `player` attacks a grid alone, with no stake,
+    /// opponent or proofs. The contract draws each probe from the ledger's
+    /// PRNG and `commitment`, the SHA-256 of a secret the player reveals at
+    /// `finish_practice`; the grid has the configured size and trap count.
+    pub fn start_practice(env: Env, player: Address, commitment: BytesN<32>) -> Result<(), Error> {
+        player.require_auth();
+        if Self::is_paused(env.clone()) {
+            return Err(Error::Paused);
+        }
+        let key = DataKey::Practice(player.clone());
+        if env.storage().temporary().has(&key) {
+            return Err(Error::PracticeInProgress);
+        }
+
+        let config = config(&env);
+        let practice = Practice {
+            commitment,
+            grid_size: config.grid_size,
+            trap_count: config.trap_count,
+            probes: Vec::new(&env),
+            found: Vec::new(&env),
+        };
+        let ttl = game_ttl(&env);
+        env.storage().temporary().set(&key, &practice);
+        env.storage().temporary().extend_ttl(&key, ttl, ttl);
+
+        PracticeStarted {
+            player,
+            grid_size: config.grid_size,
+            trap_count: config.trap_count,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Probe a cell of the player's practice grid; returns whether it holds a
+    /// trap. Once every trap is found the game takes no more probes.
+    pub fn practice_move(env: Env, player: Address, x: u32, y: u32) -> Result<bool, Error> {
+        player.require_auth();
+        let key = DataKey::Practice(player);
+        let mut practice: Practice = env
+            .storage()
+            .temporary()
+            .get(&key)
+            .ok_or(Error::PracticeNotFound)?;
+        if practice.found.len() == practice.trap_count {
+            return Err(Error::GameAlreadyEnded);
+        }
+        if x >= practice.grid_size || y >= practice.grid_size {
+            return Err(Error::InvalidMove);
+        }
+        let cell = x * practice.grid_size + y;
+        if practice.probes.contains(cell) {
+            return Err(Error::MoveAlreadyMade);
+        }
+
+        let entropy: BytesN<32> = env.prng().gen();
+        let seed = practice::seed(&env, &entropy, &practice.commitment);
+        let cells_left = practice.grid_size * practice.grid_size - practice.probes.len();
+        let traps_left = practice.trap_count - practice.found.len();
+        let is_hit = practice::is_trap(&env, &seed, traps_left, cells_left);
+        practice.probes.push_back(cell);
+        if is_hit {
+            practice.found.push_back(cell);
+        }
+        let ttl = game_ttl(&env);
+        env.storage().temporary().set(&key, &practice);
+        env.storage().temporary().extend_ttl(&key, ttl, ttl);
+        Ok(is_hit)
+    }
+
+    /// Finish the player's practice game, cleared or not, by revealing the
+    /// secret behind its commitment; records the result in the player's
+    /// practice stats and publishes the layout: the traps found, then the
+    /// rest drawn among the cells never probed
+    pub fn finish_practice(
+        env: Env,
+        player: Address,
+        secret: BytesN<32>,
+    ) -> Result<PracticeStats, Error> {
+        player.require_auth();
+        let key = DataKey::Practice(player.clone());
+        let practice: Practice = env
+            .storage()
+            .temporary()
+            .get(&key)
+            .ok_or(Error::PracticeNotFound)?;
+        let revealed: BytesN<32> = env.crypto().sha256(&secret.into()).into();
+        if revealed != practice.commitment {
+            return Err(Error::RevealMismatch);
+        }
+
+        let probes = practice.probes.len();
+        let hits = practice.found.len();
+        let cleared = hits == practice.trap_count;
+        let mut stats = Self::get_practice_stats(env.clone(), player.clone());
+        stats.games += 1;
+        stats.total_probes += u64::from(probes);
+        stats.total_hits += u64::from(hits);
+        if cleared {
+            stats.cleared += 1;
+            if stats.best_probes == 0 || probes < stats.best_probes {
+                stats.best_probes = probes;
+            }
+        }
+        let stats_key = DataKey::PracticeStats(player.clone());
+        env.storage().persistent().set(&stats_key, &stats);
+        env.storage()
+            .persistent()
+            .extend_ttl(&stats_key, MAX_TTL_LEDGERS, MAX_TTL_LEDGERS);
+        env.storage().temporary().remove(&key);
+
+        let entropy: BytesN<32> = env.prng().gen();
+        let seed = practice::seed(&env, &entropy, &practice.commitment);
+        let mut traps = practice.found;
+        traps.append(&practice::layout(
+            &env,
+            &seed,
+            practice.grid_size,
+            practice.trap_count - hits,
+            &practice.probes,
+        ));
+        PracticeFinished {
+            player,
+            probes,
+            hits,
+            cleared,
+            traps,
+        }
+        .publish(&env);
+        Ok(stats)
+    }
+
+    /// The player's practice game in progress, if any
+    pub fn get_practice(env: Env, player: Address) -> Option<Practice> {
+        env.storage().temporary().get(&DataKey::Practice(player))
+    }
+
+    /// The player's practice results, apart from ranked play
+    pub fn get_practice_stats(env: Env, player: Address) -> PracticeStats {
+        env.storage()
+            .persistent()
+            .get(&DataKey::PracticeStats(player))
+            .unwrap_or_default()
+    }
+
+    /// Commit the shared grid of `day` (days since the Unix epoch, today or
+    /// later); operator or admin only. Every player attacks the same grid,
+    /// of the configured size and trap count, and the operator answers each
+    /// probe with `answer_daily`.
+    ///
+    /// # Arguments
+    /// * `grid_hash` - SHA-256 of the trap cells, each `x * grid_size + y` as a
+    ///   big-endian u32 in ascending order, followed by a 32-byte salt
+    pub fn commit_daily(
+        env: Env,
+        operator: Address,
+        day: u32,
+        grid_hash: BytesN<32>,
+    ) -> Result<(), Error> {
+        require_operator(&env, &operator)?;
+        let key = DataKey::Daily(day);
+        if day < daily::today(&env) || env.storage().persistent().has(&key) {
+            return Err(Error::DailyExists);
+        }
+        let config = config(&env);
+        let puzzle = Daily {
+            grid_hash: grid_hash.clone(),
+            grid_size: config.grid_size,
+            trap_count: config.trap_count,
+            traps: Vec::new(&env),
+            players: 0,
+            misanswered: 0,
+        };
+        save_daily(&env, &key, &puzzle);
+        DailyCommitted { day, grid_hash }.publish(&env);
+        Ok(())
+    }
+
+    /// Probe a cell of today's grid; the operator answers it with
+    /// `answer_daily` before the player probes again. A player who was
+    /// answered a hit for every trap is done.
+    pub fn daily_move(env: Env, player: Address, x: u32, y: u32) -> Result<(), Error> {
+        player.require_auth();
+        let day = daily::today(&env);
+        let daily_key = DataKey::Daily(day);
+        let mut puzzle = Self::get_daily(env.clone(), day).ok_or(Error::DailyNotFound)?;
+        let key = DataKey::DailyEntry(day, player);
+        let mut entry = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or_else(|| DailyEntry {
+                probes: Vec::new(&env),
+                answers: Vec::new(&env),
+                pending: None,
+            });
+        if entry.pending.is_some() {
+            return Err(Error::MovePending);
+        }
+        if daily::answered_hits(&entry.answers) >= puzzle.trap_count {
+            return Err(Error::GameAlreadyEnded);
+        }
+        if x >= puzzle.grid_size || y >= puzzle.grid_size {
+            return Err(Error::InvalidMove);
+        }
+        let cell = x * puzzle.grid_size + y;
+        if entry.probes.contains(cell) {
+            return Err(Error::MoveAlreadyMade);
+        }
+
+        if entry.probes.is_empty() {
+            puzzle.players += 1;
+            save_daily(&env, &daily_key, &puzzle);
+        }
+        entry.probes.push_back(cell);
+        entry.pending = Some(cell);
+        save_daily(&env, &key, &entry);
+        Ok(())
+    }
+
+    /// Answer a player's pending probe of `day`'s grid; operator or admin
+    /// only, until the grid is revealed. Answers only guide the player and
+    /// are recorded unverified: the leaderboard scores the probes against
+    /// the revealed layout, and `rank_daily` counts the answers it
+    /// contradicts.
+    pub fn answer_daily(
+        env: Env,
+        operator: Address,
+        day: u32,
+        player: Address,
+        is_hit: bool,
+    ) -> Result<(), Error> {
+        require_operator(&env, &operator)?;
+        let puzzle = Self::get_daily(env.clone(), day).ok_or(Error::DailyNotFound)?;
+        if !puzzle.traps.is_empty() {
+            return Err(Error::DailyRevealed);
+        }
+        let key = DataKey::DailyEntry(day, player.clone());
+        let mut entry: DailyEntry = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(Error::NoPendingMove)?;
+        let cell = entry.pending.take().ok_or(Error::NoPendingMove)?;
+        entry.answers.push_back(is_hit);
+        save_daily(&env, &key, &entry);
+        DailyAnswered {
+            day,
+            player,
+            cell,
+            is_hit,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Reveal `day`'s layout once the day has closed; operator or admin
+    /// only. The cells, in ascending order, and the salt must hash to the
+    /// committed `grid_hash`.
+    pub fn reveal_daily(
+        env: Env,
+        operator: Address,
+        day: u32,
+        traps: Vec<u32>,
+        salt: BytesN<32>,
+    ) -> Result<(), Error> {
+        require_operator(&env, &operator)?;
+        let key = DataKey::Daily(day);
+        let mut puzzle = Self::get_daily(env.clone(), day).ok_or(Error::DailyNotFound)?;
+        if !puzzle.traps.is_empty() {
+            return Err(Error::DailyRevealed);
+        }
+        if day >= daily::today(&env) {
+            return Err(Error::DayNotClosed);
+        }
+        if !daily::is_canonical(&traps, puzzle.grid_size, puzzle.trap_count)
+            || daily::commitment(&env, &traps, &salt) != puzzle.grid_hash
+        {
+            return Err(Error::RevealMismatch);
+        }
+
+        puzzle.traps = traps.clone();
+        save_daily(&env, &key, &puzzle);
+        DailyRevealed { day, traps }.publish(&env);
+        Ok(())
+    }
+
+    /// Score a player's probes of a revealed day against its layout and
+    /// place them on the day's leaderboard: more hits first, then fewer
+    /// probes. Anyone can call, once per player; returns the place, or
+    /// `None` off the leaderboard.
+    ///
+    /// The operator's answers to the player are checked against the layout
+    /// too; a player given any wrong answer is counted in the day's
+    /// `misanswered`.
+    pub fn rank_daily(env: Env, day: u32, player: Address) -> Result<Option<u32>, Error> {
+        let mut puzzle = Self::get_daily(env.clone(), day).ok_or(Error::DailyNotFound)?;
+        if puzzle.traps.is_empty() {
+            return Err(Error::DailyNotRevealed);
+        }
+        let key = DataKey::DailyEntry(day, player.clone());
+        let entry: DailyEntry = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(Error::NotPlayer)?;
+        env.storage().persistent().remove(&key);
+
+        let hits = daily::hits(&puzzle.traps, &entry.probes);
+        let probes = entry.probes.len();
+        let misanswered = daily::misanswered(&puzzle.traps, &entry.probes, &entry.answers);
+        if misanswered > 0 {
+            puzzle.misanswered += 1;
+            save_daily(&env, &DataKey::Daily(day), &puzzle);
+        }
+        let board_key = DataKey::DailyLeaderboard(day);
+        let mut board = Self::get_daily_leaderboard(env.clone(), day);
+        let place = daily::rank(
+            &mut board,
+            DailyRank {
+                player: player.clone(),
+                hits,
+                probes,
+            },
+        );
+        if place.is_some() {
+            save_daily(&env, &board_key, &board);
+        }
+        DailyRanked {
+            day,
+            player,
+            hits,
+            probes,
+            place,
+            misanswered,
+        }
+        .publish(&env);
+        Ok(place)
+    }
+
+    /// The shared grid of `day`, if committed
+    pub fn get_daily(env: Env, day: u32) -> Option<Daily> {
+        env.storage().persistent().get(&DataKey::Daily(day))
+    }
+
+    /// A player's probes of `day`'s grid, until they are ranked
+    pub fn get_daily_entry(env: Env, day: u32, player: Address) -> Option<DailyEntry> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::DailyEntry(day, player))
+    }
+
+    /// The best of `day`'s ranked players, best first
+    pub fn get_daily_leaderboard(env: Env, day: u32) -> Vec<DailyRank> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::DailyLeaderboard(day))
+            .unwrap_or(vec![&env])
+    }
+
+    /// Allow `operator` routine maintenance; admin only
+    ///
+    /// The role is kept for `MAX_TTL_LEDGERS` (180 days) from the grant or
+    /// from its last use, whichever is later; unused for longer, it expires.
+    pub fn grant_operator(env: Env, operator: Address) {
+        admin(&env).require_auth();
+        let key = DataKey::Operator(operator);
+        env.storage().persistent().set(&key, &true);
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, MAX_TTL_LEDGERS, MAX_TTL_LEDGERS);
+    }
+
+    /// Take routine maintenance away from `operator`; admin only
+    pub fn revoke_operator(env: Env, operator: Address) {
+        admin(&env).require_auth();
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Operator(operator));
+    }
+
+    /// Whether `address` was granted the operator role
+    pub fn is_operator(env: Env, address: Address) -> bool {
+        env.storage().persistent().has(&DataKey::Operator(address))
+    }
+
+    /// Stop or resume `start_game`; games in progress play on. Operator or
+    /// admin only
+    pub fn set_paused(env: Env, operator: Address, paused: bool) -> Result<(), Error> {
+        require_operator(&env, &operator)?;
+        env.storage().instance().set(&DataKey::Paused, &paused);
+        Ok(())
+    }
+
+    /// Whether new games are paused
+    pub fn is_paused(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&DataKey::Paused)
+            .unwrap_or(false)
+    }
+
+    /// Extend the storage TTLs of a game and of the contract instance, so a
+    /// slow game does not expire; operator or admin only
+    pub fn extend_game_ttl(env: Env, operator: Address, session_id: u32) -> Result<(), Error> {
+        require_operator(&env, &operator)?;
+        let ttl = game_ttl(&env);
+        let game_key = DataKey::Game(session_id);
+        if !env.storage().temporary().has(&game_key) {
+            return Err(Error::GameNotFound);
+        }
+        for key in [
+            game_key,
+            DataKey::Moves(session_id),
+            DataKey::PendingMove(session_id),
+            DataKey::Sponsorships(session_id),
+            DataKey::EmergencyWithdraw(session_id),
+            DataKey::Arbitration(session_id),
+            DataKey::Claim(session_id),
+            DataKey::GridRoot(session_id),
+            DataKey::GridSetup(session_id),
+            DataKey::LastMoves(session_id),
+            DataKey::AbortVotes(session_id),
+            DataKey::Channel(session_id),
+            DataKey::Optimistic(session_id),
+            DataKey::Frozen(session_id),
+        ] {
+            if env.storage().temporary().has(&key) {
+                env.storage().temporary().extend_ttl(&key, ttl, ttl);
+            }
+        }
+        env.storage().instance().extend_ttl(ttl, ttl);
+        Ok(())
+    }
+
+    /// Propose a sensitive change as a member of the admin set, approving it.
+    /// Returns the proposal id; the change runs at once if the threshold is
+    /// one, otherwise when enough members `approve` it before it expires.
+    pub fn propose(env: Env, member: Address, action: AdminAction) -> Result<u32, Error> {
+        let set = require_member(&env, &member)?;
+        check_action(&action)?;
+        let config = config(&env);
+        let ttl = config.game_ttl_ledgers;
+        let proposal_id: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::ProposalCount)
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&DataKey::ProposalCount, &(proposal_id + 1));
+
+        let proposal = Proposal {
+            action,
+            approvals: vec![&env, member],
+            expires_ledger: env.ledger().sequence() + config.proposal_expiry_ledgers,
+        };
+        // Kept past its expiry so late approvals fail with ProposalExpired
+        if !try_run_proposal(&env, proposal_id, &proposal, &set) {
+            env.storage()
+                .temporary()
+                .extend_ttl(&DataKey::Proposal(proposal_id), ttl, ttl);
+        }
+        Ok(proposal_id)
+    }
+
+    /// Approve a proposal as a member of the admin set; returns whether the
+    /// approval reached the threshold and ran the change
+    pub fn approve(env: Env, member: Address, proposal_id: u32) -> Result<bool, Error> {
+        let set = require_member(&env, &member)?;
+        let mut proposal = Self::get_proposal(env.clone(), proposal_id)?;
+        if env.ledger().sequence() > proposal.expires_ledger {
+            return Err(Error::ProposalExpired);
+        }
+        if proposal.approvals.contains(&member) {
+            return Err(Error::AlreadyApproved);
+        }
+        proposal.approvals.push_back(member);
+        Ok(try_run_proposal(&env, proposal_id, &proposal, &set))
+    }
+
+    /// Get a proposal waiting for approvals
+    pub fn get_proposal(env: Env, proposal_id: u32) -> Result<Proposal, Error> {
+        env.storage()
+            .temporary()
+            .get(&DataKey::Proposal(proposal_id))
+            .ok_or(Error::ProposalNotFound)
+    }
+
+    /// Get the admin set and its threshold
+    pub fn get_admin_set(env: Env) -> AdminSet {
+        admin_set(&env)
+    }
+
+    /// Start a new game between defender and attacker
+    ///
+    /// # Arguments
+    /// * `session_id` - Unique session identifier
+    /// * `defender` - Player A who sets up traps
+    /// * `attacker` - Player B who makes moves
+    /// * `defender_points` - Points committed by defender
+    /// * `attacker_points` - Points committed by attacker
+    /// * `setup` - Grid-setup proof that the defender's grid holds exactly
+    ///   the configured number of traps; the commitment is bound to the
+    ///   defender and starts one game only
+    pub fn start_game(
+        env: Env,
+        session_id: u32,
+        defender: Address,
+        attacker: Address,
+        defender_points: i128,
+        attacker_points: i128,
+        setup: GridSetup,
+    ) -> Result<(), Error> {
+        // Prevent self-play
+        if defender == attacker {
+            panic!("Cannot play against yourself");
+        }
+        if Self::is_paused(env.clone()) {
+            return Err(Error::Paused);
+        }
+        let config = config(&env);
+        for points in [defender_points, attacker_points] {
+            if points <= 0 {
+                return Err(Error::StakeNotPositive);
+            }
+            if points < config.min_points || points > config.max_points {
+                return Err(Error::StakeOutOfBounds);
+            }
+        }
+        let active = Self::get_active_games(env.clone());
+        if config.max_active_games > 0 && active >= config.max_active_games {
+            return Err(Error::TooManyGames);
+        }
+        // Fresh accounts cannot enter until they have played enough games
+        for player in [&defender, &attacker] {
+            if Self::get_games_completed(env.clone(), player.clone()) < config.min_games_completed {
+                return Err(Error::TooFewGames);
+            }
+        }
+        let ttl = config.game_ttl_ledgers;
+
+        // Only defender needs to authenticate to start the game
+        // Attacker implicitly joins when they submit their first move
+        defender.require_auth_for_args(vec![
+            &env,
+            session_id.into_val(&env),
+            defender_points.into_val(&env),
+            setup.grid_commitment.into_val(&env),
+        ]);
+
+        // The grid must be well-formed, committed to by this defender, and not
+        // one a game started with
+        let used_key = DataKey::UsedSetup(setup.grid_commitment.clone());
+        if env.storage().persistent().has(&used_key) {
+            return Err(Error::SetupReused);
+        }
+        let inputs = SetupInputs {
+            grid_commitment: setup.grid_commitment.to_array(),
+            trap_count: config.trap_count,
+            grid_size: config.grid_size,
+            defender: address_key(&env, &defender),
+        };
+        let inputs = Bytes::from_array(&env, &inputs.encode());
+        if !proof_verifies(&env, &DataKey::SetupVerifierAddress, &inputs, &setup.proof) {
+            return Err(Error::InvalidSetup);
+        }
+
+        // Get GameHub address
+        let game_hub_addr: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::GameHubAddress)
+            .expect("GameHub address not set");
+
+        // Create GameHub client and start game
+        let game_hub = GameHubClient::new(&env, &game_hub_addr);
+        game_hub.start_game(
+            &env.current_contract_address(),
+            &session_id,
+            &defender,
+            &attacker,
+            &defender_points,
+            &attacker_points,
+        );
+
+        // Create game state
+        let game = Game {
+            defender: defender.clone(),
+            attacker: attacker.clone(),
+            defender_points,
+            attacker_points,
+            moves_made: 0,
+            hits: 0,
+            misses: 0,
+            game_started: true,
+            game_ended: false,
+            winner: None,
+            schema_version: GAME_SCHEMA_VERSION,
+            last_active_ledger: env.ledger().sequence(),
+            grid_size: config.grid_size,
+            trap_count: config.trap_count,
+        };
+
+        // Store game state
+        let game_key = DataKey::Game(session_id);
+        env.storage().temporary().set(&game_key, &game);
+        env.storage().temporary().extend_ttl(&game_key, ttl, ttl);
+
+        // Initialize empty moves vector
+        let moves_key = DataKey::Moves(session_id);
+        let moves: Vec<Move> = vec![&env];
+        env.storage().temporary().set(&moves_key, &moves);
+        env.storage().temporary().extend_ttl(&moves_key, ttl, ttl);
+
+        let setup_key = DataKey::GridSetup(session_id);
+        env.storage()
+            .temporary()
+            .set(&setup_key, &setup.grid_commitment);
+        env.storage().temporary().extend_ttl(&setup_key, ttl, ttl);
+        env.storage().persistent().set(&used_key, &true);
+        env.storage().persistent().extend_ttl(
+            &used_key,
+            USED_SETUP_TTL_LEDGERS,
+            USED_SETUP_TTL_LEDGERS,
+        );
+
+        env.storage()
+            .instance()
+            .set(&DataKey::ActiveGames, &(active + 1));
+        let mut stats = Self::get_global_stats(env.clone());
+        stats.games_created += 1;
+        env.storage().instance().set(&DataKey::GlobalStats, &stats);
+
+        GameStarted {
+            session_id,
+            defender,
+            attacker,
+        }
+        .publish(&env);
+
+        Ok(())
+    }
+
+    /// Start a game with third parties staking tokens on either player
+    ///
+    /// Takes the arguments of `start_game` and one or two sponsorships, at
+    /// most one per player. Every sponsor authorizes the call and its stake
+    /// is escrowed until the game ends, when the winning side is paid (see
+    /// [`Sponsorship`]).
+    pub fn start_sponsored_game(
+        env: Env,
+        session_id: u32,
+        defender: Address,
+        attacker: Address,
+        defender_points: i128,
+        attacker_points: i128,
+        setup: GridSetup,
+        sponsorships: Vec<Sponsorship>,
+    ) -> Result<(), Error> {
+        let token = sponsorship_token(&sponsorships, &defender, &attacker)?;
+        Self::start_game(
+            env.clone(),
+            session_id,
+            defender,
+            attacker,
+            defender_points,
+            attacker_points,
+            setup,
+        )?;
+
+        let token = token::Client::new(&env, &token);
+        let contract = env.current_contract_address();
+        for sponsorship in sponsorships.iter() {
+            sponsorship.sponsor.require_auth();
+            token.transfer(&sponsorship.sponsor, &contract, &sponsorship.amount);
+        }
+        let ttl = game_ttl(&env);
+        let key = DataKey::Sponsorships(session_id);
+        env.storage().temporary().set(&key, &sponsorships);
+        env.storage().temporary().extend_ttl(&key, ttl, ttl);
+        Ok(())
+    }
+
+    /// Get the sponsorships of a game; empty unless it was started sponsored
+    /// and has not been settled
+    pub fn get_sponsorships(env: Env, session_id: u32) -> Vec<Sponsorship> {
+        env.storage()
+            .temporary()
+            .get(&DataKey::Sponsorships(session_id))
+            .unwrap_or(vec![&env])
+    }
+
+    /// Get the protocol fee; none until the admin set sets one
+    pub fn get_fees(env: Env) -> Option<Fees> {
+        env.storage().instance().get(&DataKey::Fees)
+    }
+
+    /// Add `amount` of `token` to the pool decisive wins are paid bonuses
+    /// from (see `ContractConfig::bonus_curve`); anyone can fund it, and
+    /// nothing takes it back out but bonuses. Returns the pool's balance
+    pub fn fund_bonus_pool(
+        env: Env,
+        funder: Address,
+        token: Address,
+        amount: i128,
+    ) -> Result<i128, Error> {
+        funder.require_auth();
+        if amount <= 0 {
+            return Err(Error::StakeNotPositive);
+        }
+        token::Client::new(&env, &token).transfer(&funder, env.current_contract_address(), &amount);
+        let key = DataKey::BonusPool(token.clone());
+        let pool = Self::get_bonus_pool(env.clone(), token) + amount;
+        env.storage().persistent().set(&key, &pool);
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, MAX_TTL_LEDGERS, MAX_TTL_LEDGERS);
+        Ok(pool)
+    }
+
+    /// Bonus pool balance in `token`
+    pub fn get_bonus_pool(env: Env, token: Address) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::BonusPool(token))
+            .unwrap_or(0)
+    }
+
+    /// Register the player who referred `player`; allowed once, and never
+    /// the player themselves or someone `player` referred
+    pub fn register_referrer(env: Env, player: Address, referrer: Address) -> Result<(), Error> {
+        player.require_auth();
+        let key = DataKey::Referrer(player.clone());
+        if env.storage().persistent().has(&key) {
+            return Err(Error::ReferrerAlreadySet);
+        }
+        if referrer == player
+            || Self::get_referrer(env.clone(), referrer.clone()).as_ref() == Some(&player)
+        {
+            return Err(Error::SelfReferral);
+        }
+        env.storage().persistent().set(&key, &referrer);
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, REFERRAL_TTL_LEDGERS, REFERRAL_TTL_LEDGERS);
+        Ok(())
+    }
+
+    /// Get the referrer `player` registered, if any
+    pub fn get_referrer(env: Env, player: Address) -> Option<Address> {
+        env.storage().persistent().get(&DataKey::Referrer(player))
+    }
+
+    /// Referral rewards `referrer` can claim in `token`
+    pub fn get_referral_rewards(env: Env, referrer: Address, token: Address) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::ReferralRewards(referrer, token))
+            .unwrap_or(0)
+    }
+
+    /// Pay `referrer` the rewards accrued in `token`; returns the amount
+    pub fn claim_referral_rewards(env: Env, referrer: Address, token: Address) -> i128 {
+        referrer.require_auth();
+        let key = DataKey::ReferralRewards(referrer.clone(), token.clone());
+        let amount: i128 = env.storage().persistent().get(&key).unwrap_or(0);
+        if amount > 0 {
+            env.storage().persistent().remove(&key);
+            token::Client::new(&env, &token).transfer(
+                &env.current_contract_address(),
+                &referrer,
+                &amount,
+            );
+        }
+        amount
+    }
+
+    /// Attacker picks the next cell for the defender to answer
+    ///
+    /// Optional: `make_move` can still be called directly. Once a move is
+    /// submitted, `make_move` only accepts that cell until it is answered.
+    /// Fails with `MoveTooSoon` within `move_gap_ledgers` of the attacker's
+    /// last move.
+    ///
+    /// # Arguments
+    /// * `session_id` - Game session identifier
+    /// * `x` - X coordinate of move, below the game's `grid_size`
+    /// * `y` - Y coordinate of move, below the game's `grid_size`
+    pub fn submit_move(env: Env, session_id: u32, x: u32, y: u32) -> Result<(), Error> {
+        submit(&env, session_id, x, y, Bytes::new(&env))
+    }
+
+    /// `submit_move` with a short note from the attacker, such as a taunt or
+    /// an emote id
+    ///
+    /// The note is published in `move_submitted` and never stored. Fails with
+    /// `NoteTooLong` above `MAX_NOTE_BYTES`.
+    ///
+    /// # Arguments
+    /// * `session_id` - Game session identifier
+    /// * `x` - X coordinate of move, below the game's `grid_size`
+    /// * `y` - Y coordinate of move, below the game's `grid_size`
+    /// * `note` - Up to `MAX_NOTE_BYTES` bytes, read by the dApp
+    pub fn submit_move_with_note(
+        env: Env,
+        session_id: u32,
+        x: u32,
+        y: u32,
+        note: Bytes,
+    ) -> Result<(), Error> {
+        if note.len() > MAX_NOTE_BYTES {
+            return Err(Error::NoteTooLong);
+        }
+        submit(&env, session_id, x, y, note)
+    }
+
+    /// Defender answers the attacker's submitted move with a ZK proof
+    ///
+    /// Only the cell pending from `submit_move` can be answered; without one
+    /// the call fails with `NoPendingMove`. Both sides wait
+    /// `move_gap_ledgers` between their moves; the attacker's side was
+    /// counted at `submit_move`. Requires the defender's authorization.
+    ///
+    /// # Arguments
+    /// * `session_id` - Game session identifier
+    /// * `x` - X coordinate of the submitted move
+    /// * `y` - Y coordinate of the submitted move
+    /// * `is_hit` - Defender's claim: true if trap hit, false if miss
+    /// * `proof` - ZK proof of the claim (UltraHonk proof from position-movement circuit)
+    /// * `public_inputs` - Public inputs for proof verification (move_x, move_y, is_hit,
+    ///   grid_size, grid_commitment); must encode this move on the grid committed at
+    ///   setup, or the proof is rejected as invalid
+    pub fn make_move(
+        env: Env,
+        session_id: u32,
+        x: u32,
+        y: u32,
+        is_hit: bool,
+        proof: Bytes,
+        public_inputs: Bytes,
+    ) -> Result<bool, Error> {
+        // Load game
+        let game_key = DataKey::Game(session_id);
+        let mut game = load_game(&env, session_id)?;
+
+        // Only the defender answers for their grid
+        game.defender.require_auth();
+
+        // Validate the game state and the move itself
+        let moves_key = DataKey::Moves(session_id);
+        let mut moves: Vec<Move> = env
+            .storage()
+            .temporary()
+            .get(&moves_key)
+            .unwrap_or(vec![&env]);
+        let pending_key = DataKey::PendingMove(session_id);
+        let pending: Option<PendingMove> = env.storage().temporary().get(&pending_key);
+        check_move(&game, &moves, &pending, x, y)?;
+        check_no_channel(&env, session_id)?;
+        check_not_frozen(&env, session_id)?;
+        // The attacker's side of the move was counted at `submit_move`
+        check_move_gap(&env, session_id, false, true)?;
+
+        // The proof must be about this move and this claim, on the grid the
+        // defender committed to at setup
+        if !public_inputs_match(&env, session_id, &public_inputs, &game, x, y, is_hit) {
+            return Err(Error::InvalidProof);
+        }
+
+        // Verify ZK proof using the verifier contract
+        if !proof_verifies(&env, &DataKey::VerifierAddress, &public_inputs, &proof) {
+            return Err(Error::InvalidProof);
+        }
+
+        // Record the move
+        let new_move = Move {
+            x,
+            y,
+            is_hit,
+            verified: true,
+        };
+        moves.push_back(new_move);
+
+        // Update game state
+        game.moves_made += 1;
+        game.last_active_ledger = env.ledger().sequence();
+        if is_hit {
+            game.hits += 1;
+        } else {
+            game.misses += 1;
+        }
+
+        MoveMade {
+            session_id,
+            defender: game.defender.clone(),
+            attacker: game.attacker.clone(),
+            x,
+            y,
+            is_hit,
+            verified: true,
+        }
+        .publish(&env);
+        release_progressive(&env, session_id, &game, is_hit);
+
+        // Check if game should end (all moves made or other condition); an
+        // optimistic game waits for its claims' challenge windows
+        let game_complete = game.moves_made >= game.cells()
+            && !env
+                .storage()
+                .temporary()
+                .has(&DataKey::Optimistic(session_id));
+
+        if game_complete {
+            game.game_ended = true;
+            // Determine winner: defender wins if attacker couldn't find enough traps
+            // (For this demo, let's say attacker needs > 50% hit rate to win)
+            let attacker_wins = game.hits > (game.cells() / 2);
+            game.winner = if attacker_wins {
+                Some(game.attacker.clone())
+            } else {
+                Some(game.defender.clone())
+            };
+
+            post_result(&env, session_id, &game, &moves);
+
+            GameEnded {
+                session_id,
+                defender: game.defender.clone(),
+                attacker: game.attacker.clone(),
+                winner: game.winner.clone().unwrap(),
+                hits: game.hits,
+                misses: game.misses,
+            }
+            .publish(&env);
+        }
+
+        // Save updated state
+        resume_play(&env, session_id, &game);
+        record_move_ledgers(&env, session_id, false, true);
+        let ttl = game_ttl(&env);
+        env.storage().temporary().remove(&pending_key);
+        env.storage().temporary().set(&game_key, &game);
+        env.storage().temporary().set(&moves_key, &moves);
+        env.storage().temporary().extend_ttl(&game_key, ttl, ttl);
+        env.storage().temporary().extend_ttl(&moves_key, ttl, ttl);
+
+        Ok(true)
+    }
+
+    /// Check a move the way `make_move` would, without its proof
+    ///
+    /// Runs every check `make_move` makes before verifying the proof and
+    /// returns the error it would fail with, changing nothing. Clients call it
+    /// to pre-flight a cell before proving it.
+    ///
+    /// # Arguments
+    /// * `session_id` - Game session identifier
+    /// * `x` - X coordinate of move, below the game's `grid_size`
+    /// * `y` - Y coordinate of move, below the game's `grid_size`
+    pub fn simulate_move(env: Env, session_id: u32, x: u32, y: u32) -> Result<(), Error> {
+        let game = load_game(&env, session_id)?;
+        let moves = Self::get_moves(env.clone(), session_id);
+        let pending = Self::get_pending_move(env.clone(), session_id);
+        check_move(&game, &moves, &pending, x, y)?;
+        check_no_channel(&env, session_id)?;
+        check_not_frozen(&env, session_id)?;
+        check_move_gap(&env, session_id, false, true)
+    }
+
+    /// End the game early (e.g., if attacker gives up or time limit reached).
+    /// A game without a move for `expiry_ledgers` ends as the configured
+    /// `expiry_policy` says instead of by its score.
+    ///
+    /// Anyone can end an expired or forfeited game, or one with every cell
+    /// played. A live game ends early only with both players' authorization,
+    /// so neither can stop it the moment the score favours them.
+    pub fn end_game(env: Env, session_id: u32) -> Result<(), Error> {
+        let game_key = DataKey::Game(session_id);
+        let mut game = load_game(&env, session_id)?;
+
+        if game.game_ended {
+            return Err(Error::GameAlreadyEnded);
+        }
+        check_not_frozen(&env, session_id)?;
+
+        // Determine winner based on current state, or on the expiry policy
+        let config = config(&env);
+        let expired = config.expiry_ledgers != 0
+            && env.ledger().sequence()
+                > game
+                    .last_active_ledger
+                    .saturating_add(config.expiry_ledgers);
+        let moves = Self::get_moves(env.clone(), session_id);
+        // A defender who did not prove a challenged claim loses; otherwise
+        // an optimistic game ends once its claims can no longer be challenged
+        let optimistic: Option<Optimistic> = env
+            .storage()
+            .temporary()
+            .get(&DataKey::Optimistic(session_id));
+        let forfeited = claim_forfeited(&env, &optimistic);
+        if !expired && !forfeited && game.moves_made < game.cells() {
+            game.defender.require_auth();
+            game.attacker.require_auth();
+        }
+        // A channel game ends by its settlement, unless it expires
+        if !expired && !forfeited {
+            check_no_channel(&env, session_id)?;
+            if let Some(optimistic) = &optimistic {
+                check_claims_settled(&env, optimistic, &moves)?;
+            }
+        }
+        let attacker_wins = match (forfeited, expired, config.expiry_policy) {
+            (true, _, _) => Some(true),
+            (false, false, _) => Some(game.hits > (game.moves_made / 2)),
+            (false, true, ExpiryPolicy::DefenderWins) => Some(false),
+            (false, true, ExpiryPolicy::AttackerWins) => Some(true),
+            (false, true, ExpiryPolicy::Draw | ExpiryPolicy::Refund) => None,
+        };
+        game.game_ended = true;
+
+        if let Some(attacker_wins) = attacker_wins {
+            game.winner = if attacker_wins {
+                Some(game.attacker.clone())
+            } else {
+                Some(game.defender.clone())
+            };
+
+            post_result(&env, session_id, &game, &moves);
+        } else {
+            // The Game Hub only records a winner, so its session stays open
+            refund_sponsorships(&env, session_id);
+            let drawn = config.expiry_policy == ExpiryPolicy::Draw;
+            game_finished(&env, session_id, &game, &moves, drawn);
+        }
+
+        env.storage()
+            .temporary()
+            .remove(&DataKey::PendingMove(session_id));
+        env.storage()
+            .temporary()
+            .remove(&DataKey::EmergencyWithdraw(session_id));
+        env.storage()
+            .temporary()
+            .remove(&DataKey::Channel(session_id));
+        env.storage().temporary().set(&game_key, &game);
+
+        if expired && !forfeited {
+            GameExpired {
+                session_id,
+                defender: game.defender.clone(),
+                attacker: game.attacker.clone(),
+                policy: config.expiry_policy,
+            }
+            .publish(&env);
+        }
+        if let Some(winner) = game.winner.clone() {
+            GameEnded {
+                session_id,
+                defender: game.defender.clone(),
+                attacker: game.attacker.clone(),
+                winner,
+                hits: game.hits,
+                misses: game.misses,
+            }
+            .publish(&env);
+        }
+        Ok(())
+    }
+
+    /// Commit the trap Merkle root of a game's grid before the first move;
+    /// defender only, once per game. Unless the configuration allows it, a
+    /// root the defender committed in an earlier game is rejected, since
+    /// its layout may have leaked when that game was played.
+    pub fn commit_grid(
+        env: Env,
+        session_id: u32,
+        trap_merkle_root: BytesN<32>,
+    ) -> Result<(), Error> {
+        let game = load_game(&env, session_id)?;
+        game.defender.require_auth();
+
+        let key = DataKey::GridRoot(session_id);
+        if game.game_ended
+            || game.moves_made > 0
+            || env
+                .storage()
+                .temporary()
+                .has(&DataKey::PendingMove(session_id))
+            || env.storage().temporary().has(&key)
+        {
+            return Err(Error::GridLocked);
+        }
+        let used_key = DataKey::UsedRoot(game.defender.clone(), trap_merkle_root.clone());
+        let config = config(&env);
+        if config.reject_reused_roots && env.storage().persistent().has(&used_key) {
+            return Err(Error::RootReused);
+        }
+
+        let ttl = config.game_ttl_ledgers;
+        env.storage().temporary().set(&key, &trap_merkle_root);
+        env.storage().temporary().extend_ttl(&key, ttl, ttl);
+        env.storage().persistent().set(&used_key, &true);
+        env.storage().persistent().extend_ttl(
+            &used_key,
+            USED_ROOT_TTL_LEDGERS,
+            USED_ROOT_TTL_LEDGERS,
+        );
+        GridCommitted {
+            session_id,
+            defender: game.defender,
+            attacker: game.attacker,
+            trap_merkle_root,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Get the trap Merkle root committed for a game, if any
+    pub fn get_grid_root(env: Env, session_id: u32) -> Option<BytesN<32>> {
+        env.storage()
+            .temporary()
+            .get(&DataKey::GridRoot(session_id))
+    }
+
+    /// Get the grid commitment a game was started with
+    pub fn get_grid_commitment(env: Env, session_id: u32) -> Option<BytesN<32>> {
+        env.storage()
+            .temporary()
+            .get(&DataKey::GridSetup(session_id))
+    }
+
+    /// Whether the defender has committed this trap Merkle root before
+    pub fn is_root_used(env: Env, defender: Address, trap_merkle_root: BytesN<32>) -> bool {
+        env.storage()
+            .persistent()
+            .has(&DataKey::UsedRoot(defender, trap_merkle_root))
+    }
+
+    /// Appoint the arbiter of a game, with both players' consent, before
+    /// the first move. Its result is then settled only after an appeal
+    /// window (see [`Self::appeal`]).
+    pub fn appoint_arbiter(env: Env, session_id: u32, arbiter: Address) -> Result<(), Error> {
+        let game = load_game(&env, session_id)?;
+        game.defender.require_auth();
+        game.attacker.require_auth();
+
+        let key = DataKey::Arbitration(session_id);
+        let pending_key = DataKey::PendingMove(session_id);
+        if arbiter == game.defender
+            || arbiter == game.attacker
+            || game.game_ended
+            || game.moves_made > 0
+            || env.storage().temporary().has(&pending_key)
+            || env.storage().temporary().has(&key)
+        {
+            return Err(Error::InvalidArbiter);
+        }
+
+        let ttl = game_ttl(&env);
+        let arbitration = Arbitration {
+            arbiter: arbiter.clone(),
+            appeal_until: 0,
+            appellant: None,
+        };
+        env.storage().temporary().set(&key, &arbitration);
+        env.storage().temporary().extend_ttl(&key, ttl, ttl);
+        ArbiterAppointed {
+            session_id,
+            defender: game.defender,
+            attacker: game.attacker,
+            arbiter,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Open a channel to play the rest of a game off-chain; both players,
+    /// before the first move. Each names the ed25519 key it signs channel
+    /// states with, and on-chain moves are refused until the channel settles.
+    pub fn open_channel(
+        env: Env,
+        session_id: u32,
+        defender_key: BytesN<32>,
+        attacker_key: BytesN<32>,
+    ) -> Result<(), Error> {
+        let game_key = DataKey::Game(session_id);
+        let mut game = load_game(&env, session_id)?;
+        game.defender.require_auth();
+        game.attacker.require_auth();
+        if game.game_ended {
+            return Err(Error::GameAlreadyEnded);
+        }
+        let key = DataKey::Channel(session_id);
+        if game.moves_made > 0
+            || env
+                .storage()
+                .temporary()
+                .has(&DataKey::PendingMove(session_id))
+            || env.storage().temporary().has(&key)
+        {
+            return Err(Error::ChannelConflict);
+        }
+
+        let channel = Channel {
+            defender_key,
+            attacker_key,
+            state: ChannelState {
+                move_index: 0,
+                hits: 0,
+                misses: 0,
+                transcript: BytesN::from_array(&env, &[0; 32]),
+                closing: false,
+            },
+            challenge_until: 0,
+        };
+        let ttl = game_ttl(&env);
+        env.storage().temporary().set(&key, &channel);
+        env.storage().temporary().extend_ttl(&key, ttl, ttl);
+        game.last_active_ledger = env.ledger().sequence();
+        env.storage().temporary().set(&game_key, &game);
+
+        resume_play(&env, session_id, &game);
+        ChannelOpened {
+            session_id,
+            defender: game.defender,
+            attacker: game.attacker,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Submit a channel state both players signed; anyone can call it, so
+    /// either player can exit the channel alone
+    ///
+    /// The state must be newer than the one submitted. Unless it is a closing
+    /// state, the other player has `CHANNEL_CHALLENGE_LEDGERS` to replace it
+    /// with a newer one before it can be settled. A signature that does not
+    /// verify aborts the call. Returns the last ledger of the challenge window.
+    ///
+    /// # Arguments
+    /// * `session_id` - Game session identifier
+    /// * `state` - The channel state
+    /// * `defender_signature` - Defender's channel key's signature of the state
+    /// * `attacker_signature` - Attacker's channel key's signature of the state
+    pub fn submit_channel_state(
+        env: Env,
+        session_id: u32,
+        state: ChannelState,
+        defender_signature: BytesN<64>,
+        attacker_signature: BytesN<64>,
+    ) -> Result<u32, Error> {
+        let game_key = DataKey::Game(session_id);
+        let mut game = load_game(&env, session_id)?;
+        if game.game_ended {
+            return Err(Error::GameAlreadyEnded);
+        }
+        check_not_frozen(&env, session_id)?;
+        let key = DataKey::Channel(session_id);
+        let mut channel: Channel = env
+            .storage()
+            .temporary()
+            .get(&key)
+            .ok_or(Error::NoChannelState)?;
+        if channel.state.closing || state.move_index <= channel.state.move_index {
+            return Err(Error::StaleChannelState);
+        }
+        if state.move_index > game.cells()
+            || state.hits.checked_add(state.misses) != Some(state.move_index)
+        {
+            return Err(Error::InvalidTranscript);
+        }
+        let message = channel_message(&env, session_id, &state);
+        env.crypto()
+            .ed25519_verify(&channel.defender_key, &message, &defender_signature);
+        env.crypto()
+            .ed25519_verify(&channel.attacker_key, &message, &attacker_signature);
+
+        let move_index = state.move_index;
+        let challenge_until = env.ledger().sequence() + CHANNEL_CHALLENGE_LEDGERS;
+        channel.state = state;
+        channel.challenge_until = challenge_until;
+        let ttl = game_ttl(&env);
+        env.storage().temporary().set(&key, &channel);
+        env.storage().temporary().extend_ttl(&key, ttl, ttl);
+        game.last_active_ledger = env.ledger().sequence();
+        env.storage().temporary().set(&game_key, &game);
+
+        resume_play(&env, session_id, &game);
+        ChannelStateSubmitted {
+            session_id,
+            defender: game.defender,
+            attacker: game.attacker,
+            move_index,
+            challenge_until,
+        }
+        .publish(&env);
+        Ok(challenge_until)
+    }
+
+    /// Settle a channel game by its submitted state, once the challenge
+    /// window closed or at once for a closing state; anyone can call it
+    ///
+    /// `transcript` is the moves the state's transcript hashes, in play
+    /// order. They are recorded as unverified moves, their proofs having
+    /// been checked off-chain, and the game ends like `end_game`: the
+    /// attacker wins with more hits than half the moves.
+    pub fn settle_channel(
+        env: Env,
+        session_id: u32,
+        transcript: Vec<ChannelMove>,
+    ) -> Result<(), Error> {
+        let game_key = DataKey::Game(session_id);
+        let mut game = load_game(&env, session_id)?;
+        if game.game_ended {
+            return Err(Error::GameAlreadyEnded);
+        }
+        check_not_frozen(&env, session_id)?;
+        let key = DataKey::Channel(session_id);
+        let channel: Channel = env
+            .storage()
+            .temporary()
+            .get(&key)
+            .filter(|channel: &Channel| channel.challenge_until != 0)
+            .ok_or(Error::NoChannelState)?;
+        let state = channel.state;
+        if !state.closing && env.ledger().sequence() <= channel.challenge_until {
+            return Err(Error::ChallengeWindowOpen);
+        }
+
+        let mut moves: Vec<Move> = vec![&env];
+        for m in transcript.iter() {
+            if m.x >= game.grid_size
+                || m.y >= game.grid_size
+                || moves
+                    .iter()
+                    .any(|played| played.x == m.x && played.y == m.y)
+            {
+                return Err(Error::InvalidTranscript);
+            }
+            moves.push_back(Move {
+                x: m.x,
+                y: m.y,
+                is_hit: m.is_hit,
+                verified: false,
+            });
+        }
+        let hits = moves.iter().filter(|m| m.is_hit).count() as u32;
+        if moves.len() != state.move_index
+            || hits != state.hits
+            || transcript_hash(&env, &transcript) != state.transcript
+        {
+            return Err(Error::InvalidTranscript);
+        }
+
+        game.moves_made = state.move_index;
+        game.hits = state.hits;
+        game.misses = state.misses;
+        game.last_active_ledger = env.ledger().sequence();
+        game.game_ended = true;
+        game.winner = if game.hits > (game.moves_made / 2) {
+            Some(game.attacker.clone())
+        } else {
+            Some(game.defender.clone())
+        };
+        post_result(&env, session_id, &game, &moves);
+
+        let ttl = game_ttl(&env);
+        let moves_key = DataKey::Moves(session_id);
+        env.storage().temporary().remove(&key);
+        env.storage()
+            .temporary()
+            .remove(&DataKey::EmergencyWithdraw(session_id));
+        env.storage().temporary().set(&game_key, &game);
+        env.storage().temporary().set(&moves_key, &moves);
+        env.storage().temporary().extend_ttl(&moves_key, ttl, ttl);
+
+        GameEnded {
+            session_id,
+            defender: game.defender.clone(),
+            attacker: game.attacker.clone(),
+            winner: game.winner.clone().unwrap(),
+            hits: game.hits,
+            misses: game.misses,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Get a game's open channel, if any
+    pub fn get_channel(env: Env, session_id: u32) -> Option<Channel> {
+        env.storage().temporary().get(&DataKey::Channel(session_id))
+    }
+
+    /// Put a game in optimistic mode; both players, before the first move.
+    /// The defender can then answer submitted moves with `claim_move`,
+    /// without a proof, and the attacker can challenge each claim with
+    /// `challenge_claim` for `CLAIM_CHALLENGE_LEDGERS` after it is made.
+    ///
+    /// Each claim posts `bond` of `token` from the defender. The bonds go
+    /// back to the defender when the game ends, or to the attacker if the
+    /// defender failed to prove a challenged claim.
+    pub fn enable_optimistic(
+        env: Env,
+        session_id: u32,
+        token: Address,
+        bond: i128,
+    ) -> Result<(), Error> {
+        let game = load_game(&env, session_id)?;
+        game.defender.require_auth();
+        game.attacker.require_auth();
+        if game.game_ended {
+            return Err(Error::GameAlreadyEnded);
+        }
+        if bond <= 0 {
+            return Err(Error::StakeNotPositive);
+        }
+        check_no_channel(&env, session_id)?;
+        let key = DataKey::Optimistic(session_id);
+        if game.moves_made > 0
+            || env
+                .storage()
+                .temporary()
+                .has(&DataKey::PendingMove(session_id))
+            || env.storage().temporary().has(&key)
+        {
+            return Err(Error::OptimisticLocked);
+        }
+
+        let optimistic = Optimistic {
+            claims: Map::new(&env),
+            challenged: None,
+            prove_until: 0,
+            token,
+            bond,
+            bonded: 0,
+        };
+        let ttl = game_ttl(&env);
+        env.storage().temporary().set(&key, &optimistic);
+        env.storage().temporary().extend_ttl(&key, ttl, ttl);
+        OptimisticEnabled {
+            session_id,
+            defender: game.defender,
+            attacker: game.attacker,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Answer the submitted move of an optimistic game without a proof;
+    /// defender only, posting the game's claim bond
+    ///
+    /// The move is recorded unverified. A game whose last move is claimed
+    /// ends with `end_game` once no claim can be challenged.
+    pub fn claim_move(env: Env, session_id: u32, is_hit: bool) -> Result<(), Error> {
+        let game_key = DataKey::Game(session_id);
+        let mut game = load_game(&env, session_id)?;
+        game.defender.require_auth();
+        check_not_frozen(&env, session_id)?;
+
+        let key = DataKey::Optimistic(session_id);
+        let mut optimistic: Optimistic = env
+            .storage()
+            .temporary()
+            .get(&key)
+            .ok_or(Error::NotOptimistic)?;
+        let moves_key = DataKey::Moves(session_id);
+        let mut moves: Vec<Move> = env
+            .storage()
+            .temporary()
+            .get(&moves_key)
+            .unwrap_or(vec![&env]);
+        let pending_key = DataKey::PendingMove(session_id);
+        let pending: Option<PendingMove> = env.storage().temporary().get(&pending_key);
+        let Some(PendingMove { x, y }) = pending else {
+            if game.game_ended {
+                return Err(Error::GameAlreadyEnded);
+            }
+            return Err(Error::NoPendingMove);
+        };
+        check_move(&game, &moves, &pending, x, y)?;
+        check_no_channel(&env, session_id)?;
+        check_move_gap(&env, session_id, false, true)?;
+
+        token::Client::new(&env, &optimistic.token).transfer(
+            &game.defender,
+            env.current_contract_address(),
+            &optimistic.bond,
+        );
+        optimistic.bonded += optimistic.bond;
+        optimistic
+            .claims
+            .set(game.moves_made, env.ledger().sequence());
+        moves.push_back(Move {
+            x,
+            y,
             is_hit,
-            verified: true,
+            verified: false,
+        });
+        game.moves_made += 1;
+        game.last_active_ledger = env.ledger().sequence();
+        if is_hit {
+            game.hits += 1;
+        } else {
+            game.misses += 1;
+        }
+
+        resume_play(&env, session_id, &game);
+        record_move_ledgers(&env, session_id, false, true);
+        let ttl = game_ttl(&env);
+        env.storage().temporary().remove(&pending_key);
+        env.storage().temporary().set(&key, &optimistic);
+        env.storage().temporary().set(&game_key, &game);
+        env.storage().temporary().set(&moves_key, &moves);
+        env.storage().temporary().extend_ttl(&moves_key, ttl, ttl);
+
+        MoveMade {
+            session_id,
+            defender: game.defender,
+            attacker: game.attacker,
+            x,
+            y,
+            is_hit,
+            verified: false,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Demand a proof of a claimed move; attacker only, one claim at a time,
+    /// within `CLAIM_CHALLENGE_LEDGERS` of the claim. Returns the last ledger
+    /// the defender can prove it in with `prove_claim`; after that `end_game`
+    /// gives the attacker the game.
+    pub fn challenge_claim(env: Env, session_id: u32, move_index: u32) -> Result<u32, Error> {
+        let mut game = load_game(&env, session_id)?;
+        game.attacker.require_auth();
+        if game.game_ended {
+            return Err(Error::GameAlreadyEnded);
+        }
+        let key = DataKey::Optimistic(session_id);
+        let mut optimistic: Optimistic = env
+            .storage()
+            .temporary()
+            .get(&key)
+            .ok_or(Error::NotOptimistic)?;
+        let moves = Self::get_moves(env.clone(), session_id);
+        let sequence = env.ledger().sequence();
+        let challengeable = optimistic.challenged.is_none()
+            && optimistic
+                .claims
+                .get(move_index)
+                .is_some_and(|ledger| sequence <= ledger + CLAIM_CHALLENGE_LEDGERS)
+            && moves.get(move_index).is_some_and(|m| !m.verified);
+        if !challengeable {
+            return Err(Error::ClaimNotChallengeable);
+        }
+
+        let prove_until = sequence + CLAIM_CHALLENGE_LEDGERS;
+        optimistic.challenged = Some(move_index);
+        optimistic.prove_until = prove_until;
+        let ttl = game_ttl(&env);
+        env.storage().temporary().set(&key, &optimistic);
+        env.storage().temporary().extend_ttl(&key, ttl, ttl);
+        game.last_active_ledger = sequence;
+        env.storage()
+            .temporary()
+            .set(&DataKey::Game(session_id), &game);
+
+        ClaimChallenged {
+            session_id,
+            defender: game.defender,
+            attacker: game.attacker,
+            move_index,
+            prove_until,
+        }
+        .publish(&env);
+        Ok(prove_until)
+    }
+
+    /// Prove the challenged claim of an optimistic game, like `make_move`
+    /// proves a move; whoever holds the proof submits it
+    ///
+    /// # Arguments
+    /// * `session_id` - Game session identifier
+    /// * `proof` - ZK proof of the claim
+    /// * `public_inputs` - Public inputs of the claimed move and answer, on the
+    ///   grid committed at setup
+    pub fn prove_claim(
+        env: Env,
+        session_id: u32,
+        proof: Bytes,
+        public_inputs: Bytes,
+    ) -> Result<(), Error> {
+        let mut game = load_game(&env, session_id)?;
+        if game.game_ended {
+            return Err(Error::GameAlreadyEnded);
+        }
+        let key = DataKey::Optimistic(session_id);
+        let mut optimistic: Optimistic = env
+            .storage()
+            .temporary()
+            .get(&key)
+            .ok_or(Error::NotOptimistic)?;
+        let move_index = optimistic.challenged.ok_or(Error::NoChallenge)?;
+        if env.ledger().sequence() > optimistic.prove_until {
+            return Err(Error::ProofDeadlinePassed);
+        }
+        let moves_key = DataKey::Moves(session_id);
+        let mut moves = Self::get_moves(env.clone(), session_id);
+        let mut claimed = moves.get(move_index).expect("challenged move is recorded");
+        if !public_inputs_match(
+            &env,
+            session_id,
+            &public_inputs,
+            &game,
+            claimed.x,
+            claimed.y,
+            claimed.is_hit,
+        ) {
+            return Err(Error::InvalidProof);
+        }
+        if !proof_verifies(&env, &DataKey::VerifierAddress, &public_inputs, &proof) {
+            return Err(Error::InvalidProof);
+        }
+
+        claimed.verified = true;
+        release_progressive(&env, session_id, &game, claimed.is_hit);
+        moves.set(move_index, claimed);
+        optimistic.challenged = None;
+        game.last_active_ledger = env.ledger().sequence();
+        resume_play(&env, session_id, &game);
+        env.storage().temporary().set(&key, &optimistic);
+        env.storage().temporary().set(&moves_key, &moves);
+        env.storage()
+            .temporary()
+            .set(&DataKey::Game(session_id), &game);
+
+        ClaimProven {
+            session_id,
+            defender: game.defender,
+            attacker: game.attacker,
+            move_index,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Get the claims of a game in optimistic mode
+    pub fn get_optimistic(env: Env, session_id: u32) -> Option<Optimistic> {
+        env.storage()
+            .temporary()
+            .get(&DataKey::Optimistic(session_id))
+    }
+
+    /// Get the arbitration of a game, if it has an arbiter and has not
+    /// been settled
+    pub fn get_arbitration(env: Env, session_id: u32) -> Option<Arbitration> {
+        env.storage()
+            .temporary()
+            .get(&DataKey::Arbitration(session_id))
+    }
+
+    /// Flag an arbitrated game's result within the appeal window, freezing
+    /// its settlement until the arbiter rules; players only
+    pub fn appeal(env: Env, session_id: u32, player: Address) -> Result<(), Error> {
+        player.require_auth();
+        let game = load_game(&env, session_id)?;
+        if player != game.defender && player != game.attacker {
+            return Err(Error::NotPlayer);
+        }
+        let mut arbitration = pending_arbitration(&env, session_id, &game)?;
+        if arbitration.appellant.is_some() {
+            return Err(Error::UnderAppeal);
+        }
+        if env.ledger().sequence() > arbitration.appeal_until {
+            return Err(Error::AppealWindowClosed);
+        }
+
+        arbitration.appellant = Some(player.clone());
+        env.storage()
+            .temporary()
+            .set(&DataKey::Arbitration(session_id), &arbitration);
+        ResultAppealed {
+            session_id,
+            defender: game.defender,
+            attacker: game.attacker,
+            appellant: player,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Settle an arbitrated game's result once its appeal window has
+    /// closed unappealed; anyone can call it
+    pub fn finalize_game(env: Env, session_id: u32) -> Result<(), Error> {
+        let game = load_game(&env, session_id)?;
+        check_not_frozen(&env, session_id)?;
+        let arbitration = pending_arbitration(&env, session_id, &game)?;
+        if arbitration.appellant.is_some() {
+            return Err(Error::UnderAppeal);
+        }
+        if env.ledger().sequence() <= arbitration.appeal_until {
+            return Err(Error::AppealWindowOpen);
+        }
+
+        env.storage()
+            .temporary()
+            .remove(&DataKey::Arbitration(session_id));
+        settle(
+            &env,
+            session_id,
+            &game,
+            &Self::get_moves(env.clone(), session_id),
+        );
+        Ok(())
+    }
+
+    /// Rule on an appealed result and settle the game; the arbiter only.
+    /// The Game Hub cannot void a session, so a voided game leaves it
+    /// unsettled, like an emergency withdrawal.
+    pub fn resolve_appeal(env: Env, session_id: u32, ruling: Ruling) -> Result<(), Error> {
+        let game_key = DataKey::Game(session_id);
+        let mut game = load_game(&env, session_id)?;
+        let arbitration = pending_arbitration(&env, session_id, &game)?;
+        arbitration.arbiter.require_auth();
+        if arbitration.appellant.is_none() {
+            return Err(Error::NotAppealed);
+        }
+        check_not_frozen(&env, session_id)?;
+
+        env.storage()
+            .temporary()
+            .remove(&DataKey::Arbitration(session_id));
+        let moves = Self::get_moves(env.clone(), session_id);
+        match ruling {
+            Ruling::Confirm => settle(&env, session_id, &game, &moves),
+            Ruling::Overturn => {
+                game.winner = if game.winner.as_ref() == Some(&game.defender) {
+                    Some(game.attacker.clone())
+                } else {
+                    Some(game.defender.clone())
+                };
+                settle(&env, session_id, &game, &moves);
+            }
+            Ruling::Void => {
+                game.winner = None;
+                refund_sponsorships(&env, session_id);
+                game_finished(&env, session_id, &game, &moves, false);
+            }
+        }
+        env.storage().temporary().set(&game_key, &game);
+
+        AppealResolved {
+            session_id,
+            defender: game.defender,
+            attacker: game.attacker,
+            ruling,
+            winner: game.winner,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Hold one game while a dispute or a bug is looked into: its moves and
+    /// settlement fail with `GameFrozen` until `unfreeze_session`, while
+    /// every other game plays on. The game's arbiter or the admin only
+    pub fn freeze_session(env: Env, caller: Address, session_id: u32) -> Result<(), Error> {
+        let game = require_freezer(&env, &caller, session_id)?;
+        let key = DataKey::Frozen(session_id);
+        let ttl = game_ttl(&env);
+        env.storage().temporary().set(&key, &caller);
+        env.storage().temporary().extend_ttl(&key, ttl, ttl);
+        SessionFrozen {
+            session_id,
+            defender: game.defender,
+            attacker: game.attacker,
+            by: caller,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Let a frozen game play and settle again; the game's arbiter or the
+    /// admin only. Clocks such as the expiry and the appeal window kept
+    /// running while it was frozen.
+    pub fn unfreeze_session(env: Env, caller: Address, session_id: u32) -> Result<(), Error> {
+        let game = require_freezer(&env, &caller, session_id)?;
+        env.storage()
+            .temporary()
+            .remove(&DataKey::Frozen(session_id));
+        SessionUnfrozen {
+            session_id,
+            defender: game.defender,
+            attacker: game.attacker,
+            by: caller,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    /// The arbiter or admin that froze a game, if it is frozen
+    pub fn get_frozen(env: Env, session_id: u32) -> Option<Address> {
+        env.storage().temporary().get(&DataKey::Frozen(session_id))
+    }
+
+    /// Queue an emergency withdrawal of a game stuck for good; admin only.
+    /// Returns the ledger `emergency_withdraw` can run from, a public delay
+    /// during which any move by a player cancels it.
+    pub fn queue_emergency_withdraw(env: Env, session_id: u32) -> Result<u32, Error> {
+        admin(&env).require_auth();
+        let game = Self::get_game(env.clone(), session_id)?;
+        if game.game_ended {
+            return Err(Error::GameAlreadyEnded);
+        }
+        let config = config(&env);
+        let ttl = config.game_ttl_ledgers;
+        let ready_ledger = env.ledger().sequence() + config.emergency_delay_ledgers;
+        let key = DataKey::EmergencyWithdraw(session_id);
+        env.storage().temporary().set(&key, &ready_ledger);
+        env.storage().temporary().extend_ttl(&key, ttl, ttl);
+        EmergencyWithdrawQueued {
+            session_id,
+            defender: game.defender,
+            attacker: game.attacker,
+            ready_ledger,
+        }
+        .publish(&env);
+        Ok(ready_ledger)
+    }
+
+    /// Get the ledger a settled game's payout can be claimed from, if it is
+    /// waiting to be claimed
+    pub fn get_claim(env: Env, session_id: u32) -> Option<u32> {
+        env.storage().temporary().get(&DataKey::Claim(session_id))
+    }
+
+    /// Pay out a settled game's sponsorships once the claim delay has
+    /// passed; the winner only
+    pub fn claim_winnings(env: Env, session_id: u32) -> Result<(), Error> {
+        let game = load_game(&env, session_id)?;
+        check_not_frozen(&env, session_id)?;
+        let key = DataKey::Claim(session_id);
+        let claimable_from: u32 = env
+            .storage()
+            .temporary()
+            .get(&key)
+            .ok_or(Error::NothingToClaim)?;
+        let winner = game.winner.clone().expect("settled game has a winner");
+        winner.require_auth();
+        if env.ledger().sequence() < claimable_from {
+            return Err(Error::ClaimNotReady);
+        }
+
+        env.storage().temporary().remove(&key);
+        settle_sponsorships(&env, session_id, &game);
+        WinningsClaimed {
+            session_id,
+            defender: game.defender,
+            attacker: game.attacker,
+            winner,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Get the ledger a queued emergency withdrawal can run from, if queued
+    pub fn get_emergency_withdraw(env: Env, session_id: u32) -> Option<u32> {
+        env.storage()
+            .temporary()
+            .get(&DataKey::EmergencyWithdraw(session_id))
+    }
+
+    /// Run a queued emergency withdrawal once its delay has passed; admin
+    /// only. Ends the game without a winner or Game Hub settlement and
+    /// returns every sponsor's stake.
+    pub fn emergency_withdraw(env: Env, session_id: u32) -> Result<(), Error> {
+        admin(&env).require_auth();
+        let game_key = DataKey::Game(session_id);
+        let mut game = load_game(&env, session_id)?;
+        if game.game_ended {
+            return Err(Error::GameAlreadyEnded);
+        }
+        let ready_ledger = Self::get_emergency_withdraw(env.clone(), session_id)
+            .ok_or(Error::WithdrawNotQueued)?;
+        if env.ledger().sequence() < ready_ledger {
+            return Err(Error::WithdrawTimelocked);
+        }
+        check_not_frozen(&env, session_id)?;
+
+        refund_sponsorships(&env, session_id);
+        game.game_ended = true;
+        game_finished(
+            &env,
+            session_id,
+            &game,
+            &Self::get_moves(env.clone(), session_id),
+            false,
+        );
+        for key in [
+            DataKey::PendingMove(session_id),
+            DataKey::EmergencyWithdraw(session_id),
+        ] {
+            env.storage().temporary().remove(&key);
+        }
+        env.storage().temporary().set(&game_key, &game);
+
+        EmergencyWithdrawn {
+            session_id,
+            defender: game.defender,
+            attacker: game.attacker,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Vote to abort a game whose verifier no longer checks proofs (paused,
+    /// removed or trapping), so no move can be made; players and the admin
+    /// only. Once both players, or a player and the admin, voted, the game
+    /// ends without a winner or Game Hub settlement and every sponsor's stake
+    /// is returned. A move by either player clears the votes.
+    ///
+    /// Returns whether this vote aborted the game.
+    pub fn abort_due_to_verifier(env: Env, session_id: u32, voter: Address) -> Result<bool, Error> {
+        voter.require_auth();
+        let game_key = DataKey::Game(session_id);
+        let mut game = load_game(&env, session_id)?;
+        let admin = admin(&env);
+        if voter != game.defender && voter != game.attacker && voter != admin {
+            return Err(Error::NotPlayer);
+        }
+        if game.game_ended {
+            return Err(Error::GameAlreadyEnded);
+        }
+        check_not_frozen(&env, session_id)?;
+
+        let votes_key = DataKey::AbortVotes(session_id);
+        let mut votes: Vec<Address> = env
+            .storage()
+            .temporary()
+            .get(&votes_key)
+            .unwrap_or(vec![&env]);
+        if !votes.contains(&voter) {
+            votes.push_back(voter.clone());
+        }
+        let players = [&game.defender, &game.attacker]
+            .into_iter()
+            .filter(|player| votes.contains(*player))
+            .count();
+        if players < 2 && !(players == 1 && votes.contains(&admin)) {
+            let ttl = game_ttl(&env);
+            env.storage().temporary().set(&votes_key, &votes);
+            env.storage().temporary().extend_ttl(&votes_key, ttl, ttl);
+            AbortRequested {
+                session_id,
+                defender: game.defender,
+                attacker: game.attacker,
+                voter,
+            }
+            .publish(&env);
+            return Ok(false);
+        }
+
+        refund_sponsorships(&env, session_id);
+        game.game_ended = true;
+        game_finished(
+            &env,
+            session_id,
+            &game,
+            &Self::get_moves(env.clone(), session_id),
+            false,
+        );
+        for key in [
+            DataKey::PendingMove(session_id),
+            DataKey::EmergencyWithdraw(session_id),
+            votes_key,
+        ] {
+            env.storage().temporary().remove(&key);
+        }
+        env.storage().temporary().set(&game_key, &game);
+
+        GameAborted {
+            session_id,
+            defender: game.defender,
+            attacker: game.attacker,
+        }
+        .publish(&env);
+        Ok(true)
+    }
+
+    /// Get game state
+    pub fn get_game(env: Env, session_id: u32) -> Result<Game, Error> {
+        load_game(&env, session_id)
+    }
+
+    /// Get the move waiting for the defender's answer, if any
+    pub fn get_pending_move(env: Env, session_id: u32) -> Option<PendingMove> {
+        env.storage()
+            .temporary()
+            .get(&DataKey::PendingMove(session_id))
+    }
+
+    /// Get all moves for a game
+    pub fn get_moves(env: Env, session_id: u32) -> Vec<Move> {
+        let moves_key = DataKey::Moves(session_id);
+        env.storage()
+            .temporary()
+            .get(&moves_key)
+            .unwrap_or(vec![&env])
+    }
+
+    /// Get a game with its configuration, pending move, deadlines and last
+    /// `FULL_STATE_MOVES` moves, so clients poll it in one read
+    pub fn get_full_state(env: Env, session_id: u32) -> Result<FullState, Error> {
+        let game = load_game(&env, session_id)?;
+        let config = config(&env);
+        let moves = Self::get_moves(env.clone(), session_id);
+        let recent_moves = moves.slice(moves.len().saturating_sub(FULL_STATE_MOVES)..);
+        let arbitration: Option<Arbitration> = env
+            .storage()
+            .temporary()
+            .get(&DataKey::Arbitration(session_id));
+        let deadlines = Deadlines {
+            expires_after: (config.expiry_ledgers != 0 && !game.game_ended).then(|| {
+                game.last_active_ledger
+                    .saturating_add(config.expiry_ledgers)
+            }),
+            appeal_until: arbitration
+                .map(|arbitration| arbitration.appeal_until)
+                .filter(|appeal_until| *appeal_until != 0),
+            claimable_from: Self::get_claim(env.clone(), session_id),
+            emergency_withdraw_from: Self::get_emergency_withdraw(env.clone(), session_id),
+        };
+        Ok(FullState {
+            pending_move: Vec::from_iter(&env, Self::get_pending_move(env.clone(), session_id)),
+            game,
+            config,
+            deadlines,
+            recent_moves,
+        })
+    }
+
+    /// Get the rules a game is played under: its grid and trap count, how it
+    /// is won or expires, its deadlines, stakes, fees and the verifiers of
+    /// its proofs
+    pub fn get_rules(env: Env, session_id: u32) -> Result<Rules, Error> {
+        let game = load_game(&env, session_id)?;
+        let config = config(&env);
+        let fees = Self::get_fees(env.clone());
+        let storage = env.storage().instance();
+        Ok(Rules {
+            grid_size: game.grid_size,
+            trap_count: game.trap_count,
+            win_policy: WinPolicy::MajorityHits,
+            expiry_policy: config.expiry_policy,
+            expiry_ledgers: config.expiry_ledgers,
+            move_gap_ledgers: config.move_gap_ledgers,
+            appeal_window_ledgers: config.appeal_window_ledgers,
+            claim_delay_ledgers: config.claim_delay_ledgers,
+            emergency_delay_ledgers: config.emergency_delay_ledgers,
+            channel_challenge_ledgers: CHANNEL_CHALLENGE_LEDGERS,
+            claim_challenge_ledgers: CLAIM_CHALLENGE_LEDGERS,
+            min_points: config.min_points,
+            max_points: config.max_points,
+            protocol_fee_bps: fees.as_ref().map_or(0, |fees| fees.protocol_bps),
+            referral_fee_bps: fees.map_or(0, |fees| fees.referral_bps),
+            progressive_payout_bps: config.progressive_payout_bps,
+            bonus_curve: config.bonus_curve,
+            streak_bonus: config.streak_bonus,
+            min_games_completed: config.min_games_completed,
+            verifier: storage
+                .get(&DataKey::VerifierAddress)
+                .expect("Verifier address not set"),
+            setup_verifier: storage
+                .get(&DataKey::SetupVerifierAddress)
+                .expect("Setup verifier address not set"),
+        })
+    }
+
+    /// Interface version, bumped on changes old clients cannot follow
+    pub fn version(_env: Env) -> u32 {
+        INTERFACE_VERSION
+    }
+
+    /// SHA-256 of the canonical interface description clients compare against
+    pub fn interface_hash(env: Env) -> BytesN<32> {
+        env.crypto()
+            .sha256(&Bytes::from_slice(&env, INTERFACE.as_bytes()))
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use super::*;
+    use crate::testutils::{
+        grid_setup, moves, public_inputs, valid_proof, MockGameHub, MockVerifier, TestMove,
+        TestSetup, DEFAULT_POINTS,
+    };
+    use soroban_sdk::testutils::storage::{Persistent as _, Temporary as _};
+    use soroban_sdk::testutils::{
+        Address as _, AuthorizedFunction, AuthorizedInvocation, Events as _, Ledger as _,
+    };
+    use soroban_sdk::{symbol_short, Event};
+
+    #[test]
+    fn test_game_initialization() {
+        let env = Env::default();
+
+        let admin = Address::generate(&env);
+        let game_hub = Address::generate(&env);
+        let verifier = Address::generate(&env);
+        let setup_verifier = Address::generate(&env);
+
+        // The constructor runs on registration
+        env.register(
+            TrapGridContract,
+            (&admin, &game_hub, &verifier, &setup_verifier),
+        );
+
+        // Test basic initialization
+        // Note: More comprehensive tests would require mock contracts for game_hub and verifier
+    }
+
+    #[test]
+    fn test_get_game_not_found() {
+        let env = Env::default();
+        let setup = TestSetup::new(&env);
+
+        assert_eq!(setup.client.try_get_game(&1), Err(Ok(Error::GameNotFound)));
+    }
+
+    #[test]
+    fn test_interface() {
+        let env = Env::default();
+        let setup = TestSetup::new(&env);
+
+        assert_eq!(setup.client.version(), INTERFACE_VERSION);
+        let hash = env
+            .crypto()
+            .sha256(&Bytes::from_slice(&env, INTERFACE.as_bytes()));
+        assert_eq!(setup.client.interface_hash(), hash.to_bytes());
+    }
+
+    #[test]
+    fn test_admin_set_proposals() {
+        let env = Env::default();
+        let setup = TestSetup::new(&env);
+        let client = &setup.client;
+        let members = [
+            setup.admin.clone(),
+            Address::generate(&env),
+            Address::generate(&env),
+        ];
+        let set = vec![
+            &env,
+            members[0].clone(),
+            members[1].clone(),
+            members[2].clone(),
+        ];
+
+        // The constructor's admin alone runs proposals until a set is chosen
+        assert_eq!(
+            client.get_admin_set().members,
+            vec![&env, setup.admin.clone()]
+        );
+        let id = client.propose(&members[0], &AdminAction::SetAdminSet(set.clone(), 2));
+        assert_eq!(
+            client.try_get_proposal(&id),
+            Err(Ok(Error::ProposalNotFound))
+        );
+        assert_eq!(
+            client.get_admin_set(),
+            AdminSet {
+                members: set,
+                threshold: 2
+            }
+        );
+
+        // Two of three now: a proposal waits for a second member
+        let verifier = Address::generate(&env);
+        let id = client.propose(&members[1], &AdminAction::SetVerifier(verifier.clone()));
+        assert_eq!(
+            client.try_approve(&members[1], &id),
+            Err(Ok(Error::AlreadyApproved))
+        );
+        assert_eq!(
+            client.try_approve(&Address::generate(&env), &id),
+            Err(Ok(Error::NotAdminMember))
+        );
+        assert_eq!(client.get_proposal(&id).approvals.len(), 1);
+        assert!(client.approve(&members[2], &id));
+        assert_eq!(
+            env.as_contract(&setup.contract, || env
+                .storage()
+                .instance()
+                .get::<DataKey, Address>(&DataKey::VerifierAddress)),
+            Some(verifier)
+        );
+
+        // Unapproved past the expiry, a proposal can no longer run
+        let id = client.propose(&members[0], &AdminAction::SetFees(1_000, 0));
+        env.ledger()
+            .with_mut(|l| l.sequence_number += PROPOSAL_EXPIRY_LEDGERS + 1);
+        assert_eq!(
+            client.try_approve(&members[1], &id),
+            Err(Ok(Error::ProposalExpired))
+        );
+        assert_eq!(client.get_fees(), None);
+    }
+
+    #[test]
+    fn test_operator_maintenance() {
+        let env = Env::default();
+        let setup = TestSetup::new(&env);
+        let client = &setup.client;
+        let operator = Address::generate(&env);
+        setup.start_game(1);
+
+        assert_eq!(
+            client.try_set_paused(&operator, &true),
+            Err(Ok(Error::NotOperator))
+        );
+        client.grant_operator(&operator);
+        assert!(client.is_operator(&operator));
+
+        // Extending a game pushes its storage out to the full TTL again
+        let ttl = || {
+            env.as_contract(&setup.contract, || {
+                env.storage().temporary().get_ttl(&DataKey::Game(1))
+            })
+        };
+        env.ledger().with_mut(|l| l.sequence_number += 1_000);
+        let before = ttl();
+        client.extend_game_ttl(&operator, &1);
+        assert_eq!(ttl(), before + 1_000);
+        assert_eq!(
+            client.try_extend_game_ttl(&operator, &2),
+            Err(Ok(Error::GameNotFound))
+        );
+
+        // Pausing stops new games only
+        client.set_paused(&operator, &true);
+        assert!(client.is_paused());
+        let (defender, attacker) = (Address::generate(&env), Address::generate(&env));
+        assert_eq!(
+            client.try_start_game(&2, &defender, &attacker, &100, &100, &grid_setup(&env)),
+            Err(Ok(Error::Paused))
+        );
+        client.submit_move(&1, &2, &3);
+        client.set_paused(&setup.admin, &false);
+        client.start_game(&2, &defender, &attacker, &100, &100, &grid_setup(&env));
+
+        // The role outlives any game, and each use keeps it for the full TTL
+        let role_ttl = || {
+            env.as_contract(&setup.contract, || {
+                env.storage()
+                    .persistent()
+                    .get_ttl(&DataKey::Operator(operator.clone()))
+            })
+        };
+        assert_eq!(role_ttl(), MAX_TTL_LEDGERS);
+        env.ledger()
+            .with_mut(|l| l.sequence_number += GAME_TTL_LEDGERS + 1);
+        assert_eq!(role_ttl(), MAX_TTL_LEDGERS - GAME_TTL_LEDGERS - 1);
+        assert!(client.is_operator(&operator));
+        assert_eq!(role_ttl(), MAX_TTL_LEDGERS - GAME_TTL_LEDGERS - 1);
+        client.set_paused(&operator, &false);
+        assert_eq!(role_ttl(), MAX_TTL_LEDGERS);
+
+        client.revoke_operator(&operator);
+        assert_eq!(
+            client.try_set_paused(&operator, &true),
+            Err(Ok(Error::NotOperator))
+        );
+    }
+
+    #[test]
+    fn test_old_schema_games_migrate() {
+        let env = Env::default();
+        let setup = TestSetup::new(&env);
+        let (defender, attacker) = setup.start_game(1);
+        let record = |env: &Env| {
+            env.as_contract(&setup.contract, || {
+                env.storage()
+                    .temporary()
+                    .get::<DataKey, Map<Symbol, Val>>(&DataKey::Game(1))
+                    .unwrap()
+            })
+        };
+        let store = |record: Val| {
+            env.as_contract(&setup.contract, || {
+                env.storage().temporary().set(&DataKey::Game(1), &record)
+            })
+        };
+
+        // A game stored by the contract before schema versions
+        let legacy = GameV1 {
+            defender: defender.clone(),
+            attacker,
+            defender_points: 100,
+            attacker_points: 100,
+            moves_made: 0,
+            hits: 0,
+            misses: 0,
+            game_started: true,
+            game_ended: false,
+            winner: None,
+        };
+        store(legacy.clone().into_val(&env));
+        assert!(!record(&env).contains_key(Symbol::new(&env, "schema_version")));
+
+        // Records from before activity was tracked count as active when read
+        env.ledger().with_mut(|l| l.sequence_number = 100);
+        let migrated = Game {
+            last_active_ledger: 100,
+            trap_count: TRAP_COUNT,
+            ..Game::from(GameV4::from(GameV3::from(GameV2::from(legacy.clone()))))
+        };
+        assert_eq!(setup.client.get_game(&1), migrated);
+        store(GameV2::from(legacy.clone()).into_val(&env));
+        assert_eq!(setup.client.get_game(&1), migrated);
+        let v3 = GameV3::from(GameV2::from(legacy));
+        store(v3.clone().into_val(&env));
+        assert_eq!(setup.client.get_game(&1).grid_size, DEFAULT_GRID_SIZE);
+        store(GameV4::from(v3).into_val(&env));
+        assert_eq!(setup.client.get_game(&1).trap_count, TRAP_COUNT);
+        assert_eq!(migrated.schema_version, GAME_SCHEMA_VERSION);
+
+        // Play goes on, and the next write stores the current schema
+        setup.play(1, moves(|x, y| x == y)).unwrap();
+        assert!(record(&env).contains_key(Symbol::new(&env, "last_active_ledger")));
+        assert_eq!(setup.client.get_game(&1).winner, Some(defender));
+    }
+
+    #[test]
+    fn test_config() {
+        let env = Env::default();
+        let setup = TestSetup::new(&env);
+        let (contract_id, client) = (&setup.contract, &setup.client);
+        assert_eq!(client.get_config(), ContractConfig::DEFAULT);
+
+        let config = ContractConfig {
+            game_ttl_ledgers: 100_000,
+            emergency_delay_ledgers: 20_000,
+            proposal_expiry_ledgers: 30_000,
+            max_active_games: 1,
+            min_points: 10,
+            max_points: 1_000,
+            expiry_ledgers: 50_000,
+            expiry_policy: ExpiryPolicy::DefenderWins,
+            appeal_window_ledgers: 40_000,
+            claim_delay_ledgers: 30_000,
+            reject_reused_roots: false,
+            trap_count: 12,
+            move_gap_ledgers: 3,
+            grid_size: DEFAULT_GRID_SIZE,
+            progressive_payout_bps: 500,
+            bonus_curve: PayoutCurve {
+                max_bonus_bps: 2_000,
+                threshold_bps: 5_000,
+                exponent: 2,
+            },
+            streak_bonus: StreakBonus {
+                min_streak: 3,
+                fee_rebate_bps: 2_500,
+            },
+            min_games_completed: 0,
+        };
+        client.set_config(&config);
+        assert_eq!(
+            env.events().all().filter_by_contract(contract_id),
+            [ConfigUpdated {
+                game_ttl_ledgers: 100_000,
+                emergency_delay_ledgers: 20_000,
+                proposal_expiry_ledgers: 30_000,
+                max_active_games: 1,
+                min_points: 10,
+                max_points: 1_000,
+                expiry_ledgers: 50_000,
+                expiry_policy: ExpiryPolicy::DefenderWins,
+                appeal_window_ledgers: 40_000,
+                claim_delay_ledgers: 30_000,
+                reject_reused_roots: false,
+                trap_count: 12,
+                move_gap_ledgers: 3,
+                grid_size: DEFAULT_GRID_SIZE,
+                progressive_payout_bps: 500,
+                bonus_curve: config.bonus_curve.clone(),
+                streak_bonus: config.streak_bonus.clone(),
+                min_games_completed: 0,
+            }
+            .to_xdr(&env, contract_id)]
+        );
+        assert_eq!(client.get_config(), config);
+
+        // The settings apply to the next game
+        setup.start_game(1);
+        assert_eq!(client.get_active_games(), 1);
+        let ttl = env.as_contract(contract_id, || {
+            env.storage().temporary().get_ttl(&DataKey::Game(1))
+        });
+        assert_eq!(ttl, 100_000);
+        assert_eq!(
+            client.queue_emergency_withdraw(&1),
+            env.ledger().sequence() + 20_000
+        );
+        let (defender, attacker) = (Address::generate(&env), Address::generate(&env));
+        assert_eq!(
+            client.try_start_game(&2, &defender, &attacker, &100, &100, &grid_setup(&env)),
+            Err(Ok(Error::TooManyGames))
+        );
+        for (defender_points, attacker_points, error) in [
+            (0, 100, Error::StakeNotPositive),
+            (100, -5, Error::StakeNotPositive),
+            (9, 100, Error::StakeOutOfBounds),
+            (100, 1_001, Error::StakeOutOfBounds),
+        ] {
+            assert_eq!(
+                client.try_start_game(
+                    &2,
+                    &defender,
+                    &attacker,
+                    &defender_points,
+                    &attacker_points,
+                    &grid_setup(&env)
+                ),
+                Err(Ok(error))
+            );
+        }
+        client.end_game(&1);
+        assert_eq!(client.get_active_games(), 0);
+        client.start_game(&2, &defender, &attacker, &100, &100, &grid_setup(&env));
+
+        for invalid in [
+            ContractConfig {
+                game_ttl_ledgers: 3_110_401,
+                ..config.clone()
+            },
+            ContractConfig {
+                proposal_expiry_ledgers: 100,
+                ..config.clone()
+            },
+            ContractConfig {
+                min_points: 0,
+                ..config.clone()
+            },
+            ContractConfig {
+                max_points: 9,
+                ..config.clone()
+            },
+            ContractConfig {
+                expiry_ledgers: 100_000,
+                ..config.clone()
+            },
+            ContractConfig {
+                appeal_window_ledgers: 0,
+                ..config.clone()
+            },
+            ContractConfig {
+                claim_delay_ledgers: 1_000,
+                ..config.clone()
+            },
+            ContractConfig {
+                trap_count: 0,
+                ..config.clone()
+            },
+            ContractConfig {
+                trap_count: 64,
+                ..config.clone()
+            },
+            ContractConfig {
+                grid_size: 1,
+                ..config.clone()
+            },
+            ContractConfig {
+                grid_size: 17,
+                ..config.clone()
+            },
+            ContractConfig {
+                progressive_payout_bps: 10_001,
+                ..config.clone()
+            },
+            ContractConfig {
+                streak_bonus: StreakBonus {
+                    min_streak: 1,
+                    fee_rebate_bps: 10_001,
+                },
+                ..config.clone()
+            },
+            ContractConfig {
+                bonus_curve: PayoutCurve {
+                    exponent: 0,
+                    ..PayoutCurve::NONE
+                },
+                ..config.clone()
+            },
+            ContractConfig {
+                bonus_curve: PayoutCurve {
+                    threshold_bps: 10_000,
+                    ..PayoutCurve::NONE
+                },
+                ..config.clone()
+            },
+        ] {
+            assert_eq!(
+                client.try_set_config(&invalid),
+                Err(Ok(Error::InvalidConfig))
+            );
+        }
+    }
+
+    #[test]
+    fn test_grid_size() {
+        let env = Env::default();
+        let setup = TestSetup::new(&env);
+        let client = &setup.client;
+        setup.start_game(1);
+        client.set_config(&ContractConfig {
+            grid_size: MAX_GRID_SIZE,
+            trap_count: 40,
+            ..ContractConfig::DEFAULT
+        });
+
+        // A game keeps the size it started with
+        setup.start_game(2);
+        assert_eq!(client.get_game(&1).grid_size, DEFAULT_GRID_SIZE);
+        assert_eq!(client.get_game(&2).grid_size, MAX_GRID_SIZE);
+        assert_eq!(
+            client.try_submit_move(&1, &8, &0),
+            Err(Ok(Error::InvalidMove))
+        );
+
+        // Proofs are bound to the game's size
+        client.submit_move(&2, &15, &15);
+        let inputs = |grid_size| {
+            let inputs = PositionMovement {
+                move_x: 15,
+                move_y: 15,
+                is_hit: true,
+                grid_size,
+                grid_commitment: client.get_grid_commitment(&2).unwrap().to_array(),
+            };
+            Bytes::from_array(&env, &inputs.encode())
+        };
+        assert_eq!(
+            client.try_make_move(
+                &2,
+                &15,
+                &15,
+                &true,
+                &valid_proof(&env),
+                &inputs(DEFAULT_GRID_SIZE)
+            ),
+            Err(Ok(Error::InvalidProof))
+        );
+        client.make_move(
+            &2,
+            &15,
+            &15,
+            &true,
+            &valid_proof(&env),
+            &inputs(MAX_GRID_SIZE),
+        );
+        assert_eq!(
+            client.try_submit_move(&2, &16, &0),
+            Err(Ok(Error::InvalidMove))
+        );
+        assert_eq!(client.get_heatmap().probes.len(), 256);
+    }
+
+    #[test]
+    fn test_expiry_policy() {
+        let hit = TestMove {
+            x: 0,
+            y: 0,
+            is_hit: true,
+        };
+        for (policy, attacker_wins, completed) in [
+            (ExpiryPolicy::DefenderWins, Some(false), 1),
+            (ExpiryPolicy::AttackerWins, Some(true), 1),
+            (ExpiryPolicy::Draw, None, 1),
+            (ExpiryPolicy::Refund, None, 0),
+        ] {
+            let env = Env::default();
+            let setup = TestSetup::new(&env);
+            let client = &setup.client;
+            client.set_config(&ContractConfig {
+                expiry_ledgers: 20_000,
+                expiry_policy: policy,
+                ..ContractConfig::DEFAULT
+            });
+
+            // A move keeps a game going, and the score decides how it ends
+            let (_, attacker) = setup.start_game(1);
+            env.ledger().with_mut(|l| l.sequence_number += 15_000);
+            setup.make_move(1, &hit);
+            env.ledger().with_mut(|l| l.sequence_number += 15_000);
+            client.end_game(&1);
+            assert_eq!(client.get_game(&1).winner, Some(attacker));
+
+            // Past the expiry, the policy decides whatever the score
+            let (defender, attacker) = setup.start_game(2);
+            setup.make_move(2, &hit);
+            env.ledger().with_mut(|l| l.sequence_number += 20_001);
+            client.end_game(&2);
+            assert_eq!(
+                env.events()
+                    .all()
+                    .filter_by_contract(&setup.contract)
+                    .events()[0],
+                GameExpired {
+                    session_id: 2,
+                    defender: defender.clone(),
+                    attacker: attacker.clone(),
+                    policy,
+                }
+                .to_xdr(&env, &setup.contract)
+            );
+            let winner = attacker_wins.map(|attacker_wins| match attacker_wins {
+                true => attacker,
+                false => defender,
+            });
+            assert_eq!(client.get_game(&2).winner, winner);
+            assert_eq!(client.get_global_stats().games_completed, 1 + completed);
+        }
+    }
+
+    #[test]
+    fn test_end_game_requires_players() {
+        let env = Env::default();
+        let setup = TestSetup::new(&env);
+        let (contract_id, client) = (&setup.contract, &setup.client);
+        client.set_config(&ContractConfig {
+            expiry_ledgers: 20_000,
+            ..ContractConfig::DEFAULT
+        });
+        let (defender, attacker) = setup.start_game(1);
+        setup.start_game(2);
+
+        // A live game ends early only when both players agree
+        client.end_game(&1);
+        let invocation = AuthorizedInvocation {
+            function: AuthorizedFunction::Contract((
+                contract_id.clone(),
+                Symbol::new(&env, "end_game"),
+                (1u32,).into_val(&env),
+            )),
+            sub_invocations: std::vec![],
+        };
+        assert_eq!(
+            env.auths(),
+            [(defender, invocation.clone()), (attacker, invocation)]
+        );
+
+        // Once it expires, anyone can end it
+        env.set_auths(&[]);
+        assert!(client.try_end_game(&2).is_err());
+        env.ledger().with_mut(|l| l.sequence_number += 20_001);
+        client.end_game(&2);
+        assert!(client.get_game(&2).game_ended);
+    }
+
+    #[test]
+    fn test_grid_root_reuse() {
+        let env = Env::default();
+        let setup = TestSetup::new(&env);
+        let client = &setup.client;
+        let root = BytesN::from_array(&env, &[1; 32]);
+        let other_root = BytesN::from_array(&env, &[2; 32]);
+
+        let (defender, attacker) = setup.start_game(1);
+        client.commit_grid(&1, &root);
+        assert_eq!(
+            env.events()
+                .all()
+                .filter_by_contract(&setup.contract)
+                .events()[0],
+            GridCommitted {
+                session_id: 1,
+                defender: defender.clone(),
+                attacker: attacker.clone(),
+                trap_merkle_root: root.clone(),
+            }
+            .to_xdr(&env, &setup.contract)
+        );
+        assert_eq!(client.get_grid_root(&1), Some(root.clone()));
+        assert!(client.is_root_used(&defender, &root));
+        assert_eq!(
+            client.try_commit_grid(&1, &other_root),
+            Err(Ok(Error::GridLocked))
+        );
+
+        // The same defender cannot replay the grid in a new game
+        client.start_game(
+            &2,
+            &defender,
+            &attacker,
+            &DEFAULT_POINTS,
+            &DEFAULT_POINTS,
+            &grid_setup(&env),
+        );
+        assert_eq!(
+            client.try_commit_grid(&2, &root),
+            Err(Ok(Error::RootReused))
+        );
+        client.commit_grid(&2, &other_root);
+
+        // Other defenders may commit the same root, but not once play started
+        setup.start_game(3);
+        client.commit_grid(&3, &root);
+        setup.start_game(4);
+        setup.make_move(
+            4,
+            &TestMove {
+                x: 0,
+                y: 0,
+                is_hit: false,
+            },
+        );
+        assert_eq!(
+            client.try_commit_grid(&4, &root),
+            Err(Ok(Error::GridLocked))
+        );
+
+        // Reuse can be allowed
+        client.set_config(&ContractConfig {
+            reject_reused_roots: false,
+            ..ContractConfig::DEFAULT
+        });
+        client.start_game(
+            &5,
+            &defender,
+            &attacker,
+            &DEFAULT_POINTS,
+            &DEFAULT_POINTS,
+            &grid_setup(&env),
+        );
+        client.commit_grid(&5, &root);
+        assert_eq!(client.get_grid_root(&5), Some(root));
+    }
+
+    #[test]
+    fn test_grid_setup() {
+        let env = Env::default();
+        let setup = TestSetup::new(&env);
+        let client = &setup.client;
+        let (defender, attacker) = (Address::generate(&env), Address::generate(&env));
+
+        // Without a valid setup proof the game never starts
+        let grid = grid_setup(&env);
+        let unverified = GridSetup {
+            proof: Bytes::new(&env),
+            ..grid.clone()
+        };
+        assert_eq!(
+            client.try_start_game(&1, &defender, &attacker, &100, &100, &unverified),
+            Err(Ok(Error::InvalidSetup))
+        );
+        assert_eq!(client.try_get_game(&1), Err(Ok(Error::GameNotFound)));
+
+        client.start_game(&1, &defender, &attacker, &100, &100, &grid);
+        assert_eq!(
+            client.get_grid_commitment(&1),
+            Some(grid.grid_commitment.clone())
+        );
+
+        // A commitment starts one game; its proof only verifies for the
+        // defender it is bound to anyway (see test_setup_inputs)
+        let other = Address::generate(&env);
+        assert_eq!(
+            client.try_start_game(&2, &other, &attacker, &100, &100, &grid),
+            Err(Ok(Error::SetupReused))
+        );
+
+        // The admin set can rotate the setup verifier
+        let verifier = env.register(MockVerifier, ());
+        client.propose(
+            &setup.admin,
+            &AdminAction::SetSetupVerifier(verifier.clone()),
+        );
+        assert_eq!(
+            env.as_contract(&setup.contract, || env
+                .storage()
+                .instance()
+                .get::<DataKey, Address>(&DataKey::SetupVerifierAddress)),
+            Some(verifier)
+        );
+        client.start_game(&2, &other, &attacker, &100, &100, &grid_setup(&env));
+        assert_eq!(client.get_grid_commitment(&3), None);
+    }
+
+    #[test]
+    fn test_full_game() {
+        let env = Env::default();
+        let setup = TestSetup::new(&env);
+        let (defender, _) = setup.start_game(1);
+
+        // Traps on the diagonal: 8 hits out of 64 lets the defender win
+        setup.play(1, moves(|x, y| x == y)).unwrap();
+        let game = setup.client.get_game(&1);
+        assert_eq!((game.moves_made, game.hits, game.misses), (64, 8, 56));
+        assert!(game.game_ended);
+        assert_eq!(game.winner, Some(defender));
+        assert_eq!(
+            setup.try_make_move(
+                1,
+                &TestMove {
+                    x: 0,
+                    y: 0,
+                    is_hit: true
+                }
+            ),
+            Err(Error::GameAlreadyEnded)
+        );
+    }
+
+    #[test]
+    fn test_global_stats() {
+        let env = Env::default();
+        let setup = TestSetup::new(&env);
+        assert_eq!(setup.client.get_global_stats(), GlobalStats::default());
+
+        setup.start_game(1);
+        setup.play(1, moves(|x, y| x == y)).unwrap();
+        setup.start_game(2);
+        setup.make_move(
+            2,
+            &TestMove {
+                x: 0,
+                y: 1,
+                is_hit: true,
+            },
+        );
+        setup.start_game(3);
+        setup.client.end_game(&3);
+
+        // Game 2 is still in play, so its move is not counted yet
+        let mut stats = GlobalStats {
+            games_created: 3,
+            games_completed: 2,
+            total_moves: 64,
+            total_hits: 8,
+            points_settled: 4 * DEFAULT_POINTS,
         };
-        moves.push_back(new_move);
+        assert_eq!(setup.client.get_global_stats(), stats);
+        setup.client.end_game(&2);
+        stats.games_completed += 1;
+        stats.total_moves += 1;
+        stats.total_hits += 1;
+        stats.points_settled += 2 * DEFAULT_POINTS;
+        assert_eq!(setup.client.get_global_stats(), stats);
 
-        // Update game state
-        game.moves_made += 1;
-        if is_hit {
-            game.hits += 1;
-        } else {
-            game.misses += 1;
-        }
+        // Every cell was probed in game 1, and (0, 1), at 0 * 8 + 1, again in
+        // game 2
+        let heatmap = setup.client.get_heatmap();
+        assert_eq!(heatmap.probes.len(), 64);
+        assert_eq!(
+            (heatmap.probes.get(0), heatmap.hits.get(0)),
+            (Some(1), Some(1))
+        );
+        assert_eq!(
+            (heatmap.probes.get(9), heatmap.hits.get(9)),
+            (Some(1), Some(1))
+        );
+        assert_eq!(
+            (heatmap.probes.get(1), heatmap.hits.get(1)),
+            (Some(2), Some(1))
+        );
+        assert_eq!(
+            (heatmap.probes.get(8), heatmap.hits.get(8)),
+            (Some(1), Some(0))
+        );
+    }
 
-        // Check if game should end (all moves made or other condition)
-        let game_complete = game.moves_made >= MAX_MOVES;
+    #[test]
+    fn test_invalid_proof_rejected() {
+        let env = Env::default();
+        let setup = TestSetup::new(&env);
+        let client = &setup.client;
+        setup.start_game(1);
 
-        if game_complete {
-            game.game_ended = true;
-            // Determine winner: defender wins if attacker couldn't find enough traps
-            // (For this demo, let's say attacker needs > 50% hit rate to win)
-            let attacker_wins = game.hits > (MAX_MOVES / 2);
-            game.winner = if attacker_wins {
-                Some(game.attacker.clone())
-            } else {
-                Some(game.defender.clone())
-            };
+        let empty = Bytes::new(&env);
+        let inputs = setup.public_inputs(1, 2, 3, true);
+        client.submit_move(&1, &2, &3);
+        assert_eq!(
+            client.try_make_move(&1, &2, &3, &true, &empty, &inputs),
+            Err(Ok(Error::InvalidProof))
+        );
+        assert_eq!(client.get_moves(&1).len(), 0);
+    }
 
-            // Call GameHub to end game
-            let game_hub_addr: Address = env
-                .storage()
+    /// Keeps the arguments of the last `verify_proof` call and accepts it
+    #[contract]
+    pub struct RecordingVerifier;
+
+    #[contractimpl]
+    impl RecordingVerifier {
+        pub fn verify_proof(env: Env, public_inputs: Bytes, proof_bytes: Bytes) {
+            env.storage()
                 .instance()
-                .get(&DataKey::GameHubAddress)
-                .expect("GameHub address not set");
-            let game_hub = GameHubClient::new(&env, &game_hub_addr);
-            game_hub.end_game(&session_id, &!attacker_wins); // true if defender won
+                .set(&symbol_short!("call"), &(public_inputs, proof_bytes));
         }
 
-        // Save updated state
-        env.storage().temporary().set(&game_key, &game);
-        env.storage().temporary().set(&moves_key, &moves);
-        env.storage()
-            .temporary()
-            .extend_ttl(&game_key, GAME_TTL_LEDGERS, GAME_TTL_LEDGERS);
-        env.storage()
-            .temporary()
-            .extend_ttl(&moves_key, GAME_TTL_LEDGERS, GAME_TTL_LEDGERS);
+        pub fn last_call(env: Env) -> Option<(Bytes, Bytes)> {
+            env.storage().instance().get(&symbol_short!("call"))
+        }
+    }
+
+    #[test]
+    fn test_verifier_call() {
+        let env = Env::default();
+        let verifier = env.register(RecordingVerifier, ());
+        let game_hub = env.register(MockGameHub, ());
+        let setup = TestSetup::with_contracts(&env, game_hub, verifier.clone());
+        setup.start_game(1);
 
-        Ok(proof_valid)
+        // The UltraHonk verifier takes the public inputs first
+        let proof = valid_proof(&env);
+        let inputs = setup.public_inputs(1, 2, 3, true);
+        setup.client.submit_move(&1, &2, &3);
+        setup.client.make_move(&1, &2, &3, &true, &proof, &inputs);
+        assert_eq!(
+            RecordingVerifierClient::new(&env, &verifier).last_call(),
+            Some((inputs, proof))
+        );
     }
 
-    /// End the game early (e.g., if attacker gives up or time limit reached)
-    pub fn end_game(env: Env, session_id: u32) -> Result<(), Error> {
-        let game_key = DataKey::Game(session_id);
-        let mut game: Game = env
-            .storage()
-            .temporary()
-            .get(&game_key)
-            .ok_or(Error::GameNotFound)?;
+    #[test]
+    fn test_setup_inputs() {
+        let env = Env::default();
+        let setup = TestSetup::new(&env);
+        let verifier = env.register(RecordingVerifier, ());
+        setup.client.propose(
+            &setup.admin,
+            &AdminAction::SetSetupVerifier(verifier.clone()),
+        );
+        let (defender, _) = setup.start_game(1);
 
-        if game.game_ended {
-            return Err(Error::GameAlreadyEnded);
+        // The setup proof is checked against the configured grid and the key
+        // of the defender the game starts for, which the commitment hashes in
+        let (inputs, _) = RecordingVerifierClient::new(&env, &verifier)
+            .last_call()
+            .unwrap();
+        let mut buf = [0u8; SetupInputs::LEN];
+        inputs.copy_into_slice(&mut buf);
+        let inputs = SetupInputs::decode(&buf).unwrap();
+        let xdr = defender.to_xdr(&env);
+        assert_eq!(
+            Bytes::from_array(&env, &inputs.defender),
+            xdr.slice(xdr.len() - 32..)
+        );
+        assert_eq!(
+            (inputs.trap_count, inputs.grid_size),
+            (TRAP_COUNT, DEFAULT_GRID_SIZE)
+        );
+        assert_eq!(
+            setup.client.get_grid_commitment(&1),
+            Some(BytesN::from_array(&env, &inputs.grid_commitment))
+        );
+    }
+
+    #[test]
+    fn test_moves_open_setup_commitment() {
+        let env = Env::default();
+        let verifier = env.register(RecordingVerifier, ());
+        let game_hub = env.register(MockGameHub, ());
+        let setup = TestSetup::with_contracts(&env, game_hub, verifier.clone());
+        let client = &setup.client;
+        setup.start_game(1);
+        setup.start_game(2);
+
+        // Every move proof opens the commitment the setup proof verified
+        let proof = valid_proof(&env);
+        let inputs = setup.public_inputs(1, 2, 3, true);
+        client.submit_move(&1, &2, &3);
+        client.make_move(&1, &2, &3, &true, &proof, &inputs);
+        let (inputs, _) = RecordingVerifierClient::new(&env, &verifier)
+            .last_call()
+            .unwrap();
+        let mut buf = [0u8; PositionMovement::LEN];
+        inputs.copy_into_slice(&mut buf);
+        let inputs = PositionMovement::decode(&buf).unwrap();
+        assert_eq!(
+            client.get_grid_commitment(&1),
+            Some(BytesN::from_array(&env, &inputs.grid_commitment))
+        );
+
+        // so the defender cannot answer one game from the grid of another
+        let other_grid = setup.public_inputs(2, 2, 4, false);
+        client.submit_move(&1, &2, &4);
+        assert_eq!(
+            client.try_make_move(&1, &2, &4, &false, &proof, &other_grid),
+            Err(Ok(Error::InvalidProof))
+        );
+        assert_eq!(client.get_moves(&1).len(), 1);
+    }
+
+    #[test]
+    fn test_public_inputs_must_match_move() {
+        let env = Env::default();
+        let setup = TestSetup::new(&env);
+        let client = &setup.client;
+        setup.start_game(1);
+
+        // A valid proof of another cell or of the opposite claim is not enough
+        let proof = valid_proof(&env);
+        client.submit_move(&1, &2, &3);
+        for inputs in [
+            Bytes::new(&env),
+            setup.public_inputs(1, 3, 2, false),
+            setup.public_inputs(1, 2, 3, true),
+        ] {
+            assert_eq!(
+                client.try_make_move(&1, &2, &3, &false, &proof, &inputs),
+                Err(Ok(Error::InvalidProof))
+            );
         }
+        client.make_move(
+            &1,
+            &2,
+            &3,
+            &false,
+            &proof,
+            &setup.public_inputs(1, 2, 3, false),
+        );
+        assert_eq!(client.get_moves(&1).len(), 1);
 
-        // Determine winner based on current state
-        let attacker_wins = game.hits > (game.moves_made / 2);
-        game.winner = if attacker_wins {
-            Some(game.attacker.clone())
-        } else {
-            Some(game.defender.clone())
-        };
-        game.game_ended = true;
+        // Nor is a proof about another grid than the one set up
+        let other_grid = grid_setup(&env).grid_commitment;
+        client.submit_move(&1, &4, &4);
+        assert_eq!(
+            client.try_make_move(
+                &1,
+                &4,
+                &4,
+                &false,
+                &proof,
+                &public_inputs(&env, &other_grid, 4, 4, false)
+            ),
+            Err(Ok(Error::InvalidProof))
+        );
+        assert_eq!(client.get_moves(&1).len(), 1);
+    }
 
-        // Call GameHub to end game
-        let game_hub_addr: Address = env
-            .storage()
-            .instance()
-            .get(&DataKey::GameHubAddress)
-            .expect("GameHub address not set");
-        let game_hub = GameHubClient::new(&env, &game_hub_addr);
-        game_hub.end_game(&session_id, &!attacker_wins);
+    #[test]
+    fn test_make_move_requires_defender() {
+        let env = Env::default();
+        let setup = TestSetup::new(&env);
+        let (contract_id, client) = (&setup.contract, &setup.client);
+        let (defender, _) = setup.start_game(1);
 
-        env.storage().temporary().set(&game_key, &game);
-        Ok(())
+        // Only the defender answers for their grid
+        let (proof, inputs) = (valid_proof(&env), setup.public_inputs(1, 2, 3, true));
+        client.submit_move(&1, &2, &3);
+        client.make_move(&1, &2, &3, &true, &proof, &inputs);
+        assert_eq!(
+            env.auths(),
+            [(
+                defender,
+                AuthorizedInvocation {
+                    function: AuthorizedFunction::Contract((
+                        contract_id.clone(),
+                        Symbol::new(&env, "make_move"),
+                        (1u32, 2u32, 3u32, true, proof.clone(), inputs).into_val(&env),
+                    )),
+                    sub_invocations: std::vec![],
+                }
+            )]
+        );
+
+        // Without it the move is not recorded
+        client.submit_move(&1, &4, &4);
+        env.set_auths(&[]);
+        let inputs = setup.public_inputs(1, 4, 4, false);
+        assert!(client
+            .try_make_move(&1, &4, &4, &false, &proof, &inputs)
+            .is_err());
+        assert_eq!(client.get_moves(&1).len(), 1);
     }
 
-    /// Get game state
-    pub fn get_game(env: Env, session_id: u32) -> Result<Game, Error> {
-        let game_key = DataKey::Game(session_id);
-        env.storage()
-            .temporary()
-            .get(&game_key)
-            .ok_or(Error::GameNotFound)
+    #[test]
+    fn test_submit_move() {
+        let env = Env::default();
+        let setup = TestSetup::new(&env);
+        let (contract_id, client) = (&setup.contract, &setup.client);
+        let (defender, attacker) = setup.start_game(1);
+
+        client.submit_move(&1, &2, &3);
+        assert_eq!(
+            env.events().all().filter_by_contract(contract_id),
+            [MoveSubmitted {
+                session_id: 1,
+                defender,
+                attacker,
+                x: 2,
+                y: 3,
+                note: Bytes::new(&env),
+            }
+            .to_xdr(&env, contract_id)]
+        );
+        assert_eq!(
+            client.get_pending_move(&1),
+            Some(PendingMove { x: 2, y: 3 })
+        );
+        assert_eq!(
+            client.try_submit_move(&1, &4, &4),
+            Err(Ok(Error::MovePending))
+        );
+
+        let proof = valid_proof(&env);
+        assert_eq!(
+            client.try_make_move(
+                &1,
+                &4,
+                &4,
+                &false,
+                &proof,
+                &setup.public_inputs(1, 4, 4, false)
+            ),
+            Err(Ok(Error::MovePending))
+        );
+        client.make_move(
+            &1,
+            &2,
+            &3,
+            &false,
+            &proof,
+            &setup.public_inputs(1, 2, 3, false),
+        );
+        assert_eq!(client.get_pending_move(&1), None);
+        assert_eq!(
+            client.try_submit_move(&1, &2, &3),
+            Err(Ok(Error::MoveAlreadyMade))
+        );
     }
 
-    /// Get all moves for a game
-    pub fn get_moves(env: Env, session_id: u32) -> Vec<Move> {
-        let moves_key = DataKey::Moves(session_id);
-        env.storage()
-            .temporary()
-            .get(&moves_key)
-            .unwrap_or(vec![&env])
+    #[test]
+    fn test_submit_move_protocol() {
+        let env = Env::default();
+        let setup = TestSetup::new(&env);
+        let (contract_id, client) = (&setup.contract, &setup.client);
+        let (_, attacker) = setup.start_game(1);
+
+        // Only the attacker picks the cell
+        client.submit_move(&1, &2, &3);
+        assert_eq!(
+            env.auths(),
+            [(
+                attacker,
+                AuthorizedInvocation {
+                    function: AuthorizedFunction::Contract((
+                        contract_id.clone(),
+                        Symbol::new(&env, "submit_move"),
+                        (1u32, 2u32, 3u32).into_val(&env),
+                    )),
+                    sub_invocations: std::vec![],
+                }
+            )]
+        );
+
+        // Kept as long as the game itself
+        let (ttl, expected) = env.as_contract(contract_id, || {
+            let ttl = env.storage().temporary().get_ttl(&DataKey::PendingMove(1));
+            (ttl, game_ttl(&env))
+        });
+        assert_eq!(ttl, expected);
+
+        // Ending the game drops the unanswered cell, and no other can follow
+        client.end_game(&1);
+        assert_eq!(client.get_pending_move(&1), None);
+        assert_eq!(
+            client.try_submit_move(&1, &4, &4),
+            Err(Ok(Error::GameAlreadyEnded))
+        );
+
+        setup.start_game(2);
+        assert_eq!(
+            client.try_submit_move(&2, &DEFAULT_GRID_SIZE, &0),
+            Err(Ok(Error::InvalidMove))
+        );
+        assert_eq!(
+            client.try_submit_move(&3, &0, &0),
+            Err(Ok(Error::GameNotFound))
+        );
     }
-}
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use soroban_sdk::testutils::Address as _;
+    #[test]
+    fn test_submit_move_with_note() {
+        let env = Env::default();
+        let setup = TestSetup::new(&env);
+        let (contract_id, client) = (&setup.contract, &setup.client);
+        let (defender, attacker) = setup.start_game(1);
+
+        let too_long = Bytes::from_array(&env, &[b'!'; MAX_NOTE_BYTES as usize + 1]);
+        assert_eq!(
+            client.try_submit_move_with_note(&1, &2, &3, &too_long),
+            Err(Ok(Error::NoteTooLong))
+        );
+        assert_eq!(client.get_pending_move(&1), None);
+
+        let note = Bytes::from_slice(&env, b"gg");
+        client.submit_move_with_note(&1, &2, &3, &note);
+        assert_eq!(
+            env.events().all().filter_by_contract(contract_id),
+            [MoveSubmitted {
+                session_id: 1,
+                defender,
+                attacker,
+                x: 2,
+                y: 3,
+                note,
+            }
+            .to_xdr(&env, contract_id)]
+        );
+        assert_eq!(
+            client.get_pending_move(&1),
+            Some(PendingMove { x: 2, y: 3 })
+        );
+    }
 
     #[test]
-    fn test_game_initialization() {
+    fn test_practice_game() {
         let env = Env::default();
-        let contract_id = env.register_contract(None, TrapGridContract);
-        let client = TrapGridContractClient::new(&env, &contract_id);
+        let setup = TestSetup::new(&env);
+        let (contract_id, client) = (&setup.contract, &setup.client);
+        let player = Address::generate(&env);
+        let secret = BytesN::from_array(&env, &[9; 32]);
+        let commitment: BytesN<32> = env.crypto().sha256(&secret.clone().into()).into();
 
-        let admin = Address::generate(&env);
-        let game_hub = Address::generate(&env);
-        let verifier = Address::generate(&env);
+        // Nothing of the layout is stored before it is probed
+        client.start_practice(&player, &commitment);
+        let practice = client.get_practice(&player).unwrap();
+        assert_eq!((practice.probes.len(), practice.found.len()), (0, 0));
+        assert_eq!(practice.trap_count, TRAP_COUNT);
+
+        // Probe the grid in order until every trap is found; the traps left
+        // must be among the cells left, so the grid always runs out of traps
+        let grid = practice.grid_size;
+        let mut misses = Vec::new(&env);
+        for cell in 0..grid * grid {
+            let before = client.get_practice(&player).unwrap();
+            if before.found.len() == TRAP_COUNT {
+                break;
+            }
+            let is_hit = client.practice_move(&player, &(cell / grid), &(cell % grid));
+            let cleared = before.found.len() + u32::from(is_hit) == TRAP_COUNT;
+            assert_eq!(
+                client.try_practice_move(&player, &(cell / grid), &(cell % grid)),
+                Err(Ok(if cleared {
+                    Error::GameAlreadyEnded
+                } else {
+                    Error::MoveAlreadyMade
+                }))
+            );
+            if !is_hit {
+                misses.push_back(cell);
+            }
+        }
+        let practice = client.get_practice(&player).unwrap();
+        assert_eq!(practice.found.len(), TRAP_COUNT);
+        assert_eq!(practice.probes.len(), TRAP_COUNT + misses.len());
+        assert_eq!(
+            client.try_practice_move(&player, &grid, &0),
+            Err(Ok(Error::GameAlreadyEnded))
+        );
 
-        client.__constructor(&admin, &game_hub, &verifier);
+        let probes = practice.probes.len();
+        let stats = client.finish_practice(&player, &secret);
+        assert_eq!(
+            env.events().all().filter_by_contract(contract_id),
+            [PracticeFinished {
+                player: player.clone(),
+                probes,
+                hits: TRAP_COUNT,
+                cleared: true,
+                traps: practice.found,
+            }
+            .to_xdr(&env, contract_id)]
+        );
+        assert_eq!(
+            stats,
+            PracticeStats {
+                games: 1,
+                cleared: 1,
+                best_probes: probes,
+                total_probes: u64::from(probes),
+                total_hits: u64::from(TRAP_COUNT),
+            }
+        );
+        assert_eq!(client.get_practice(&player), None);
 
-        // Test basic initialization
-        // Note: More comprehensive tests would require mock contracts for game_hub and verifier
+        // A game given up counts, but not as cleared, and reveals the traps
+        // it hid among the cells never probed
+        client.start_practice(&player, &commitment);
+        let is_hit = client.practice_move(&player, &0, &0);
+        client.finish_practice(&player, &secret);
+        let events = env.events().all().filter_by_contract(contract_id);
+        let soroban_sdk::xdr::ContractEventBody::V0(body) = &events.events()[0].body;
+        let data = Map::<Symbol, Val>::try_from_val(&env, &body.data).unwrap();
+        let traps =
+            Vec::<u32>::try_from_val(&env, &data.get(Symbol::new(&env, "traps")).unwrap()).unwrap();
+        assert_eq!(traps.len(), TRAP_COUNT);
+        assert_eq!(traps.contains(0), is_hit);
+        for (i, cell) in traps.iter().enumerate() {
+            assert!(cell < grid * grid);
+            assert_eq!(traps.first_index_of(cell), Some(i as u32));
+        }
+        let stats = client.get_practice_stats(&player);
+        assert_eq!(
+            (stats.games, stats.cleared, stats.best_probes),
+            (2, 1, probes)
+        );
+        // Ranked play is untouched
+        assert_eq!(client.get_global_stats(), GlobalStats::default());
+    }
+
+    #[test]
+    fn test_daily_puzzle() {
+        let env = Env::default();
+        let setup = TestSetup::new(&env);
+        let (client, operator) = (&setup.client, &setup.admin);
+        let day = 20_000;
+        env.ledger()
+            .with_mut(|ledger| ledger.timestamp = u64::from(day) * daily::DAY_SECONDS);
+        let traps = vec![&env, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9];
+        let salt = BytesN::from_array(&env, &[5; 32]);
+        let grid_hash = daily::commitment(&env, &traps, &salt);
+
+        assert_eq!(
+            client.try_commit_daily(operator, &(day - 1), &grid_hash),
+            Err(Ok(Error::DailyExists))
+        );
+        client.commit_daily(operator, &day, &grid_hash);
+        let (a, b) = (Address::generate(&env), Address::generate(&env));
+        for (player, x, y, is_hit) in [(&a, 0, 0, true), (&a, 0, 1, true), (&b, 1, 0, true)] {
+            client.daily_move(player, &x, &y);
+            assert_eq!(
+                client.try_daily_move(player, &7, &7),
+                Err(Ok(Error::MovePending))
+            );
+            client.answer_daily(operator, &day, player, &is_hit);
+        }
+        client.daily_move(&a, &7, &7);
+        client.answer_daily(operator, &day, &a, &false);
+        // (1, 1) holds a trap, but the operator answers b a miss
+        client.daily_move(&b, &1, &1);
+        client.answer_daily(operator, &day, &b, &false);
+        let entry = client.get_daily_entry(&day, &a).unwrap();
+        assert_eq!(
+            (entry.probes, entry.answers),
+            (vec![&env, 0, 1, 63], vec![&env, true, true, false])
+        );
+        assert_eq!(client.get_daily(&day).unwrap().players, 2);
+
+        // The layout is revealed after the day closes, as committed
+        assert_eq!(
+            client.try_reveal_daily(operator, &day, &traps, &salt),
+            Err(Ok(Error::DayNotClosed))
+        );
+        assert_eq!(
+            client.try_rank_daily(&day, &a),
+            Err(Ok(Error::DailyNotRevealed))
+        );
+        env.ledger()
+            .with_mut(|ledger| ledger.timestamp += daily::DAY_SECONDS);
+        assert_eq!(
+            client.try_daily_move(&a, &1, &1),
+            Err(Ok(Error::DailyNotFound))
+        );
+        let other_salt = BytesN::from_array(&env, &[6; 32]);
+        assert_eq!(
+            client.try_reveal_daily(operator, &day, &traps, &other_salt),
+            Err(Ok(Error::RevealMismatch))
+        );
+        client.reveal_daily(operator, &day, &traps, &salt);
+        assert_eq!(client.get_daily(&day).unwrap().traps, traps);
+        assert_eq!(
+            client.try_answer_daily(operator, &day, &a, &true),
+            Err(Ok(Error::DailyRevealed))
+        );
+
+        // Scored by the layout, which catches the wrong answer to b: b's
+        // miss at cell 9 counts as a hit
+        assert_eq!(client.rank_daily(&day, &b), Some(0));
+        assert_eq!(
+            env.events().all().filter_by_contract(&setup.contract),
+            [DailyRanked {
+                day,
+                player: b.clone(),
+                hits: 2,
+                probes: 2,
+                place: Some(0),
+                misanswered: 1,
+            }
+            .to_xdr(&env, &setup.contract)]
+        );
+        assert_eq!(client.get_daily(&day).unwrap().misanswered, 1);
+        assert_eq!(client.rank_daily(&day, &a), Some(1));
+        assert_eq!(client.get_daily(&day).unwrap().misanswered, 1);
+        assert_eq!(client.try_rank_daily(&day, &a), Err(Ok(Error::NotPlayer)));
+        let board = client.get_daily_leaderboard(&day);
+        assert_eq!(
+            board,
+            vec![
+                &env,
+                DailyRank {
+                    player: b,
+                    hits: 2,
+                    probes: 2,
+                },
+                DailyRank {
+                    player: a,
+                    hits: 2,
+                    probes: 3,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_simulate_move() {
+        let env = Env::default();
+        let setup = TestSetup::new(&env);
+        let client = &setup.client;
+
+        assert_eq!(
+            client.try_simulate_move(&1, &2, &3),
+            Err(Ok(Error::GameNotFound))
+        );
+        setup.start_game(1);
+        assert_eq!(
+            client.try_simulate_move(&1, &2, &3),
+            Err(Ok(Error::NoPendingMove))
+        );
+        assert_eq!(
+            client.try_simulate_move(&1, &8, &0),
+            Err(Ok(Error::InvalidMove))
+        );
+
+        client.submit_move(&1, &2, &3);
+        client.simulate_move(&1, &2, &3);
+        assert_eq!(
+            client.try_simulate_move(&1, &4, &4),
+            Err(Ok(Error::MovePending))
+        );
+
+        let proof = valid_proof(&env);
+        client.make_move(
+            &1,
+            &2,
+            &3,
+            &true,
+            &proof,
+            &setup.public_inputs(1, 2, 3, true),
+        );
+        assert_eq!(
+            client.try_simulate_move(&1, &2, &3),
+            Err(Ok(Error::MoveAlreadyMade))
+        );
+        assert_eq!(client.get_game(&1).moves_made, 1);
+        assert_eq!(client.get_pending_move(&1), None);
+
+        client.end_game(&1);
+        assert_eq!(
+            client.try_simulate_move(&1, &4, &4),
+            Err(Ok(Error::GameAlreadyEnded))
+        );
+    }
+
+    #[test]
+    fn test_full_state() {
+        let env = Env::default();
+        let setup = TestSetup::new(&env);
+        let client = &setup.client;
+
+        assert_eq!(client.try_get_full_state(&1), Err(Ok(Error::GameNotFound)));
+        client.set_config(&ContractConfig {
+            expiry_ledgers: 17_280,
+            ..ContractConfig::DEFAULT
+        });
+        setup.start_game(1);
+        let proof = valid_proof(&env);
+        for x in 0..DEFAULT_GRID_SIZE {
+            client.submit_move(&1, &x, &0);
+            client.make_move(
+                &1,
+                &x,
+                &0,
+                &false,
+                &proof,
+                &setup.public_inputs(1, x, 0, false),
+            );
+        }
+        client.submit_move(&1, &0, &1);
+        client.make_move(
+            &1,
+            &0,
+            &1,
+            &false,
+            &proof,
+            &setup.public_inputs(1, 0, 1, false),
+        );
+        client.submit_move(&1, &1, &1);
+        let ready_ledger = client.queue_emergency_withdraw(&1);
+
+        let state = client.get_full_state(&1);
+        assert_eq!(state.game, client.get_game(&1));
+        assert_eq!(state.config, client.get_config());
+        assert_eq!(state.pending_move, vec![&env, PendingMove { x: 1, y: 1 }]);
+        assert_eq!(
+            state.deadlines,
+            Deadlines {
+                expires_after: Some(state.game.last_active_ledger + 17_280),
+                appeal_until: None,
+                claimable_from: None,
+                emergency_withdraw_from: Some(ready_ledger),
+            }
+        );
+        let moves = client.get_moves(&1);
+        assert_eq!(state.recent_moves.len(), FULL_STATE_MOVES);
+        assert_eq!(state.recent_moves, moves.slice(1..));
+
+        client.end_game(&1);
+        let state = client.get_full_state(&1);
+        assert_eq!(state.deadlines.expires_after, None);
+        assert!(state.pending_move.is_empty());
+    }
+
+    #[test]
+    fn test_rules() {
+        let env = Env::default();
+        let setup = TestSetup::new(&env);
+        let client = &setup.client;
+
+        assert_eq!(client.try_get_rules(&1), Err(Ok(Error::GameNotFound)));
+        setup.start_game(1);
+        let rules = client.get_rules(&1);
+        assert_eq!(rules.grid_size, DEFAULT_GRID_SIZE);
+        assert_eq!(rules.trap_count, ContractConfig::DEFAULT.trap_count);
+        assert_eq!(rules.win_policy, WinPolicy::MajorityHits);
+        assert_eq!(rules.expiry_policy, ExpiryPolicy::Refund);
+        assert_eq!(rules.claim_challenge_ledgers, CLAIM_CHALLENGE_LEDGERS);
+        assert_eq!(rules.protocol_fee_bps, 0);
+        assert_eq!(rules.progressive_payout_bps, 0);
+        assert_eq!(rules.bonus_curve, PayoutCurve::NONE);
+        assert_eq!(rules.streak_bonus, StreakBonus::NONE);
+        assert_eq!(rules.min_games_completed, 0);
+        assert_eq!(rules.verifier, setup.verifier);
+        assert_eq!(rules.setup_verifier, setup.setup_verifier);
+
+        // The grid stays the game's own; the rest follows the configuration
+        client.set_config(&ContractConfig {
+            grid_size: 12,
+            trap_count: 20,
+            expiry_ledgers: 17_280,
+            expiry_policy: ExpiryPolicy::DefenderWins,
+            ..ContractConfig::DEFAULT
+        });
+        let id = client.propose(&setup.admin, &AdminAction::SetFees(1_000, 2_000));
+        assert_eq!(
+            client.try_get_proposal(&id),
+            Err(Ok(Error::ProposalNotFound))
+        );
+        let rules = client.get_rules(&1);
+        assert_eq!(rules.grid_size, DEFAULT_GRID_SIZE);
+        assert_eq!(rules.trap_count, TRAP_COUNT);
+        assert_eq!(rules.expiry_ledgers, 17_280);
+        assert_eq!(rules.expiry_policy, ExpiryPolicy::DefenderWins);
+        assert_eq!(
+            (rules.protocol_fee_bps, rules.referral_fee_bps),
+            (1_000, 2_000)
+        );
+    }
+
+    #[test]
+    fn test_move_gap() {
+        let env = Env::default();
+        let setup = TestSetup::new(&env);
+        let client = &setup.client;
+        client.set_config(&ContractConfig {
+            move_gap_ledgers: 5,
+            ..ContractConfig::DEFAULT
+        });
+        setup.start_game(1);
+        let proof = valid_proof(&env);
+
+        client.submit_move(&1, &0, &0);
+        client.make_move(
+            &1,
+            &0,
+            &0,
+            &false,
+            &proof,
+            &setup.public_inputs(1, 0, 0, false),
+        );
+        assert_eq!(
+            client.try_submit_move(&1, &1, &0),
+            Err(Ok(Error::MoveTooSoon))
+        );
+
+        // The attacker submits once the gap has passed; the defender answers
+        // on its own clock
+        env.ledger().with_mut(|l| l.sequence_number += 5);
+        client.submit_move(&1, &1, &0);
+        env.ledger().with_mut(|l| l.sequence_number += 2);
+        client.make_move(
+            &1,
+            &1,
+            &0,
+            &false,
+            &proof,
+            &setup.public_inputs(1, 1, 0, false),
+        );
+        env.ledger().with_mut(|l| l.sequence_number += 3);
+        client.submit_move(&1, &2, &0);
+        assert_eq!(
+            client.try_make_move(
+                &1,
+                &2,
+                &0,
+                &false,
+                &proof,
+                &setup.public_inputs(1, 2, 0, false)
+            ),
+            Err(Ok(Error::MoveTooSoon))
+        );
+        assert_eq!(
+            client.try_simulate_move(&1, &2, &0),
+            Err(Ok(Error::MoveTooSoon))
+        );
+        env.ledger().with_mut(|l| l.sequence_number += 2);
+        client.make_move(
+            &1,
+            &2,
+            &0,
+            &false,
+            &proof,
+            &setup.public_inputs(1, 2, 0, false),
+        );
+        assert_eq!(client.get_game(&1).moves_made, 3);
+
+        // The gap must be shorter than a day, the shortest expiry
+        assert_eq!(
+            client.try_set_config(&ContractConfig {
+                move_gap_ledgers: MIN_DELAY_LEDGERS,
+                ..ContractConfig::DEFAULT
+            }),
+            Err(Ok(Error::InvalidConfig))
+        );
+    }
+
+    #[test]
+    fn test_abort_due_to_verifier() {
+        let env = Env::default();
+        let setup = TestSetup::new(&env);
+        let client = &setup.client;
+        let (defender, attacker) = setup.start_game(1);
+
+        let stranger = Address::generate(&env);
+        assert_eq!(
+            client.try_abort_due_to_verifier(&1, &stranger),
+            Err(Ok(Error::NotPlayer))
+        );
+        assert!(!client.abort_due_to_verifier(&1, &defender));
+        assert_eq!(
+            env.events().all().filter_by_contract(&setup.contract),
+            [AbortRequested {
+                session_id: 1,
+                defender: defender.clone(),
+                attacker: attacker.clone(),
+                voter: defender.clone(),
+            }
+            .to_xdr(&env, &setup.contract)]
+        );
+        // A vote twice is still one player's
+        assert!(!client.abort_due_to_verifier(&1, &defender));
+
+        // A move shows the verifier works, and clears the votes
+        let proof = valid_proof(&env);
+        client.submit_move(&1, &0, &0);
+        client.make_move(
+            &1,
+            &0,
+            &0,
+            &false,
+            &proof,
+            &setup.public_inputs(1, 0, 0, false),
+        );
+        assert!(!client.abort_due_to_verifier(&1, &setup.admin));
+        assert!(client.abort_due_to_verifier(&1, &attacker));
+        assert_eq!(
+            env.events().all().filter_by_contract(&setup.contract),
+            [GameAborted {
+                session_id: 1,
+                defender: defender.clone(),
+                attacker: attacker.clone(),
+            }
+            .to_xdr(&env, &setup.contract)]
+        );
+        let game = client.get_game(&1);
+        assert!(game.game_ended);
+        assert_eq!(game.winner, None);
+        assert_eq!(client.get_active_games(), 0);
+        assert_eq!(
+            client.try_abort_due_to_verifier(&1, &defender),
+            Err(Ok(Error::GameAlreadyEnded))
+        );
+
+        // Both players agree without the admin
+        let (defender, attacker) = setup.start_game(2);
+        assert!(!client.abort_due_to_verifier(&2, &attacker));
+        assert!(client.abort_due_to_verifier(&2, &defender));
+        assert!(client.get_game(&2).game_ended);
+    }
+
+    #[test]
+    fn test_events() {
+        let env = Env::default();
+        let setup = TestSetup::new(&env);
+        let (contract_id, client) = (&setup.contract, &setup.client);
+
+        let (defender, attacker) = setup.start_game(1);
+        assert_eq!(
+            env.events().all().filter_by_contract(contract_id),
+            [GameStarted {
+                session_id: 1,
+                defender: defender.clone(),
+                attacker: attacker.clone(),
+            }
+            .to_xdr(&env, contract_id)]
+        );
+
+        let proof = valid_proof(&env);
+        client.submit_move(&1, &2, &3);
+        client.make_move(
+            &1,
+            &2,
+            &3,
+            &true,
+            &proof,
+            &setup.public_inputs(1, 2, 3, true),
+        );
+        assert_eq!(
+            env.events().all().filter_by_contract(contract_id),
+            [MoveMade {
+                session_id: 1,
+                defender: defender.clone(),
+                attacker: attacker.clone(),
+                x: 2,
+                y: 3,
+                is_hit: true,
+                verified: true,
+            }
+            .to_xdr(&env, contract_id)]
+        );
+
+        client.end_game(&1);
+        assert_eq!(
+            env.events().all().filter_by_contract(contract_id),
+            [GameEnded {
+                session_id: 1,
+                defender,
+                attacker: attacker.clone(),
+                winner: attacker,
+                hits: 1,
+                misses: 0,
+            }
+            .to_xdr(&env, contract_id)]
+        );
     }
 }
@@ -0,0 +1,206 @@
+//! Tests of games in optimistic mode
+//!
+//! The defender claims answers without proofs; the attacker challenges
+//! claims, and a challenge left unproven past its deadline loses the game
+//! and the claim bonds.
+
+use soroban_sdk::testutils::{Address as _, Events as _, Ledger as _};
+use soroban_sdk::token::TokenClient;
+use soroban_sdk::{Address, Env, Event};
+
+use crate::testutils::{
+    grid_setup, invalid_proof, public_inputs, valid_proof, TestSetup, CLAIM_BOND,
+};
+use crate::{ClaimChallenged, ClaimProven, Error, MoveMade, CLAIM_CHALLENGE_LEDGERS};
+
+const SESSION_ID: u32 = 1;
+
+/// Submit `(x, y)` and claim the answer
+fn claim(setup: &TestSetup, x: u32, y: u32, is_hit: bool) {
+    setup.client.submit_move(&SESSION_ID, &x, &y);
+    setup.client.claim_move(&SESSION_ID, &is_hit);
+}
+
+fn advance(env: &Env, ledgers: u32) {
+    env.ledger()
+        .with_mut(|ledger| ledger.sequence_number += ledgers);
+}
+
+#[test]
+fn test_claims_and_challenges() {
+    let env = Env::default();
+    let setup = TestSetup::new(&env);
+    let client = &setup.client;
+    let (defender, attacker) = setup.start_game(SESSION_ID);
+    assert_eq!(
+        client.try_enable_optimistic(&SESSION_ID, &Address::generate(&env), &0),
+        Err(Ok(Error::StakeNotPositive))
+    );
+    let token = setup.enable_optimistic(SESSION_ID);
+    let balance = |address: &Address| TokenClient::new(&env, &token).balance(address);
+    let funds = balance(&defender);
+
+    claim(&setup, 0, 0, true);
+    assert_eq!(
+        env.events().all().filter_by_contract(&setup.contract),
+        [MoveMade {
+            session_id: SESSION_ID,
+            defender: defender.clone(),
+            attacker: attacker.clone(),
+            x: 0,
+            y: 0,
+            is_hit: true,
+            verified: false,
+        }
+        .to_xdr(&env, &setup.contract)]
+    );
+    assert_eq!(balance(&setup.contract), CLAIM_BOND);
+
+    let prove_until = client.challenge_claim(&SESSION_ID, &0);
+    assert_eq!(
+        prove_until,
+        env.ledger().sequence() + CLAIM_CHALLENGE_LEDGERS
+    );
+    assert_eq!(
+        env.events().all().filter_by_contract(&setup.contract),
+        [ClaimChallenged {
+            session_id: SESSION_ID,
+            defender: defender.clone(),
+            attacker: attacker.clone(),
+            move_index: 0,
+            prove_until,
+        }
+        .to_xdr(&env, &setup.contract)]
+    );
+    assert_eq!(
+        client.try_end_game(&SESSION_ID),
+        Err(Ok(Error::ChallengeWindowOpen))
+    );
+
+    // The proof must be of the claimed answer
+    assert_eq!(
+        client.try_prove_claim(
+            &SESSION_ID,
+            &valid_proof(&env),
+            &setup.public_inputs(SESSION_ID, 0, 0, false)
+        ),
+        Err(Ok(Error::InvalidProof))
+    );
+    client.prove_claim(
+        &SESSION_ID,
+        &valid_proof(&env),
+        &setup.public_inputs(SESSION_ID, 0, 0, true),
+    );
+    assert_eq!(
+        env.events().all().filter_by_contract(&setup.contract),
+        [ClaimProven {
+            session_id: SESSION_ID,
+            defender: defender.clone(),
+            attacker: attacker.clone(),
+            move_index: 0,
+        }
+        .to_xdr(&env, &setup.contract)]
+    );
+    assert!(client.get_moves(&SESSION_ID).get(0).unwrap().verified);
+    assert_eq!(
+        client.try_challenge_claim(&SESSION_ID, &0),
+        Err(Ok(Error::ClaimNotChallengeable))
+    );
+
+    // An unchallenged claim holds once its window has closed
+    claim(&setup, 1, 0, false);
+    assert_eq!(
+        client.try_end_game(&SESSION_ID),
+        Err(Ok(Error::ChallengeWindowOpen))
+    );
+    advance(&env, CLAIM_CHALLENGE_LEDGERS + 1);
+    assert_eq!(
+        client.try_challenge_claim(&SESSION_ID, &1),
+        Err(Ok(Error::ClaimNotChallengeable))
+    );
+    client.end_game(&SESSION_ID);
+    let game = client.get_game(&SESSION_ID);
+    assert_eq!((game.hits, game.misses), (1, 1));
+    assert_eq!(game.winner, Some(defender.clone()));
+
+    // Every claim held, so the defender gets the bonds back
+    assert_eq!(balance(&defender), funds);
+    assert_eq!(balance(&setup.contract), 0);
+    assert_eq!(client.get_optimistic(&SESSION_ID).unwrap().bonded, 0);
+}
+
+#[test]
+fn test_unproven_challenge_loses() {
+    let env = Env::default();
+    let setup = TestSetup::new(&env);
+    let client = &setup.client;
+    let (defender, attacker) = setup.start_game(SESSION_ID);
+    let token = setup.enable_optimistic(SESSION_ID);
+    let balance = |address: &Address| TokenClient::new(&env, &token).balance(address);
+    let funds = balance(&defender);
+
+    claim(&setup, 2, 3, false);
+    client.challenge_claim(&SESSION_ID, &0);
+    assert_eq!(
+        client.try_prove_claim(
+            &SESSION_ID,
+            &invalid_proof(&env),
+            &setup.public_inputs(SESSION_ID, 2, 3, false)
+        ),
+        Err(Ok(Error::InvalidProof))
+    );
+
+    advance(&env, CLAIM_CHALLENGE_LEDGERS + 1);
+    assert_eq!(
+        client.try_prove_claim(
+            &SESSION_ID,
+            &valid_proof(&env),
+            &setup.public_inputs(SESSION_ID, 2, 3, false)
+        ),
+        Err(Ok(Error::ProofDeadlinePassed))
+    );
+    client.end_game(&SESSION_ID);
+    let game = client.get_game(&SESSION_ID);
+    assert_eq!(game.winner, Some(attacker.clone()));
+    assert_eq!((game.hits, game.misses), (0, 1));
+
+    // The unproven claim's bond goes to the attacker
+    assert_eq!(balance(&attacker), CLAIM_BOND);
+    assert_eq!(balance(&defender), funds - CLAIM_BOND);
+    assert_eq!(balance(&setup.contract), 0);
+}
+
+#[test]
+fn test_challenge_catches_lying_defender() {
+    let env = Env::default();
+    let setup = TestSetup::new(&env);
+    let client = &setup.client;
+    let (_, attacker) = setup.start_game(SESSION_ID);
+    setup.start_game(SESSION_ID + 1);
+    setup.enable_optimistic(SESSION_ID);
+
+    // (2, 3) holds a trap, but the defender claims a miss
+    claim(&setup, 2, 3, false);
+    client.challenge_claim(&SESSION_ID, &0);
+
+    // The circuit proves no miss at (2, 3) of this game's grid: the true
+    // answer is not the claim, and a miss there only opens another grid
+    let proof = valid_proof(&env);
+    for inputs in [
+        setup.public_inputs(SESSION_ID, 2, 3, true),
+        setup.public_inputs(SESSION_ID + 1, 2, 3, false),
+        public_inputs(&env, &grid_setup(&env).grid_commitment, 2, 3, false),
+    ] {
+        assert_eq!(
+            client.try_prove_claim(&SESSION_ID, &proof, &inputs),
+            Err(Ok(Error::InvalidProof))
+        );
+    }
+
+    // So the lie stays unproven and the attacker takes the game
+    advance(&env, CLAIM_CHALLENGE_LEDGERS + 1);
+    client.end_game(&SESSION_ID);
+    let game = client.get_game(&SESSION_ID);
+    assert_eq!(game.winner, Some(attacker));
+    assert!(!client.get_moves(&SESSION_ID).get(0).unwrap().verified);
+}
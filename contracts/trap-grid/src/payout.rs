@@ -0,0 +1,148 @@
+//! Payout curve: how decisively a game was won, and the bonus that earns
+//!
+//! A win's margin is the winner's share of the moves above a bare majority,
+//! in basis points: the attacker counts its hits, the defender the misses.
+//! Only verified moves count for the winner, since the pool is paid by
+//! neither player: unproven optimistic claims and channel moves, which the
+//! players could agree on between themselves, earn no bonus. Finding every
+//! trap without a miss is a margin of 10,000, and so is a defender never
+//! hit. [`PayoutCurve`] maps the margin above its threshold to
+//! a bonus on the winnings, paid from the bonus pool of the stake's token.
+
+use soroban_sdk::Vec;
+
+use crate::{Game, Move, PayoutCurve, MAX_BPS};
+
+/// The winner's margin over `moves`, in basis points, counting the verified
+/// moves it won; 0 for a game without a winner or without moves
+pub fn margin_bps(game: &Game, moves: &Vec<Move>) -> u32 {
+    let Some(winner) = &game.winner else {
+        return 0;
+    };
+    let attacker_won = *winner == game.attacker;
+    let won = moves
+        .iter()
+        .filter(|m| m.verified && m.is_hit == attacker_won)
+        .count() as u64;
+    let moves = u64::from(moves.len());
+    if moves == 0 {
+        return 0;
+    }
+    let lead = (2 * won).saturating_sub(moves);
+    (lead * u64::from(MAX_BPS) / moves) as u32
+}
+
+/// The bonus `curve` pays on a win of `margin_bps`, in basis points of the
+/// winnings: nothing up to the threshold, rising to `max_bonus_bps` at a
+/// margin of 10,000, linearly or faster by `exponent`
+pub fn bonus_bps(curve: &PayoutCurve, margin_bps: u32) -> u32 {
+    if curve.max_bonus_bps == 0 || margin_bps <= curve.threshold_bps {
+        return 0;
+    }
+    let max = u128::from(MAX_BPS);
+    let x = u128::from(margin_bps - curve.threshold_bps) * max
+        / u128::from(MAX_BPS - curve.threshold_bps);
+    let shaped = (1..curve.exponent).fold(x, |acc, _| acc * x / max);
+    (u128::from(curve.max_bonus_bps) * shaped / max) as u32
+}
+
+#[cfg(test)]
+mod test {
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::{Address, Env};
+
+    use super::*;
+    use crate::GAME_SCHEMA_VERSION;
+
+    fn game(env: &Env, attacker_won: bool, hits: u32, misses: u32) -> Game {
+        let (defender, attacker) = (Address::generate(env), Address::generate(env));
+        Game {
+            winner: Some(if attacker_won {
+                attacker.clone()
+            } else {
+                defender.clone()
+            }),
+            defender,
+            attacker,
+            defender_points: 100,
+            attacker_points: 100,
+            moves_made: hits + misses,
+            hits,
+            misses,
+            game_started: true,
+            game_ended: true,
+            schema_version: GAME_SCHEMA_VERSION,
+            last_active_ledger: 0,
+            grid_size: 8,
+            trap_count: 10,
+        }
+    }
+
+    /// `hits` hits, then `misses` misses, all `verified` or none
+    fn moves(env: &Env, hits: u32, misses: u32, verified: bool) -> Vec<Move> {
+        let mut moves = Vec::new(env);
+        for i in 0..hits + misses {
+            moves.push_back(Move {
+                x: i / 8,
+                y: i % 8,
+                is_hit: i < hits,
+                verified,
+            });
+        }
+        moves
+    }
+
+    #[test]
+    fn test_margin() {
+        let env = Env::default();
+        let margin = |attacker_won, hits, misses| {
+            margin_bps(
+                &game(&env, attacker_won, hits, misses),
+                &moves(&env, hits, misses, true),
+            )
+        };
+        assert_eq!(margin(true, 10, 0), 10_000);
+        assert_eq!(margin(true, 3, 1), 5_000);
+        assert_eq!(margin(false, 10, 54), 6_875);
+        assert_eq!(margin(false, 0, 0), 0);
+        assert_eq!(
+            margin_bps(
+                &Game {
+                    winner: None,
+                    ..game(&env, true, 10, 0)
+                },
+                &moves(&env, 10, 0, true)
+            ),
+            0
+        );
+
+        // Unverified moves dilute the margin but never add to it
+        assert_eq!(
+            margin_bps(&game(&env, true, 10, 0), &moves(&env, 10, 0, false)),
+            0
+        );
+        let mut mixed = moves(&env, 2, 0, true);
+        mixed.append(&moves(&env, 2, 0, false));
+        assert_eq!(margin_bps(&game(&env, true, 4, 0), &mixed), 0);
+        mixed.append(&moves(&env, 2, 0, true));
+        assert_eq!(margin_bps(&game(&env, true, 6, 0), &mixed), 3_333);
+    }
+
+    #[test]
+    fn test_bonus() {
+        let curve = |exponent| PayoutCurve {
+            max_bonus_bps: 5_000,
+            threshold_bps: 2_000,
+            exponent,
+        };
+        assert_eq!(bonus_bps(&curve(1), 2_000), 0);
+        assert_eq!(bonus_bps(&curve(1), 6_000), 2_500);
+        assert_eq!(bonus_bps(&curve(2), 6_000), 1_250);
+        assert_eq!(bonus_bps(&curve(2), 10_000), 5_000);
+        let off = PayoutCurve {
+            max_bonus_bps: 0,
+            ..curve(1)
+        };
+        assert_eq!(bonus_bps(&off, 10_000), 0);
+    }
+}
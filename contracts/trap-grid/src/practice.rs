@@ -0,0 +1,105 @@
+//! Practice grids: the trap layout of a solo game, drawn by the contract
+//!
+//! Storage is public, so no layout exists while the game is played: each
+//! probe is drawn when it is made, a trap with the chance the remaining traps
+//! have among the remaining cells, which gives every layout the same odds as
+//! drawing it up front. Draws are seeded with the SHA-256 of the ledger's PRNG
+//! output for that call and the player's commitment. At the end, the traps
+//! still hidden are drawn among the cells never probed, and the whole layout
+//! is published.
+
+use soroban_sdk::{Bytes, BytesN, Env, Vec};
+
+/// The seed of a practice draw
+pub fn seed(env: &Env, entropy: &BytesN<32>, commitment: &BytesN<32>) -> BytesN<32> {
+    let mut input = Bytes::from(entropy);
+    input.append(&Bytes::from(commitment));
+    env.crypto().sha256(&input).into()
+}
+
+/// The `counter`th number below `bound` drawn from `seed`
+fn draw(env: &Env, seed: &BytesN<32>, counter: u32, bound: u32) -> u32 {
+    let mut input = Bytes::from(seed);
+    input.extend_from_array(&counter.to_be_bytes());
+    let hash = env.crypto().sha256(&input).to_array();
+    u32::from_be_bytes([hash[0], hash[1], hash[2], hash[3]]) % bound
+}
+
+/// Whether a probe seeded with `seed` holds a trap, when `traps_left` traps
+/// hide among `cells_left` unprobed cells
+pub fn is_trap(env: &Env, seed: &BytesN<32>, traps_left: u32, cells_left: u32) -> bool {
+    draw(env, seed, 0, cells_left) < traps_left
+}
+
+/// `trap_count` distinct cells of a `grid_size` grid, at `x * grid_size + y`,
+/// none of them in `taken`, in the order they were drawn; `trap_count` must
+/// not exceed the cells left
+pub fn layout(
+    env: &Env,
+    seed: &BytesN<32>,
+    grid_size: u32,
+    trap_count: u32,
+    taken: &Vec<u32>,
+) -> Vec<u32> {
+    let cells = grid_size * grid_size;
+    let mut traps = Vec::new(env);
+    let mut counter = 0u32;
+    while traps.len() < trap_count {
+        let cell = draw(env, seed, counter, cells);
+        if !traps.contains(cell) && !taken.contains(cell) {
+            traps.push_back(cell);
+        }
+        counter += 1;
+    }
+    traps
+}
+
+#[cfg(test)]
+mod test {
+    use soroban_sdk::vec;
+
+    use super::*;
+
+    #[test]
+    fn test_layout() {
+        let env = Env::default();
+        let first = seed(
+            &env,
+            &BytesN::from_array(&env, &[1; 32]),
+            &BytesN::from_array(&env, &[2; 32]),
+        );
+        let none = Vec::new(&env);
+        let traps = layout(&env, &first, 8, 12, &none);
+        assert_eq!(traps.len(), 12);
+        for (i, cell) in traps.iter().enumerate() {
+            assert!(cell < 64);
+            assert_eq!(traps.first_index_of(cell), Some(i as u32));
+        }
+        // Deterministic in the seed, and a full grid but one cell still fills
+        assert_eq!(layout(&env, &first, 8, 12, &none), traps);
+        assert_eq!(layout(&env, &first, 2, 3, &none).len(), 3);
+        // Taken cells are skipped, down to the last one left
+        let taken = vec![&env, 0, 1, 2];
+        assert_eq!(layout(&env, &first, 2, 1, &taken), vec![&env, 3]);
+
+        let other = seed(
+            &env,
+            &BytesN::from_array(&env, &[1; 32]),
+            &BytesN::from_array(&env, &[3; 32]),
+        );
+        assert_ne!(layout(&env, &other, 8, 12, &none), traps);
+    }
+
+    #[test]
+    fn test_is_trap() {
+        let env = Env::default();
+        let seed = seed(
+            &env,
+            &BytesN::from_array(&env, &[1; 32]),
+            &BytesN::from_array(&env, &[2; 32]),
+        );
+        // Certain once every cell left is a trap, impossible once none is
+        assert!(is_trap(&env, &seed, 5, 5));
+        assert!(!is_trap(&env, &seed, 0, 5));
+    }
+}
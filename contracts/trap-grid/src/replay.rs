@@ -0,0 +1,81 @@
+//! Replays: the moves of a finished game as the archive stores them
+//!
+//! Each move is two bytes in play order: `x` with the top bit set for a hit,
+//! then `y`. Coordinates stay below `MAX_GRID_SIZE`, so `x` never reaches the
+//! hit bit.
+
+use soroban_sdk::{Bytes, Env, Vec};
+
+use crate::Move;
+
+const HIT: u8 = 0x80;
+
+/// The replay of `moves`
+pub fn encode(env: &Env, moves: &Vec<Move>) -> Bytes {
+    let mut replay = Bytes::new(env);
+    for m in moves.iter() {
+        let x = m.x as u8;
+        replay.push_back(if m.is_hit { x | HIT } else { x });
+        replay.push_back(m.y as u8);
+    }
+    replay
+}
+
+/// The (x, y, is_hit) of each move of `replay`, or `None` if it is not a
+/// whole number of moves
+pub fn decode(env: &Env, replay: &Bytes) -> Option<Vec<(u32, u32, bool)>> {
+    if !replay.len().is_multiple_of(2) {
+        return None;
+    }
+    let mut moves = Vec::new(env);
+    for i in (0..replay.len()).step_by(2) {
+        let (x, y) = (replay.get_unchecked(i), replay.get_unchecked(i + 1));
+        moves.push_back((u32::from(x & !HIT), u32::from(y), x & HIT != 0));
+    }
+    Some(moves)
+}
+
+#[cfg(test)]
+mod test {
+    use soroban_sdk::vec;
+
+    use super::*;
+    use crate::MAX_GRID_SIZE;
+
+    #[test]
+    fn test_round_trip() {
+        let env = Env::default();
+        let last = MAX_GRID_SIZE - 1;
+        let moves = vec![
+            &env,
+            Move {
+                x: 3,
+                y: 2,
+                is_hit: true,
+                verified: true,
+            },
+            Move {
+                x: 0,
+                y: 5,
+                is_hit: false,
+                verified: true,
+            },
+            Move {
+                x: last,
+                y: last,
+                is_hit: true,
+                verified: false,
+            },
+        ];
+        let replay = encode(&env, &moves);
+        assert_eq!(
+            replay,
+            Bytes::from_array(&env, &[0x83, 0x02, 0x00, 0x05, 0x8f, 0x0f])
+        );
+        assert_eq!(
+            decode(&env, &replay),
+            Some(vec![&env, (3, 2, true), (0, 5, false), (last, last, true)])
+        );
+        assert_eq!(decode(&env, &Bytes::from_array(&env, &[0x83])), None);
+    }
+}
@@ -0,0 +1,390 @@
+//! Storage layout snapshots
+//!
+//! Games live in temporary storage across contract upgrades, so the XDR of
+//! every stored type and key must not change by accident. Each value below is
+//! encoded and compared with `storage_layout.txt`: a reordered field, a renamed
+//! variant or a changed type fails here instead of corrupting live games.
+//!
+//! When a layout change is intended (and migrated), replace the file with the
+//! listing the failing test prints.
+
+extern crate std;
+
+use std::fmt::Write as _;
+use std::string::String;
+
+use soroban_sdk::xdr::ToXdr;
+use soroban_sdk::{map, vec, Address, Bytes, BytesN, Env, Vec};
+
+use crate::{
+    AdminAction, AdminSet, Arbitration, Channel, ChannelState, ContractConfig, Daily, DailyEntry,
+    DailyRank, DataKey, Fees, Game, GameV1, GameV2, GameV3, GameV4, GlobalStats, Heatmap,
+    LastMoves, Move, Optimistic, PendingMove, Practice, PracticeStats, Proposal, Sponsorship,
+    Streak,
+};
+
+const DEFENDER: &str = "GAAQCAIBAEAQCAIBAEAQCAIBAEAQCAIBAEAQCAIBAEAQCAIBAEAQDZ7H";
+const ATTACKER: &str = "GAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAWHF";
+const CONTRACT: &str = "CABAEAQCAIBAEAQCAIBAEAQCAIBAEAQCAIBAEAQCAIBAEAQCAIBAFNSZ";
+
+/// `name hex-xdr` lines of every stored key and value
+fn layout(env: &Env) -> String {
+    let defender = Address::from_str(env, DEFENDER);
+    let attacker = Address::from_str(env, ATTACKER);
+    let game_v1 = GameV1 {
+        defender: defender.clone(),
+        attacker: attacker.clone(),
+        defender_points: 100,
+        attacker_points: 250,
+        moves_made: 2,
+        hits: 1,
+        misses: 1,
+        game_started: true,
+        game_ended: false,
+        winner: None,
+    };
+    let game_v2 = GameV2::from(game_v1.clone());
+    let game_v3 = GameV3 {
+        last_active_ledger: 120,
+        ..GameV3::from(game_v2.clone())
+    };
+    let game_v4 = GameV4 {
+        grid_size: 12,
+        ..GameV4::from(game_v3.clone())
+    };
+    let game = Game {
+        trap_count: 20,
+        ..Game::from(game_v4.clone())
+    };
+    let moves: Vec<Move> = vec![
+        env,
+        Move {
+            x: 2,
+            y: 3,
+            is_hit: true,
+            verified: true,
+        },
+        Move {
+            x: 7,
+            y: 0,
+            is_hit: false,
+            verified: true,
+        },
+    ];
+
+    let entries: [(&str, Bytes); 68] = [
+        ("DataKey::Game", DataKey::Game(7).to_xdr(env)),
+        ("DataKey::Moves", DataKey::Moves(7).to_xdr(env)),
+        ("DataKey::PendingMove", DataKey::PendingMove(7).to_xdr(env)),
+        (
+            "DataKey::GameHubAddress",
+            DataKey::GameHubAddress.to_xdr(env),
+        ),
+        (
+            "DataKey::VerifierAddress",
+            DataKey::VerifierAddress.to_xdr(env),
+        ),
+        ("DataKey::Admin", DataKey::Admin.to_xdr(env)),
+        ("DataKey::TrophyAddress", DataKey::TrophyAddress.to_xdr(env)),
+        (
+            "DataKey::Sponsorships",
+            DataKey::Sponsorships(7).to_xdr(env),
+        ),
+        ("DataKey::Fees", DataKey::Fees.to_xdr(env)),
+        (
+            "DataKey::Referrer",
+            DataKey::Referrer(attacker.clone()).to_xdr(env),
+        ),
+        (
+            "DataKey::ReferralRewards",
+            DataKey::ReferralRewards(defender.clone(), Address::from_str(env, CONTRACT))
+                .to_xdr(env),
+        ),
+        (
+            "DataKey::EmergencyWithdraw",
+            DataKey::EmergencyWithdraw(7).to_xdr(env),
+        ),
+        ("DataKey::AdminSet", DataKey::AdminSet.to_xdr(env)),
+        ("DataKey::ProposalCount", DataKey::ProposalCount.to_xdr(env)),
+        ("DataKey::Proposal", DataKey::Proposal(7).to_xdr(env)),
+        (
+            "DataKey::Operator",
+            DataKey::Operator(defender.clone()).to_xdr(env),
+        ),
+        ("DataKey::Paused", DataKey::Paused.to_xdr(env)),
+        ("DataKey::Config", DataKey::Config.to_xdr(env)),
+        ("DataKey::ActiveGames", DataKey::ActiveGames.to_xdr(env)),
+        ("DataKey::GlobalStats", DataKey::GlobalStats.to_xdr(env)),
+        ("DataKey::Heatmap", DataKey::Heatmap.to_xdr(env)),
+        ("DataKey::Arbitration", DataKey::Arbitration(7).to_xdr(env)),
+        ("DataKey::Claim", DataKey::Claim(7).to_xdr(env)),
+        ("DataKey::GridRoot", DataKey::GridRoot(7).to_xdr(env)),
+        (
+            "DataKey::UsedRoot",
+            DataKey::UsedRoot(defender.clone(), BytesN::from_array(env, &[1; 32])).to_xdr(env),
+        ),
+        (
+            "DataKey::SetupVerifierAddress",
+            DataKey::SetupVerifierAddress.to_xdr(env),
+        ),
+        ("DataKey::GridSetup", DataKey::GridSetup(7).to_xdr(env)),
+        (
+            "DataKey::UsedSetup",
+            DataKey::UsedSetup(BytesN::from_array(env, &[1; 32])).to_xdr(env),
+        ),
+        (
+            "DataKey::ArchiveAddress",
+            DataKey::ArchiveAddress.to_xdr(env),
+        ),
+        ("DataKey::LastMoves", DataKey::LastMoves(7).to_xdr(env)),
+        ("DataKey::AbortVotes", DataKey::AbortVotes(7).to_xdr(env)),
+        ("DataKey::Channel", DataKey::Channel(7).to_xdr(env)),
+        ("DataKey::Optimistic", DataKey::Optimistic(7).to_xdr(env)),
+        ("DataKey::Frozen", DataKey::Frozen(7).to_xdr(env)),
+        (
+            "DataKey::BonusPool",
+            DataKey::BonusPool(attacker.clone()).to_xdr(env),
+        ),
+        (
+            "DataKey::Practice",
+            DataKey::Practice(defender.clone()).to_xdr(env),
+        ),
+        (
+            "DataKey::PracticeStats",
+            DataKey::PracticeStats(defender.clone()).to_xdr(env),
+        ),
+        ("DataKey::Daily", DataKey::Daily(7).to_xdr(env)),
+        (
+            "DataKey::DailyEntry",
+            DataKey::DailyEntry(7, defender.clone()).to_xdr(env),
+        ),
+        (
+            "DataKey::DailyLeaderboard",
+            DataKey::DailyLeaderboard(7).to_xdr(env),
+        ),
+        (
+            "DataKey::Streak",
+            DataKey::Streak(defender.clone()).to_xdr(env),
+        ),
+        (
+            "DataKey::GamesCompleted",
+            DataKey::GamesCompleted(defender.clone()).to_xdr(env),
+        ),
+        ("GameV1", game_v1.to_xdr(env)),
+        ("GameV2", game_v2.to_xdr(env)),
+        ("GameV3", game_v3.to_xdr(env)),
+        ("GameV4", game_v4.to_xdr(env)),
+        ("Game", game.clone().to_xdr(env)),
+        (
+            "Game::ended",
+            Game {
+                game_ended: true,
+                winner: Some(attacker),
+                ..game
+            }
+            .to_xdr(env),
+        ),
+        ("Vec<Move>", moves.to_xdr(env)),
+        ("PendingMove", PendingMove { x: 4, y: 5 }.to_xdr(env)),
+        (
+            "Vec<Sponsorship>",
+            vec![
+                env,
+                Sponsorship {
+                    sponsor: Address::from_str(env, CONTRACT),
+                    player: defender.clone(),
+                    token: Address::from_str(env, CONTRACT),
+                    amount: 500,
+                    player_share_bps: 2_500,
+                },
+            ]
+            .to_xdr(env),
+        ),
+        (
+            "Fees",
+            Fees {
+                protocol_bps: 1_000,
+                referral_bps: 2_500,
+            }
+            .to_xdr(env),
+        ),
+        (
+            "AdminSet",
+            AdminSet {
+                members: vec![env, defender.clone(), Address::from_str(env, CONTRACT)],
+                threshold: 2,
+            }
+            .to_xdr(env),
+        ),
+        (
+            "Proposal",
+            Proposal {
+                action: AdminAction::SetFees(1_000, 2_500),
+                approvals: vec![env, defender.clone()],
+                expires_ledger: 120_960,
+            }
+            .to_xdr(env),
+        ),
+        ("ContractConfig", ContractConfig::DEFAULT.to_xdr(env)),
+        (
+            "GlobalStats",
+            GlobalStats {
+                games_created: 3,
+                games_completed: 2,
+                total_moves: 65,
+                total_hits: 9,
+                points_settled: 400,
+            }
+            .to_xdr(env),
+        ),
+        (
+            "Heatmap",
+            Heatmap {
+                probes: vec![env, 2, 1, 0],
+                hits: vec![env, 1, 0, 0],
+            }
+            .to_xdr(env),
+        ),
+        (
+            "Arbitration",
+            Arbitration {
+                arbiter: Address::from_str(env, CONTRACT),
+                appeal_until: 17_400,
+                appellant: Some(defender.clone()),
+            }
+            .to_xdr(env),
+        ),
+        (
+            "LastMoves",
+            LastMoves {
+                attacker_ledger: Some(17_400),
+                defender_ledger: None,
+            }
+            .to_xdr(env),
+        ),
+        (
+            "Channel",
+            Channel {
+                defender_key: BytesN::from_array(env, &[1; 32]),
+                attacker_key: BytesN::from_array(env, &[2; 32]),
+                state: ChannelState {
+                    move_index: 2,
+                    hits: 1,
+                    misses: 1,
+                    transcript: BytesN::from_array(env, &[3; 32]),
+                    closing: false,
+                },
+                challenge_until: 17_400,
+            }
+            .to_xdr(env),
+        ),
+        (
+            "Optimistic",
+            Optimistic {
+                claims: map![env, (0, 17_000), (1, 17_100)],
+                challenged: Some(1),
+                prove_until: 17_820,
+                token: Address::from_str(env, CONTRACT),
+                bond: 10,
+                bonded: 20,
+            }
+            .to_xdr(env),
+        ),
+        (
+            "Practice",
+            Practice {
+                commitment: BytesN::from_array(env, &[1; 32]),
+                grid_size: 8,
+                trap_count: 2,
+                probes: vec![env, 3, 4],
+                found: vec![env, 3],
+            }
+            .to_xdr(env),
+        ),
+        (
+            "PracticeStats",
+            PracticeStats {
+                games: 2,
+                cleared: 1,
+                best_probes: 11,
+                total_probes: 20,
+                total_hits: 12,
+            }
+            .to_xdr(env),
+        ),
+        (
+            "Daily",
+            Daily {
+                grid_hash: BytesN::from_array(env, &[1; 32]),
+                grid_size: 8,
+                trap_count: 10,
+                traps: vec![env, 3, 17],
+                players: 2,
+                misanswered: 1,
+            }
+            .to_xdr(env),
+        ),
+        (
+            "DailyEntry",
+            DailyEntry {
+                probes: vec![env, 0, 8],
+                answers: vec![env, true, false],
+                pending: Some(63),
+            }
+            .to_xdr(env),
+        ),
+        (
+            "Vec<DailyRank>",
+            vec![
+                env,
+                DailyRank {
+                    player: defender.clone(),
+                    hits: 2,
+                    probes: 3,
+                },
+            ]
+            .to_xdr(env),
+        ),
+        (
+            "Streak",
+            Streak {
+                current: 2,
+                best: 5,
+            }
+            .to_xdr(env),
+        ),
+        (
+            "Address::Contract",
+            Address::from_str(env, CONTRACT).to_xdr(env),
+        ),
+    ];
+
+    let mut listing = String::new();
+    for (name, xdr) in entries {
+        write!(listing, "{name} ").unwrap();
+        for byte in xdr.iter() {
+            write!(listing, "{byte:02x}").unwrap();
+        }
+        listing.push('\n');
+    }
+    listing
+}
+
+#[test]
+fn test_storage_layout_unchanged() {
+    let env = Env::default();
+    let actual = layout(&env);
+    let expected = include_str!("storage_layout.txt");
+    for (line, golden) in actual.lines().zip(expected.lines()) {
+        let name = line.split(' ').next().unwrap_or_default();
+        assert!(
+            line == golden,
+            "the XDR of {name} changed; if intended, replace src/storage_layout.txt with:\n{actual}"
+        );
+    }
+    assert_eq!(
+        actual.lines().count(),
+        expected.lines().count(),
+        "entries were added or removed; replace src/storage_layout.txt with:\n{actual}"
+    );
+}
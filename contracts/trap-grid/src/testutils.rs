@@ -0,0 +1,269 @@
+#![cfg(any(test, feature = "testutils"))]
+
+//! Helpers for tests of trap-grid and of contracts built around it
+//!
+//! Enabled by the `testutils` feature. [`TestSetup`] registers trap-grid with
+//! a mock Game Hub and mock verifiers that accept any non-empty proof,
+//! [`grid_setup`] fabricates setup proofs `start_game` accepts and [`moves`]
+//! moves `make_move` accepts against them:
+//!
+//! ```ignore
+//! let env = Env::default();
+//! let setup = TestSetup::new(&env);
+//! let (defender, attacker) = setup.start_game(1);
+//! for test_move in moves(|x, y| x == y).take(3) {
+//!     setup.make_move(1, &test_move);
+//! }
+//! ```
+
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::token::StellarAssetClient;
+use soroban_sdk::xdr::ToXdr;
+use soroban_sdk::{contract, contracterror, contractimpl, Address, Bytes, BytesN, Env};
+use trap_grid_encoding::{PositionMovement, DEFAULT_GRID_SIZE};
+
+use crate::{Error, GridSetup, PendingMove, TrapGridContract, TrapGridContractClient};
+
+/// Points each player commits in [`TestSetup::start_game`]
+pub const DEFAULT_POINTS: i128 = 100;
+
+/// Bond of each claim in [`TestSetup::enable_optimistic`]
+pub const CLAIM_BOND: i128 = 10;
+
+/// Game Hub that accepts every session and records nothing
+#[contract]
+pub struct MockGameHub;
+
+#[contractimpl]
+impl MockGameHub {
+    pub fn start_game(
+        _env: Env,
+        _game_id: Address,
+        _session_id: u32,
+        _player1: Address,
+        _player2: Address,
+        _player1_points: i128,
+        _player2_points: i128,
+    ) {
+    }
+
+    pub fn end_game(_env: Env, _session_id: u32, _player1_won: bool) {}
+}
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum MockVerifierError {
+    VerificationFailed = 3,
+}
+
+/// Accepts every non-empty proof
+#[contract]
+pub struct MockVerifier;
+
+#[contractimpl]
+impl MockVerifier {
+    pub fn verify_proof(
+        _env: Env,
+        _public_inputs: Bytes,
+        proof_bytes: Bytes,
+    ) -> Result<(), MockVerifierError> {
+        if proof_bytes.is_empty() {
+            return Err(MockVerifierError::VerificationFailed);
+        }
+        Ok(())
+    }
+}
+
+/// A proof [`MockVerifier`] accepts
+pub fn valid_proof(env: &Env) -> Bytes {
+    Bytes::from_array(env, &[1u8; 4])
+}
+
+/// A proof [`MockVerifier`] rejects
+pub fn invalid_proof(env: &Env) -> Bytes {
+    Bytes::new(env)
+}
+
+/// A setup proof [`MockVerifier`] accepts, for a grid commitment no game
+/// has used; the commitment's top byte is cleared so that it is a canonical
+/// field element, as the circuit's would be
+pub fn grid_setup(env: &Env) -> GridSetup {
+    let unique = Address::generate(env).to_xdr(env);
+    let mut grid_commitment = env.crypto().sha256(&unique).to_array();
+    grid_commitment[0] = 0;
+    GridSetup {
+        grid_commitment: BytesN::from_array(env, &grid_commitment),
+        proof: valid_proof(env),
+    }
+}
+
+/// Position-movement public inputs of a move and its claim on a default-size
+/// grid with `grid_commitment`
+pub fn public_inputs(
+    env: &Env,
+    grid_commitment: &BytesN<32>,
+    x: u32,
+    y: u32,
+    is_hit: bool,
+) -> Bytes {
+    let inputs = PositionMovement {
+        move_x: x,
+        move_y: y,
+        is_hit,
+        grid_size: DEFAULT_GRID_SIZE,
+        grid_commitment: grid_commitment.to_array(),
+    };
+    Bytes::from_array(env, &inputs.encode())
+}
+
+/// A cell and the defender's claim about it
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TestMove {
+    pub x: u32,
+    pub y: u32,
+    pub is_hit: bool,
+}
+
+impl TestMove {
+    pub fn public_inputs(&self, env: &Env, grid_commitment: &BytesN<32>) -> Bytes {
+        public_inputs(env, grid_commitment, self.x, self.y, self.is_hit)
+    }
+}
+
+/// Every cell of a default-size grid once, row by row, claimed as a hit where
+/// `is_trap` says so
+pub fn moves(is_trap: impl Fn(u32, u32) -> bool) -> impl Iterator<Item = TestMove> {
+    (0..DEFAULT_GRID_SIZE)
+        .flat_map(|x| (0..DEFAULT_GRID_SIZE).map(move |y| (x, y)))
+        .map(move |(x, y)| TestMove {
+            x,
+            y,
+            is_hit: is_trap(x, y),
+        })
+}
+
+/// A trap-grid contract wired to a Game Hub and verifiers, with all
+/// authorizations mocked
+pub struct TestSetup<'a> {
+    pub env: Env,
+    pub admin: Address,
+    pub game_hub: Address,
+    pub verifier: Address,
+    pub setup_verifier: Address,
+    pub contract: Address,
+    pub client: TrapGridContractClient<'a>,
+}
+
+impl TestSetup<'_> {
+    /// Trap-grid with [`MockGameHub`] and [`MockVerifier`]s
+    pub fn new(env: &Env) -> Self {
+        let game_hub = env.register(MockGameHub, ());
+        let verifier = env.register(MockVerifier, ());
+        Self::with_contracts(env, game_hub, verifier)
+    }
+
+    /// Trap-grid with the given Game Hub and move verifier, e.g. a hub that
+    /// keeps score; setup proofs go to a [`MockVerifier`]
+    pub fn with_contracts(env: &Env, game_hub: Address, verifier: Address) -> Self {
+        env.mock_all_auths();
+        let admin = Address::generate(env);
+        let setup_verifier = env.register(MockVerifier, ());
+        let contract = env.register(
+            TrapGridContract,
+            (&admin, &game_hub, &verifier, &setup_verifier),
+        );
+        Self {
+            env: env.clone(),
+            client: TrapGridContractClient::new(env, &contract),
+            admin,
+            game_hub,
+            verifier,
+            setup_verifier,
+            contract,
+        }
+    }
+
+    /// Start `session_id` between two new players with [`DEFAULT_POINTS`]
+    /// each; returns (defender, attacker)
+    pub fn start_game(&self, session_id: u32) -> (Address, Address) {
+        let defender = Address::generate(&self.env);
+        let attacker = Address::generate(&self.env);
+        self.client.start_game(
+            &session_id,
+            &defender,
+            &attacker,
+            &DEFAULT_POINTS,
+            &DEFAULT_POINTS,
+            &grid_setup(&self.env),
+        );
+        (defender, attacker)
+    }
+
+    /// Put `session_id` in optimistic mode, bonding claims with
+    /// [`CLAIM_BOND`] of a new token its defender holds enough of to claim
+    /// every cell; returns the token
+    pub fn enable_optimistic(&self, session_id: u32) -> Address {
+        let issuer = Address::generate(&self.env);
+        let token = self
+            .env
+            .register_stellar_asset_contract_v2(issuer)
+            .address();
+        let game = self.client.get_game(&session_id);
+        let funds = CLAIM_BOND * i128::from(game.cells());
+        StellarAssetClient::new(&self.env, &token).mint(&game.defender, &funds);
+        self.client
+            .enable_optimistic(&session_id, &token, &CLAIM_BOND);
+        token
+    }
+
+    /// Position-movement public inputs of a move and its claim on the grid
+    /// `session_id` started with, or on a zero commitment if it never started
+    pub fn public_inputs(&self, session_id: u32, x: u32, y: u32, is_hit: bool) -> Bytes {
+        let grid_commitment = self
+            .client
+            .get_grid_commitment(&session_id)
+            .unwrap_or_else(|| BytesN::from_array(&self.env, &[0; 32]));
+        public_inputs(&self.env, &grid_commitment, x, y, is_hit)
+    }
+
+    /// Play `test_move`: the attacker submits it, unless it is pending
+    /// already, and the defender answers with a proof the mock verifier
+    /// accepts
+    pub fn make_move(&self, session_id: u32, test_move: &TestMove) {
+        self.try_make_move(session_id, test_move)
+            .expect("make_move failed");
+    }
+
+    /// [`make_move`](Self::make_move), returning the error of the answer; a
+    /// submission that fails leaves `make_move` to fail on its own checks
+    pub fn try_make_move(&self, session_id: u32, test_move: &TestMove) -> Result<(), Error> {
+        let (x, y) = (test_move.x, test_move.y);
+        if self.client.get_pending_move(&session_id) != Some(PendingMove { x, y }) {
+            let _ = self.client.try_submit_move(&session_id, &x, &y);
+        }
+        match self.client.try_make_move(
+            &session_id,
+            &test_move.x,
+            &test_move.y,
+            &test_move.is_hit,
+            &valid_proof(&self.env),
+            &self.public_inputs(session_id, test_move.x, test_move.y, test_move.is_hit),
+        ) {
+            Ok(_) => Ok(()),
+            Err(Ok(error)) => Err(error),
+            Err(Err(error)) => panic!("make_move aborted: {error:?}"),
+        }
+    }
+
+    /// Play `test_moves` in order, stopping at the first failure
+    pub fn play(
+        &self,
+        session_id: u32,
+        test_moves: impl IntoIterator<Item = TestMove>,
+    ) -> Result<(), Error> {
+        test_moves
+            .into_iter()
+            .try_for_each(|test_move| self.try_make_move(session_id, &test_move))
+    }
+}
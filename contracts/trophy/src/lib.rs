@@ -0,0 +1,307 @@
+#![no_std]
+
+//! # Trophy
+//!
+//! Non-transferable trophy tokens for match and tournament winners:
+//! - The admin allows game contracts (trap-grid, a tournament) to mint
+//! - A game mints a trophy to the winner when a session ends, recording the
+//!   session, the opponent and the winner's score
+//! - Trophies have no transfer function: each stays with the player it was
+//!   minted to, a verifiable record beyond the Game Hub's stats
+
+use soroban_sdk::{
+    contract, contracterror, contractevent, contractimpl, contracttype, Address, Env, Vec,
+};
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum Error {
+    /// The caller is not a game the admin allowed to mint
+    NotMinter = 1,
+    TrophyNotFound = 2,
+}
+
+// ============================================================================
+// Data Types
+// ============================================================================
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Trophy {
+    pub id: u32,
+    pub owner: Address,
+    /// Game contract that minted the trophy
+    pub game: Address,
+    pub session_id: u32,
+    pub opponent: Address,
+    /// The winner's score, in the game's own unit
+    pub score: u32,
+    /// Ledger sequence the trophy was minted in
+    pub ledger: u32,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum DataKey {
+    Admin,
+    TrophyCount,
+    Minter(Address),   // game -> true
+    Trophy(u32),       // id -> Trophy
+    Trophies(Address), // player -> Vec<u32> of trophy ids
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+/// Published by `mint`
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TrophyMinted {
+    #[topic]
+    pub owner: Address,
+    pub id: u32,
+    pub game: Address,
+    pub session_id: u32,
+}
+
+// ============================================================================
+// Storage TTL Management
+// ============================================================================
+
+const TROPHY_TTL_LEDGERS: u32 = 518_400; // 30 days
+
+// ============================================================================
+// Contract Definition
+// ============================================================================
+
+#[contract]
+pub struct TrophyContract;
+
+#[contractimpl]
+impl TrophyContract {
+    /// Initialize the contract with the admin who allows games to mint
+    pub fn __constructor(env: Env, admin: Address) {
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.storage().instance().set(&DataKey::TrophyCount, &0u32);
+    }
+
+    /// Allow or disallow `game` to mint trophies
+    pub fn set_minter(env: Env, game: Address, allowed: bool) {
+        admin(&env).require_auth();
+        let key = DataKey::Minter(game);
+        if allowed {
+            env.storage().persistent().set(&key, &true);
+            env.storage()
+                .persistent()
+                .extend_ttl(&key, TROPHY_TTL_LEDGERS, TROPHY_TTL_LEDGERS);
+        } else {
+            env.storage().persistent().remove(&key);
+        }
+    }
+
+    /// Whether `game` may mint trophies
+    pub fn is_minter(env: Env, game: Address) -> bool {
+        env.storage().persistent().has(&DataKey::Minter(game))
+    }
+
+    /// Mint a trophy to the winner of `session_id` of `game`; returns its id
+    ///
+    /// # Arguments
+    /// * `game` - Game contract minting the trophy; must authorize the call
+    /// * `winner` - Player who receives the trophy
+    /// * `session_id` - Session the trophy was won in
+    /// * `opponent` - Player the winner beat
+    /// * `score` - The winner's score
+    pub fn mint(
+        env: Env,
+        game: Address,
+        winner: Address,
+        session_id: u32,
+        opponent: Address,
+        score: u32,
+    ) -> Result<u32, Error> {
+        game.require_auth();
+        if !Self::is_minter(env.clone(), game.clone()) {
+            return Err(Error::NotMinter);
+        }
+
+        let id: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::TrophyCount)
+            .unwrap_or(0);
+        let trophy = Trophy {
+            id,
+            owner: winner.clone(),
+            game: game.clone(),
+            session_id,
+            opponent,
+            score,
+            ledger: env.ledger().sequence(),
+        };
+        let trophy_key = DataKey::Trophy(id);
+        env.storage().persistent().set(&trophy_key, &trophy);
+        env.storage()
+            .persistent()
+            .extend_ttl(&trophy_key, TROPHY_TTL_LEDGERS, TROPHY_TTL_LEDGERS);
+
+        let owned_key = DataKey::Trophies(winner.clone());
+        let mut owned: Vec<u32> = env
+            .storage()
+            .persistent()
+            .get(&owned_key)
+            .unwrap_or(Vec::new(&env));
+        owned.push_back(id);
+        env.storage().persistent().set(&owned_key, &owned);
+        env.storage()
+            .persistent()
+            .extend_ttl(&owned_key, TROPHY_TTL_LEDGERS, TROPHY_TTL_LEDGERS);
+
+        env.storage()
+            .instance()
+            .set(&DataKey::TrophyCount, &(id + 1));
+        env.storage()
+            .instance()
+            .extend_ttl(TROPHY_TTL_LEDGERS, TROPHY_TTL_LEDGERS);
+
+        TrophyMinted {
+            owner: winner,
+            id,
+            game,
+            session_id,
+        }
+        .publish(&env);
+        Ok(id)
+    }
+
+    /// Get a trophy by id
+    pub fn get_trophy(env: Env, id: u32) -> Result<Trophy, Error> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Trophy(id))
+            .ok_or(Error::TrophyNotFound)
+    }
+
+    /// Trophies `player` has won, oldest first
+    pub fn get_trophies(env: Env, player: Address) -> Vec<Trophy> {
+        let ids: Vec<u32> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Trophies(player))
+            .unwrap_or(Vec::new(&env));
+        let mut trophies = Vec::new(&env);
+        for id in ids.iter() {
+            if let Some(trophy) = env.storage().persistent().get(&DataKey::Trophy(id)) {
+                trophies.push_back(trophy);
+            }
+        }
+        trophies
+    }
+
+    /// Number of trophies minted
+    pub fn trophy_count(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::TrophyCount)
+            .unwrap_or(0)
+    }
+}
+
+fn admin(env: &Env) -> Address {
+    env.storage()
+        .instance()
+        .get(&DataKey::Admin)
+        .expect("Admin not set")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use soroban_sdk::testutils::{Address as _, Events as _};
+    use soroban_sdk::Event;
+
+    struct Setup<'a> {
+        env: Env,
+        game: Address,
+        client: TrophyContractClient<'a>,
+    }
+
+    fn setup(env: &Env) -> Setup<'_> {
+        env.mock_all_auths();
+        let admin = Address::generate(env);
+        let contract = env.register(TrophyContract, (&admin,));
+        let client = TrophyContractClient::new(env, &contract);
+        let game = Address::generate(env);
+        client.set_minter(&game, &true);
+        Setup {
+            env: env.clone(),
+            game,
+            client,
+        }
+    }
+
+    #[test]
+    fn test_mint_and_get_trophies() {
+        let env = Env::default();
+        let setup = setup(&env);
+        let client = &setup.client;
+        let winner = Address::generate(&env);
+        let opponent = Address::generate(&env);
+
+        assert_eq!(client.get_trophies(&winner).len(), 0);
+        assert_eq!(client.mint(&setup.game, &winner, &7, &opponent, &40), 0);
+        let event = TrophyMinted {
+            owner: winner.clone(),
+            id: 0,
+            game: setup.game.clone(),
+            session_id: 7,
+        };
+        assert_eq!(
+            env.events().all().filter_by_contract(&client.address),
+            [event.to_xdr(&env, &client.address)]
+        );
+        assert_eq!(client.mint(&setup.game, &winner, &9, &opponent, &33), 1);
+        assert_eq!(client.mint(&setup.game, &opponent, &8, &winner, &56), 2);
+
+        let trophies = client.get_trophies(&winner);
+        assert_eq!(trophies.len(), 2);
+        let first = trophies.get(0).unwrap();
+        assert_eq!(
+            (first.session_id, first.opponent, first.score),
+            (7, opponent.clone(), 40)
+        );
+        assert_eq!(first.game, setup.game);
+        assert_eq!(client.get_trophy(&2).owner, opponent);
+        assert_eq!(client.trophy_count(), 3);
+        assert_eq!(client.try_get_trophy(&3), Err(Ok(Error::TrophyNotFound)));
+    }
+
+    #[test]
+    fn test_only_minters_mint() {
+        let env = Env::default();
+        let setup = setup(&env);
+        let client = &setup.client;
+        let winner = Address::generate(&env);
+        let opponent = Address::generate(&env);
+
+        let stranger = Address::generate(&env);
+        assert_eq!(
+            client.try_mint(&stranger, &winner, &1, &opponent, &1),
+            Err(Ok(Error::NotMinter))
+        );
+
+        client.set_minter(&setup.game, &false);
+        assert!(!client.is_minter(&setup.game));
+        assert_eq!(
+            client.try_mint(&setup.game, &winner, &1, &opponent, &1),
+            Err(Ok(Error::NotMinter))
+        );
+        assert_eq!(setup.env.events().all().events().len(), 0);
+    }
+}
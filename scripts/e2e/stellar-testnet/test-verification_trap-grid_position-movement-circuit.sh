@@ -16,7 +16,7 @@ mkdir -p "$CARGO_TARGET_DIR"
 # on Stellar testnet.
 #
 # CIRCUIT STRUCTURE (Simplified - No Commitments):
-# - Public inputs (3 fields): move_x, move_y, is_hit
+# - Public inputs (4 fields): move_x, move_y, is_hit, grid_size
 # - Private inputs: trap_value
 # - Constraints: coordinate bounds checking, boolean validation, value matching
 #
@@ -24,7 +24,7 @@ mkdir -p "$CARGO_TARGET_DIR"
 # and Merkle proof verification, containing only basic constraint checks.
 # This minimizes the circuit size to:
 #   - 20 ACIR opcodes
-#   - 3 public input fields (96 bytes)
+#   - 4 public input fields (128 bytes)
 #
 # VERIFICATION STATUS:
 # Testing required after circuit simplification
@@ -0,0 +1,259 @@
+//! # API
+//!
+//! Read-only HTTP API over the `indexer` database, so the dApp can list games,
+//! replays and rankings without querying the chain itself:
+//! - `GET /games/{id}` - a session's players, counters and result
+//! - `GET /games/{id}/replay` - the same with every move in play order
+//! - `GET /players/{address}/games` - a player's games, newest first
+//! - `GET /leaderboard` - players ranked by wins
+//!
+//! List endpoints take `?limit=` (at most 100) and `?offset=`. Responses are
+//! cached in memory for `--cache-ttl` seconds and carry a matching
+//! `Cache-Control` header.
+
+mod store;
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use axum::extract::{Path, Query, State};
+use axum::http::{header, StatusCode, Uri};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use clap::Parser;
+use serde_json::{json, Value};
+
+use crate::store::{Page, Store};
+
+#[derive(Parser)]
+#[command(
+    name = "api",
+    version,
+    about = "Serves indexed trap-grid games over HTTP"
+)]
+struct Cli {
+    /// Database written by the indexer
+    #[arg(long, default_value = "trap-grid.sqlite")]
+    db: PathBuf,
+
+    /// Address to listen on
+    #[arg(long, env = "TRAP_GRID_API_ADDR", default_value = "127.0.0.1:8080")]
+    listen: SocketAddr,
+
+    /// Seconds a response is cached; 0 disables caching
+    #[arg(long, default_value_t = 5)]
+    cache_ttl: u64,
+}
+
+/// Responses by request URI
+struct ResponseCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, (Instant, Value)>>,
+}
+
+impl ResponseCache {
+    /// Entries kept before the cache is emptied
+    const MAX_ENTRIES: usize = 10_000;
+
+    fn get(&self, key: &str) -> Option<Value> {
+        let entries = self.entries.lock().expect("cache lock poisoned");
+        match entries.get(key) {
+            Some((at, value)) if at.elapsed() < self.ttl => Some(value.clone()),
+            _ => None,
+        }
+    }
+
+    fn put(&self, key: String, value: Value) {
+        if self.ttl.is_zero() {
+            return;
+        }
+        let mut entries = self.entries.lock().expect("cache lock poisoned");
+        if entries.len() >= Self::MAX_ENTRIES {
+            entries.clear();
+        }
+        entries.insert(key, (Instant::now(), value));
+    }
+}
+
+struct AppState {
+    store: Store,
+    cache: ResponseCache,
+}
+
+enum ApiError {
+    NotFound(String),
+    Internal(anyhow::Error),
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            ApiError::NotFound(message) => (StatusCode::NOT_FOUND, message),
+            ApiError::Internal(err) => {
+                eprintln!("{err:#}");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "internal error".to_string(),
+                )
+            }
+        };
+        (status, Json(json!({ "error": message }))).into_response()
+    }
+}
+
+/// Serve `uri` from the cache, or run `query` on a blocking thread and cache it
+async fn cached<T, F>(state: Arc<AppState>, uri: Uri, query: F) -> Result<Response, ApiError>
+where
+    T: serde::Serialize + Send + 'static,
+    F: FnOnce(&Store) -> Result<Option<T>> + Send + 'static,
+{
+    let key = uri.to_string();
+    let value = match state.cache.get(&key) {
+        Some(value) => value,
+        None => {
+            let fetch = state.clone();
+            let found = tokio::task::spawn_blocking(move || query(&fetch.store))
+                .await
+                .map_err(|err| ApiError::Internal(err.into()))?
+                .map_err(ApiError::Internal)?
+                .ok_or_else(|| ApiError::NotFound(format!("nothing at {}", uri.path())))?;
+            let value =
+                serde_json::to_value(found).map_err(|err| ApiError::Internal(err.into()))?;
+            state.cache.put(key, value.clone());
+            value
+        }
+    };
+    let cache_control = format!("public, max-age={}", state.cache.ttl.as_secs());
+    Ok(([(header::CACHE_CONTROL, cache_control)], Json(value)).into_response())
+}
+
+async fn game(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<u32>,
+    uri: Uri,
+) -> Result<Response, ApiError> {
+    cached(state, uri, move |store| store.game(session_id)).await
+}
+
+async fn replay(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<u32>,
+    uri: Uri,
+) -> Result<Response, ApiError> {
+    cached(state, uri, move |store| store.replay(session_id)).await
+}
+
+async fn player_games(
+    State(state): State<Arc<AppState>>,
+    Path(player): Path<String>,
+    Query(page): Query<Page>,
+    uri: Uri,
+) -> Result<Response, ApiError> {
+    cached(state, uri, move |store| {
+        store.player_games(&player, page).map(Some)
+    })
+    .await
+}
+
+async fn leaderboard(
+    State(state): State<Arc<AppState>>,
+    Query(page): Query<Page>,
+    uri: Uri,
+) -> Result<Response, ApiError> {
+    cached(state, uri, move |store| store.leaderboard(page).map(Some)).await
+}
+
+fn router(store: Store, cache_ttl: Duration) -> Router {
+    let state = Arc::new(AppState {
+        store,
+        cache: ResponseCache {
+            ttl: cache_ttl,
+            entries: Mutex::new(HashMap::new()),
+        },
+    });
+    Router::new()
+        .route("/games/{id}", get(game))
+        .route("/games/{id}/replay", get(replay))
+        .route("/players/{address}/games", get(player_games))
+        .route("/leaderboard", get(leaderboard))
+        .with_state(state)
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    let store = Store::open(&cli.db)?;
+    let app = router(store, Duration::from_secs(cli.cache_ttl));
+    let listener = tokio::net::TcpListener::bind(cli.listen).await?;
+    println!("Serving {} on http://{}", cli.db.display(), cli.listen);
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use axum::body::{to_bytes, Body};
+    use axum::http::Request;
+    use rusqlite::Connection;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    fn app() -> Router {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(include_str!("../../indexer/src/schema.sql"))
+            .unwrap();
+        conn.execute_batch(
+            "PRAGMA user_version = 1;
+             INSERT INTO games (session_id, defender, attacker, started_ledger, started_at, moves_made, hits, misses)
+                 VALUES (1, 'GD', 'GA', 10, 't0', 2, 2, 0), (2, 'GD', 'GB', 20, 't1', 1, 0, 1);
+             INSERT INTO moves VALUES (1, 1, 2, 3, 1, 1, 11, 'aa'), (1, 2, 4, 4, 1, 1, 12, 'bb');
+             INSERT INTO results VALUES (1, 'GA', 1, 2, 0, 13, 't2'), (2, 'GD', 0, 0, 1, 21, 't3');",
+        )
+        .unwrap();
+        router(
+            Store::from_connection(conn).unwrap(),
+            Duration::from_secs(5),
+        )
+    }
+
+    async fn get_json(app: Router, uri: &str) -> (StatusCode, Value) {
+        let response = app
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_endpoints() {
+        let app = app();
+
+        let (status, replay) = get_json(app.clone(), "/games/1/replay").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(replay["game"]["result"]["winner"], "GA");
+        assert_eq!(replay["moves"][1]["x"], 4);
+
+        let (status, _) = get_json(app.clone(), "/games/3").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (_, games) = get_json(app.clone(), "/players/GD/games?limit=1").await;
+        assert_eq!(games["total"], 2);
+        assert_eq!(games["items"][0]["session_id"], 2);
+
+        let (_, leaderboard) = get_json(app, "/leaderboard").await;
+        assert_eq!(leaderboard["total"], 3);
+        // Equal wins rank the player with fewer games first
+        assert_eq!(leaderboard["items"][0]["player"], "GA");
+        assert_eq!(leaderboard["items"][1]["player"], "GD");
+        assert_eq!(leaderboard["items"][1]["win_rate"], 0.5);
+    }
+}
@@ -0,0 +1,259 @@
+//! Queries over the indexer's SQLite database
+//!
+//! The database is opened read-only; the indexer is its only writer.
+
+use std::path::Path;
+use std::sync::Mutex;
+
+use anyhow::{bail, Context, Result};
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+/// Schema version of the indexer this API understands (`PRAGMA user_version`)
+const SCHEMA_VERSION: u32 = 1;
+
+const GAME_COLUMNS: &str = "g.session_id, g.defender, g.attacker, g.started_ledger,
+    g.started_at, g.moves_made, g.hits, g.misses, g.pending_x, g.pending_y,
+    r.winner, r.attacker_won, r.ledger, r.ended_at";
+
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct Cell {
+    pub x: u32,
+    pub y: u32,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct GameResult {
+    pub winner: String,
+    /// Unknown when the game started before the indexer's first ledger
+    pub attacker_won: Option<bool>,
+    pub ledger: u32,
+    pub ended_at: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct GameView {
+    pub session_id: u32,
+    pub defender: String,
+    pub attacker: String,
+    pub started_ledger: u32,
+    pub started_at: String,
+    pub moves_made: u32,
+    pub hits: u32,
+    pub misses: u32,
+    pub pending: Option<Cell>,
+    pub result: Option<GameResult>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct MoveView {
+    pub move_number: u32,
+    pub x: u32,
+    pub y: u32,
+    pub is_hit: bool,
+    pub verified: bool,
+    pub ledger: u32,
+    pub tx_hash: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Replay {
+    pub game: GameView,
+    pub moves: Vec<MoveView>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct LeaderboardEntry {
+    pub player: String,
+    pub games: u32,
+    pub wins: u32,
+    pub win_rate: f64,
+}
+
+/// `?limit=&offset=` query parameters
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub struct Page {
+    #[serde(default = "Page::default_limit")]
+    pub limit: u32,
+    #[serde(default)]
+    pub offset: u32,
+}
+
+impl Page {
+    pub const MAX_LIMIT: u32 = 100;
+
+    fn default_limit() -> u32 {
+        20
+    }
+
+    fn limit(&self) -> u32 {
+        self.limit.clamp(1, Self::MAX_LIMIT)
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Paged<T> {
+    pub items: Vec<T>,
+    pub limit: u32,
+    pub offset: u32,
+    pub total: u32,
+}
+
+fn game_view(row: &Row<'_>) -> rusqlite::Result<GameView> {
+    let pending = match (row.get(8)?, row.get(9)?) {
+        (Some(x), Some(y)) => Some(Cell { x, y }),
+        _ => None,
+    };
+    let result = match row.get::<_, Option<String>>(10)? {
+        Some(winner) => Some(GameResult {
+            winner,
+            attacker_won: row.get(11)?,
+            ledger: row.get(12)?,
+            ended_at: row.get(13)?,
+        }),
+        None => None,
+    };
+    Ok(GameView {
+        session_id: row.get(0)?,
+        defender: row.get(1)?,
+        attacker: row.get(2)?,
+        started_ledger: row.get(3)?,
+        started_at: row.get(4)?,
+        moves_made: row.get(5)?,
+        hits: row.get(6)?,
+        misses: row.get(7)?,
+        pending,
+        result,
+    })
+}
+
+/// Finished games from each player's side: `(player, won)`
+const PLAYER_RESULTS: &str = "
+    WITH player_results AS (
+        SELECT g.defender AS player, r.winner = g.defender AS won
+        FROM games g JOIN results r USING (session_id)
+        UNION ALL
+        SELECT g.attacker, r.winner = g.attacker
+        FROM games g JOIN results r USING (session_id)
+    )";
+
+pub struct Store {
+    conn: Mutex<Connection>,
+}
+
+impl Store {
+    pub fn open(path: &Path) -> Result<Self> {
+        let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .with_context(|| format!("failed to open {}", path.display()))?;
+        Self::from_connection(conn)
+    }
+
+    pub fn from_connection(conn: Connection) -> Result<Self> {
+        let version: u32 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        if version != SCHEMA_VERSION {
+            bail!("database schema version {version}, expected {SCHEMA_VERSION}; is it an indexer database?");
+        }
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn conn(&self) -> std::sync::MutexGuard<'_, Connection> {
+        self.conn.lock().expect("database lock poisoned")
+    }
+
+    pub fn game(&self, session_id: u32) -> Result<Option<GameView>> {
+        Ok(self
+            .conn()
+            .query_row(
+                &format!(
+                    "SELECT {GAME_COLUMNS} FROM games g LEFT JOIN results r USING (session_id)
+                     WHERE g.session_id = ?1"
+                ),
+                [session_id],
+                game_view,
+            )
+            .optional()?)
+    }
+
+    pub fn replay(&self, session_id: u32) -> Result<Option<Replay>> {
+        let Some(game) = self.game(session_id)? else {
+            return Ok(None);
+        };
+        let conn = self.conn();
+        let mut statement = conn.prepare(
+            "SELECT move_number, x, y, is_hit, verified, ledger, tx_hash FROM moves
+             WHERE session_id = ?1 ORDER BY move_number",
+        )?;
+        let moves = statement
+            .query_map([session_id], |row| {
+                Ok(MoveView {
+                    move_number: row.get(0)?,
+                    x: row.get(1)?,
+                    y: row.get(2)?,
+                    is_hit: row.get(3)?,
+                    verified: row.get(4)?,
+                    ledger: row.get(5)?,
+                    tx_hash: row.get(6)?,
+                })
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(Some(Replay { game, moves }))
+    }
+
+    /// A player's games as defender or attacker, newest first
+    pub fn player_games(&self, player: &str, page: Page) -> Result<Paged<GameView>> {
+        let conn = self.conn();
+        let total = conn.query_row(
+            "SELECT COUNT(*) FROM games WHERE defender = ?1 OR attacker = ?1",
+            [player],
+            |row| row.get(0),
+        )?;
+        let mut statement = conn.prepare(&format!(
+            "SELECT {GAME_COLUMNS} FROM games g LEFT JOIN results r USING (session_id)
+             WHERE g.defender = ?1 OR g.attacker = ?1
+             ORDER BY g.started_ledger DESC, g.session_id DESC LIMIT ?2 OFFSET ?3"
+        ))?;
+        let items = statement
+            .query_map(params![player, page.limit(), page.offset], game_view)?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(Paged {
+            items,
+            limit: page.limit(),
+            offset: page.offset,
+            total,
+        })
+    }
+
+    /// Players ranked by wins in finished games
+    pub fn leaderboard(&self, page: Page) -> Result<Paged<LeaderboardEntry>> {
+        let conn = self.conn();
+        let total = conn.query_row(
+            &format!("{PLAYER_RESULTS} SELECT COUNT(DISTINCT player) FROM player_results"),
+            [],
+            |row| row.get(0),
+        )?;
+        let mut statement = conn.prepare(&format!(
+            "{PLAYER_RESULTS}
+             SELECT player, COUNT(*) AS games, SUM(won) AS wins FROM player_results
+             GROUP BY player ORDER BY wins DESC, games ASC, player LIMIT ?1 OFFSET ?2"
+        ))?;
+        let items = statement
+            .query_map(params![page.limit(), page.offset], |row| {
+                let (games, wins): (u32, u32) = (row.get(1)?, row.get(2)?);
+                Ok(LeaderboardEntry {
+                    player: row.get(0)?,
+                    games,
+                    wins,
+                    win_rate: f64::from(wins) / f64::from(games.max(1)),
+                })
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(Paged {
+            items,
+            limit: page.limit(),
+            offset: page.offset,
+            total,
+        })
+    }
+}
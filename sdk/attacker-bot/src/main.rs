@@ -0,0 +1,216 @@
+//! # Attacker bot
+//!
+//! Plays the attacker's side of live games with an [`AttackerStrategy`]:
+//! for each session it picks a cell, calls `submit_move` and waits for the
+//! defender's proven answer before picking the next one. Several sessions
+//! are played in parallel, which doubles as a load test of the contract and
+//! of the defender side (e.g. `defender-bot`).
+
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+use stellar_xdr::curr::ScAddress;
+use trap_grid_sdk::rpc::{RpcClient, DEFAULT_RPC_URL};
+use trap_grid_sdk::strategy::{Rng, StrategyKind};
+use trap_grid_sdk::watcher::{GameHandler, GameState, GameWatcher};
+use trap_grid_sdk::{AttackerStrategy, GameClient, Keypair, Move};
+
+#[derive(Parser)]
+#[command(
+    name = "attacker-bot",
+    version,
+    about = "Plays trap-grid games as the attacker"
+)]
+struct Cli {
+    /// Trap-grid contract id
+    #[arg(long, env = "TRAP_GRID_CONTRACT")]
+    contract_id: String,
+
+    /// Soroban RPC endpoint
+    #[arg(long, env = "STELLAR_RPC_URL", default_value = DEFAULT_RPC_URL)]
+    rpc_url: String,
+
+    /// Attacker's secret key (S...)
+    #[arg(long, env = "ATTACKER_SECRET", hide_env_values = true)]
+    secret: String,
+
+    /// Sessions to play (repeatable); each is played in its own thread
+    #[arg(long, required = true)]
+    session: Vec<u32>,
+
+    /// How the next cell is chosen: random, hunt-target or density
+    #[arg(long, default_value_t = StrategyKind::Density)]
+    strategy: StrategyKind,
+
+    /// Seed for reproducible choices (offset by the session id); random by default
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Number of traps the density strategy assumes the defender placed
+    #[arg(long, default_value_t = 10)]
+    traps: u32,
+
+    /// Stop after this many moves per session instead of playing until the game ends
+    #[arg(long)]
+    moves: Option<u32>,
+
+    /// Seconds to wait for the defender's answer to a move
+    #[arg(long, default_value_t = 300)]
+    answer_timeout: u64,
+
+    /// Polling interval in seconds
+    #[arg(long, default_value_t = 2)]
+    interval: u64,
+}
+
+/// Logs each answered move
+struct Log;
+
+impl GameHandler for Log {
+    fn on_move(&mut self, state: &GameState, recorded: &Move) {
+        println!(
+            "session {}: ({}, {}) {} - {} hits, {} misses",
+            state.session_id,
+            recorded.x,
+            recorded.y,
+            if recorded.is_hit { "HIT" } else { "MISS" },
+            state.hits,
+            state.misses
+        );
+    }
+}
+
+struct Player<'a> {
+    cli: &'a Cli,
+    rpc: &'a RpcClient,
+    game: &'a GameClient<'a>,
+    attacker: &'a Keypair,
+    /// Held while submitting so the account's transactions do not race
+    submit_lock: Mutex<()>,
+}
+
+impl Player<'_> {
+    fn play(
+        &self,
+        session_id: u32,
+        mut strategy: Box<dyn AttackerStrategy + Send>,
+    ) -> Result<GameState> {
+        let mut watcher =
+            GameWatcher::new(self.rpc, self.game.contract().clone(), session_id, None)?;
+        let address = self.attacker.address();
+        if watcher.state().attacker.as_ref() != Some(&address) {
+            bail!("session {session_id}: {address} is not the attacker");
+        }
+
+        let timeout = Duration::from_secs(self.cli.answer_timeout);
+        let mut awaiting = watcher
+            .state()
+            .pending
+            .map(|p| ((p.x, p.y), Instant::now()));
+        let mut submitted = 0;
+        loop {
+            watcher.poll(&mut Log)?;
+            let state = watcher.state();
+            if state.ended {
+                break;
+            }
+            if let Some(((x, y), since)) = awaiting {
+                if state.is_played(x, y) {
+                    awaiting = None;
+                } else if since.elapsed() > timeout {
+                    bail!("session {session_id}: no answer to ({x}, {y}) within {timeout:?}");
+                }
+            }
+            if awaiting.is_none() {
+                if self.cli.moves.is_some_and(|limit| submitted >= limit) {
+                    break;
+                }
+                let Some((x, y)) = strategy.next_cell(&state.moves) else {
+                    break;
+                };
+                {
+                    let _guard = self.submit_lock.lock().expect("submit lock poisoned");
+                    self.game
+                        .submit_move(self.attacker, session_id, x, y)
+                        .with_context(|| format!("session {session_id}: submit ({x}, {y})"))?;
+                }
+                awaiting = Some(((x, y), Instant::now()));
+                submitted += 1;
+            }
+            thread::sleep(Duration::from_secs(self.cli.interval));
+        }
+        Ok(watcher.state().clone())
+    }
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    let attacker = Keypair::from_secret(&cli.secret)?;
+    let contract: ScAddress = cli
+        .contract_id
+        .parse()
+        .with_context(|| format!("invalid contract id {}", cli.contract_id))?;
+    let rpc = RpcClient::new(&cli.rpc_url);
+    let game = GameClient::new(&rpc, contract)?;
+    if let Some(warning) = game.check_interface()? {
+        eprintln!("warning: {warning}");
+    }
+    let player = Player {
+        cli: &cli,
+        rpc: &rpc,
+        game: &game,
+        attacker: &attacker,
+        submit_lock: Mutex::new(()),
+    };
+
+    let results = thread::scope(|scope| {
+        let handles = cli
+            .session
+            .iter()
+            .map(|&session_id| {
+                let rng = match cli.seed {
+                    Some(seed) => Ok(Rng::new(seed.wrapping_add(session_id.into()))),
+                    None => Rng::from_entropy(),
+                };
+                let player = &player;
+                let handle = scope
+                    .spawn(move || player.play(session_id, cli.strategy.build(rng?, cli.traps)));
+                (session_id, handle)
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .map(|(session_id, handle)| {
+                (session_id, handle.join().expect("player thread panicked"))
+            })
+            .collect::<Vec<_>>()
+    });
+
+    let mut failed = 0;
+    for (session_id, result) in results {
+        match result {
+            Ok(state) => println!(
+                "session {session_id}: {} hits in {} moves{}",
+                state.hits,
+                state.moves.len(),
+                match &state.winner {
+                    Some(winner) if *winner == attacker.address() => ", attacker won",
+                    Some(_) => ", defender won",
+                    None => "",
+                }
+            ),
+            Err(err) => {
+                failed += 1;
+                eprintln!("session {session_id}: {err:#}");
+            }
+        }
+    }
+    if failed > 0 {
+        bail!("{failed} session(s) failed");
+    }
+    Ok(())
+}
@@ -0,0 +1,194 @@
+//! # Bench
+//!
+//! Measures what a move costs before it is deployed:
+//! - proving time (witness and proof) and proof size for every circuit
+//! - CPU instructions, memory, ledger I/O and fee of `verify_proof`,
+//!   `start_game`, `submit_move`, `make_move` and `end_game`, from the Soroban host's
+//!   metering of the release wasm
+//!
+//! The report is printed, optionally written as JSON, and compared against a
+//! baseline report so cost regressions fail the run.
+
+mod onchain;
+mod proving;
+mod report;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+use trap_grid_sdk::prover::{self, Circuit};
+
+use crate::onchain::Wasms;
+use crate::proving::{run_in, Bench};
+use crate::report::Report;
+
+/// Release wasm target used for every contract
+const WASM_TARGET: &str = "wasm32v1-none";
+
+#[derive(Parser)]
+#[command(
+    name = "bench",
+    version,
+    about = "Measures trap-grid proving time, proof sizes and on-chain costs"
+)]
+struct Cli {
+    /// Repository root (containing contracts/ and circuits/)
+    #[arg(long, default_value = "..")]
+    repo_root: PathBuf,
+
+    /// Proofs generated per circuit
+    #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u32).range(1..))]
+    runs: u32,
+
+    /// Only benchmark these circuits (repeatable); the contract costs need
+    /// position-movement and grid-setup
+    #[arg(long)]
+    circuit: Vec<Circuit>,
+
+    /// Skip the contract costs
+    #[arg(long)]
+    no_contract: bool,
+
+    /// Reuse existing circuit and contract build artifacts
+    #[arg(long)]
+    skip_build: bool,
+
+    /// bb.js entrypoint (relative paths are resolved against each circuit directory)
+    #[arg(long, default_value = prover::DEFAULT_BBJS)]
+    bbjs: PathBuf,
+
+    /// Write the report as JSON
+    #[arg(long)]
+    json: Option<PathBuf>,
+
+    /// Fail if proof sizes or contract costs exceed this earlier JSON report
+    #[arg(long)]
+    baseline: Option<PathBuf>,
+
+    /// Percentage a metric may grow over the baseline
+    #[arg(long, default_value_t = 5.0)]
+    tolerance: f64,
+}
+
+fn read(path: &Path) -> Result<Vec<u8>> {
+    fs::read(path).with_context(|| {
+        format!(
+            "failed to read {}; run without --skip-build",
+            path.display()
+        )
+    })
+}
+
+fn build_contracts(repo_root: &Path) -> Result<()> {
+    let cargo_build = ["build", "--release", "--target", WASM_TARGET];
+    println!("==> Building the UltraHonk verifier contract");
+    run_in(
+        &repo_root.join("circuits/position-movement/rs-soroban-ultrahonk"),
+        "cargo",
+        &cargo_build,
+    )?;
+    println!("==> Building the game contracts");
+    run_in(&repo_root.join("contracts"), "cargo", &cargo_build)
+}
+
+fn load_wasms(repo_root: &Path) -> Result<Wasms> {
+    let contracts_release = repo_root
+        .join("contracts/target")
+        .join(WASM_TARGET)
+        .join("release");
+    let verifier_release = repo_root
+        .join("circuits/position-movement/rs-soroban-ultrahonk/target")
+        .join(WASM_TARGET)
+        .join("release");
+    Ok(Wasms {
+        game_hub: read(&contracts_release.join("mock_game_hub.wasm"))?,
+        verifier: read(&verifier_release.join("rs_soroban_ultrahonk.wasm"))?,
+        trap_grid: read(&contracts_release.join("trap_grid.wasm"))?,
+    })
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let repo_root = &cli
+        .repo_root
+        .canonicalize()
+        .with_context(|| format!("repository root {} not found", cli.repo_root.display()))?;
+    let circuits = if cli.circuit.is_empty() {
+        Circuit::ALL.to_vec()
+    } else {
+        cli.circuit.clone()
+    };
+    let verified = [Circuit::PositionMovement, Circuit::GridSetup];
+    if !cli.no_contract && !verified.iter().all(|circuit| circuits.contains(circuit)) {
+        bail!(
+            "contract costs need position-movement and grid-setup proofs; \
+             add --circuit position-movement --circuit grid-setup or pass --no-contract"
+        );
+    }
+
+    let mut report = Report::default();
+    let mut move_proof = None;
+    let mut setup_proof = None;
+    for circuit in circuits {
+        let dir = repo_root.join("circuits").join(circuit.id());
+        let bench = Bench {
+            circuit,
+            bbjs: if cli.bbjs.is_absolute() {
+                cli.bbjs.clone()
+            } else {
+                dir.join(&cli.bbjs)
+            },
+            dir,
+        };
+        if !cli.skip_build {
+            println!("==> Building the {} circuit", circuit.id());
+            bench.build()?;
+        }
+        println!("==> Proving {} ({} runs)", circuit.id(), cli.runs);
+        let (proving, proof) = bench.run(cli.runs)?;
+        report.proving.push(proving);
+        match circuit {
+            Circuit::PositionMovement => {
+                move_proof = Some((read(&bench.dir.join("target/vk"))?, proof))
+            }
+            Circuit::GridSetup => setup_proof = Some((read(&bench.dir.join("target/vk"))?, proof)),
+            _ => {}
+        }
+    }
+
+    if let (false, Some(move_proof), Some(setup_proof)) =
+        (cli.no_contract, &move_proof, &setup_proof)
+    {
+        if !cli.skip_build {
+            build_contracts(repo_root)?;
+        }
+        println!("==> Metering contract calls");
+        report.contract = onchain::run(&load_wasms(repo_root)?, move_proof, setup_proof)?;
+    }
+
+    println!();
+    report.print();
+    if let Some(path) = &cli.json {
+        fs::write(path, serde_json::to_string_pretty(&report)?)
+            .with_context(|| format!("failed to write {}", path.display()))?;
+    }
+    if let Some(path) = &cli.baseline {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let baseline: Report = serde_json::from_str(&text)
+            .with_context(|| format!("invalid baseline report {}", path.display()))?;
+        let regressions = report.regressions(&baseline, cli.tolerance);
+        if !regressions.is_empty() {
+            bail!(
+                "costs regressed by more than {}% over {}:\n  {}",
+                cli.tolerance,
+                path.display(),
+                regressions.join("\n  ")
+            );
+        }
+        println!("\nNo regressions over {}", path.display());
+    }
+    Ok(())
+}
@@ -0,0 +1,142 @@
+//! Contract costs in a Soroban test environment
+//!
+//! The release wasm of the mock Game Hub, the UltraHonk verifier (once for
+//! moves and once for grid setups) and trap-grid are registered in one `Env`, so VM instantiation and wasm
+//! execution are metered as on the network. Each call is measured in
+//! isolation with the host's invocation metering.
+
+use anyhow::{anyhow, bail, Result};
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::xdr::{AccountId, PublicKey, ScAddress, Uint256};
+use soroban_sdk::{Address, Bytes, BytesN, Env, IntoVal, Map, Symbol, Val};
+use trap_grid_encoding::{GridSetup, PositionMovement};
+
+use crate::proving::Proof;
+use crate::report::ContractCost;
+
+const SESSION_ID: u32 = 1;
+const POINTS: i128 = 100;
+
+pub struct Wasms {
+    pub game_hub: Vec<u8>,
+    pub verifier: Vec<u8>,
+    pub trap_grid: Vec<u8>,
+}
+
+/// Invoke `function` and record what it cost
+fn measure(
+    env: &Env,
+    contract: &Address,
+    function: &str,
+    args: soroban_sdk::Vec<Val>,
+) -> Result<ContractCost> {
+    let result = env.try_invoke_contract::<Val, soroban_sdk::Error>(
+        contract,
+        &Symbol::new(env, function),
+        args,
+    );
+    if let Err(err) = result {
+        bail!("{function} failed: {err:?}");
+    }
+    let resources = env.cost_estimate().resources();
+    Ok(ContractCost {
+        call: function.to_string(),
+        instructions: resources.instructions,
+        mem_bytes: resources.mem_bytes,
+        read_entries: resources.disk_read_entries + resources.memory_read_entries,
+        write_entries: resources.write_entries,
+        write_bytes: resources.write_bytes,
+        fee: env.cost_estimate().fee().total,
+    })
+}
+
+/// Cost of `verify_proof` on its own and of a game started with the
+/// grid-setup proof `setup` and played with the position-movement proof
+/// `moves`, each paired with the verification key it was made against
+pub fn run(
+    wasms: &Wasms,
+    (vk, proof): &(Vec<u8>, Proof),
+    (setup_vk, setup): &(Vec<u8>, Proof),
+) -> Result<Vec<ContractCost>> {
+    let inputs = PositionMovement::decode(&proof.public_inputs)
+        .map_err(|e| anyhow!("invalid position-movement public inputs: {e}"))?;
+    let setup_inputs = GridSetup::decode(&setup.public_inputs)
+        .map_err(|e| anyhow!("invalid grid-setup public inputs: {e}"))?;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    // Costs are reported rather than enforced; the verifier is heavy
+    env.cost_estimate().budget().reset_unlimited();
+    env.cost_estimate().disable_resource_limits();
+
+    let hub = env.register(wasms.game_hub.as_slice(), ());
+    let verifier = env.register(wasms.verifier.as_slice(), (Bytes::from_slice(&env, vk),));
+    let setup_verifier = env.register(
+        wasms.verifier.as_slice(),
+        (Bytes::from_slice(&env, setup_vk),),
+    );
+    let admin = Address::generate(&env);
+    let trap_grid = env.register(
+        wasms.trap_grid.as_slice(),
+        (admin, hub, verifier.clone(), setup_verifier),
+    );
+    // The setup proof starts games for its defender only, and the move proof
+    // opens that defender's commitment
+    let defender = ScAddress::Account(AccountId(PublicKey::PublicKeyTypeEd25519(Uint256(
+        setup_inputs.defender,
+    ))));
+    let defender = Address::from_str(&env, &defender.to_string());
+    if inputs.grid_commitment != setup_inputs.grid_commitment {
+        bail!("the position-movement proof does not open the grid-setup proof's commitment");
+    }
+    let attacker = Address::generate(&env);
+    let public_inputs = Bytes::from_slice(&env, &proof.public_inputs);
+    let proof_bytes = Bytes::from_slice(&env, &proof.proof);
+    // The contract's `GridSetup` struct, as the map its fields encode to
+    let mut grid_setup = Map::<Symbol, Val>::new(&env);
+    grid_setup.set(
+        Symbol::new(&env, "grid_commitment"),
+        BytesN::from_array(&env, &setup_inputs.grid_commitment).into_val(&env),
+    );
+    grid_setup.set(
+        Symbol::new(&env, "proof"),
+        Bytes::from_slice(&env, &setup.proof).into_val(&env),
+    );
+
+    // In order: each game call needs the state the previous one left
+    let calls = [
+        (
+            &verifier,
+            "verify_proof",
+            (public_inputs.clone(), proof_bytes.clone()).into_val(&env),
+        ),
+        (
+            &trap_grid,
+            "start_game",
+            (SESSION_ID, defender, attacker, POINTS, POINTS, grid_setup).into_val(&env),
+        ),
+        (
+            &trap_grid,
+            "submit_move",
+            (SESSION_ID, inputs.move_x, inputs.move_y).into_val(&env),
+        ),
+        (
+            &trap_grid,
+            "make_move",
+            (
+                SESSION_ID,
+                inputs.move_x,
+                inputs.move_y,
+                inputs.is_hit,
+                proof_bytes,
+                public_inputs,
+            )
+                .into_val(&env),
+        ),
+        (&trap_grid, "end_game", (SESSION_ID,).into_val(&env)),
+    ];
+    calls
+        .into_iter()
+        .map(|(contract, function, args)| measure(&env, contract, function, args))
+        .collect()
+}
@@ -0,0 +1,127 @@
+//! Proving time and proof size per circuit
+//!
+//! Each circuit is proved on its checked-in `Prover.toml`: `nargo execute`
+//! for the witness, then bb.js (UltraHonk, keccak transcript) for the proof,
+//! timed separately over several runs.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+use trap_grid_sdk::prover::Circuit;
+
+use crate::report::{ProvingReport, Timing};
+
+/// A proof as bb.js wrote it
+pub struct Proof {
+    pub public_inputs: Vec<u8>,
+    pub proof: Vec<u8>,
+}
+
+pub fn run_in(dir: &Path, program: &str, args: &[&str]) -> Result<()> {
+    let output = Command::new(program)
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .with_context(|| format!("failed to run {program}; is it installed and on PATH?"))?;
+    if !output.status.success() {
+        bail!(
+            "{program} {} failed in {}: {}",
+            args.join(" "),
+            dir.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+fn timed(dir: &Path, program: &str, args: &[&str]) -> Result<Duration> {
+    let started = Instant::now();
+    run_in(dir, program, args)?;
+    Ok(started.elapsed())
+}
+
+pub struct Bench {
+    pub circuit: Circuit,
+    pub dir: PathBuf,
+    pub bbjs: PathBuf,
+}
+
+impl Bench {
+    /// Compile the circuit; for the circuits the contract verifies
+    /// (position-movement and grid-setup) also write the verification key a
+    /// verifier contract is deployed with
+    pub fn build(&self) -> Result<()> {
+        if !self.dir.join("node_modules").is_dir() {
+            run_in(&self.dir, "npm", &["install"])?;
+        }
+        run_in(&self.dir, "nargo", &["compile"])?;
+        if matches!(self.circuit, Circuit::PositionMovement | Circuit::GridSetup) {
+            let bytecode = format!("target/{}.json", self.circuit.package());
+            run_in(
+                &self.dir,
+                "node",
+                &[
+                    &self.bbjs.to_string_lossy(),
+                    "write_vk_ultra_keccak_honk",
+                    "-b",
+                    &bytecode,
+                    "-o",
+                    "target/vk",
+                ],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Prove `runs` times and return the timings with the last proof
+    pub fn run(&self, runs: u32) -> Result<(ProvingReport, Proof)> {
+        let target = self.dir.join("target");
+        let bytecode = target.join(format!("{}.json", self.circuit.package()));
+        let witness = target.join(format!("{}.gz", self.circuit.package()));
+        let output = target.join("proof.with_public_inputs");
+
+        let mut witness_times = Vec::new();
+        let mut prove_times = Vec::new();
+        for _ in 0..runs {
+            witness_times.push(timed(&self.dir, "nargo", &["execute"])?);
+            prove_times.push(timed(
+                &self.dir,
+                "node",
+                &[
+                    &self.bbjs.to_string_lossy(),
+                    "prove_ultra_keccak_honk",
+                    "-b",
+                    &bytecode.to_string_lossy(),
+                    "-w",
+                    &witness.to_string_lossy(),
+                    "-o",
+                    &output.to_string_lossy(),
+                ],
+            )?);
+        }
+
+        let bytes =
+            fs::read(&output).with_context(|| format!("failed to read {}", output.display()))?;
+        let len = self.circuit.public_inputs_len();
+        if bytes.len() <= len {
+            bail!("proof output is only {} bytes", bytes.len());
+        }
+        let (public_inputs, proof) = bytes.split_at(len);
+        let report = ProvingReport {
+            circuit: self.circuit,
+            runs,
+            witness_ms: Timing::of(&witness_times),
+            prove_ms: Timing::of(&prove_times),
+            proof_bytes: proof.len(),
+            public_inputs_bytes: public_inputs.len(),
+        };
+        let proof = Proof {
+            public_inputs: public_inputs.to_vec(),
+            proof: proof.to_vec(),
+        };
+        Ok((report, proof))
+    }
+}
@@ -0,0 +1,197 @@
+//! Benchmark report and comparison against a baseline
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use trap_grid_sdk::prover::Circuit;
+
+/// Milliseconds over several runs
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct Timing {
+    pub min: u64,
+    pub mean: u64,
+    pub max: u64,
+}
+
+impl Timing {
+    pub fn of(durations: &[Duration]) -> Self {
+        let ms: Vec<u64> = durations.iter().map(|d| d.as_millis() as u64).collect();
+        if ms.is_empty() {
+            return Self::default();
+        }
+        Self {
+            min: ms.iter().copied().min().unwrap_or_default(),
+            mean: ms.iter().sum::<u64>() / ms.len() as u64,
+            max: ms.iter().copied().max().unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ProvingReport {
+    pub circuit: Circuit,
+    pub runs: u32,
+    /// `nargo execute`
+    pub witness_ms: Timing,
+    /// bb.js `prove_ultra_keccak_honk`
+    pub prove_ms: Timing,
+    pub proof_bytes: usize,
+    pub public_inputs_bytes: usize,
+}
+
+/// Resources of one contract invocation, as metered by the Soroban host
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ContractCost {
+    pub call: String,
+    pub instructions: i64,
+    pub mem_bytes: i64,
+    pub read_entries: u32,
+    pub write_entries: u32,
+    pub write_bytes: u32,
+    /// Estimated resource fee in stroops, at mainnet rates
+    pub fee: i64,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Report {
+    pub proving: Vec<ProvingReport>,
+    pub contract: Vec<ContractCost>,
+}
+
+impl Report {
+    pub fn print(&self) {
+        if !self.proving.is_empty() {
+            println!(
+                "{:<18} {:>6} {:>22} {:>22} {:>8} {:>8}",
+                "circuit",
+                "runs",
+                "witness ms min/avg/max",
+                "prove ms min/avg/max",
+                "proof",
+                "inputs"
+            );
+            for p in &self.proving {
+                println!(
+                    "{:<18} {:>6} {:>22} {:>22} {:>8} {:>8}",
+                    p.circuit.id(),
+                    p.runs,
+                    format!(
+                        "{}/{}/{}",
+                        p.witness_ms.min, p.witness_ms.mean, p.witness_ms.max
+                    ),
+                    format!("{}/{}/{}", p.prove_ms.min, p.prove_ms.mean, p.prove_ms.max),
+                    p.proof_bytes,
+                    p.public_inputs_bytes
+                );
+            }
+        }
+        if !self.contract.is_empty() {
+            if !self.proving.is_empty() {
+                println!();
+            }
+            println!(
+                "{:<14} {:>14} {:>12} {:>6} {:>6} {:>8} {:>10}",
+                "call", "instructions", "memory", "reads", "writes", "written", "fee"
+            );
+            for c in &self.contract {
+                println!(
+                    "{:<14} {:>14} {:>12} {:>6} {:>6} {:>8} {:>10}",
+                    c.call,
+                    c.instructions,
+                    c.mem_bytes,
+                    c.read_entries,
+                    c.write_entries,
+                    c.write_bytes,
+                    c.fee
+                );
+            }
+        }
+    }
+
+    /// Metrics that grew by more than `tolerance` percent over `baseline`
+    ///
+    /// Timings depend on the machine and are not compared; proof sizes and
+    /// metered contract costs are deterministic.
+    pub fn regressions(&self, baseline: &Report, tolerance: f64) -> Vec<String> {
+        let mut found = Vec::new();
+        let mut check = |what: String, old: i64, new: i64| {
+            if new as f64 > old as f64 * (1.0 + tolerance / 100.0) {
+                found.push(format!("{what}: {old} -> {new}"));
+            }
+        };
+        for p in &self.proving {
+            if let Some(old) = baseline.proving.iter().find(|o| o.circuit == p.circuit) {
+                check(
+                    format!("{} proof bytes", p.circuit.id()),
+                    old.proof_bytes as i64,
+                    p.proof_bytes as i64,
+                );
+            }
+        }
+        for c in &self.contract {
+            if let Some(old) = baseline.contract.iter().find(|o| o.call == c.call) {
+                check(
+                    format!("{} instructions", c.call),
+                    old.instructions,
+                    c.instructions,
+                );
+                check(format!("{} memory", c.call), old.mem_bytes, c.mem_bytes);
+                check(
+                    format!("{} write bytes", c.call),
+                    old.write_bytes.into(),
+                    c.write_bytes.into(),
+                );
+                check(format!("{} fee", c.call), old.fee, c.fee);
+            }
+        }
+        found
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn cost(call: &str, instructions: i64) -> ContractCost {
+        ContractCost {
+            call: call.to_string(),
+            instructions,
+            mem_bytes: 1000,
+            read_entries: 2,
+            write_entries: 1,
+            write_bytes: 100,
+            fee: 5000,
+        }
+    }
+
+    #[test]
+    fn test_regressions() {
+        let baseline = Report {
+            proving: Vec::new(),
+            contract: vec![cost("verify_proof", 100_000_000), cost("make_move", 1000)],
+        };
+        let current = Report {
+            proving: Vec::new(),
+            contract: vec![
+                cost("verify_proof", 104_000_000),
+                cost("make_move", 2000),
+                cost("end_game", 5000),
+            ],
+        };
+        assert_eq!(
+            current.regressions(&baseline, 5.0),
+            ["make_move instructions: 1000 -> 2000"]
+        );
+        assert_eq!(current.regressions(&baseline, 100.0), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_timing() {
+        let timing = Timing::of(&[
+            Duration::from_millis(30),
+            Duration::from_millis(10),
+            Duration::from_millis(20),
+        ]);
+        assert_eq!((timing.min, timing.mean, timing.max), (10, 20, 30));
+    }
+}
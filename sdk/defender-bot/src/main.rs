@@ -0,0 +1,256 @@
+//! # Defender bot
+//!
+//! Answers attacker moves on the defender's behalf: follows the trap-grid
+//! contract's `move_submitted` events and, for every session in which its
+//! account is the defender, proves the hit/miss answer against its trap
+//! layout and submits it with `make_move`.
+
+mod worker;
+
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::{mpsc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
+use clap::Parser;
+use stellar_xdr::curr::{ScAddress, ScSymbol, ScVal};
+use trap_grid_sdk::client::{fetch_game, fetch_pending_move};
+use trap_grid_sdk::commitment::layout_salt;
+use trap_grid_sdk::prover::{self, Prover};
+use trap_grid_sdk::rpc::{EventsStart, RpcClient, DEFAULT_RPC_URL};
+use trap_grid_sdk::{player_topics, GameClient, GameEvent, Keypair, ProofCache, TrapLayout};
+
+use crate::worker::{Job, Pool, RetryPolicy};
+
+#[derive(Parser)]
+#[command(
+    name = "defender-bot",
+    version,
+    about = "Answers trap-grid moves with hit/miss proofs"
+)]
+struct Cli {
+    /// Trap-grid contract id
+    #[arg(long, env = "TRAP_GRID_CONTRACT")]
+    contract_id: String,
+
+    /// Soroban RPC endpoint
+    #[arg(long, env = "STELLAR_RPC_URL", default_value = DEFAULT_RPC_URL)]
+    rpc_url: String,
+
+    /// Defender's secret key (S...)
+    #[arg(long, env = "DEFENDER_SECRET", hide_env_values = true)]
+    secret: String,
+
+    /// Defender's trap layout (with the salt it was committed with)
+    #[arg(long)]
+    layout: PathBuf,
+
+    /// Only answer these sessions (repeatable); all of the defender's sessions by default
+    #[arg(long)]
+    session: Vec<u32>,
+
+    /// First ledger to read events from; defaults to the latest ledger
+    #[arg(long)]
+    from_ledger: Option<u32>,
+
+    /// Position-movement circuit directory
+    #[arg(long, default_value = "../circuits/position-movement")]
+    circuit_dir: PathBuf,
+
+    /// bb.js entrypoint (relative paths are resolved against --circuit-dir)
+    #[arg(long, default_value = prover::DEFAULT_BBJS)]
+    bbjs: PathBuf,
+
+    /// Proof cache directory; defaults to `$TRAP_GRID_HOME/proofs` or the platform cache directory
+    #[arg(long)]
+    proof_cache: Option<PathBuf>,
+
+    /// Run the prover for every move instead of reusing cached proofs
+    #[arg(long, conflicts_with = "proof_cache")]
+    no_proof_cache: bool,
+
+    /// Directory for the workers' copies of the circuit
+    #[arg(long)]
+    work_dir: Option<PathBuf>,
+
+    /// Number of proofs generated in parallel
+    #[arg(long, default_value_t = 2, value_parser = clap::value_parser!(u32).range(1..))]
+    workers: u32,
+
+    /// Retries for a failed proof or submission
+    #[arg(long, default_value_t = 3)]
+    retries: u32,
+
+    /// Seconds before the first retry, doubled after every failure
+    #[arg(long, default_value_t = 5)]
+    retry_delay: u64,
+
+    /// Polling interval in seconds
+    #[arg(long, default_value_t = 2)]
+    interval: u64,
+}
+
+/// Which sessions the bot answers, and which have been checked already
+struct Sessions<'a> {
+    rpc: &'a RpcClient,
+    contract: &'a ScAddress,
+    defender: String,
+    only: HashSet<u32>,
+    ours: HashMap<u32, bool>,
+}
+
+impl Sessions<'_> {
+    /// Whether the bot's account defends `session_id` (looked up once per session)
+    fn is_ours(&mut self, session_id: u32) -> Result<bool> {
+        if !self.only.is_empty() && !self.only.contains(&session_id) {
+            return Ok(false);
+        }
+        if let Some(ours) = self.ours.get(&session_id) {
+            return Ok(*ours);
+        }
+        let ours = fetch_game(self.rpc, self.contract, session_id)?
+            .is_some_and(|game| game.defender == self.defender);
+        self.ours.insert(session_id, ours);
+        Ok(ours)
+    }
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    let layout = TrapLayout::load(&cli.layout)?;
+    // Moves open the grid commitment, so they need the salt it was made with
+    if layout.salt.is_none() {
+        bail!(
+            "{} has no salt; the bot needs the layout its games were started with",
+            cli.layout.display()
+        );
+    }
+    let salt = layout_salt(&layout)?;
+    let defender = Keypair::from_secret(&cli.secret)?;
+    let contract: ScAddress = cli
+        .contract_id
+        .parse()
+        .with_context(|| format!("invalid contract id {}", cli.contract_id))?;
+    let rpc = RpcClient::new(&cli.rpc_url);
+    let game = GameClient::new(&rpc, contract.clone())?;
+    if let Some(warning) = game.check_interface()? {
+        eprintln!("warning: {warning}");
+    }
+
+    // Every worker proves in its own copy of the circuit, sharing bb.js
+    let prover = Prover::new(&cli.circuit_dir, &cli.bbjs)?;
+    let bbjs = prover.bbjs().to_path_buf();
+    let work_dir = cli
+        .work_dir
+        .clone()
+        .unwrap_or_else(|| std::env::temp_dir().join("defender-bot"));
+    let cache = match (&cli.proof_cache, cli.no_proof_cache) {
+        (_, true) => None,
+        (Some(dir), false) => Some(ProofCache::at(dir)),
+        (None, false) => Some(ProofCache::open()?),
+    };
+    let provers = (0..cli.workers)
+        .map(|i| {
+            let dir = work_dir.join(format!("worker-{i}"));
+            prover::copy_circuit(prover.circuit_dir(), &dir)?;
+            let prover = Prover::new(&dir, &bbjs)?;
+            match &cache {
+                Some(cache) => prover.with_cache(cache.clone()),
+                None => Ok(prover),
+            }
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let pool = Pool {
+        game: &game,
+        defender: &defender,
+        layout: &layout,
+        salt,
+        retry: RetryPolicy {
+            retries: cli.retries,
+            delay: Duration::from_secs(cli.retry_delay),
+        },
+        submit_lock: Mutex::new(()),
+    };
+    let mut sessions = Sessions {
+        rpc: &rpc,
+        contract: &contract,
+        defender: defender.address(),
+        only: cli.session.iter().copied().collect(),
+        ours: HashMap::new(),
+    };
+
+    println!(
+        "Defending as {} with {} worker(s)",
+        sessions.defender, cli.workers
+    );
+
+    let (sender, receiver) = mpsc::channel::<Job>();
+    let receiver = Mutex::new(receiver);
+    thread::scope(|scope| {
+        // Owned by the polling loop so the workers stop if it fails
+        let sender = sender;
+        for prover in &provers {
+            let (pool, receiver) = (&pool, &receiver);
+            scope.spawn(move || pool.work(prover, receiver));
+        }
+
+        // Moves submitted before the bot started
+        for session_id in &cli.session {
+            if sessions.is_ours(*session_id)? {
+                if let Some(pending) = fetch_pending_move(&rpc, &contract, *session_id)? {
+                    sender.send(Job {
+                        session_id: *session_id,
+                        x: pending.x,
+                        y: pending.y,
+                    })?;
+                }
+            }
+        }
+
+        // Only moves in games the bot's account defends
+        let name =
+            ScSymbol::try_from("move_submitted").map_err(|_| anyhow!("invalid event name"))?;
+        let [mut topics, _] = player_topics(&defender.address())?;
+        topics[0] = Some(ScVal::Symbol(name));
+        let mut start_ledger = match cli.from_ledger {
+            Some(ledger) => ledger,
+            None => rpc.get_latest_ledger()?,
+        };
+        let mut cursor: Option<String> = None;
+        loop {
+            let start = match &cursor {
+                Some(cursor) => EventsStart::Cursor(cursor),
+                None => EventsStart::Ledger(start_ledger),
+            };
+            match rpc.get_events(&contract, &topics, start) {
+                Ok(result) => {
+                    for info in &result.events {
+                        let (topics, value) = info.decode()?;
+                        let Some(GameEvent::MoveSubmitted(submitted)) =
+                            GameEvent::decode(&topics, &value)?
+                        else {
+                            continue;
+                        };
+                        if sessions.is_ours(submitted.session_id)? {
+                            sender.send(Job {
+                                session_id: submitted.session_id,
+                                x: submitted.x,
+                                y: submitted.y,
+                            })?;
+                        }
+                    }
+                    match result.cursor {
+                        Some(next) => cursor = Some(next),
+                        None => start_ledger = result.latest_ledger,
+                    }
+                }
+                Err(err) => eprintln!("getEvents failed: {err:#}"),
+            }
+            thread::sleep(Duration::from_secs(cli.interval));
+        }
+    })
+}
@@ -0,0 +1,138 @@
+//! Proving workers
+//!
+//! Each worker proves in its own copy of the circuit (see
+//! [`copy_circuit`](trap_grid_sdk::prover::copy_circuit)). Proofs are made in
+//! parallel; submissions share one lock so only one transaction from the
+//! defender's account is in flight at a time.
+
+use std::sync::mpsc::Receiver;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use anyhow::Result;
+use trap_grid_sdk::{ContractError, GameClient, Keypair, Prover, TrapLayout};
+
+/// A submitted move waiting for the defender's answer
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Job {
+    pub session_id: u32,
+    pub x: u32,
+    pub y: u32,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RetryPolicy {
+    /// Attempts after the first one
+    pub retries: u32,
+    /// Delay before the first retry, doubled after every failure
+    pub delay: Duration,
+}
+
+impl RetryPolicy {
+    /// Run `op` until it succeeds or the retries run out
+    ///
+    /// Contract errors are returned at once: the contract would reject the
+    /// same call again.
+    pub fn run<T>(&self, what: &str, mut op: impl FnMut() -> Result<T>) -> Result<T> {
+        let mut delay = self.delay;
+        let mut attempt = 0;
+        loop {
+            match op() {
+                Ok(value) => return Ok(value),
+                Err(err)
+                    if attempt < self.retries && err.downcast_ref::<ContractError>().is_none() =>
+                {
+                    attempt += 1;
+                    eprintln!(
+                        "{what} failed ({err:#}); retry {attempt}/{} in {}s",
+                        self.retries,
+                        delay.as_secs()
+                    );
+                    thread::sleep(delay);
+                    delay *= 2;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
+
+/// Everything the workers share
+pub struct Pool<'a> {
+    pub game: &'a GameClient<'a>,
+    pub defender: &'a Keypair,
+    pub layout: &'a TrapLayout,
+    /// The salt `layout` was committed with
+    pub salt: [u8; 32],
+    pub retry: RetryPolicy,
+    pub submit_lock: Mutex<()>,
+}
+
+impl Pool<'_> {
+    /// Answer jobs from `jobs` until the channel closes
+    pub fn work(&self, prover: &Prover, jobs: &Mutex<Receiver<Job>>) {
+        loop {
+            let job = match jobs.lock().expect("job queue poisoned").recv() {
+                Ok(job) => job,
+                Err(_) => return,
+            };
+            let label = format!("session {} ({}, {})", job.session_id, job.x, job.y);
+            match self.answer(prover, job, &label) {
+                Ok(is_hit) => println!("{label}: answered {}", if is_hit { "HIT" } else { "MISS" }),
+                Err(err) => eprintln!("{label}: giving up: {err:#}"),
+            }
+        }
+    }
+
+    fn answer(&self, prover: &Prover, job: Job, label: &str) -> Result<bool> {
+        let proven = self.retry.run(&format!("{label}: proving"), || {
+            prover.prove_move(
+                job.session_id,
+                self.layout,
+                &self.salt,
+                &self.defender.address(),
+                job.x,
+                job.y,
+            )
+        })?;
+        let recorded = self.retry.run(&format!("{label}: submitting"), || {
+            let _guard = self.submit_lock.lock().expect("submit lock poisoned");
+            self.game.respond_move(self.defender, &proven)
+        })?;
+        Ok(recorded.is_hit)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use anyhow::anyhow;
+
+    #[test]
+    fn test_retry_policy() {
+        let policy = RetryPolicy {
+            retries: 2,
+            delay: Duration::ZERO,
+        };
+
+        let mut calls = 0;
+        let result = policy.run("flaky", || {
+            calls += 1;
+            if calls < 3 {
+                Err(anyhow!("connection reset"))
+            } else {
+                Ok(calls)
+            }
+        });
+        assert_eq!(result.unwrap(), 3);
+
+        let mut calls = 0;
+        let result: Result<()> = policy.run("rejected", || {
+            calls += 1;
+            Err(anyhow!(ContractError::MoveAlreadyMade))
+        });
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
+}
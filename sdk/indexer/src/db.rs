@@ -0,0 +1,294 @@
+//! SQLite storage
+//!
+//! Each event is written in the same transaction as the cursor that follows
+//! it, so a restarted indexer resumes exactly where it stopped. Writes are
+//! idempotent as well, so re-reading a ledger range is harmless.
+
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+use trap_grid_sdk::rpc::EventInfo;
+use trap_grid_sdk::{GameEnded, GameEvent, GameStarted, MoveMade, MoveSubmitted};
+
+/// Bumped whenever `schema.sql` changes; the API checks it too
+const SCHEMA_VERSION: u32 = 1;
+
+const SCHEMA: &str = include_str!("schema.sql");
+
+/// Resume point of one contract's event stream
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Cursor {
+    /// `getEvents` paging cursor, once a page has been read
+    pub cursor: Option<String>,
+    pub start_ledger: u32,
+}
+
+pub struct Db {
+    conn: Connection,
+}
+
+impl Db {
+    pub fn open(path: &Path) -> Result<Self> {
+        let conn =
+            Connection::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+        Self::init(conn)
+    }
+
+    #[cfg(test)]
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        let version: u32 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        match version {
+            0 => {
+                conn.execute_batch(SCHEMA)?;
+                conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+            }
+            SCHEMA_VERSION => {}
+            other => bail!("database schema version {other} is newer than {SCHEMA_VERSION}"),
+        }
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        Ok(Self { conn })
+    }
+
+    pub fn cursor(&self, contract: &str) -> Result<Option<Cursor>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT cursor, start_ledger FROM cursors WHERE contract = ?1",
+                [contract],
+                |row| {
+                    Ok(Cursor {
+                        cursor: row.get(0)?,
+                        start_ledger: row.get(1)?,
+                    })
+                },
+            )
+            .optional()?)
+    }
+
+    /// Store one page of a contract's events and the cursor after it;
+    /// `decode` is false for contracts whose events are kept raw
+    pub fn write_page(
+        &mut self,
+        contract: &str,
+        events: &[EventInfo],
+        decode: bool,
+        next: &Cursor,
+    ) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        for info in events {
+            if decode {
+                let (topics, value) = info.decode()?;
+                if let Some(event) = GameEvent::decode(&topics, &value)? {
+                    apply(&tx, &event, info)?;
+                }
+            } else {
+                tx.execute(
+                    "INSERT OR IGNORE INTO hub_events
+                         (event_id, contract, ledger, closed_at, tx_hash, topics, value)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                    params![
+                        info.id,
+                        contract,
+                        info.ledger,
+                        info.ledger_closed_at,
+                        info.tx_hash,
+                        info.topic.join(","),
+                        info.value
+                    ],
+                )?;
+            }
+        }
+        tx.execute(
+            "INSERT INTO cursors (contract, cursor, start_ledger) VALUES (?1, ?2, ?3)
+             ON CONFLICT (contract) DO UPDATE
+                 SET cursor = excluded.cursor, start_ledger = excluded.start_ledger",
+            params![contract, next.cursor, next.start_ledger],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+/// Fold one trap-grid event into the tables
+fn apply(conn: &Connection, event: &GameEvent, info: &EventInfo) -> Result<()> {
+    match event {
+        GameEvent::GameStarted(GameStarted {
+            session_id,
+            defender,
+            attacker,
+        }) => {
+            conn.execute(
+                "INSERT OR IGNORE INTO games
+                     (session_id, defender, attacker, started_ledger, started_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    session_id,
+                    defender,
+                    attacker,
+                    info.ledger,
+                    info.ledger_closed_at
+                ],
+            )?;
+        }
+        GameEvent::MoveSubmitted(MoveSubmitted {
+            session_id, x, y, ..
+        }) => {
+            conn.execute(
+                "UPDATE games SET pending_x = ?2, pending_y = ?3 WHERE session_id = ?1",
+                params![session_id, x, y],
+            )?;
+        }
+        GameEvent::MoveMade(MoveMade {
+            session_id,
+            x,
+            y,
+            is_hit,
+            verified,
+            ..
+        }) => {
+            let inserted = conn.execute(
+                "INSERT OR IGNORE INTO moves
+                     (session_id, move_number, x, y, is_hit, verified, ledger, tx_hash)
+                 SELECT ?1, COUNT(*) + 1, ?2, ?3, ?4, ?5, ?6, ?7
+                 FROM moves WHERE session_id = ?1",
+                params![
+                    session_id,
+                    x,
+                    y,
+                    is_hit,
+                    verified,
+                    info.ledger,
+                    info.tx_hash
+                ],
+            )?;
+            if inserted > 0 {
+                conn.execute(
+                    "UPDATE games SET moves_made = moves_made + 1,
+                         hits = hits + ?2, misses = misses + ?3,
+                         pending_x = NULL, pending_y = NULL
+                     WHERE session_id = ?1",
+                    params![session_id, is_hit, !is_hit],
+                )?;
+            }
+        }
+        GameEvent::GameEnded(GameEnded {
+            session_id,
+            winner,
+            hits,
+            misses,
+            ..
+        }) => {
+            conn.execute(
+                "INSERT OR IGNORE INTO results
+                     (session_id, winner, attacker_won, hits, misses, ledger, ended_at)
+                 SELECT ?1, ?2, (SELECT attacker = ?2 FROM games WHERE session_id = ?1),
+                        ?3, ?4, ?5, ?6",
+                params![
+                    session_id,
+                    winner,
+                    hits,
+                    misses,
+                    info.ledger,
+                    info.ledger_closed_at
+                ],
+            )?;
+            conn.execute(
+                "UPDATE games SET pending_x = NULL, pending_y = NULL WHERE session_id = ?1",
+                [session_id],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use stellar_xdr::curr::{Limits, WriteXdr};
+    use trap_grid_sdk::Keypair;
+
+    use super::*;
+
+    fn info(event: &GameEvent, ledger: u32) -> EventInfo {
+        let (topics, value) = event.encode().unwrap();
+        EventInfo {
+            id: format!("{ledger:019}-0000000001"),
+            ledger,
+            ledger_closed_at: "2026-01-01T00:00:00Z".into(),
+            tx_hash: "00".repeat(32),
+            topic: topics
+                .iter()
+                .map(|topic| topic.to_xdr_base64(Limits::none()).unwrap())
+                .collect(),
+            value: value.to_xdr_base64(Limits::none()).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_write_page_is_idempotent() {
+        let mut db = Db::open_in_memory().unwrap();
+        let defender = Keypair::generate().unwrap().address();
+        let attacker = Keypair::generate().unwrap().address();
+        let events = [
+            info(
+                &GameEvent::GameStarted(GameStarted {
+                    session_id: 1,
+                    defender: defender.clone(),
+                    attacker: attacker.clone(),
+                }),
+                10,
+            ),
+            info(
+                &GameEvent::MoveMade(MoveMade {
+                    session_id: 1,
+                    defender: defender.clone(),
+                    attacker: attacker.clone(),
+                    x: 2,
+                    y: 3,
+                    is_hit: true,
+                    verified: true,
+                }),
+                11,
+            ),
+            info(
+                &GameEvent::GameEnded(GameEnded {
+                    session_id: 1,
+                    defender: defender.clone(),
+                    attacker: attacker.clone(),
+                    winner: attacker.clone(),
+                    hits: 1,
+                    misses: 0,
+                }),
+                12,
+            ),
+        ];
+        let next = Cursor {
+            cursor: Some("0000000012-0000000001".into()),
+            start_ledger: 10,
+        };
+        // A replayed page must not double-count
+        db.write_page("CTRAP", &events, true, &next).unwrap();
+        db.write_page("CTRAP", &events, true, &next).unwrap();
+
+        let (moves_made, hits): (u32, u32) = db
+            .conn
+            .query_row(
+                "SELECT moves_made, hits FROM games WHERE session_id = 1",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!((moves_made, hits), (1, 1));
+        let attacker_won: bool = db
+            .conn
+            .query_row("SELECT attacker_won FROM results", [], |row| row.get(0))
+            .unwrap();
+        assert!(attacker_won);
+        assert_eq!(db.cursor("CTRAP").unwrap(), Some(next));
+        assert_eq!(db.cursor("CHUB").unwrap(), None);
+    }
+}
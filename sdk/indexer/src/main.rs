@@ -0,0 +1,193 @@
+//! # Indexer
+//!
+//! Follows the trap-grid contract's events, and optionally the Game Hub's,
+//! and writes games, moves and results into SQLite so the dApp and analytics
+//! can query them instead of reading contract storage. The `getEvents`
+//! cursor of each contract is stored with the rows it produced, so the
+//! indexer resumes where it stopped after a restart.
+
+mod db;
+
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use stellar_xdr::curr::{ScAddress, ScVal};
+use trap_grid_sdk::player_topics;
+use trap_grid_sdk::rpc::{EventsStart, RpcClient, DEFAULT_RPC_URL};
+
+use crate::db::{Cursor, Db};
+
+#[derive(Parser)]
+#[command(
+    name = "indexer",
+    version,
+    about = "Indexes trap-grid games into SQLite"
+)]
+struct Cli {
+    /// Trap-grid contract id
+    #[arg(long, env = "TRAP_GRID_CONTRACT")]
+    contract_id: String,
+
+    /// Game Hub contract id; its events are stored raw in `hub_events`
+    #[arg(long, env = "GAME_HUB_CONTRACT")]
+    hub_id: Option<String>,
+
+    /// Soroban RPC endpoint
+    #[arg(long, env = "STELLAR_RPC_URL", default_value = DEFAULT_RPC_URL)]
+    rpc_url: String,
+
+    /// SQLite database file, created on first run
+    #[arg(long, default_value = "trap-grid.sqlite")]
+    db: PathBuf,
+
+    /// First ledger to index when the database has no cursor yet (must be
+    /// within the RPC retention window); defaults to the latest ledger
+    #[arg(long)]
+    from_ledger: Option<u32>,
+
+    /// Polling interval in seconds
+    #[arg(long, default_value_t = 5)]
+    interval: u64,
+
+    /// Index only the games this address plays, as defender or attacker.
+    /// The cursor is kept as is, so switching to another player later does
+    /// not index the earlier games of the new one
+    #[arg(long)]
+    player: Option<String>,
+
+    /// Exit once caught up instead of following new ledgers
+    #[arg(long)]
+    once: bool,
+}
+
+/// One contract's event stream
+struct Stream {
+    contract: ScAddress,
+    id: String,
+    /// Trap-grid events are decoded into tables, others are stored raw
+    decode: bool,
+    /// Topic filters the events must match one of, empty for every event
+    filters: Vec<Vec<Option<ScVal>>>,
+    next: Cursor,
+}
+
+impl Stream {
+    fn new(
+        db: &Db,
+        rpc: &RpcClient,
+        id: &str,
+        decode: bool,
+        filters: Vec<Vec<Option<ScVal>>>,
+        from: Option<u32>,
+    ) -> Result<Self> {
+        let contract = id
+            .parse()
+            .with_context(|| format!("invalid contract id {id}"))?;
+        let next = match db.cursor(id)? {
+            Some(cursor) => cursor,
+            None => Cursor {
+                cursor: None,
+                start_ledger: match from {
+                    Some(ledger) => ledger,
+                    None => rpc.get_latest_ledger()?,
+                },
+            },
+        };
+        Ok(Self {
+            contract,
+            id: id.to_string(),
+            decode,
+            filters,
+            next,
+        })
+    }
+
+    /// Index one page; returns how many events it held
+    fn poll(&mut self, db: &mut Db, rpc: &RpcClient) -> Result<usize> {
+        let start = match &self.next.cursor {
+            Some(cursor) => EventsStart::Cursor(cursor),
+            None => EventsStart::Ledger(self.next.start_ledger),
+        };
+        let filters: Vec<&[Option<ScVal>]> = self.filters.iter().map(Vec::as_slice).collect();
+        let result = rpc.get_events_matching(&self.contract, &filters, start)?;
+        let next = match result.cursor {
+            Some(cursor) => Cursor {
+                cursor: Some(cursor),
+                start_ledger: self.next.start_ledger,
+            },
+            None => Cursor {
+                cursor: None,
+                start_ledger: result.latest_ledger,
+            },
+        };
+        db.write_page(&self.id, &result.events, self.decode, &next)?;
+        self.next = next;
+        Ok(result.events.len())
+    }
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    let rpc = RpcClient::new(&cli.rpc_url);
+    let mut db = Db::open(&cli.db)?;
+    let filters = match &cli.player {
+        Some(player) => player_topics(player)?.into(),
+        None => Vec::new(),
+    };
+    let mut streams = vec![Stream::new(
+        &db,
+        &rpc,
+        &cli.contract_id,
+        true,
+        filters,
+        cli.from_ledger,
+    )?];
+    if let Some(hub_id) = &cli.hub_id {
+        streams.push(Stream::new(
+            &db,
+            &rpc,
+            hub_id,
+            false,
+            Vec::new(),
+            cli.from_ledger,
+        )?);
+    }
+    for stream in &streams {
+        println!(
+            "Indexing {} from {}",
+            stream.id,
+            match &stream.next.cursor {
+                Some(cursor) => format!("cursor {cursor}"),
+                None => format!("ledger {}", stream.next.start_ledger),
+            }
+        );
+    }
+
+    loop {
+        let mut indexed = 0;
+        for stream in &mut streams {
+            // Drain the backlog before sleeping
+            loop {
+                match stream.poll(&mut db, &rpc) {
+                    Ok(0) => break,
+                    Ok(count) => indexed += count,
+                    Err(err) => {
+                        eprintln!("{}: {err:#}", stream.id);
+                        break;
+                    }
+                }
+            }
+        }
+        if indexed > 0 {
+            println!("{indexed} event(s) indexed");
+        }
+        if cli.once {
+            return Ok(());
+        }
+        thread::sleep(Duration::from_secs(cli.interval));
+    }
+}
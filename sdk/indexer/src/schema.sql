@@ -0,0 +1,57 @@
+CREATE TABLE games (
+    session_id     INTEGER PRIMARY KEY,
+    defender       TEXT NOT NULL,
+    attacker       TEXT NOT NULL,
+    started_ledger INTEGER NOT NULL,
+    started_at     TEXT NOT NULL,
+    moves_made     INTEGER NOT NULL DEFAULT 0,
+    hits           INTEGER NOT NULL DEFAULT 0,
+    misses         INTEGER NOT NULL DEFAULT 0,
+    pending_x      INTEGER,
+    pending_y      INTEGER
+);
+
+CREATE TABLE moves (
+    session_id  INTEGER NOT NULL,
+    move_number INTEGER NOT NULL,
+    x           INTEGER NOT NULL,
+    y           INTEGER NOT NULL,
+    is_hit      INTEGER NOT NULL,
+    verified    INTEGER NOT NULL,
+    ledger      INTEGER NOT NULL,
+    tx_hash     TEXT NOT NULL,
+    PRIMARY KEY (session_id, move_number),
+    UNIQUE (session_id, x, y)
+);
+
+CREATE TABLE results (
+    session_id   INTEGER PRIMARY KEY,
+    winner       TEXT NOT NULL,
+    attacker_won INTEGER,
+    hits         INTEGER NOT NULL,
+    misses       INTEGER NOT NULL,
+    ledger       INTEGER NOT NULL,
+    ended_at     TEXT NOT NULL
+);
+
+CREATE INDEX games_defender ON games (defender);
+CREATE INDEX games_attacker ON games (attacker);
+CREATE INDEX results_winner ON results (winner);
+
+-- Events of other contracts (the Game Hub), kept as base64 XDR
+CREATE TABLE hub_events (
+    event_id  TEXT PRIMARY KEY,
+    contract  TEXT NOT NULL,
+    ledger    INTEGER NOT NULL,
+    closed_at TEXT NOT NULL,
+    tx_hash   TEXT NOT NULL,
+    topics    TEXT NOT NULL,
+    value     TEXT NOT NULL
+);
+
+-- Where to resume each contract's event stream
+CREATE TABLE cursors (
+    contract     TEXT PRIMARY KEY,
+    cursor       TEXT,
+    start_ledger INTEGER NOT NULL
+);
@@ -0,0 +1,224 @@
+//! Waiting intents and the pairing rules
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use trap_grid_sdk::GridSetup;
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    Defender,
+    Attacker,
+    #[default]
+    Either,
+}
+
+/// A player's wish to play: a side, a stake range and a rating band
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Intent {
+    pub address: String,
+    #[serde(default)]
+    pub role: Role,
+    /// Points the player is willing to commit, inclusive
+    pub min_stake: i128,
+    pub max_stake: i128,
+    /// Self-reported rating and the largest gap the player accepts
+    pub rating: u32,
+    pub rating_band: u32,
+    /// Proof of the player's grid, which `start_game` needs from the
+    /// defender; a player without one only attacks
+    #[serde(default)]
+    pub setup: Option<GridSetup>,
+}
+
+impl Intent {
+    pub fn validate(&self) -> Result<()> {
+        if self.min_stake <= 0 || self.min_stake > self.max_stake {
+            bail!("stake range must be positive and min_stake <= max_stake");
+        }
+        if self.role == Role::Defender && self.setup.is_none() {
+            bail!("defenders must include the setup proof of their grid");
+        }
+        Ok(())
+    }
+
+    /// The side the player can take: only players with a grid setup defend
+    fn playable_role(&self) -> Role {
+        match (self.role, &self.setup) {
+            (Role::Either, None) => Role::Attacker,
+            (role, _) => role,
+        }
+    }
+}
+
+/// Two intents that can play each other
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Pairing {
+    pub defender: String,
+    pub attacker: String,
+    /// Committed by both players: the smallest stake both accept
+    pub stake: i128,
+    /// The defender's grid setup proof
+    pub setup: GridSetup,
+    pub intents: [u64; 2],
+}
+
+/// Whether `a` defends against `b`, if their roles fit together
+fn a_defends(a: Role, b: Role) -> Option<bool> {
+    match (a, b) {
+        (Role::Defender, Role::Defender) | (Role::Attacker, Role::Attacker) => None,
+        (Role::Defender, _) | (Role::Either, Role::Attacker) | (Role::Either, Role::Either) => {
+            Some(true)
+        }
+        (Role::Attacker, _) | (Role::Either, Role::Defender) => Some(false),
+    }
+}
+
+/// `(a defends, stake)` if `a` and `b` can be matched
+pub fn pair(a: &Intent, b: &Intent) -> Option<(bool, i128)> {
+    if a.address == b.address {
+        return None;
+    }
+    let stake = a.min_stake.max(b.min_stake);
+    if stake > a.max_stake.min(b.max_stake) {
+        return None;
+    }
+    if a.rating.abs_diff(b.rating) > a.rating_band.min(b.rating_band) {
+        return None;
+    }
+    Some((a_defends(a.playable_role(), b.playable_role())?, stake))
+}
+
+#[derive(Default)]
+pub struct Book {
+    next_id: u64,
+    /// Oldest first, so the longest-waiting compatible intent is matched
+    waiting: Vec<(u64, Intent)>,
+}
+
+impl Book {
+    /// Queue `intent`, or match it with a waiting one; returns its id
+    pub fn add(&mut self, intent: Intent) -> (u64, Option<Pairing>) {
+        self.next_id += 1;
+        let id = self.next_id;
+        let found = self
+            .waiting
+            .iter()
+            .enumerate()
+            .find_map(|(index, (_, waiting))| Some((index, pair(waiting, &intent)?)));
+        match found {
+            Some((index, (waiting_defends, stake))) => {
+                let (waiting_id, waiting) = self.waiting.remove(index);
+                let (defender, attacker) = if waiting_defends {
+                    (waiting, intent)
+                } else {
+                    (intent, waiting)
+                };
+                let pairing = Pairing {
+                    setup: defender
+                        .setup
+                        .expect("pair() only lets players with a grid setup defend"),
+                    defender: defender.address,
+                    attacker: attacker.address,
+                    stake,
+                    intents: [waiting_id, id],
+                };
+                (id, Some(pairing))
+            }
+            None => {
+                self.waiting.push((id, intent));
+                (id, None)
+            }
+        }
+    }
+
+    /// Withdraw a waiting intent; false if it is not waiting
+    pub fn cancel(&mut self, id: u64) -> bool {
+        let before = self.waiting.len();
+        self.waiting.retain(|(waiting, _)| *waiting != id);
+        self.waiting.len() < before
+    }
+
+    pub fn is_waiting(&self, id: u64) -> bool {
+        self.waiting.iter().any(|(waiting, _)| *waiting == id)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn intent(address: &str, role: Role, stakes: (i128, i128), rating: u32) -> Intent {
+        Intent {
+            address: address.into(),
+            role,
+            min_stake: stakes.0,
+            max_stake: stakes.1,
+            rating,
+            rating_band: 100,
+            setup: (role != Role::Attacker).then(setup),
+        }
+    }
+
+    fn setup() -> GridSetup {
+        GridSetup {
+            grid_commitment: [7; 32],
+            proof: vec![1, 2, 3],
+        }
+    }
+
+    #[test]
+    fn test_pair() {
+        let a = intent("GA", Role::Either, (10, 50), 1000);
+        assert_eq!(
+            pair(&a, &intent("GB", Role::Defender, (20, 80), 1080)),
+            Some((false, 20))
+        );
+        assert_eq!(pair(&a, &intent("GB", Role::Either, (60, 80), 1000)), None);
+        assert_eq!(pair(&a, &intent("GB", Role::Either, (10, 50), 1200)), None);
+        assert_eq!(
+            pair(&a, &intent("GA", Role::Attacker, (10, 50), 1000)),
+            None
+        );
+        let defender = intent("GA", Role::Defender, (10, 50), 1000);
+        assert_eq!(
+            pair(&defender, &intent("GB", Role::Defender, (10, 50), 1000)),
+            None
+        );
+
+        // Without a grid setup, a player can only attack
+        let mut unproven = intent("GB", Role::Either, (10, 50), 1000);
+        unproven.setup = None;
+        assert_eq!(pair(&a, &unproven), Some((true, 10)));
+        assert_eq!(pair(&unproven, &a), Some((false, 10)));
+        let mut a_unproven = a.clone();
+        a_unproven.setup = None;
+        assert_eq!(pair(&a_unproven, &unproven), None);
+        unproven.role = Role::Defender;
+        assert!(unproven.validate().is_err());
+    }
+
+    #[test]
+    fn test_book_matches_oldest_compatible() {
+        let mut book = Book::default();
+        let (a, none) = book.add(intent("GA", Role::Attacker, (10, 20), 1000));
+        assert!(none.is_none());
+        let (c, none) = book.add(intent("GC", Role::Attacker, (10, 20), 1000));
+        assert!(none.is_none());
+
+        let (b, pairing) = book.add(intent("GB", Role::Either, (5, 20), 1000));
+        assert_eq!(
+            pairing,
+            Some(Pairing {
+                defender: "GB".into(),
+                attacker: "GA".into(),
+                stake: 10,
+                setup: setup(),
+                intents: [a, b],
+            })
+        );
+        assert!(!book.is_waiting(a));
+        assert!(book.cancel(c));
+        assert!(!book.cancel(c));
+    }
+}
@@ -0,0 +1,382 @@
+//! # Matchmaker
+//!
+//! Pairs players who want a game and starts it for them. `start_game` needs
+//! the authorization of players who are usually not online at the same
+//! place, so the server does the coordination:
+//! 1. Players post an intent (side, stake range, rating band) and poll it.
+//! 2. When two intents fit, the server simulates `start_game` and publishes
+//!    the payload each required player has to sign.
+//! 3. Players post their ed25519 signature over that payload.
+//! 4. Once every authorization is signed, the server, as fee payer, signs and
+//!    submits the transaction.
+
+mod book;
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Context, Result};
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use stellar_xdr::curr::ScAddress;
+use trap_grid_sdk::rpc::{RpcClient, DEFAULT_RPC_URL};
+use trap_grid_sdk::tx::AuthRequest;
+use trap_grid_sdk::{GameClient, Keypair};
+
+use crate::book::{Book, Intent, Pairing};
+
+#[derive(Parser)]
+#[command(
+    name = "matchmaker",
+    version,
+    about = "Pairs trap-grid players and starts their games"
+)]
+struct Cli {
+    /// Trap-grid contract id
+    #[arg(long, env = "TRAP_GRID_CONTRACT")]
+    contract_id: String,
+
+    /// Soroban RPC endpoint
+    #[arg(long, env = "STELLAR_RPC_URL", default_value = DEFAULT_RPC_URL)]
+    rpc_url: String,
+
+    /// Secret key (S...) of the account that pays for `start_game`
+    #[arg(long, env = "MATCHMAKER_SECRET", hide_env_values = true)]
+    secret: String,
+
+    /// Address to listen on
+    #[arg(long, env = "MATCHMAKER_ADDR", default_value = "127.0.0.1:8081")]
+    listen: SocketAddr,
+
+    /// Seconds players have to sign before a match is abandoned; must stay
+    /// below the ~8 minutes an authorization signature is valid
+    #[arg(long, default_value_t = 300)]
+    signature_timeout: u64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+enum MatchStatus {
+    /// Simulating `start_game`
+    Preparing,
+    AwaitingSignatures,
+    Submitting,
+    Started,
+    Failed {
+        error: String,
+    },
+}
+
+struct Match {
+    pairing: Pairing,
+    created: Instant,
+    status: MatchStatus,
+    request: Option<AuthRequest>,
+}
+
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+enum IntentStatus {
+    Waiting,
+    Matched { session_id: u32 },
+    Cancelled,
+}
+
+#[derive(Default)]
+struct Lobby {
+    book: Book,
+    intents: HashMap<u64, IntentStatus>,
+    /// By session id
+    matches: HashMap<u32, Match>,
+}
+
+struct AppState {
+    game: GameClient<'static>,
+    source: Keypair,
+    signature_timeout: Duration,
+    lobby: Mutex<Lobby>,
+}
+
+impl AppState {
+    fn lobby(&self) -> std::sync::MutexGuard<'_, Lobby> {
+        self.lobby.lock().expect("lobby lock poisoned")
+    }
+}
+
+struct ApiError(StatusCode, String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({ "error": self.1 }))).into_response()
+    }
+}
+
+fn not_found(what: &str) -> ApiError {
+    ApiError(StatusCode::NOT_FOUND, format!("no such {what}"))
+}
+
+fn bad_request(err: anyhow::Error) -> ApiError {
+    ApiError(StatusCode::BAD_REQUEST, format!("{err:#}"))
+}
+
+fn random_session_id() -> Result<u32> {
+    let mut bytes = [0u8; 4];
+    getrandom::getrandom(&mut bytes).map_err(|e| anyhow!("no system randomness: {e}"))?;
+    Ok(u32::from_be_bytes(bytes))
+}
+
+/// Record the outcome of a blocking step of a match
+fn settle(state: &AppState, session_id: u32, outcome: Result<MatchStatus>) {
+    let status = outcome.unwrap_or_else(|err| MatchStatus::Failed {
+        error: format!("{err:#}"),
+    });
+    if let Some(m) = state.lobby().matches.get_mut(&session_id) {
+        m.status = status;
+    }
+}
+
+/// Sign and send a match's transaction in the background
+fn submit(state: Arc<AppState>, session_id: u32, request: AuthRequest) {
+    tokio::task::spawn_blocking(move || {
+        let outcome = state
+            .game
+            .submit_prepared(&state.source, request)
+            .map(|_| MatchStatus::Started);
+        settle(&state, session_id, outcome);
+    });
+}
+
+/// Simulate `start_game` for a new match in the background
+fn prepare(state: Arc<AppState>, session_id: u32, pairing: Pairing) {
+    tokio::task::spawn_blocking(move || {
+        let prepared = state.game.prepare_start_game(
+            &state.source,
+            &pairing.defender,
+            &pairing.attacker,
+            session_id,
+            (pairing.stake, pairing.stake),
+            &pairing.setup,
+        );
+        match prepared {
+            // Nothing to sign when the server's own account is the only signer
+            Ok(request) if request.required().is_empty() => {
+                settle(&state, session_id, Ok(MatchStatus::Submitting));
+                submit(state.clone(), session_id, request);
+            }
+            Ok(request) => {
+                if let Some(m) = state.lobby().matches.get_mut(&session_id) {
+                    m.request = Some(request);
+                    m.status = MatchStatus::AwaitingSignatures;
+                }
+            }
+            Err(err) => settle(&state, session_id, Err(err)),
+        }
+    });
+}
+
+async fn post_intent(
+    State(state): State<Arc<AppState>>,
+    Json(intent): Json<Intent>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    intent.validate().map_err(bad_request)?;
+    intent
+        .address
+        .parse::<ScAddress>()
+        .map_err(|e| bad_request(anyhow!("invalid address {}: {e}", intent.address)))?;
+
+    let mut lobby = state.lobby();
+    let (id, pairing) = lobby.book.add(intent);
+    let Some(pairing) = pairing else {
+        lobby.intents.insert(id, IntentStatus::Waiting);
+        return Ok(Json(json!({ "intent_id": id, "status": "waiting" })));
+    };
+
+    let session_id = random_session_id()
+        .map_err(|err| ApiError(StatusCode::INTERNAL_SERVER_ERROR, format!("{err:#}")))?;
+    for intent_id in pairing.intents {
+        lobby
+            .intents
+            .insert(intent_id, IntentStatus::Matched { session_id });
+    }
+    lobby.matches.insert(
+        session_id,
+        Match {
+            pairing: pairing.clone(),
+            created: Instant::now(),
+            status: MatchStatus::Preparing,
+            request: None,
+        },
+    );
+    drop(lobby);
+    prepare(state.clone(), session_id, pairing);
+    Ok(Json(
+        json!({ "intent_id": id, "status": "matched", "session_id": session_id }),
+    ))
+}
+
+async fn get_intent(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<u64>,
+) -> Result<Json<IntentStatus>, ApiError> {
+    state
+        .lobby()
+        .intents
+        .get(&id)
+        .copied()
+        .map(Json)
+        .ok_or_else(|| not_found("intent"))
+}
+
+async fn delete_intent(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<u64>,
+) -> Result<Json<IntentStatus>, ApiError> {
+    let mut lobby = state.lobby();
+    if !lobby.book.is_waiting(id) {
+        return Err(ApiError(
+            StatusCode::CONFLICT,
+            "intent is not waiting".to_string(),
+        ));
+    }
+    lobby.book.cancel(id);
+    lobby.intents.insert(id, IntentStatus::Cancelled);
+    Ok(Json(IntentStatus::Cancelled))
+}
+
+/// Abandon a match whose players did not sign in time
+fn expire(m: &mut Match, timeout: Duration) {
+    if matches!(m.status, MatchStatus::AwaitingSignatures) && m.created.elapsed() > timeout {
+        m.status = MatchStatus::Failed {
+            error: "signatures were not collected in time".to_string(),
+        };
+        m.request = None;
+    }
+}
+
+async fn get_match(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<u32>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let mut lobby = state.lobby();
+    let m = lobby
+        .matches
+        .get_mut(&session_id)
+        .ok_or_else(|| not_found("match"))?;
+    expire(m, state.signature_timeout);
+    let awaiting: Vec<_> = m
+        .request
+        .iter()
+        .flat_map(AuthRequest::required)
+        .map(|(address, payload)| {
+            json!({ "address": address.to_string(), "payload": hex::encode(payload) })
+        })
+        .collect();
+    Ok(Json(json!({
+        "session_id": session_id,
+        "defender": m.pairing.defender,
+        "attacker": m.pairing.attacker,
+        "stake": m.pairing.stake.to_string(),
+        "state": m.status,
+        "awaiting": awaiting,
+    })))
+}
+
+#[derive(Deserialize)]
+struct SignatureBody {
+    /// Signer's account (G...)
+    address: String,
+    /// Hex ed25519 signature over the payload listed for the address
+    signature: String,
+}
+
+fn parse_signature(body: &SignatureBody) -> Result<([u8; 32], [u8; 64])> {
+    let public_key = stellar_strkey::ed25519::PublicKey::from_string(&body.address)
+        .with_context(|| format!("invalid account {}", body.address))?;
+    let signature: [u8; 64] = hex::decode(&body.signature)
+        .context("signature is not hex")?
+        .try_into()
+        .map_err(|_| anyhow!("signature must be 64 bytes"))?;
+    Ok((public_key.0, signature))
+}
+
+async fn post_signature(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<u32>,
+    Json(body): Json<SignatureBody>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let (public_key, signature) = parse_signature(&body).map_err(bad_request)?;
+
+    let mut lobby = state.lobby();
+    let m = lobby
+        .matches
+        .get_mut(&session_id)
+        .ok_or_else(|| not_found("match"))?;
+    expire(m, state.signature_timeout);
+    let Some(request) = m.request.as_mut() else {
+        return Err(ApiError(
+            StatusCode::CONFLICT,
+            "match is not awaiting signatures".to_string(),
+        ));
+    };
+    request
+        .add_signature(public_key, signature)
+        .map_err(bad_request)?;
+    if !request.required().is_empty() {
+        return Ok(Json(json!({ "status": "awaiting_signatures" })));
+    }
+
+    let request = m.request.take().expect("checked above");
+    m.status = MatchStatus::Submitting;
+    drop(lobby);
+    submit(state.clone(), session_id, request);
+    Ok(Json(json!({ "status": "submitting" })))
+}
+
+fn router(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/intents", post(post_intent))
+        .route("/intents/{id}", get(get_intent).delete(delete_intent))
+        .route("/matches/{session_id}", get(get_match))
+        .route("/matches/{session_id}/signatures", post(post_signature))
+        .with_state(state)
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    let source = Keypair::from_secret(&cli.secret)?;
+    let contract: ScAddress = cli
+        .contract_id
+        .parse()
+        .with_context(|| format!("invalid contract id {}", cli.contract_id))?;
+    // Lives as long as the server
+    let rpc: &'static RpcClient = Box::leak(Box::new(RpcClient::new(&cli.rpc_url)));
+    let game = GameClient::new(rpc, contract)?;
+    if let Some(warning) = game.check_interface()? {
+        eprintln!("warning: {warning}");
+    }
+    let state = Arc::new(AppState {
+        game,
+        source,
+        signature_timeout: Duration::from_secs(cli.signature_timeout),
+        lobby: Mutex::new(Lobby::default()),
+    });
+
+    let listener = tokio::net::TcpListener::bind(cli.listen).await?;
+    println!(
+        "Matching players on http://{} (fee payer {})",
+        cli.listen,
+        state.source.address()
+    );
+    axum::serve(listener, router(state)).await?;
+    Ok(())
+}
@@ -0,0 +1,186 @@
+//! How a worker proves a job
+//!
+//! A job's inputs are the circuit's `Prover.toml` as JSON. The worker writes
+//! them out, runs `nargo execute` and bb.js (UltraHonk, keccak transcript)
+//! in its own copy of the circuit, and splits `proof.with_public_inputs`
+//! into the public inputs and the proof.
+
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::Serialize;
+use trap_grid_sdk::prover::{self, Circuit};
+
+/// How often a running command is checked against its deadline
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// A finished proof
+#[derive(Clone, Debug, Serialize)]
+pub struct Proof {
+    #[serde(serialize_with = "hex_bytes")]
+    pub proof: Vec<u8>,
+    #[serde(serialize_with = "hex_bytes")]
+    pub public_inputs: Vec<u8>,
+}
+
+fn hex_bytes<S: serde::Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&hex::encode(bytes))
+}
+
+/// Render a job's JSON inputs as `Prover.toml`
+pub fn prover_toml(inputs: &serde_json::Value) -> Result<String> {
+    if !inputs.is_object() {
+        bail!("inputs must be an object of Prover.toml keys");
+    }
+    let value = toml::Value::try_from(inputs).context("inputs do not fit in Prover.toml")?;
+    Ok(toml::to_string(&value)?)
+}
+
+/// The circuits a worker proves in, copied from the shared circuits directory
+pub struct Workspace {
+    source: PathBuf,
+    dir: PathBuf,
+    bbjs: PathBuf,
+}
+
+impl Workspace {
+    /// Copy `circuits` into `dir`; `bbjs` is resolved against each circuit
+    /// directory of `source` when relative
+    pub fn create(source: &Path, circuits: &[Circuit], bbjs: &Path, dir: &Path) -> Result<Self> {
+        for circuit in circuits {
+            prover::copy_circuit(&source.join(circuit.id()), &dir.join(circuit.id()))?;
+        }
+        Ok(Self {
+            source: source.to_path_buf(),
+            dir: dir.to_path_buf(),
+            bbjs: bbjs.to_path_buf(),
+        })
+    }
+
+    /// Prove `circuit` on `inputs`, giving up at `deadline`
+    pub fn prove(&self, circuit: Circuit, inputs: &str, deadline: Instant) -> Result<Proof> {
+        let dir = self.dir.join(circuit.id());
+        fs::write(dir.join("Prover.toml"), inputs).context("failed to write Prover.toml")?;
+        let target = dir.join("target");
+        // A witness or proof left over from the previous job must not be picked up
+        fs::remove_dir_all(&target).ok();
+
+        run(&dir, "nargo", &["execute"], deadline)?;
+
+        // npm packages stay in the shared circuit directory
+        let bbjs = if self.bbjs.is_absolute() {
+            self.bbjs.clone()
+        } else {
+            self.source.join(circuit.id()).join(&self.bbjs)
+        };
+        let bytecode = target.join(format!("{}.json", circuit.package()));
+        let witness = target.join(format!("{}.gz", circuit.package()));
+        let output = target.join("proof.with_public_inputs");
+        run(
+            &dir,
+            "node",
+            &[
+                &bbjs.to_string_lossy(),
+                "prove_ultra_keccak_honk",
+                "-b",
+                &bytecode.to_string_lossy(),
+                "-w",
+                &witness.to_string_lossy(),
+                "-o",
+                &output.to_string_lossy(),
+            ],
+            deadline,
+        )?;
+
+        let bytes =
+            fs::read(&output).with_context(|| format!("failed to read {}", output.display()))?;
+        let len = circuit.public_inputs_len();
+        if bytes.len() <= len {
+            bail!("proof output is only {} bytes", bytes.len());
+        }
+        let (public_inputs, proof) = bytes.split_at(len);
+        Ok(Proof {
+            proof: proof.to_vec(),
+            public_inputs: public_inputs.to_vec(),
+        })
+    }
+}
+
+/// The error of a job that ran past its deadline
+#[derive(Debug)]
+pub struct TimedOut;
+
+impl std::fmt::Display for TimedOut {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("proving timed out")
+    }
+}
+
+impl std::error::Error for TimedOut {}
+
+/// Run `program` in `dir`, killing it at `deadline`
+fn run(dir: &Path, program: &str, args: &[&str], deadline: Instant) -> Result<()> {
+    // A file rather than a pipe, which would fill up while nobody reads it
+    let stderr_path = dir.join(format!("{program}.stderr"));
+    let mut child = Command::new(program)
+        .args(args)
+        .current_dir(dir)
+        .stdout(Stdio::null())
+        .stderr(File::create(&stderr_path)?)
+        .spawn()
+        .with_context(|| format!("failed to run {program}; is it installed and on PATH?"))?;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            child.kill().ok();
+            child.wait().ok();
+            return Err(anyhow!(TimedOut));
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+    if !status.success() {
+        let stderr = fs::read_to_string(&stderr_path).unwrap_or_default();
+        bail!(
+            "{program} {} failed: {}",
+            args.first().unwrap_or(&""),
+            stderr.trim()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_prover_toml() {
+        let inputs = json!({
+            "public_inputs": { "move_x": "2", "move_y": "3", "is_hit": "1" },
+            "private_inputs": { "trap_value": "1" },
+        });
+        let toml = prover_toml(&inputs).unwrap();
+        let parsed: toml::Value = toml::from_str(&toml).unwrap();
+        assert_eq!(parsed["public_inputs"]["move_y"].as_str(), Some("3"));
+        assert_eq!(parsed["private_inputs"]["trap_value"].as_str(), Some("1"));
+
+        assert!(prover_toml(&json!(["move_x"])).is_err());
+        assert!(prover_toml(&json!({ "move_x": null })).is_err());
+    }
+
+    #[test]
+    fn test_run_times_out() {
+        let dir = std::env::temp_dir();
+        let deadline = Instant::now() + Duration::from_millis(100);
+        let err = run(&dir, "sleep", &["5"], deadline).unwrap_err();
+        assert!(err.is::<TimedOut>());
+        assert!(run(&dir, "true", &[], Instant::now() + Duration::from_secs(5)).is_ok());
+    }
+}
@@ -0,0 +1,261 @@
+//! # Prover service
+//!
+//! Proves for clients that cannot run nargo and bb themselves, such as a
+//! defender playing from a phone or a browser:
+//! 1. A client posts a job: a circuit id and the circuit's `Prover.toml` as
+//!    JSON. It is queued and gets an id.
+//! 2. A pool of workers takes jobs in order, each proving in its own copy of
+//!    the circuits and killed at the per-job timeout.
+//! 3. The client polls the job until it is done and reads the proof and
+//!    public inputs as hex.
+//!
+//! `/metrics` exposes queue depth, outcomes and proving time per circuit in
+//! the Prometheus text format.
+
+mod circuit;
+mod queue;
+
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+use axum::extract::{Path, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use clap::Parser;
+use serde::Deserialize;
+use serde_json::json;
+use trap_grid_sdk::prover::{self, Circuit};
+
+use crate::circuit::{TimedOut, Workspace};
+use crate::queue::{JobStatus, Queue};
+
+#[derive(Parser)]
+#[command(
+    name = "prover-service",
+    version,
+    about = "Queues and runs trap-grid proving jobs for thin clients"
+)]
+struct Cli {
+    /// Directory holding the circuits (`position-movement`, `trap-commitment`, `trap-merkle-root`)
+    #[arg(long, default_value = "../circuits")]
+    circuits_dir: PathBuf,
+
+    /// bb.js entrypoint (relative paths are resolved against each circuit directory)
+    #[arg(long, default_value = prover::DEFAULT_BBJS)]
+    bbjs: PathBuf,
+
+    /// Directory for the workers' copies of the circuits
+    #[arg(long)]
+    work_dir: Option<PathBuf>,
+
+    /// Number of proofs generated in parallel
+    #[arg(long, default_value_t = 2, value_parser = clap::value_parser!(u32).range(1..))]
+    workers: u32,
+
+    /// Seconds a job may take before it is killed
+    #[arg(long, default_value_t = 120)]
+    timeout: u64,
+
+    /// Jobs that may wait for a worker before new ones are refused
+    #[arg(long, default_value_t = 100)]
+    max_queued: usize,
+
+    /// Seconds a finished job's result stays available
+    #[arg(long, default_value_t = 600)]
+    retain: u64,
+
+    /// Address to listen on
+    #[arg(long, env = "PROVER_SERVICE_ADDR", default_value = "127.0.0.1:8082")]
+    listen: SocketAddr,
+}
+
+struct AppState {
+    circuits: Vec<Circuit>,
+    queue: Arc<Queue>,
+}
+
+struct ApiError(StatusCode, String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({ "error": self.1 }))).into_response()
+    }
+}
+
+fn not_found() -> ApiError {
+    ApiError(StatusCode::NOT_FOUND, "no such job".to_string())
+}
+
+#[derive(Deserialize)]
+struct JobRequest {
+    circuit: Circuit,
+    /// `Prover.toml` as JSON
+    inputs: serde_json::Value,
+}
+
+async fn get_circuits(State(state): State<Arc<AppState>>) -> Json<Vec<&'static str>> {
+    Json(state.circuits.iter().map(|circuit| circuit.id()).collect())
+}
+
+async fn post_job(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<JobRequest>,
+) -> Result<(StatusCode, Json<serde_json::Value>), ApiError> {
+    if !state.circuits.contains(&request.circuit) {
+        return Err(ApiError(
+            StatusCode::BAD_REQUEST,
+            format!("{} is not available here", request.circuit.id()),
+        ));
+    }
+    let inputs = circuit::prover_toml(&request.inputs)
+        .map_err(|err| ApiError(StatusCode::BAD_REQUEST, format!("{err:#}")))?;
+    let (id, position) = state.queue.push(request.circuit, inputs).map_err(|_| {
+        ApiError(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "the queue is full; try again later".to_string(),
+        )
+    })?;
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(json!({ "job_id": id, "status": "queued", "position": position })),
+    ))
+}
+
+async fn get_job(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<u64>,
+) -> Result<Json<JobStatus>, ApiError> {
+    state.queue.status(id).map(Json).ok_or_else(not_found)
+}
+
+async fn delete_job(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<u64>,
+) -> Result<Json<JobStatus>, ApiError> {
+    match state.queue.cancel(id) {
+        Some(true) => Ok(Json(JobStatus::Cancelled)),
+        Some(false) => Err(ApiError(
+            StatusCode::CONFLICT,
+            "job is no longer queued".to_string(),
+        )),
+        None => Err(not_found()),
+    }
+}
+
+async fn get_metrics(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        state.queue.metrics(),
+    )
+}
+
+fn router(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/circuits", get(get_circuits))
+        .route("/jobs", post(post_job))
+        .route("/jobs/{id}", get(get_job).delete(delete_job))
+        .route("/metrics", get(get_metrics))
+        .with_state(state)
+}
+
+/// Prove jobs from `queue` forever
+fn work(queue: &Queue, workspace: &Workspace, timeout: Duration) {
+    loop {
+        let job = queue.take();
+        let started = Instant::now();
+        let outcome = workspace.prove(job.circuit, &job.inputs, started + timeout);
+        let elapsed = started.elapsed();
+        let status = match outcome {
+            Ok(proof) => JobStatus::Done {
+                proof,
+                proving_ms: elapsed.as_millis(),
+            },
+            Err(err) if err.is::<TimedOut>() => JobStatus::TimedOut,
+            Err(err) => JobStatus::Failed {
+                error: format!("{err:#}"),
+            },
+        };
+        println!(
+            "job {} ({}): {} in {:.1}s",
+            job.id,
+            job.circuit.id(),
+            match &status {
+                JobStatus::Done { .. } => "done",
+                JobStatus::TimedOut => "timed out",
+                _ => "failed",
+            },
+            elapsed.as_secs_f64()
+        );
+        queue.finish(job.id, status, elapsed);
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    // Commands run inside the workers' copies, so paths must be absolute
+    let circuits_dir = cli
+        .circuits_dir
+        .canonicalize()
+        .with_context(|| format!("no circuits directory at {}", cli.circuits_dir.display()))?;
+    let circuits: Vec<Circuit> = Circuit::ALL
+        .into_iter()
+        .filter(|circuit| {
+            let dir = circuits_dir.join(circuit.id());
+            let available = dir.join("Nargo.toml").is_file() && dir.join(&cli.bbjs).is_file();
+            if !available {
+                eprintln!(
+                    "Skipping {}: no Nargo.toml or bb.js (run `npm install` in {})",
+                    circuit.id(),
+                    dir.display()
+                );
+            }
+            available
+        })
+        .collect();
+    if circuits.is_empty() {
+        bail!("no circuit in {} can be proved", circuits_dir.display());
+    }
+
+    let work_dir = cli
+        .work_dir
+        .clone()
+        .unwrap_or_else(|| std::env::temp_dir().join("prover-service"));
+    let queue = Arc::new(Queue::new(cli.max_queued, Duration::from_secs(cli.retain)));
+    let timeout = Duration::from_secs(cli.timeout);
+    for i in 0..cli.workers {
+        let workspace = Workspace::create(
+            &circuits_dir,
+            &circuits,
+            &cli.bbjs,
+            &work_dir.join(format!("worker-{i}")),
+        )?;
+        let queue = queue.clone();
+        thread::spawn(move || work(&queue, &workspace, timeout));
+    }
+
+    let state = Arc::new(AppState {
+        circuits: circuits.clone(),
+        queue,
+    });
+    let listener = tokio::net::TcpListener::bind(cli.listen).await?;
+    println!(
+        "Proving {} with {} worker(s) on http://{}",
+        circuits
+            .iter()
+            .map(|circuit| circuit.id())
+            .collect::<Vec<_>>()
+            .join(", "),
+        cli.workers,
+        cli.listen
+    );
+    axum::serve(listener, router(state)).await?;
+    Ok(())
+}
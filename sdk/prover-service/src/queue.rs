@@ -0,0 +1,298 @@
+//! Job queue and metrics
+//!
+//! Jobs wait in FIFO order until a worker takes them. Finished jobs are kept
+//! for a while so clients can poll their result, then dropped.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt::Write;
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use trap_grid_sdk::prover::Circuit;
+
+use crate::circuit::Proof;
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued {
+        /// Jobs ahead of this one
+        position: usize,
+    },
+    Running,
+    Done {
+        #[serde(flatten)]
+        proof: Proof,
+        proving_ms: u128,
+    },
+    Failed {
+        error: String,
+    },
+    TimedOut,
+    Cancelled,
+}
+
+impl JobStatus {
+    fn is_finished(&self) -> bool {
+        !matches!(self, JobStatus::Queued { .. } | JobStatus::Running)
+    }
+}
+
+/// A job as handed to a worker
+pub struct Job {
+    pub id: u64,
+    pub circuit: Circuit,
+    /// `Prover.toml` contents
+    pub inputs: String,
+}
+
+struct Entry {
+    circuit: Circuit,
+    status: JobStatus,
+    finished: Option<Instant>,
+}
+
+/// Counters for one circuit
+#[derive(Clone, Copy, Debug, Default)]
+struct Counters {
+    submitted: u64,
+    done: u64,
+    failed: u64,
+    timed_out: u64,
+    proving_time: Duration,
+}
+
+type CounterField = fn(&Counters) -> u64;
+
+#[derive(Default)]
+struct State {
+    next_id: u64,
+    pending: VecDeque<Job>,
+    jobs: HashMap<u64, Entry>,
+    running: usize,
+    counters: BTreeMap<Circuit, Counters>,
+}
+
+pub struct Queue {
+    max_queued: usize,
+    retain: Duration,
+    state: Mutex<State>,
+    ready: Condvar,
+}
+
+/// The queue already holds its maximum of waiting jobs
+#[derive(Debug, Eq, PartialEq)]
+pub struct Full;
+
+impl Queue {
+    pub fn new(max_queued: usize, retain: Duration) -> Self {
+        Self {
+            max_queued,
+            retain,
+            state: Mutex::new(State::default()),
+            ready: Condvar::new(),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().expect("queue lock poisoned")
+    }
+
+    /// Enqueue a job and return its id and position
+    pub fn push(&self, circuit: Circuit, inputs: String) -> Result<(u64, usize), Full> {
+        let mut state = self.lock();
+        self.evict(&mut state);
+        if state.pending.len() >= self.max_queued {
+            return Err(Full);
+        }
+        state.next_id += 1;
+        let id = state.next_id;
+        let position = state.pending.len();
+        state.pending.push_back(Job {
+            id,
+            circuit,
+            inputs,
+        });
+        state.jobs.insert(
+            id,
+            Entry {
+                circuit,
+                status: JobStatus::Queued { position },
+                finished: None,
+            },
+        );
+        state.counters.entry(circuit).or_default().submitted += 1;
+        drop(state);
+        self.ready.notify_one();
+        Ok((id, position))
+    }
+
+    /// Wait for the next job and mark it running
+    pub fn take(&self) -> Job {
+        let mut state = self.lock();
+        loop {
+            if let Some(job) = state.pending.pop_front() {
+                state.running += 1;
+                if let Some(entry) = state.jobs.get_mut(&job.id) {
+                    entry.status = JobStatus::Running;
+                }
+                return job;
+            }
+            state = self.ready.wait(state).expect("queue lock poisoned");
+        }
+    }
+
+    /// Record a running job's outcome
+    pub fn finish(&self, id: u64, status: JobStatus, elapsed: Duration) {
+        let mut state = self.lock();
+        state.running -= 1;
+        let Some(entry) = state.jobs.get_mut(&id) else {
+            return;
+        };
+        let circuit = entry.circuit;
+        entry.status = status.clone();
+        entry.finished = Some(Instant::now());
+        let counters = state.counters.entry(circuit).or_default();
+        match status {
+            JobStatus::Done { .. } => {
+                counters.done += 1;
+                counters.proving_time += elapsed;
+            }
+            JobStatus::TimedOut => counters.timed_out += 1,
+            _ => counters.failed += 1,
+        }
+    }
+
+    /// A job's status, with its current place in the queue
+    pub fn status(&self, id: u64) -> Option<JobStatus> {
+        let mut state = self.lock();
+        self.evict(&mut state);
+        let status = state.jobs.get(&id)?.status.clone();
+        Some(match status {
+            JobStatus::Queued { .. } => JobStatus::Queued {
+                position: state.pending.iter().position(|job| job.id == id)?,
+            },
+            status => status,
+        })
+    }
+
+    /// Drop a job that no worker has taken yet; `false` if it has started
+    pub fn cancel(&self, id: u64) -> Option<bool> {
+        let mut state = self.lock();
+        let entry = state.jobs.get(&id)?;
+        if !matches!(entry.status, JobStatus::Queued { .. }) {
+            return Some(false);
+        }
+        state.pending.retain(|job| job.id != id);
+        let entry = state.jobs.get_mut(&id).expect("checked above");
+        entry.status = JobStatus::Cancelled;
+        entry.finished = Some(Instant::now());
+        Some(true)
+    }
+
+    /// Forget jobs that finished more than `retain` ago
+    fn evict(&self, state: &mut State) {
+        let retain = self.retain;
+        state.jobs.retain(|_, entry| {
+            !entry.status.is_finished()
+                || entry
+                    .finished
+                    .is_none_or(|finished| finished.elapsed() < retain)
+        });
+    }
+
+    /// Metrics in the Prometheus text format
+    pub fn metrics(&self) -> String {
+        let state = self.lock();
+        let mut out = String::new();
+        let gauges = [
+            ("queued", "Jobs waiting for a worker", state.pending.len()),
+            ("running", "Jobs being proved", state.running),
+        ];
+        for (name, help, value) in gauges {
+            writeln!(out, "# HELP prover_jobs_{name} {help}").unwrap();
+            writeln!(out, "# TYPE prover_jobs_{name} gauge").unwrap();
+            writeln!(out, "prover_jobs_{name} {value}").unwrap();
+        }
+        let counters: [(&str, &str, CounterField); 4] = [
+            ("submitted", "Jobs accepted", |c| c.submitted),
+            ("done", "Jobs that produced a proof", |c| c.done),
+            ("failed", "Jobs that failed", |c| c.failed),
+            ("timed_out", "Jobs killed at their timeout", |c| c.timed_out),
+        ];
+        for (name, help, value) in counters {
+            writeln!(out, "# HELP prover_jobs_{name}_total {help}").unwrap();
+            writeln!(out, "# TYPE prover_jobs_{name}_total counter").unwrap();
+            for (circuit, c) in &state.counters {
+                writeln!(
+                    out,
+                    "prover_jobs_{name}_total{{circuit=\"{}\"}} {}",
+                    circuit.id(),
+                    value(c)
+                )
+                .unwrap();
+            }
+        }
+        writeln!(
+            out,
+            "# HELP prover_proving_seconds_total Time spent on successful proofs"
+        )
+        .unwrap();
+        writeln!(out, "# TYPE prover_proving_seconds_total counter").unwrap();
+        for (circuit, c) in &state.counters {
+            writeln!(
+                out,
+                "prover_proving_seconds_total{{circuit=\"{}\"}} {:.3}",
+                circuit.id(),
+                c.proving_time.as_secs_f64()
+            )
+            .unwrap();
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_queue_lifecycle() {
+        let queue = Queue::new(2, Duration::from_secs(60));
+        let (first, _) = queue
+            .push(Circuit::PositionMovement, String::new())
+            .unwrap();
+        let (second, position) = queue.push(Circuit::TrapCommitment, String::new()).unwrap();
+        assert_eq!(position, 1);
+        assert!(queue.push(Circuit::TrapMerkleRoot, String::new()).is_err());
+
+        let job = queue.take();
+        assert_eq!(job.id, first);
+        assert!(matches!(queue.status(first), Some(JobStatus::Running)));
+        assert!(matches!(
+            queue.status(second),
+            Some(JobStatus::Queued { position: 0 })
+        ));
+        assert_eq!(queue.cancel(first), Some(false));
+        assert_eq!(queue.cancel(second), Some(true));
+
+        queue.finish(first, JobStatus::TimedOut, Duration::from_secs(1));
+        assert!(matches!(queue.status(first), Some(JobStatus::TimedOut)));
+        let metrics = queue.metrics();
+        assert!(metrics.contains("prover_jobs_timed_out_total{circuit=\"position-movement\"} 1"));
+        assert!(metrics.contains("prover_jobs_queued 0"));
+    }
+
+    #[test]
+    fn test_finished_jobs_are_evicted() {
+        let queue = Queue::new(1, Duration::ZERO);
+        let (id, _) = queue
+            .push(Circuit::PositionMovement, String::new())
+            .unwrap();
+        queue.take();
+        let error = "nargo execute failed".to_string();
+        queue.finish(id, JobStatus::Failed { error }, Duration::ZERO);
+        assert!(queue.status(id).is_none());
+    }
+}
@@ -0,0 +1,81 @@
+//! Calls the relayer pays for
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use stellar_xdr::curr::{Limits, ReadXdr, ScVal};
+
+/// Game calls relayed by default: the moves and exits of a game, but not
+/// starting one, which commits stakes, nor admin calls
+pub const DEFAULT_FUNCTIONS: &[&str] = &[
+    "submit_move",
+    "make_move",
+    "end_game",
+    "abort_due_to_verifier",
+    "open_channel",
+    "submit_channel_state",
+    "settle_channel",
+    "enable_optimistic",
+    "claim_move",
+    "challenge_claim",
+    "prove_claim",
+];
+
+/// A call a player asks the relayer to send
+#[derive(Clone, Debug, Deserialize)]
+pub struct Call {
+    pub function: String,
+    /// Base64 XDR of each `ScVal` argument
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+impl Call {
+    /// The call's arguments, if `function` is one the relayer pays for
+    pub fn decode(&self, functions: &[String]) -> Result<Vec<ScVal>> {
+        if !functions.contains(&self.function) {
+            bail!("{} is not relayed", self.function);
+        }
+        self.args
+            .iter()
+            .enumerate()
+            .map(|(i, arg)| {
+                ScVal::from_xdr_base64(arg, Limits::none())
+                    .with_context(|| format!("argument {i} is not base64 ScVal XDR"))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use stellar_xdr::curr::WriteXdr;
+
+    fn functions() -> Vec<String> {
+        DEFAULT_FUNCTIONS.iter().map(|f| f.to_string()).collect()
+    }
+
+    #[test]
+    fn test_decode() {
+        let args = [ScVal::U32(7), ScVal::U32(2), ScVal::U32(3)];
+        let call = Call {
+            function: "submit_move".into(),
+            args: args
+                .iter()
+                .map(|arg| arg.to_xdr_base64(Limits::none()).unwrap())
+                .collect(),
+        };
+        assert_eq!(call.decode(&functions()).unwrap(), args);
+
+        let start = Call {
+            function: "start_game".into(),
+            ..call.clone()
+        };
+        assert!(start.decode(&functions()).is_err());
+        let garbled = Call {
+            args: vec!["not xdr".into()],
+            ..call
+        };
+        assert!(garbled.decode(&functions()).is_err());
+    }
+}
@@ -0,0 +1,344 @@
+//! # Relayer
+//!
+//! Pays the fees of players' game calls, so a player whose account holds no
+//! XLM can still play:
+//! 1. A player posts the call it wants sent (function and arguments).
+//! 2. The server simulates it from one of its channel accounts and
+//!    publishes the authorization preimage each required player has to sign.
+//! 3. Players check the preimage (`GameClient::sign_relayed`) and post their
+//!    ed25519 signature over its hash.
+//! 4. Once every authorization is signed, the channel account signs the
+//!    transaction and the fee account wraps it in a fee bump it pays for.
+
+mod call;
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Context, Result};
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use stellar_xdr::curr::{Limits, ScAddress, ScVal, WriteXdr};
+use trap_grid_sdk::rpc::{RpcClient, DEFAULT_RPC_URL};
+use trap_grid_sdk::tx::AuthRequest;
+use trap_grid_sdk::{GameClient, Keypair};
+
+use crate::call::{Call, DEFAULT_FUNCTIONS};
+
+#[derive(Parser)]
+#[command(
+    name = "relayer",
+    version,
+    about = "Pays the fees of trap-grid players' calls"
+)]
+struct Cli {
+    /// Trap-grid contract id
+    #[arg(long, env = "TRAP_GRID_CONTRACT")]
+    contract_id: String,
+
+    /// Soroban RPC endpoint
+    #[arg(long, env = "STELLAR_RPC_URL", default_value = DEFAULT_RPC_URL)]
+    rpc_url: String,
+
+    /// Secret key (S...) of the account that pays the fee bumps
+    #[arg(long, env = "RELAYER_SECRET", hide_env_values = true)]
+    secret: String,
+
+    /// Secret keys (S...) of accounts whose sequence numbers the relayed
+    /// transactions use, taken in turn; defaults to the fee account. More
+    /// than one lets relayed calls be submitted side by side
+    #[arg(
+        long = "channel-secret",
+        env = "RELAYER_CHANNEL_SECRETS",
+        value_delimiter = ',',
+        hide_env_values = true
+    )]
+    channel_secrets: Vec<String>,
+
+    /// Functions relayed; defaults to the calls of a game in progress
+    #[arg(long, value_delimiter = ',')]
+    functions: Vec<String>,
+
+    /// Address to listen on
+    #[arg(long, env = "RELAYER_ADDR", default_value = "127.0.0.1:8083")]
+    listen: SocketAddr,
+
+    /// Seconds players have to sign before a relay is abandoned; must stay
+    /// below the ~8 minutes an authorization signature is valid
+    #[arg(long, default_value_t = 300)]
+    signature_timeout: u64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+enum RelayStatus {
+    /// Simulating the call
+    Preparing,
+    AwaitingSignatures,
+    Submitting,
+    /// Base64 XDR of the call's return value
+    Done {
+        result: String,
+    },
+    Failed {
+        error: String,
+    },
+}
+
+struct Relay {
+    function: String,
+    /// Index of the channel account the transaction is built from
+    channel: usize,
+    created: Instant,
+    status: RelayStatus,
+    request: Option<AuthRequest>,
+}
+
+struct AppState {
+    game: GameClient<'static>,
+    fee_source: Keypair,
+    channels: Vec<Keypair>,
+    functions: Vec<String>,
+    signature_timeout: Duration,
+    next_id: AtomicU64,
+    relays: Mutex<HashMap<u64, Relay>>,
+}
+
+impl AppState {
+    fn relays(&self) -> std::sync::MutexGuard<'_, HashMap<u64, Relay>> {
+        self.relays.lock().expect("relays lock poisoned")
+    }
+}
+
+struct ApiError(StatusCode, String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({ "error": self.1 }))).into_response()
+    }
+}
+
+fn not_found() -> ApiError {
+    ApiError(StatusCode::NOT_FOUND, "no such relay".to_string())
+}
+
+fn bad_request(err: anyhow::Error) -> ApiError {
+    ApiError(StatusCode::BAD_REQUEST, format!("{err:#}"))
+}
+
+/// Record the outcome of a blocking step of a relay
+fn settle(state: &AppState, id: u64, outcome: Result<RelayStatus>) {
+    let status = outcome.unwrap_or_else(|err| RelayStatus::Failed {
+        error: format!("{err:#}"),
+    });
+    if let Some(relay) = state.relays().get_mut(&id) {
+        relay.status = status;
+    }
+}
+
+/// Sign, fee-bump and send a relay's transaction in the background
+fn submit(state: Arc<AppState>, id: u64, channel: usize, request: AuthRequest) {
+    tokio::task::spawn_blocking(move || {
+        let outcome = state
+            .game
+            .submit_sponsored(&state.channels[channel], &state.fee_source, request)
+            .and_then(|result| Ok(result.to_xdr_base64(Limits::none())?))
+            .map(|result| RelayStatus::Done { result });
+        settle(&state, id, outcome);
+    });
+}
+
+/// Simulate a new relay's call in the background
+fn prepare(state: Arc<AppState>, id: u64, channel: usize, function: String, args: Vec<ScVal>) {
+    tokio::task::spawn_blocking(move || {
+        let prepared = state
+            .game
+            .prepare_call(&state.channels[channel], &function, args);
+        match prepared {
+            // A call no player authorizes, such as a defender's proven answer
+            Ok(request) if request.required().is_empty() => {
+                settle(&state, id, Ok(RelayStatus::Submitting));
+                submit(state.clone(), id, channel, request);
+            }
+            Ok(request) => {
+                if let Some(relay) = state.relays().get_mut(&id) {
+                    relay.request = Some(request);
+                    relay.status = RelayStatus::AwaitingSignatures;
+                }
+            }
+            Err(err) => settle(&state, id, Err(err)),
+        }
+    });
+}
+
+async fn post_relay(
+    State(state): State<Arc<AppState>>,
+    Json(call): Json<Call>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let args = call.decode(&state.functions).map_err(bad_request)?;
+    let id = state.next_id.fetch_add(1, Ordering::Relaxed);
+    let channel = id as usize % state.channels.len();
+    state.relays().insert(
+        id,
+        Relay {
+            function: call.function.clone(),
+            channel,
+            created: Instant::now(),
+            status: RelayStatus::Preparing,
+            request: None,
+        },
+    );
+    prepare(state.clone(), id, channel, call.function, args);
+    Ok(Json(json!({ "relay_id": id, "status": "preparing" })))
+}
+
+/// Abandon a relay whose players did not sign in time
+fn expire(relay: &mut Relay, timeout: Duration) {
+    if matches!(relay.status, RelayStatus::AwaitingSignatures) && relay.created.elapsed() > timeout
+    {
+        relay.status = RelayStatus::Failed {
+            error: "signatures were not collected in time".to_string(),
+        };
+        relay.request = None;
+    }
+}
+
+async fn get_relay(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<u64>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let mut relays = state.relays();
+    let relay = relays.get_mut(&id).ok_or_else(not_found)?;
+    expire(relay, state.signature_timeout);
+    let awaiting: Vec<_> = relay
+        .request
+        .iter()
+        .flat_map(AuthRequest::preimages)
+        .map(|(address, preimage)| {
+            json!({ "address": address.to_string(), "preimage": hex::encode(preimage) })
+        })
+        .collect();
+    Ok(Json(json!({
+        "relay_id": id,
+        "function": relay.function,
+        "state": relay.status,
+        "awaiting": awaiting,
+    })))
+}
+
+#[derive(Deserialize)]
+struct SignatureBody {
+    /// Signer's account (G...)
+    address: String,
+    /// Hex ed25519 signature over the SHA-256 of the preimage listed for the
+    /// address
+    signature: String,
+}
+
+fn parse_signature(body: &SignatureBody) -> Result<([u8; 32], [u8; 64])> {
+    let public_key = stellar_strkey::ed25519::PublicKey::from_string(&body.address)
+        .with_context(|| format!("invalid account {}", body.address))?;
+    let signature: [u8; 64] = hex::decode(&body.signature)
+        .context("signature is not hex")?
+        .try_into()
+        .map_err(|_| anyhow!("signature must be 64 bytes"))?;
+    Ok((public_key.0, signature))
+}
+
+async fn post_signature(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<u64>,
+    Json(body): Json<SignatureBody>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let (public_key, signature) = parse_signature(&body).map_err(bad_request)?;
+
+    let mut relays = state.relays();
+    let relay = relays.get_mut(&id).ok_or_else(not_found)?;
+    expire(relay, state.signature_timeout);
+    let Some(request) = relay.request.as_mut() else {
+        return Err(ApiError(
+            StatusCode::CONFLICT,
+            "relay is not awaiting signatures".to_string(),
+        ));
+    };
+    request
+        .add_signature(public_key, signature)
+        .map_err(bad_request)?;
+    if !request.required().is_empty() {
+        return Ok(Json(json!({ "status": "awaiting_signatures" })));
+    }
+
+    let request = relay.request.take().expect("checked above");
+    relay.status = RelayStatus::Submitting;
+    let channel = relay.channel;
+    drop(relays);
+    submit(state.clone(), id, channel, request);
+    Ok(Json(json!({ "status": "submitting" })))
+}
+
+fn router(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/relays", post(post_relay))
+        .route("/relays/{id}", get(get_relay))
+        .route("/relays/{id}/signatures", post(post_signature))
+        .with_state(state)
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    let fee_source = Keypair::from_secret(&cli.secret)?;
+    let channels = if cli.channel_secrets.is_empty() {
+        vec![Keypair::from_secret(&cli.secret)?]
+    } else {
+        cli.channel_secrets
+            .iter()
+            .map(|secret| Keypair::from_secret(secret))
+            .collect::<Result<_>>()?
+    };
+    let functions = if cli.functions.is_empty() {
+        DEFAULT_FUNCTIONS.iter().map(|f| f.to_string()).collect()
+    } else {
+        cli.functions
+    };
+    let contract: ScAddress = cli
+        .contract_id
+        .parse()
+        .with_context(|| format!("invalid contract id {}", cli.contract_id))?;
+    // Lives as long as the server
+    let rpc: &'static RpcClient = Box::leak(Box::new(RpcClient::new(&cli.rpc_url)));
+    let game = GameClient::new(rpc, contract)?;
+    if let Some(warning) = game.check_interface()? {
+        eprintln!("warning: {warning}");
+    }
+    let state = Arc::new(AppState {
+        game,
+        fee_source,
+        channels,
+        functions,
+        signature_timeout: Duration::from_secs(cli.signature_timeout),
+        next_id: AtomicU64::new(0),
+        relays: Mutex::new(HashMap::new()),
+    });
+
+    let listener = tokio::net::TcpListener::bind(cli.listen).await?;
+    println!(
+        "Relaying {} on http://{} (fee payer {}, {} channel account(s))",
+        state.functions.join(", "),
+        cli.listen,
+        state.fee_source.address(),
+        state.channels.len()
+    );
+    axum::serve(listener, router(state)).await?;
+    Ok(())
+}
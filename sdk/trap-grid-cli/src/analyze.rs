@@ -0,0 +1,171 @@
+//! `analyze` subcommand
+//!
+//! Loads finished games, from the contract or from a replay file saved by an
+//! earlier run, and prints the SDK's replay analysis: a hit-rate heatmap,
+//! defender placement tendencies and attacker efficiency. `--json` and
+//! `--csv` export the same numbers for the dApp's stats pages.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use clap::Args;
+use serde::Serialize;
+use trap_grid_sdk::analysis::{self, Analysis, Replay};
+use trap_grid_sdk::GRID_SIZE;
+
+use crate::network::NetworkArgs;
+
+#[derive(Args)]
+pub struct AnalyzeArgs {
+    /// Trap-grid contract id, overriding the profile's (or set TRAP_GRID_CONTRACT)
+    #[arg(long)]
+    pub contract_id: Option<String>,
+
+    #[command(flatten)]
+    pub network: NetworkArgs,
+
+    /// Finished session to fetch from the contract (repeatable)
+    #[arg(long, conflicts_with = "replays")]
+    pub session: Vec<u32>,
+
+    /// JSON replay file written by --save-replays
+    #[arg(long, required_unless_present = "session")]
+    pub replays: Option<PathBuf>,
+
+    /// Save the fetched replays, since sessions expire from contract storage
+    #[arg(long)]
+    pub save_replays: Option<PathBuf>,
+
+    /// Print the analysis as JSON
+    #[arg(long, conflicts_with = "csv")]
+    pub json: bool,
+
+    /// Write heatmap.csv, defenders.csv and attackers.csv to this directory
+    #[arg(long)]
+    pub csv: Option<PathBuf>,
+}
+
+fn load(args: &AnalyzeArgs) -> Result<Vec<Replay>> {
+    if let Some(path) = &args.replays {
+        let json = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        return serde_json::from_str(&json)
+            .with_context(|| format!("{} is not a replay file", path.display()));
+    }
+    let network = args.network.resolve(args.contract_id.as_deref())?;
+    let contract = network.contract()?;
+    let rpc = network.rpc();
+    args.session
+        .iter()
+        .map(|&session_id| Replay::fetch(&rpc, &contract, session_id))
+        .collect()
+}
+
+fn write_csv<T: Serialize>(path: &Path, rows: &[T]) -> Result<()> {
+    let mut writer = csv::Writer::from_path(path)
+        .with_context(|| format!("failed to write {}", path.display()))?;
+    for row in rows {
+        writer.serialize(row)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Hit rate per cell as a board of percentages, `-` for cells never played
+fn heatmap_board(analysis: &Analysis) -> String {
+    let mut out = String::from("   ");
+    for x in 0..GRID_SIZE {
+        out.push_str(&format!("{x:>5}"));
+    }
+    out.push('\n');
+    for y in 0..GRID_SIZE {
+        out.push_str(&format!("{y:>3}"));
+        for x in 0..GRID_SIZE {
+            let cell = &analysis.heatmap[(x * GRID_SIZE + y) as usize];
+            if cell.plays == 0 {
+                out.push_str("    -");
+            } else {
+                out.push_str(&format!("{:>4.0}%", cell.hit_rate * 100.0));
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+fn print(analysis: &Analysis) {
+    println!("{} games\n\nhit rate per cell:", analysis.games);
+    print!("{}", heatmap_board(analysis));
+
+    println!("\ndefenders (share of revealed traps):");
+    for d in &analysis.defenders {
+        println!(
+            "  {}: {}/{} won, {} revealed, corner {:.0}%, edge {:.0}%, inner {:.0}%",
+            d.defender,
+            d.wins,
+            d.games,
+            d.revealed_traps,
+            d.corner_share * 100.0,
+            d.edge_share * 100.0,
+            d.inner_share * 100.0
+        );
+    }
+
+    println!("\nattackers:");
+    for a in &analysis.attackers {
+        println!(
+            "  {}: {}/{} won, {} hits in {} moves ({:.1}%), first hit at move {}",
+            a.attacker,
+            a.wins,
+            a.games,
+            a.hits,
+            a.moves,
+            a.hit_rate * 100.0,
+            a.mean_first_hit
+                .map_or("-".to_string(), |m| format!("{m:.1}"))
+        );
+    }
+}
+
+pub fn run(args: AnalyzeArgs) -> Result<()> {
+    let replays = load(&args)?;
+    if let Some(path) = &args.save_replays {
+        fs::write(path, serde_json::to_string_pretty(&replays)?)
+            .with_context(|| format!("failed to write {}", path.display()))?;
+    }
+    let analysis = analysis::analyze(&replays);
+
+    if let Some(dir) = &args.csv {
+        fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+        write_csv(&dir.join("heatmap.csv"), &analysis.heatmap)?;
+        write_csv(&dir.join("defenders.csv"), &analysis.defenders)?;
+        write_csv(&dir.join("attackers.csv"), &analysis.attackers)?;
+        println!(
+            "{} games analyzed, CSV written to {}",
+            analysis.games,
+            dir.display()
+        );
+    } else if args.json {
+        println!("{}", serde_json::to_string_pretty(&analysis)?);
+    } else {
+        print(&analysis);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_heatmap_board() {
+        let analysis = analysis::analyze(&[]);
+        let board = heatmap_board(&analysis);
+        assert_eq!(board.lines().count(), GRID_SIZE as usize + 1);
+        assert_eq!(
+            board.lines().nth(1).unwrap(),
+            format!("  0{}", "    -".repeat(8))
+        );
+    }
+}
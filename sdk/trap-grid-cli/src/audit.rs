@@ -0,0 +1,361 @@
+//! `audit` subcommand
+//!
+//! Estimates how long the answer behind a puzzle-hunt stage commitment would
+//! hold out against brute force, before the commitment goes on-chain with a
+//! prize attached. A hash commitment hides nothing from an attacker who can
+//! guess the answer: they hash candidates until one matches. The search space
+//! is the smaller of the answer's character-set entropy and its position in a
+//! dictionary, plus the bits of a secret salt.
+
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use clap::Args;
+use serde::Serialize;
+
+use crate::secret;
+
+/// Words assumed in an attacker's dictionary when a one-word answer is
+/// audited without `--wordlist`
+const DEFAULT_DICTIONARY_WORDS: u64 = 500_000;
+
+#[derive(Args)]
+pub struct AuditArgs {
+    /// Answer to audit. Prompted for without echo when omitted
+    #[arg(long, conflicts_with = "stdin")]
+    pub answer: Option<String>,
+
+    /// Read the answer from the first line of stdin
+    #[arg(long)]
+    pub stdin: bool,
+
+    /// Dictionary an attacker would try first, one word per line
+    #[arg(long)]
+    pub wordlist: Option<PathBuf>,
+
+    /// Salt hashed with the answer into the commitment, if any
+    #[arg(long)]
+    pub salt: Option<String>,
+
+    /// The salt is published with the commitment: it stops precomputed
+    /// tables but adds no guesses
+    #[arg(long, requires = "salt")]
+    pub public_salt: bool,
+
+    /// Attacker guesses per second (default: one GPU hashing SHA-256)
+    #[arg(long, default_value_t = 1e9)]
+    pub rate: f64,
+
+    /// Print the audit as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Risk {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Risk {
+    fn from_seconds(seconds: f64) -> Self {
+        const DAY: f64 = 86_400.0;
+        const YEAR: f64 = 365.25 * DAY;
+        if seconds < DAY {
+            Risk::Critical
+        } else if seconds < YEAR {
+            Risk::High
+        } else if seconds < 1000.0 * YEAR {
+            Risk::Medium
+        } else {
+            Risk::Low
+        }
+    }
+
+    fn advice(self) -> &'static str {
+        match self {
+            Risk::Critical => {
+                "do not attach a prize: the answer falls to a dictionary or short search"
+            }
+            Risk::High => "a determined attacker recovers the answer before the hunt ends",
+            Risk::Medium => {
+                "safe for small prizes; add a secret salt or a longer answer for large ones"
+            }
+            Risk::Low => "out of reach of brute force",
+        }
+    }
+}
+
+/// Where the answer sits in the dictionary
+#[derive(Debug, PartialEq, Serialize)]
+pub struct DictionaryHit {
+    /// 1-based line of the answer, or `None` when assumed without a wordlist
+    pub line: Option<usize>,
+    pub words: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Audit {
+    pub length: usize,
+    pub classes: Vec<&'static str>,
+    pub charset: u32,
+    pub charset_bits: f64,
+    pub dictionary: Option<DictionaryHit>,
+    pub salt_bits: f64,
+    pub salted: bool,
+    /// log2 of the guesses needed to try every candidate
+    pub search_bits: f64,
+    /// Expected time to find the answer: half the search space
+    pub seconds: f64,
+    pub risk: Risk,
+}
+
+/// A character class: name, size and membership test
+type Class = (&'static str, u32, fn(char) -> bool);
+
+/// Character classes present in `text` and the size of their union
+fn charset(text: &str) -> (Vec<&'static str>, u32) {
+    let classes: [Class; 5] = [
+        ("lowercase", 26, |c| c.is_ascii_lowercase()),
+        ("uppercase", 26, |c| c.is_ascii_uppercase()),
+        ("digits", 10, |c| c.is_ascii_digit()),
+        ("symbols", 33, |c| c.is_ascii_punctuation() || c == ' '),
+        ("non-ascii", 100, |c| !c.is_ascii()),
+    ];
+    let mut names = Vec::new();
+    let mut size = 0;
+    for (name, count, matches) in classes {
+        if text.chars().any(matches) {
+            names.push(name);
+            size += count;
+        }
+    }
+    (names, size)
+}
+
+/// Bits to guess `text` character by character
+fn charset_bits(text: &str) -> f64 {
+    let (_, size) = charset(text);
+    text.chars().count() as f64 * f64::from(size.max(1)).log2()
+}
+
+/// The answer's line in `words`, compared case-insensitively
+fn lookup(answer: &str, words: &str) -> Option<DictionaryHit> {
+    let answer = answer.trim().to_lowercase();
+    let mut count = 0;
+    let mut line = None;
+    for word in words.lines().map(str::trim).filter(|w| !w.is_empty()) {
+        count += 1;
+        if line.is_none() && word.to_lowercase() == answer {
+            line = Some(count);
+        }
+    }
+    line.map(|line| DictionaryHit {
+        line: Some(line),
+        words: count as u64,
+    })
+}
+
+/// A single word of letters, the shape dictionaries are made of
+fn looks_like_word(answer: &str) -> bool {
+    !answer.is_empty() && answer.chars().all(char::is_alphabetic)
+}
+
+pub fn audit(answer: &str, wordlist: Option<&str>, salt: Option<(&str, bool)>, rate: f64) -> Audit {
+    let (classes, charset_size) = charset(answer);
+    let salt_bits = match salt {
+        Some((salt, false)) => charset_bits(salt),
+        _ => 0.0,
+    };
+    let charset_bits = charset_bits(answer);
+
+    let dictionary = match wordlist {
+        Some(words) => lookup(answer, words),
+        None if looks_like_word(answer) => Some(DictionaryHit {
+            line: None,
+            words: DEFAULT_DICTIONARY_WORDS,
+        }),
+        None => None,
+    };
+    let answer_bits = match &dictionary {
+        Some(hit) => charset_bits.min((hit.words as f64).log2()),
+        None => charset_bits,
+    };
+
+    let search_bits = answer_bits + salt_bits;
+    let seconds = (search_bits - 1.0).exp2() / rate;
+
+    // Unsalted commitments to the same answer share one precomputed table
+    let mut risk = Risk::from_seconds(seconds);
+    if salt.is_none() {
+        risk = risk.max(Risk::Medium);
+    }
+
+    Audit {
+        length: answer.chars().count(),
+        classes,
+        charset: charset_size,
+        charset_bits,
+        dictionary,
+        salt_bits,
+        salted: salt.is_some(),
+        search_bits,
+        seconds,
+        risk,
+    }
+}
+
+/// `seconds` in the largest unit that keeps the number readable
+fn duration(seconds: f64) -> String {
+    const UNITS: [(&str, f64); 5] = [
+        ("years", 365.25 * 86_400.0),
+        ("days", 86_400.0),
+        ("hours", 3_600.0),
+        ("minutes", 60.0),
+        ("seconds", 1.0),
+    ];
+    if seconds < 1.0 {
+        return "under a second".to_string();
+    }
+    let (unit, size) = UNITS
+        .iter()
+        .find(|(_, size)| seconds >= *size)
+        .unwrap_or(&UNITS[4]);
+    let value = seconds / size;
+    if value >= 1e6 {
+        format!("{value:.1e} {unit}")
+    } else {
+        format!("{value:.0} {unit}")
+    }
+}
+
+fn report(audit: &Audit, rate: f64) -> String {
+    let mut out = format!(
+        "answer:     {} characters, {} ({} symbols): {:.1} bits\n",
+        audit.length,
+        audit.classes.join(" + "),
+        audit.charset,
+        audit.charset_bits
+    );
+    out.push_str(&match &audit.dictionary {
+        Some(DictionaryHit {
+            line: Some(line),
+            words,
+        }) => format!(
+            "dictionary: line {line} of {words} words: {:.1} bits\n",
+            (*words as f64).log2()
+        ),
+        Some(DictionaryHit { line: None, words }) => format!(
+            "dictionary: one word, assumed in a {words}-word dictionary: {:.1} bits (pass --wordlist to check)\n",
+            (*words as f64).log2()
+        ),
+        None => "dictionary: not found\n".to_string(),
+    });
+    out.push_str(&if !audit.salted {
+        "salt:       none; equal answers have equal commitments\n".to_string()
+    } else if audit.salt_bits == 0.0 {
+        "salt:       public; adds no guesses\n".to_string()
+    } else {
+        format!("salt:       secret: {:.1} bits\n", audit.salt_bits)
+    });
+    out.push_str(&format!(
+        "search:     {:.1} bits, expected {} at {rate:.0e} guesses/s\n",
+        audit.search_bits,
+        duration(audit.seconds)
+    ));
+    out.push_str(&format!(
+        "risk:       {:?}: {}\n",
+        audit.risk,
+        audit.risk.advice()
+    ));
+    out
+}
+
+pub fn run(args: AuditArgs) -> Result<()> {
+    let answer = secret::read_secret(args.answer, args.stdin, "Answer: ")?;
+    let words = args
+        .wordlist
+        .as_ref()
+        .map(|path| {
+            fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))
+        })
+        .transpose()?;
+    let salt = args.salt.as_deref().map(|salt| (salt, args.public_salt));
+
+    let audit = audit(&answer, words.as_deref(), salt, args.rate);
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&audit)?);
+    } else {
+        print!("{}", report(&audit, args.rate));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const WORDS: &str = "apple\nbanana\n\nCherry\n";
+
+    #[test]
+    fn test_charset() {
+        assert_eq!(charset("treasure"), (vec!["lowercase"], 26));
+        assert_eq!(
+            charset("Tr3asure!"),
+            (vec!["lowercase", "uppercase", "digits", "symbols"], 95)
+        );
+        assert_eq!(charset("café").1, 126);
+    }
+
+    #[test]
+    fn test_lookup() {
+        assert_eq!(
+            lookup(" cherry", WORDS),
+            Some(DictionaryHit {
+                line: Some(3),
+                words: 3
+            })
+        );
+        assert_eq!(lookup("durian", WORDS), None);
+    }
+
+    #[test]
+    fn test_dictionary_word_is_critical() {
+        let audit = audit("banana", Some(WORDS), None, 1e9);
+        assert!((audit.search_bits - 3f64.log2()).abs() < 1e-9);
+        assert_eq!(audit.risk, Risk::Critical);
+
+        // A one-word answer is assumed guessable without a wordlist
+        let audit = super::audit("lighthouse", None, None, 1e9);
+        assert_eq!(audit.dictionary.unwrap().words, DEFAULT_DICTIONARY_WORDS);
+        assert_eq!(audit.risk, Risk::Critical);
+    }
+
+    #[test]
+    fn test_salt() {
+        let salt = "9f8e7d6c5b4a39281706f5e4d3c2b1a0";
+        let secret = audit("banana", Some(WORDS), Some((salt, false)), 1e9);
+        assert!(secret.salt_bits > 100.0);
+        assert_eq!(secret.risk, Risk::Low);
+
+        let public = audit("banana", Some(WORDS), Some((salt, true)), 1e9);
+        assert_eq!(public.salt_bits, 0.0);
+        assert_eq!(public.risk, Risk::Critical);
+
+        // Unsalted answers are never rated low
+        let long = audit("correct horse battery staple 1987!", None, None, 1e9);
+        assert_eq!(long.risk, Risk::Medium);
+    }
+
+    #[test]
+    fn test_duration() {
+        assert_eq!(duration(0.5), "under a second");
+        assert_eq!(duration(90.0), "2 minutes");
+        assert_eq!(duration(3.0 * 86_400.0), "3 days");
+        assert_eq!(duration(1e20), "3.2e12 years");
+    }
+}
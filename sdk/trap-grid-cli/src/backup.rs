@@ -0,0 +1,272 @@
+//! `grid export` / `grid import` subcommands
+//!
+//! A backup code carries everything a defender needs to keep playing on
+//! another machine: the layout, its salt and the session id. The code is
+//! uppercase hex behind a `TG1-` prefix, which fits QR alphanumeric mode. It
+//! ends in a SHA-256 checksum, so a mistyped or truncated code is rejected.
+//! The contract keeps no layout commitment, so `grid import` compares the
+//! restored layout with every verified move of the session instead.
+//!
+//! Payload: version (1 byte), session id (u32), trap bitmap (u64, bit `i` is
+//! cell `i`), salt flag (1 byte) and salt (32 bytes, only when flagged), then
+//! the first 4 bytes of the SHA-256 of all of that.
+
+use std::path::PathBuf;
+
+use anyhow::{bail, ensure, Context, Result};
+use clap::Args;
+use sha2::{Digest, Sha256};
+use trap_grid_sdk::{client, Move};
+
+use crate::encoding::{hex_field, parse_field, FIELD_BYTES};
+use crate::grid::{self, TrapLayout, GRID_SIZE, NUM_CELLS};
+use crate::network::NetworkArgs;
+use crate::sealed;
+
+const PREFIX: &str = "TG1-";
+const VERSION: u8 = 1;
+const CHECKSUM_LEN: usize = 4;
+
+#[derive(Args)]
+pub struct ExportArgs {
+    /// Layout file, plain or written by `grid save --encrypt`
+    #[arg(long)]
+    pub layout: PathBuf,
+
+    /// Session the layout is playing
+    #[arg(long)]
+    pub session: u32,
+}
+
+#[derive(Args)]
+pub struct ImportArgs {
+    /// Backup code printed by `grid export`
+    pub code: String,
+
+    /// Write the restored layout here
+    #[arg(long)]
+    pub out: PathBuf,
+
+    /// Skip checking the layout against the session's moves on chain
+    #[arg(long)]
+    pub offline: bool,
+
+    /// Trap-grid contract id, overriding the profile's (or set TRAP_GRID_CONTRACT)
+    #[arg(long)]
+    pub contract_id: Option<String>,
+
+    #[command(flatten)]
+    pub network: NetworkArgs,
+}
+
+/// What a backup code restores
+#[derive(Debug, PartialEq)]
+pub struct Backup {
+    pub session_id: u32,
+    pub layout: TrapLayout,
+}
+
+fn checksum(payload: &[u8]) -> [u8; CHECKSUM_LEN] {
+    Sha256::digest(payload)[..CHECKSUM_LEN]
+        .try_into()
+        .expect("digest is longer than the checksum")
+}
+
+impl Backup {
+    pub fn encode(&self) -> Result<String> {
+        self.layout.validate()?;
+        ensure!(
+            self.layout.trap_values.len() == NUM_CELLS,
+            "backup codes hold {GRID_SIZE}x{GRID_SIZE} layouts; save larger grids with `grid save`"
+        );
+        let bitmap = self
+            .layout
+            .trap_values
+            .iter()
+            .enumerate()
+            .fold(0u64, |bits, (i, value)| bits | (u64::from(*value) << i));
+
+        let mut payload = vec![VERSION];
+        payload.extend(self.session_id.to_be_bytes());
+        payload.extend(bitmap.to_be_bytes());
+        match &self.layout.salt {
+            Some(salt) => {
+                payload.push(1);
+                payload.extend(parse_field(salt).context("invalid layout salt")?);
+            }
+            None => payload.push(0),
+        }
+        payload.extend(checksum(&payload));
+        Ok(format!("{PREFIX}{}", hex::encode_upper(payload)))
+    }
+
+    pub fn decode(code: &str) -> Result<Self> {
+        let code: String = code.chars().filter(|c| !c.is_whitespace()).collect();
+        let hex = code
+            .strip_prefix(PREFIX)
+            .with_context(|| format!("backup codes start with {PREFIX}"))?;
+        let bytes = hex::decode(hex).context("backup code is not hex")?;
+        ensure!(bytes.len() > CHECKSUM_LEN, "backup code is truncated");
+        let (payload, sum) = bytes.split_at(bytes.len() - CHECKSUM_LEN);
+        ensure!(
+            checksum(payload) == sum,
+            "backup code checksum does not match; check for typos"
+        );
+
+        let [version, rest @ ..] = payload else {
+            bail!("backup code is truncated");
+        };
+        ensure!(
+            *version == VERSION,
+            "unsupported backup code version {version}"
+        );
+        ensure!(rest.len() >= 13, "backup code is truncated");
+        let session_id = u32::from_be_bytes(rest[..4].try_into().unwrap());
+        let bitmap = u64::from_be_bytes(rest[4..12].try_into().unwrap());
+        let salt = match (rest[12], &rest[13..]) {
+            (0, []) => None,
+            (1, salt) if salt.len() == FIELD_BYTES => Some(hex_field(&salt.try_into().unwrap())),
+            _ => bail!("backup code has a malformed salt"),
+        };
+
+        Ok(Self {
+            session_id,
+            layout: TrapLayout {
+                trap_values: (0..NUM_CELLS).map(|i| ((bitmap >> i) & 1) as u8).collect(),
+                salt,
+            },
+        })
+    }
+}
+
+/// Check that `layout` agrees with every verified move: a hit must be a trap
+fn check_moves(layout: &TrapLayout, moves: &[Move]) -> Result<()> {
+    for m in moves.iter().filter(|m| m.verified) {
+        if layout.is_trap(m.x, m.y) != m.is_hit {
+            bail!(
+                "cell ({}, {}) was proven a {} but the restored layout disagrees; \
+                 this is not the session's layout",
+                m.x,
+                m.y,
+                if m.is_hit { "hit" } else { "miss" }
+            );
+        }
+    }
+    Ok(())
+}
+
+pub fn export(args: ExportArgs) -> Result<()> {
+    let backup = Backup {
+        session_id: args.session,
+        layout: sealed::read_layout(&args.layout)?,
+    };
+    println!("{}", backup.encode()?);
+    Ok(())
+}
+
+pub fn import(args: ImportArgs) -> Result<()> {
+    let backup = Backup::decode(&args.code)?;
+    if !args.offline {
+        let network = args.network.resolve(args.contract_id.as_deref())?;
+        let moves = client::fetch_moves(&network.rpc(), &network.contract()?, backup.session_id)?;
+        check_moves(&backup.layout, &moves)?;
+        println!(
+            "Layout agrees with the {} moves of session {}",
+            moves.len(),
+            backup.session_id
+        );
+    }
+    backup.layout.save(&args.out)?;
+    print!(
+        "{}",
+        grid::render_board(backup.layout.grid_size(), Some(&backup.layout), &[], false)
+    );
+    println!(
+        "Session {} layout written to {}",
+        backup.session_id,
+        args.out.display()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::grid::cell_index;
+    use crate::seed;
+
+    fn backup() -> Backup {
+        Backup {
+            session_id: 42,
+            layout: seed::derive_layout("correct horse battery staple", 10),
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let code = backup().encode().unwrap();
+        assert!(code.starts_with("TG1-"));
+        assert_eq!(code.len(), PREFIX.len() + 2 * (1 + 4 + 8 + 1 + 32 + 4));
+        assert!(code[PREFIX.len()..]
+            .chars()
+            .all(|c| c.is_ascii_digit() || c.is_ascii_uppercase()));
+        assert_eq!(Backup::decode(&code).unwrap(), backup());
+
+        let unsalted = Backup {
+            session_id: 7,
+            layout: TrapLayout {
+                trap_values: vec![1; NUM_CELLS],
+                salt: None,
+            },
+        };
+        let code = unsalted.encode().unwrap();
+        let (head, tail) = code.split_at(20);
+        assert_eq!(
+            Backup::decode(&format!("{head}\n {tail}")).unwrap(),
+            unsalted
+        );
+
+        let large = Backup {
+            session_id: 7,
+            layout: TrapLayout {
+                trap_values: vec![0; 256],
+                salt: None,
+            },
+        };
+        assert!(large.encode().is_err());
+    }
+
+    #[test]
+    fn test_decode_rejects_damage() {
+        let code = backup().encode().unwrap();
+        let mut typo = code.clone().into_bytes();
+        typo[10] = if typo[10] == b'0' { b'1' } else { b'0' };
+        let typo = String::from_utf8(typo).unwrap();
+        assert!(Backup::decode(&typo)
+            .unwrap_err()
+            .to_string()
+            .contains("checksum"));
+        assert!(Backup::decode(&code[..code.len() - 2]).is_err());
+        assert!(Backup::decode(&code[PREFIX.len()..]).is_err());
+    }
+
+    #[test]
+    fn test_check_moves() {
+        let layout = backup().layout;
+        let trap = (0..NUM_CELLS)
+            .find(|i| layout.trap_values[*i] == 1)
+            .unwrap() as u32;
+        let (x, y) = (trap / 8, trap % 8);
+        assert_eq!(cell_index(GRID_SIZE, x, y), trap as usize);
+        let proven = |is_hit, verified| Move {
+            x,
+            y,
+            is_hit,
+            verified,
+        };
+
+        assert!(check_moves(&layout, &[proven(true, true)]).is_ok());
+        assert!(check_moves(&layout, &[proven(false, false)]).is_ok());
+        assert!(check_moves(&layout, &[proven(false, true)]).is_err());
+    }
+}
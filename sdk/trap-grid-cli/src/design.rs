@@ -0,0 +1,372 @@
+//! `grid design` subcommand
+//!
+//! A terminal board editor for defenders: move with the arrow keys (or
+//! `hjkl`), toggle traps with space and save with `s`. The trap count and
+//! balance warnings update as traps are placed. Saving writes the layout file
+//! and prints its Merkle root; with `--session` the grid is proven with the
+//! grid-setup circuit and the game is then started.
+
+use std::io::{self, IsTerminal};
+use std::path::PathBuf;
+
+use anyhow::{bail, Context, Result};
+use clap::Args;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use trap_grid_sdk::commitment::{layout_salt, TrapTree};
+use trap_grid_sdk::prover::{self, Prover};
+use trap_grid_sdk::GameClient;
+
+use crate::encoding::format_field;
+use trap_grid_encoding::{MAX_GRID_SIZE, MIN_GRID_SIZE};
+
+use crate::grid::{self, cell_index, TrapLayout, GRID_SIZE};
+use crate::keys::KeyStore;
+use crate::network::NetworkArgs;
+
+#[derive(Args)]
+pub struct DesignArgs {
+    /// Layout file to write; its traps are loaded first when it exists
+    #[arg(long)]
+    pub out: PathBuf,
+
+    /// Side of a new layout's grid; an existing layout keeps its own
+    #[arg(long, default_value_t = GRID_SIZE, value_parser = clap::value_parser!(u32).range(MIN_GRID_SIZE as i64..=MAX_GRID_SIZE as i64))]
+    pub grid_size: u32,
+
+    /// Number of traps you mean to place; warns until the count matches
+    #[arg(long)]
+    pub traps: Option<u32>,
+
+    /// Start this session with the saved layout
+    #[arg(long, requires_all = ["defender", "attacker"])]
+    pub session: Option<u32>,
+
+    /// Defender key name (see `trap-grid keys`)
+    #[arg(long)]
+    pub defender: Option<String>,
+
+    /// Attacker address
+    #[arg(long)]
+    pub attacker: Option<String>,
+
+    /// Points committed by each player
+    #[arg(long, default_value_t = 100)]
+    pub points: i128,
+
+    /// Trap-grid contract id, overriding the profile's (or set TRAP_GRID_CONTRACT)
+    #[arg(long)]
+    pub contract_id: Option<String>,
+
+    #[command(flatten)]
+    pub network: NetworkArgs,
+
+    /// Grid-setup circuit directory, for the proof start_game requires
+    #[arg(long, default_value = "../circuits/grid-setup")]
+    pub setup_circuit_dir: PathBuf,
+
+    /// bb.js entrypoint (relative paths are resolved against --setup-circuit-dir)
+    #[arg(long, default_value = prover::DEFAULT_BBJS)]
+    pub bbjs: PathBuf,
+}
+
+#[derive(Debug, PartialEq)]
+enum Action {
+    Continue,
+    Save,
+    Quit,
+}
+
+struct Designer {
+    layout: TrapLayout,
+    /// Cursor as (`x`, `y`): rows are `x`, like `grid show`
+    cursor: (u32, u32),
+    target: Option<usize>,
+}
+
+impl Designer {
+    fn new(layout: TrapLayout, target: Option<u32>) -> Self {
+        Self {
+            layout,
+            cursor: (0, 0),
+            target: target.map(|t| t as usize),
+        }
+    }
+
+    fn handle(&mut self, key: KeyCode) -> Action {
+        let size = self.layout.grid_size();
+        let (x, y) = &mut self.cursor;
+        match key {
+            KeyCode::Up | KeyCode::Char('k') => *x = x.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => *x = (*x + 1).min(size - 1),
+            KeyCode::Left | KeyCode::Char('h') => *y = y.saturating_sub(1),
+            KeyCode::Right | KeyCode::Char('l') => *y = (*y + 1).min(size - 1),
+            KeyCode::Char(' ') | KeyCode::Enter => {
+                let cell = &mut self.layout.trap_values[cell_index(size, *x, *y)];
+                *cell ^= 1;
+            }
+            KeyCode::Char('c') => self.layout.trap_values.fill(0),
+            KeyCode::Char('s') => return Action::Save,
+            KeyCode::Char('q') | KeyCode::Esc => return Action::Quit,
+            _ => {}
+        }
+        Action::Continue
+    }
+}
+
+/// Balance problems with the layout as it stands
+fn warnings(layout: &TrapLayout, target: Option<usize>) -> Vec<String> {
+    let traps = layout.trap_count();
+    let size = layout.grid_size();
+    let mut warnings = Vec::new();
+    if let Some(target) = target.filter(|t| *t != traps) {
+        warnings.push(format!("{traps} of {target} traps placed"));
+    }
+    if traps == 0 {
+        warnings.push("no traps: the attacker can never win".to_string());
+    }
+    if traps > layout.trap_values.len() / 2 {
+        warnings.push("more than half the cells are traps: random guesses win".to_string());
+    }
+    for x in 0..size {
+        if (0..size).all(|y| layout.is_trap(x, y)) {
+            warnings.push(format!("row {x} is all traps"));
+        }
+    }
+    for y in 0..size {
+        if (0..size).all(|x| layout.is_trap(x, y)) {
+            warnings.push(format!("column {y} is all traps"));
+        }
+    }
+    warnings
+}
+
+fn draw(frame: &mut Frame, designer: &Designer) {
+    let size = designer.layout.grid_size();
+    let [board_area, status_area] =
+        Layout::vertical([Constraint::Length(size as u16 + 3), Constraint::Min(0)])
+            .areas(frame.area());
+
+    let mut header = String::from("   y");
+    for y in 0..size {
+        header.push_str(&format!(" {y}"));
+    }
+    let mut board = vec![Line::from(header)];
+    for x in 0..size {
+        let mut row = vec![Span::raw(format!(" {x}  "))];
+        for y in 0..size {
+            row.push(Span::raw(" "));
+            let mut cell = if designer.layout.is_trap(x, y) {
+                Span::styled("■", Style::default().fg(Color::Red))
+            } else {
+                Span::raw("·")
+            };
+            if designer.cursor == (x, y) {
+                cell = cell.patch_style(Style::default().add_modifier(Modifier::REVERSED));
+            }
+            row.push(cell);
+        }
+        board.push(Line::from(row));
+    }
+    frame.render_widget(
+        Paragraph::new(board).block(Block::bordered().title(" trap grid ")),
+        board_area,
+    );
+
+    let mut status = vec![Line::from(format!(
+        "traps: {}   cell: ({}, {})",
+        designer.layout.trap_count(),
+        designer.cursor.0,
+        designer.cursor.1
+    ))];
+    for warning in warnings(&designer.layout, designer.target) {
+        status.push(Line::styled(
+            format!("warning: {warning}"),
+            Style::default().fg(Color::Yellow),
+        ));
+    }
+    status.push(Line::from(
+        "arrows/hjkl move   space toggle   c clear   s save   q quit",
+    ));
+    frame.render_widget(Paragraph::new(status), status_area);
+}
+
+/// Run the editor until the defender saves (`true`) or quits (`false`)
+fn edit(terminal: &mut DefaultTerminal, designer: &mut Designer) -> Result<bool> {
+    loop {
+        terminal.draw(|frame| draw(frame, designer))?;
+        if let Event::Key(key) = event::read()? {
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match designer.handle(key.code) {
+                Action::Continue => {}
+                Action::Save => return Ok(true),
+                Action::Quit => return Ok(false),
+            }
+        }
+    }
+}
+
+pub fn run(args: DesignArgs) -> Result<()> {
+    if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        bail!("grid design needs a terminal");
+    }
+    let layout = if args.out.exists() {
+        TrapLayout::load(&args.out)?
+    } else {
+        TrapLayout {
+            trap_values: vec![0; (args.grid_size * args.grid_size) as usize],
+            salt: None,
+        }
+    };
+    let mut designer = Designer::new(layout, args.traps);
+
+    let mut terminal = ratatui::init();
+    let saved = edit(&mut terminal, &mut designer);
+    ratatui::restore();
+    if !saved? {
+        println!("Quit without saving");
+        return Ok(());
+    }
+
+    let layout = designer.layout;
+    layout.save(&args.out)?;
+    print!(
+        "{}",
+        grid::render_board(layout.grid_size(), Some(&layout), &[], false)
+    );
+    let root = TrapTree::new(&layout.trap_values)?.root();
+    println!("Layout written to {}", args.out.display());
+    println!("Merkle root: {}", format_field(&root));
+    for warning in warnings(&layout, designer.target) {
+        println!("warning: {warning}");
+    }
+
+    if let Some(session_id) = args.session {
+        let defender = args
+            .defender
+            .as_deref()
+            .context("--session needs --defender")?;
+        let attacker = args
+            .attacker
+            .as_deref()
+            .context("--session needs --attacker")?;
+        let network = args.network.resolve(args.contract_id.as_deref())?;
+        let contract = network.contract()?;
+        let rpc = network.rpc();
+        let game = GameClient::new(&rpc, contract.clone())?;
+        if let Some(warning) = game.check_interface()? {
+            eprintln!("warning: {warning}");
+        }
+        let defender = KeyStore::open()?.load(defender)?;
+        let setup = Prover::new(&args.setup_circuit_dir, &args.bbjs)?.prove_setup(
+            &layout,
+            &layout_salt(&layout)?,
+            &defender.address(),
+        )?;
+        game.start_game(
+            &defender,
+            &defender,
+            attacker,
+            session_id,
+            (args.points, args.points),
+            &setup,
+        )?;
+        println!("Session {session_id} started on {contract}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    use super::*;
+    use crate::grid::NUM_CELLS;
+
+    fn empty() -> TrapLayout {
+        TrapLayout {
+            trap_values: vec![0; NUM_CELLS],
+            salt: None,
+        }
+    }
+
+    #[test]
+    fn test_handle_moves_and_toggles() {
+        let mut designer = Designer::new(empty(), None);
+        assert_eq!(designer.handle(KeyCode::Up), Action::Continue);
+        assert_eq!(designer.cursor, (0, 0));
+        for _ in 0..10 {
+            designer.handle(KeyCode::Char('j'));
+        }
+        designer.handle(KeyCode::Right);
+        assert_eq!(designer.cursor, (GRID_SIZE - 1, 1));
+
+        designer.handle(KeyCode::Char(' '));
+        assert!(designer.layout.is_trap(GRID_SIZE - 1, 1));
+        designer.handle(KeyCode::Enter);
+        assert_eq!(designer.layout.trap_count(), 0);
+
+        designer.handle(KeyCode::Char(' '));
+        designer.handle(KeyCode::Char('c'));
+        assert_eq!(designer.layout.trap_count(), 0);
+        assert_eq!(designer.handle(KeyCode::Char('s')), Action::Save);
+        assert_eq!(designer.handle(KeyCode::Esc), Action::Quit);
+    }
+
+    #[test]
+    fn test_warnings() {
+        let mut layout = empty();
+        assert_eq!(
+            warnings(&layout, Some(2)),
+            [
+                "0 of 2 traps placed",
+                "no traps: the attacker can never win"
+            ]
+        );
+
+        for y in 0..GRID_SIZE {
+            layout.trap_values[cell_index(GRID_SIZE, 3, y)] = 1;
+        }
+        assert_eq!(warnings(&layout, Some(8)), ["row 3 is all traps"]);
+
+        layout.trap_values.fill(1);
+        let all = warnings(&layout, None);
+        assert_eq!(all.len(), 1 + 2 * GRID_SIZE as usize);
+        assert!(all[0].starts_with("more than half"));
+
+        // Rows and columns follow the layout's grid
+        let mut large = TrapLayout {
+            trap_values: vec![0; 256],
+            salt: None,
+        };
+        for y in 0..16 {
+            large.trap_values[cell_index(16, 15, y)] = 1;
+        }
+        assert_eq!(warnings(&large, None), ["row 15 is all traps"]);
+    }
+
+    #[test]
+    fn test_draw() {
+        let mut designer = Designer::new(empty(), Some(1));
+        designer.handle(KeyCode::Char(' '));
+        let mut terminal = Terminal::new(TestBackend::new(40, 16)).unwrap();
+        terminal.draw(|frame| draw(frame, &designer)).unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let row = |y: u16| -> String {
+            (0..buffer.area.width)
+                .map(|x| buffer[(x, y)].symbol())
+                .collect()
+        };
+        assert!(row(2).starts_with("│ 0   ■ · ·"));
+        assert!(row(11).starts_with("traps: 1   cell: (0, 0)"));
+        assert!(row(12).starts_with("arrows/hjkl"));
+    }
+}
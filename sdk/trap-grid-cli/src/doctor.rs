@@ -0,0 +1,378 @@
+//! `doctor` subcommand
+//!
+//! Checks the toolchain and build artifacts behind most "proof verifies
+//! locally but fails on-chain" reports: a nargo or bb.js other than the
+//! release the circuits and the dApp were built with, a verification key
+//! written before the circuit was last compiled, or a dApp artifact that no
+//! longer matches the compiled circuit. Every problem comes with a fix.
+
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::SystemTime;
+
+use anyhow::{bail, Result};
+use clap::Args;
+use serde::Deserialize;
+use trap_grid_sdk::prover::{Circuit, DEFAULT_BBJS};
+
+/// Noir release of the circuits' artifacts and the dApp's noir_js
+const NOIR_VERSION: &str = "1.0.0-beta.11";
+/// bb.js release line (`^0.87.0` in each circuit's package.json)
+const BBJS_VERSION: &str = "0.87";
+/// The position-movement artifact the dApp proves with
+const APP_ARTIFACT: &str = "app/public/circuits/trap_grid_position_movement.json";
+
+#[derive(Args)]
+pub struct DoctorArgs {
+    /// Repository root
+    #[arg(long, default_value = ".")]
+    pub root: PathBuf,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Status {
+    Ok,
+    Warn,
+    Fail,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Status::Ok => "ok",
+            Status::Warn => "warn",
+            Status::Fail => "FAIL",
+        })
+    }
+}
+
+#[derive(Debug)]
+struct Check {
+    name: String,
+    status: Status,
+    detail: String,
+    fix: Option<String>,
+}
+
+impl Check {
+    fn ok(name: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            status: Status::Ok,
+            detail: detail.into(),
+            fix: None,
+        }
+    }
+
+    fn problem(
+        status: Status,
+        name: impl Into<String>,
+        detail: impl Into<String>,
+        fix: impl Into<String>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            status,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+}
+
+/// `found` is `expected` or a later build or patch of it
+fn same_release(found: &str, expected: &str) -> bool {
+    found == expected
+        || found.starts_with(&format!("{expected}."))
+        || found.starts_with(&format!("{expected}+"))
+}
+
+/// Trimmed stdout of `program` run with `args`, if it runs and succeeds
+fn tool_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Some(stdout.trim().to_string())
+}
+
+/// The version in `nargo --version` output
+fn nargo_version(output: &str) -> Option<&str> {
+    output
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("nargo version = "))
+}
+
+fn check_tools() -> Vec<Check> {
+    let mut checks = Vec::new();
+    let noirup = format!("noirup --version {NOIR_VERSION}");
+    checks.push(
+        match tool_output("nargo", &["--version"])
+            .as_deref()
+            .map(nargo_version)
+        {
+            None => Check::problem(Status::Fail, "nargo", "not found", noirup),
+            Some(None) => Check::problem(
+                Status::Fail,
+                "nargo",
+                "unrecognized `nargo --version` output",
+                noirup,
+            ),
+            Some(Some(version)) if same_release(version, NOIR_VERSION) => {
+                Check::ok("nargo", version)
+            }
+            Some(Some(version)) => Check::problem(
+                Status::Fail,
+                "nargo",
+                format!("{version}, circuits are built with {NOIR_VERSION}"),
+                noirup,
+            ),
+        },
+    );
+    checks.push(match tool_output("node", &["--version"]) {
+        Some(version) => Check::ok("node", version),
+        None => Check::problem(
+            Status::Fail,
+            "node",
+            "not found; bb.js needs it to prove",
+            "install Node.js 20 or later",
+        ),
+    });
+    checks.push(match tool_output("stellar", &["--version"]) {
+        Some(version) => Check::ok("stellar-cli", version.lines().next().unwrap_or_default()),
+        None => Check::problem(
+            Status::Fail,
+            "stellar-cli",
+            "not found",
+            "cargo install --locked stellar-cli --features opt",
+        ),
+    });
+    checks
+}
+
+#[derive(Deserialize)]
+struct Artifact {
+    noir_version: String,
+    hash: serde_json::Value,
+}
+
+fn read_artifact(path: &Path) -> Option<Artifact> {
+    serde_json::from_str(&fs::read_to_string(path).ok()?).ok()
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn check_circuit(root: &Path, circuit: Circuit) -> Vec<Check> {
+    let rel = format!("circuits/{}", circuit.id());
+    let dir = root.join(&rel);
+    let name = |what: &str| format!("{} {what}", circuit.id());
+    let mut checks = Vec::new();
+
+    #[derive(Deserialize)]
+    struct Package {
+        version: String,
+    }
+    let bbjs = fs::read_to_string(dir.join("node_modules/@aztec/bb.js/package.json"))
+        .ok()
+        .and_then(|json| serde_json::from_str::<Package>(&json).ok());
+    let reinstall = format!("cd {rel} && rm -rf node_modules && npm install");
+    checks.push(match bbjs {
+        None => Check::problem(
+            Status::Fail,
+            name("bb.js"),
+            "not installed",
+            format!("cd {rel} && npm install"),
+        ),
+        Some(p) if same_release(&p.version, BBJS_VERSION) => Check::ok(name("bb.js"), p.version),
+        Some(p) => Check::problem(
+            Status::Fail,
+            name("bb.js"),
+            format!(
+                "{}, proofs must come from {BBJS_VERSION}.x to match the verifier",
+                p.version
+            ),
+            reinstall,
+        ),
+    });
+
+    let artifact_path = dir.join(format!("target/{}.json", circuit.package()));
+    let compile = format!("cd {rel} && nargo compile");
+    let Some(artifact) = read_artifact(&artifact_path) else {
+        checks.push(Check::problem(
+            Status::Fail,
+            name("artifact"),
+            format!("target/{}.json missing or unreadable", circuit.package()),
+            compile,
+        ));
+        return checks;
+    };
+    checks.push(if same_release(&artifact.noir_version, NOIR_VERSION) {
+        Check::ok(name("artifact"), format!("hash {}", artifact.hash))
+    } else {
+        Check::problem(
+            Status::Fail,
+            name("artifact"),
+            format!("compiled with noir {}", artifact.noir_version),
+            format!("noirup --version {NOIR_VERSION} && {compile}"),
+        )
+    });
+
+    let vk = dir.join("target/vk");
+    let write_vk = format!(
+        "cd {rel} && node {DEFAULT_BBJS} write_vk_ultra_keccak_honk \
+         -b target/{}.json -o target/vk, then redeploy the verifier",
+        circuit.package()
+    );
+    checks.push(match (modified(&vk), modified(&artifact_path)) {
+        (None, _) => Check::problem(Status::Warn, name("vk"), "target/vk missing", write_vk),
+        (Some(vk), Some(compiled)) if vk < compiled => Check::problem(
+            Status::Fail,
+            name("vk"),
+            "older than the compiled circuit; a verifier deployed with it rejects new proofs",
+            write_vk,
+        ),
+        _ => Check::ok(name("vk"), "up to date"),
+    });
+
+    if circuit == Circuit::PositionMovement {
+        let copy = format!("cp {rel}/target/{}.json {APP_ARTIFACT}", circuit.package());
+        checks.push(match read_artifact(&root.join(APP_ARTIFACT)) {
+            None => Check::problem(Status::Warn, "dApp artifact", "missing", copy),
+            Some(app) if app.hash == artifact.hash => Check::ok("dApp artifact", "matches"),
+            Some(app) => Check::problem(
+                Status::Fail,
+                "dApp artifact",
+                format!(
+                    "hash {} differs from the compiled circuit's {}",
+                    app.hash, artifact.hash
+                ),
+                copy,
+            ),
+        });
+    }
+    checks
+}
+
+fn report(checks: &[Check]) -> String {
+    let width = checks.iter().map(|c| c.name.len()).max().unwrap_or(0);
+    let mut out = String::new();
+    for check in checks {
+        out.push_str(&format!(
+            "{:<4}  {:<width$}  {}\n",
+            check.status, check.name, check.detail
+        ));
+        if let Some(fix) = &check.fix {
+            out.push_str(&format!("{:<4}  {:<width$}  fix: {fix}\n", "", ""));
+        }
+    }
+    out
+}
+
+pub fn run(args: DoctorArgs) -> Result<()> {
+    if !args.root.join("circuits").is_dir() {
+        bail!(
+            "{} has no circuits/ directory; pass the repository root with --root",
+            args.root.display()
+        );
+    }
+    let mut checks = check_tools();
+    for circuit in Circuit::ALL {
+        checks.extend(check_circuit(&args.root, circuit));
+    }
+    print!("{}", report(&checks));
+
+    let failed = checks.iter().filter(|c| c.status == Status::Fail).count();
+    if failed > 0 {
+        bail!("{failed} checks failed");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_versions() {
+        let output = "nargo version = 1.0.0-beta.11\nnoirc version = 1.0.0-beta.11+fd3925aa\n";
+        assert_eq!(nargo_version(output), Some("1.0.0-beta.11"));
+        assert_eq!(nargo_version("nargo 0.36"), None);
+
+        assert!(same_release("1.0.0-beta.11+fd3925aa", NOIR_VERSION));
+        assert!(!same_release("1.0.0-beta.110", NOIR_VERSION));
+        assert!(same_release("0.87.3", BBJS_VERSION));
+        assert!(!same_release("0.82.2", BBJS_VERSION));
+    }
+
+    fn write(path: &Path, contents: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    /// Backdate `path` by `secs` so modification order does not depend on timing
+    fn backdate(path: &Path, secs: u64) {
+        let time = SystemTime::now() - std::time::Duration::from_secs(secs);
+        fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(time)
+            .unwrap();
+    }
+
+    fn statuses(checks: &[Check]) -> Vec<(String, Status)> {
+        checks.iter().map(|c| (c.name.clone(), c.status)).collect()
+    }
+
+    #[test]
+    fn test_check_circuit() {
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path();
+        let dir = root.join("circuits/position-movement");
+        let artifact = |noir: &str, hash: u64| {
+            format!(r#"{{"noir_version":"{noir}","hash":"{hash}","abi":{{}}}}"#)
+        };
+
+        let checks = check_circuit(root, Circuit::PositionMovement);
+        assert_eq!(
+            statuses(&checks),
+            [
+                ("position-movement bb.js".into(), Status::Fail),
+                ("position-movement artifact".into(), Status::Fail),
+            ]
+        );
+
+        write(
+            &dir.join("node_modules/@aztec/bb.js/package.json"),
+            r#"{"version":"0.87.0"}"#,
+        );
+        write(&dir.join("target/vk"), "vk");
+        write(
+            &dir.join("target/position_movement.json"),
+            &artifact("1.0.0-beta.11+fd3925aa", 1),
+        );
+        write(&root.join(APP_ARTIFACT), &artifact("1.0.0-beta.11", 2));
+        backdate(&dir.join("target/vk"), 60);
+        let checks = check_circuit(root, Circuit::PositionMovement);
+        assert_eq!(
+            statuses(&checks),
+            [
+                ("position-movement bb.js".into(), Status::Ok),
+                ("position-movement artifact".into(), Status::Ok),
+                ("position-movement vk".into(), Status::Fail),
+                ("dApp artifact".into(), Status::Fail),
+            ]
+        );
+        assert!(report(&checks)
+            .contains("fix: cp circuits/position-movement/target/position_movement.json"));
+
+        backdate(&dir.join("target/position_movement.json"), 120);
+        write(&root.join(APP_ARTIFACT), &artifact("1.0.0-beta.11", 1));
+        let checks = check_circuit(root, Circuit::PositionMovement);
+        assert!(checks.iter().all(|c| c.status == Status::Ok), "{checks:?}");
+    }
+}
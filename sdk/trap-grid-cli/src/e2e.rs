@@ -0,0 +1,450 @@
+//! `e2e` subcommand
+//!
+//! Validates the whole stack against a local network in one command: builds
+//! the position-movement and grid-setup circuits, the UltraHonk verifier and
+//! the game contracts, deploys them, plays a scripted game with real proofs and checks
+//! the resulting contract state and events.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{bail, ensure, Context, Result};
+use clap::Args;
+use stellar_xdr::curr::ScAddress;
+use trap_grid_sdk::commitment::layout_salt;
+use trap_grid_sdk::prover::{self, Circuit, Prover};
+use trap_grid_sdk::rpc::{EventsStart, RpcClient, LOCAL_RPC_URL};
+use trap_grid_sdk::tx::{self, Submitter};
+use trap_grid_sdk::{session_topics, GameClient, GameConfig, GameEvent, Keypair, Move};
+
+use crate::grid::{self, TrapLayout, GRID_SIZE};
+use crate::keys;
+use crate::seed;
+
+/// Friendbot of the local quickstart network
+const LOCAL_FRIENDBOT_URL: &str = "http://localhost:8000/friendbot";
+
+/// Container name used by --start-network
+const CONTAINER_NAME: &str = "trap-grid-e2e";
+
+/// Seed phrase of the scripted defender layout
+const E2E_SEED: &str = "trap-grid e2e";
+
+const E2E_TRAPS: usize = 10;
+const SESSION_ID: u32 = 1;
+const POINTS: i128 = 100;
+
+/// Release wasm target used for every contract
+const WASM_TARGET: &str = "wasm32v1-none";
+
+#[derive(Args)]
+pub struct E2eArgs {
+    /// Repository root (containing contracts/ and circuits/)
+    #[arg(long, default_value = "..")]
+    pub repo_root: PathBuf,
+
+    /// Soroban RPC endpoint of the local network
+    #[arg(long, env = "STELLAR_RPC_URL", default_value = LOCAL_RPC_URL)]
+    pub rpc_url: String,
+
+    /// Friendbot of the local network
+    #[arg(long, default_value = LOCAL_FRIENDBOT_URL)]
+    pub friendbot_url: String,
+
+    /// Start a stellar/quickstart container instead of using a running network
+    #[arg(long)]
+    pub start_network: bool,
+
+    /// Leave the container started by --start-network running afterwards
+    #[arg(long, requires = "start_network")]
+    pub keep_network: bool,
+
+    /// Reuse existing circuit and contract build artifacts
+    #[arg(long)]
+    pub skip_build: bool,
+
+    /// Moves to play before ending the game; 64 plays the whole grid
+    #[arg(long, default_value_t = 4, value_parser = clap::value_parser!(u32).range(1..=64))]
+    pub moves: u32,
+
+    /// bb.js entrypoint (relative paths are resolved against each circuit directory)
+    #[arg(long, default_value = prover::DEFAULT_BBJS)]
+    pub bbjs: PathBuf,
+}
+
+/// Stops the quickstart container when dropped
+struct Network {
+    keep: bool,
+}
+
+impl Drop for Network {
+    fn drop(&mut self) {
+        if !self.keep {
+            println!("==> Stopping {CONTAINER_NAME}");
+            let _ = Command::new("docker")
+                .args(["stop", CONTAINER_NAME])
+                .output();
+        }
+    }
+}
+
+fn run_in(dir: &Path, program: &str, args: &[&str]) -> Result<()> {
+    let output = Command::new(program)
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .with_context(|| format!("failed to run {program}; is it installed and on PATH?"))?;
+    if !output.status.success() {
+        bail!(
+            "{program} {} failed in {}: {}",
+            args.join(" "),
+            dir.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+fn start_network(rpc: &RpcClient, keep: bool) -> Result<Network> {
+    println!("==> Starting stellar/quickstart as {CONTAINER_NAME}");
+    run_in(
+        Path::new("."),
+        "docker",
+        &[
+            "run",
+            "-d",
+            "--rm",
+            "-p",
+            "8000:8000",
+            "--name",
+            CONTAINER_NAME,
+            "stellar/quickstart",
+            "--local",
+            "--limits",
+            "unlimited",
+            "--enable",
+            "core,rpc,friendbot",
+        ],
+    )?;
+    let network = Network { keep };
+    for _ in 0..90 {
+        if rpc.is_healthy() {
+            return Ok(network);
+        }
+        thread::sleep(Duration::from_secs(2));
+    }
+    bail!("local network did not become healthy within 3 minutes")
+}
+
+/// Compile a circuit and write its verification key to `target/vk`
+fn build_circuit(circuit_dir: &Path, circuit: Circuit, bbjs: &Path) -> Result<()> {
+    println!("==> Building the {} circuit", circuit.id());
+    if !circuit_dir.join("node_modules").is_dir() {
+        run_in(circuit_dir, "npm", &["install"])?;
+    }
+    run_in(circuit_dir, "nargo", &["compile"])?;
+    let bbjs = if bbjs.is_absolute() {
+        bbjs.to_path_buf()
+    } else {
+        circuit_dir.join(bbjs)
+    };
+    run_in(
+        circuit_dir,
+        "node",
+        &[
+            &bbjs.to_string_lossy(),
+            "write_vk_ultra_keccak_honk",
+            "-b",
+            &format!("target/{}.json", circuit.package()),
+            "-o",
+            "target/vk",
+        ],
+    )
+}
+
+fn build(repo_root: &Path, circuit_dir: &Path, setup_dir: &Path, bbjs: &Path) -> Result<()> {
+    build_circuit(circuit_dir, Circuit::PositionMovement, bbjs)?;
+    build_circuit(setup_dir, Circuit::GridSetup, bbjs)?;
+
+    let cargo_build = ["build", "--release", "--target", WASM_TARGET];
+    println!("==> Building the UltraHonk verifier contract");
+    run_in(
+        &circuit_dir.join("rs-soroban-ultrahonk"),
+        "cargo",
+        &cargo_build,
+    )?;
+    println!("==> Building the game contracts");
+    run_in(&repo_root.join("contracts"), "cargo", &cargo_build)
+}
+
+fn read(path: &Path) -> Result<Vec<u8>> {
+    fs::read(path).with_context(|| {
+        format!(
+            "failed to read {}; run without --skip-build",
+            path.display()
+        )
+    })
+}
+
+fn fund(rpc: &RpcClient, friendbot_url: &str, keypair: &Keypair) -> Result<()> {
+    // Friendbot lags behind RPC health on a freshly started network
+    let mut attempts = 0;
+    loop {
+        match keys::fund(rpc, Some(friendbot_url), &keypair.address()) {
+            Ok(()) => return Ok(()),
+            Err(_) if attempts < 15 => {
+                attempts += 1;
+                thread::sleep(Duration::from_secs(2));
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// Scripted moves: trap and empty cells alternately, in cell-index order
+pub fn script_moves(layout: &TrapLayout, count: usize) -> Vec<(u32, u32)> {
+    let size = layout.grid_size();
+    let cells = (0..size).flat_map(|x| (0..size).map(move |y| (x, y)));
+    let (mut traps, mut empty): (Vec<_>, Vec<_>) = cells.partition(|&(x, y)| layout.is_trap(x, y));
+    traps.reverse();
+    empty.reverse();
+
+    let mut moves = Vec::with_capacity(count);
+    while moves.len() < count {
+        let next = if moves.len() % 2 == 0 {
+            traps.pop().or_else(|| empty.pop())
+        } else {
+            empty.pop().or_else(|| traps.pop())
+        };
+        match next {
+            Some(cell) => moves.push(cell),
+            None => break,
+        }
+    }
+    moves
+}
+
+pub fn run(args: E2eArgs) -> Result<()> {
+    let repo_root = &args
+        .repo_root
+        .canonicalize()
+        .with_context(|| format!("repository root {} not found", args.repo_root.display()))?;
+    let circuit_dir = repo_root.join("circuits/position-movement");
+    let setup_dir = repo_root.join("circuits/grid-setup");
+    let rpc = RpcClient::new(&args.rpc_url);
+
+    let _network = if args.start_network {
+        Some(start_network(&rpc, args.keep_network)?)
+    } else {
+        ensure!(
+            rpc.is_healthy(),
+            "no healthy network at {}; start one or pass --start-network",
+            args.rpc_url
+        );
+        None
+    };
+
+    if !args.skip_build {
+        build(repo_root, &circuit_dir, &setup_dir, &args.bbjs)?;
+    }
+    let prover = Prover::new(&circuit_dir, &args.bbjs)?;
+    let setup_prover = Prover::new(&setup_dir, &args.bbjs)?;
+
+    println!("==> Funding deployer, defender and attacker accounts");
+    let deployer = Keypair::generate()?;
+    let defender = Keypair::generate()?;
+    let attacker = Keypair::generate()?;
+    for keypair in [&deployer, &defender, &attacker] {
+        fund(&rpc, &args.friendbot_url, keypair)?;
+    }
+    let submitter = Submitter::new(&rpc)?;
+
+    println!("==> Deploying contracts");
+    let contracts_release = repo_root
+        .join("contracts/target")
+        .join(WASM_TARGET)
+        .join("release");
+    let verifier_wasm = circuit_dir
+        .join("rs-soroban-ultrahonk/target")
+        .join(WASM_TARGET)
+        .join("release/rs_soroban_ultrahonk.wasm");
+
+    let hub_hash = submitter.upload_wasm(
+        &deployer,
+        &read(&contracts_release.join("mock_game_hub.wasm"))?,
+    )?;
+    let hub = submitter.deploy(&deployer, hub_hash, Vec::new())?;
+    println!("    Game Hub:  {hub}");
+
+    let verifier_hash = submitter.upload_wasm(&deployer, &read(&verifier_wasm)?)?;
+    let vk = read(&circuit_dir.join("target/vk"))?;
+    let verifier = submitter.deploy(&deployer, verifier_hash, vec![tx::bytes_arg(&vk)?])?;
+    println!("    Verifier:  {verifier}");
+    let setup_vk = read(&setup_dir.join("target/vk"))?;
+    let setup_verifier =
+        submitter.deploy(&deployer, verifier_hash, vec![tx::bytes_arg(&setup_vk)?])?;
+    println!("    Setup Verifier: {setup_verifier}");
+
+    let trap_grid_hash =
+        submitter.upload_wasm(&deployer, &read(&contracts_release.join("trap_grid.wasm"))?)?;
+    let config = GameConfig {
+        admin: deployer.address(),
+        game_hub: hub.to_string(),
+        verifier: verifier.to_string(),
+        setup_verifier: setup_verifier.to_string(),
+    };
+    let trap_grid = submitter.deploy(&deployer, trap_grid_hash, config.constructor_args()?)?;
+    println!("    Trap Grid: {trap_grid}");
+
+    let layout = seed::derive_layout(E2E_SEED, E2E_TRAPS);
+    let script = script_moves(&layout, args.moves as usize);
+    let start_ledger = rpc.get_latest_ledger()?;
+
+    println!("==> Playing {} scripted moves", script.len());
+    let game = GameClient::new(&rpc, trap_grid.clone())?;
+    if let Some(warning) = game.check_interface()? {
+        eprintln!("warning: {warning}");
+    }
+    let salt = layout_salt(&layout)?;
+    let setup = setup_prover.prove_setup(&layout, &salt, &defender.address())?;
+    game.start_game(
+        &defender,
+        &defender,
+        &attacker.address(),
+        SESSION_ID,
+        (POINTS, POINTS),
+        &setup,
+    )?;
+    for &(x, y) in &script {
+        let trap_value = layout.trap_values[grid::cell_index(GRID_SIZE, x, y)];
+        game.submit_move(&attacker, SESSION_ID, x, y)?;
+        let proven = prover.prove_move(SESSION_ID, &layout, &salt, &defender.address(), x, y)?;
+        game.respond_move(&defender, &proven)?;
+        println!(
+            "    ({x}, {y}) {}",
+            if trap_value == 1 { "hit" } else { "miss" }
+        );
+    }
+    if script.len() < grid::NUM_CELLS {
+        game.end_game(&defender, &[&attacker], SESSION_ID)?;
+    }
+
+    println!("==> Checking final state");
+    check_final_state(&game, &layout, &script, &attacker, &defender)?;
+    check_events(&rpc, &trap_grid, start_ledger, script.len())?;
+
+    println!(
+        "\nE2E passed: {} moves proven and verified on-chain",
+        script.len()
+    );
+    Ok(())
+}
+
+fn check_final_state(
+    game: &GameClient,
+    layout: &TrapLayout,
+    script: &[(u32, u32)],
+    attacker: &Keypair,
+    defender: &Keypair,
+) -> Result<()> {
+    let expected_moves: Vec<Move> = script
+        .iter()
+        .map(|&(x, y)| Move {
+            x,
+            y,
+            is_hit: layout.is_trap(x, y),
+            verified: true,
+        })
+        .collect();
+    let hits = expected_moves.iter().filter(|m| m.is_hit).count() as u32;
+    let moves_made = expected_moves.len() as u32;
+    let winner = if hits > moves_made / 2 {
+        attacker.address()
+    } else {
+        defender.address()
+    };
+
+    let state = game.get_game(SESSION_ID)?;
+    ensure!(state.game_ended, "game has not ended");
+    ensure!(
+        state.moves_made == moves_made && state.hits == hits && state.misses == moves_made - hits,
+        "expected {moves_made} moves with {hits} hits, contract has {} moves with {} hits and {} misses",
+        state.moves_made,
+        state.hits,
+        state.misses
+    );
+    ensure!(
+        state.winner.as_deref() == Some(winner.as_str()),
+        "expected winner {winner}, contract has {:?}",
+        state.winner
+    );
+    let moves = game.get_moves(SESSION_ID)?;
+    ensure!(
+        moves == expected_moves,
+        "recorded moves differ from the script: {moves:?}"
+    );
+    println!("    ✓ game state: {moves_made} moves, {hits} hits, winner {winner}");
+    Ok(())
+}
+
+fn check_events(
+    rpc: &RpcClient,
+    trap_grid: &ScAddress,
+    start_ledger: u32,
+    move_count: usize,
+) -> Result<()> {
+    let topics = session_topics(SESSION_ID);
+    let mut events = Vec::new();
+    let mut result = rpc.get_events(trap_grid, &topics, EventsStart::Ledger(start_ledger))?;
+    loop {
+        for info in &result.events {
+            let (topics, value) = info.decode()?;
+            events.extend(GameEvent::decode(&topics, &value)?);
+        }
+        match (&result.cursor, result.events.is_empty()) {
+            (Some(cursor), false) => {
+                result = rpc.get_events(trap_grid, &topics, EventsStart::Cursor(cursor))?
+            }
+            _ => break,
+        }
+    }
+
+    let moves = events
+        .iter()
+        .filter(|e| matches!(e, GameEvent::MoveMade(_)))
+        .count();
+    ensure!(
+        matches!(events.first(), Some(GameEvent::GameStarted(_)))
+            && matches!(events.last(), Some(GameEvent::GameEnded(_)))
+            && moves == move_count,
+        "unexpected event sequence: {events:?}"
+    );
+    println!("    ✓ events: game_started, {moves} move_made, game_ended");
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_script_moves() {
+        let layout = seed::derive_layout(E2E_SEED, E2E_TRAPS);
+        let script = script_moves(&layout, 4);
+        let hits: Vec<bool> = script.iter().map(|&(x, y)| layout.is_trap(x, y)).collect();
+        assert_eq!(hits, [true, false, true, false]);
+
+        let full = script_moves(&layout, 64);
+        assert_eq!(full.len(), 64);
+        let mut cells: Vec<usize> = full
+            .iter()
+            .map(|&(x, y)| grid::cell_index(GRID_SIZE, x, y))
+            .collect();
+        cells.sort_unstable();
+        cells.dedup();
+        assert_eq!(cells.len(), 64);
+    }
+}
@@ -0,0 +1,106 @@
+//! Public-input encoding shared by the circuits and the on-chain verifier
+//!
+//! The byte layout itself lives in `trap-grid-encoding`, which the contract
+//! uses too; this module adds the CLI's parsing and formatting around it.
+
+use anyhow::{bail, Result};
+use clap::ValueEnum;
+pub use trap_grid_encoding::{
+    encode_u64, key_fields, FieldBytes, FIELD_BYTES, MAX_MERKLE_TREE_DEPTH,
+};
+use trap_grid_encoding::{GridSetup, PositionMovement, TrapCommitment, TrapMerkleRoot};
+pub use trap_grid_sdk::commitment::parse_field;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+pub enum Claim {
+    Hit,
+    Miss,
+}
+
+impl Claim {
+    pub fn is_hit(self) -> bool {
+        self == Claim::Hit
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+pub enum Circuit {
+    PositionMovement,
+    TrapCommitment,
+    TrapMerkleRoot,
+    GridSetup,
+}
+
+impl Circuit {
+    /// Names of the public inputs in encoding order
+    pub fn field_names(self) -> &'static [&'static str] {
+        match self {
+            Circuit::PositionMovement => &PositionMovement::NAMES,
+            Circuit::TrapCommitment => &TrapCommitment::NAMES,
+            Circuit::TrapMerkleRoot => &TrapMerkleRoot::NAMES,
+            Circuit::GridSetup => &GridSetup::NAMES,
+        }
+    }
+}
+
+/// Split an encoded public-input string into field elements
+pub fn split_fields(bytes: &[u8]) -> Result<Vec<FieldBytes>> {
+    if !bytes.len().is_multiple_of(FIELD_BYTES) {
+        bail!(
+            "public inputs are {} bytes, expected a multiple of {FIELD_BYTES}",
+            bytes.len()
+        );
+    }
+    Ok(bytes
+        .chunks(FIELD_BYTES)
+        .map(|chunk| chunk.try_into().expect("chunk is FIELD_BYTES long"))
+        .collect())
+}
+
+/// Field value as 0x-prefixed hex, the form Prover.toml files use
+pub fn hex_field(field: &FieldBytes) -> String {
+    format!("0x{}", hex::encode(field))
+}
+
+/// Human-readable field value: decimal when it fits in a u64, hex otherwise
+pub fn format_field(field: &FieldBytes) -> String {
+    if field[..FIELD_BYTES - 8].iter().all(|b| *b == 0) {
+        let value = u64::from_be_bytes(field[FIELD_BYTES - 8..].try_into().unwrap());
+        value.to_string()
+    } else {
+        format!("0x{}", hex::encode(field))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_split_and_format_fields() {
+        let mut bytes = encode_u64(3).to_vec();
+        bytes.extend(
+            parse_field("0x0c7c517f1ddb7e98c83cda5a4061540940af216957d2993a2cf02dd26eca13fb")
+                .unwrap(),
+        );
+
+        let fields = split_fields(&bytes).unwrap();
+        assert_eq!(format_field(&fields[0]), "3");
+        assert_eq!(
+            format_field(&fields[1]),
+            "0x0c7c517f1ddb7e98c83cda5a4061540940af216957d2993a2cf02dd26eca13fb"
+        );
+        assert!(split_fields(&bytes[1..]).is_err());
+    }
+
+    #[test]
+    fn test_field_names() {
+        assert_eq!(Circuit::PositionMovement.field_names().len(), 5);
+        assert_eq!(Circuit::TrapCommitment.field_names().len(), 2);
+        assert_eq!(
+            Circuit::TrapMerkleRoot.field_names().len(),
+            5 + 2 * MAX_MERKLE_TREE_DEPTH
+        );
+        assert_eq!(Circuit::GridSetup.field_names().len(), 5);
+    }
+}
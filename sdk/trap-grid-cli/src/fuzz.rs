@@ -0,0 +1,853 @@
+//! `fuzz-inputs` subcommand
+//!
+//! Generates edge-case and randomized inputs for the circuits together with
+//! the outcome the Rust reference implementation expects, for differential
+//! testing against `nargo execute`. Each case is written as
+//! `<out>/<circuit>/<case>/Prover.toml`, and `<out>/<circuit>/expected.json`
+//! lists whether each case should execute and, if so, its public inputs.
+//!
+//! With `--execute`, every case is also run through `nargo execute` in a
+//! scratch copy of the circuit, and the runs that disagree with the reference
+//! are reported in `<out>/<circuit>/differential.json`. A disagreement means
+//! the circuit and the Rust side, which the contract and the bots rely on,
+//! no longer accept the same inputs.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{anyhow, bail, Context, Result};
+use clap::{Args, ValueEnum};
+use serde::Serialize;
+use trap_grid_sdk::prover::copy_circuit;
+
+use crate::encoding::{
+    encode_u64, hex_field, key_fields, Circuit, FieldBytes, MAX_MERKLE_TREE_DEPTH,
+};
+use crate::grid::{cell_index, GRID_SIZE, NUM_CELLS};
+use crate::reference::{
+    self, GridSetupInputs, PositionMovementInputs, TrapCommitmentInputs, TrapMerkleRootInputs,
+    TrapTree,
+};
+
+/// BN254 modulus minus one, the largest field element
+const FIELD_MAX: &str = "0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000000";
+
+#[derive(Args)]
+pub struct FuzzInputsArgs {
+    /// Circuit to generate cases for (all circuits when omitted)
+    #[arg(long, value_enum)]
+    pub circuit: Option<Circuit>,
+
+    /// Random cases per circuit, on top of the fixed edge cases
+    #[arg(long, default_value_t = 32)]
+    pub count: usize,
+
+    /// RNG seed, to reproduce a corpus (random when omitted)
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Output directory
+    #[arg(long, default_value = "fuzz-inputs")]
+    pub out: PathBuf,
+
+    /// Directory holding the circuits; run every case through `nargo execute`
+    /// and fail on any disagreement with the reference
+    #[arg(long, value_name = "CIRCUITS_DIR")]
+    pub execute: Option<PathBuf>,
+
+    /// nargo binary used by --execute
+    #[arg(long, default_value = "nargo")]
+    pub nargo: String,
+}
+
+/// SplitMix64; fuzz corpora only need to be reproducible, not unpredictable
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, bound: u32) -> u32 {
+        (self.next_u64() % u64::from(bound)) as u32
+    }
+
+    fn chance(&mut self, percent: u32) -> bool {
+        self.below(100) < percent
+    }
+
+    /// Random field element (top byte cleared so it is below the modulus)
+    fn field(&mut self) -> FieldBytes {
+        let mut out = [0u8; 32];
+        for chunk in out.chunks_mut(8) {
+            chunk.copy_from_slice(&self.next_u64().to_be_bytes());
+        }
+        out[0] = 0;
+        out
+    }
+
+    fn layout(&mut self) -> Vec<u8> {
+        self.layout_of(GRID_SIZE)
+    }
+
+    fn layout_of(&mut self, grid_size: u32) -> Vec<u8> {
+        let density = self.below(101);
+        (0..grid_size * grid_size)
+            .map(|_| u8::from(self.below(100) < density))
+            .collect()
+    }
+}
+
+enum Inputs {
+    PositionMovement(Box<PositionMovementInputs>),
+    TrapCommitment(TrapCommitmentInputs),
+    TrapMerkleRoot(Box<TrapMerkleRootInputs>),
+    GridSetup(GridSetupInputs),
+}
+
+struct Case {
+    name: String,
+    inputs: Inputs,
+}
+
+/// One entry of `expected.json`
+#[derive(Serialize)]
+struct Expected {
+    case: String,
+    valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    /// Hex-encoded verifier public inputs
+    public_inputs: String,
+}
+
+fn field(value: &str) -> FieldBytes {
+    crate::encoding::parse_field(value).expect("constant is a valid field element")
+}
+
+impl Inputs {
+    fn check(&self) -> Result<()> {
+        match self {
+            Inputs::PositionMovement(inputs) => reference::check_position_movement(inputs),
+            Inputs::TrapCommitment(inputs) => reference::check_trap_commitment(inputs),
+            Inputs::TrapMerkleRoot(inputs) => reference::check_trap_merkle_root(inputs),
+            Inputs::GridSetup(inputs) => reference::check_grid_setup(inputs),
+        }
+    }
+
+    fn public_inputs(&self) -> Vec<FieldBytes> {
+        match self {
+            Inputs::PositionMovement(inputs) => vec![
+                encode_u64(inputs.move_x.into()),
+                encode_u64(inputs.move_y.into()),
+                encode_u64(inputs.is_hit.into()),
+                encode_u64(inputs.grid_size.into()),
+                inputs.grid_commitment,
+            ],
+            Inputs::TrapCommitment(inputs) => vec![inputs.trap_commitment, inputs.defender],
+            Inputs::TrapMerkleRoot(inputs) => {
+                let mut fields = vec![
+                    inputs.trap_merkle_root,
+                    encode_u64(inputs.move_x.into()),
+                    encode_u64(inputs.move_y.into()),
+                    encode_u64(inputs.proof_length.into()),
+                ];
+                fields.extend(inputs.indices.iter().map(|bit| encode_u64((*bit).into())));
+                fields.extend(inputs.siblings);
+                fields.push(encode_u64(inputs.grid_size.into()));
+                fields
+            }
+            Inputs::GridSetup(inputs) => {
+                let [head, last] = key_fields(&inputs.defender);
+                vec![
+                    inputs.grid_commitment,
+                    encode_u64(inputs.trap_count.into()),
+                    encode_u64(inputs.grid_size.into()),
+                    head,
+                    last,
+                ]
+            }
+        }
+    }
+
+    fn prover_toml(&self) -> String {
+        match self {
+            Inputs::PositionMovement(inputs) => inputs.prover_toml(),
+            Inputs::TrapCommitment(inputs) => format!(
+                "[public_inputs]\ntrap_commitment = \"{}\"\ndefender = \"{}\"\n\n\
+                 [private_inputs]\ntrap_value = \"{}\"\nsecret = \"{}\"\n",
+                hex_field(&inputs.trap_commitment),
+                hex_field(&inputs.defender),
+                inputs.trap_value,
+                hex_field(&inputs.secret)
+            ),
+            Inputs::TrapMerkleRoot(inputs) => inputs.prover_toml(),
+            Inputs::GridSetup(inputs) => inputs.prover_toml(),
+        }
+    }
+}
+
+fn position_movement(name: &str, inputs: PositionMovementInputs) -> Case {
+    Case {
+        name: name.to_string(),
+        inputs: Inputs::PositionMovement(Box::new(inputs)),
+    }
+}
+
+/// An honest answer at (`x`, `y`) of a random layout holding `value` there
+fn answer(rng: &mut Rng, x: u32, y: u32, value: u8) -> Result<PositionMovementInputs> {
+    let mut layout = rng.layout();
+    let index = cell_index(GRID_SIZE, x, y);
+    layout[index] = value;
+    PositionMovementInputs::opening(&layout, rng.field(), rng.field(), index, x, y)
+}
+
+fn position_movement_cases(rng: &mut Rng, count: usize) -> Result<Vec<Case>> {
+    let mut cases = Vec::new();
+    for (x, y) in [(0, 0), (0, 7), (7, 0), (7, 7)] {
+        for value in [0, 1] {
+            cases.push(position_movement(
+                &format!("corner_{x}_{y}_{value}"),
+                answer(rng, x, y, value)?,
+            ));
+        }
+    }
+
+    let layout = rng.layout();
+    let opening = |rng: &mut Rng, index: usize, x: u32, y: u32| {
+        PositionMovementInputs::opening(&layout, rng.field(), rng.field(), index, x, y)
+    };
+    // (0, 8) would alias cell (1, 0) if the coordinates were not range checked
+    cases.push(position_movement(
+        "y_wraps_to_next_row",
+        opening(rng, cell_index(GRID_SIZE, 1, 0), 0, 8)?,
+    ));
+    cases.push(position_movement(
+        "x_out_of_range",
+        opening(rng, cell_index(GRID_SIZE, 7, 7), 8, 0)?,
+    ));
+    cases.push(position_movement(
+        "x_u32_max",
+        opening(rng, 0, u32::MAX, 0)?,
+    ));
+    cases.push(position_movement(
+        "index_for_other_cell",
+        opening(rng, cell_index(GRID_SIZE, 2, 3), 3, 2)?,
+    ));
+    let mut non_boolean = answer(rng, 3, 3, 0)?;
+    non_boolean.trap_value = 2;
+    non_boolean.is_hit = 2;
+    cases.push(position_movement("non_boolean_trap", non_boolean));
+    // A claim the trap value contradicts, and a lie backed by a matching trap value
+    let mut mismatch = answer(rng, 3, 4, 1)?;
+    mismatch.is_hit = 0;
+    cases.push(position_movement("claim_miss_on_trap", mismatch));
+    let mut lie = answer(rng, 3, 4, 1)?;
+    lie.is_hit = 0;
+    lie.trap_value = 0;
+    cases.push(position_movement("lie_miss_on_trap", lie));
+    let mut lie = answer(rng, 4, 3, 0)?;
+    lie.is_hit = 1;
+    lie.trap_value = 1;
+    cases.push(position_movement("lie_hit_on_empty", lie));
+    // The opening is sound, but of another grid than the game's
+    let mut other_grid = answer(rng, 5, 5, 1)?;
+    other_grid.grid_commitment = answer(rng, 5, 5, 1)?.grid_commitment;
+    cases.push(position_movement("other_grid", other_grid));
+    let mut stolen = answer(rng, 5, 5, 1)?;
+    stolen.defender = rng.field();
+    cases.push(position_movement("other_defender", stolen));
+
+    for (grid_size, x, y) in [(16, 15, 15), (10, 9, 0), (2, 1, 1)] {
+        let layout = rng.layout_of(grid_size);
+        let index = cell_index(grid_size, x, y);
+        cases.push(position_movement(
+            &format!("grid_{grid_size}_corner"),
+            PositionMovementInputs::opening(&layout, rng.field(), rng.field(), index, x, y)?,
+        ));
+    }
+    let layout = rng.layout_of(16);
+    let mut out_of_range =
+        PositionMovementInputs::opening(&layout, rng.field(), rng.field(), 255, 16, 0)?;
+    cases.push(position_movement(
+        "large_grid_out_of_range",
+        out_of_range.clone(),
+    ));
+    out_of_range.grid_size = 17;
+    cases.push(position_movement("grid_size_too_large", out_of_range));
+    let mut too_small =
+        PositionMovementInputs::opening(&[0; 4], rng.field(), rng.field(), 0, 0, 0)?;
+    too_small.grid_size = 1;
+    cases.push(position_movement("grid_size_too_small", too_small));
+
+    for i in 0..count {
+        let (x, y) = (rng.below(8), rng.below(8));
+        let value = rng.below(2) as u8;
+        let mut inputs = answer(rng, x, y, value)?;
+        match rng.below(8) {
+            0 => inputs.is_hit = 1 - inputs.is_hit,
+            1 => {
+                inputs.is_hit = 1 - inputs.is_hit;
+                inputs.trap_value = inputs.is_hit;
+            }
+            2 => inputs.siblings[rng.below(6) as usize] = rng.field(),
+            3 => inputs.indices[rng.below(MAX_MERKLE_TREE_DEPTH as u32) as usize] ^= 1,
+            4 => inputs.salt = rng.field(),
+            _ => {}
+        }
+        cases.push(position_movement(&format!("random_{i}"), inputs));
+    }
+    Ok(cases)
+}
+
+fn trap_commitment(name: &str, trap_value: u32, secret: FieldBytes, claimed: Option<u32>) -> Case {
+    // `claimed` commits to a different trap value than the one proven
+    let committed = claimed.unwrap_or(trap_value);
+    let defender = encode_u64(1);
+    Case {
+        name: name.to_string(),
+        inputs: Inputs::TrapCommitment(TrapCommitmentInputs {
+            trap_commitment: reference::trap_commitment(committed, &secret, &defender),
+            defender,
+            trap_value,
+            secret,
+        }),
+    }
+}
+
+/// A commitment made by one defender, proven as another's
+fn stolen_commitment(name: &str, trap_value: u32, secret: FieldBytes, owner: FieldBytes) -> Case {
+    Case {
+        name: name.to_string(),
+        inputs: Inputs::TrapCommitment(TrapCommitmentInputs {
+            trap_commitment: reference::trap_commitment(trap_value, &secret, &owner),
+            defender: encode_u64(1),
+            trap_value,
+            secret,
+        }),
+    }
+}
+
+fn trap_commitment_cases(rng: &mut Rng, count: usize) -> Vec<Case> {
+    let mut cases = vec![
+        trap_commitment("zero_secret_empty", 0, [0u8; 32], None),
+        trap_commitment("zero_secret_trap", 1, [0u8; 32], None),
+        trap_commitment("max_secret_empty", 0, field(FIELD_MAX), None),
+        trap_commitment("max_secret_trap", 1, field(FIELD_MAX), None),
+        trap_commitment("non_boolean_trap", 2, rng.field(), None),
+        trap_commitment("u32_max_trap", u32::MAX, rng.field(), None),
+        trap_commitment("opened_as_empty", 0, rng.field(), Some(1)),
+        trap_commitment("opened_as_trap", 1, rng.field(), Some(0)),
+        stolen_commitment("other_defender", 1, rng.field(), rng.field()),
+        stolen_commitment("zero_defender", 0, rng.field(), [0u8; 32]),
+    ];
+    for i in 0..count {
+        let value = rng.below(2);
+        let claimed = rng.chance(25).then_some(1 - value);
+        cases.push(trap_commitment(
+            &format!("random_{i}"),
+            value,
+            rng.field(),
+            claimed,
+        ));
+    }
+    cases
+}
+
+fn trap_merkle_root(name: &str, inputs: TrapMerkleRootInputs) -> Case {
+    Case {
+        name: name.to_string(),
+        inputs: Inputs::TrapMerkleRoot(Box::new(inputs)),
+    }
+}
+
+fn trap_merkle_root_cases(rng: &mut Rng, count: usize) -> Result<Vec<Case>> {
+    let mut cases = Vec::new();
+    for (grid, layout) in [
+        ("empty", vec![0u8; NUM_CELLS]),
+        ("full", vec![1u8; NUM_CELLS]),
+    ] {
+        let tree = TrapTree::new(&layout)?;
+        for (x, y) in [(0, 0), (0, 7), (7, 0), (7, 7)] {
+            let index = cell_index(GRID_SIZE, x, y);
+            let opening = TrapMerkleRootInputs::opening(&tree, &layout, index, x, y);
+            cases.push(trap_merkle_root(&format!("{grid}_grid_{x}_{y}"), opening));
+        }
+        let mut lie = TrapMerkleRootInputs::opening(&tree, &layout, 0, 0, 0);
+        lie.trap_value = 1 - lie.trap_value;
+        cases.push(trap_merkle_root(&format!("{grid}_grid_lie"), lie));
+    }
+
+    // Grids whose cell count is not a power of two pad the tree with empty leaves
+    for grid_size in [2, 10, 16] {
+        let layout = rng.layout_of(grid_size);
+        let tree = TrapTree::new(&layout)?;
+        let last = grid_size - 1;
+        let index = cell_index(grid_size, last, last);
+        cases.push(trap_merkle_root(
+            &format!("grid_{grid_size}_corner"),
+            TrapMerkleRootInputs::opening(&tree, &layout, index, last, last),
+        ));
+    }
+
+    let layout = rng.layout();
+    let tree = TrapTree::new(&layout)?;
+    // (0, 8) would alias cell (1, 0) if the coordinates were not range checked
+    cases.push(trap_merkle_root(
+        "y_wraps_to_next_row",
+        TrapMerkleRootInputs::opening(&tree, &layout, cell_index(GRID_SIZE, 1, 0), 0, 8),
+    ));
+    cases.push(trap_merkle_root(
+        "x_out_of_range",
+        TrapMerkleRootInputs::opening(&tree, &layout, cell_index(GRID_SIZE, 7, 7), 8, 0),
+    ));
+    cases.push(trap_merkle_root(
+        "x_u32_max",
+        TrapMerkleRootInputs::opening(&tree, &layout, 0, u32::MAX, 0),
+    ));
+    cases.push(trap_merkle_root(
+        "index_for_other_cell",
+        TrapMerkleRootInputs::opening(&tree, &layout, cell_index(GRID_SIZE, 2, 3), 3, 2),
+    ));
+    // An 8x8 opening replayed as a 16x16 one: the proof is two levels short
+    let mut grid_mismatch = TrapMerkleRootInputs::opening(&tree, &layout, 0, 0, 0);
+    grid_mismatch.grid_size = 16;
+    cases.push(trap_merkle_root("grid_size_mismatch", grid_mismatch));
+    let mut non_boolean = TrapMerkleRootInputs::opening(&tree, &layout, 0, 0, 0);
+    non_boolean.trap_value = 2;
+    cases.push(trap_merkle_root("non_boolean_trap", non_boolean));
+    // A zero-length proof whose root is the leaf itself is still too short for the grid
+    let mut zero_length = TrapMerkleRootInputs::opening(&tree, &layout, 0, 0, 0);
+    zero_length.proof_length = 0;
+    zero_length.trap_merkle_root = reference::trap_leaf(zero_length.trap_value);
+    cases.push(trap_merkle_root("zero_length_proof", zero_length));
+    let mut too_long = TrapMerkleRootInputs::opening(&tree, &layout, 0, 0, 0);
+    too_long.proof_length = MAX_MERKLE_TREE_DEPTH as u32 + 1;
+    cases.push(trap_merkle_root("proof_length_over_depth", too_long));
+
+    for i in 0..count {
+        let layout = rng.layout();
+        let tree = TrapTree::new(&layout)?;
+        let (x, y) = (rng.below(8), rng.below(8));
+        let index = cell_index(GRID_SIZE, x, y);
+        let mut opening = TrapMerkleRootInputs::opening(&tree, &layout, index, x, y);
+        match rng.below(6) {
+            0 => opening.trap_value = 1 - opening.trap_value,
+            1 => opening.siblings[rng.below(opening.proof_length) as usize] = rng.field(),
+            2 => opening.indices[rng.below(MAX_MERKLE_TREE_DEPTH as u32) as usize] ^= 1,
+            _ => {}
+        }
+        cases.push(trap_merkle_root(&format!("random_{i}"), opening));
+    }
+    Ok(cases)
+}
+
+fn grid_setup(name: &str, inputs: GridSetupInputs) -> Case {
+    Case {
+        name: name.to_string(),
+        inputs: Inputs::GridSetup(inputs),
+    }
+}
+
+fn grid_setup_cases(rng: &mut Rng, count: usize) -> Result<Vec<Case>> {
+    let mut cases = Vec::new();
+    for (grid, layout) in [
+        ("empty", vec![0u8; NUM_CELLS]),
+        ("full", vec![1u8; NUM_CELLS]),
+    ] {
+        cases.push(grid_setup(
+            &format!("{grid}_grid"),
+            GridSetupInputs::honest(&layout, rng.field(), rng.field())?,
+        ));
+    }
+    cases.push(grid_setup(
+        "max_salt",
+        GridSetupInputs::honest(&rng.layout(), field(FIELD_MAX), rng.field())?,
+    ));
+    // Committed with the zero salt, so only the salt check fails
+    cases.push(grid_setup(
+        "zero_salt",
+        GridSetupInputs::honest(&rng.layout(), [0u8; 32], rng.field())?,
+    ));
+    let mut miscounted = GridSetupInputs::honest(&rng.layout(), rng.field(), rng.field())?;
+    miscounted.trap_count += 1;
+    cases.push(grid_setup("trap_count_off_by_one", miscounted));
+    let mut non_boolean = GridSetupInputs::honest(&[0u8; NUM_CELLS], rng.field(), rng.field())?;
+    non_boolean.trap_values[5] = 2;
+    non_boolean.trap_count = 2;
+    cases.push(grid_setup("non_boolean_trap", non_boolean));
+    let mut flipped = GridSetupInputs::honest(&rng.layout(), rng.field(), rng.field())?;
+    flipped.trap_values[0] = 1 - flipped.trap_values[0];
+    flipped.trap_count = flipped.trap_values.iter().sum();
+    cases.push(grid_setup("flipped_cell", flipped));
+    for grid_size in [2, 10, 16] {
+        cases.push(grid_setup(
+            &format!("grid_{grid_size}"),
+            GridSetupInputs::honest(&rng.layout_of(grid_size), rng.field(), rng.field())?,
+        ));
+    }
+    // A trap in the padding past the 8x8 grid, counted and committed to as if on the grid
+    let mut outside = GridSetupInputs::honest(&[0u8; NUM_CELLS], rng.field(), rng.field())?;
+    outside.trap_values[NUM_CELLS] = 1;
+    outside.trap_count = 1;
+    cases.push(grid_setup("trap_outside_grid", outside));
+    let mut resized = GridSetupInputs::honest(&rng.layout(), rng.field(), rng.field())?;
+    resized.grid_size = 16;
+    cases.push(grid_setup("grid_size_mismatch", resized));
+    // Another defender presenting the commitment
+    let mut stolen = GridSetupInputs::honest(&rng.layout(), rng.field(), rng.field())?;
+    stolen.defender = rng.field();
+    cases.push(grid_setup("other_defender", stolen));
+
+    for i in 0..count {
+        let mut setup = GridSetupInputs::honest(&rng.layout(), rng.field(), rng.field())?;
+        match rng.below(7) {
+            0 => setup.trap_count = rng.below(NUM_CELLS as u32 + 1),
+            1 => setup.salt = rng.field(),
+            2 => setup.grid_commitment = rng.field(),
+            3 => setup.defender = rng.field(),
+            _ => {}
+        }
+        cases.push(grid_setup(&format!("random_{i}"), setup));
+    }
+    Ok(cases)
+}
+
+fn circuit_name(circuit: Circuit) -> String {
+    circuit
+        .to_possible_value()
+        .expect("no skipped variants")
+        .get_name()
+        .to_string()
+}
+
+/// Write the cases' Prover.toml files and `expected.json` under `dir`
+fn write_cases(dir: &Path, cases: &[Case]) -> Result<usize> {
+    let mut expected = Vec::new();
+    for case in cases {
+        let case_dir = dir.join(&case.name);
+        fs::create_dir_all(&case_dir)
+            .with_context(|| format!("failed to create {}", case_dir.display()))?;
+        fs::write(case_dir.join("Prover.toml"), case.inputs.prover_toml())
+            .with_context(|| format!("failed to write {}/Prover.toml", case_dir.display()))?;
+        let outcome = case.inputs.check();
+        expected.push(Expected {
+            case: case.name.clone(),
+            valid: outcome.is_ok(),
+            error: outcome.err().map(|e| e.to_string()),
+            public_inputs: hex::encode(case.inputs.public_inputs().concat()),
+        });
+    }
+    fs::write(
+        dir.join("expected.json"),
+        serde_json::to_string_pretty(&expected)? + "\n",
+    )
+    .with_context(|| format!("failed to write {}/expected.json", dir.display()))?;
+    Ok(expected.iter().filter(|e| e.valid).count())
+}
+
+/// One entry of `differential.json`
+#[derive(Serialize)]
+struct Differential {
+    case: String,
+    expected_valid: bool,
+    executed_valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reference_error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    nargo_error: Option<String>,
+}
+
+impl Differential {
+    fn agrees(&self) -> bool {
+        self.expected_valid == self.executed_valid
+    }
+}
+
+/// The line of nargo's output that names the failure
+fn nargo_failure(stderr: &str) -> String {
+    let lines = || {
+        stderr
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+    };
+    lines()
+        .find(|line| line.starts_with("error"))
+        .or_else(|| lines().next())
+        .unwrap_or("nargo execute failed")
+        .to_string()
+}
+
+/// Run every case through `nargo execute` in `work_dir`, a copy of the
+/// circuit, and write `differential.json` under `dir`
+fn execute_cases(
+    nargo: &str,
+    circuit_dir: &Path,
+    work_dir: &Path,
+    dir: &Path,
+    cases: &[Case],
+) -> Result<Vec<Differential>> {
+    if work_dir.exists() {
+        fs::remove_dir_all(work_dir)
+            .with_context(|| format!("failed to clear {}", work_dir.display()))?;
+    }
+    copy_circuit(circuit_dir, work_dir)?;
+
+    // Compile once, so a broken circuit fails here rather than as a mismatch per case
+    let compiled = Command::new(nargo)
+        .arg("compile")
+        .current_dir(work_dir)
+        .output()
+        .with_context(|| format!("failed to run {nargo}; is it installed and on PATH?"))?;
+    if !compiled.status.success() {
+        bail!(
+            "{nargo} compile failed in {}: {}",
+            work_dir.display(),
+            nargo_failure(&String::from_utf8_lossy(&compiled.stderr))
+        );
+    }
+
+    let mut results = Vec::new();
+    for case in cases {
+        fs::write(work_dir.join("Prover.toml"), case.inputs.prover_toml())
+            .with_context(|| format!("failed to write {}/Prover.toml", work_dir.display()))?;
+        let output = Command::new(nargo)
+            .arg("execute")
+            .current_dir(work_dir)
+            .output()
+            .with_context(|| format!("failed to run {nargo} execute"))?;
+        let expected = case.inputs.check();
+        results.push(Differential {
+            case: case.name.clone(),
+            expected_valid: expected.is_ok(),
+            executed_valid: output.status.success(),
+            reference_error: expected.err().map(|e| e.to_string()),
+            nargo_error: (!output.status.success())
+                .then(|| nargo_failure(&String::from_utf8_lossy(&output.stderr))),
+        });
+    }
+
+    fs::write(
+        dir.join("differential.json"),
+        serde_json::to_string_pretty(&results)? + "\n",
+    )
+    .with_context(|| format!("failed to write {}/differential.json", dir.display()))?;
+    Ok(results)
+}
+
+fn generate(circuit: Circuit, rng: &mut Rng, count: usize) -> Result<Vec<Case>> {
+    match circuit {
+        Circuit::PositionMovement => position_movement_cases(rng, count),
+        Circuit::TrapCommitment => Ok(trap_commitment_cases(rng, count)),
+        Circuit::TrapMerkleRoot => trap_merkle_root_cases(rng, count),
+        Circuit::GridSetup => grid_setup_cases(rng, count),
+    }
+}
+
+pub fn run(args: FuzzInputsArgs) -> Result<()> {
+    let seed = match args.seed {
+        Some(seed) => seed,
+        None => {
+            let mut bytes = [0u8; 8];
+            getrandom::getrandom(&mut bytes).map_err(|e| anyhow!("no system randomness: {e}"))?;
+            u64::from_le_bytes(bytes)
+        }
+    };
+    let circuits = match args.circuit {
+        Some(circuit) => vec![circuit],
+        None => Circuit::value_variants().to_vec(),
+    };
+
+    let mut rng = Rng(seed);
+    let mut mismatches = Vec::new();
+    for circuit in circuits {
+        let name = circuit_name(circuit);
+        let cases = generate(circuit, &mut rng, args.count)?;
+        let dir = args.out.join(&name);
+        let valid = write_cases(&dir, &cases)?;
+        eprintln!(
+            "{name}: {} cases ({valid} expected to execute) in {}",
+            cases.len(),
+            dir.display()
+        );
+
+        if let Some(circuits_dir) = &args.execute {
+            let results = execute_cases(
+                &args.nargo,
+                &circuits_dir.join(&name),
+                &dir.join("circuit"),
+                &dir,
+                &cases,
+            )?;
+            for result in results.iter().filter(|result| !result.agrees()) {
+                eprintln!(
+                    "  MISMATCH {}: reference {}, nargo {}",
+                    result.case,
+                    result.reference_error.as_deref().unwrap_or("accepts"),
+                    result.nargo_error.as_deref().unwrap_or("accepts")
+                );
+                mismatches.push(format!("{name}/{}", result.case));
+            }
+            eprintln!(
+                "{name}: nargo agreed with the reference on {} of {} cases",
+                results.iter().filter(|result| result.agrees()).count(),
+                results.len()
+            );
+        }
+    }
+    eprintln!("Seed: {seed} (pass --seed {seed} to regenerate)");
+    if !mismatches.is_empty() {
+        bail!(
+            "{} cases differ between the circuits and the reference: {}",
+            mismatches.len(),
+            mismatches.join(", ")
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn outcome(cases: &[Case], name: &str) -> bool {
+        cases
+            .iter()
+            .find(|case| case.name == name)
+            .unwrap()
+            .inputs
+            .check()
+            .is_ok()
+    }
+
+    #[test]
+    fn test_edge_case_expectations() {
+        let mut rng = Rng(7);
+        let cases = position_movement_cases(&mut rng, 0).unwrap();
+        assert!(outcome(&cases, "corner_7_7_1"));
+        assert!(!outcome(&cases, "y_wraps_to_next_row"));
+        assert!(!outcome(&cases, "non_boolean_trap"));
+        assert!(!outcome(&cases, "index_for_other_cell"));
+        assert!(!outcome(&cases, "lie_miss_on_trap"));
+        assert!(!outcome(&cases, "lie_hit_on_empty"));
+        assert!(!outcome(&cases, "other_grid"));
+        assert!(!outcome(&cases, "other_defender"));
+        assert!(outcome(&cases, "grid_16_corner"));
+        assert!(outcome(&cases, "grid_2_corner"));
+        assert!(!outcome(&cases, "large_grid_out_of_range"));
+        assert!(!outcome(&cases, "grid_size_too_large"));
+
+        let cases = trap_commitment_cases(&mut rng, 0);
+        assert!(outcome(&cases, "max_secret_trap"));
+        assert!(outcome(&cases, "non_boolean_trap"));
+        assert!(!outcome(&cases, "opened_as_empty"));
+        assert!(!outcome(&cases, "other_defender"));
+
+        let cases = trap_merkle_root_cases(&mut rng, 0).unwrap();
+        assert!(outcome(&cases, "full_grid_7_0"));
+        assert!(!outcome(&cases, "empty_grid_lie"));
+        assert!(outcome(&cases, "grid_10_corner"));
+        assert!(outcome(&cases, "grid_16_corner"));
+        assert!(!outcome(&cases, "y_wraps_to_next_row"));
+        assert!(!outcome(&cases, "x_out_of_range"));
+        assert!(!outcome(&cases, "index_for_other_cell"));
+        assert!(!outcome(&cases, "grid_size_mismatch"));
+        assert!(!outcome(&cases, "zero_length_proof"));
+
+        let cases = grid_setup_cases(&mut rng, 0).unwrap();
+        assert!(outcome(&cases, "full_grid"));
+        assert!(outcome(&cases, "max_salt"));
+        assert!(!outcome(&cases, "zero_salt"));
+        assert!(!outcome(&cases, "trap_count_off_by_one"));
+        assert!(!outcome(&cases, "non_boolean_trap"));
+        assert!(!outcome(&cases, "flipped_cell"));
+        assert!(outcome(&cases, "grid_10"));
+        assert!(!outcome(&cases, "trap_outside_grid"));
+        assert!(!outcome(&cases, "grid_size_mismatch"));
+    }
+
+    #[test]
+    fn test_nargo_failure() {
+        let stderr = "warning: unused variable\n\nerror: Failed assertion\n  ┌─ src/main.nr:36:5\n";
+        assert_eq!(nargo_failure(stderr), "error: Failed assertion");
+        assert_eq!(nargo_failure("  killed\n"), "killed");
+        assert_eq!(nargo_failure(""), "nargo execute failed");
+    }
+
+    /// A stand-in for nargo that only rejects non-boolean trap values
+    #[cfg(unix)]
+    fn fake_nargo(dir: &Path) -> String {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = dir.join("nargo");
+        fs::write(
+            &path,
+            "#!/bin/sh\n\
+             [ \"$1\" = compile ] && exit 0\n\
+             if grep -q 'trap_value = \"2\"' Prover.toml; then\n\
+             echo 'error: Trap value must be 0 or 1' >&2; exit 1\n\
+             fi\n",
+        )
+        .unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_execute_cases() {
+        let dir = tempfile::tempdir().unwrap();
+        let circuit_dir = dir.path().join("position-movement");
+        fs::create_dir_all(circuit_dir.join("src")).unwrap();
+        fs::write(circuit_dir.join("Nargo.toml"), "").unwrap();
+
+        let cases = position_movement_cases(&mut Rng(3), 0).unwrap();
+        let results = execute_cases(
+            &fake_nargo(dir.path()),
+            &circuit_dir,
+            &dir.path().join("work"),
+            dir.path(),
+            &cases,
+        )
+        .unwrap();
+        let result = |name: &str| results.iter().find(|r| r.case == name).unwrap();
+
+        assert!(result("corner_0_7_1").agrees());
+        let non_boolean = result("non_boolean_trap");
+        assert!(non_boolean.agrees());
+        assert_eq!(
+            non_boolean.nargo_error.as_deref(),
+            Some("error: Trap value must be 0 or 1")
+        );
+        // The stand-in accepts what the circuit's range check rejects
+        assert!(!result("x_out_of_range").agrees());
+        assert!(dir.path().join("differential.json").exists());
+    }
+
+    #[test]
+    fn test_write_cases() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut rng = Rng(1);
+        let cases = generate(Circuit::TrapMerkleRoot, &mut rng, 4).unwrap();
+        write_cases(dir.path(), &cases).unwrap();
+
+        let toml = fs::read_to_string(dir.path().join("empty_grid_0_0/Prover.toml")).unwrap();
+        assert!(toml.contains(
+            "trap_merkle_proof_indices = [\"0\", \"0\", \"0\", \"0\", \"0\", \"0\", \"0\", \"0\"]"
+        ));
+        let expected: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(dir.path().join("expected.json")).unwrap())
+                .unwrap();
+        assert_eq!(expected.as_array().unwrap().len(), cases.len());
+        assert_eq!(
+            expected[0]["public_inputs"].as_str().unwrap().len(),
+            (5 + 2 * MAX_MERKLE_TREE_DEPTH) * 64
+        );
+    }
+}
@@ -0,0 +1,354 @@
+//! `grid` subcommands
+//!
+//! Layout files are read and written with the SDK's [`TrapLayout`] (the
+//! dApp's grid export format).
+
+use std::path::PathBuf;
+
+use anyhow::{bail, Result};
+use clap::{Args, Subcommand, ValueEnum};
+use trap_grid_sdk::{client, Move};
+
+use crate::encoding::hex_field;
+use crate::network::NetworkArgs;
+use crate::reference::{trap_leaf, TrapMerkleRootInputs, TrapTree};
+use crate::{backup, design, play, sealed, secret, seed};
+
+pub use trap_grid_sdk::layout::{cell_index, TrapLayout, NUM_CELLS};
+pub use trap_grid_sdk::GRID_SIZE;
+
+#[derive(Subcommand)]
+pub enum GridCommand {
+    /// Render a trap layout and/or a live game as a board
+    Show(ShowArgs),
+
+    /// Derive a trap layout and salt deterministically from a seed phrase
+    Gen(GenArgs),
+
+    /// Print one cell's Merkle opening: its leaf, path and circuit inputs
+    Open(OpenArgs),
+
+    /// Save a layout file, encrypted with a passphrase when --encrypt is given
+    Save(sealed::SaveArgs),
+
+    /// Decrypt and check a layout written by `grid save`
+    Load(sealed::LoadArgs),
+
+    /// Print a backup code holding a layout, its salt and its session
+    Export(backup::ExportArgs),
+
+    /// Restore a layout from a backup code, checking it against the session's moves
+    Import(backup::ImportArgs),
+
+    /// Place traps on an interactive board, save the layout and optionally start a game
+    Design(design::DesignArgs),
+}
+
+#[derive(Args)]
+pub struct ShowArgs {
+    /// Trap layout file
+    #[arg(long)]
+    pub layout: Option<PathBuf>,
+
+    /// Fetch the moves of this session from the trap-grid contract
+    #[arg(long)]
+    pub session: Option<u32>,
+
+    /// Trap-grid contract id, overriding the profile's (or set TRAP_GRID_CONTRACT)
+    #[arg(long)]
+    pub contract_id: Option<String>,
+
+    #[command(flatten)]
+    pub network: NetworkArgs,
+
+    /// Use plain ASCII instead of Unicode symbols
+    #[arg(long)]
+    pub ascii: bool,
+}
+
+#[derive(Args)]
+pub struct GenArgs {
+    /// Seed phrase; the same phrase always yields the same layout and salt.
+    /// Prompted for without echo when omitted (avoids leaking it into shell history)
+    #[arg(long, conflicts_with = "stdin")]
+    pub seed: Option<String>,
+
+    /// Read the seed phrase from the first line of stdin
+    #[arg(long)]
+    pub stdin: bool,
+
+    /// Number of traps to place
+    #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u32).range(0..=NUM_CELLS as i64))]
+    pub traps: u32,
+
+    /// Write the layout to this file instead of stdout
+    #[arg(long)]
+    pub out: Option<PathBuf>,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+pub enum OpeningFormat {
+    /// Leaf, root and path, one level per line
+    Text,
+    /// The trap-merkle-root circuit's Prover.toml
+    Toml,
+    /// Hex-encoded public inputs, as the verifier takes them
+    Hex,
+}
+
+#[derive(Args)]
+pub struct OpenArgs {
+    /// Trap layout file
+    #[arg(long)]
+    pub layout: PathBuf,
+
+    /// Cell to open, as x,y
+    #[arg(long, value_parser = play::parse_cell)]
+    pub cell: (u32, u32),
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OpeningFormat::Text)]
+    pub format: OpeningFormat,
+}
+
+pub fn run(command: GridCommand) -> Result<()> {
+    match command {
+        GridCommand::Show(args) => show(args),
+        GridCommand::Gen(args) => gen(args),
+        GridCommand::Open(args) => open(args),
+        GridCommand::Save(args) => sealed::save(args),
+        GridCommand::Load(args) => sealed::load(args),
+        GridCommand::Export(args) => backup::export(args),
+        GridCommand::Import(args) => backup::import(args),
+        GridCommand::Design(args) => design::run(args),
+    }
+}
+
+fn show(args: ShowArgs) -> Result<()> {
+    if args.layout.is_none() && args.session.is_none() {
+        bail!("pass --layout, --session, or both");
+    }
+
+    let layout = args.layout.as_deref().map(TrapLayout::load).transpose()?;
+    let mut grid_size = layout.as_ref().map_or(GRID_SIZE, TrapLayout::grid_size);
+    let moves = match args.session {
+        Some(session_id) => {
+            let network = args.network.resolve(args.contract_id.as_deref())?;
+            let (rpc, contract) = (network.rpc(), network.contract()?);
+            if let Some(game) = client::fetch_game(&rpc, &contract, session_id)? {
+                if layout.is_some() && game.grid_size != grid_size {
+                    bail!(
+                        "the layout is a {grid_size}x{grid_size} grid, session {session_id} plays on {0}x{0}",
+                        game.grid_size
+                    );
+                }
+                grid_size = game.grid_size;
+            }
+            client::fetch_moves(&rpc, &contract, session_id)?
+        }
+        None => Vec::new(),
+    };
+
+    print!(
+        "{}",
+        render_board(grid_size, layout.as_ref(), &moves, args.ascii)
+    );
+    Ok(())
+}
+
+fn gen(args: GenArgs) -> Result<()> {
+    let phrase = secret::read_secret(args.seed, args.stdin, "Seed phrase: ")?;
+    let layout = seed::derive_layout(&phrase, args.traps as usize);
+
+    match &args.out {
+        Some(path) => {
+            layout.save(path)?;
+            print!("{}", render_board(GRID_SIZE, Some(&layout), &[], false));
+            println!("Layout written to {}", path.display());
+        }
+        None => println!("{}", serde_json::to_string(&layout)?),
+    }
+    Ok(())
+}
+
+/// Open cell (`x`, `y`) of `layout` against the layout's Merkle root
+fn opening(layout: &TrapLayout, x: u32, y: u32) -> Result<TrapMerkleRootInputs> {
+    let grid_size = layout.grid_size();
+    play::check_cell(grid_size, x, y)?;
+    let tree = TrapTree::new(&layout.trap_values)?;
+    Ok(TrapMerkleRootInputs::opening(
+        &tree,
+        &layout.trap_values,
+        cell_index(grid_size, x, y),
+        x,
+        y,
+    ))
+}
+
+fn opening_text(opening: &TrapMerkleRootInputs) -> String {
+    let mut out = format!(
+        "cell ({}, {}), leaf index {}\ntrap value: {}\nleaf: {}\nroot: {}\npath (leaf level first):\n",
+        opening.move_x,
+        opening.move_y,
+        cell_index(opening.grid_size, opening.move_x, opening.move_y),
+        opening.trap_value,
+        hex_field(&trap_leaf(opening.trap_value)),
+        hex_field(&opening.trap_merkle_root)
+    );
+    let path = opening.indices.iter().zip(&opening.siblings);
+    for (level, (index, sibling)) in path.take(opening.proof_length as usize).enumerate() {
+        out.push_str(&format!(
+            "  {level}: index {index}, sibling {}\n",
+            hex_field(sibling)
+        ));
+    }
+    out
+}
+
+fn open(args: OpenArgs) -> Result<()> {
+    let layout = TrapLayout::load(&args.layout)?;
+    let (x, y) = args.cell;
+    let opening = opening(&layout, x, y)?;
+    match args.format {
+        OpeningFormat::Text => print!("{}", opening_text(&opening)),
+        OpeningFormat::Toml => print!("{}", opening.prover_toml()),
+        OpeningFormat::Hex => println!("{}", hex::encode(opening.public_inputs().encode())),
+    }
+    Ok(())
+}
+
+struct Symbols {
+    trap: char,
+    empty: char,
+    hit: char,
+    miss: char,
+}
+
+const UNICODE: Symbols = Symbols {
+    trap: '■',
+    empty: '·',
+    hit: '✖',
+    miss: '○',
+};
+const ASCII: Symbols = Symbols {
+    trap: '#',
+    empty: '.',
+    hit: 'X',
+    miss: 'o',
+};
+
+/// Render a `grid_size` x `grid_size` board with rows for `x` and columns for `y`
+pub fn render_board(
+    grid_size: u32,
+    layout: Option<&TrapLayout>,
+    moves: &[Move],
+    ascii: bool,
+) -> String {
+    let symbols = if ascii { &ASCII } else { &UNICODE };
+    let mut out = String::from("   y");
+    for y in 0..grid_size {
+        out.push_str(&format!(" {y}"));
+    }
+    out.push_str("\n x\n");
+
+    for x in 0..grid_size {
+        out.push_str(&format!(" {x}  "));
+        for y in 0..grid_size {
+            let played = moves.iter().find(|m| m.x == x && m.y == y);
+            let cell = match (played, layout) {
+                (Some(m), _) if m.is_hit => symbols.hit,
+                (Some(_), _) => symbols.miss,
+                (None, Some(layout)) if layout.is_trap(x, y) => symbols.trap,
+                (None, _) => symbols.empty,
+            };
+            out.push(' ');
+            out.push(cell);
+        }
+        out.push('\n');
+    }
+
+    out.push('\n');
+    let hits = moves.iter().filter(|m| m.is_hit).count();
+    if let Some(layout) = layout {
+        out.push_str(&format!(
+            "{} trap   traps: {}\n",
+            symbols.trap,
+            layout.trap_count()
+        ));
+    }
+    if !moves.is_empty() || layout.is_none() {
+        out.push_str(&format!(
+            "{} hit    {} miss    {} unknown   moves: {}, hits: {}, misses: {}\n",
+            symbols.hit,
+            symbols.miss,
+            symbols.empty,
+            moves.len(),
+            hits,
+            moves.len() - hits
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn layout_with_traps(traps: &[(u32, u32)]) -> TrapLayout {
+        let mut trap_values = vec![0; NUM_CELLS];
+        for (x, y) in traps {
+            trap_values[cell_index(GRID_SIZE, *x, *y)] = 1;
+        }
+        TrapLayout {
+            trap_values,
+            salt: None,
+        }
+    }
+
+    #[test]
+    fn test_render_board() {
+        let layout = layout_with_traps(&[(0, 0), (1, 2)]);
+        let moves = vec![
+            Move {
+                x: 0,
+                y: 0,
+                is_hit: true,
+                verified: true,
+            },
+            Move {
+                x: 0,
+                y: 1,
+                is_hit: false,
+                verified: true,
+            },
+        ];
+        let board = render_board(GRID_SIZE, Some(&layout), &moves, true);
+        let rows: Vec<&str> = board.lines().collect();
+
+        assert_eq!(rows[2], " 0   X o . . . . . .");
+        assert_eq!(rows[3], " 1   . . # . . . . .");
+        assert!(board.contains("traps: 2"));
+        assert!(board.contains("moves: 2, hits: 1, misses: 1"));
+    }
+
+    #[test]
+    fn test_opening() {
+        let layout = layout_with_traps(&[(2, 3), (7, 7)]);
+        let opening = opening(&layout, 2, 3).unwrap();
+        assert_eq!(opening.trap_value, 1);
+        crate::reference::check_trap_merkle_root(&opening).unwrap();
+
+        let text = opening_text(&opening);
+        assert!(text.starts_with("cell (2, 3), leaf index 19\ntrap value: 1\n"));
+        assert!(text.contains("  0: index 1, sibling 0x"));
+        assert!(opening
+            .prover_toml()
+            .contains("move_x = \"2\"\nmove_y = \"3\"\n"));
+
+        let bytes = opening.public_inputs().encode();
+        let decoded = trap_grid_encoding::TrapMerkleRoot::decode(&bytes).unwrap();
+        assert_eq!(decoded.indices, [1, 1, 0, 0, 1, 0, 0, 0]);
+        assert_eq!(decoded.trap_merkle_root, opening.trap_merkle_root);
+        assert!(self::opening(&layout, 8, 0).is_err());
+    }
+}
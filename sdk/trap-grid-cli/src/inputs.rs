@@ -0,0 +1,344 @@
+//! `encode-inputs` / `decode-inputs` subcommands
+//!
+//! Produce the exact public-input bytes the deployed verifier expects for a
+//! circuit, and decode such bytes back into named values when a verification
+//! fails and it is unclear which input is off.
+
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{bail, Context, Result};
+use clap::Args;
+
+use trap_grid_encoding::{
+    merkle_depth, GridSetup, PositionMovement, TrapCommitment, TrapMerkleRoot, MAX_GRID_SIZE,
+    MIN_GRID_SIZE,
+};
+use trap_grid_sdk::commitment::{defender_field, defender_key};
+
+use crate::encoding::{self, Circuit, Claim, FieldBytes, MAX_MERKLE_TREE_DEPTH};
+use crate::grid::{cell_index, GRID_SIZE};
+
+#[derive(Args)]
+pub struct EncodeInputsArgs {
+    /// Circuit whose public-input layout to use
+    #[arg(long, value_enum)]
+    pub circuit: Circuit,
+
+    /// X coordinate of the move (position-movement, trap-merkle-root)
+    #[arg(long)]
+    pub x: Option<u32>,
+
+    /// Y coordinate of the move (position-movement, trap-merkle-root)
+    #[arg(long)]
+    pub y: Option<u32>,
+
+    /// Defender's claim (position-movement)
+    #[arg(long, value_enum)]
+    pub claim: Option<Claim>,
+
+    /// Trap commitment (trap-commitment) or grid commitment (grid-setup,
+    /// position-movement)
+    #[arg(long)]
+    pub commitment: Option<String>,
+
+    /// Defender address the commitment is bound to (trap-commitment,
+    /// grid-setup)
+    #[arg(long)]
+    pub defender: Option<String>,
+
+    /// Trap Merkle root (trap-merkle-root)
+    #[arg(long)]
+    pub root: Option<String>,
+
+    /// Comma-separated Merkle siblings, leaf level first (trap-merkle-root)
+    #[arg(long, value_delimiter = ',')]
+    pub siblings: Vec<String>,
+
+    /// Number of traps the grid holds (grid-setup)
+    #[arg(long)]
+    pub traps: Option<u32>,
+
+    /// Side of the game's grid, the contract's configured `grid_size`
+    #[arg(long, default_value_t = GRID_SIZE, value_parser = clap::value_parser!(u32).range(MIN_GRID_SIZE as i64..=MAX_GRID_SIZE as i64))]
+    pub grid_size: u32,
+
+    /// Write raw bytes to this file instead of printing hex
+    #[arg(long)]
+    pub out: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct DecodeInputsArgs {
+    /// Circuit whose public-input layout to use
+    #[arg(long, value_enum)]
+    pub circuit: Circuit,
+
+    /// Hex-encoded public inputs (as passed to the contract)
+    #[arg(long, conflicts_with = "file", required_unless_present = "file")]
+    pub hex: Option<String>,
+
+    /// Raw public inputs file (e.g. `target/public_inputs`)
+    #[arg(long)]
+    pub file: Option<PathBuf>,
+}
+
+fn required<T: Copy>(value: Option<T>, flag: &str, circuit: Circuit) -> Result<T> {
+    value.with_context(|| format!("--{flag} is required for {circuit:?}"))
+}
+
+fn coordinates(args: &EncodeInputsArgs) -> Result<(u32, u32)> {
+    let x = required(args.x, "x", args.circuit)?;
+    let y = required(args.y, "y", args.circuit)?;
+    let size = args.grid_size;
+    if x >= size || y >= size {
+        bail!("move ({x}, {y}) is outside the {size}x{size} grid");
+    }
+    Ok((x, y))
+}
+
+/// Encode the public inputs described by the arguments
+pub fn encode(args: &EncodeInputsArgs) -> Result<Vec<u8>> {
+    let bytes = match args.circuit {
+        Circuit::PositionMovement => {
+            let (move_x, move_y) = coordinates(args)?;
+            let claim = required(args.claim, "claim", args.circuit)?;
+            let commitment = args
+                .commitment
+                .as_deref()
+                .context("--commitment is required for PositionMovement")?;
+            PositionMovement {
+                move_x,
+                move_y,
+                is_hit: claim.is_hit(),
+                grid_size: args.grid_size,
+                grid_commitment: encoding::parse_field(commitment)?,
+            }
+            .encode()
+            .to_vec()
+        }
+        Circuit::TrapCommitment => {
+            let commitment = args
+                .commitment
+                .as_deref()
+                .context("--commitment is required for TrapCommitment")?;
+            let defender = args
+                .defender
+                .as_deref()
+                .context("--defender is required for TrapCommitment")?;
+            TrapCommitment {
+                trap_commitment: encoding::parse_field(commitment)?,
+                defender: defender_field(defender)?,
+            }
+            .encode()
+            .to_vec()
+        }
+        Circuit::TrapMerkleRoot => {
+            let (move_x, move_y) = coordinates(args)?;
+            let root = args
+                .root
+                .as_deref()
+                .context("--root is required for TrapMerkleRoot")?;
+            // Levels past the grid's tree depth are zero
+            let depth = merkle_depth(args.grid_size);
+            if args.siblings.len() != depth {
+                bail!(
+                    "expected {depth} --siblings for a {0}x{0} grid, found {1}",
+                    args.grid_size,
+                    args.siblings.len()
+                );
+            }
+
+            let leaf_index = cell_index(args.grid_size, move_x, move_y);
+            let mut siblings = [FieldBytes::default(); MAX_MERKLE_TREE_DEPTH];
+            for (sibling, value) in siblings.iter_mut().zip(&args.siblings) {
+                *sibling = encoding::parse_field(value)?;
+            }
+            TrapMerkleRoot {
+                trap_merkle_root: encoding::parse_field(root)?,
+                move_x,
+                move_y,
+                proof_length: depth as u32,
+                indices: std::array::from_fn(|i| ((leaf_index >> i) & 1) as u8),
+                siblings,
+                grid_size: args.grid_size,
+            }
+            .encode()
+            .to_vec()
+        }
+        Circuit::GridSetup => {
+            let commitment = args
+                .commitment
+                .as_deref()
+                .context("--commitment is required for GridSetup")?;
+            let defender = args
+                .defender
+                .as_deref()
+                .context("--defender is required for GridSetup")?;
+            GridSetup {
+                grid_commitment: encoding::parse_field(commitment)?,
+                trap_count: required(args.traps, "traps", args.circuit)?,
+                grid_size: args.grid_size,
+                defender: defender_key(defender)?,
+            }
+            .encode()
+            .to_vec()
+        }
+    };
+    Ok(bytes)
+}
+
+pub fn run_encode(args: EncodeInputsArgs) -> Result<()> {
+    let bytes = encode(&args)?;
+    match &args.out {
+        Some(path) => {
+            fs::write(path, &bytes)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+            println!("{} bytes written to {}", bytes.len(), path.display());
+        }
+        None => println!("{}", hex::encode(&bytes)),
+    }
+    Ok(())
+}
+
+/// Decode public inputs into `(name, value)` pairs
+pub fn decode(circuit: Circuit, bytes: &[u8]) -> Result<Vec<(String, String)>> {
+    let fields = encoding::split_fields(bytes)?;
+    let names = circuit.field_names();
+    if fields.len() != names.len() {
+        bail!(
+            "{circuit:?} has {} public inputs, found {}",
+            names.len(),
+            fields.len()
+        );
+    }
+    Ok(names
+        .iter()
+        .map(|name| name.to_string())
+        .zip(fields.iter().map(encoding::format_field))
+        .collect())
+}
+
+pub fn run_decode(args: DecodeInputsArgs) -> Result<()> {
+    let bytes = match (&args.hex, &args.file) {
+        (Some(value), _) => {
+            hex::decode(value.trim_start_matches("0x")).context("invalid hex public inputs")?
+        }
+        (None, Some(path)) => {
+            fs::read(path).with_context(|| format!("failed to read {}", path.display()))?
+        }
+        (None, None) => unreachable!("clap requires --hex or --file"),
+    };
+
+    for (name, value) in decode(args.circuit, &bytes)? {
+        println!("{name} = {value}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn args(circuit: Circuit) -> EncodeInputsArgs {
+        EncodeInputsArgs {
+            circuit,
+            x: None,
+            y: None,
+            claim: None,
+            commitment: None,
+            defender: None,
+            root: None,
+            siblings: Vec::new(),
+            traps: None,
+            grid_size: GRID_SIZE,
+            out: None,
+        }
+    }
+
+    #[test]
+    fn test_position_movement_round_trip() {
+        let mut a = args(Circuit::PositionMovement);
+        a.x = Some(2);
+        a.y = Some(3);
+        a.claim = Some(Claim::Hit);
+        assert!(encode(&a).is_err());
+        a.commitment = Some("0x2a".into());
+
+        let bytes = encode(&a).unwrap();
+        assert_eq!(bytes.len(), 160);
+        let decoded = decode(Circuit::PositionMovement, &bytes).unwrap();
+        assert_eq!(decoded[0], ("move_x".to_string(), "2".to_string()));
+        assert_eq!(decoded[2], ("is_hit".to_string(), "1".to_string()));
+        assert_eq!(decoded[3], ("grid_size".to_string(), "8".to_string()));
+        assert_eq!(
+            decoded[4],
+            ("grid_commitment".to_string(), "42".to_string())
+        );
+    }
+
+    #[test]
+    fn test_trap_merkle_root_indices() {
+        // Matches circuits/trap-merkle-root/Prover.toml: (2, 3) -> leaf 19 = 0b00010011
+        let mut a = args(Circuit::TrapMerkleRoot);
+        a.x = Some(2);
+        a.y = Some(3);
+        a.root = Some("0x0c7c517f1ddb7e98c83cda5a4061540940af216957d2993a2cf02dd26eca13fb".into());
+        a.siblings = vec!["0x1".into(); 6];
+
+        let bytes = encode(&a).unwrap();
+        let decoded = decode(Circuit::TrapMerkleRoot, &bytes).unwrap();
+        let indices: Vec<&str> = decoded[4..12].iter().map(|(_, v)| v.as_str()).collect();
+        assert_eq!(indices, ["1", "1", "0", "0", "1", "0", "0", "0"]);
+        assert_eq!(decoded[3].1, "6");
+        assert_eq!(decoded[19].1, "0");
+
+        // A 16x16 grid takes a sibling per level of its 256-leaf tree
+        a.grid_size = 16;
+        assert!(encode(&a).is_err());
+        a.siblings = vec!["0x1".into(); 8];
+        let decoded = decode(Circuit::TrapMerkleRoot, &encode(&a).unwrap()).unwrap();
+        assert_eq!(decoded[3].1, "8");
+        assert_eq!(decoded[20], ("grid_size".to_string(), "16".to_string()));
+    }
+
+    #[test]
+    fn test_grid_setup_round_trip() {
+        let mut a = args(Circuit::GridSetup);
+        a.commitment = Some("0x2a".into());
+        assert!(encode(&a).is_err());
+        a.traps = Some(10);
+        assert!(encode(&a).is_err());
+        a.defender = Some("GAIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCF6M".into());
+
+        let bytes = encode(&a).unwrap();
+        let decoded = decode(Circuit::GridSetup, &bytes).unwrap();
+        assert_eq!(
+            decoded[0],
+            ("grid_commitment".to_string(), "42".to_string())
+        );
+        assert_eq!(decoded[1], ("trap_count".to_string(), "10".to_string()));
+        // The defender's key: its first 31 bytes, then its last
+        assert_eq!(decoded[4], ("defender[1]".to_string(), "17".to_string()));
+    }
+
+    #[test]
+    fn test_encode_rejects_missing_or_invalid_args() {
+        assert!(encode(&args(Circuit::PositionMovement)).is_err());
+
+        let mut a = args(Circuit::PositionMovement);
+        a.x = Some(8);
+        a.y = Some(0);
+        a.claim = Some(Claim::Miss);
+        a.commitment = Some("0x1".into());
+        assert!(encode(&a).is_err());
+
+        let mut a = args(Circuit::TrapCommitment);
+        a.commitment = Some("0x1".into());
+        assert!(encode(&a).is_err());
+        a.defender = Some("GABC".into());
+        assert!(encode(&a).is_err());
+
+        assert!(decode(Circuit::TrapCommitment, &[0u8; 32]).is_err());
+    }
+}
@@ -0,0 +1,213 @@
+//! `invoke` subcommand
+//!
+//! Turns a session id, move coordinates, the defender's claim and the proof
+//! artifacts written by `bb` into a `stellar contract invoke` command for the
+//! trap-grid contract's `make_move` entrypoint, which answers the move the
+//! attacker submitted with `submit_move`. `Bytes` arguments are passed
+//! to stellar-cli as plain hex, which is the part most people get wrong when
+//! assembling the command by hand. With `--submit` the transaction is instead
+//! signed with a key from the key store and sent over RPC directly.
+
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, bail, Context, Result};
+use clap::Args;
+use stellar_xdr::curr::{Limits, ScVal, WriteXdr};
+use trap_grid_encoding::PositionMovement;
+use trap_grid_sdk::proof_format::{self, ProofFormat, ProofSystem};
+use trap_grid_sdk::tx::Submitter;
+use trap_grid_sdk::{Network, ProvenMove};
+
+use crate::encoding::{self, Claim};
+use crate::keys::KeyStore;
+use crate::network::NetworkArgs;
+use crate::stellar::InvokeCommand;
+
+#[derive(Args, Debug)]
+pub struct InvokeArgs {
+    /// Trap-grid contract id, overriding the profile's (or set TRAP_GRID_CONTRACT)
+    #[arg(long)]
+    pub contract_id: Option<String>,
+
+    #[command(flatten)]
+    pub network: NetworkArgs,
+
+    /// Source account, the game's defender, who must authorize the move: a
+    /// stellar-cli identity, or a `trap-grid keys` name with --submit/--sign-only
+    #[arg(long, env = "STELLAR_SOURCE_ACCOUNT")]
+    pub source: String,
+
+    /// Game session identifier
+    #[arg(long)]
+    pub session: u32,
+
+    /// X coordinate of the move (0-7)
+    #[arg(long)]
+    pub x: u32,
+
+    /// Y coordinate of the move (0-7)
+    #[arg(long)]
+    pub y: u32,
+
+    /// Defender's claim for the move
+    #[arg(long, value_enum)]
+    pub claim: Claim,
+
+    /// Proof bytes file (e.g. `target/proof`)
+    #[arg(long)]
+    pub proof: PathBuf,
+
+    /// Layout of the proof file (barretenberg, barretenberg-fields, snarkjs,
+    /// arkworks, arkworks-compressed, gnark, gnark-compressed); detected when
+    /// omitted
+    #[arg(long)]
+    pub proof_format: Option<ProofFormat>,
+
+    /// Public inputs file (e.g. `target/public_inputs`)
+    #[arg(long)]
+    pub public_inputs: PathBuf,
+
+    /// Skip checking the public inputs against the move coordinates and claim
+    #[arg(long)]
+    pub no_check: bool,
+
+    /// Only build the transaction and print its unsigned XDR instead of sending it
+    #[arg(long, conflicts_with_all = ["submit", "sign_only"])]
+    pub build_only: bool,
+
+    /// Sign with the --source key and send the transaction over RPC
+    #[arg(long, conflicts_with = "sign_only")]
+    pub submit: bool,
+
+    /// Sign with the --source key and print the signed transaction XDR without sending it
+    #[arg(long)]
+    pub sign_only: bool,
+}
+
+pub fn run(args: InvokeArgs) -> Result<()> {
+    let proof = fs::read(&args.proof)
+        .with_context(|| format!("failed to read proof file {}", args.proof.display()))?;
+    let public_inputs = fs::read(&args.public_inputs).with_context(|| {
+        format!(
+            "failed to read public inputs file {}",
+            args.public_inputs.display()
+        )
+    })?;
+
+    if proof.is_empty() {
+        bail!("proof file {} is empty", args.proof.display());
+    }
+    let (format, proof) = match args.proof_format {
+        Some(format) => (format, format.normalize(&proof)?),
+        None => proof_format::normalize(&proof)?,
+    };
+    if format.system() != ProofSystem::UltraHonk {
+        eprintln!(
+            "note: {} is a {format} Groth16 proof; only a Groth16 verifier accepts it",
+            args.proof.display()
+        );
+    }
+    encoding::split_fields(&public_inputs)?;
+    if !args.no_check {
+        check_public_inputs(&public_inputs, args.x, args.y, args.claim)?;
+    }
+
+    let proven = ProvenMove {
+        session_id: args.session,
+        x: args.x,
+        y: args.y,
+        is_hit: args.claim.is_hit(),
+        proof,
+        public_inputs,
+    };
+
+    let network = args.network.resolve(args.contract_id.as_deref())?;
+    let contract = network.contract()?;
+    if args.submit || args.sign_only {
+        let source = KeyStore::open()?.load(&args.source)?;
+        let rpc = network.rpc();
+        let submitter = Submitter::new(&rpc)?;
+        let envelope = submitter.build(&source, &contract, "make_move", proven.args()?)?;
+        if args.sign_only {
+            println!("{}", envelope.to_xdr_base64(Limits::none())?);
+        } else {
+            let verified = submitter.send(&envelope).context("make_move failed")?;
+            println!(
+                "Move ({}, {}) recorded in session {} (proof verified: {})",
+                args.x,
+                args.y,
+                args.session,
+                matches!(verified, ScVal::Bool(true))
+            );
+        }
+        return Ok(());
+    }
+
+    let mut command = invoke_command(&proven, &contract.to_string(), &args.source, &network);
+    command.build_only = args.build_only;
+    println!("{}", command.to_shell());
+    Ok(())
+}
+
+/// Check that position-movement public inputs match the move being submitted
+fn check_public_inputs(public_inputs: &[u8], x: u32, y: u32, claim: Claim) -> Result<()> {
+    let decoded = PositionMovement::decode(public_inputs).map_err(|err| {
+        anyhow!(
+            "{err} (move_x, move_y, is_hit, grid_size, grid_commitment); pass --no-check for other circuits"
+        )
+    })?;
+
+    let expected = [
+        ("move_x", decoded.move_x, x),
+        ("move_y", decoded.move_y, y),
+        ("is_hit", decoded.is_hit.into(), claim.is_hit().into()),
+    ];
+    for (name, found, value) in expected {
+        if found != value {
+            bail!("public input {name} is {found} but the move has {name} = {value}");
+        }
+    }
+    Ok(())
+}
+
+/// `make_move` invocation with the proof artifacts hex-encoded
+pub fn invoke_command(
+    proven: &ProvenMove,
+    contract_id: &str,
+    source: &str,
+    network: &Network,
+) -> InvokeCommand {
+    InvokeCommand::new(contract_id, source, network, "make_move")
+        .arg("session_id", proven.session_id)
+        .arg("x", proven.x)
+        .arg("y", proven.y)
+        .arg("is_hit", proven.is_hit)
+        .arg("proof", hex::encode(&proven.proof))
+        .arg("public_inputs", hex::encode(&proven.public_inputs))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::encoding::encode_u64;
+
+    fn inputs(x: u64, y: u64, hit: u64) -> Vec<u8> {
+        [
+            encode_u64(x),
+            encode_u64(y),
+            encode_u64(hit),
+            encode_u64(8),
+            [7; 32],
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn test_check_public_inputs() {
+        assert!(check_public_inputs(&inputs(2, 3, 1), 2, 3, Claim::Hit).is_ok());
+        assert!(check_public_inputs(&inputs(2, 3, 1), 2, 3, Claim::Miss).is_err());
+        assert!(check_public_inputs(&inputs(3, 2, 0), 2, 3, Claim::Miss).is_err());
+        assert!(check_public_inputs(&inputs(2, 3, 0)[..64], 2, 3, Claim::Miss).is_err());
+    }
+}
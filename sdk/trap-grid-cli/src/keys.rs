@@ -0,0 +1,459 @@
+//! `keys` subcommands and the on-disk key store
+//!
+//! Each identity is stored as `<name>.json` in `$TRAP_GRID_HOME/keys`
+//! (default: `trap-grid/keys` under the platform config directory). Files are
+//! created with mode 0600 inside a 0700 directory. With `--encrypt` the secret
+//! key is additionally sealed with XChaCha20-Poly1305 under a key derived from
+//! a passphrase with Argon2id, and the passphrase is asked for whenever the key
+//! signs something.
+
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Context, Result};
+use argon2::Argon2;
+use chacha20poly1305::aead::Aead;
+use chacha20poly1305::{KeyInit, XChaCha20Poly1305, XNonce};
+use clap::{Args, Subcommand};
+use serde::{Deserialize, Serialize};
+use trap_grid_sdk::rpc::RpcClient;
+use trap_grid_sdk::Keypair;
+use zeroize::Zeroizing;
+
+use crate::network::NetworkArgs;
+use crate::secret;
+
+/// Passphrase for encrypted keys, read instead of prompting when set
+const PASSPHRASE_ENV: &str = "TRAP_GRID_KEY_PASSPHRASE";
+
+#[derive(Serialize, Deserialize)]
+struct KeyFile {
+    address: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    secret: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    encrypted: Option<EncryptedSecret>,
+}
+
+/// A secret sealed with XChaCha20-Poly1305 under an Argon2id key (hex fields)
+#[derive(Serialize, Deserialize)]
+pub struct EncryptedSecret {
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+fn cipher(passphrase: &str, salt: &[u8]) -> Result<XChaCha20Poly1305> {
+    let mut key = Zeroizing::new([0u8; 32]);
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, key.as_mut())
+        .map_err(|e| anyhow!("key derivation failed: {e}"))?;
+    Ok(XChaCha20Poly1305::new(key.as_ref().into()))
+}
+
+impl EncryptedSecret {
+    pub fn seal(secret: &str, passphrase: &str) -> Result<Self> {
+        let mut salt = [0u8; 16];
+        let mut nonce = [0u8; 24];
+        getrandom::getrandom(&mut salt).map_err(|e| anyhow!("no system randomness: {e}"))?;
+        getrandom::getrandom(&mut nonce).map_err(|e| anyhow!("no system randomness: {e}"))?;
+        let ciphertext = cipher(passphrase, &salt)?
+            .encrypt(XNonce::from_slice(&nonce), secret.as_bytes())
+            .map_err(|_| anyhow!("failed to encrypt the secret"))?;
+        Ok(Self {
+            salt: hex::encode(salt),
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+        })
+    }
+
+    pub fn open(&self, passphrase: &str) -> Result<Zeroizing<String>> {
+        let salt = hex::decode(&self.salt).context("corrupt encrypted salt")?;
+        let nonce = hex::decode(&self.nonce).context("corrupt encrypted nonce")?;
+        let ciphertext = hex::decode(&self.ciphertext).context("corrupt encrypted ciphertext")?;
+        if nonce.len() != 24 {
+            bail!("corrupt encrypted nonce");
+        }
+        let plaintext = Zeroizing::new(
+            cipher(passphrase, &salt)?
+                .decrypt(XNonce::from_slice(&nonce), ciphertext.as_slice())
+                .map_err(|_| anyhow!("wrong passphrase"))?,
+        );
+        Ok(Zeroizing::new(
+            String::from_utf8(plaintext.to_vec()).context("corrupt encrypted secret")?,
+        ))
+    }
+}
+
+/// Passphrase from the `env_var` environment variable or a hidden prompt
+pub fn read_passphrase(env_var: &str, prompt: &str) -> Result<Zeroizing<String>> {
+    match env::var(env_var) {
+        Ok(value) => secret::read_secret(Some(value), false, prompt),
+        Err(_) => secret::read_secret(None, false, prompt),
+    }
+}
+
+/// Stored identity as listed by `keys list`
+pub struct StoredKey {
+    pub name: String,
+    pub address: String,
+    pub encrypted: bool,
+}
+
+pub struct KeyStore {
+    dir: PathBuf,
+}
+
+impl KeyStore {
+    /// Key store under `$TRAP_GRID_HOME` or the platform config directory
+    pub fn open() -> Result<Self> {
+        let home = match env::var_os("TRAP_GRID_HOME") {
+            Some(home) => PathBuf::from(home),
+            None => dirs::config_dir()
+                .context("no config directory; set TRAP_GRID_HOME")?
+                .join("trap-grid"),
+        };
+        Ok(Self::at(&home.join("keys")))
+    }
+
+    pub fn at(dir: &Path) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+        }
+    }
+
+    fn path(&self, name: &str) -> Result<PathBuf> {
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            bail!("key name {name:?} may only contain letters, digits, '-' and '_'");
+        }
+        Ok(self.dir.join(format!("{name}.json")))
+    }
+
+    fn read(&self, name: &str) -> Result<KeyFile> {
+        let path = self.path(name)?;
+        let json = fs::read_to_string(&path).with_context(|| {
+            format!("no key named {name}; create it with `trap-grid keys generate {name}`")
+        })?;
+        serde_json::from_str(&json).with_context(|| format!("invalid key file {}", path.display()))
+    }
+
+    /// Store a keypair, encrypting it when a passphrase is given
+    pub fn save(
+        &self,
+        name: &str,
+        keypair: &Keypair,
+        passphrase: Option<&str>,
+        overwrite: bool,
+    ) -> Result<PathBuf> {
+        let path = self.path(name)?;
+        if path.exists() && !overwrite {
+            bail!("key {name} already exists; pass --force to overwrite it");
+        }
+
+        let secret = keypair.secret();
+        let file = KeyFile {
+            address: keypair.address(),
+            secret: passphrase.is_none().then(|| secret.to_string()),
+            encrypted: passphrase
+                .map(|passphrase| EncryptedSecret::seal(&secret, passphrase))
+                .transpose()?,
+        };
+        let json = Zeroizing::new(serde_json::to_string_pretty(&file)?);
+        drop(file);
+
+        let mut dir = fs::DirBuilder::new();
+        dir.recursive(true);
+        let mut options = OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+            dir.mode(0o700);
+            options.mode(0o600);
+        }
+        dir.create(&self.dir)
+            .with_context(|| format!("failed to create {}", self.dir.display()))?;
+        options
+            .open(&path)
+            .and_then(|mut f| f.write_all(json.as_bytes()))
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        Ok(path)
+    }
+
+    /// Public address of a stored key, without decrypting it
+    pub fn address(&self, name: &str) -> Result<String> {
+        Ok(self.read(name)?.address)
+    }
+
+    /// Load a key, prompting for its passphrase if it is encrypted
+    pub fn load(&self, name: &str) -> Result<Keypair> {
+        self.load_with(name, || {
+            read_passphrase(PASSPHRASE_ENV, &format!("Passphrase for key {name}: "))
+        })
+    }
+
+    fn load_with(
+        &self,
+        name: &str,
+        passphrase: impl FnOnce() -> Result<Zeroizing<String>>,
+    ) -> Result<Keypair> {
+        let file = self.read(name)?;
+        let keypair = match (&file.secret, &file.encrypted) {
+            (Some(secret), _) => Keypair::from_secret(&Zeroizing::new(secret.clone()))?,
+            (None, Some(encrypted)) => Keypair::from_secret(&encrypted.open(&passphrase()?)?)
+                .with_context(|| format!("failed to unlock key {name}"))?,
+            (None, None) => bail!("key file for {name} holds no secret"),
+        };
+        if keypair.address() != file.address {
+            bail!("key file for {name} is corrupt: secret does not match its address");
+        }
+        Ok(keypair)
+    }
+
+    pub fn list(&self) -> Result<Vec<StoredKey>> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => {
+                return Err(err).with_context(|| format!("failed to read {}", self.dir.display()))
+            }
+        };
+        let mut keys = Vec::new();
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            let file = self.read(name)?;
+            keys.push(StoredKey {
+                name: name.to_string(),
+                address: file.address,
+                encrypted: file.encrypted.is_some(),
+            });
+        }
+        keys.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(keys)
+    }
+}
+
+#[derive(Subcommand)]
+pub enum KeysCommand {
+    /// Generate a new keypair
+    Generate(GenerateArgs),
+
+    /// Import an existing S... secret key
+    Import(ImportArgs),
+
+    /// List stored keys
+    List,
+
+    /// Print the public address of a stored key
+    Address {
+        /// Key name
+        name: String,
+    },
+
+    /// Fund a key's account on testnet or a local network via friendbot
+    Fund(FundArgs),
+}
+
+#[derive(Args)]
+pub struct GenerateArgs {
+    /// Key name
+    pub name: String,
+
+    /// Encrypt the secret key with a passphrase
+    #[arg(long)]
+    pub encrypt: bool,
+
+    /// Overwrite an existing key with the same name
+    #[arg(long)]
+    pub force: bool,
+
+    /// Fund the new account via friendbot
+    #[arg(long)]
+    pub fund: bool,
+
+    /// Network whose friendbot funds the account (with --fund)
+    #[command(flatten)]
+    pub network: NetworkArgs,
+}
+
+#[derive(Args)]
+pub struct ImportArgs {
+    /// Key name
+    pub name: String,
+
+    /// Read the secret key from stdin instead of prompting
+    #[arg(long)]
+    pub stdin: bool,
+
+    /// Encrypt the secret key with a passphrase
+    #[arg(long)]
+    pub encrypt: bool,
+
+    /// Overwrite an existing key with the same name
+    #[arg(long)]
+    pub force: bool,
+}
+
+#[derive(Args)]
+pub struct FundArgs {
+    /// Key name or G... address
+    pub name: String,
+
+    /// Network whose friendbot funds the account
+    #[command(flatten)]
+    pub network: NetworkArgs,
+
+    /// Friendbot URL; defaults to the one advertised by the RPC server
+    #[arg(long)]
+    pub friendbot_url: Option<String>,
+}
+
+/// A new passphrase, typed twice unless it comes from `env_var`
+pub fn new_passphrase(env_var: &str) -> Result<Zeroizing<String>> {
+    let passphrase = read_passphrase(env_var, "New passphrase: ")?;
+    if env::var_os(env_var).is_none()
+        && *read_passphrase(env_var, "Repeat passphrase: ")? != *passphrase
+    {
+        bail!("passphrases do not match");
+    }
+    Ok(passphrase)
+}
+
+/// Ask friendbot to create and fund `address`
+pub fn fund(rpc: &RpcClient, friendbot_url: Option<&str>, address: &str) -> Result<()> {
+    let url = match friendbot_url {
+        Some(url) => url.to_string(),
+        None => rpc
+            .get_network()?
+            .friendbot_url
+            .context("the RPC server advertises no friendbot; pass --friendbot-url")?,
+    };
+    match ureq::get(&url).query("addr", address).call() {
+        Ok(_) => Ok(()),
+        Err(ureq::Error::StatusCode(400)) => {
+            bail!("friendbot refused to fund {address}; the account probably exists already")
+        }
+        Err(err) => Err(err).with_context(|| format!("friendbot request to {url} failed")),
+    }
+}
+
+pub fn run(command: KeysCommand) -> Result<()> {
+    let store = KeyStore::open()?;
+    match command {
+        KeysCommand::Generate(args) => {
+            let keypair = Keypair::generate()?;
+            let passphrase = args
+                .encrypt
+                .then(|| new_passphrase(PASSPHRASE_ENV))
+                .transpose()?;
+            let path = store.save(
+                &args.name,
+                &keypair,
+                passphrase.as_deref().map(|p| p.as_str()),
+                args.force,
+            )?;
+            println!("{}", keypair.address());
+            eprintln!("Key {} written to {}", args.name, path.display());
+            if args.fund {
+                fund(&args.network.resolve(None)?.rpc(), None, &keypair.address())?;
+                eprintln!("Account funded via friendbot");
+            }
+        }
+        KeysCommand::Import(args) => {
+            let secret = secret::read_secret(None, args.stdin, "Secret key (S...): ")?;
+            let keypair = Keypair::from_secret(&secret)?;
+            let passphrase = args
+                .encrypt
+                .then(|| new_passphrase(PASSPHRASE_ENV))
+                .transpose()?;
+            let path = store.save(
+                &args.name,
+                &keypair,
+                passphrase.as_deref().map(|p| p.as_str()),
+                args.force,
+            )?;
+            println!("{}", keypair.address());
+            eprintln!("Key {} written to {}", args.name, path.display());
+        }
+        KeysCommand::List => {
+            for key in store.list()? {
+                let note = if key.encrypted { " (encrypted)" } else { "" };
+                println!("{:<16} {}{note}", key.name, key.address);
+            }
+        }
+        KeysCommand::Address { name } => println!("{}", store.address(&name)?),
+        KeysCommand::Fund(args) => {
+            let address = if args.name.starts_with('G') && args.name.len() == 56 {
+                args.name.clone()
+            } else {
+                store.address(&args.name)?
+            };
+            fund(
+                &args.network.resolve(None)?.rpc(),
+                args.friendbot_url.as_deref(),
+                &address,
+            )?;
+            println!("Funded {address}");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_store_plain_and_encrypted() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = KeyStore::at(dir.path());
+        let alice = Keypair::generate().unwrap();
+        let bob = Keypair::generate().unwrap();
+
+        store.save("alice", &alice, None, false).unwrap();
+        store.save("bob", &bob, Some("hunter2"), false).unwrap();
+        assert!(store.save("alice", &bob, None, false).is_err());
+        assert!(store.save("../evil", &bob, None, false).is_err());
+
+        let no_passphrase = || -> Result<Zeroizing<String>> { panic!("alice is not encrypted") };
+        assert_eq!(
+            store.load_with("alice", no_passphrase).unwrap().address(),
+            alice.address()
+        );
+        let passphrase = |p: &str| {
+            let p = p.to_string();
+            move || Ok(Zeroizing::new(p))
+        };
+        assert_eq!(
+            store
+                .load_with("bob", passphrase("hunter2"))
+                .unwrap()
+                .address(),
+            bob.address()
+        );
+        assert!(store.load_with("bob", passphrase("hunter3")).is_err());
+
+        let json = fs::read_to_string(dir.path().join("bob.json")).unwrap();
+        assert!(!json.contains(&*bob.secret()));
+
+        let names: Vec<(String, bool)> = store
+            .list()
+            .unwrap()
+            .into_iter()
+            .map(|k| (k.name, k.encrypted))
+            .collect();
+        assert_eq!(names, [("alice".into(), false), ("bob".into(), true)]);
+    }
+}
@@ -0,0 +1,124 @@
+//! # Trap Grid CLI
+//!
+//! Command-line helpers for playing ZK Trap Grid on Stellar:
+//! - `invoke` - Build a ready-to-run `stellar contract invoke` command for `make_move`, or sign and submit it
+//! - `keys` - Generate, import, list and fund signing keys
+//! - `networks` - List the network profiles selected with `--network`
+//! - `grid show` - Render a trap layout or a live game as a board
+//! - `grid gen` - Derive a trap layout and salt from a seed phrase
+//! - `grid save` / `grid load` - Keep a layout file encrypted with a passphrase
+//! - `grid export` / `grid import` - Move a layout between machines as a backup code
+//! - `grid design` - Place traps on an interactive board and save the layout
+//! - `encode-inputs` / `decode-inputs` - Convert between circuit public inputs and verifier bytes
+//! - `fuzz-inputs` - Generate edge-case circuit inputs with reference-implementation expectations
+//! - `simulate` - Compare attacker strategies in offline games
+//! - `analyze` - Heatmaps and player statistics from finished games
+//! - `play` - Play a full game against a local network or testnet
+//! - `e2e` - Build, deploy and play a scripted game on a local network
+//! - `watch` - Stream a session's contract events
+//! - `doctor` - Check the toolchain and circuit artifacts against the versions they were built for
+//! - `audit` - Rate how easily a puzzle answer's commitment could be brute-forced
+
+mod analyze;
+mod audit;
+mod backup;
+mod design;
+mod doctor;
+mod e2e;
+mod encoding;
+mod fuzz;
+mod grid;
+mod inputs;
+mod invoke;
+mod keys;
+mod network;
+mod play;
+mod reference;
+mod sealed;
+mod secret;
+mod seed;
+mod simulate;
+mod stellar;
+mod vectors;
+mod watch;
+
+use clap::{Parser, Subcommand};
+
+#[derive(Parser)]
+#[command(name = "trap-grid", version, about = "ZK Trap Grid command-line tools")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Emit a `stellar contract invoke` command for `make_move`, or submit it
+    Invoke(invoke::InvokeArgs),
+
+    /// Manage signing keys
+    #[command(subcommand)]
+    Keys(keys::KeysCommand),
+
+    /// List network profiles: built-in, from config.toml and the environment
+    Networks(network::NetworksArgs),
+
+    /// Trap layout tools
+    #[command(subcommand)]
+    Grid(grid::GridCommand),
+
+    /// Encode public inputs into the bytes the verifier expects
+    EncodeInputs(inputs::EncodeInputsArgs),
+
+    /// Decode verifier public-input bytes into named values
+    DecodeInputs(inputs::DecodeInputsArgs),
+
+    /// Generate circuit inputs and expected outcomes for differential testing
+    FuzzInputs(fuzz::FuzzInputsArgs),
+
+    /// Regenerate the golden test vectors shared with the circuits' tests
+    TestVectors(vectors::TestVectorsArgs),
+
+    /// Compare attacker strategies in offline games
+    Simulate(simulate::SimulateArgs),
+
+    /// Analyze finished games: hit-rate heatmap, defender and attacker statistics
+    Analyze(analyze::AnalyzeArgs),
+
+    /// Play a game end to end, proving and submitting every move
+    Play(play::PlayArgs),
+
+    /// Build, deploy and play a scripted game on a local network
+    E2e(e2e::E2eArgs),
+
+    /// Print a live feed of a session's contract events
+    Watch(watch::WatchArgs),
+
+    /// Check nargo, bb.js, stellar-cli and the circuit artifacts, with fixes for problems
+    Doctor(doctor::DoctorArgs),
+
+    /// Estimate how quickly a puzzle answer's commitment could be brute-forced
+    Audit(audit::AuditArgs),
+}
+
+fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+
+    match cli.command {
+        Command::Invoke(args) => invoke::run(args),
+        Command::Keys(command) => keys::run(command),
+        Command::Networks(args) => network::run(args),
+        Command::Grid(command) => grid::run(command),
+        Command::EncodeInputs(args) => inputs::run_encode(args),
+        Command::DecodeInputs(args) => inputs::run_decode(args),
+        Command::FuzzInputs(args) => fuzz::run(args),
+        Command::TestVectors(args) => vectors::run(args),
+        Command::Simulate(args) => simulate::run(args),
+        Command::Analyze(args) => analyze::run(args),
+        Command::Play(args) => play::run(args),
+        Command::E2e(args) => e2e::run(args),
+        Command::Watch(args) => watch::run(args),
+        Command::Doctor(args) => doctor::run(args),
+        Command::Audit(args) => audit::run(args),
+    }
+}
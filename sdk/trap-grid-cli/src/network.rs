@@ -0,0 +1,86 @@
+//! `--network` selection and the `networks` subcommand
+//!
+//! Commands that talk to a network take a profile name instead of an RPC URL
+//! and contract id; see [`trap_grid_sdk::network`] for where profiles come
+//! from. `--rpc-url` and `--contract-id` still override the profile.
+
+use anyhow::Result;
+use clap::Args;
+use trap_grid_sdk::{Config, Network};
+
+#[derive(Args, Clone, Debug)]
+pub struct NetworkArgs {
+    /// Network profile: localnet, testnet, futurenet, mainnet or one from config.toml
+    #[arg(long, env = "TRAP_GRID_NETWORK")]
+    pub network: Option<String>,
+
+    /// Soroban RPC endpoint, overriding the profile's (or set STELLAR_RPC_URL)
+    #[arg(long)]
+    pub rpc_url: Option<String>,
+}
+
+impl NetworkArgs {
+    /// The selected profile, or the configured default, with `contract_id` applied
+    pub fn resolve(&self, contract_id: Option<&str>) -> Result<Network> {
+        self.resolve_from(None, contract_id)
+    }
+
+    /// Like [`NetworkArgs::resolve`], with `default` when no `--network` is given
+    pub fn resolve_or(&self, default: &str, contract_id: Option<&str>) -> Result<Network> {
+        self.resolve_from(Some(default), contract_id)
+    }
+
+    fn resolve_from(&self, default: Option<&str>, contract_id: Option<&str>) -> Result<Network> {
+        let mut network = Config::load()?.network(self.network.as_deref().or(default))?;
+        if let Some(rpc_url) = &self.rpc_url {
+            network.rpc_url = rpc_url.clone();
+        }
+        if let Some(contract_id) = contract_id {
+            network.contract_id = Some(contract_id.to_string());
+        }
+        Ok(network)
+    }
+}
+
+#[derive(Args)]
+pub struct NetworksArgs {
+    /// Print the resolved profiles as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+pub fn run(args: NetworksArgs) -> Result<()> {
+    let config = Config::load()?;
+    let default = config.network(None).map(|network| network.name).ok();
+    let mut resolved = Vec::new();
+    for name in config.names() {
+        match config.network(Some(&name)) {
+            Ok(network) => resolved.push(network),
+            // An incomplete profile (mainnet without an RPC URL) is listed anyway
+            Err(err) if !args.json => println!("  {name:<10} {err:#}"),
+            Err(_) => {}
+        }
+    }
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&resolved)?);
+        return Ok(());
+    }
+    for network in resolved {
+        let marker = if default.as_deref() == Some(network.name.as_str()) {
+            '*'
+        } else {
+            ' '
+        };
+        println!(
+            "{marker} {:<10} {}  contract {}",
+            network.name,
+            network.rpc_url,
+            network.contract_id.as_deref().unwrap_or("-")
+        );
+    }
+    eprintln!(
+        "Profiles are read from {}",
+        Config::default_path()?.display()
+    );
+    Ok(())
+}
@@ -0,0 +1,284 @@
+//! `play` subcommand
+//!
+//! Hot-seat game client for a local network or testnet: the defender's grid
+//! is committed to locally and proven well formed with the grid-setup
+//! circuit, the attacker picks cells at the prompt with `submit_move`, and
+//! every move is proven with the position-movement circuit and answered
+//! through `make_move` automatically. Transactions are signed with the players' keys
+//! from the key store (`trap-grid keys`) and sent over RPC.
+
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+use clap::Args;
+use trap_grid_encoding::MAX_GRID_SIZE;
+use trap_grid_sdk::commitment::layout_salt;
+use trap_grid_sdk::prover::{self, Prover};
+use trap_grid_sdk::{ContractError, GameClient, Move, ProofCache};
+
+use crate::grid::{self, TrapLayout, NUM_CELLS};
+use crate::keys::KeyStore;
+use crate::network::NetworkArgs;
+use crate::{secret, seed};
+
+#[derive(Args)]
+pub struct PlayArgs {
+    /// Trap-grid contract id to attach to, overriding the profile's (or set TRAP_GRID_CONTRACT)
+    #[arg(long)]
+    pub contract_id: Option<String>,
+
+    /// Deploy fresh contracts with the local deploy script instead of attaching
+    #[arg(long, conflicts_with = "contract_id")]
+    pub deploy: bool,
+
+    /// Deploy script used by --deploy
+    #[arg(long, default_value = "../contracts/deploy-local.sh")]
+    pub deploy_script: PathBuf,
+
+    /// Network profile; defaults to localnet
+    #[command(flatten)]
+    pub network: NetworkArgs,
+
+    /// Defender key name (see `trap-grid keys`)
+    #[arg(long)]
+    pub defender: String,
+
+    /// Attacker key name (see `trap-grid keys`)
+    #[arg(long)]
+    pub attacker: String,
+
+    /// Game session identifier
+    #[arg(long)]
+    pub session: u32,
+
+    /// Points committed by each player
+    #[arg(long, default_value_t = 100)]
+    pub points: i128,
+
+    /// Defender's trap layout; derived from a prompted seed phrase when omitted
+    #[arg(long)]
+    pub layout: Option<PathBuf>,
+
+    /// Number of traps when deriving the layout from a seed phrase
+    #[arg(long, default_value_t = 10)]
+    pub traps: u32,
+
+    /// Attach to an already started session instead of calling start_game
+    #[arg(long)]
+    pub resume: bool,
+
+    /// Position-movement circuit directory
+    #[arg(long, default_value = "../circuits/position-movement")]
+    pub circuit_dir: PathBuf,
+
+    /// Grid-setup circuit directory, for the proof start_game requires
+    #[arg(long, default_value = "../circuits/grid-setup")]
+    pub setup_circuit_dir: PathBuf,
+
+    /// bb.js entrypoint (relative paths are resolved against each circuit directory)
+    #[arg(long, default_value = prover::DEFAULT_BBJS)]
+    pub bbjs: PathBuf,
+
+    /// Run the prover for every move instead of reusing cached proofs
+    #[arg(long)]
+    pub no_proof_cache: bool,
+}
+
+/// Fail unless (`x`, `y`) is on the `grid_size` x `grid_size` grid
+pub fn check_cell(grid_size: u32, x: u32, y: u32) -> Result<()> {
+    if x >= grid_size || y >= grid_size {
+        bail!("cell ({x}, {y}) is outside the {grid_size}x{grid_size} grid");
+    }
+    Ok(())
+}
+
+/// Parse a cell entered as `x,y` or `x y`, on a grid of any supported size
+pub fn parse_cell(input: &str) -> Result<(u32, u32)> {
+    let parts: Vec<&str> = input
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|p| !p.is_empty())
+        .collect();
+    let [x, y] = parts.as_slice() else {
+        bail!("enter a cell as x,y");
+    };
+    let x: u32 = x.parse().context("x must be a number")?;
+    let y: u32 = y.parse().context("y must be a number")?;
+    check_cell(MAX_GRID_SIZE, x, y)?;
+    Ok((x, y))
+}
+
+/// Run the deploy script and return the trap-grid contract id it printed
+fn deploy(script: &Path) -> Result<String> {
+    let dir = script
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    println!("Deploying contracts with {} ...", script.display());
+    let output = Command::new("bash")
+        .arg(script.file_name().context("invalid deploy script path")?)
+        .current_dir(dir)
+        .output()
+        .with_context(|| format!("failed to run {}", script.display()))?;
+    if !output.status.success() {
+        bail!(
+            "deployment failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| {
+            line.strip_prefix("Trap Grid:")
+                .map(|id| id.trim().to_string())
+        })
+        .context("deploy script did not print a Trap Grid contract id")
+}
+
+fn prompt(label: &str) -> Result<Option<String>> {
+    print!("{label}");
+    io::stdout().flush()?;
+    let mut line = String::new();
+    if io::stdin().lock().read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(line.trim().to_string()))
+}
+
+pub fn run(args: PlayArgs) -> Result<()> {
+    let layout = match &args.layout {
+        Some(path) => TrapLayout::load(path)?,
+        None => {
+            let phrase = secret::read_secret(None, false, "Defender seed phrase: ")?;
+            seed::derive_layout(&phrase, args.traps.min(NUM_CELLS as u32) as usize)
+        }
+    };
+    let mut prover = Prover::new(&args.circuit_dir, &args.bbjs)?;
+    if !args.no_proof_cache {
+        prover = prover.with_cache(ProofCache::open()?)?;
+    }
+
+    let deployed = if args.deploy {
+        Some(deploy(&args.deploy_script)?)
+    } else {
+        None
+    };
+    let network = args.network.resolve_or(
+        "localnet",
+        deployed.as_deref().or(args.contract_id.as_deref()),
+    )?;
+    let contract = network.contract()?;
+    let rpc = network.rpc();
+    let game = GameClient::new(&rpc, contract.clone())?;
+    if let Some(warning) = game.check_interface()? {
+        eprintln!("warning: {warning}");
+    }
+    let store = KeyStore::open()?;
+    let defender = store.load(&args.defender)?;
+    let attacker = store.load(&args.attacker)?;
+
+    let grid_size = layout.grid_size();
+    println!("\nDefender's grid (keep this hidden from the attacker):");
+    print!(
+        "{}",
+        grid::render_board(grid_size, Some(&layout), &[], false)
+    );
+
+    // A resumed game's moves open the commitment it was started with
+    if args.resume && layout.salt.is_none() {
+        bail!("--resume needs the layout with the salt the game was started with");
+    }
+    let salt = layout_salt(&layout)?;
+    if !args.resume {
+        println!("\nProving the grid is well formed ...");
+        let setup = Prover::new(&args.setup_circuit_dir, &args.bbjs)?.prove_setup(
+            &layout,
+            &salt,
+            &defender.address(),
+        )?;
+        game.start_game(
+            &defender,
+            &defender,
+            &attacker.address(),
+            args.session,
+            (args.points, args.points),
+            &setup,
+        )?;
+        println!("\nSession {} started on {contract}", args.session);
+    }
+
+    let mut moves: Vec<Move> = game.get_moves(args.session)?;
+    while moves.len() < layout.trap_values.len() {
+        println!("\nAttacker's view:");
+        print!("{}", grid::render_board(grid_size, None, &moves, false));
+
+        let Some(input) = prompt("Attacker move (x,y or q to end the game): ")? else {
+            break;
+        };
+        if input.eq_ignore_ascii_case("q") {
+            game.end_game(&defender, &[&attacker], args.session)?;
+            println!("Game ended early.");
+            break;
+        }
+        let cell = parse_cell(&input).and_then(|(x, y)| {
+            check_cell(grid_size, x, y)?;
+            Ok((x, y))
+        });
+        let (x, y) = match cell {
+            Ok(cell) => cell,
+            Err(err) => {
+                println!("{err}");
+                continue;
+            }
+        };
+        if let Err(err) = game.submit_move(&attacker, args.session, x, y) {
+            match err.downcast_ref::<ContractError>() {
+                Some(code) => {
+                    println!("({x}, {y}) cannot be played: {code}");
+                    continue;
+                }
+                None => return Err(err),
+            }
+        }
+
+        println!("Defender is proving the result for ({x}, {y}) ...");
+        let proven = prover.prove_move(args.session, &layout, &salt, &defender.address(), x, y)?;
+        game.respond_move(&defender, &proven)?;
+        println!(
+            "({x}, {y}) is a {} (proof verified on-chain)",
+            if proven.is_hit { "HIT" } else { "MISS" }
+        );
+
+        moves = game.get_moves(args.session)?;
+    }
+
+    let hits = moves.iter().filter(|m| m.is_hit).count();
+    println!("\nFinal board:");
+    print!(
+        "{}",
+        grid::render_board(grid_size, Some(&layout), &moves, false)
+    );
+    println!(
+        "{} moves, {hits} hits. Check the result with `stellar contract invoke ... -- get_game --session_id {}`.",
+        moves.len(),
+        args.session
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_cell() {
+        assert_eq!(parse_cell("2,3").unwrap(), (2, 3));
+        assert_eq!(parse_cell(" 7 0 ").unwrap(), (7, 0));
+        assert_eq!(parse_cell("15,8").unwrap(), (15, 8));
+        assert!(parse_cell("16,0").is_err());
+        assert!(check_cell(8, 8, 0).is_err());
+        assert!(parse_cell("2").is_err());
+        assert!(parse_cell("a,b").is_err());
+    }
+}
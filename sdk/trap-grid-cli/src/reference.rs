@@ -0,0 +1,466 @@
+//! Rust reference implementation of the Noir circuits
+//!
+//! Replays each circuit's assertions over the SDK's Poseidon hashes, so
+//! inputs can be checked without running nargo. The checks follow the
+//! circuits as written, including the assertions they leave out.
+
+use anyhow::{bail, ensure, Result};
+use trap_grid_encoding::domain;
+use trap_grid_encoding::{
+    merkle_depth, PositionMovement, TrapMerkleRoot, MAX_GRID_SIZE, MIN_GRID_SIZE,
+};
+pub use trap_grid_sdk::commitment::{
+    defender_field, grid_commitment, hash_to_field, merkle_root, trap_commitment, trap_leaf,
+    TrapTree,
+};
+
+use crate::encoding::{hex_field, key_fields, FieldBytes, MAX_MERKLE_TREE_DEPTH};
+
+/// Trap values the grid-setup circuit takes, a cell of the largest grid each
+pub const MAX_CELLS: usize = (MAX_GRID_SIZE * MAX_GRID_SIZE) as usize;
+
+/// Inputs of the position-movement circuit
+#[derive(Clone, Debug)]
+pub struct PositionMovementInputs {
+    pub move_x: u32,
+    pub move_y: u32,
+    pub is_hit: u32,
+    pub grid_size: u32,
+    pub grid_commitment: FieldBytes,
+    pub trap_value: u32,
+    pub salt: FieldBytes,
+    /// Account key or contract id of the defender
+    pub defender: [u8; 32],
+    pub indices: [u8; MAX_MERKLE_TREE_DEPTH],
+    pub siblings: [FieldBytes; MAX_MERKLE_TREE_DEPTH],
+}
+
+impl PositionMovementInputs {
+    /// An honest answer opening `index` of `layout`, committed by `defender`
+    /// with `salt`, claimed at (`move_x`, `move_y`)
+    pub fn opening(
+        layout: &[u8],
+        salt: FieldBytes,
+        defender: [u8; 32],
+        index: usize,
+        move_x: u32,
+        move_y: u32,
+    ) -> Result<Self> {
+        let tree = TrapTree::new(layout)?;
+        let (indices, siblings) = tree.proof(index);
+        let trap_value = layout[index].into();
+        Ok(Self {
+            move_x,
+            move_y,
+            is_hit: trap_value,
+            grid_size: tree.grid_size(),
+            grid_commitment: grid_commitment(&tree.root(), &salt, &key_hash(&defender)?),
+            trap_value,
+            salt,
+            defender,
+            indices,
+            siblings,
+        })
+    }
+
+    /// The public inputs the verifier checks
+    pub fn public_inputs(&self) -> PositionMovement {
+        PositionMovement {
+            move_x: self.move_x,
+            move_y: self.move_y,
+            is_hit: self.is_hit == 1,
+            grid_size: self.grid_size,
+            grid_commitment: self.grid_commitment,
+        }
+    }
+
+    /// The circuit's Prover.toml
+    pub fn prover_toml(&self) -> String {
+        let indices: Vec<String> = self
+            .indices
+            .iter()
+            .map(|bit| format!("\"{bit}\""))
+            .collect();
+        let siblings: Vec<String> = self
+            .siblings
+            .iter()
+            .map(|sibling| format!("    \"{}\"", hex_field(sibling)))
+            .collect();
+        let [head, last] = key_fields(&self.defender);
+        format!(
+            "[public_inputs]\nmove_x = \"{}\"\nmove_y = \"{}\"\nis_hit = \"{}\"\n\
+             grid_size = \"{}\"\ngrid_commitment = \"{}\"\n\n\
+             [private_inputs]\ntrap_value = \"{}\"\nsalt = \"{}\"\n\
+             defender = [\"{}\", \"{}\"]\n\
+             trap_merkle_proof_indices = [{}]\n\
+             trap_merkle_proof_siblings = [\n{}\n]\n",
+            self.move_x,
+            self.move_y,
+            self.is_hit,
+            self.grid_size,
+            hex_field(&self.grid_commitment),
+            self.trap_value,
+            hex_field(&self.salt),
+            hex_field(&head),
+            hex_field(&last),
+            indices.join(", "),
+            siblings.join(",\n")
+        )
+    }
+}
+
+/// Inputs of the trap-commitment circuit
+#[derive(Clone, Debug)]
+pub struct TrapCommitmentInputs {
+    pub trap_commitment: FieldBytes,
+    pub defender: FieldBytes,
+    pub trap_value: u32,
+    pub secret: FieldBytes,
+}
+
+/// Inputs of the trap-merkle-root circuit
+#[derive(Clone, Debug)]
+pub struct TrapMerkleRootInputs {
+    pub trap_merkle_root: FieldBytes,
+    pub move_x: u32,
+    pub move_y: u32,
+    pub proof_length: u32,
+    pub indices: [u8; MAX_MERKLE_TREE_DEPTH],
+    pub siblings: [FieldBytes; MAX_MERKLE_TREE_DEPTH],
+    pub grid_size: u32,
+    pub trap_value: u32,
+}
+
+impl TrapMerkleRootInputs {
+    /// An honest Merkle opening of `index` in `tree`, claimed at (`move_x`, `move_y`)
+    pub fn opening(tree: &TrapTree, layout: &[u8], index: usize, move_x: u32, move_y: u32) -> Self {
+        let (indices, siblings) = tree.proof(index);
+        Self {
+            trap_merkle_root: tree.root(),
+            move_x,
+            move_y,
+            proof_length: tree.depth() as u32,
+            indices,
+            siblings,
+            grid_size: tree.grid_size(),
+            trap_value: layout[index].into(),
+        }
+    }
+
+    /// The public inputs the verifier checks
+    pub fn public_inputs(&self) -> TrapMerkleRoot {
+        TrapMerkleRoot {
+            trap_merkle_root: self.trap_merkle_root,
+            move_x: self.move_x,
+            move_y: self.move_y,
+            proof_length: self.proof_length,
+            indices: self.indices,
+            siblings: self.siblings,
+            grid_size: self.grid_size,
+        }
+    }
+
+    /// The circuit's Prover.toml
+    pub fn prover_toml(&self) -> String {
+        let indices: Vec<String> = self
+            .indices
+            .iter()
+            .map(|bit| format!("\"{bit}\""))
+            .collect();
+        let siblings: Vec<String> = self
+            .siblings
+            .iter()
+            .map(|sibling| format!("    \"{}\"", hex_field(sibling)))
+            .collect();
+        format!(
+            "[public_inputs]\ntrap_merkle_root = \"{}\"\nmove_x = \"{}\"\nmove_y = \"{}\"\n\
+             trap_merkle_proof_length = \"{}\"\n\
+             trap_merkle_proof_indices = [{}]\n\
+             trap_merkle_proof_siblings = [\n{}\n]\n\
+             grid_size = \"{}\"\n\n\
+             [private_inputs]\ntrap_value = \"{}\"\n",
+            hex_field(&self.trap_merkle_root),
+            self.move_x,
+            self.move_y,
+            self.proof_length,
+            indices.join(", "),
+            siblings.join(",\n"),
+            self.grid_size,
+            self.trap_value
+        )
+    }
+}
+
+/// Inputs of the grid-setup circuit
+#[derive(Clone, Debug)]
+pub struct GridSetupInputs {
+    pub grid_commitment: FieldBytes,
+    pub trap_count: u32,
+    pub grid_size: u32,
+    /// Account key or contract id of the defender
+    pub defender: [u8; 32],
+    /// [`MAX_CELLS`] values, indexed by `x * grid_size + y`, then 0 past the grid
+    pub trap_values: Vec<u32>,
+    pub salt: FieldBytes,
+}
+
+impl GridSetupInputs {
+    /// An honest setup of `layout` by `defender`: the commitment its values,
+    /// `salt` and the defender's key hash to
+    pub fn honest(layout: &[u8], salt: FieldBytes, defender: [u8; 32]) -> Result<Self> {
+        let tree = TrapTree::new(layout)?;
+        let mut trap_values: Vec<u32> = layout.iter().map(|value| (*value).into()).collect();
+        trap_values.resize(MAX_CELLS, 0);
+        Ok(Self {
+            grid_commitment: grid_commitment(&tree.root(), &salt, &key_hash(&defender)?),
+            trap_count: layout.iter().map(|value| u32::from(*value)).sum(),
+            grid_size: tree.grid_size(),
+            defender,
+            trap_values,
+            salt,
+        })
+    }
+
+    /// The circuit's Prover.toml
+    pub fn prover_toml(&self) -> String {
+        let trap_values: Vec<String> = self
+            .trap_values
+            .iter()
+            .map(|value| format!("\"{value}\""))
+            .collect();
+        let [head, last] = key_fields(&self.defender);
+        format!(
+            "[public_inputs]\ngrid_commitment = \"{}\"\ntrap_count = \"{}\"\n\
+             grid_size = \"{}\"\ndefender = [\"{}\", \"{}\"]\n\n\
+             [private_inputs]\ntrap_values = [{}]\nsalt = \"{}\"\n",
+            hex_field(&self.grid_commitment),
+            self.trap_count,
+            self.grid_size,
+            hex_field(&head),
+            hex_field(&last),
+            trap_values.join(", "),
+            hex_field(&self.salt)
+        )
+    }
+}
+
+/// The `defender_field` the grid-setup circuit hashes a defender's key to
+fn key_hash(defender: &[u8; 32]) -> Result<FieldBytes> {
+    hash_to_field(&domain::DEFENDER, defender)
+}
+
+/// The circuits' grid size assertion
+fn check_grid_size(grid_size: u32) -> Result<()> {
+    ensure!(
+        (MIN_GRID_SIZE..=MAX_GRID_SIZE).contains(&grid_size),
+        "Grid size out of range"
+    );
+    Ok(())
+}
+
+/// The leaf index Merkle path `indices` lead to, leaf level first
+fn leaf_index(indices: &[u8; MAX_MERKLE_TREE_DEPTH]) -> u32 {
+    indices
+        .iter()
+        .enumerate()
+        .map(|(i, bit)| u32::from(*bit) << i)
+        .sum()
+}
+
+/// Replay the position-movement assertions; the error is the failing assertion
+pub fn check_position_movement(inputs: &PositionMovementInputs) -> Result<()> {
+    if inputs.indices.iter().any(|bit| *bit > 1) {
+        bail!("Merkle proof indices must be 0 or 1");
+    }
+    check_grid_size(inputs.grid_size)?;
+    ensure!(inputs.move_x < inputs.grid_size, "move_x < grid_size");
+    ensure!(inputs.move_y < inputs.grid_size, "move_y < grid_size");
+    ensure!(inputs.trap_value <= 1, "Trap value must be 0 or 1");
+    ensure!(
+        inputs.trap_value == inputs.is_hit,
+        "Claimed hit/miss does not match trap value"
+    );
+    ensure!(
+        leaf_index(&inputs.indices) == inputs.move_x * inputs.grid_size + inputs.move_y,
+        "Merkle proof is not for the move's cell"
+    );
+    let root = merkle_root(
+        &trap_leaf(inputs.trap_value),
+        merkle_depth(inputs.grid_size) as u32,
+        &inputs.indices,
+        &inputs.siblings,
+    )?;
+    ensure!(
+        grid_commitment(&root, &inputs.salt, &key_hash(&inputs.defender)?)
+            == inputs.grid_commitment,
+        "Trap value does not open the grid commitment"
+    );
+    Ok(())
+}
+
+/// Replay the trap-commitment assertion (it does not constrain `trap_value` to 0 or 1)
+pub fn check_trap_commitment(inputs: &TrapCommitmentInputs) -> Result<()> {
+    ensure!(
+        trap_commitment(inputs.trap_value, &inputs.secret, &inputs.defender)
+            == inputs.trap_commitment,
+        "Invalid commitment: computed commitment does not match public commitment"
+    );
+    Ok(())
+}
+
+/// Replay the trap-merkle-root assertions
+///
+/// The circuit's boolean checks are commented out, so only the grid, the
+/// proof length, the leaf index and the root are constrained.
+pub fn check_trap_merkle_root(inputs: &TrapMerkleRootInputs) -> Result<()> {
+    if inputs.indices.iter().any(|bit| *bit > 1) {
+        bail!("Merkle proof indices must be 0 or 1");
+    }
+    check_grid_size(inputs.grid_size)?;
+    ensure!(inputs.move_x < inputs.grid_size, "move_x < grid_size");
+    ensure!(inputs.move_y < inputs.grid_size, "move_y < grid_size");
+    ensure!(
+        inputs.proof_length as usize == merkle_depth(inputs.grid_size),
+        "Merkle proof length does not match the grid size"
+    );
+    let Some(index) = inputs
+        .move_x
+        .checked_mul(inputs.grid_size)
+        .and_then(|row| row.checked_add(inputs.move_y))
+    else {
+        bail!("attempt to compute the move index overflowed");
+    };
+    ensure!(
+        leaf_index(&inputs.indices) == index,
+        "reconstructed_moved_position_index == moved_position_index"
+    );
+    let root = merkle_root(
+        &trap_leaf(inputs.trap_value),
+        inputs.proof_length,
+        &inputs.indices,
+        &inputs.siblings,
+    )?;
+    ensure!(
+        root == inputs.trap_merkle_root,
+        "Invalid Merkle proof: a computed root does not match a given public trap root"
+    );
+    Ok(())
+}
+
+/// Replay the grid-setup assertions
+pub fn check_grid_setup(inputs: &GridSetupInputs) -> Result<()> {
+    check_grid_size(inputs.grid_size)?;
+    ensure!(
+        inputs.trap_values.len() == MAX_CELLS,
+        "expected {MAX_CELLS} trap values, found {}",
+        inputs.trap_values.len()
+    );
+    let cells = (inputs.grid_size * inputs.grid_size) as usize;
+    for (i, value) in inputs.trap_values.iter().enumerate() {
+        ensure!(*value <= 1, "Trap value must be 0 or 1");
+        ensure!(i < cells || *value == 0, "Trap outside the grid");
+    }
+    let traps: u32 = inputs.trap_values.iter().sum();
+    ensure!(
+        traps == inputs.trap_count,
+        "Invalid grid: the number of traps does not match trap_count"
+    );
+    ensure!(inputs.salt != [0u8; 32], "Salt must not be zero");
+    let layout: Vec<u8> = inputs.trap_values[..cells]
+        .iter()
+        .map(|value| *value as u8)
+        .collect();
+    let root = TrapTree::new(&layout)?.root();
+    ensure!(
+        grid_commitment(&root, &inputs.salt, &key_hash(&inputs.defender)?)
+            == inputs.grid_commitment,
+        "Invalid grid commitment: computed commitment does not match public commitment"
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::encoding::parse_field;
+
+    #[test]
+    fn test_position_movement_opens_commitment() {
+        // The grid of circuits/grid-setup/Prover.toml, opened at (7, 0) as in
+        // circuits/position-movement/Prover.toml
+        let mut layout = vec![0u8; 64];
+        for cell in [0, 9, 18, 27, 36, 45, 54, 63, 7, 56] {
+            layout[cell] = 1;
+        }
+        let salt = parse_field("12345").unwrap();
+        let inputs = PositionMovementInputs::opening(&layout, salt, [0x11; 32], 56, 7, 0).unwrap();
+        assert_eq!(
+            hex_field(&inputs.grid_commitment),
+            "0x2ee5032b9c54a99742b33bb21516e00e4872bd72a4b0ee205189c5a365e65f85"
+        );
+        check_position_movement(&inputs).unwrap();
+
+        // A defender lying about the cell cannot open the commitment
+        let lie = PositionMovementInputs {
+            is_hit: 0,
+            trap_value: 0,
+            ..inputs.clone()
+        };
+        let err = check_position_movement(&lie).unwrap_err().to_string();
+        assert_eq!(err, "Trap value does not open the grid commitment");
+        // Nor answer from its grid committed for another defender
+        let other = PositionMovementInputs {
+            defender: [0x22; 32],
+            ..inputs.clone()
+        };
+        assert!(check_position_movement(&other).is_err());
+        // Nor open another cell
+        let moved = PositionMovementInputs {
+            move_y: 1,
+            ..inputs
+        };
+        let err = check_position_movement(&moved).unwrap_err().to_string();
+        assert_eq!(err, "Merkle proof is not for the move's cell");
+    }
+
+    #[test]
+    fn test_matches_circuit_sample() {
+        // circuits/trap-merkle-root/Prover.toml
+        let siblings = [
+            "0x1e607445894dca5bf8e858c6d56f955fe63b98ba5d92068da997d8fcbe998350",
+            "0x1182da31623a8ef9b08b5b30e7ecff720a9d0da6caf8dc7a979d0a2651acb34e",
+            "0x1aa5a4dccd96c991a39f45b9ad6c512f78e5b52b653b912abbcf51c0f6cec9c7",
+            "0x02ba05bbe7d2fa105efeb530742aaeb38f6ad3efbf8727baaa76354f061def8b",
+            "0x0f4dba746185f1137d3446c61a7184f16577e23cd4bbe823e95349ae9fc8669a",
+            "0x1761a0a763a442ad45d679fb751d638933ff0c007afd62122541a182c24f81ee",
+            "0x0",
+            "0x0",
+        ]
+        .map(|s| parse_field(s).unwrap());
+        let inputs = TrapMerkleRootInputs {
+            trap_merkle_root: parse_field(
+                "0x0c7c517f1ddb7e98c83cda5a4061540940af216957d2993a2cf02dd26eca13fb",
+            )
+            .unwrap(),
+            move_x: 2,
+            move_y: 3,
+            proof_length: 6,
+            indices: [1, 1, 0, 0, 1, 0, 0, 0],
+            siblings,
+            grid_size: 8,
+            trap_value: 0,
+        };
+        check_trap_merkle_root(&inputs).unwrap();
+        assert!(check_trap_merkle_root(&TrapMerkleRootInputs {
+            trap_value: 1,
+            ..inputs.clone()
+        })
+        .is_err());
+        // The proof must be as long as the grid's tree is deep
+        assert!(check_trap_merkle_root(&TrapMerkleRootInputs {
+            grid_size: 16,
+            ..inputs
+        })
+        .is_err());
+    }
+}
@@ -0,0 +1,192 @@
+//! `grid save` / `grid load` subcommands
+//!
+//! A defender who loses their layout mid-game can never prove another move,
+//! so layouts are worth keeping, but a plain layout file gives the game away
+//! to anyone who reads it. `grid save --encrypt` seals the layout and its salt
+//! the way `keys` seals secret keys (Argon2id and XChaCha20-Poly1305). The
+//! layout's Merkle root is kept in the clear: `grid load` checks that the
+//! decrypted layout still hashes to it.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use clap::Args;
+use serde::{Deserialize, Serialize};
+
+use crate::encoding::hex_field;
+use crate::grid::{self, TrapLayout};
+use crate::keys::{self, EncryptedSecret};
+use crate::reference::TrapTree;
+
+/// Passphrase for encrypted layouts, read instead of prompting when set
+const PASSPHRASE_ENV: &str = "TRAP_GRID_LAYOUT_PASSPHRASE";
+
+#[derive(Args)]
+pub struct SaveArgs {
+    /// Plain layout file to save
+    #[arg(long)]
+    pub layout: PathBuf,
+
+    /// File to write
+    #[arg(long)]
+    pub out: PathBuf,
+
+    /// Encrypt the layout and salt with a passphrase
+    #[arg(long)]
+    pub encrypt: bool,
+}
+
+#[derive(Args)]
+pub struct LoadArgs {
+    /// Layout file written by `grid save`
+    #[arg(long)]
+    pub file: PathBuf,
+
+    /// Write the decrypted layout here, for commands that take --layout
+    #[arg(long)]
+    pub out: Option<PathBuf>,
+}
+
+/// Layout file sealed under a passphrase, with its root in the clear
+#[derive(Serialize, Deserialize)]
+pub struct EncryptedLayout {
+    #[serde(rename = "trapMerkleRoot")]
+    pub root: String,
+    pub encrypted: EncryptedSecret,
+}
+
+fn root(layout: &TrapLayout) -> Result<String> {
+    Ok(hex_field(&TrapTree::new(&layout.trap_values)?.root()))
+}
+
+impl EncryptedLayout {
+    pub fn seal(layout: &TrapLayout, passphrase: &str) -> Result<Self> {
+        Ok(Self {
+            root: root(layout)?,
+            encrypted: EncryptedSecret::seal(&serde_json::to_string(layout)?, passphrase)?,
+        })
+    }
+
+    pub fn open(&self, passphrase: &str) -> Result<TrapLayout> {
+        let json = self.encrypted.open(passphrase)?;
+        let layout: TrapLayout =
+            serde_json::from_str(&json).context("encrypted data is not a layout")?;
+        layout.validate()?;
+        if root(&layout)? != self.root {
+            bail!(
+                "decrypted layout does not hash to the saved root {}",
+                self.root
+            );
+        }
+        Ok(layout)
+    }
+}
+
+/// A layout file written by `grid save`, encrypted or plain
+enum Saved {
+    Plain(TrapLayout),
+    Encrypted(EncryptedLayout),
+}
+
+fn read(path: &Path) -> Result<Saved> {
+    let json =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    if let Ok(sealed) = serde_json::from_str::<EncryptedLayout>(&json) {
+        return Ok(Saved::Encrypted(sealed));
+    }
+    TrapLayout::load(path).map(Saved::Plain)
+}
+
+pub fn save(args: SaveArgs) -> Result<()> {
+    let layout = TrapLayout::load(&args.layout)?;
+    if !args.encrypt {
+        layout.save(&args.out)?;
+        println!("Layout written to {}", args.out.display());
+        return Ok(());
+    }
+
+    let passphrase = keys::new_passphrase(PASSPHRASE_ENV)?;
+    let sealed = EncryptedLayout::seal(&layout, &passphrase)?;
+    fs::write(&args.out, serde_json::to_string_pretty(&sealed)? + "\n")
+        .with_context(|| format!("failed to write {}", args.out.display()))?;
+    println!(
+        "Encrypted layout written to {} (root {})",
+        args.out.display(),
+        sealed.root
+    );
+    Ok(())
+}
+
+/// Load a plain or encrypted layout file, prompting for its passphrase
+pub fn read_layout(path: &Path) -> Result<TrapLayout> {
+    match read(path)? {
+        Saved::Plain(layout) => Ok(layout),
+        Saved::Encrypted(sealed) => {
+            let prompt = format!("Passphrase for {}: ", path.display());
+            sealed
+                .open(&keys::read_passphrase(PASSPHRASE_ENV, &prompt)?)
+                .with_context(|| format!("failed to load {}", path.display()))
+        }
+    }
+}
+
+pub fn load(args: LoadArgs) -> Result<()> {
+    let layout = read_layout(&args.file)?;
+
+    print!(
+        "{}",
+        grid::render_board(layout.grid_size(), Some(&layout), &[], false)
+    );
+    println!("Merkle root: {}", root(&layout)?);
+    if let Some(out) = &args.out {
+        layout.save(out)?;
+        println!("Layout written to {}", out.display());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::grid::NUM_CELLS;
+
+    fn layout() -> TrapLayout {
+        let mut trap_values = vec![0; NUM_CELLS];
+        trap_values[19] = 1;
+        TrapLayout {
+            trap_values,
+            salt: Some("0x2a".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_seal_and_open() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("grid.json");
+        let sealed = EncryptedLayout::seal(&layout(), "hunter2").unwrap();
+        fs::write(&path, serde_json::to_string(&sealed).unwrap()).unwrap();
+        assert!(!fs::read_to_string(&path).unwrap().contains("trapValues"));
+
+        let Saved::Encrypted(sealed) = read(&path).unwrap() else {
+            panic!("read as a plain layout");
+        };
+        assert_eq!(sealed.open("hunter2").unwrap(), layout());
+        assert!(sealed.open("hunter3").is_err());
+
+        layout().save(&path).unwrap();
+        assert!(matches!(read(&path).unwrap(), Saved::Plain(_)));
+    }
+
+    #[test]
+    fn test_open_checks_root() {
+        let mut sealed = EncryptedLayout::seal(&layout(), "hunter2").unwrap();
+        sealed.root = root(&TrapLayout {
+            trap_values: vec![0; NUM_CELLS],
+            salt: None,
+        })
+        .unwrap();
+        let err = sealed.open("hunter2").unwrap_err();
+        assert!(err.to_string().contains("does not hash to the saved root"));
+    }
+}
@@ -0,0 +1,41 @@
+//! Reading secrets (seed phrases) without echoing them
+//!
+//! Secrets are returned in `Zeroizing` buffers so they are wiped from memory
+//! once dropped.
+
+use std::io::{self, BufRead, IsTerminal};
+
+use anyhow::{bail, Context, Result};
+use zeroize::Zeroizing;
+
+/// Read a secret from `--<flag>`, from the first line of stdin, or from a hidden prompt
+pub fn read_secret(
+    value: Option<String>,
+    from_stdin: bool,
+    prompt: &str,
+) -> Result<Zeroizing<String>> {
+    let secret = match value {
+        Some(value) => Zeroizing::new(value),
+        None if from_stdin => {
+            let mut line = Zeroizing::new(String::new());
+            io::stdin()
+                .lock()
+                .read_line(&mut line)
+                .context("failed to read secret from stdin")?;
+            let trimmed = line.trim_end_matches(['\r', '\n']).len();
+            line.truncate(trimmed);
+            line
+        }
+        None => {
+            if !io::stdin().is_terminal() {
+                bail!("no terminal to prompt for the secret; pass --stdin to pipe it in");
+            }
+            Zeroizing::new(rpassword::prompt_password(prompt).context("failed to read secret")?)
+        }
+    };
+
+    if secret.is_empty() {
+        bail!("secret must not be empty");
+    }
+    Ok(secret)
+}
@@ -0,0 +1,146 @@
+//! Deterministic layout derivation from a seed phrase
+//!
+//! The phrase is hashed into a 64-byte BLAKE2b key, which then keys a
+//! counter-mode BLAKE2b-512 expansion (personalized per purpose). The same
+//! phrase always yields the same trap layout and salt. Key material is
+//! zeroized once the stream is dropped.
+
+use blake2::digest::{FixedOutput, Update};
+use blake2::{Blake2b512, Blake2bMac512, Digest};
+use zeroize::Zeroize;
+
+use crate::grid::{TrapLayout, NUM_CELLS};
+
+const LAYOUT_PERSONAL: &[u8] = b"trapgrid-layout";
+const SALT_PERSONAL: &[u8] = b"trapgrid-salt";
+
+/// Keyed BLAKE2b-512 output stream
+struct SeedStream {
+    key: [u8; 64],
+    personal: &'static [u8],
+    counter: u64,
+    block: [u8; 64],
+    pos: usize,
+}
+
+impl SeedStream {
+    fn new(phrase: &str, personal: &'static [u8]) -> Self {
+        let mut digest = Blake2b512::digest(phrase.as_bytes());
+        let mut key = [0u8; 64];
+        key.copy_from_slice(&digest);
+        digest.as_mut_slice().zeroize();
+        Self {
+            key,
+            personal,
+            counter: 0,
+            block: [0u8; 64],
+            pos: 64,
+        }
+    }
+
+    fn refill(&mut self) {
+        let mut mac = Blake2bMac512::new_with_salt_and_personal(&self.key, &[], self.personal)
+            .expect("key and personalization lengths are within BLAKE2b limits");
+        Update::update(&mut mac, &self.counter.to_le_bytes());
+        self.block.copy_from_slice(&mac.finalize_fixed());
+        self.counter += 1;
+        self.pos = 0;
+    }
+
+    fn fill(&mut self, out: &mut [u8]) {
+        for byte in out.iter_mut() {
+            if self.pos == self.block.len() {
+                self.refill();
+            }
+            *byte = self.block[self.pos];
+            self.pos += 1;
+        }
+    }
+
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0u8; 4];
+        self.fill(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    /// Uniform value in `0..bound` (rejection sampling, no modulo bias)
+    fn below(&mut self, bound: u32) -> u32 {
+        let zone = u32::MAX - (u32::MAX % bound);
+        loop {
+            let value = self.next_u32();
+            if value < zone {
+                return value % bound;
+            }
+        }
+    }
+}
+
+impl Drop for SeedStream {
+    fn drop(&mut self) {
+        self.key.zeroize();
+        self.block.zeroize();
+    }
+}
+
+/// Derive a trap layout with exactly `trap_count` traps from a seed phrase
+pub fn derive_layout(phrase: &str, trap_count: usize) -> TrapLayout {
+    let mut stream = SeedStream::new(phrase, LAYOUT_PERSONAL);
+
+    // Partial Fisher-Yates shuffle: the first `trap_count` cells are the traps
+    let mut cells: Vec<usize> = (0..NUM_CELLS).collect();
+    for i in 0..trap_count {
+        let j = i + stream.below((NUM_CELLS - i) as u32) as usize;
+        cells.swap(i, j);
+    }
+
+    let mut trap_values = vec![0u8; NUM_CELLS];
+    for cell in &cells[..trap_count] {
+        trap_values[*cell] = 1;
+    }
+    cells.zeroize();
+    TrapLayout {
+        trap_values,
+        salt: Some(derive_salt(phrase)),
+    }
+}
+
+/// Derive a salt as a 0x-prefixed 32-byte hex field element
+///
+/// The top byte is cleared so the value is always below the BN254 modulus.
+pub fn derive_salt(phrase: &str) -> String {
+    let mut stream = SeedStream::new(phrase, SALT_PERSONAL);
+    let mut salt = [0u8; 32];
+    stream.fill(&mut salt[1..]);
+    format!("0x{}", hex::encode(salt))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_derive_layout_is_deterministic() {
+        let a = derive_layout("correct horse battery staple", 10);
+        let b = derive_layout("correct horse battery staple", 10);
+        let c = derive_layout("correct horse battery stapler", 10);
+
+        assert_eq!(a, b);
+        assert_ne!(a.trap_values, c.trap_values);
+        assert_ne!(a.salt, c.salt);
+        assert_eq!(a.trap_count(), 10);
+        assert!(a.validate().is_ok());
+    }
+
+    #[test]
+    fn test_derive_layout_trap_counts() {
+        assert_eq!(derive_layout("seed", 0).trap_count(), 0);
+        assert_eq!(derive_layout("seed", NUM_CELLS).trap_count(), NUM_CELLS);
+    }
+
+    #[test]
+    fn test_derive_salt_format() {
+        let salt = derive_salt("seed");
+        assert_eq!(salt.len(), 66);
+        assert!(salt.starts_with("0x00"));
+    }
+}
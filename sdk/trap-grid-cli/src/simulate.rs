@@ -0,0 +1,122 @@
+//! `simulate` subcommand
+//!
+//! Runs the SDK's offline simulator for one or all attacker strategies and
+//! prints a comparison table, or the full statistics as JSON.
+
+use std::path::PathBuf;
+
+use anyhow::{bail, Result};
+use clap::Args;
+use trap_grid_sdk::simulator::{self, Ending, Layouts, SimStats};
+use trap_grid_sdk::strategy::{Rng, StrategyKind};
+use trap_grid_sdk::{TrapLayout, MAX_MOVES};
+
+#[derive(Args)]
+pub struct SimulateArgs {
+    /// Strategy to simulate: random, hunt-target or density (all when omitted)
+    #[arg(long)]
+    pub strategy: Option<StrategyKind>,
+
+    /// Games per strategy
+    #[arg(long, default_value_t = 1000)]
+    pub games: u32,
+
+    /// Traps in each random layout
+    #[arg(long, default_value_t = 10, conflicts_with = "layout")]
+    pub traps: u32,
+
+    /// Play every game against this layout instead of random ones
+    #[arg(long)]
+    pub layout: Option<PathBuf>,
+
+    /// End each game after this many moves
+    #[arg(long, default_value_t = MAX_MOVES)]
+    pub moves: u32,
+
+    /// The attacker ends the game as soon as it is winning
+    #[arg(long)]
+    pub end_when_ahead: bool,
+
+    /// RNG seed, to reproduce a run (random when omitted)
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Print the full statistics as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+fn table(stats: &[SimStats]) -> String {
+    let mut out = format!(
+        "{:<12} {:>8} {:>9} {:>10} {:>11} {:>13}\n",
+        "strategy", "games", "hit rate", "mean hits", "mean moves", "attacker wins"
+    );
+    for s in stats {
+        out.push_str(&format!(
+            "{:<12} {:>8} {:>8.1}% {:>10.2} {:>11.2} {:>12.1}%\n",
+            s.strategy,
+            s.games,
+            s.hit_rate * 100.0,
+            s.mean_hits,
+            s.mean_length,
+            s.attacker_win_rate * 100.0
+        ));
+    }
+    out
+}
+
+pub fn run(args: SimulateArgs) -> Result<()> {
+    if args.moves == 0 || args.moves > MAX_MOVES {
+        bail!("--moves must be between 1 and {MAX_MOVES}");
+    }
+    let layouts = match &args.layout {
+        Some(path) => Layouts::Fixed(TrapLayout::load(path)?),
+        None => Layouts::Random { traps: args.traps },
+    };
+    let ending = Ending {
+        max_moves: args.moves,
+        end_when_ahead: args.end_when_ahead,
+    };
+    let seed = match args.seed {
+        Some(seed) => seed,
+        None => Rng::from_entropy()?.next_u64(),
+    };
+    let kinds = match args.strategy {
+        Some(kind) => vec![kind],
+        None => StrategyKind::ALL.to_vec(),
+    };
+    let stats: Vec<SimStats> = kinds
+        .into_iter()
+        .map(|kind| simulator::simulate(kind, &layouts, ending, args.games, seed))
+        .collect();
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+    } else {
+        print!("{}", table(&stats));
+        println!("seed: {seed}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_table() {
+        let stats = simulator::simulate(
+            StrategyKind::Random,
+            &Layouts::Random { traps: 10 },
+            Ending::default(),
+            4,
+            1,
+        );
+        let table = table(&[stats]);
+        let rows: Vec<&str> = table.lines().collect();
+        assert_eq!(
+            rows[1],
+            "random              4     15.6%      10.00       64.00          0.0%"
+        );
+    }
+}
@@ -0,0 +1,114 @@
+//! Thin wrapper around stellar-cli
+//!
+//! Builds `stellar contract invoke` command lines for the user to run.
+
+use trap_grid_sdk::Network;
+
+#[derive(Clone, Debug)]
+pub struct InvokeCommand {
+    pub contract_id: String,
+    pub source: String,
+    pub rpc_url: String,
+    pub network_passphrase: String,
+    pub build_only: bool,
+    pub function: String,
+    pub args: Vec<(String, String)>,
+}
+
+impl InvokeCommand {
+    pub fn new(contract_id: &str, source: &str, network: &Network, function: &str) -> Self {
+        Self {
+            contract_id: contract_id.to_string(),
+            source: source.to_string(),
+            rpc_url: network.rpc_url.clone(),
+            network_passphrase: network.passphrase.clone(),
+            build_only: false,
+            function: function.to_string(),
+            args: Vec::new(),
+        }
+    }
+
+    pub fn arg(mut self, name: &str, value: impl ToString) -> Self {
+        self.args.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Command-line arguments after `stellar`, grouped as they are rendered on separate lines
+    fn groups(&self) -> Vec<Vec<String>> {
+        let mut groups = vec![
+            vec!["contract".to_string(), "invoke".to_string()],
+            vec!["--id".to_string(), self.contract_id.clone()],
+            vec!["--source-account".to_string(), self.source.clone()],
+            vec!["--rpc-url".to_string(), self.rpc_url.clone()],
+            vec![
+                "--network-passphrase".to_string(),
+                self.network_passphrase.clone(),
+            ],
+        ];
+        if self.build_only {
+            groups.push(vec!["--build-only".to_string()]);
+        } else {
+            groups.push(vec!["--send".to_string(), "yes".to_string()]);
+        }
+        groups.push(vec!["--".to_string()]);
+        groups.push(vec![self.function.clone()]);
+        for (name, value) in &self.args {
+            groups.push(vec![format!("--{name}"), value.clone()]);
+        }
+        groups
+    }
+
+    /// Render as a multi-line shell command
+    pub fn to_shell(&self) -> String {
+        let lines: Vec<String> = self
+            .groups()
+            .iter()
+            .map(|group| {
+                group
+                    .iter()
+                    .map(|token| shell_quote(token))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect();
+        format!("stellar {}", lines.join(" \\\n  "))
+    }
+}
+
+/// Quote a value for a POSIX shell unless it only contains safe characters
+pub fn shell_quote(value: &str) -> String {
+    let safe = !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=@".contains(c));
+    if safe {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', r"'\''"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_to_shell() {
+        let network = trap_grid_sdk::Config::default()
+            .network(Some("testnet"))
+            .unwrap();
+        let command =
+            InvokeCommand::new("CABC", "my key", &network, "end_game").arg("session_id", 7);
+        assert_eq!(
+            command.to_shell(),
+            "stellar contract invoke \\\n  --id CABC \\\n  --source-account 'my key' \\\n  --rpc-url https://soroban-testnet.stellar.org \\\n  --network-passphrase 'Test SDF Network ; September 2015' \\\n  --send yes \\\n  -- \\\n  end_game \\\n  --session_id 7"
+        );
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("testnet"), "testnet");
+        assert_eq!(shell_quote("my key"), "'my key'");
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+    }
+}
@@ -0,0 +1,663 @@
+//! `test-vectors` subcommand
+//!
+//! Golden vectors shared by the circuits and the Rust crates: trap layouts
+//! with their roots and Merkle openings, trap commitments, grid setups, and
+//! the public inputs each circuit exposes. `test-vectors/vectors.json` holds them for
+//! any consumer; the Noir tests read the same values from the generated
+//! `circuits/<circuit>/src/tests/vectors.nr`, since nargo cannot load files.
+//! A test here recomputes everything with the SDK, so a hashing or encoding
+//! change that the circuits do not share fails on one side or the other.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use clap::Args;
+use serde::Serialize;
+use trap_grid_encoding::{GridSetup, TrapCommitment, MAX_GRID_SIZE};
+
+use crate::encoding::{key_fields, parse_field, FieldBytes};
+use crate::grid::{cell_index, GRID_SIZE};
+use crate::reference::{
+    defender_field, grid_commitment, trap_commitment, PositionMovementInputs, TrapTree, MAX_CELLS,
+};
+use trap_grid_sdk::commitment::defender_key;
+
+/// Where the vectors live, relative to the repository root
+const JSON_PATH: &str = "test-vectors/vectors.json";
+const POSITION_MOVEMENT_NR: &str = "circuits/position-movement/src/tests/vectors.nr";
+const TRAP_COMMITMENT_NR: &str = "circuits/trap-commitment/src/tests/vectors.nr";
+const TRAP_MERKLE_ROOT_NR: &str = "circuits/trap-merkle-root/src/tests/vectors.nr";
+const GRID_SETUP_NR: &str = "circuits/grid-setup/src/tests/vectors.nr";
+
+/// Defenders the commitments are bound to
+const DEFENDER: &str = "GAIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCF6M";
+const ZERO_ACCOUNT: &str = "GAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAWHF";
+const CONTRACT: &str = "CARCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEVQO";
+
+const NR_HEADER: &str =
+    "// Generated from test-vectors/vectors.json by `trap-grid test-vectors`; do not edit.\n";
+
+#[derive(Args)]
+pub struct TestVectorsArgs {
+    /// Repository root
+    #[arg(long, default_value = ".")]
+    pub root: PathBuf,
+
+    /// Fail if the files on disk differ instead of rewriting them
+    #[arg(long)]
+    pub check: bool,
+}
+
+#[derive(Serialize)]
+struct Vectors {
+    trap_commitment: Vec<CommitmentVector>,
+    trap_merkle_root: Vec<LayoutVector>,
+    position_movement: Vec<MovementVector>,
+    grid_setup: Vec<SetupVector>,
+}
+
+#[derive(Serialize)]
+struct CommitmentVector {
+    trap_value: u32,
+    secret: String,
+    /// Address the commitment is bound to
+    defender: String,
+    /// Its hash, the circuit's `defender` input
+    defender_field: String,
+    trap_commitment: String,
+    /// Hex-encoded verifier public inputs
+    public_inputs: String,
+}
+
+#[derive(Serialize)]
+struct LayoutVector {
+    name: String,
+    grid_size: u32,
+    /// One string per `x`, one character per `y`; `1` is a trap
+    grid: Vec<String>,
+    trap_merkle_root: String,
+    openings: Vec<OpeningVector>,
+}
+
+#[derive(Serialize)]
+struct OpeningVector {
+    move_x: u32,
+    move_y: u32,
+    trap_value: u8,
+    trap_merkle_proof_length: u32,
+    trap_merkle_proof_indices: Vec<u8>,
+    trap_merkle_proof_siblings: Vec<String>,
+    public_inputs: String,
+}
+
+#[derive(Serialize)]
+struct SetupVector {
+    name: String,
+    grid_size: u32,
+    /// One string per `x`, one character per `y`; `1` is a trap
+    grid: Vec<String>,
+    salt: String,
+    trap_count: u32,
+    /// Address the commitment is bound to
+    defender: String,
+    /// Its key as the circuit's `defender` input, its first 31 bytes then its last
+    defender_key: [String; 2],
+    trap_merkle_root: String,
+    grid_commitment: String,
+    /// Hex-encoded verifier public inputs
+    public_inputs: String,
+}
+
+#[derive(Serialize)]
+struct MovementVector {
+    /// The grid setup whose commitment the move opens
+    setup: String,
+    move_x: u32,
+    move_y: u32,
+    is_hit: u32,
+    grid_size: u32,
+    grid_commitment: String,
+    trap_value: u32,
+    salt: String,
+    /// The key of the defender the commitment is bound to, as the circuit's
+    /// `defender` input
+    defender_key: [String; 2],
+    trap_merkle_proof_indices: Vec<u8>,
+    trap_merkle_proof_siblings: Vec<String>,
+    /// Hex-encoded verifier public inputs
+    public_inputs: String,
+}
+
+fn hex_field(field: &FieldBytes) -> String {
+    format!("0x{}", hex::encode(field))
+}
+
+fn field(value: &str) -> FieldBytes {
+    parse_field(value).expect("constant is a valid field element")
+}
+
+fn commitment_vector(trap_value: u32, secret: &str, defender: &str) -> CommitmentVector {
+    let secret = field(secret);
+    let defender_field = defender_field(defender).expect("constant is a valid address");
+    let trap_commitment = trap_commitment(trap_value, &secret, &defender_field);
+    CommitmentVector {
+        trap_value,
+        secret: hex_field(&secret),
+        defender: defender.to_string(),
+        defender_field: hex_field(&defender_field),
+        trap_commitment: hex_field(&trap_commitment),
+        public_inputs: hex::encode(
+            TrapCommitment {
+                trap_commitment,
+                defender: defender_field,
+            }
+            .encode(),
+        ),
+    }
+}
+
+fn trap_values(grid_size: u32, is_trap: impl Fn(u32, u32) -> bool) -> Vec<u8> {
+    let mut trap_values = vec![0u8; (grid_size * grid_size) as usize];
+    for x in 0..grid_size {
+        for y in 0..grid_size {
+            trap_values[cell_index(grid_size, x, y)] = u8::from(is_trap(x, y));
+        }
+    }
+    trap_values
+}
+
+fn grid_rows(grid_size: u32, trap_values: &[u8]) -> Vec<String> {
+    trap_values
+        .chunks(grid_size as usize)
+        .map(|row| row.iter().map(|value| value.to_string()).collect())
+        .collect()
+}
+
+fn layout_vector(name: &str, grid_size: u32, is_trap: impl Fn(u32, u32) -> bool) -> LayoutVector {
+    let trap_values = trap_values(grid_size, is_trap);
+    let tree = TrapTree::new(&trap_values).expect("the grid size is supported");
+
+    // Both corners, the Prover.toml sample cell and the first trap, if any
+    let last = grid_size - 1;
+    let mut cells = vec![(0, 0), (2, 3), (last, last)];
+    if let Some(index) = trap_values.iter().position(|value| *value == 1) {
+        let cell = (index as u32 / grid_size, index as u32 % grid_size);
+        if !cells.contains(&cell) {
+            cells.push(cell);
+        }
+    }
+    let openings = cells
+        .into_iter()
+        .map(|(x, y)| {
+            let opening = tree.opening(x, y);
+            OpeningVector {
+                move_x: x,
+                move_y: y,
+                trap_value: trap_values[cell_index(grid_size, x, y)],
+                trap_merkle_proof_length: opening.proof_length,
+                trap_merkle_proof_indices: opening.indices.to_vec(),
+                trap_merkle_proof_siblings: opening.siblings.iter().map(hex_field).collect(),
+                public_inputs: hex::encode(opening.encode()),
+            }
+        })
+        .collect();
+
+    LayoutVector {
+        name: name.to_string(),
+        grid_size,
+        grid: grid_rows(grid_size, &trap_values),
+        trap_merkle_root: hex_field(&tree.root()),
+        openings,
+    }
+}
+
+fn setup_vector(
+    name: &str,
+    grid_size: u32,
+    salt: &str,
+    defender: &str,
+    is_trap: impl Fn(u32, u32) -> bool,
+) -> SetupVector {
+    let trap_values = trap_values(grid_size, is_trap);
+    let root = TrapTree::new(&trap_values)
+        .expect("the grid size is supported")
+        .root();
+    let salt = field(salt);
+    let defender_field = defender_field(defender).expect("constant is a valid address");
+    let inputs = GridSetup {
+        grid_commitment: grid_commitment(&root, &salt, &defender_field),
+        trap_count: trap_values.iter().map(|value| u32::from(*value)).sum(),
+        grid_size,
+        defender: defender_key(defender).expect("constant is a valid address"),
+    };
+    SetupVector {
+        name: name.to_string(),
+        grid_size,
+        grid: grid_rows(grid_size, &trap_values),
+        salt: hex_field(&salt),
+        trap_count: inputs.trap_count,
+        defender: defender.to_string(),
+        defender_key: key_fields(&inputs.defender).map(|field| hex_field(&field)),
+        trap_merkle_root: hex_field(&root),
+        grid_commitment: hex_field(&inputs.grid_commitment),
+        public_inputs: hex::encode(inputs.encode()),
+    }
+}
+
+/// An answer at (`move_x`, `move_y`) opening the commitment of `setup`
+fn movement_vector(setup: &SetupVector, move_x: u32, move_y: u32) -> MovementVector {
+    let trap_values: Vec<u8> = setup
+        .grid
+        .concat()
+        .bytes()
+        .map(|cell| cell - b'0')
+        .collect();
+    let inputs = PositionMovementInputs::opening(
+        &trap_values,
+        field(&setup.salt),
+        defender_key(&setup.defender).expect("constant is a valid address"),
+        cell_index(setup.grid_size, move_x, move_y),
+        move_x,
+        move_y,
+    )
+    .expect("the grid size is supported");
+    MovementVector {
+        setup: setup.name.clone(),
+        move_x,
+        move_y,
+        is_hit: inputs.is_hit,
+        grid_size: inputs.grid_size,
+        grid_commitment: hex_field(&inputs.grid_commitment),
+        trap_value: inputs.trap_value,
+        salt: setup.salt.clone(),
+        defender_key: setup.defender_key.clone(),
+        trap_merkle_proof_indices: inputs.indices.to_vec(),
+        trap_merkle_proof_siblings: inputs.siblings.iter().map(hex_field).collect(),
+        public_inputs: hex::encode(inputs.public_inputs().encode()),
+    }
+}
+
+fn vectors() -> Vectors {
+    let grid_setup = vec![
+        // circuits/grid-setup/Prover.toml
+        setup_vector(
+            "diagonal_and_corners",
+            GRID_SIZE,
+            "12345",
+            DEFENDER,
+            |x, y| x == y || x + y == GRID_SIZE - 1 && (x == 0 || y == 0),
+        ),
+        // The same grid and salt bound to a contract defender
+        setup_vector(
+            "diagonal_and_corners_contract",
+            GRID_SIZE,
+            "12345",
+            CONTRACT,
+            |x, y| x == y || x + y == GRID_SIZE - 1 && (x == 0 || y == 0),
+        ),
+        setup_vector("checkerboard", GRID_SIZE, "1", ZERO_ACCOUNT, |x, y| {
+            (x + y) % 2 == 1
+        }),
+        setup_vector(
+            "single_trap",
+            GRID_SIZE,
+            "0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000000",
+            DEFENDER,
+            |x, y| (x, y) == (2, 3),
+        ),
+        setup_vector("diagonal_10x10", 10, "7", DEFENDER, |x, y| x == y),
+        setup_vector("checkerboard_16x16", 16, "7", DEFENDER, |x, y| {
+            (x + y) % 2 == 1
+        }),
+    ];
+    // Hits and misses on the Prover.toml grid, then on the other defenders' and sizes' grids
+    let position_movement = vec![
+        movement_vector(&grid_setup[0], 0, 0),
+        movement_vector(&grid_setup[0], 2, 3),
+        movement_vector(&grid_setup[0], 7, 0),
+        movement_vector(&grid_setup[1], 7, 7),
+        movement_vector(&grid_setup[2], 0, 0),
+        movement_vector(&grid_setup[4], 9, 8),
+        movement_vector(&grid_setup[5], 15, 15),
+        movement_vector(&grid_setup[5], 15, 14),
+    ];
+    Vectors {
+        trap_commitment: vec![
+            commitment_vector(0, "0", DEFENDER),
+            // circuits/trap-commitment/Prover.toml
+            commitment_vector(1, "12345", DEFENDER),
+            commitment_vector(0, "12345", DEFENDER),
+            // The same opening bound to other defenders
+            commitment_vector(1, "12345", ZERO_ACCOUNT),
+            commitment_vector(1, "12345", CONTRACT),
+            commitment_vector(
+                1,
+                "0x0f1e2d3c4b5a69788796a5b4c3d2e1f00112233445566778899aabbccddeeff0",
+                DEFENDER,
+            ),
+            // The largest field element
+            commitment_vector(
+                1,
+                "0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000000",
+                DEFENDER,
+            ),
+        ],
+        trap_merkle_root: vec![
+            layout_vector("empty", GRID_SIZE, |_, _| false),
+            layout_vector("full", GRID_SIZE, |_, _| true),
+            layout_vector("diagonal", GRID_SIZE, |x, y| x == y),
+            layout_vector("checkerboard", GRID_SIZE, |x, y| (x + y) % 2 == 1),
+            // 100 cells, so the tree pads leaves 100..128 with empty ones
+            layout_vector("diagonal_10x10", 10, |x, y| x == y),
+            layout_vector("checkerboard_16x16", 16, |x, y| (x + y) % 2 == 1),
+        ],
+        position_movement,
+        grid_setup,
+    }
+}
+
+fn render_json(vectors: &Vectors) -> String {
+    let mut json = serde_json::to_string_pretty(vectors).expect("vectors serialize");
+    json.push('\n');
+    json
+}
+
+fn nr_fields(fields: &[String]) -> String {
+    format!("[{}]", fields.join(", "))
+}
+
+fn render_position_movement(vectors: &Vectors) -> String {
+    let entries: Vec<String> = vectors
+        .position_movement
+        .iter()
+        .map(|vector| {
+            let indices: Vec<String> = vector
+                .trap_merkle_proof_indices
+                .iter()
+                .map(u8::to_string)
+                .collect();
+            let siblings: Vec<String> = vector
+                .trap_merkle_proof_siblings
+                .iter()
+                .map(|sibling| format!("            {sibling},\n"))
+                .collect();
+            format!(
+                "    // {} ({}, {})\n    PositionMovementVector {{\n        move_x: {},\n        \
+                 move_y: {},\n        is_hit: {},\n        grid_size: {},\n        \
+                 grid_commitment: {},\n        trap_value: {},\n        salt: {},\n        \
+                 defender: {},\n        trap_merkle_proof_indices: {},\n        \
+                 trap_merkle_proof_siblings: [\n{}        ],\n    }},\n",
+                vector.setup,
+                vector.move_x,
+                vector.move_y,
+                vector.move_x,
+                vector.move_y,
+                vector.is_hit,
+                vector.grid_size,
+                vector.grid_commitment,
+                vector.trap_value,
+                vector.salt,
+                nr_fields(&vector.defender_key),
+                nr_fields(&indices),
+                siblings.concat()
+            )
+        })
+        .collect();
+    format!(
+        "{NR_HEADER}\nuse crate::types::constants::MAX_MERKLE_TREE_DEPTH;\n\n\
+         pub struct PositionMovementVector {{\n    pub move_x: u32,\n    pub move_y: u32,\n    \
+         pub is_hit: u32,\n    pub grid_size: u32,\n    pub grid_commitment: Field,\n    \
+         pub trap_value: u32,\n    pub salt: Field,\n    pub defender: [Field; 2],\n    \
+         pub trap_merkle_proof_indices: [u1; MAX_MERKLE_TREE_DEPTH],\n    \
+         pub trap_merkle_proof_siblings: [Field; MAX_MERKLE_TREE_DEPTH],\n}}\n\n\
+         pub global POSITION_MOVEMENT_VECTORS: [PositionMovementVector; {}] = [\n{}];\n",
+        entries.len(),
+        entries.concat()
+    )
+}
+
+fn render_trap_commitment(vectors: &Vectors) -> String {
+    let entries: Vec<String> = vectors
+        .trap_commitment
+        .iter()
+        .map(|vector| {
+            format!(
+                "    TrapCommitmentVector {{\n        trap_value: {},\n        secret: {},\n        \
+                 defender: {},\n        trap_commitment: {},\n    }},\n",
+                vector.trap_value, vector.secret, vector.defender_field, vector.trap_commitment
+            )
+        })
+        .collect();
+    format!(
+        "{NR_HEADER}\npub struct TrapCommitmentVector {{\n    pub trap_value: u32,\n    \
+         pub secret: Field,\n    pub defender: Field,\n    pub trap_commitment: Field,\n}}\n\n\
+         pub global TRAP_COMMITMENT_VECTORS: [TrapCommitmentVector; {}] = [\n{}];\n",
+        entries.len(),
+        entries.concat()
+    )
+}
+
+fn render_trap_merkle_root(vectors: &Vectors) -> String {
+    let entries: Vec<String> = vectors
+        .trap_merkle_root
+        .iter()
+        .flat_map(|layout| {
+            layout.openings.iter().map(move |opening| {
+                let indices: Vec<String> = opening
+                    .trap_merkle_proof_indices
+                    .iter()
+                    .map(u8::to_string)
+                    .collect();
+                let siblings: Vec<String> = opening
+                    .trap_merkle_proof_siblings
+                    .iter()
+                    .map(|sibling| format!("            {sibling},\n"))
+                    .collect();
+                format!(
+                    "    // {} ({}, {})\n    TrapMerkleRootVector {{\n        trap_merkle_root: {},\n        \
+                     move_x: {},\n        move_y: {},\n        trap_value: {},\n        \
+                     trap_merkle_proof_length: {},\n        \
+                     trap_merkle_proof_indices: {},\n        trap_merkle_proof_siblings: [\n{}        ],\n        \
+                     grid_size: {},\n    }},\n",
+                    layout.name,
+                    opening.move_x,
+                    opening.move_y,
+                    layout.trap_merkle_root,
+                    opening.move_x,
+                    opening.move_y,
+                    opening.trap_value,
+                    opening.trap_merkle_proof_length,
+                    nr_fields(&indices),
+                    siblings.concat(),
+                    layout.grid_size
+                )
+            })
+        })
+        .collect();
+    format!(
+        "{NR_HEADER}\nuse crate::types::constants::MAX_MERKLE_TREE_DEPTH;\n\n\
+         pub struct TrapMerkleRootVector {{\n    pub trap_merkle_root: Field,\n    pub move_x: u32,\n    \
+         pub move_y: u32,\n    pub trap_value: u32,\n    pub trap_merkle_proof_length: u32,\n    \
+         pub trap_merkle_proof_indices: [u1; MAX_MERKLE_TREE_DEPTH],\n    \
+         pub trap_merkle_proof_siblings: [Field; MAX_MERKLE_TREE_DEPTH],\n    pub grid_size: u32,\n}}\n\n\
+         pub global TRAP_MERKLE_ROOT_VECTORS: [TrapMerkleRootVector; {}] = [\n{}];\n",
+        entries.len(),
+        entries.concat()
+    )
+}
+
+fn render_grid_setup(vectors: &Vectors) -> String {
+    let entries: Vec<String> = vectors
+        .grid_setup
+        .iter()
+        .map(|vector| {
+            let mut rows: Vec<String> = vector
+                .grid
+                .iter()
+                .map(|row| {
+                    let cells: Vec<String> = row.chars().map(String::from).collect();
+                    format!("            {},\n", cells.join(", "))
+                })
+                .collect();
+            // The cells past the grid, up to the circuit's MAX_CELLS, are empty
+            let cells = (vector.grid_size * vector.grid_size) as usize;
+            let padding = vec!["0"; MAX_CELLS - cells];
+            rows.extend(
+                padding
+                    .chunks(MAX_GRID_SIZE as usize)
+                    .map(|row| format!("            {},\n", row.join(", "))),
+            );
+            format!(
+                "    // {}\n    GridSetupVector {{\n        trap_values: [\n{}        ],\n        \
+                 salt: {},\n        trap_count: {},\n        grid_size: {},\n        \
+                 defender: {},\n        grid_commitment: {},\n    }},\n",
+                vector.name,
+                rows.concat(),
+                vector.salt,
+                vector.trap_count,
+                vector.grid_size,
+                nr_fields(&vector.defender_key),
+                vector.grid_commitment
+            )
+        })
+        .collect();
+    format!(
+        "{NR_HEADER}\nuse crate::types::constants::MAX_CELLS;\n\n\
+         pub struct GridSetupVector {{\n    pub trap_values: [u32; MAX_CELLS],\n    \
+         pub salt: Field,\n    pub trap_count: u32,\n    pub grid_size: u32,\n    \
+         pub defender: [Field; 2],\n    pub grid_commitment: Field,\n}}\n\n\
+         pub global GRID_SETUP_VECTORS: [GridSetupVector; {}] = [\n{}];\n",
+        entries.len(),
+        entries.concat()
+    )
+}
+
+/// Every generated file, by path relative to the repository root
+fn files() -> Vec<(&'static str, String)> {
+    let vectors = vectors();
+    vec![
+        (JSON_PATH, render_json(&vectors)),
+        (POSITION_MOVEMENT_NR, render_position_movement(&vectors)),
+        (TRAP_COMMITMENT_NR, render_trap_commitment(&vectors)),
+        (TRAP_MERKLE_ROOT_NR, render_trap_merkle_root(&vectors)),
+        (GRID_SETUP_NR, render_grid_setup(&vectors)),
+    ]
+}
+
+pub fn run(args: TestVectorsArgs) -> Result<()> {
+    let mut stale = Vec::new();
+    for (relative, contents) in files() {
+        let path = args.root.join(relative);
+        if args.check {
+            let current = fs::read_to_string(&path).unwrap_or_default();
+            if current != contents {
+                stale.push(relative);
+            }
+            continue;
+        }
+        write(&path, &contents)?;
+        println!("wrote {}", path.display());
+    }
+    if !stale.is_empty() {
+        bail!(
+            "test vectors out of date: {}; run `trap-grid test-vectors` to regenerate",
+            stale.join(", ")
+        );
+    }
+    Ok(())
+}
+
+fn write(path: &Path, contents: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    fs::write(path, contents).with_context(|| format!("failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_checked_in_vectors_match() {
+        let checked_in = [
+            include_str!("../../../test-vectors/vectors.json"),
+            include_str!("../../../circuits/position-movement/src/tests/vectors.nr"),
+            include_str!("../../../circuits/trap-commitment/src/tests/vectors.nr"),
+            include_str!("../../../circuits/trap-merkle-root/src/tests/vectors.nr"),
+            include_str!("../../../circuits/grid-setup/src/tests/vectors.nr"),
+        ];
+        for ((path, generated), checked_in) in files().iter().zip(checked_in) {
+            assert!(
+                *generated == checked_in,
+                "{path} differs from what the SDK computes; run `trap-grid test-vectors`"
+            );
+        }
+    }
+
+    #[test]
+    fn test_vectors_match_circuit_samples() {
+        let vectors = vectors();
+        // circuits/trap-commitment/Prover.toml
+        assert_eq!(
+            vectors.trap_commitment[1].trap_commitment,
+            "0x2d69e166d6b29b7c5159dcca7edd5a6c2f13aafe4d14e2525680d1422066e6e7"
+        );
+        // The opening of (2, 3) in every 8x8 layout proves leaf 19
+        for layout in vectors
+            .trap_merkle_root
+            .iter()
+            .filter(|l| l.grid_size == GRID_SIZE)
+        {
+            assert_eq!(
+                layout.openings[1].trap_merkle_proof_indices,
+                [1, 1, 0, 0, 1, 0, 0, 0]
+            );
+        }
+        // circuits/grid-setup/Prover.toml
+        assert_eq!(
+            vectors.grid_setup[0].grid_commitment,
+            "0x2ee5032b9c54a99742b33bb21516e00e4872bd72a4b0ee205189c5a365e65f85"
+        );
+        assert_eq!(vectors.grid_setup[0].trap_count, 10);
+        // Only the defender tells the first two setups apart
+        assert_eq!(
+            vectors.grid_setup[0].trap_merkle_root,
+            vectors.grid_setup[1].trap_merkle_root
+        );
+        assert_ne!(
+            vectors.grid_setup[0].grid_commitment,
+            vectors.grid_setup[1].grid_commitment
+        );
+        // circuits/position-movement/Prover.toml: a hit at (7, 0) opening the
+        // grid-setup sample's commitment
+        let sample = &vectors.position_movement[2];
+        assert_eq!((sample.move_x, sample.move_y, sample.is_hit), (7, 0, 1));
+        assert_eq!(
+            sample.grid_commitment,
+            vectors.grid_setup[0].grid_commitment
+        );
+        for vector in &vectors.position_movement {
+            let setup = vectors.grid_setup.iter().find(|s| s.name == vector.setup);
+            assert_eq!(vector.grid_commitment, setup.unwrap().grid_commitment);
+        }
+    }
+
+    #[test]
+    fn test_run_check() {
+        let dir = tempfile::tempdir().unwrap();
+        let args = |check| TestVectorsArgs {
+            root: dir.path().to_path_buf(),
+            check,
+        };
+        assert!(run(args(true)).is_err());
+        run(args(false)).unwrap();
+        run(args(true)).unwrap();
+
+        fs::write(dir.path().join(JSON_PATH), "{}").unwrap();
+        let err = run(args(true)).unwrap_err().to_string();
+        assert!(err.contains(JSON_PATH), "{err}");
+    }
+}
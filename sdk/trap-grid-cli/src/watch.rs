@@ -0,0 +1,153 @@
+//! `watch` subcommand
+//!
+//! Polls `getEvents` for a session's trap-grid events and prints them as a
+//! live feed, or as one JSON object per line with `--json` so the output can
+//! be piped into other tools.
+
+use std::time::Duration;
+
+use anyhow::Result;
+use clap::Args;
+use trap_grid_sdk::watcher::{GameHandler, GameState, GameWatcher, WatchedEvent};
+use trap_grid_sdk::{GameEnded, GameEvent, GameStarted, MoveMade, MoveSubmitted};
+
+use crate::network::NetworkArgs;
+
+#[derive(Args)]
+pub struct WatchArgs {
+    /// Trap-grid contract id, overriding the profile's (or set TRAP_GRID_CONTRACT)
+    #[arg(long)]
+    pub contract_id: Option<String>,
+
+    #[command(flatten)]
+    pub network: NetworkArgs,
+
+    /// Game session identifier
+    #[arg(long)]
+    pub session: u32,
+
+    /// First ledger to read events from (must be within the RPC retention window);
+    /// defaults to the latest ledger
+    #[arg(long)]
+    pub from_ledger: Option<u32>,
+
+    /// Polling interval in seconds
+    #[arg(long, default_value_t = 2)]
+    pub interval: u64,
+
+    /// Keep watching after the game has ended
+    #[arg(long)]
+    pub follow: bool,
+
+    /// Print one JSON object per event
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// One-line human-readable description of an event
+fn describe(event: &GameEvent) -> String {
+    match event {
+        GameEvent::GameStarted(GameStarted {
+            session_id,
+            defender,
+            attacker,
+        }) => format!("session {session_id} started: defender {defender}, attacker {attacker}"),
+        GameEvent::MoveSubmitted(MoveSubmitted { x, y, note, .. }) => {
+            let note = if note.is_empty() {
+                String::new()
+            } else {
+                format!(" \"{}\"", String::from_utf8_lossy(note))
+            };
+            format!("move ({x}, {y}) submitted{note}, waiting for the defender's proof")
+        }
+        GameEvent::MoveMade(MoveMade {
+            x,
+            y,
+            is_hit,
+            verified,
+            ..
+        }) => format!(
+            "move ({x}, {y}): {}{}",
+            if *is_hit { "HIT" } else { "MISS" },
+            if *verified { " (proof verified)" } else { "" }
+        ),
+        GameEvent::GameEnded(GameEnded {
+            winner,
+            hits,
+            misses,
+            ..
+        }) => format!("game over: {winner} wins ({hits} hits, {misses} misses)"),
+    }
+}
+
+/// Prints each event as it arrives
+struct Feed {
+    json: bool,
+}
+
+impl GameHandler for Feed {
+    fn on_event(&mut self, _state: &GameState, event: &WatchedEvent) {
+        if self.json {
+            match serde_json::to_string(event) {
+                Ok(line) => println!("{line}"),
+                Err(err) => eprintln!("failed to encode event: {err}"),
+            }
+        } else {
+            println!("[ledger {}] {}", event.ledger, describe(&event.event));
+        }
+    }
+}
+
+pub fn run(args: WatchArgs) -> Result<()> {
+    let network = args.network.resolve(args.contract_id.as_deref())?;
+    let contract = network.contract()?;
+    let rpc = network.rpc();
+    let mut watcher = GameWatcher::new(&rpc, contract, args.session, args.from_ledger)?
+        .with_interval(Duration::from_secs(args.interval));
+
+    if !args.json {
+        eprintln!(
+            "Watching session {} from ledger {} (Ctrl-C to stop)",
+            args.session,
+            watcher.start_ledger()
+        );
+        let state = watcher.state();
+        if state.ended && !args.follow {
+            eprintln!(
+                "The game has already ended ({} hits, {} misses)",
+                state.hits, state.misses
+            );
+        }
+    }
+    watcher.run(&mut Feed { json: args.json }, args.follow)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_describe() {
+        let event = GameEvent::MoveMade(MoveMade {
+            session_id: 1,
+            defender: "GAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAWHF".into(),
+            attacker: "GAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAWHF".into(),
+            x: 2,
+            y: 3,
+            is_hit: true,
+            verified: true,
+        });
+        assert_eq!(describe(&event), "move (2, 3): HIT (proof verified)");
+
+        let entry = WatchedEvent {
+            ledger: 7,
+            ledger_closed_at: "2026-01-01T00:00:00Z".into(),
+            tx_hash: "ab".into(),
+            event,
+        };
+        assert_eq!(
+            serde_json::to_string(&entry).unwrap(),
+            r#"{"ledger":7,"ledger_closed_at":"2026-01-01T00:00:00Z","tx_hash":"ab","event":"move_made","session_id":1,"defender":"GAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAWHF","attacker":"GAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAWHF","x":2,"y":3,"is_hit":true,"verified":true}"#
+        );
+    }
+}
@@ -0,0 +1,70 @@
+//! Domain-separation tags of the hashes feeding the circuits
+//!
+//! Every Poseidon hash a circuit checks starts with a tag naming what it
+//! hashes, so a value computed for one purpose can never stand in for
+//! another: a leaf for an inner node, or a trap commitment for a grid
+//! commitment.
+//! A tag is its ASCII name read as a big-endian integer; the circuits'
+//! `libs/hash/domain.nr` declare the same values.
+
+use crate::{FieldBytes, FIELD_BYTES};
+
+/// Longest tag name; 31 bytes always stay below the BN254 modulus
+pub const MAX_TAG_LEN: usize = FIELD_BYTES - 1;
+
+/// The field element tagging hashes of `name`
+pub const fn tag(name: &str) -> FieldBytes {
+    let name = name.as_bytes();
+    assert!(
+        !name.is_empty() && name.len() <= MAX_TAG_LEN,
+        "tag names are 1 to 31 bytes"
+    );
+    let mut out = [0u8; FIELD_BYTES];
+    let mut i = 0;
+    while i < name.len() {
+        out[FIELD_BYTES - name.len() + i] = name[i];
+        i += 1;
+    }
+    out
+}
+
+/// Leaf of the trap Merkle tree: `H(tag, trap_value)`
+pub const TRAP_LEAF: FieldBytes = tag("trap-grid/leaf");
+
+/// Inner node of the trap Merkle tree: `H(tag, left, right)`
+pub const MERKLE_NODE: FieldBytes = tag("trap-grid/node");
+
+/// The trap-commitment circuit's commitment: `H(tag, trap_value, secret, defender)`
+pub const TRAP_COMMITMENT: FieldBytes = tag("trap-grid/trap-commitment");
+
+/// The grid-setup circuit's commitment: `H(tag, trap_merkle_root, salt, defender)`
+pub const GRID_COMMITMENT: FieldBytes = tag("trap-grid/grid-commitment");
+
+/// A defender's address key hashed to a field: `H(tag, length, chunks...)`
+pub const DEFENDER: FieldBytes = tag("trap-grid/defender");
+
+/// Every tag, which must all differ
+pub const ALL: [FieldBytes; 5] = [
+    TRAP_LEAF,
+    MERKLE_NODE,
+    TRAP_COMMITMENT,
+    GRID_COMMITMENT,
+    DEFENDER,
+];
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::BN254_MODULUS;
+
+    #[test]
+    fn test_tags() {
+        // libs/hash/domain.nr
+        assert_eq!(TRAP_LEAF[FIELD_BYTES - 14..], *b"trap-grid/leaf");
+        assert!(TRAP_LEAF[..FIELD_BYTES - 14].iter().all(|b| *b == 0));
+        for (i, a) in ALL.iter().enumerate() {
+            assert!(*a < BN254_MODULUS);
+            assert!(ALL[i + 1..].iter().all(|b| a != b));
+        }
+    }
+}
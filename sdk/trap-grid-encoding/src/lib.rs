@@ -0,0 +1,582 @@
+//! # Trap Grid public-input encoding
+//!
+//! Byte layout of the circuits' public inputs, shared by the trap-grid
+//! contract and the off-chain SDK and CLI so the two sides cannot drift.
+//! Every public input is a BN254 field element serialized as 32 big-endian
+//! bytes, concatenated in the order the circuit's `PublicInputs` struct
+//! declares them (arrays flattened element by element).
+//!
+//! `no_std` and dependency-free, so it builds for the contracts' wasm target.
+
+#![cfg_attr(not(test), no_std)]
+
+use core::fmt;
+
+pub mod domain;
+
+/// Size of one encoded public input
+pub const FIELD_BYTES: usize = 32;
+
+pub type FieldBytes = [u8; FIELD_BYTES];
+
+/// BN254 scalar field modulus, big-endian
+pub const BN254_MODULUS: FieldBytes = [
+    0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29, 0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81, 0x58, 0x5d,
+    0x28, 0x33, 0xe8, 0x48, 0x79, 0xb9, 0x70, 0x91, 0x43, 0xe1, 0xf5, 0x93, 0xf0, 0x00, 0x00, 0x01,
+];
+
+/// Side of the grid a deployment plays on unless configured otherwise
+pub const DEFAULT_GRID_SIZE: u32 = 8;
+
+/// Smallest grid side, so a grid has room for a trap and a safe cell
+pub const MIN_GRID_SIZE: u32 = 2;
+
+/// Largest grid side the circuits are sized for (256 cells)
+pub const MAX_GRID_SIZE: u32 = 16;
+
+/// Merkle tree depth of the largest grid, the length of the circuits' path
+/// arrays; smaller grids fill only their first [`merkle_depth`] levels
+pub const MAX_MERKLE_TREE_DEPTH: usize = merkle_depth(MAX_GRID_SIZE);
+
+/// Depth of the trap Merkle tree of a `grid_size` x `grid_size` grid: the
+/// fewest levels holding every cell, missing leaves being empty cells
+pub const fn merkle_depth(grid_size: u32) -> usize {
+    let cells = grid_size * grid_size;
+    let mut depth = 0;
+    while (1u32 << depth) < cells {
+        depth += 1;
+    }
+    depth
+}
+
+/// Version of the trap-grid contract interface, returned by its `version()`
+///
+/// Bumped whenever [`INTERFACE`] changes in a way old clients cannot follow.
+pub const INTERFACE_VERSION: u32 = 5;
+
+/// Canonical description of the trap-grid contract interface: the calls
+/// clients make, the topics of its events and the public inputs `make_move`
+/// verifies. The contract's `interface_hash()` is its SHA-256, so a client
+/// built against a different description notices even when the version was
+/// not bumped.
+pub const INTERFACE: &str = "trap-grid/5\n\
+    start_game(u32,address,address,i128,i128,grid_setup)\n\
+    start_sponsored_game(u32,address,address,i128,i128,grid_setup,vec<sponsorship>)\n\
+    submit_move(u32,u32,u32)\n\
+    submit_move_with_note(u32,u32,u32,bytes)\n\
+    simulate_move(u32,u32,u32)\n\
+    get_full_state(u32)->full_state\n\
+    get_rules(u32)->rules\n\
+    make_move(u32,u32,u32,bool,bytes,bytes)->bool\n\
+    end_game(u32)\n\
+    abort_due_to_verifier(u32,address)->bool\n\
+    open_channel(u32,bytes32,bytes32)\n\
+    submit_channel_state(u32,channel_state,bytes64,bytes64)->u32\n\
+    settle_channel(u32,vec<channel_move>)\n\
+    enable_optimistic(u32,address,i128)\n\
+    claim_move(u32,bool)\n\
+    challenge_claim(u32,u32)->u32\n\
+    prove_claim(u32,bytes,bytes)\n\
+    appoint_arbiter(u32,address)\n\
+    appeal(u32,address)\n\
+    finalize_game(u32)\n\
+    resolve_appeal(u32,ruling)\n\
+    freeze_session(address,u32)\n\
+    unfreeze_session(address,u32)\n\
+    claim_winnings(u32)\n\
+    fund_bonus_pool(address,address,i128)->i128\n\
+    start_practice(address,bytes32)\n\
+    practice_move(address,u32,u32)->bool\n\
+    finish_practice(address,bytes32)->practice_stats\n\
+    daily_move(address,u32,u32)\n\
+    rank_daily(u32,address)->option<u32>\n\
+    commit_grid(u32,bytes32)\n\
+    event_topics(name,session_id,defender,attacker)\n\
+    position_movement(move_x,move_y,is_hit,grid_size,grid_commitment)\n\
+    grid_setup(grid_commitment,trap_count,grid_size,defender[0],defender[1])\n";
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DecodeError {
+    /// The input is not exactly `expected` bytes long
+    Length { expected: usize, found: usize },
+    /// Field `index` is not below the BN254 modulus
+    NotCanonical { index: usize },
+    /// Field `index` does not fit the type the circuit declares for it
+    OutOfRange { index: usize },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::Length { expected, found } => {
+                write!(f, "public inputs are {found} bytes, expected {expected}")
+            }
+            DecodeError::NotCanonical { index } => {
+                write!(f, "public input {index} is not below the BN254 modulus")
+            }
+            DecodeError::OutOfRange { index } => {
+                write!(f, "public input {index} is out of range for its type")
+            }
+        }
+    }
+}
+
+impl core::error::Error for DecodeError {}
+
+/// Encode an integer as a field element
+pub const fn encode_u64(value: u64) -> FieldBytes {
+    let mut out = [0u8; FIELD_BYTES];
+    let bytes = value.to_be_bytes();
+    let mut i = 0;
+    while i < 8 {
+        out[FIELD_BYTES - 8 + i] = bytes[i];
+        i += 1;
+    }
+    out
+}
+
+/// The integer a field element encodes, if it fits in a u64
+pub fn decode_u64(field: &FieldBytes) -> Option<u64> {
+    let (high, low) = field.split_at(FIELD_BYTES - 8);
+    if high.iter().any(|b| *b != 0) {
+        return None;
+    }
+    Some(u64::from_be_bytes(low.try_into().expect("8 bytes")))
+}
+
+/// Whether `field` is a reduced field element (below the modulus)
+pub fn is_canonical(field: &FieldBytes) -> bool {
+    *field < BN254_MODULUS
+}
+
+/// Split `bytes` into exactly `N` canonical field elements
+fn split<const N: usize>(bytes: &[u8]) -> Result<[FieldBytes; N], DecodeError> {
+    if bytes.len() != N * FIELD_BYTES {
+        return Err(DecodeError::Length {
+            expected: N * FIELD_BYTES,
+            found: bytes.len(),
+        });
+    }
+    let mut fields = [[0u8; FIELD_BYTES]; N];
+    for (index, (field, chunk)) in fields
+        .iter_mut()
+        .zip(bytes.chunks_exact(FIELD_BYTES))
+        .enumerate()
+    {
+        field.copy_from_slice(chunk);
+        if !is_canonical(field) {
+            return Err(DecodeError::NotCanonical { index });
+        }
+    }
+    Ok(fields)
+}
+
+fn join<const N: usize, const LEN: usize>(fields: [FieldBytes; N]) -> [u8; LEN] {
+    let mut out = [0u8; LEN];
+    for (chunk, field) in out.chunks_exact_mut(FIELD_BYTES).zip(fields) {
+        chunk.copy_from_slice(&field);
+    }
+    out
+}
+
+fn u32_at(fields: &[FieldBytes], index: usize) -> Result<u32, DecodeError> {
+    decode_u64(&fields[index])
+        .and_then(|value| u32::try_from(value).ok())
+        .ok_or(DecodeError::OutOfRange { index })
+}
+
+/// A `u1`/boolean input: 0 or 1
+fn bit_at(fields: &[FieldBytes], index: usize) -> Result<u8, DecodeError> {
+    match decode_u64(&fields[index]) {
+        Some(value @ (0 | 1)) => Ok(value as u8),
+        _ => Err(DecodeError::OutOfRange { index }),
+    }
+}
+
+/// Public inputs of the position-movement circuit (the ones `make_move` verifies)
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PositionMovement {
+    pub move_x: u32,
+    pub move_y: u32,
+    pub is_hit: bool,
+    /// Side of the game's grid, which the move must fall on
+    pub grid_size: u32,
+    /// The game's [`GridSetup::grid_commitment`], which the proof opens at
+    /// cell `move_x * grid_size + move_y`
+    pub grid_commitment: FieldBytes,
+}
+
+impl PositionMovement {
+    pub const NAMES: [&'static str; 5] =
+        ["move_x", "move_y", "is_hit", "grid_size", "grid_commitment"];
+    pub const LEN: usize = Self::NAMES.len() * FIELD_BYTES;
+
+    pub fn encode(&self) -> [u8; Self::LEN] {
+        join([
+            encode_u64(self.move_x.into()),
+            encode_u64(self.move_y.into()),
+            encode_u64(self.is_hit.into()),
+            encode_u64(self.grid_size.into()),
+            self.grid_commitment,
+        ])
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let fields = split::<5>(bytes)?;
+        Ok(Self {
+            move_x: u32_at(&fields, 0)?,
+            move_y: u32_at(&fields, 1)?,
+            is_hit: bit_at(&fields, 2)? == 1,
+            grid_size: u32_at(&fields, 3)?,
+            grid_commitment: fields[4],
+        })
+    }
+}
+
+/// Public inputs of the trap-commitment circuit
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TrapCommitment {
+    pub trap_commitment: FieldBytes,
+    /// Hash of the defender's address the commitment is bound to
+    pub defender: FieldBytes,
+}
+
+impl TrapCommitment {
+    pub const NAMES: [&'static str; 2] = ["trap_commitment", "defender"];
+    pub const LEN: usize = Self::NAMES.len() * FIELD_BYTES;
+
+    pub fn encode(&self) -> [u8; Self::LEN] {
+        join([self.trap_commitment, self.defender])
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let [trap_commitment, defender] = split::<2>(bytes)?;
+        Ok(Self {
+            trap_commitment,
+            defender,
+        })
+    }
+}
+
+/// A 32-byte key as the two fields the circuits hash it from: its first 31
+/// bytes, then its last byte
+pub fn key_fields(key: &[u8; FIELD_BYTES]) -> [FieldBytes; 2] {
+    let mut head = [0u8; FIELD_BYTES];
+    head[1..].copy_from_slice(&key[..FIELD_BYTES - 1]);
+    [head, encode_u64(key[FIELD_BYTES - 1].into())]
+}
+
+/// The key [`key_fields`] split into `fields[index]` and `fields[index + 1]`
+fn key_at(fields: &[FieldBytes], index: usize) -> Result<[u8; FIELD_BYTES], DecodeError> {
+    let (head, last) = (&fields[index], &fields[index + 1]);
+    if head[0] != 0 {
+        return Err(DecodeError::OutOfRange { index });
+    }
+    let last = decode_u64(last)
+        .and_then(|value| u8::try_from(value).ok())
+        .ok_or(DecodeError::OutOfRange { index: index + 1 })?;
+    let mut key = [0u8; FIELD_BYTES];
+    key[..FIELD_BYTES - 1].copy_from_slice(&head[1..]);
+    key[FIELD_BYTES - 1] = last;
+    Ok(key)
+}
+
+/// Public inputs of the grid-setup circuit, which `start_game` verifies
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct GridSetup {
+    /// `poseidon_hash_4([DOMAIN_GRID_COMMITMENT, trap_merkle_root, salt, defender])`,
+    /// `defender` being the hash of [`Self::defender`]
+    pub grid_commitment: FieldBytes,
+    pub trap_count: u32,
+    /// Side of the committed grid
+    pub grid_size: u32,
+    /// Account key or contract id of the defender starting the game, encoded
+    /// as its [`key_fields`]
+    pub defender: [u8; FIELD_BYTES],
+}
+
+impl GridSetup {
+    pub const NAMES: [&'static str; 5] = [
+        "grid_commitment",
+        "trap_count",
+        "grid_size",
+        "defender[0]",
+        "defender[1]",
+    ];
+    pub const LEN: usize = Self::NAMES.len() * FIELD_BYTES;
+
+    pub fn encode(&self) -> [u8; Self::LEN] {
+        let [head, last] = key_fields(&self.defender);
+        join([
+            self.grid_commitment,
+            encode_u64(self.trap_count.into()),
+            encode_u64(self.grid_size.into()),
+            head,
+            last,
+        ])
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let fields = split::<5>(bytes)?;
+        Ok(Self {
+            grid_commitment: fields[0],
+            trap_count: u32_at(&fields, 1)?,
+            grid_size: u32_at(&fields, 2)?,
+            defender: key_at(&fields, 3)?,
+        })
+    }
+}
+
+/// Public inputs of the trap-merkle-root circuit
+///
+/// The path arrays are sized for the largest grid; `proof_length` is the
+/// [`merkle_depth`] of `grid_size`, and the levels past it are zero.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TrapMerkleRoot {
+    pub trap_merkle_root: FieldBytes,
+    pub move_x: u32,
+    pub move_y: u32,
+    pub proof_length: u32,
+    /// Path bits, leaf level first
+    pub indices: [u8; MAX_MERKLE_TREE_DEPTH],
+    /// Sibling hashes, leaf level first
+    pub siblings: [FieldBytes; MAX_MERKLE_TREE_DEPTH],
+    /// Side of the grid the root commits to
+    pub grid_size: u32,
+}
+
+impl TrapMerkleRoot {
+    pub const NAMES: [&'static str; 5 + 2 * MAX_MERKLE_TREE_DEPTH] = [
+        "trap_merkle_root",
+        "move_x",
+        "move_y",
+        "trap_merkle_proof_length",
+        "trap_merkle_proof_indices[0]",
+        "trap_merkle_proof_indices[1]",
+        "trap_merkle_proof_indices[2]",
+        "trap_merkle_proof_indices[3]",
+        "trap_merkle_proof_indices[4]",
+        "trap_merkle_proof_indices[5]",
+        "trap_merkle_proof_indices[6]",
+        "trap_merkle_proof_indices[7]",
+        "trap_merkle_proof_siblings[0]",
+        "trap_merkle_proof_siblings[1]",
+        "trap_merkle_proof_siblings[2]",
+        "trap_merkle_proof_siblings[3]",
+        "trap_merkle_proof_siblings[4]",
+        "trap_merkle_proof_siblings[5]",
+        "trap_merkle_proof_siblings[6]",
+        "trap_merkle_proof_siblings[7]",
+        "grid_size",
+    ];
+    pub const LEN: usize = Self::NAMES.len() * FIELD_BYTES;
+
+    pub fn encode(&self) -> [u8; Self::LEN] {
+        let mut fields = [[0u8; FIELD_BYTES]; 5 + 2 * MAX_MERKLE_TREE_DEPTH];
+        fields[0] = self.trap_merkle_root;
+        fields[1] = encode_u64(self.move_x.into());
+        fields[2] = encode_u64(self.move_y.into());
+        fields[3] = encode_u64(self.proof_length.into());
+        for level in 0..MAX_MERKLE_TREE_DEPTH {
+            fields[4 + level] = encode_u64(self.indices[level].into());
+            fields[4 + MAX_MERKLE_TREE_DEPTH + level] = self.siblings[level];
+        }
+        fields[4 + 2 * MAX_MERKLE_TREE_DEPTH] = encode_u64(self.grid_size.into());
+        join(fields)
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let fields = split::<{ 5 + 2 * MAX_MERKLE_TREE_DEPTH }>(bytes)?;
+        let mut indices = [0u8; MAX_MERKLE_TREE_DEPTH];
+        let mut siblings = [[0u8; FIELD_BYTES]; MAX_MERKLE_TREE_DEPTH];
+        for level in 0..MAX_MERKLE_TREE_DEPTH {
+            indices[level] = bit_at(&fields, 4 + level)?;
+            siblings[level] = fields[4 + MAX_MERKLE_TREE_DEPTH + level];
+        }
+        Ok(Self {
+            trap_merkle_root: fields[0],
+            move_x: u32_at(&fields, 1)?,
+            move_y: u32_at(&fields, 2)?,
+            proof_length: u32_at(&fields, 3)?,
+            indices,
+            siblings,
+            grid_size: u32_at(&fields, 4 + 2 * MAX_MERKLE_TREE_DEPTH)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Largest canonical field element
+    const FIELD_MAX: FieldBytes = {
+        let mut max = BN254_MODULUS;
+        max[FIELD_BYTES - 1] -= 1;
+        max
+    };
+
+    fn field(seed: u8) -> FieldBytes {
+        let mut field = [seed; FIELD_BYTES];
+        field[0] = seed % 0x30;
+        field
+    }
+
+    #[test]
+    fn test_position_movement_round_trip() {
+        for move_x in [0, 1, 7, 8, u32::MAX] {
+            for move_y in 0..8 {
+                for is_hit in [false, true] {
+                    let inputs = PositionMovement {
+                        move_x,
+                        move_y,
+                        is_hit,
+                        grid_size: DEFAULT_GRID_SIZE,
+                        grid_commitment: FIELD_MAX,
+                    };
+                    let bytes = inputs.encode();
+                    assert_eq!(PositionMovement::decode(&bytes), Ok(inputs));
+                    assert_eq!(&bytes[..FIELD_BYTES], &encode_u64(move_x.into()));
+                    assert_eq!(bytes[3 * FIELD_BYTES - 1], u8::from(is_hit));
+                    assert_eq!(bytes[4 * FIELD_BYTES..], FIELD_MAX);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_interface_describes_encoding() {
+        assert!(INTERFACE.starts_with(&format!("trap-grid/{INTERFACE_VERSION}\n")));
+        let inputs = format!("position_movement({})", PositionMovement::NAMES.join(","));
+        assert!(INTERFACE.lines().any(|line| line == inputs));
+        let inputs = format!("grid_setup({})", GridSetup::NAMES.join(","));
+        assert!(INTERFACE.lines().any(|line| line == inputs));
+    }
+
+    #[test]
+    fn test_merkle_depth() {
+        assert_eq!(merkle_depth(DEFAULT_GRID_SIZE), 6);
+        assert_eq!(merkle_depth(MAX_GRID_SIZE), 8);
+        assert_eq!(MAX_MERKLE_TREE_DEPTH, 8);
+        // 100 cells fill 128 leaves
+        assert_eq!(merkle_depth(10), 7);
+        assert_eq!(merkle_depth(MIN_GRID_SIZE), 2);
+    }
+
+    #[test]
+    fn test_trap_merkle_root_round_trip() {
+        for cell in 0..256u32 {
+            let mut indices = [0u8; MAX_MERKLE_TREE_DEPTH];
+            for (level, bit) in indices.iter_mut().enumerate() {
+                *bit = ((cell >> level) & 1) as u8;
+            }
+            let inputs = TrapMerkleRoot {
+                trap_merkle_root: if cell == 0 {
+                    FIELD_MAX
+                } else {
+                    field(cell as u8)
+                },
+                move_x: cell / MAX_GRID_SIZE,
+                move_y: cell % MAX_GRID_SIZE,
+                proof_length: MAX_MERKLE_TREE_DEPTH as u32,
+                indices,
+                siblings: core::array::from_fn(|level| field((cell as u8) ^ level as u8)),
+                grid_size: MAX_GRID_SIZE,
+            };
+            assert_eq!(TrapMerkleRoot::decode(&inputs.encode()), Ok(inputs));
+        }
+
+        let commitment = TrapCommitment {
+            trap_commitment: FIELD_MAX,
+            defender: field(7),
+        };
+        assert_eq!(TrapCommitment::decode(&commitment.encode()), Ok(commitment));
+
+        let setup = GridSetup {
+            grid_commitment: FIELD_MAX,
+            trap_count: 10,
+            grid_size: DEFAULT_GRID_SIZE,
+            defender: [0xff; FIELD_BYTES],
+        };
+        let bytes = setup.encode();
+        assert_eq!(GridSetup::decode(&bytes), Ok(setup));
+        // The key's first 31 bytes, then its last byte
+        assert_eq!(bytes[3 * FIELD_BYTES], 0);
+        assert_eq!(bytes[3 * FIELD_BYTES + 1..4 * FIELD_BYTES], [0xff; 31]);
+        assert_eq!(bytes[4 * FIELD_BYTES..], encode_u64(0xff));
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        for len in (0..=PositionMovement::LEN + FIELD_BYTES).filter(|l| *l != PositionMovement::LEN)
+        {
+            assert_eq!(
+                PositionMovement::decode(&[0u8; PositionMovement::LEN + FIELD_BYTES][..len]),
+                Err(DecodeError::Length {
+                    expected: PositionMovement::LEN,
+                    found: len
+                })
+            );
+        }
+
+        let valid = TrapMerkleRoot {
+            trap_merkle_root: field(1),
+            move_x: 2,
+            move_y: 3,
+            proof_length: 6,
+            indices: [1, 1, 0, 0, 1, 0, 0, 0],
+            siblings: [field(2); MAX_MERKLE_TREE_DEPTH],
+            grid_size: DEFAULT_GRID_SIZE,
+        }
+        .encode();
+        for index in 0..TrapMerkleRoot::NAMES.len() {
+            let mut bytes = valid;
+            bytes[index * FIELD_BYTES..(index + 1) * FIELD_BYTES].copy_from_slice(&BN254_MODULUS);
+            assert_eq!(
+                TrapMerkleRoot::decode(&bytes),
+                Err(DecodeError::NotCanonical { index })
+            );
+        }
+
+        // Integers past u32 and bits other than 0/1
+        let mut bytes = PositionMovement {
+            move_x: 0,
+            move_y: 0,
+            is_hit: false,
+            grid_size: DEFAULT_GRID_SIZE,
+            grid_commitment: field(1),
+        }
+        .encode();
+        bytes[..FIELD_BYTES].copy_from_slice(&encode_u64(1 << 32));
+        assert_eq!(
+            PositionMovement::decode(&bytes),
+            Err(DecodeError::OutOfRange { index: 0 })
+        );
+        bytes[..FIELD_BYTES].copy_from_slice(&encode_u64(0));
+        bytes[2 * FIELD_BYTES..3 * FIELD_BYTES].copy_from_slice(&encode_u64(2));
+        assert_eq!(
+            PositionMovement::decode(&bytes),
+            Err(DecodeError::OutOfRange { index: 2 })
+        );
+
+        // Key chunks wider than the bytes they hold
+        let mut bytes = GridSetup {
+            grid_commitment: field(1),
+            trap_count: 10,
+            grid_size: DEFAULT_GRID_SIZE,
+            defender: [7; FIELD_BYTES],
+        }
+        .encode();
+        bytes[3 * FIELD_BYTES] = 1;
+        assert_eq!(
+            GridSetup::decode(&bytes),
+            Err(DecodeError::OutOfRange { index: 3 })
+        );
+        bytes[3 * FIELD_BYTES] = 0;
+        bytes[4 * FIELD_BYTES..].copy_from_slice(&encode_u64(0x100));
+        assert_eq!(
+            GridSetup::decode(&bytes),
+            Err(DecodeError::OutOfRange { index: 4 })
+        );
+    }
+}
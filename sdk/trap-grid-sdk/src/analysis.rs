@@ -0,0 +1,261 @@
+//! Replay analysis
+//!
+//! Aggregates finished games into per-cell hit rates, what each defender's
+//! revealed traps say about where they place them, and how efficiently each
+//! attacker finds traps. Only hits reveal traps, so placement tendencies are
+//! drawn from the traps attackers found, not from complete layouts.
+
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use stellar_xdr::curr::ScAddress;
+
+use crate::client::{fetch_game, fetch_moves};
+use crate::rpc::RpcClient;
+use crate::{ContractError, Game, Move, GRID_SIZE};
+
+/// A finished game: its final state and its moves in play order
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Replay {
+    pub session_id: u32,
+    pub game: Game,
+    pub moves: Vec<Move>,
+}
+
+impl Replay {
+    /// Read a finished session from the contract; it must still be within its TTL
+    pub fn fetch(rpc: &RpcClient, contract: &ScAddress, session_id: u32) -> Result<Self> {
+        let Some(game) = fetch_game(rpc, contract, session_id)? else {
+            return Err(ContractError::GameNotFound.into());
+        };
+        if !game.game_ended {
+            bail!("session {session_id} has not ended");
+        }
+        Ok(Self {
+            session_id,
+            game,
+            moves: fetch_moves(rpc, contract, session_id)?,
+        })
+    }
+
+    pub fn attacker_won(&self) -> bool {
+        self.game.winner.as_ref() == Some(&self.game.attacker)
+    }
+}
+
+/// How often one cell was played and hit
+#[derive(Clone, Debug, Serialize)]
+pub struct CellStats {
+    pub x: u32,
+    pub y: u32,
+    pub plays: u32,
+    pub hits: u32,
+    /// Hits over plays, 0 for a cell never played
+    pub hit_rate: f64,
+}
+
+/// Where one defender's revealed traps are
+#[derive(Clone, Debug, Serialize)]
+pub struct DefenderStats {
+    pub defender: String,
+    pub games: u32,
+    pub wins: u32,
+    pub revealed_traps: u32,
+    /// Shares of the revealed traps in the corners, on the rest of the
+    /// border and in the inner 6x6
+    pub corner_share: f64,
+    pub edge_share: f64,
+    pub inner_share: f64,
+}
+
+/// How quickly one attacker finds traps
+#[derive(Clone, Debug, Serialize)]
+pub struct AttackerStats {
+    pub attacker: String,
+    pub games: u32,
+    pub wins: u32,
+    pub moves: u32,
+    pub hits: u32,
+    pub hit_rate: f64,
+    /// Mean 1-based move number of the first hit, over games with a hit
+    pub mean_first_hit: Option<f64>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Analysis {
+    pub games: u32,
+    /// `x * GRID_SIZE + y`, as trap-grid indexes cells, over the games played
+    /// on the default grid
+    pub heatmap: Vec<CellStats>,
+    pub defenders: Vec<DefenderStats>,
+    pub attackers: Vec<AttackerStats>,
+}
+
+fn ratio(numerator: u32, denominator: u32) -> f64 {
+    if denominator == 0 {
+        0.0
+    } else {
+        f64::from(numerator) / f64::from(denominator)
+    }
+}
+
+/// Whether (x, y) is a corner, on the border, or neither of a `grid_size` grid
+fn region(grid_size: u32, x: u32, y: u32) -> usize {
+    let border = |v: u32| v == 0 || v == grid_size - 1;
+    match (border(x), border(y)) {
+        (true, true) => 0,
+        (true, false) | (false, true) => 1,
+        (false, false) => 2,
+    }
+}
+
+pub fn analyze(replays: &[Replay]) -> Analysis {
+    let mut heatmap: Vec<CellStats> = (0..GRID_SIZE * GRID_SIZE)
+        .map(|i| CellStats {
+            x: i / GRID_SIZE,
+            y: i % GRID_SIZE,
+            plays: 0,
+            hits: 0,
+            hit_rate: 0.0,
+        })
+        .collect();
+    // defender -> (games, wins, traps per region)
+    let mut defenders: BTreeMap<&str, (u32, u32, [u32; 3])> = BTreeMap::new();
+    // attacker -> (games, wins, moves, hits, first-hit sum, games with a hit)
+    let mut attackers: BTreeMap<&str, (u32, u32, u32, u32, u32, u32)> = BTreeMap::new();
+
+    for replay in replays {
+        let won = replay.attacker_won();
+        let defender = defenders.entry(&replay.game.defender).or_default();
+        defender.0 += 1;
+        defender.1 += u32::from(!won);
+        let attacker = attackers.entry(&replay.game.attacker).or_default();
+        attacker.0 += 1;
+        attacker.1 += u32::from(won);
+
+        let grid_size = replay.game.grid_size;
+        for m in &replay.moves {
+            if m.x >= grid_size || m.y >= grid_size {
+                continue;
+            }
+            let cell =
+                (grid_size == GRID_SIZE).then(|| &mut heatmap[(m.x * GRID_SIZE + m.y) as usize]);
+            attacker.2 += 1;
+            if m.is_hit {
+                attacker.3 += 1;
+                defender.2[region(grid_size, m.x, m.y)] += 1;
+            }
+            if let Some(cell) = cell {
+                cell.plays += 1;
+                cell.hits += u32::from(m.is_hit);
+            }
+        }
+        if let Some(first) = replay.moves.iter().position(|m| m.is_hit) {
+            attacker.4 += first as u32 + 1;
+            attacker.5 += 1;
+        }
+    }
+
+    for cell in &mut heatmap {
+        cell.hit_rate = ratio(cell.hits, cell.plays);
+    }
+    Analysis {
+        games: replays.len() as u32,
+        heatmap,
+        defenders: defenders
+            .into_iter()
+            .map(|(defender, (games, wins, regions))| {
+                let revealed = regions.iter().sum();
+                DefenderStats {
+                    defender: defender.to_string(),
+                    games,
+                    wins,
+                    revealed_traps: revealed,
+                    corner_share: ratio(regions[0], revealed),
+                    edge_share: ratio(regions[1], revealed),
+                    inner_share: ratio(regions[2], revealed),
+                }
+            })
+            .collect(),
+        attackers: attackers
+            .into_iter()
+            .map(
+                |(attacker, (games, wins, moves, hits, first_hits, with_hit))| AttackerStats {
+                    attacker: attacker.to_string(),
+                    games,
+                    wins,
+                    moves,
+                    hits,
+                    hit_rate: ratio(hits, moves),
+                    mean_first_hit: (with_hit > 0).then(|| ratio(first_hits, with_hit)),
+                },
+            )
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::GAME_SCHEMA_VERSION;
+
+    fn replay(session_id: u32, attacker: &str, moves: &[(u32, u32, bool)]) -> Replay {
+        let hits = moves.iter().filter(|m| m.2).count() as u32;
+        let won = hits > moves.len() as u32 / 2;
+        Replay {
+            session_id,
+            game: Game {
+                defender: "GDEF".into(),
+                attacker: attacker.into(),
+                defender_points: 100,
+                attacker_points: 100,
+                moves_made: moves.len() as u32,
+                hits,
+                misses: moves.len() as u32 - hits,
+                game_started: true,
+                game_ended: true,
+                winner: Some(if won { attacker.into() } else { "GDEF".into() }),
+                schema_version: GAME_SCHEMA_VERSION,
+                last_active_ledger: 0,
+                grid_size: GRID_SIZE,
+                trap_count: 10,
+            },
+            moves: moves
+                .iter()
+                .map(|&(x, y, is_hit)| Move {
+                    x,
+                    y,
+                    is_hit,
+                    verified: true,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_analyze() {
+        let analysis = analyze(&[
+            replay(1, "GA", &[(0, 0, true), (3, 3, false), (7, 7, false)]),
+            replay(2, "GB", &[(3, 3, false), (0, 0, true), (0, 4, true)]),
+        ]);
+        assert_eq!(analysis.games, 2);
+        assert_eq!(analysis.heatmap[0].hit_rate, 1.0);
+        let center = &analysis.heatmap[(3 * GRID_SIZE + 3) as usize];
+        assert_eq!((center.plays, center.hits), (2, 0));
+        let cell = &analysis.heatmap[4];
+        assert_eq!((cell.x, cell.y, cell.hits), (0, 4, 1));
+
+        let defender = &analysis.defenders[0];
+        assert_eq!(
+            (defender.games, defender.wins, defender.revealed_traps),
+            (2, 1, 3)
+        );
+        assert_eq!(defender.corner_share, 2.0 / 3.0);
+        assert_eq!(defender.inner_share, 0.0);
+
+        let b = &analysis.attackers[1];
+        assert_eq!((b.attacker.as_str(), b.wins, b.hits), ("GB", 1, 2));
+        assert_eq!(b.mean_first_hit, Some(2.0));
+    }
+}
@@ -0,0 +1,254 @@
+//! Off-chain play through a channel
+//!
+//! Once both players call `open_channel`, moves are exchanged off-chain: the
+//! attacker picks a cell, the defender answers with a proof the attacker
+//! checks, and both sign the new [`ChannelState`] with their channel keys.
+//! The contract only sees the latest signed state and, at settlement, the
+//! [`ChannelMove`]s its transcript hashes.
+//!
+//! ```ignore
+//! let moves = vec![ChannelMove::new(3, 3, true, &proof)];
+//! let state = ChannelState::after(&moves, false);
+//! let message = channel::message(&contract, session_id, &state)?;
+//! let signature = channel_key.sign(&message);
+//! ```
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use stellar_xdr::curr::{Limits, ScBytes, ScVal, ScVec, WriteXdr};
+
+use crate::scval::{address, as_bool, as_map, as_u32, map, map_field};
+
+/// A state of a channel game, as both players sign it
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ChannelState {
+    /// Moves played in the channel so far
+    pub move_index: u32,
+    pub hits: u32,
+    pub misses: u32,
+    /// [`transcript_hash`] of the moves played
+    pub transcript: [u8; 32],
+    /// Both players agree the game ends here, so it settles without a
+    /// challenge window
+    pub closing: bool,
+}
+
+/// A move played in a channel
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ChannelMove {
+    pub x: u32,
+    pub y: u32,
+    pub is_hit: bool,
+    /// SHA-256 of the defender's proof of the answer
+    pub proof_hash: [u8; 32],
+}
+
+/// A game's open channel (`get_channel`)
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Channel {
+    /// Ed25519 keys the players sign states with
+    pub defender_key: [u8; 32],
+    pub attacker_key: [u8; 32],
+    /// Move 0 with an empty transcript until a state is submitted
+    pub state: ChannelState,
+    /// Last ledger a newer state can replace `state` in; 0 until a state is
+    /// submitted
+    pub challenge_until: u32,
+}
+
+impl ChannelMove {
+    /// A move answered with `proof`, which the attacker keeps to show it
+    pub fn new(x: u32, y: u32, is_hit: bool, proof: &[u8]) -> Self {
+        Self {
+            x,
+            y,
+            is_hit,
+            proof_hash: Sha256::digest(proof).into(),
+        }
+    }
+}
+
+impl ChannelState {
+    /// The state after `moves`, in play order
+    pub fn after(moves: &[ChannelMove], closing: bool) -> Self {
+        let hits = moves.iter().filter(|m| m.is_hit).count() as u32;
+        Self {
+            move_index: moves.len() as u32,
+            hits,
+            misses: moves.len() as u32 - hits,
+            transcript: transcript_hash(moves),
+            closing,
+        }
+    }
+}
+
+/// Hash chain of a channel's moves, as `settle_channel` recomputes it: from
+/// 32 zero bytes, each move hashes the previous hash, its x, y and is_hit as
+/// a byte each, and its proof hash
+pub fn transcript_hash(moves: &[ChannelMove]) -> [u8; 32] {
+    moves.iter().fold([0; 32], |hash, m| {
+        let mut hasher = Sha256::new();
+        hasher.update(hash);
+        hasher.update([m.x as u8, m.y as u8, m.is_hit as u8]);
+        hasher.update(m.proof_hash);
+        hasher.finalize().into()
+    })
+}
+
+/// What both channel keys sign: the XDR of `(contract, session_id, state)`
+pub fn message(contract: &str, session_id: u32, state: &ChannelState) -> Result<Vec<u8>> {
+    let items = vec![address(contract)?, session_id.into(), state.try_into()?];
+    Ok(ScVal::Vec(Some(ScVec(items.try_into()?))).to_xdr(Limits::none())?)
+}
+
+fn bytes32(val: &ScVal, name: &str) -> Result<[u8; 32]> {
+    match val {
+        ScVal::Bytes(bytes) => Ok(bytes.as_slice().try_into()?),
+        other => bail!("expected {name} bytes, found {}", other.name()),
+    }
+}
+
+fn bytes_val(bytes: &[u8]) -> Result<ScVal> {
+    Ok(ScVal::Bytes(ScBytes(bytes.to_vec().try_into()?)))
+}
+
+impl TryFrom<&ScVal> for ChannelState {
+    type Error = anyhow::Error;
+
+    fn try_from(val: &ScVal) -> Result<Self> {
+        let map = as_map(val, "ChannelState")?;
+        Ok(ChannelState {
+            move_index: as_u32(map_field(map, "move_index")?)?,
+            hits: as_u32(map_field(map, "hits")?)?,
+            misses: as_u32(map_field(map, "misses")?)?,
+            transcript: bytes32(map_field(map, "transcript")?, "transcript")?,
+            closing: as_bool(map_field(map, "closing")?)?,
+        })
+    }
+}
+
+impl TryFrom<&ChannelState> for ScVal {
+    type Error = anyhow::Error;
+
+    fn try_from(state: &ChannelState) -> Result<Self> {
+        map(vec![
+            ("move_index", state.move_index.into()),
+            ("hits", state.hits.into()),
+            ("misses", state.misses.into()),
+            ("transcript", bytes_val(&state.transcript)?),
+            ("closing", state.closing.into()),
+        ])
+    }
+}
+
+impl TryFrom<&ScVal> for ChannelMove {
+    type Error = anyhow::Error;
+
+    fn try_from(val: &ScVal) -> Result<Self> {
+        let map = as_map(val, "ChannelMove")?;
+        Ok(ChannelMove {
+            x: as_u32(map_field(map, "x")?)?,
+            y: as_u32(map_field(map, "y")?)?,
+            is_hit: as_bool(map_field(map, "is_hit")?)?,
+            proof_hash: bytes32(map_field(map, "proof_hash")?, "proof_hash")?,
+        })
+    }
+}
+
+impl TryFrom<&ChannelMove> for ScVal {
+    type Error = anyhow::Error;
+
+    fn try_from(m: &ChannelMove) -> Result<Self> {
+        map(vec![
+            ("x", m.x.into()),
+            ("y", m.y.into()),
+            ("is_hit", m.is_hit.into()),
+            ("proof_hash", bytes_val(&m.proof_hash)?),
+        ])
+    }
+}
+
+impl TryFrom<&ScVal> for Channel {
+    type Error = anyhow::Error;
+
+    fn try_from(val: &ScVal) -> Result<Self> {
+        let map = as_map(val, "Channel")?;
+        Ok(Channel {
+            defender_key: bytes32(map_field(map, "defender_key")?, "defender_key")?,
+            attacker_key: bytes32(map_field(map, "attacker_key")?, "attacker_key")?,
+            state: ChannelState::try_from(map_field(map, "state")?)?,
+            challenge_until: as_u32(map_field(map, "challenge_until")?)?,
+        })
+    }
+}
+
+impl TryFrom<&Channel> for ScVal {
+    type Error = anyhow::Error;
+
+    fn try_from(channel: &Channel) -> Result<Self> {
+        map(vec![
+            ("defender_key", bytes_val(&channel.defender_key)?),
+            ("attacker_key", bytes_val(&channel.attacker_key)?),
+            ("state", (&channel.state).try_into()?),
+            ("challenge_until", channel.challenge_until.into()),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const CONTRACT: &str = "CABAEAQCAIBAEAQCAIBAEAQCAIBAEAQCAIBAEAQCAIBAEAQCAIBAFNSZ";
+
+    /// [`message`] of session 7 after [`moves`]; known answer shared with the
+    /// contract's tests
+    const MESSAGE: &str = "0000001000000001000000030000001200000001020202020202020202020202020202020202020202020202020202020202020200000003000000070000001100000001000000050000000f00000007636c6f73696e670000000000000000000000000f000000046869747300000003000000010000000f000000066d6973736573000000000003000000010000000f0000000a6d6f76655f696e646578000000000003000000020000000f0000000a7472616e73637269707400000000000d000000207f7378de2b28cd3717b44292ad3d7168339ed4f7a7dc5045b724a9c1b1013c09";
+
+    /// The first moves of the contract's channel tests
+    fn moves() -> Vec<ChannelMove> {
+        (0..2u8)
+            .map(|i| ChannelMove {
+                x: i as u32,
+                y: 0,
+                is_hit: i == 0,
+                proof_hash: [i + 1; 32],
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_transcript_hash() {
+        // Known answer shared with the contract's tests
+        assert_eq!(
+            hex::encode(transcript_hash(&moves())),
+            "7f7378de2b28cd3717b44292ad3d7168339ed4f7a7dc5045b724a9c1b1013c09"
+        );
+        assert_eq!(transcript_hash(&[]), [0; 32]);
+    }
+
+    #[test]
+    fn test_channel_round_trip() {
+        let channel = Channel {
+            defender_key: [1; 32],
+            attacker_key: [2; 32],
+            state: ChannelState::after(&moves(), true),
+            challenge_until: 17_400,
+        };
+        let val = ScVal::try_from(&channel).unwrap();
+        assert_eq!(Channel::try_from(&val).unwrap(), channel);
+        let m = &moves()[1];
+        assert_eq!(
+            ChannelMove::try_from(&ScVal::try_from(m).unwrap()).unwrap(),
+            *m
+        );
+    }
+
+    #[test]
+    fn test_message() {
+        let state = ChannelState::after(&moves(), false);
+        let message = message(CONTRACT, 7, &state).unwrap();
+        assert_eq!(hex::encode(message), MESSAGE);
+    }
+}
@@ -0,0 +1,780 @@
+//! High-level client for a deployed trap-grid contract
+//!
+//! Every call is simulated for its footprint and fee, authorization entries
+//! are signed by the player they name, and contract failures surface as a
+//! [`ContractError`] in the error chain (`err.downcast_ref::<ContractError>()`).
+
+use anyhow::{anyhow, bail, Context, Result};
+use sha2::{Digest, Sha256};
+use stellar_xdr::curr::{ContractDataDurability, ScAddress, ScVal};
+use trap_grid_encoding::{INTERFACE, INTERFACE_VERSION};
+
+use crate::channel::{Channel, ChannelMove, ChannelState};
+use crate::retry::RetryPolicy;
+use crate::rpc::RpcClient;
+use crate::scval::address;
+use crate::tx::{self, AuthRequest, Submitter};
+use crate::{
+    ContractError, DataKey, FullState, Game, GameConfig, GridSetup, Move, Optimistic, PendingMove,
+    Rules, Sponsorship, TransactionSigner,
+};
+
+/// A move together with the defender's proof, ready for `make_move`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProvenMove {
+    pub session_id: u32,
+    pub x: u32,
+    pub y: u32,
+    pub is_hit: bool,
+    pub proof: Vec<u8>,
+    pub public_inputs: Vec<u8>,
+}
+
+impl ProvenMove {
+    /// `make_move` arguments
+    pub fn args(&self) -> Result<Vec<ScVal>> {
+        Ok(vec![
+            self.session_id.into(),
+            self.x.into(),
+            self.y.into(),
+            self.is_hit.into(),
+            tx::bytes_arg(&self.proof)?,
+            tx::bytes_arg(&self.public_inputs)?,
+        ])
+    }
+}
+
+/// Fetch the state of a session, `None` if it does not exist (or has expired)
+pub fn fetch_game(rpc: &RpcClient, contract: &ScAddress, session_id: u32) -> Result<Option<Game>> {
+    let key = ScVal::try_from(DataKey::Game(session_id))?;
+    rpc.get_contract_data(contract, key, ContractDataDurability::Temporary)?
+        .as_ref()
+        .map(Game::try_from)
+        .transpose()
+}
+
+/// Fetch the recorded moves of a session (empty if the session does not exist)
+pub fn fetch_moves(rpc: &RpcClient, contract: &ScAddress, session_id: u32) -> Result<Vec<Move>> {
+    let key = ScVal::try_from(DataKey::Moves(session_id))?;
+    match rpc.get_contract_data(contract, key, ContractDataDurability::Temporary)? {
+        Some(ScVal::Vec(Some(moves))) => moves.iter().map(Move::try_from).collect(),
+        Some(other) => bail!("expected Vec<Move>, found {}", other.name()),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Fetch the move waiting for the defender's answer, if any
+pub fn fetch_pending_move(
+    rpc: &RpcClient,
+    contract: &ScAddress,
+    session_id: u32,
+) -> Result<Option<PendingMove>> {
+    let key = ScVal::try_from(DataKey::PendingMove(session_id))?;
+    rpc.get_contract_data(contract, key, ContractDataDurability::Temporary)?
+        .as_ref()
+        .map(PendingMove::try_from)
+        .transpose()
+}
+
+/// Interface version and hash a trap-grid contract reports
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Interface {
+    pub version: u32,
+    pub hash: [u8; 32],
+}
+
+impl Interface {
+    /// The interface this SDK encodes calls and public inputs for
+    pub fn expected() -> Self {
+        Self {
+            version: INTERFACE_VERSION,
+            hash: Sha256::digest(INTERFACE.as_bytes()).into(),
+        }
+    }
+
+    /// Compare the `deployed` interface (`None` if the contract predates
+    /// `version()`) with this one: a different version is an error, anything
+    /// else that cannot be confirmed is returned as a warning
+    pub fn check(&self, deployed: Option<&Interface>) -> Result<Option<String>> {
+        let Some(deployed) = deployed else {
+            return Ok(Some(format!(
+                "contract has no version(); cannot confirm it speaks interface v{}",
+                self.version
+            )));
+        };
+        if deployed.version != self.version {
+            bail!(
+                "contract speaks interface v{}, this client v{}; use a client built for v{}",
+                deployed.version,
+                self.version,
+                deployed.version
+            );
+        }
+        if deployed.hash != self.hash {
+            return Ok(Some(format!(
+                "contract interface hash {} differs from the client's {}; \
+                 calls or public inputs may be misencoded",
+                hex::encode(deployed.hash),
+                hex::encode(self.hash)
+            )));
+        }
+        Ok(None)
+    }
+}
+
+/// A simulation failed because the contract has no such function
+fn missing_function(err: &anyhow::Error) -> bool {
+    format!("{err:#}").contains("non-existent contract function")
+}
+
+/// Attach the contract error a failed call of `contract` ended with, if any
+///
+/// The diagnostic events name it precisely; without them the host error in
+/// the simulation message does.
+fn typed_error(contract: &ScAddress, err: anyhow::Error) -> anyhow::Error {
+    let from_events = match contract {
+        ScAddress::Contract(id) => ContractError::from_events(tx::diagnostic_events(&err), id),
+        _ => None,
+    };
+    match from_events.or_else(|| ContractError::from_diagnostic(&format!("{err:#}"))) {
+        Some(code) => err.context(code),
+        None => err,
+    }
+}
+
+fn start_game_args(
+    defender: &str,
+    attacker: &str,
+    session_id: u32,
+    (defender_points, attacker_points): (i128, i128),
+    setup: &GridSetup,
+) -> Result<Vec<ScVal>> {
+    let address = |address: &str| -> Result<ScVal> {
+        Ok(ScVal::Address(address.parse().map_err(|e| {
+            anyhow!("invalid player address {address}: {e}")
+        })?))
+    };
+    Ok(vec![
+        session_id.into(),
+        address(defender)?,
+        address(attacker)?,
+        defender_points.into(),
+        attacker_points.into(),
+        ScVal::try_from(setup)?,
+    ])
+}
+
+pub struct GameClient<'a> {
+    rpc: &'a RpcClient,
+    submitter: Submitter<'a>,
+    contract: ScAddress,
+}
+
+impl<'a> GameClient<'a> {
+    /// Client for the trap-grid contract `contract` on the RPC server's network
+    pub fn new(rpc: &'a RpcClient, contract: ScAddress) -> Result<Self> {
+        Ok(Self {
+            rpc,
+            submitter: Submitter::new(rpc)?,
+            contract,
+        })
+    }
+
+    /// Retry rejected transactions according to `policy`
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.submitter = self.submitter.with_retry_policy(policy);
+        self
+    }
+
+    pub fn contract(&self) -> &ScAddress {
+        &self.contract
+    }
+
+    fn invoke(
+        &self,
+        source: &dyn TransactionSigner,
+        cosigners: &[&dyn TransactionSigner],
+        function: &str,
+        args: Vec<ScVal>,
+    ) -> Result<ScVal> {
+        self.submitter
+            .invoke_authorized(source, cosigners, &self.contract, function, args)
+            .map_err(|err| typed_error(&self.contract, err))
+    }
+
+    /// Start a session between `defender` and `attacker`
+    ///
+    /// The defender authorizes its stake and grid commitment, and `setup`
+    /// proves its grid (see [`crate::Prover::prove_setup`]); `source` pays the
+    /// fee and may be the defender itself or any other account (a relayer, or
+    /// the attacker).
+    pub fn start_game(
+        &self,
+        source: &dyn TransactionSigner,
+        defender: &dyn TransactionSigner,
+        attacker: &str,
+        session_id: u32,
+        points: (i128, i128),
+        setup: &GridSetup,
+    ) -> Result<()> {
+        let args = start_game_args(
+            &tx::account_address(defender).to_string(),
+            attacker,
+            session_id,
+            points,
+            setup,
+        )?;
+        self.invoke(source, &[defender], "start_game", args)?;
+        Ok(())
+    }
+
+    /// Prepare `start_game` for players who sign their authorizations
+    /// elsewhere, e.g. through a matchmaking server; send it with
+    /// [`GameClient::submit_prepared`] once [`AuthRequest::required`] is empty
+    pub fn prepare_start_game(
+        &self,
+        source: &dyn TransactionSigner,
+        defender: &str,
+        attacker: &str,
+        session_id: u32,
+        points: (i128, i128),
+        setup: &GridSetup,
+    ) -> Result<AuthRequest> {
+        let args = start_game_args(defender, attacker, session_id, points, setup)?;
+        self.submitter
+            .prepare_authorized(source, &self.contract, "start_game", args)
+            .map_err(|err| typed_error(&self.contract, err))
+    }
+
+    /// Prepare `start_sponsored_game`: `start_game` with sponsors staking
+    /// `sponsorships` on the players. Every sponsor must sign, usually
+    /// elsewhere, so the call is prepared like [`GameClient::prepare_start_game`]
+    pub fn prepare_start_sponsored_game(
+        &self,
+        source: &dyn TransactionSigner,
+        defender: &str,
+        attacker: &str,
+        session_id: u32,
+        points: (i128, i128),
+        setup: &GridSetup,
+        sponsorships: &[Sponsorship],
+    ) -> Result<AuthRequest> {
+        let mut args = start_game_args(defender, attacker, session_id, points, setup)?;
+        let sponsorships = sponsorships
+            .iter()
+            .map(ScVal::try_from)
+            .collect::<Result<Vec<_>>>()?;
+        args.push(ScVal::Vec(Some(sponsorships.try_into()?)));
+        self.submitter
+            .prepare_authorized(source, &self.contract, "start_sponsored_game", args)
+            .map_err(|err| typed_error(&self.contract, err))
+    }
+
+    /// Sign and send a prepared invocation
+    pub fn submit_prepared(
+        &self,
+        source: &dyn TransactionSigner,
+        request: AuthRequest,
+    ) -> Result<ScVal> {
+        self.submitter
+            .send_prepared(source, request)
+            .map_err(|err| typed_error(&self.contract, err))
+    }
+
+    /// Prepare any call for players who sign their authorizations elsewhere,
+    /// e.g. a player's move sent through a relayer that pays its fee
+    pub fn prepare_call(
+        &self,
+        source: &dyn TransactionSigner,
+        function: &str,
+        args: Vec<ScVal>,
+    ) -> Result<AuthRequest> {
+        self.submitter
+            .prepare_authorized(source, &self.contract, function, args)
+            .map_err(|err| typed_error(&self.contract, err))
+    }
+
+    /// Sign and send a prepared invocation wrapped in a fee bump that
+    /// `fee_source` pays for; `source` only provides the sequence number
+    pub fn submit_sponsored(
+        &self,
+        source: &dyn TransactionSigner,
+        fee_source: &dyn TransactionSigner,
+        request: AuthRequest,
+    ) -> Result<ScVal> {
+        self.submitter
+            .send_prepared_sponsored(source, fee_source, request)
+            .map_err(|err| typed_error(&self.contract, err))
+    }
+
+    /// Sign a player's authorization of `function` on this contract, given
+    /// the preimage a relayer published (see [`AuthRequest::preimages`]);
+    /// anything else is refused
+    pub fn sign_relayed(
+        &self,
+        signer: &dyn TransactionSigner,
+        preimage: &[u8],
+        function: &str,
+    ) -> Result<[u8; 64]> {
+        self.submitter
+            .sign_preimage(signer, preimage, &self.contract, function)
+    }
+
+    /// Pick the next cell as the attacker, for the defender to answer
+    ///
+    /// The pending move is published as a `move_submitted` event so a defender
+    /// (or `defender-bot`) can prove and answer it; `make_move` answers no
+    /// other cell.
+    pub fn submit_move(
+        &self,
+        attacker: &dyn TransactionSigner,
+        session_id: u32,
+        x: u32,
+        y: u32,
+    ) -> Result<()> {
+        self.invoke(
+            attacker,
+            &[],
+            "submit_move",
+            vec![session_id.into(), x.into(), y.into()],
+        )?;
+        Ok(())
+    }
+
+    /// [`submit_move`](Self::submit_move) with a short note, such as a taunt
+    /// or an emote id, published in the `move_submitted` event and not stored
+    ///
+    /// The contract rejects notes over 32 bytes with `NoteTooLong`.
+    pub fn submit_move_with_note(
+        &self,
+        attacker: &dyn TransactionSigner,
+        session_id: u32,
+        x: u32,
+        y: u32,
+        note: &[u8],
+    ) -> Result<()> {
+        self.invoke(
+            attacker,
+            &[],
+            "submit_move_with_note",
+            vec![session_id.into(), x.into(), y.into(), tx::bytes_arg(note)?],
+        )?;
+        Ok(())
+    }
+
+    /// Pre-flight a move: the error `make_move` would fail with for this cell,
+    /// checked by simulation without a proof or a transaction
+    pub fn simulate_move(&self, session_id: u32, x: u32, y: u32) -> Result<()> {
+        self.submitter
+            .read(
+                &self.contract,
+                "simulate_move",
+                vec![session_id.into(), x.into(), y.into()],
+            )
+            .map_err(|err| typed_error(&self.contract, err))?;
+        Ok(())
+    }
+
+    /// Record an attacker's move together with the defender's proof of the answer
+    ///
+    /// The contract takes the answer and its proof in a single `make_move`
+    /// call, which the game's defender must authorize. It only accepts the
+    /// cell the attacker submitted with [`submit_move`](Self::submit_move).
+    pub fn respond_move(
+        &self,
+        defender: &dyn TransactionSigner,
+        proven: &ProvenMove,
+    ) -> Result<Move> {
+        let verified = self.invoke(defender, &[], "make_move", proven.args()?)?;
+        Ok(Move {
+            x: proven.x,
+            y: proven.y,
+            is_hit: proven.is_hit,
+            verified: matches!(verified, ScVal::Bool(true)),
+        })
+    }
+
+    /// End a session early; the winner is decided from the moves so far
+    ///
+    /// A live game needs both players' authorization: pass the ones other
+    /// than `source` as `players`. An expired or forfeited game, or one with
+    /// every cell played, takes none.
+    pub fn end_game(
+        &self,
+        source: &dyn TransactionSigner,
+        players: &[&dyn TransactionSigner],
+        session_id: u32,
+    ) -> Result<()> {
+        self.invoke(source, players, "end_game", vec![session_id.into()])?;
+        Ok(())
+    }
+
+    /// Vote, as a player or the admin, to abort a session whose verifier no
+    /// longer checks proofs; returns whether this vote aborted it
+    ///
+    /// The session aborts once both players, or a player and the admin,
+    /// voted, returning every sponsor's stake.
+    pub fn abort_due_to_verifier(
+        &self,
+        voter: &dyn TransactionSigner,
+        session_id: u32,
+    ) -> Result<bool> {
+        let aborted = self.invoke(
+            voter,
+            &[],
+            "abort_due_to_verifier",
+            vec![
+                session_id.into(),
+                ScVal::Address(tx::account_address(voter)),
+            ],
+        )?;
+        Ok(matches!(aborted, ScVal::Bool(true)))
+    }
+
+    /// Open a channel to play a session off-chain, before its first move;
+    /// both players sign. Each names the ed25519 key it signs channel states
+    /// with (see [`crate::channel`]).
+    pub fn open_channel(
+        &self,
+        defender: &dyn TransactionSigner,
+        attacker: &dyn TransactionSigner,
+        session_id: u32,
+        defender_key: &[u8; 32],
+        attacker_key: &[u8; 32],
+    ) -> Result<()> {
+        self.invoke(
+            defender,
+            &[attacker],
+            "open_channel",
+            vec![
+                session_id.into(),
+                tx::bytes_arg(defender_key)?,
+                tx::bytes_arg(attacker_key)?,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Submit a channel state both players signed; returns the last ledger
+    /// the other player can replace it with a newer one in
+    ///
+    /// Either player, or anyone holding the signatures, can submit, so a
+    /// player whose opponent stops signing exits the channel alone.
+    pub fn submit_channel_state(
+        &self,
+        source: &dyn TransactionSigner,
+        session_id: u32,
+        state: &ChannelState,
+        signatures: (&[u8; 64], &[u8; 64]),
+    ) -> Result<u32> {
+        let challenge_until = self.invoke(
+            source,
+            &[],
+            "submit_channel_state",
+            vec![
+                session_id.into(),
+                state.try_into()?,
+                tx::bytes_arg(signatures.0)?,
+                tx::bytes_arg(signatures.1)?,
+            ],
+        )?;
+        match challenge_until {
+            ScVal::U32(ledger) => Ok(ledger),
+            other => bail!("expected a u32 ledger, found {}", other.name()),
+        }
+    }
+
+    /// Settle a channel session by its submitted state, with the moves its
+    /// transcript hashes in play order
+    pub fn settle_channel(
+        &self,
+        source: &dyn TransactionSigner,
+        session_id: u32,
+        transcript: &[ChannelMove],
+    ) -> Result<()> {
+        let moves = transcript
+            .iter()
+            .map(ScVal::try_from)
+            .collect::<Result<Vec<_>>>()?;
+        self.invoke(
+            source,
+            &[],
+            "settle_channel",
+            vec![session_id.into(), ScVal::Vec(Some(moves.try_into()?))],
+        )?;
+        Ok(())
+    }
+
+    /// A session's open channel, if any
+    pub fn get_channel(&self, session_id: u32) -> Result<Option<Channel>> {
+        let key = ScVal::try_from(DataKey::Channel(session_id))?;
+        self.rpc
+            .get_contract_data(&self.contract, key, ContractDataDurability::Temporary)?
+            .map(|channel| Channel::try_from(&channel))
+            .transpose()
+    }
+
+    /// Put a session in optimistic mode before its first move; both players
+    /// sign. The defender then answers submitted moves with
+    /// [`GameClient::claim_move`] instead of proving each one, posting
+    /// `bond` of `token` with each claim.
+    pub fn enable_optimistic(
+        &self,
+        defender: &dyn TransactionSigner,
+        attacker: &dyn TransactionSigner,
+        session_id: u32,
+        token: &str,
+        bond: i128,
+    ) -> Result<()> {
+        self.invoke(
+            defender,
+            &[attacker],
+            "enable_optimistic",
+            vec![session_id.into(), address(token)?, bond.into()],
+        )?;
+        Ok(())
+    }
+
+    /// Answer the submitted move of an optimistic session without a proof;
+    /// keep the proof at hand in case the attacker challenges the claim
+    pub fn claim_move(
+        &self,
+        defender: &dyn TransactionSigner,
+        session_id: u32,
+        is_hit: bool,
+    ) -> Result<()> {
+        self.invoke(
+            defender,
+            &[],
+            "claim_move",
+            vec![session_id.into(), is_hit.into()],
+        )?;
+        Ok(())
+    }
+
+    /// Demand a proof of the claim of move `move_index`; returns the last
+    /// ledger the defender can prove it in before forfeiting the session
+    pub fn challenge_claim(
+        &self,
+        attacker: &dyn TransactionSigner,
+        session_id: u32,
+        move_index: u32,
+    ) -> Result<u32> {
+        match self.invoke(
+            attacker,
+            &[],
+            "challenge_claim",
+            vec![session_id.into(), move_index.into()],
+        )? {
+            ScVal::U32(ledger) => Ok(ledger),
+            other => bail!("expected a u32 ledger, found {}", other.name()),
+        }
+    }
+
+    /// Prove the challenged claim with the proof of its move
+    pub fn prove_claim(&self, source: &dyn TransactionSigner, proven: &ProvenMove) -> Result<()> {
+        self.invoke(
+            source,
+            &[],
+            "prove_claim",
+            vec![
+                proven.session_id.into(),
+                tx::bytes_arg(&proven.proof)?,
+                tx::bytes_arg(&proven.public_inputs)?,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// A session's claims, if it is in optimistic mode
+    pub fn get_optimistic(&self, session_id: u32) -> Result<Option<Optimistic>> {
+        let key = ScVal::try_from(DataKey::Optimistic(session_id))?;
+        self.rpc
+            .get_contract_data(&self.contract, key, ContractDataDurability::Temporary)?
+            .map(|optimistic| Optimistic::try_from(&optimistic))
+            .transpose()
+    }
+
+    /// Current state of a session, [`ContractError::GameNotFound`] if there is none
+    pub fn get_game(&self, session_id: u32) -> Result<Game> {
+        fetch_game(self.rpc, &self.contract, session_id)?
+            .ok_or_else(|| anyhow!(ContractError::GameNotFound))
+    }
+
+    /// Moves recorded so far in a session
+    pub fn get_moves(&self, session_id: u32) -> Result<Vec<Move>> {
+        fetch_moves(self.rpc, &self.contract, session_id)
+    }
+
+    /// The move waiting for the defender's answer, if any
+    pub fn get_pending_move(&self, session_id: u32) -> Result<Option<PendingMove>> {
+        fetch_pending_move(self.rpc, &self.contract, session_id)
+    }
+
+    /// A session with the deployment configuration, its pending move,
+    /// deadlines and last few moves, in one simulated read
+    pub fn get_full_state(&self, session_id: u32) -> Result<FullState> {
+        let state = self
+            .submitter
+            .read(&self.contract, "get_full_state", vec![session_id.into()])
+            .map_err(|err| typed_error(&self.contract, err))?;
+        FullState::try_from(&state)
+    }
+
+    /// The rules a session is played under: its grid size, the trap count,
+    /// win and expiry policies, deadlines, stakes, fees and verifiers
+    pub fn get_rules(&self, session_id: u32) -> Result<Rules> {
+        let rules = self
+            .submitter
+            .read(&self.contract, "get_rules", vec![session_id.into()])
+            .map_err(|err| typed_error(&self.contract, err))?;
+        Rules::try_from(&rules)
+    }
+
+    /// Stakes escrowed for a session, empty unless it was started sponsored
+    /// and has not been settled
+    pub fn get_sponsorships(&self, session_id: u32) -> Result<Vec<Sponsorship>> {
+        let key = ScVal::try_from(DataKey::Sponsorships(session_id))?;
+        match self
+            .rpc
+            .get_contract_data(&self.contract, key, ContractDataDurability::Temporary)?
+        {
+            Some(ScVal::Vec(Some(sponsorships))) => {
+                sponsorships.iter().map(Sponsorship::try_from).collect()
+            }
+            Some(other) => bail!("expected Vec<Sponsorship>, found {}", other.name()),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Interface the contract reports, `None` if it predates `version()`
+    pub fn interface(&self) -> Result<Option<Interface>> {
+        let version = match self.submitter.read(&self.contract, "version", vec![]) {
+            Ok(ScVal::U32(version)) => version,
+            Ok(other) => bail!("expected a u32 version, found {}", other.name()),
+            Err(err) if missing_function(&err) => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        let hash = match self
+            .submitter
+            .read(&self.contract, "interface_hash", vec![])?
+        {
+            ScVal::Bytes(hash) => hash
+                .as_slice()
+                .try_into()
+                .context("interface hash is not 32 bytes")?,
+            other => bail!("expected interface hash bytes, found {}", other.name()),
+        };
+        Ok(Some(Interface { version, hash }))
+    }
+
+    /// Check the contract speaks the SDK's interface; see [`Interface::check`]
+    pub fn check_interface(&self) -> Result<Option<String>> {
+        Interface::expected().check(self.interface()?.as_ref())
+    }
+
+    /// Admin, Game Hub and verifier the contract was constructed with
+    pub fn get_config(&self) -> Result<GameConfig> {
+        let instance = self
+            .rpc
+            .get_contract_data(
+                &self.contract,
+                ScVal::LedgerKeyContractInstance,
+                ContractDataDurability::Persistent,
+            )?
+            .with_context(|| format!("contract {} not found", self.contract))?;
+        match instance {
+            ScVal::ContractInstance(instance) => GameConfig::from_instance_storage(
+                instance
+                    .storage
+                    .as_ref()
+                    .context("contract instance has no storage")?,
+            ),
+            other => bail!("expected a contract instance, found {}", other.name()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tx::SimulationFailed;
+    use stellar_xdr::curr::{
+        ContractEvent, ContractEventBody, ContractEventType, ContractEventV0, ContractId,
+        DiagnosticEvent, ExtensionPoint, Hash, ScError,
+    };
+
+    #[test]
+    fn test_interface_check() {
+        let expected = Interface::expected();
+        assert_eq!(expected.check(Some(&expected.clone())).unwrap(), None);
+        assert!(expected
+            .check(None)
+            .unwrap()
+            .unwrap()
+            .contains("no version()"));
+
+        let rehashed = Interface {
+            hash: [0; 32],
+            ..expected.clone()
+        };
+        assert!(expected
+            .check(Some(&rehashed))
+            .unwrap()
+            .unwrap()
+            .contains("differs"));
+
+        let newer = Interface {
+            version: INTERFACE_VERSION + 1,
+            ..expected.clone()
+        };
+        assert!(expected.check(Some(&newer)).is_err());
+    }
+
+    #[test]
+    fn test_typed_error() {
+        let contract = ScAddress::Contract(ContractId(Hash([1; 32])));
+        let err = typed_error(
+            &contract,
+            anyhow!("make_move simulation failed: HostError: Error(Contract, #10)"),
+        );
+        assert_eq!(
+            err.downcast_ref::<ContractError>(),
+            Some(&ContractError::InvalidProof)
+        );
+        assert!(typed_error(&contract, anyhow!("connection refused"))
+            .downcast_ref::<ContractError>()
+            .is_none());
+
+        // The events win over the message, which may name another contract's error
+        let failed = SimulationFailed {
+            message: "make_move simulation failed: HostError: Error(Contract, #4)".to_string(),
+            events: vec![DiagnosticEvent {
+                in_successful_contract_call: false,
+                event: ContractEvent {
+                    ext: ExtensionPoint::V0,
+                    contract_id: Some(ContractId(Hash([1; 32]))),
+                    type_: ContractEventType::Diagnostic,
+                    body: ContractEventBody::V0(ContractEventV0 {
+                        topics: vec![
+                            ScVal::Symbol("error".try_into().unwrap()),
+                            ScVal::Error(ScError::Contract(8)),
+                        ]
+                        .try_into()
+                        .unwrap(),
+                        data: ScVal::Void,
+                    }),
+                },
+            }],
+        };
+        let err = typed_error(
+            &contract,
+            anyhow::Error::new(failed).context("move rejected"),
+        );
+        assert_eq!(
+            err.downcast_ref::<ContractError>(),
+            Some(&ContractError::MoveAlreadyMade)
+        );
+        assert_eq!(tx::diagnostic_events(&err).len(), 1);
+    }
+}
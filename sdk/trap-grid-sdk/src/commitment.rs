@@ -0,0 +1,453 @@
+//! Trap commitments and the trap Merkle tree
+//!
+//! Recomputes the hashes the circuits use (circom-compatible Poseidon over
+//! BN254, as in `poseidon::bn254`, tagged with the
+//! [`trap_grid_encoding::domain`] of each hash), so commitments, roots and
+//! Merkle proofs can be built without running nargo. Pure computation, available in the
+//! wasm build.
+
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use ark_bn254::Fr;
+use ark_ff::{BigInteger, PrimeField};
+use light_poseidon::{Poseidon, PoseidonHasher};
+use stellar_xdr::curr::{AccountId, ContractId, Hash, PublicKey, ScAddress, Uint256};
+use trap_grid_encoding::domain::{self, MAX_TAG_LEN};
+use trap_grid_encoding::{
+    encode_u64, merkle_depth, FieldBytes, TrapMerkleRoot, BN254_MODULUS, FIELD_BYTES,
+    MAX_MERKLE_TREE_DEPTH,
+};
+
+use crate::layout::{cell_index, grid_size, TrapLayout};
+
+/// Parse a field element from a decimal or 0x-prefixed hex string
+pub fn parse_field(value: &str) -> Result<FieldBytes> {
+    let mut out = [0u8; FIELD_BYTES];
+    if let Some(digits) = value.strip_prefix("0x") {
+        if digits.is_empty() || digits.len() > FIELD_BYTES * 2 {
+            bail!("field element {value} must have 1 to 64 hex digits");
+        }
+        let padded = format!("{digits:0>64}");
+        hex::decode_to_slice(&padded, &mut out)
+            .with_context(|| format!("invalid hex field element {value}"))?;
+    } else {
+        let parsed: u128 = value.parse().with_context(|| {
+            format!("invalid field element {value}; use 0x-prefixed hex for large values")
+        })?;
+        out[FIELD_BYTES - 16..].copy_from_slice(&parsed.to_be_bytes());
+    }
+    if out >= BN254_MODULUS {
+        bail!("field element {value} is not below the BN254 modulus");
+    }
+    Ok(out)
+}
+
+/// Poseidon hash of 1 to 12 field elements
+pub fn poseidon(inputs: &[FieldBytes]) -> FieldBytes {
+    let mut hasher = Poseidon::<Fr>::new_circom(inputs.len()).expect("1 to 12 Poseidon inputs");
+    let inputs: Vec<Fr> = inputs
+        .iter()
+        .map(|input| Fr::from_be_bytes_mod_order(input))
+        .collect();
+    let hash = hasher
+        .hash(&inputs)
+        .expect("input count matches the hasher width");
+    hash.into_bigint()
+        .to_bytes_be()
+        .try_into()
+        .expect("BN254 field elements are 32 bytes")
+}
+
+/// Poseidon hash of `inputs` under a [`domain`] tag, as the circuits'
+/// `libs/hash/domain.nr` compute it: the tag is the first input
+pub fn hash_fields(tag: &FieldBytes, inputs: &[FieldBytes]) -> FieldBytes {
+    let mut tagged = Vec::with_capacity(inputs.len() + 1);
+    tagged.push(*tag);
+    tagged.extend_from_slice(inputs);
+    poseidon(&tagged)
+}
+
+/// Most bytes [`hash_to_field`] takes: ten 31-byte chunks
+pub const MAX_HASHED_BYTES: usize = 10 * MAX_TAG_LEN;
+
+/// Hash arbitrary bytes to a field element under a [`domain`] tag
+///
+/// `hash_fields(tag, [length, chunks...])` over 31-byte big-endian chunks,
+/// the last one right-aligned; each chunk is below the modulus, and the
+/// length tells inputs differing only by leading zeros of the last chunk
+/// apart.
+pub fn hash_to_field(tag: &FieldBytes, bytes: &[u8]) -> Result<FieldBytes> {
+    ensure!(
+        bytes.len() <= MAX_HASHED_BYTES,
+        "cannot hash {} bytes to a field, at most {MAX_HASHED_BYTES}",
+        bytes.len()
+    );
+    let mut inputs = vec![encode_u64(bytes.len() as u64)];
+    for chunk in bytes.chunks(MAX_TAG_LEN) {
+        let mut field = [0u8; FIELD_BYTES];
+        field[FIELD_BYTES - chunk.len()..].copy_from_slice(chunk);
+        inputs.push(field);
+    }
+    Ok(hash_fields(tag, &inputs))
+}
+
+/// The account key or contract id of a defender's address, the bytes the
+/// grid-setup circuit takes as its `defender` public input
+pub fn defender_key(address: &str) -> Result<[u8; FIELD_BYTES]> {
+    let address: ScAddress = address
+        .parse()
+        .map_err(|e| anyhow!("invalid address {address}: {e}"))?;
+    match address {
+        ScAddress::Account(AccountId(PublicKey::PublicKeyTypeEd25519(Uint256(key)))) => Ok(key),
+        ScAddress::Contract(ContractId(Hash(key))) => Ok(key),
+        other => bail!("defender {other} must be an account or contract address"),
+    }
+}
+
+/// Field element binding a commitment to its defender
+///
+/// [`hash_to_field`] of the [`defender_key`] under [`domain::DEFENDER`], so
+/// the same layout and secret commit differently for every defender.
+pub fn defender_field(address: &str) -> Result<FieldBytes> {
+    hash_to_field(&domain::DEFENDER, &defender_key(address)?)
+}
+
+/// `trap_commitment(trap_value, secret, defender)`, the trap-commitment circuit's commitment
+///
+/// `defender` is the [`defender_field`] of the defender's address.
+pub fn trap_commitment(trap_value: u32, secret: &FieldBytes, defender: &FieldBytes) -> FieldBytes {
+    hash_fields(
+        &domain::TRAP_COMMITMENT,
+        &[encode_u64(trap_value.into()), *secret, *defender],
+    )
+}
+
+/// `trap_leaf(trap_value)`, a leaf of the trap Merkle tree
+pub fn trap_leaf(trap_value: u32) -> FieldBytes {
+    hash_fields(&domain::TRAP_LEAF, &[encode_u64(trap_value.into())])
+}
+
+/// `merkle_node([left, right])`, an inner node of the trap Merkle tree
+pub fn merkle_node(left: &FieldBytes, right: &FieldBytes) -> FieldBytes {
+    hash_fields(&domain::MERKLE_NODE, &[*left, *right])
+}
+
+/// `grid_commitment(trap_merkle_root, salt, defender)`, the grid-setup
+/// circuit's commitment
+///
+/// Hides the root until the defender chooses to reveal it, so it can start
+/// a game without giving the grid away. `defender` is the
+/// [`defender_field`] of the defender's address: the contract passes the
+/// address it starts the game for to the circuit, so a commitment and its
+/// proof start games for that defender only.
+pub fn grid_commitment(
+    trap_merkle_root: &FieldBytes,
+    salt: &FieldBytes,
+    defender: &FieldBytes,
+) -> FieldBytes {
+    hash_fields(
+        &domain::GRID_COMMITMENT,
+        &[*trap_merkle_root, *salt, *defender],
+    )
+}
+
+/// The salt a layout is committed with: its own, or a fresh random one
+///
+/// Random salts are 31 bytes, so they are always below the BN254 modulus,
+/// and never zero, which the grid-setup circuit rejects.
+pub fn layout_salt(layout: &TrapLayout) -> Result<FieldBytes> {
+    if let Some(salt) = &layout.salt {
+        let salt = parse_field(salt).context("invalid layout salt")?;
+        ensure!(salt != [0; FIELD_BYTES], "layout salt must not be zero");
+        return Ok(salt);
+    }
+    let mut salt = [0u8; FIELD_BYTES];
+    while salt == [0; FIELD_BYTES] {
+        getrandom::getrandom(&mut salt[1..]).map_err(|e| anyhow!("no system randomness: {e}"))?;
+    }
+    Ok(salt)
+}
+
+/// Root of zk-kit's `binary_merkle_root` with [`merkle_node`] nodes
+///
+/// Only the first `proof_length` levels are hashed; a length of zero yields the leaf.
+pub fn merkle_root(
+    leaf: &FieldBytes,
+    proof_length: u32,
+    indices: &[u8; MAX_MERKLE_TREE_DEPTH],
+    siblings: &[FieldBytes; MAX_MERKLE_TREE_DEPTH],
+) -> Result<FieldBytes> {
+    ensure!(
+        proof_length as usize <= MAX_MERKLE_TREE_DEPTH,
+        "Depth is greater than the maximum allowed depth"
+    );
+    let mut node = *leaf;
+    for level in 0..proof_length as usize {
+        node = match indices[level] {
+            0 => merkle_node(&node, &siblings[level]),
+            _ => merkle_node(&siblings[level], &node),
+        };
+    }
+    Ok(node)
+}
+
+/// Full Merkle tree over the trap leaves of a grid
+///
+/// The tree is [`merkle_depth`] levels deep; the leaves past the grid's
+/// cells are those of empty cells, as the grid-setup circuit pads them.
+pub struct TrapTree {
+    grid_size: u32,
+    /// Leaves first, root last
+    levels: Vec<Vec<FieldBytes>>,
+}
+
+impl TrapTree {
+    pub fn new(trap_values: &[u8]) -> Result<Self> {
+        let grid_size = grid_size(trap_values.len())?;
+        let mut leaves = trap_values
+            .iter()
+            .map(|value| trap_leaf((*value).into()))
+            .collect::<Vec<_>>();
+        leaves.resize(1 << merkle_depth(grid_size), trap_leaf(0));
+        let mut levels = vec![leaves];
+        while levels.last().expect("at least the leaves").len() > 1 {
+            let next = levels
+                .last()
+                .expect("at least the leaves")
+                .chunks(2)
+                .map(|pair| merkle_node(&pair[0], &pair[1]))
+                .collect();
+            levels.push(next);
+        }
+        Ok(Self { grid_size, levels })
+    }
+
+    pub fn root(&self) -> FieldBytes {
+        self.levels.last().expect("at least the root")[0]
+    }
+
+    /// Side of the grid the tree is built over
+    pub fn grid_size(&self) -> u32 {
+        self.grid_size
+    }
+
+    /// Levels of the tree, the length of its proofs
+    pub fn depth(&self) -> usize {
+        self.levels.len() - 1
+    }
+
+    /// Path indices (leaf level first) and siblings of a leaf, zero past the
+    /// tree's [`depth`](Self::depth)
+    pub fn proof(
+        &self,
+        index: usize,
+    ) -> (
+        [u8; MAX_MERKLE_TREE_DEPTH],
+        [FieldBytes; MAX_MERKLE_TREE_DEPTH],
+    ) {
+        let mut indices = [0u8; MAX_MERKLE_TREE_DEPTH];
+        let mut siblings = [[0u8; 32]; MAX_MERKLE_TREE_DEPTH];
+        for level in 0..self.depth() {
+            let position = index >> level;
+            indices[level] = (position & 1) as u8;
+            siblings[level] = self.levels[level][position ^ 1];
+        }
+        (indices, siblings)
+    }
+
+    /// Public inputs of the trap-merkle-root circuit opening cell (`x`, `y`)
+    pub fn opening(&self, x: u32, y: u32) -> TrapMerkleRoot {
+        let (indices, siblings) = self.proof(cell_index(self.grid_size, x, y));
+        TrapMerkleRoot {
+            trap_merkle_root: self.root(),
+            move_x: x,
+            move_y: y,
+            proof_length: self.depth() as u32,
+            indices,
+            siblings,
+            grid_size: self.grid_size,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::layout::NUM_CELLS;
+    use crate::GRID_SIZE;
+
+    #[test]
+    fn test_parse_field() {
+        assert_eq!(parse_field("7").unwrap(), encode_u64(7));
+        assert_eq!(parse_field("0x7").unwrap(), encode_u64(7));
+        assert!(parse_field("0x").is_err());
+        assert!(parse_field("seven").is_err());
+
+        let modulus = format!("0x{}", hex::encode(BN254_MODULUS));
+        assert!(parse_field(&modulus).is_err());
+    }
+
+    #[test]
+    fn test_matches_circuit_sample() {
+        // circuits/trap-commitment/Prover.toml
+        let defender =
+            defender_field("GAIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCF6M").unwrap();
+        assert_eq!(
+            defender,
+            parse_field("0x07878929af8c9ebc097a6750be25da0519ca2bda5326199bb5780ec95dc2c580")
+                .unwrap()
+        );
+        let commitment = trap_commitment(1, &parse_field("12345").unwrap(), &defender);
+        assert_eq!(
+            commitment,
+            parse_field("0x2d69e166d6b29b7c5159dcca7edd5a6c2f13aafe4d14e2525680d1422066e6e7")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_domain_separation() {
+        // The same inputs hash differently in every domain
+        let (a, b) = (encode_u64(1), encode_u64(2));
+        assert_ne!(merkle_node(&a, &b), poseidon(&[a, b]));
+        assert_ne!(merkle_node(&a, &b), grid_commitment(&a, &b, &a));
+        assert_ne!(trap_leaf(1), poseidon(&[a]));
+
+        // Lengths keep trailing chunks with leading zeros apart
+        let key = [7u8; 32];
+        assert_ne!(
+            hash_to_field(&domain::DEFENDER, &key).unwrap(),
+            hash_to_field(&domain::DEFENDER, &key[1..]).unwrap()
+        );
+        assert_ne!(
+            hash_to_field(&domain::DEFENDER, &[0, 1]).unwrap(),
+            hash_to_field(&domain::DEFENDER, &[1]).unwrap()
+        );
+        assert!(hash_to_field(&domain::DEFENDER, &[0; MAX_HASHED_BYTES]).is_ok());
+        assert!(hash_to_field(&domain::DEFENDER, &[0; MAX_HASHED_BYTES + 1]).is_err());
+    }
+
+    #[test]
+    fn test_defender_binding() {
+        let secret = parse_field("12345").unwrap();
+        let account =
+            defender_field("GAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAWHF").unwrap();
+        let contract =
+            defender_field("CARCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEVQO").unwrap();
+        assert_ne!(
+            trap_commitment(1, &secret, &account),
+            trap_commitment(1, &secret, &contract)
+        );
+        assert!(defender_field("GABC").is_err());
+    }
+
+    #[test]
+    fn test_grid_commitment_matches_circuit_sample() {
+        // circuits/grid-setup/Prover.toml: traps on the diagonal and in the
+        // two other corners, salt 12345, bound to the trap-commitment sample's
+        // defender
+        let mut trap_values = vec![0u8; NUM_CELLS];
+        for x in 0..8 {
+            trap_values[cell_index(GRID_SIZE, x, x)] = 1;
+        }
+        trap_values[cell_index(GRID_SIZE, 0, 7)] = 1;
+        trap_values[cell_index(GRID_SIZE, 7, 0)] = 1;
+        let tree = TrapTree::new(&trap_values).unwrap();
+        assert_eq!(
+            tree.root(),
+            parse_field("0x023518dc0d6455732abb82883904b35a20f29bc8e7c041b84398be6c8cf432b9")
+                .unwrap()
+        );
+        let defender =
+            defender_field("GAIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCF6M").unwrap();
+        let salt = parse_field("12345").unwrap();
+        assert_eq!(
+            grid_commitment(&tree.root(), &salt, &defender),
+            parse_field("0x2ee5032b9c54a99742b33bb21516e00e4872bd72a4b0ee205189c5a365e65f85")
+                .unwrap()
+        );
+        // Another defender's commitment to the same grid differs
+        let other =
+            defender_field("GAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAWHF").unwrap();
+        assert_ne!(
+            grid_commitment(&tree.root(), &salt, &other),
+            grid_commitment(&tree.root(), &salt, &defender)
+        );
+    }
+
+    #[test]
+    fn test_layout_salt() {
+        let mut layout = TrapLayout {
+            trap_values: vec![0; NUM_CELLS],
+            salt: Some("12345".into()),
+        };
+        assert_eq!(layout_salt(&layout).unwrap(), encode_u64(12345));
+
+        layout.salt = Some("0".into());
+        assert!(layout_salt(&layout).is_err());
+
+        layout.salt = None;
+        let salt = layout_salt(&layout).unwrap();
+        assert_ne!(salt, [0; FIELD_BYTES]);
+        assert!(salt < BN254_MODULUS);
+        assert_ne!(layout_salt(&layout).unwrap(), salt);
+    }
+
+    #[test]
+    fn test_tree_proofs() {
+        let mut trap_values = vec![0u8; NUM_CELLS];
+        trap_values[19] = 1;
+        let tree = TrapTree::new(&trap_values).unwrap();
+        for index in [0, 19, 63] {
+            let (indices, siblings) = tree.proof(index);
+            let leaf = trap_leaf(trap_values[index].into());
+            assert_eq!(
+                merkle_root(&leaf, tree.depth() as u32, &indices, &siblings).unwrap(),
+                tree.root()
+            );
+        }
+
+        let opening = tree.opening(2, 3);
+        assert_eq!(opening.indices, [1, 1, 0, 0, 1, 0, 0, 0]);
+        assert_eq!(opening.proof_length, 6);
+        assert_eq!(
+            merkle_root(
+                &trap_leaf(trap_values[19].into()),
+                opening.proof_length,
+                &opening.indices,
+                &opening.siblings
+            )
+            .unwrap(),
+            opening.trap_merkle_root
+        );
+    }
+
+    #[test]
+    fn test_tree_sizes() {
+        // A 16x16 grid fills its 256 leaves, a 10x10 one pads 100 to 128
+        for (grid_size, depth) in [(16u32, 8), (10, 7), (2, 2)] {
+            let cells = (grid_size * grid_size) as usize;
+            let mut trap_values = vec![0u8; cells];
+            trap_values[cells - 1] = 1;
+            let tree = TrapTree::new(&trap_values).unwrap();
+            assert_eq!(tree.depth(), depth);
+            assert_eq!(tree.grid_size(), grid_size);
+
+            let last = grid_size - 1;
+            let opening = tree.opening(last, last);
+            assert_eq!(opening.proof_length, depth as u32);
+            assert_eq!(opening.grid_size, grid_size);
+            assert!(opening.siblings[depth..].iter().all(|s| *s == [0; 32]));
+            assert_eq!(
+                merkle_root(
+                    &trap_leaf(1),
+                    opening.proof_length,
+                    &opening.indices,
+                    &opening.siblings
+                )
+                .unwrap(),
+                tree.root()
+            );
+        }
+        assert!(TrapTree::new(&[0u8; 65]).is_err());
+    }
+}
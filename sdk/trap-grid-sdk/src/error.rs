@@ -0,0 +1,374 @@
+//! The contract's error codes
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use stellar_xdr::curr::{ContractEventBody, ContractId, DiagnosticEvent, ScError, ScSymbol, ScVal};
+
+/// Mirror of the contract's `Error` enum (`#[contracterror]`, `repr(u32)`)
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[repr(u32)]
+pub enum ContractError {
+    GameNotFound = 1,
+    NotPlayer = 2,
+    GameNotStarted = 3,
+    GameAlreadyEnded = 4,
+    InvalidMove = 5,
+    NotDefender = 6,
+    NotAttacker = 7,
+    MoveAlreadyMade = 8,
+    DefenderMustCommit = 9,
+    InvalidProof = 10,
+    AllMovesCompleted = 11,
+    GameNotComplete = 12,
+    MovePending = 13,
+    InvalidSponsorship = 14,
+    ReferrerAlreadySet = 15,
+    SelfReferral = 16,
+    InvalidFees = 17,
+    WithdrawNotQueued = 18,
+    WithdrawTimelocked = 19,
+    NotAdminMember = 20,
+    ProposalNotFound = 21,
+    ProposalExpired = 22,
+    AlreadyApproved = 23,
+    InvalidThreshold = 24,
+    NotOperator = 25,
+    Paused = 26,
+    InvalidConfig = 27,
+    TooManyGames = 28,
+    StakeNotPositive = 29,
+    StakeOutOfBounds = 30,
+    InvalidArbiter = 31,
+    NotArbitrated = 32,
+    AppealWindowOpen = 33,
+    AppealWindowClosed = 34,
+    UnderAppeal = 35,
+    NotAppealed = 36,
+    NothingToClaim = 37,
+    ClaimNotReady = 38,
+    GridLocked = 39,
+    RootReused = 40,
+    InvalidSetup = 41,
+    SetupReused = 42,
+    MoveTooSoon = 43,
+    StaleChannelState = 44,
+    ChallengeWindowOpen = 45,
+    InvalidTranscript = 46,
+    NoChannelState = 47,
+    ChannelConflict = 48,
+    NotOptimistic = 49,
+    NoPendingMove = 50,
+    ClaimNotChallengeable = 51,
+    NoChallenge = 52,
+    ProofDeadlinePassed = 53,
+    OptimisticLocked = 54,
+    NoteTooLong = 55,
+    GameFrozen = 56,
+    NotArbiter = 57,
+    PracticeNotFound = 58,
+    PracticeInProgress = 59,
+    RevealMismatch = 60,
+    DailyNotFound = 61,
+    DailyExists = 62,
+    DayNotClosed = 63,
+    DailyRevealed = 64,
+    DailyNotRevealed = 65,
+    TooFewGames = 66,
+}
+
+impl ContractError {
+    pub const ALL: [ContractError; 66] = [
+        ContractError::GameNotFound,
+        ContractError::NotPlayer,
+        ContractError::GameNotStarted,
+        ContractError::GameAlreadyEnded,
+        ContractError::InvalidMove,
+        ContractError::NotDefender,
+        ContractError::NotAttacker,
+        ContractError::MoveAlreadyMade,
+        ContractError::DefenderMustCommit,
+        ContractError::InvalidProof,
+        ContractError::AllMovesCompleted,
+        ContractError::GameNotComplete,
+        ContractError::MovePending,
+        ContractError::InvalidSponsorship,
+        ContractError::ReferrerAlreadySet,
+        ContractError::SelfReferral,
+        ContractError::InvalidFees,
+        ContractError::WithdrawNotQueued,
+        ContractError::WithdrawTimelocked,
+        ContractError::NotAdminMember,
+        ContractError::ProposalNotFound,
+        ContractError::ProposalExpired,
+        ContractError::AlreadyApproved,
+        ContractError::InvalidThreshold,
+        ContractError::NotOperator,
+        ContractError::Paused,
+        ContractError::InvalidConfig,
+        ContractError::TooManyGames,
+        ContractError::StakeNotPositive,
+        ContractError::StakeOutOfBounds,
+        ContractError::InvalidArbiter,
+        ContractError::NotArbitrated,
+        ContractError::AppealWindowOpen,
+        ContractError::AppealWindowClosed,
+        ContractError::UnderAppeal,
+        ContractError::NotAppealed,
+        ContractError::NothingToClaim,
+        ContractError::ClaimNotReady,
+        ContractError::GridLocked,
+        ContractError::RootReused,
+        ContractError::InvalidSetup,
+        ContractError::SetupReused,
+        ContractError::MoveTooSoon,
+        ContractError::StaleChannelState,
+        ContractError::ChallengeWindowOpen,
+        ContractError::InvalidTranscript,
+        ContractError::NoChannelState,
+        ContractError::ChannelConflict,
+        ContractError::NotOptimistic,
+        ContractError::NoPendingMove,
+        ContractError::ClaimNotChallengeable,
+        ContractError::NoChallenge,
+        ContractError::ProofDeadlinePassed,
+        ContractError::OptimisticLocked,
+        ContractError::NoteTooLong,
+        ContractError::GameFrozen,
+        ContractError::NotArbiter,
+        ContractError::PracticeNotFound,
+        ContractError::PracticeInProgress,
+        ContractError::RevealMismatch,
+        ContractError::DailyNotFound,
+        ContractError::DailyExists,
+        ContractError::DayNotClosed,
+        ContractError::DailyRevealed,
+        ContractError::DailyNotRevealed,
+        ContractError::TooFewGames,
+    ];
+
+    pub fn code(self) -> u32 {
+        self as u32
+    }
+
+    pub fn from_code(code: u32) -> Option<Self> {
+        Self::ALL.into_iter().find(|error| error.code() == code)
+    }
+
+    /// Decode an `Error(Contract, code)` value, e.g. from a failed simulation
+    pub fn from_scval(val: &ScVal) -> Option<Self> {
+        match val {
+            ScVal::Error(ScError::Contract(code)) => Self::from_code(*code),
+            _ => None,
+        }
+    }
+
+    /// Find the contract error in a host diagnostic such as
+    /// `HostError: Error(Contract, #10)` from a failed simulation
+    pub fn from_diagnostic(message: &str) -> Option<Self> {
+        let start = message.find("Error(Contract, #")? + "Error(Contract, #".len();
+        let digits = &message[start..];
+        let end = digits.find(')')?;
+        Self::from_code(digits[..end].parse().ok()?)
+    }
+
+    /// The error a failed invocation of `contract` ended with, from the
+    /// host's `error` diagnostic events
+    ///
+    /// Errors raised in contracts it called (the verifier, the Game Hub) are
+    /// skipped, since their codes mean something else there.
+    pub fn from_events(events: &[DiagnosticEvent], contract: &ContractId) -> Option<Self> {
+        events.iter().rev().find_map(|diagnostic| {
+            if diagnostic.event.contract_id.as_ref() != Some(contract) {
+                return None;
+            }
+            let ContractEventBody::V0(body) = &diagnostic.event.body;
+            match body.topics.as_slice() {
+                [ScVal::Symbol(ScSymbol(name)), error, ..] if name.as_slice() == b"error" => {
+                    Self::from_scval(error)
+                }
+                _ => None,
+            }
+        })
+    }
+
+    fn message(self) -> &'static str {
+        match self {
+            ContractError::GameNotFound => "game not found",
+            ContractError::NotPlayer => "caller is not a player in this game",
+            ContractError::GameNotStarted => "game has not started",
+            ContractError::GameAlreadyEnded => "game has already ended",
+            ContractError::InvalidMove => "move is outside the grid",
+            ContractError::NotDefender => "caller is not the defender",
+            ContractError::NotAttacker => "caller is not the attacker",
+            ContractError::MoveAlreadyMade => "cell has already been played",
+            ContractError::DefenderMustCommit => "defender must commit the grid first",
+            ContractError::InvalidProof => "proof failed verification",
+            ContractError::AllMovesCompleted => "all moves have been made",
+            ContractError::GameNotComplete => "game is not complete",
+            ContractError::MovePending => "another move is waiting for an answer",
+            ContractError::InvalidSponsorship => {
+                "sponsorship backs no player or one already backed, stakes nothing, \
+                 splits over 100% or mixes tokens"
+            }
+            ContractError::ReferrerAlreadySet => "player already registered a referrer",
+            ContractError::SelfReferral => {
+                "players cannot refer themselves or the player who referred them"
+            }
+            ContractError::InvalidFees => "fee is over 100% or the referral share over 50%",
+            ContractError::WithdrawNotQueued => "no emergency withdrawal is queued for the game",
+            ContractError::WithdrawTimelocked => "emergency withdrawal delay has not passed",
+            ContractError::NotAdminMember => "caller is not a member of the admin set",
+            ContractError::ProposalNotFound => "proposal not found or already run",
+            ContractError::ProposalExpired => "proposal has expired",
+            ContractError::AlreadyApproved => "member already approved the proposal",
+            ContractError::InvalidThreshold => {
+                "admin set threshold must be between one and the number of members"
+            }
+            ContractError::NotOperator => "caller is neither an operator nor the admin",
+            ContractError::Paused => "new games are paused",
+            ContractError::InvalidConfig => "configuration value out of bounds",
+            ContractError::TooManyGames => "too many games in progress; try again later",
+            ContractError::StakeNotPositive => "stakes must be positive",
+            ContractError::StakeOutOfBounds => "stake outside the allowed points range",
+            ContractError::InvalidArbiter => {
+                "arbiter must not be a player, and is appointed once before the first move"
+            }
+            ContractError::NotArbitrated => "game has no result awaiting an appeal window",
+            ContractError::AppealWindowOpen => "appeal window is still open",
+            ContractError::AppealWindowClosed => "appeal window has closed",
+            ContractError::UnderAppeal => "result is under appeal; waiting for the arbiter",
+            ContractError::NotAppealed => "result was not appealed",
+            ContractError::NothingToClaim => "game has no winnings waiting to be claimed",
+            ContractError::ClaimNotReady => {
+                "winnings cannot be claimed until the claim delay passes"
+            }
+            ContractError::GridLocked => {
+                "grid cannot be committed: already committed, or play has started"
+            }
+            ContractError::RootReused => "defender already committed this grid in an earlier game",
+            ContractError::InvalidSetup => "grid setup proof is invalid",
+            ContractError::SetupReused => "grid commitment was already used by an earlier game",
+            ContractError::MoveTooSoon => {
+                "player moved too recently; wait for the move gap to pass"
+            }
+            ContractError::StaleChannelState => {
+                "channel state is not newer than the one submitted, or that one closed the channel"
+            }
+            ContractError::ChallengeWindowOpen => {
+                "channel state can still be challenged; settle after the window"
+            }
+            ContractError::InvalidTranscript => {
+                "channel counts or transcript do not match the signed state"
+            }
+            ContractError::NoChannelState => "session has no channel, or no state was submitted",
+            ContractError::ChannelConflict => "session is played through a channel, not on-chain",
+            ContractError::NotOptimistic => "session is not in optimistic mode",
+            ContractError::NoPendingMove => "no submitted move is waiting for a claim",
+            ContractError::ClaimNotChallengeable => {
+                "claim is proven, its challenge window has closed, or another claim is challenged"
+            }
+            ContractError::NoChallenge => "no claim of the session is challenged",
+            ContractError::ProofDeadlinePassed => "the challenged claim was not proven in time",
+            ContractError::OptimisticLocked => {
+                "session is already in optimistic mode, or play has started"
+            }
+            ContractError::NoteTooLong => "move note is longer than 32 bytes",
+            ContractError::GameFrozen => {
+                "session is frozen by its arbiter or the admin; wait for it to be unfrozen"
+            }
+            ContractError::NotArbiter => "caller is neither the session's arbiter nor the admin",
+            ContractError::PracticeNotFound => "player has no practice game in progress",
+            ContractError::PracticeInProgress => {
+                "player's last practice game is not finished; call finish_practice"
+            }
+            ContractError::RevealMismatch => {
+                "revealed secret does not hash to the practice game's commitment"
+            }
+            ContractError::DailyNotFound => "no daily grid is committed for the day",
+            ContractError::DailyExists => "day's grid is already committed, or the day has passed",
+            ContractError::DayNotClosed => "day's grid cannot be revealed before the day closes",
+            ContractError::DailyRevealed => "day's grid is revealed and takes no more probes",
+            ContractError::DailyNotRevealed => "day's grid is not revealed yet",
+            ContractError::TooFewGames => {
+                "a player has completed fewer games than the configuration requires"
+            }
+        }
+    }
+}
+
+impl From<ContractError> for ScVal {
+    fn from(error: ContractError) -> Self {
+        ScVal::Error(ScError::Contract(error.code()))
+    }
+}
+
+impl fmt::Display for ContractError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (contract error #{})", self.message(), self.code())
+    }
+}
+
+impl std::error::Error for ContractError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use stellar_xdr::curr::{
+        ContractEvent, ContractEventType, ContractEventV0, ExtensionPoint, Hash,
+    };
+
+    #[test]
+    fn test_codes_round_trip() {
+        for error in ContractError::ALL {
+            assert_eq!(ContractError::from_scval(&error.into()), Some(error));
+        }
+        assert_eq!(ContractError::from_code(0), None);
+        assert_eq!(
+            ContractError::from_diagnostic(
+                "make_move simulation failed: HostError: Error(Contract, #8)\n\nEvent log ..."
+            ),
+            Some(ContractError::MoveAlreadyMade)
+        );
+        assert_eq!(
+            ContractError::from_diagnostic("Error(WasmVm, InvalidAction)"),
+            None
+        );
+        assert_eq!(
+            ContractError::InvalidProof.to_string(),
+            "proof failed verification (contract error #10)"
+        );
+    }
+
+    fn error_event(contract: u8, code: u32) -> DiagnosticEvent {
+        DiagnosticEvent {
+            in_successful_contract_call: false,
+            event: ContractEvent {
+                ext: ExtensionPoint::V0,
+                contract_id: Some(ContractId(Hash([contract; 32]))),
+                type_: ContractEventType::Diagnostic,
+                body: ContractEventBody::V0(ContractEventV0 {
+                    topics: vec![
+                        ScVal::Symbol("error".try_into().unwrap()),
+                        ScVal::Error(ScError::Contract(code)),
+                    ]
+                    .try_into()
+                    .unwrap(),
+                    data: ScVal::Void,
+                }),
+            },
+        }
+    }
+
+    #[test]
+    fn test_from_events() {
+        let trap_grid = ContractId(Hash([1; 32]));
+        // The verifier fails first, trap-grid turns it into InvalidProof
+        let events = [error_event(2, 4), error_event(1, 10)];
+        assert_eq!(
+            ContractError::from_events(&events, &trap_grid),
+            Some(ContractError::InvalidProof)
+        );
+        assert_eq!(ContractError::from_events(&events[..1], &trap_grid), None);
+    }
+}
@@ -0,0 +1,274 @@
+//! Events published by the contract
+//!
+//! Every event has four topics, the snake_case event name, the session id,
+//! the defender and the attacker; the remaining fields are the data, encoded
+//! as a map. [`session_topics`] and [`player_topics`] build `getEvents`
+//! filters on them.
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use stellar_xdr::curr::{ScBytes, ScVal};
+
+use crate::scval::{address, as_address, as_bool, as_map, as_u32, map, map_field, symbol};
+
+/// Published by `start_game`
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct GameStarted {
+    pub session_id: u32,
+    pub defender: String,
+    pub attacker: String,
+}
+
+/// Published by `submit_move` when the attacker picks a cell
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct MoveSubmitted {
+    pub session_id: u32,
+    pub defender: String,
+    pub attacker: String,
+    pub x: u32,
+    pub y: u32,
+    /// The attacker's note from `submit_move_with_note`, empty otherwise
+    pub note: Vec<u8>,
+}
+
+/// Published by `make_move` once the move is recorded
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct MoveMade {
+    pub session_id: u32,
+    pub defender: String,
+    pub attacker: String,
+    pub x: u32,
+    pub y: u32,
+    pub is_hit: bool,
+    pub verified: bool,
+}
+
+/// Published when the last cell is played or by `end_game`
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct GameEnded {
+    pub session_id: u32,
+    pub defender: String,
+    pub attacker: String,
+    pub winner: String,
+    pub hits: u32,
+    pub misses: u32,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum GameEvent {
+    GameStarted(GameStarted),
+    MoveSubmitted(MoveSubmitted),
+    MoveMade(MoveMade),
+    GameEnded(GameEnded),
+}
+
+/// `getEvents` topic filter matching every event of one session
+pub fn session_topics(session_id: u32) -> Vec<Option<ScVal>> {
+    vec![None, Some(session_id.into()), None, None]
+}
+
+/// `getEvents` topic filters matching every event of the games `player`
+/// plays: the first as defender, the second as attacker
+pub fn player_topics(player: &str) -> Result<[Vec<Option<ScVal>>; 2]> {
+    let player = address(player)?;
+    Ok([
+        vec![None, None, Some(player.clone()), None],
+        vec![None, None, None, Some(player)],
+    ])
+}
+
+impl GameEvent {
+    pub fn session_id(&self) -> u32 {
+        match self {
+            GameEvent::GameStarted(event) => event.session_id,
+            GameEvent::MoveSubmitted(event) => event.session_id,
+            GameEvent::MoveMade(event) => event.session_id,
+            GameEvent::GameEnded(event) => event.session_id,
+        }
+    }
+
+    /// The defender and attacker of the event's session
+    pub fn players(&self) -> (&str, &str) {
+        let (defender, attacker) = match self {
+            GameEvent::GameStarted(event) => (&event.defender, &event.attacker),
+            GameEvent::MoveSubmitted(event) => (&event.defender, &event.attacker),
+            GameEvent::MoveMade(event) => (&event.defender, &event.attacker),
+            GameEvent::GameEnded(event) => (&event.defender, &event.attacker),
+        };
+        (defender, attacker)
+    }
+
+    /// Decode an event from its topics and data; `None` for unrelated events
+    pub fn decode(topics: &[ScVal], data: &ScVal) -> Result<Option<Self>> {
+        let [ScVal::Symbol(name), ScVal::U32(session_id), defender, attacker] = topics else {
+            return Ok(None);
+        };
+        let session_id = *session_id;
+        let defender = as_address(defender)?;
+        let attacker = as_address(attacker)?;
+        let event = match name.to_utf8_string_lossy().as_str() {
+            "game_started" => GameEvent::GameStarted(GameStarted {
+                session_id,
+                defender,
+                attacker,
+            }),
+            "move_submitted" => {
+                let map = as_map(data, "event data")?;
+                GameEvent::MoveSubmitted(MoveSubmitted {
+                    session_id,
+                    defender,
+                    attacker,
+                    x: as_u32(map_field(map, "x")?)?,
+                    y: as_u32(map_field(map, "y")?)?,
+                    note: match map_field(map, "note")? {
+                        ScVal::Bytes(note) => note.to_vec(),
+                        other => bail!("expected note bytes, found {}", other.name()),
+                    },
+                })
+            }
+            "move_made" => {
+                let map = as_map(data, "event data")?;
+                GameEvent::MoveMade(MoveMade {
+                    session_id,
+                    defender,
+                    attacker,
+                    x: as_u32(map_field(map, "x")?)?,
+                    y: as_u32(map_field(map, "y")?)?,
+                    is_hit: as_bool(map_field(map, "is_hit")?)?,
+                    verified: as_bool(map_field(map, "verified")?)?,
+                })
+            }
+            "game_ended" => {
+                let map = as_map(data, "event data")?;
+                GameEvent::GameEnded(GameEnded {
+                    session_id,
+                    defender,
+                    attacker,
+                    winner: as_address(map_field(map, "winner")?)?,
+                    hits: as_u32(map_field(map, "hits")?)?,
+                    misses: as_u32(map_field(map, "misses")?)?,
+                })
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(event))
+    }
+
+    /// Encode the event as the contract publishes it: `(topics, data)`
+    pub fn encode(&self) -> Result<(Vec<ScVal>, ScVal)> {
+        let (name, data) = match self {
+            GameEvent::GameStarted(_) => ("game_started", map(vec![])?),
+            GameEvent::MoveSubmitted(event) => (
+                "move_submitted",
+                map(vec![
+                    ("x", event.x.into()),
+                    ("y", event.y.into()),
+                    (
+                        "note",
+                        ScVal::Bytes(ScBytes(event.note.clone().try_into()?)),
+                    ),
+                ])?,
+            ),
+            GameEvent::MoveMade(event) => (
+                "move_made",
+                map(vec![
+                    ("x", event.x.into()),
+                    ("y", event.y.into()),
+                    ("is_hit", event.is_hit.into()),
+                    ("verified", event.verified.into()),
+                ])?,
+            ),
+            GameEvent::GameEnded(event) => (
+                "game_ended",
+                map(vec![
+                    ("winner", address(&event.winner)?),
+                    ("hits", event.hits.into()),
+                    ("misses", event.misses.into()),
+                ])?,
+            ),
+        };
+        let (defender, attacker) = self.players();
+        let topics = vec![
+            symbol(name)?,
+            self.session_id().into(),
+            address(defender)?,
+            address(attacker)?,
+        ];
+        Ok((topics, data))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Keypair;
+
+    const ZERO_ACCOUNT: &str = "GAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAWHF";
+
+    #[test]
+    fn test_decode_event() {
+        let defender = Keypair::generate().unwrap().address();
+        let event = GameEvent::GameEnded(GameEnded {
+            session_id: 9,
+            defender: defender.clone(),
+            attacker: ZERO_ACCOUNT.into(),
+            winner: ZERO_ACCOUNT.into(),
+            hits: 3,
+            misses: 4,
+        });
+        let (topics, data) = event.encode().unwrap();
+        assert_eq!(
+            GameEvent::decode(&topics, &data).unwrap(),
+            Some(event.clone())
+        );
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            format!(
+                r#"{{"event":"game_ended","session_id":9,"defender":"{defender}","attacker":"{ZERO_ACCOUNT}","winner":"{ZERO_ACCOUNT}","hits":3,"misses":4}}"#
+            )
+        );
+        assert_eq!(
+            GameEvent::decode(&[symbol("transfer").unwrap(), ScVal::U32(9)], &data).unwrap(),
+            None
+        );
+
+        let event = GameEvent::MoveSubmitted(MoveSubmitted {
+            session_id: 9,
+            defender,
+            attacker: ZERO_ACCOUNT.into(),
+            x: 1,
+            y: 2,
+            note: b"gg".to_vec(),
+        });
+        let (topics, data) = event.encode().unwrap();
+        assert_eq!(GameEvent::decode(&topics, &data).unwrap(), Some(event));
+    }
+
+    #[test]
+    fn test_topic_filters() {
+        let matches = |filter: &[Option<ScVal>], topics: &[ScVal]| {
+            filter.len() == topics.len()
+                && filter
+                    .iter()
+                    .zip(topics)
+                    .all(|(segment, topic)| segment.as_ref().is_none_or(|s| s == topic))
+        };
+        let defender = Keypair::generate().unwrap().address();
+        let event = GameEvent::GameStarted(GameStarted {
+            session_id: 4,
+            defender: defender.clone(),
+            attacker: ZERO_ACCOUNT.into(),
+        });
+        let (topics, _) = event.encode().unwrap();
+
+        assert!(matches(&session_topics(4), &topics));
+        assert!(!matches(&session_topics(5), &topics));
+        let [as_defender, as_attacker] = player_topics(&defender).unwrap();
+        assert!(matches(&as_defender, &topics));
+        assert!(!matches(&as_attacker, &topics));
+        let [as_defender, as_attacker] = player_topics(ZERO_ACCOUNT).unwrap();
+        assert!(!matches(&as_defender, &topics));
+        assert!(matches(&as_attacker, &topics));
+    }
+}
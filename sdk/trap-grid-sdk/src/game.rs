@@ -0,0 +1,1385 @@
+//! Contract state: `Game`, `Move`, sponsorships, grid setups, the constructor
+//! and deployment configurations, the full-state and rules reads, optimistic
+//! claims and storage keys
+
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use stellar_xdr::curr::{ScBytes, ScMap, ScVal, ScVec};
+
+use crate::scval::{
+    address, as_address, as_bool, as_i128, as_map, as_u32, map, map_field, symbol, variant,
+};
+
+/// Stored state of a session (`DataKey::Game`)
+///
+/// Addresses are strkeys (`G...` for accounts, `C...` for contracts).
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Game {
+    pub defender: String,
+    pub attacker: String,
+    pub defender_points: i128,
+    pub attacker_points: i128,
+    pub moves_made: u32,
+    pub hits: u32,
+    pub misses: u32,
+    pub game_started: bool,
+    pub game_ended: bool,
+    pub winner: Option<String>,
+    /// Layout of the stored record; 1 for records written before the
+    /// contract versioned them, which the contract migrates when read
+    #[serde(default = "legacy_schema")]
+    pub schema_version: u32,
+    /// Ledger of the last start or move, which expiry counts from; 0 in
+    /// records before schema 3
+    #[serde(default)]
+    pub last_active_ledger: u32,
+    /// Side of the game's grid; the default 8 in records before schema 4
+    #[serde(default = "default_grid_size")]
+    pub grid_size: u32,
+    /// Traps on the game's grid; 0 in records before schema 5, which the
+    /// contract gives its configured count when read
+    #[serde(default)]
+    pub trap_count: u32,
+}
+
+/// Schema of game records the current contract writes
+pub const GAME_SCHEMA_VERSION: u32 = 5;
+
+/// First schema recording `last_active_ledger`
+const ACTIVITY_SCHEMA: u32 = 3;
+
+/// First schema recording `grid_size`
+const GRID_SIZE_SCHEMA: u32 = 4;
+
+/// First schema recording `trap_count`
+const TRAP_COUNT_SCHEMA: u32 = 5;
+
+fn legacy_schema() -> u32 {
+    1
+}
+
+fn default_grid_size() -> u32 {
+    crate::GRID_SIZE
+}
+
+/// A recorded move (an element of `DataKey::Moves`)
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Move {
+    pub x: u32,
+    pub y: u32,
+    pub is_hit: bool,
+    pub verified: bool,
+}
+
+/// A move submitted by the attacker and not answered yet (`DataKey::PendingMove`)
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PendingMove {
+    pub x: u32,
+    pub y: u32,
+}
+
+/// A third party staking tokens on one player (an element of
+/// `DataKey::Sponsorships`, and of `start_sponsored_game`'s argument)
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Sponsorship {
+    pub sponsor: String,
+    /// The defender or the attacker
+    pub player: String,
+    pub token: String,
+    pub amount: i128,
+    /// Share of the winnings paid to the player if they win, in basis points
+    pub player_share_bps: u32,
+}
+
+/// The defender's proof that its hidden grid is well formed (`start_game`'s
+/// last argument)
+///
+/// `grid_commitment` hashes the trap Merkle root with a salt, which the
+/// grid-setup circuit proves holds exactly the configured number of traps.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct GridSetup {
+    pub grid_commitment: [u8; 32],
+    pub proof: Vec<u8>,
+}
+
+/// Addresses the contract is constructed with and keeps in instance storage
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct GameConfig {
+    pub admin: String,
+    pub game_hub: String,
+    pub verifier: String,
+    /// Verifier of grid-setup proofs
+    pub setup_verifier: String,
+}
+
+/// Tunable parameters of a deployment (`get_config`, `set_config`)
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ContractConfig {
+    pub game_ttl_ledgers: u32,
+    pub emergency_delay_ledgers: u32,
+    pub proposal_expiry_ledgers: u32,
+    /// Games in play at once; 0 for no limit
+    pub max_active_games: u32,
+    pub min_points: i128,
+    pub max_points: i128,
+    /// Ledgers without a move after which a game has expired; 0 for never
+    pub expiry_ledgers: u32,
+    pub expiry_policy: ExpiryPolicy,
+    pub appeal_window_ledgers: u32,
+    /// Ledgers between settlement and `claim_winnings`; 0 pays at settlement
+    pub claim_delay_ledgers: u32,
+    pub reject_reused_roots: bool,
+    pub trap_count: u32,
+    /// Ledgers between an attacker's moves, and between the defender's
+    /// answers; 0 for no limit
+    pub move_gap_ledgers: u32,
+    /// Side of the grid new games are played on, 2 to 16
+    pub grid_size: u32,
+    /// Share of what remains of a sponsorship paid to the other player on
+    /// each proven move against its side, in basis points; 0 for none
+    pub progressive_payout_bps: u32,
+    /// Bonus on the winnings of a decisive win, paid from the bonus pool
+    pub bonus_curve: PayoutCurve,
+    /// Fee rebate on the winnings of a player on a winning streak
+    pub streak_bonus: StreakBonus,
+    /// Games each player must have completed to start one; 0 for none
+    pub min_games_completed: u32,
+}
+
+/// Bonus on a sponsored win by its margin, the winner's share of the moves
+/// above a bare majority, in basis points
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PayoutCurve {
+    /// Bonus on the winnings of a win by every move; 0 pays no bonus
+    pub max_bonus_bps: u32,
+    /// Margin a win must exceed to earn a bonus
+    pub threshold_bps: u32,
+    /// 1 for a linear curve, 2 or 3 to favour the most decisive wins
+    pub exponent: u32,
+}
+
+/// Share of the protocol fee waived on the winnings of a player whose
+/// streak is at least `min_streak`
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct StreakBonus {
+    /// Consecutive wins that earn the rebate; 0 grants none
+    pub min_streak: u32,
+    pub fee_rebate_bps: u32,
+}
+
+/// Outcome of an expired game, whatever its score
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum ExpiryPolicy {
+    DefenderWins,
+    AttackerWins,
+    Draw,
+    Refund,
+}
+
+/// Ledgers a game's next steps hinge on; `None` where they don't apply
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Deadlines {
+    /// Last ledger before `end_game` treats the game as expired
+    pub expires_after: Option<u32>,
+    /// Last ledger a player can appeal the arbitrated result in
+    pub appeal_until: Option<u32>,
+    /// Ledger the winner can claim the sponsorships from
+    pub claimable_from: Option<u32>,
+    /// Ledger a queued emergency withdrawal can run from
+    pub emergency_withdraw_from: Option<u32>,
+}
+
+/// Everything a client polls about a session, read in one call
+/// (`get_full_state`)
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct FullState {
+    pub game: Game,
+    pub config: ContractConfig,
+    pub pending_move: Option<PendingMove>,
+    pub deadlines: Deadlines,
+    /// The last few moves, oldest first; `get_moves` has them all
+    pub recent_moves: Vec<Move>,
+}
+
+/// How a game that ends in play is decided
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum WinPolicy {
+    /// The attacker wins with hits on more than half its moves
+    MajorityHits,
+}
+
+/// The rules a session is played under (`get_rules`)
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Rules {
+    /// Side of the session's grid
+    pub grid_size: u32,
+    pub trap_count: u32,
+    pub win_policy: WinPolicy,
+    pub expiry_policy: ExpiryPolicy,
+    /// Ledgers without a move after which the session has expired; 0 for never
+    pub expiry_ledgers: u32,
+    pub move_gap_ledgers: u32,
+    pub appeal_window_ledgers: u32,
+    pub claim_delay_ledgers: u32,
+    pub emergency_delay_ledgers: u32,
+    pub channel_challenge_ledgers: u32,
+    pub claim_challenge_ledgers: u32,
+    pub min_points: i128,
+    pub max_points: i128,
+    /// Protocol fee and the referrers' share of it, in basis points
+    pub protocol_fee_bps: u32,
+    pub referral_fee_bps: u32,
+    /// Share of a sponsorship released per proven move against its side
+    pub progressive_payout_bps: u32,
+    pub bonus_curve: PayoutCurve,
+    pub streak_bonus: StreakBonus,
+    pub min_games_completed: u32,
+    /// Verifiers of position-movement and grid-setup proofs
+    pub verifier: String,
+    pub setup_verifier: String,
+}
+
+/// Claims of a session in optimistic mode (`get_optimistic`)
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Optimistic {
+    /// Ledger each claimed move was answered in, by move index
+    pub claims: BTreeMap<u32, u32>,
+    /// Index of the move the attacker challenged, until it is proven
+    pub challenged: Option<u32>,
+    /// Last ledger the challenged move can be proven in
+    pub prove_until: u32,
+    /// Token of the claim bonds
+    pub token: String,
+    /// Deposit the defender posts with each claim
+    pub bond: i128,
+    /// Deposits held for the claims made so far, until the session ends
+    pub bonded: i128,
+}
+
+/// Storage keys of the contract
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum DataKey {
+    Game(u32),
+    Moves(u32),
+    PendingMove(u32),
+    GameHubAddress,
+    VerifierAddress,
+    Admin,
+    TrophyAddress,
+    Sponsorships(u32),
+    Fees,
+    Referrer(String),
+    /// (referrer, token)
+    ReferralRewards(String, String),
+    EmergencyWithdraw(u32),
+    AdminSet,
+    ProposalCount,
+    Proposal(u32),
+    Operator(String),
+    Paused,
+    Config,
+    ActiveGames,
+    GlobalStats,
+    Heatmap,
+    Arbitration(u32),
+    Claim(u32),
+    GridRoot(u32),
+    /// (defender, trap Merkle root)
+    UsedRoot(String, [u8; 32]),
+    SetupVerifierAddress,
+    GridSetup(u32),
+    UsedSetup([u8; 32]),
+    ArchiveAddress,
+    LastMoves(u32),
+    AbortVotes(u32),
+    Channel(u32),
+    Optimistic(u32),
+    Frozen(u32),
+    /// token
+    BonusPool(String),
+    /// player
+    Practice(String),
+    /// player
+    PracticeStats(String),
+    /// day
+    Daily(u32),
+    /// (day, player)
+    DailyEntry(u32, String),
+    /// day
+    DailyLeaderboard(u32),
+    /// player
+    Streak(String),
+    /// player
+    GamesCompleted(String),
+}
+
+impl TryFrom<&ScVal> for Game {
+    type Error = anyhow::Error;
+
+    fn try_from(val: &ScVal) -> Result<Self> {
+        let map = as_map(val, "Game")?;
+        Ok(Game {
+            defender: as_address(map_field(map, "defender")?)?,
+            attacker: as_address(map_field(map, "attacker")?)?,
+            defender_points: as_i128(map_field(map, "defender_points")?)?,
+            attacker_points: as_i128(map_field(map, "attacker_points")?)?,
+            moves_made: as_u32(map_field(map, "moves_made")?)?,
+            hits: as_u32(map_field(map, "hits")?)?,
+            misses: as_u32(map_field(map, "misses")?)?,
+            game_started: as_bool(map_field(map, "game_started")?)?,
+            game_ended: as_bool(map_field(map, "game_ended")?)?,
+            winner: match map_field(map, "winner")? {
+                ScVal::Void => None,
+                winner => Some(as_address(winner)?),
+            },
+            schema_version: match map_field(map, "schema_version") {
+                Ok(version) => as_u32(version)?,
+                Err(_) => legacy_schema(),
+            },
+            last_active_ledger: match map_field(map, "last_active_ledger") {
+                Ok(ledger) => as_u32(ledger)?,
+                Err(_) => 0,
+            },
+            grid_size: match map_field(map, "grid_size") {
+                Ok(size) => as_u32(size)?,
+                Err(_) => default_grid_size(),
+            },
+            trap_count: match map_field(map, "trap_count") {
+                Ok(count) => as_u32(count)?,
+                Err(_) => 0,
+            },
+        })
+    }
+}
+
+impl TryFrom<&Game> for ScVal {
+    type Error = anyhow::Error;
+
+    fn try_from(game: &Game) -> Result<Self> {
+        let mut fields = vec![
+            ("defender", address(&game.defender)?),
+            ("attacker", address(&game.attacker)?),
+            ("defender_points", game.defender_points.into()),
+            ("attacker_points", game.attacker_points.into()),
+            ("moves_made", game.moves_made.into()),
+            ("hits", game.hits.into()),
+            ("misses", game.misses.into()),
+            ("game_started", game.game_started.into()),
+            ("game_ended", game.game_ended.into()),
+            (
+                "winner",
+                match &game.winner {
+                    Some(winner) => address(winner)?,
+                    None => ScVal::Void,
+                },
+            ),
+        ];
+        if game.schema_version != legacy_schema() {
+            fields.push(("schema_version", game.schema_version.into()));
+        }
+        if game.schema_version >= ACTIVITY_SCHEMA {
+            fields.push(("last_active_ledger", game.last_active_ledger.into()));
+        }
+        if game.schema_version >= GRID_SIZE_SCHEMA {
+            fields.push(("grid_size", game.grid_size.into()));
+        }
+        if game.schema_version >= TRAP_COUNT_SCHEMA {
+            fields.push(("trap_count", game.trap_count.into()));
+        }
+        map(fields)
+    }
+}
+
+impl TryFrom<&ScVal> for Move {
+    type Error = anyhow::Error;
+
+    fn try_from(val: &ScVal) -> Result<Self> {
+        let map = as_map(val, "Move")?;
+        Ok(Move {
+            x: as_u32(map_field(map, "x")?)?,
+            y: as_u32(map_field(map, "y")?)?,
+            is_hit: as_bool(map_field(map, "is_hit")?)?,
+            verified: as_bool(map_field(map, "verified")?)?,
+        })
+    }
+}
+
+impl TryFrom<&Move> for ScVal {
+    type Error = anyhow::Error;
+
+    fn try_from(mv: &Move) -> Result<Self> {
+        map(vec![
+            ("x", mv.x.into()),
+            ("y", mv.y.into()),
+            ("is_hit", mv.is_hit.into()),
+            ("verified", mv.verified.into()),
+        ])
+    }
+}
+
+impl TryFrom<&ScVal> for PendingMove {
+    type Error = anyhow::Error;
+
+    fn try_from(val: &ScVal) -> Result<Self> {
+        let map = as_map(val, "PendingMove")?;
+        Ok(PendingMove {
+            x: as_u32(map_field(map, "x")?)?,
+            y: as_u32(map_field(map, "y")?)?,
+        })
+    }
+}
+
+impl TryFrom<&PendingMove> for ScVal {
+    type Error = anyhow::Error;
+
+    fn try_from(pending: &PendingMove) -> Result<Self> {
+        map(vec![("x", pending.x.into()), ("y", pending.y.into())])
+    }
+}
+
+impl TryFrom<&ScVal> for Sponsorship {
+    type Error = anyhow::Error;
+
+    fn try_from(val: &ScVal) -> Result<Self> {
+        let map = as_map(val, "Sponsorship")?;
+        Ok(Sponsorship {
+            sponsor: as_address(map_field(map, "sponsor")?)?,
+            player: as_address(map_field(map, "player")?)?,
+            token: as_address(map_field(map, "token")?)?,
+            amount: as_i128(map_field(map, "amount")?)?,
+            player_share_bps: as_u32(map_field(map, "player_share_bps")?)?,
+        })
+    }
+}
+
+impl TryFrom<&Sponsorship> for ScVal {
+    type Error = anyhow::Error;
+
+    fn try_from(sponsorship: &Sponsorship) -> Result<Self> {
+        map(vec![
+            ("sponsor", address(&sponsorship.sponsor)?),
+            ("player", address(&sponsorship.player)?),
+            ("token", address(&sponsorship.token)?),
+            ("amount", sponsorship.amount.into()),
+            ("player_share_bps", sponsorship.player_share_bps.into()),
+        ])
+    }
+}
+
+impl TryFrom<&ScVal> for GridSetup {
+    type Error = anyhow::Error;
+
+    fn try_from(val: &ScVal) -> Result<Self> {
+        let map = as_map(val, "GridSetup")?;
+        let bytes = |name: &str| -> Result<Vec<u8>> {
+            match map_field(map, name)? {
+                ScVal::Bytes(bytes) => Ok(bytes.to_vec()),
+                other => bail!("expected {name} bytes, found {}", other.name()),
+            }
+        };
+        Ok(GridSetup {
+            grid_commitment: bytes("grid_commitment")?.as_slice().try_into()?,
+            proof: bytes("proof")?,
+        })
+    }
+}
+
+impl TryFrom<&GridSetup> for ScVal {
+    type Error = anyhow::Error;
+
+    fn try_from(setup: &GridSetup) -> Result<Self> {
+        map(vec![
+            (
+                "grid_commitment",
+                ScVal::Bytes(ScBytes(setup.grid_commitment.to_vec().try_into()?)),
+            ),
+            (
+                "proof",
+                ScVal::Bytes(ScBytes(setup.proof.clone().try_into()?)),
+            ),
+        ])
+    }
+}
+
+impl TryFrom<&ScVal> for ContractConfig {
+    type Error = anyhow::Error;
+
+    fn try_from(val: &ScVal) -> Result<Self> {
+        let map = as_map(val, "ContractConfig")?;
+        let u32_field = |name: &str| as_u32(map_field(map, name)?);
+        Ok(ContractConfig {
+            game_ttl_ledgers: u32_field("game_ttl_ledgers")?,
+            emergency_delay_ledgers: u32_field("emergency_delay_ledgers")?,
+            proposal_expiry_ledgers: u32_field("proposal_expiry_ledgers")?,
+            max_active_games: u32_field("max_active_games")?,
+            min_points: as_i128(map_field(map, "min_points")?)?,
+            max_points: as_i128(map_field(map, "max_points")?)?,
+            expiry_ledgers: u32_field("expiry_ledgers")?,
+            expiry_policy: ExpiryPolicy::try_from(map_field(map, "expiry_policy")?)?,
+            appeal_window_ledgers: u32_field("appeal_window_ledgers")?,
+            claim_delay_ledgers: u32_field("claim_delay_ledgers")?,
+            reject_reused_roots: as_bool(map_field(map, "reject_reused_roots")?)?,
+            trap_count: u32_field("trap_count")?,
+            move_gap_ledgers: u32_field("move_gap_ledgers")?,
+            grid_size: u32_field("grid_size")?,
+            progressive_payout_bps: u32_field("progressive_payout_bps")?,
+            bonus_curve: PayoutCurve::try_from(map_field(map, "bonus_curve")?)?,
+            streak_bonus: StreakBonus::try_from(map_field(map, "streak_bonus")?)?,
+            min_games_completed: u32_field("min_games_completed")?,
+        })
+    }
+}
+
+impl TryFrom<&ContractConfig> for ScVal {
+    type Error = anyhow::Error;
+
+    fn try_from(config: &ContractConfig) -> Result<Self> {
+        map(vec![
+            ("game_ttl_ledgers", config.game_ttl_ledgers.into()),
+            (
+                "emergency_delay_ledgers",
+                config.emergency_delay_ledgers.into(),
+            ),
+            (
+                "proposal_expiry_ledgers",
+                config.proposal_expiry_ledgers.into(),
+            ),
+            ("max_active_games", config.max_active_games.into()),
+            ("min_points", config.min_points.into()),
+            ("max_points", config.max_points.into()),
+            ("expiry_ledgers", config.expiry_ledgers.into()),
+            ("expiry_policy", ScVal::try_from(config.expiry_policy)?),
+            ("appeal_window_ledgers", config.appeal_window_ledgers.into()),
+            ("claim_delay_ledgers", config.claim_delay_ledgers.into()),
+            ("reject_reused_roots", config.reject_reused_roots.into()),
+            ("trap_count", config.trap_count.into()),
+            ("move_gap_ledgers", config.move_gap_ledgers.into()),
+            ("grid_size", config.grid_size.into()),
+            (
+                "progressive_payout_bps",
+                config.progressive_payout_bps.into(),
+            ),
+            ("bonus_curve", ScVal::try_from(&config.bonus_curve)?),
+            ("streak_bonus", ScVal::try_from(&config.streak_bonus)?),
+            ("min_games_completed", config.min_games_completed.into()),
+        ])
+    }
+}
+
+impl TryFrom<&ScVal> for PayoutCurve {
+    type Error = anyhow::Error;
+
+    fn try_from(val: &ScVal) -> Result<Self> {
+        let map = as_map(val, "PayoutCurve")?;
+        let u32_field = |name: &str| as_u32(map_field(map, name)?);
+        Ok(PayoutCurve {
+            max_bonus_bps: u32_field("max_bonus_bps")?,
+            threshold_bps: u32_field("threshold_bps")?,
+            exponent: u32_field("exponent")?,
+        })
+    }
+}
+
+impl TryFrom<&PayoutCurve> for ScVal {
+    type Error = anyhow::Error;
+
+    fn try_from(curve: &PayoutCurve) -> Result<Self> {
+        map(vec![
+            ("max_bonus_bps", curve.max_bonus_bps.into()),
+            ("threshold_bps", curve.threshold_bps.into()),
+            ("exponent", curve.exponent.into()),
+        ])
+    }
+}
+
+impl TryFrom<&ScVal> for StreakBonus {
+    type Error = anyhow::Error;
+
+    fn try_from(val: &ScVal) -> Result<Self> {
+        let map = as_map(val, "StreakBonus")?;
+        let u32_field = |name: &str| as_u32(map_field(map, name)?);
+        Ok(StreakBonus {
+            min_streak: u32_field("min_streak")?,
+            fee_rebate_bps: u32_field("fee_rebate_bps")?,
+        })
+    }
+}
+
+impl TryFrom<&StreakBonus> for ScVal {
+    type Error = anyhow::Error;
+
+    fn try_from(bonus: &StreakBonus) -> Result<Self> {
+        map(vec![
+            ("min_streak", bonus.min_streak.into()),
+            ("fee_rebate_bps", bonus.fee_rebate_bps.into()),
+        ])
+    }
+}
+
+impl TryFrom<&ScVal> for ExpiryPolicy {
+    type Error = anyhow::Error;
+
+    fn try_from(val: &ScVal) -> Result<Self> {
+        let ScVal::Vec(Some(items)) = val else {
+            bail!("expected ExpiryPolicy vec, found {}", val.name());
+        };
+        let policy = match items.as_slice() {
+            [name] if *name == symbol("DefenderWins")? => ExpiryPolicy::DefenderWins,
+            [name] if *name == symbol("AttackerWins")? => ExpiryPolicy::AttackerWins,
+            [name] if *name == symbol("Draw")? => ExpiryPolicy::Draw,
+            [name] if *name == symbol("Refund")? => ExpiryPolicy::Refund,
+            _ => bail!("unknown ExpiryPolicy {val:?}"),
+        };
+        Ok(policy)
+    }
+}
+
+impl TryFrom<ExpiryPolicy> for ScVal {
+    type Error = anyhow::Error;
+
+    fn try_from(policy: ExpiryPolicy) -> Result<Self> {
+        let name = match policy {
+            ExpiryPolicy::DefenderWins => "DefenderWins",
+            ExpiryPolicy::AttackerWins => "AttackerWins",
+            ExpiryPolicy::Draw => "Draw",
+            ExpiryPolicy::Refund => "Refund",
+        };
+        variant(name, None)
+    }
+}
+
+impl TryFrom<&ScVal> for Deadlines {
+    type Error = anyhow::Error;
+
+    fn try_from(val: &ScVal) -> Result<Self> {
+        let map = as_map(val, "Deadlines")?;
+        let ledger = |name: &str| -> Result<Option<u32>> {
+            match map_field(map, name)? {
+                ScVal::Void => Ok(None),
+                ledger => Ok(Some(as_u32(ledger)?)),
+            }
+        };
+        Ok(Deadlines {
+            expires_after: ledger("expires_after")?,
+            appeal_until: ledger("appeal_until")?,
+            claimable_from: ledger("claimable_from")?,
+            emergency_withdraw_from: ledger("emergency_withdraw_from")?,
+        })
+    }
+}
+
+impl TryFrom<&Deadlines> for ScVal {
+    type Error = anyhow::Error;
+
+    fn try_from(deadlines: &Deadlines) -> Result<Self> {
+        let ledger = |ledger: Option<u32>| ledger.map_or(ScVal::Void, ScVal::U32);
+        map(vec![
+            ("expires_after", ledger(deadlines.expires_after)),
+            ("appeal_until", ledger(deadlines.appeal_until)),
+            ("claimable_from", ledger(deadlines.claimable_from)),
+            (
+                "emergency_withdraw_from",
+                ledger(deadlines.emergency_withdraw_from),
+            ),
+        ])
+    }
+}
+
+impl TryFrom<&ScVal> for FullState {
+    type Error = anyhow::Error;
+
+    /// The contract returns the pending move as a vec of at most one
+    fn try_from(val: &ScVal) -> Result<Self> {
+        let map = as_map(val, "FullState")?;
+        let items = |name: &str| -> Result<&[ScVal]> {
+            match map_field(map, name)? {
+                ScVal::Vec(Some(items)) => Ok(items.as_slice()),
+                other => bail!("expected {name} vec, found {}", other.name()),
+            }
+        };
+        let pending_move = match items("pending_move")? {
+            [] => None,
+            [pending] => Some(PendingMove::try_from(pending)?),
+            _ => bail!("more than one pending move"),
+        };
+        Ok(FullState {
+            game: Game::try_from(map_field(map, "game")?)?,
+            config: ContractConfig::try_from(map_field(map, "config")?)?,
+            pending_move,
+            deadlines: Deadlines::try_from(map_field(map, "deadlines")?)?,
+            recent_moves: items("recent_moves")?
+                .iter()
+                .map(Move::try_from)
+                .collect::<Result<_>>()?,
+        })
+    }
+}
+
+impl TryFrom<&ScVal> for WinPolicy {
+    type Error = anyhow::Error;
+
+    fn try_from(val: &ScVal) -> Result<Self> {
+        let ScVal::Vec(Some(items)) = val else {
+            bail!("expected WinPolicy vec, found {}", val.name());
+        };
+        match items.as_slice() {
+            [name] if *name == symbol("MajorityHits")? => Ok(WinPolicy::MajorityHits),
+            _ => bail!("unknown WinPolicy {val:?}"),
+        }
+    }
+}
+
+impl TryFrom<WinPolicy> for ScVal {
+    type Error = anyhow::Error;
+
+    fn try_from(policy: WinPolicy) -> Result<Self> {
+        match policy {
+            WinPolicy::MajorityHits => variant("MajorityHits", None),
+        }
+    }
+}
+
+impl TryFrom<&ScVal> for Rules {
+    type Error = anyhow::Error;
+
+    fn try_from(val: &ScVal) -> Result<Self> {
+        let map = as_map(val, "Rules")?;
+        let u32_field = |name: &str| as_u32(map_field(map, name)?);
+        Ok(Rules {
+            grid_size: u32_field("grid_size")?,
+            trap_count: u32_field("trap_count")?,
+            win_policy: WinPolicy::try_from(map_field(map, "win_policy")?)?,
+            expiry_policy: ExpiryPolicy::try_from(map_field(map, "expiry_policy")?)?,
+            expiry_ledgers: u32_field("expiry_ledgers")?,
+            move_gap_ledgers: u32_field("move_gap_ledgers")?,
+            appeal_window_ledgers: u32_field("appeal_window_ledgers")?,
+            claim_delay_ledgers: u32_field("claim_delay_ledgers")?,
+            emergency_delay_ledgers: u32_field("emergency_delay_ledgers")?,
+            channel_challenge_ledgers: u32_field("channel_challenge_ledgers")?,
+            claim_challenge_ledgers: u32_field("claim_challenge_ledgers")?,
+            min_points: as_i128(map_field(map, "min_points")?)?,
+            max_points: as_i128(map_field(map, "max_points")?)?,
+            protocol_fee_bps: u32_field("protocol_fee_bps")?,
+            referral_fee_bps: u32_field("referral_fee_bps")?,
+            progressive_payout_bps: u32_field("progressive_payout_bps")?,
+            bonus_curve: PayoutCurve::try_from(map_field(map, "bonus_curve")?)?,
+            streak_bonus: StreakBonus::try_from(map_field(map, "streak_bonus")?)?,
+            min_games_completed: u32_field("min_games_completed")?,
+            verifier: as_address(map_field(map, "verifier")?)?,
+            setup_verifier: as_address(map_field(map, "setup_verifier")?)?,
+        })
+    }
+}
+
+impl TryFrom<&Rules> for ScVal {
+    type Error = anyhow::Error;
+
+    fn try_from(rules: &Rules) -> Result<Self> {
+        map(vec![
+            ("grid_size", rules.grid_size.into()),
+            ("trap_count", rules.trap_count.into()),
+            ("win_policy", ScVal::try_from(rules.win_policy)?),
+            ("expiry_policy", ScVal::try_from(rules.expiry_policy)?),
+            ("expiry_ledgers", rules.expiry_ledgers.into()),
+            ("move_gap_ledgers", rules.move_gap_ledgers.into()),
+            ("appeal_window_ledgers", rules.appeal_window_ledgers.into()),
+            ("claim_delay_ledgers", rules.claim_delay_ledgers.into()),
+            (
+                "emergency_delay_ledgers",
+                rules.emergency_delay_ledgers.into(),
+            ),
+            (
+                "channel_challenge_ledgers",
+                rules.channel_challenge_ledgers.into(),
+            ),
+            (
+                "claim_challenge_ledgers",
+                rules.claim_challenge_ledgers.into(),
+            ),
+            ("min_points", rules.min_points.into()),
+            ("max_points", rules.max_points.into()),
+            ("protocol_fee_bps", rules.protocol_fee_bps.into()),
+            ("referral_fee_bps", rules.referral_fee_bps.into()),
+            (
+                "progressive_payout_bps",
+                rules.progressive_payout_bps.into(),
+            ),
+            ("bonus_curve", ScVal::try_from(&rules.bonus_curve)?),
+            ("streak_bonus", ScVal::try_from(&rules.streak_bonus)?),
+            ("min_games_completed", rules.min_games_completed.into()),
+            ("verifier", address(&rules.verifier)?),
+            ("setup_verifier", address(&rules.setup_verifier)?),
+        ])
+    }
+}
+
+impl TryFrom<&ScVal> for Optimistic {
+    type Error = anyhow::Error;
+
+    fn try_from(val: &ScVal) -> Result<Self> {
+        let map = as_map(val, "Optimistic")?;
+        let claims = as_map(map_field(map, "claims")?, "claims")?
+            .iter()
+            .map(|entry| Ok((as_u32(&entry.key)?, as_u32(&entry.val)?)))
+            .collect::<Result<_>>()?;
+        Ok(Optimistic {
+            claims,
+            challenged: match map_field(map, "challenged")? {
+                ScVal::Void => None,
+                index => Some(as_u32(index)?),
+            },
+            prove_until: as_u32(map_field(map, "prove_until")?)?,
+            token: as_address(map_field(map, "token")?)?,
+            bond: as_i128(map_field(map, "bond")?)?,
+            bonded: as_i128(map_field(map, "bonded")?)?,
+        })
+    }
+}
+
+impl TryFrom<&Optimistic> for ScVal {
+    type Error = anyhow::Error;
+
+    fn try_from(optimistic: &Optimistic) -> Result<Self> {
+        let claims = optimistic
+            .claims
+            .iter()
+            .map(|(&index, &ledger)| (ScVal::U32(index), ScVal::U32(ledger)));
+        map(vec![
+            (
+                "claims",
+                ScVal::Map(Some(ScMap::sorted_from_pairs(claims)?)),
+            ),
+            (
+                "challenged",
+                optimistic.challenged.map_or(ScVal::Void, ScVal::U32),
+            ),
+            ("prove_until", optimistic.prove_until.into()),
+            ("token", address(&optimistic.token)?),
+            ("bond", optimistic.bond.into()),
+            ("bonded", optimistic.bonded.into()),
+        ])
+    }
+}
+
+impl TryFrom<&FullState> for ScVal {
+    type Error = anyhow::Error;
+
+    fn try_from(state: &FullState) -> Result<Self> {
+        let pending_move = state
+            .pending_move
+            .iter()
+            .map(ScVal::try_from)
+            .collect::<Result<Vec<_>>>()?;
+        let recent_moves = state
+            .recent_moves
+            .iter()
+            .map(ScVal::try_from)
+            .collect::<Result<Vec<_>>>()?;
+        map(vec![
+            ("game", ScVal::try_from(&state.game)?),
+            ("config", ScVal::try_from(&state.config)?),
+            (
+                "pending_move",
+                ScVal::Vec(Some(ScVec(pending_move.try_into()?))),
+            ),
+            ("deadlines", ScVal::try_from(&state.deadlines)?),
+            (
+                "recent_moves",
+                ScVal::Vec(Some(ScVec(recent_moves.try_into()?))),
+            ),
+        ])
+    }
+}
+
+impl GameConfig {
+    /// Arguments of `__constructor(admin, game_hub, verifier, setup_verifier)`
+    pub fn constructor_args(&self) -> Result<Vec<ScVal>> {
+        Ok(vec![
+            address(&self.admin)?,
+            address(&self.game_hub)?,
+            address(&self.verifier)?,
+            address(&self.setup_verifier)?,
+        ])
+    }
+
+    /// Read the configuration from the contract instance's storage map
+    pub fn from_instance_storage(storage: &ScMap) -> Result<Self> {
+        let field = |key: DataKey| -> Result<String> {
+            let key = ScVal::try_from(key)?;
+            match storage.iter().find(|entry| entry.key == key) {
+                Some(entry) => as_address(&entry.val),
+                None => bail!("instance storage has no {key:?} entry"),
+            }
+        };
+        Ok(GameConfig {
+            admin: field(DataKey::Admin)?,
+            game_hub: field(DataKey::GameHubAddress)?,
+            verifier: field(DataKey::VerifierAddress)?,
+            setup_verifier: field(DataKey::SetupVerifierAddress)?,
+        })
+    }
+}
+
+impl TryFrom<DataKey> for ScVal {
+    type Error = anyhow::Error;
+
+    fn try_from(key: DataKey) -> Result<Self> {
+        match key {
+            DataKey::Game(session_id) => variant("Game", Some(session_id.into())),
+            DataKey::Moves(session_id) => variant("Moves", Some(session_id.into())),
+            DataKey::PendingMove(session_id) => variant("PendingMove", Some(session_id.into())),
+            DataKey::GameHubAddress => variant("GameHubAddress", None),
+            DataKey::VerifierAddress => variant("VerifierAddress", None),
+            DataKey::Admin => variant("Admin", None),
+            DataKey::TrophyAddress => variant("TrophyAddress", None),
+            DataKey::Sponsorships(session_id) => variant("Sponsorships", Some(session_id.into())),
+            DataKey::Fees => variant("Fees", None),
+            DataKey::AdminSet => variant("AdminSet", None),
+            DataKey::Operator(operator) => variant("Operator", Some(address(&operator)?)),
+            DataKey::Paused => variant("Paused", None),
+            DataKey::Config => variant("Config", None),
+            DataKey::ActiveGames => variant("ActiveGames", None),
+            DataKey::GlobalStats => variant("GlobalStats", None),
+            DataKey::Heatmap => variant("Heatmap", None),
+            DataKey::Arbitration(session_id) => variant("Arbitration", Some(session_id.into())),
+            DataKey::Claim(session_id) => variant("Claim", Some(session_id.into())),
+            DataKey::GridRoot(session_id) => variant("GridRoot", Some(session_id.into())),
+            DataKey::ProposalCount => variant("ProposalCount", None),
+            DataKey::Proposal(proposal_id) => variant("Proposal", Some(proposal_id.into())),
+            DataKey::EmergencyWithdraw(session_id) => {
+                variant("EmergencyWithdraw", Some(session_id.into()))
+            }
+            DataKey::Referrer(player) => variant("Referrer", Some(address(&player)?)),
+            DataKey::BonusPool(token) => variant("BonusPool", Some(address(&token)?)),
+            DataKey::Practice(player) => variant("Practice", Some(address(&player)?)),
+            DataKey::PracticeStats(player) => variant("PracticeStats", Some(address(&player)?)),
+            DataKey::Daily(day) => variant("Daily", Some(day.into())),
+            DataKey::DailyEntry(day, player) => Ok(ScVal::Vec(Some(ScVec(
+                vec![symbol("DailyEntry")?, day.into(), address(&player)?].try_into()?,
+            )))),
+            DataKey::DailyLeaderboard(day) => variant("DailyLeaderboard", Some(day.into())),
+            DataKey::Streak(player) => variant("Streak", Some(address(&player)?)),
+            DataKey::GamesCompleted(player) => variant("GamesCompleted", Some(address(&player)?)),
+            DataKey::ReferralRewards(referrer, token) => Ok(ScVal::Vec(Some(ScVec(
+                vec![
+                    symbol("ReferralRewards")?,
+                    address(&referrer)?,
+                    address(&token)?,
+                ]
+                .try_into()?,
+            )))),
+            DataKey::UsedRoot(defender, root) => Ok(ScVal::Vec(Some(ScVec(
+                vec![
+                    symbol("UsedRoot")?,
+                    address(&defender)?,
+                    ScVal::Bytes(ScBytes(root.to_vec().try_into()?)),
+                ]
+                .try_into()?,
+            )))),
+            DataKey::SetupVerifierAddress => variant("SetupVerifierAddress", None),
+            DataKey::GridSetup(session_id) => variant("GridSetup", Some(session_id.into())),
+            DataKey::UsedSetup(commitment) => variant(
+                "UsedSetup",
+                Some(ScVal::Bytes(ScBytes(commitment.to_vec().try_into()?))),
+            ),
+            DataKey::ArchiveAddress => variant("ArchiveAddress", None),
+            DataKey::LastMoves(session_id) => variant("LastMoves", Some(session_id.into())),
+            DataKey::AbortVotes(session_id) => variant("AbortVotes", Some(session_id.into())),
+            DataKey::Channel(session_id) => variant("Channel", Some(session_id.into())),
+            DataKey::Optimistic(session_id) => variant("Optimistic", Some(session_id.into())),
+            DataKey::Frozen(session_id) => variant("Frozen", Some(session_id.into())),
+        }
+    }
+}
+
+impl TryFrom<&ScVal> for DataKey {
+    type Error = anyhow::Error;
+
+    fn try_from(val: &ScVal) -> Result<Self> {
+        let ScVal::Vec(Some(items)) = val else {
+            bail!("expected DataKey vec, found {}", val.name());
+        };
+        let key = match items.as_slice() {
+            [name, ScVal::U32(session_id)] if *name == symbol("Game")? => {
+                DataKey::Game(*session_id)
+            }
+            [name, ScVal::U32(session_id)] if *name == symbol("Moves")? => {
+                DataKey::Moves(*session_id)
+            }
+            [name, ScVal::U32(session_id)] if *name == symbol("PendingMove")? => {
+                DataKey::PendingMove(*session_id)
+            }
+            [name, ScVal::U32(session_id)] if *name == symbol("Sponsorships")? => {
+                DataKey::Sponsorships(*session_id)
+            }
+            [name, ScVal::U32(session_id)] if *name == symbol("EmergencyWithdraw")? => {
+                DataKey::EmergencyWithdraw(*session_id)
+            }
+            [name] if *name == symbol("GameHubAddress")? => DataKey::GameHubAddress,
+            [name] if *name == symbol("VerifierAddress")? => DataKey::VerifierAddress,
+            [name] if *name == symbol("Admin")? => DataKey::Admin,
+            [name] if *name == symbol("TrophyAddress")? => DataKey::TrophyAddress,
+            [name] if *name == symbol("Fees")? => DataKey::Fees,
+            [name] if *name == symbol("AdminSet")? => DataKey::AdminSet,
+            [name, operator] if *name == symbol("Operator")? => {
+                DataKey::Operator(as_address(operator)?)
+            }
+            [name] if *name == symbol("Paused")? => DataKey::Paused,
+            [name] if *name == symbol("Config")? => DataKey::Config,
+            [name] if *name == symbol("ActiveGames")? => DataKey::ActiveGames,
+            [name] if *name == symbol("GlobalStats")? => DataKey::GlobalStats,
+            [name] if *name == symbol("Heatmap")? => DataKey::Heatmap,
+            [name, ScVal::U32(session_id)] if *name == symbol("Arbitration")? => {
+                DataKey::Arbitration(*session_id)
+            }
+            [name, ScVal::U32(session_id)] if *name == symbol("Claim")? => {
+                DataKey::Claim(*session_id)
+            }
+            [name, ScVal::U32(session_id)] if *name == symbol("GridRoot")? => {
+                DataKey::GridRoot(*session_id)
+            }
+            [name, defender, ScVal::Bytes(root)] if *name == symbol("UsedRoot")? => {
+                DataKey::UsedRoot(as_address(defender)?, root.as_slice().try_into()?)
+            }
+            [name] if *name == symbol("SetupVerifierAddress")? => DataKey::SetupVerifierAddress,
+            [name, ScVal::U32(session_id)] if *name == symbol("GridSetup")? => {
+                DataKey::GridSetup(*session_id)
+            }
+            [name, ScVal::Bytes(commitment)] if *name == symbol("UsedSetup")? => {
+                DataKey::UsedSetup(commitment.as_slice().try_into()?)
+            }
+            [name] if *name == symbol("ArchiveAddress")? => DataKey::ArchiveAddress,
+            [name, ScVal::U32(session_id)] if *name == symbol("LastMoves")? => {
+                DataKey::LastMoves(*session_id)
+            }
+            [name, ScVal::U32(session_id)] if *name == symbol("AbortVotes")? => {
+                DataKey::AbortVotes(*session_id)
+            }
+            [name, ScVal::U32(session_id)] if *name == symbol("Channel")? => {
+                DataKey::Channel(*session_id)
+            }
+            [name, ScVal::U32(session_id)] if *name == symbol("Optimistic")? => {
+                DataKey::Optimistic(*session_id)
+            }
+            [name, ScVal::U32(session_id)] if *name == symbol("Frozen")? => {
+                DataKey::Frozen(*session_id)
+            }
+            [name] if *name == symbol("ProposalCount")? => DataKey::ProposalCount,
+            [name, ScVal::U32(proposal_id)] if *name == symbol("Proposal")? => {
+                DataKey::Proposal(*proposal_id)
+            }
+            [name, player] if *name == symbol("Referrer")? => {
+                DataKey::Referrer(as_address(player)?)
+            }
+            [name, token] if *name == symbol("BonusPool")? => {
+                DataKey::BonusPool(as_address(token)?)
+            }
+            [name, player] if *name == symbol("Practice")? => {
+                DataKey::Practice(as_address(player)?)
+            }
+            [name, player] if *name == symbol("PracticeStats")? => {
+                DataKey::PracticeStats(as_address(player)?)
+            }
+            [name, ScVal::U32(day)] if *name == symbol("Daily")? => DataKey::Daily(*day),
+            [name, ScVal::U32(day), player] if *name == symbol("DailyEntry")? => {
+                DataKey::DailyEntry(*day, as_address(player)?)
+            }
+            [name, ScVal::U32(day)] if *name == symbol("DailyLeaderboard")? => {
+                DataKey::DailyLeaderboard(*day)
+            }
+            [name, player] if *name == symbol("Streak")? => DataKey::Streak(as_address(player)?),
+            [name, player] if *name == symbol("GamesCompleted")? => {
+                DataKey::GamesCompleted(as_address(player)?)
+            }
+            [name, referrer, token] if *name == symbol("ReferralRewards")? => {
+                DataKey::ReferralRewards(as_address(referrer)?, as_address(token)?)
+            }
+            _ => bail!("unknown DataKey {val:?}"),
+        };
+        Ok(key)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use stellar_xdr::curr::ScMapEntry;
+
+    const ACCOUNT: &str = "GAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAWHF";
+
+    #[test]
+    fn test_decode_move() {
+        let entries = vec![
+            ScMapEntry {
+                key: symbol("is_hit").unwrap(),
+                val: ScVal::Bool(true),
+            },
+            ScMapEntry {
+                key: symbol("verified").unwrap(),
+                val: ScVal::Bool(true),
+            },
+            ScMapEntry {
+                key: symbol("x").unwrap(),
+                val: ScVal::U32(2),
+            },
+            ScMapEntry {
+                key: symbol("y").unwrap(),
+                val: ScVal::U32(5),
+            },
+        ];
+        let val = ScVal::Map(Some(ScMap(entries.try_into().unwrap())));
+
+        let decoded = Move::try_from(&val).unwrap();
+        let expected = Move {
+            x: 2,
+            y: 5,
+            is_hit: true,
+            verified: true,
+        };
+        assert_eq!(decoded, expected);
+        assert_eq!(ScVal::try_from(&expected).unwrap(), val);
+    }
+
+    #[test]
+    fn test_game_round_trip() {
+        let game = Game {
+            defender: ACCOUNT.into(),
+            attacker: ACCOUNT.into(),
+            defender_points: -5,
+            attacker_points: i128::MAX,
+            moves_made: 3,
+            hits: 1,
+            misses: 2,
+            game_started: true,
+            game_ended: true,
+            winner: Some(ACCOUNT.into()),
+            schema_version: GAME_SCHEMA_VERSION,
+            last_active_ledger: 120,
+            grid_size: 16,
+            trap_count: 40,
+        };
+        let val = ScVal::try_from(&game).unwrap();
+        assert_eq!(Game::try_from(&val).unwrap(), game);
+
+        let json = serde_json::to_string(&game).unwrap();
+        assert_eq!(serde_json::from_str::<Game>(&json).unwrap(), game);
+
+        // Records and JSON from before schema versions are schema 1
+        let legacy = Game {
+            schema_version: 1,
+            last_active_ledger: 0,
+            grid_size: 8,
+            trap_count: 0,
+            ..game
+        };
+        let val = ScVal::try_from(&legacy).unwrap();
+        assert_eq!(as_map(&val, "Game").unwrap().len(), 10);
+        assert_eq!(Game::try_from(&val).unwrap(), legacy);
+        let json = serde_json::to_string(&legacy).unwrap().replace(
+            r#","schema_version":1,"last_active_ledger":0,"grid_size":8,"trap_count":0"#,
+            "",
+        );
+        assert_eq!(serde_json::from_str::<Game>(&json).unwrap(), legacy);
+    }
+
+    #[test]
+    fn test_sponsorship_round_trip() {
+        let sponsorship = Sponsorship {
+            sponsor: ACCOUNT.into(),
+            player: ACCOUNT.into(),
+            token: "CB4VZAT2U3UC6XFK3N23SKRF2NDCMP3QHJYMCHHFMZO7MRQO6DQ2EMYG".into(),
+            amount: 500,
+            player_share_bps: 2_500,
+        };
+        let val = ScVal::try_from(&sponsorship).unwrap();
+        assert_eq!(Sponsorship::try_from(&val).unwrap(), sponsorship);
+    }
+
+    #[test]
+    fn test_grid_setup_round_trip() {
+        let setup = GridSetup {
+            grid_commitment: [9; 32],
+            proof: vec![1, 2, 3],
+        };
+        let val = ScVal::try_from(&setup).unwrap();
+        assert_eq!(GridSetup::try_from(&val).unwrap(), setup);
+    }
+
+    #[test]
+    fn test_full_state_round_trip() {
+        let state = FullState {
+            game: Game {
+                defender: ACCOUNT.into(),
+                attacker: ACCOUNT.into(),
+                defender_points: 100,
+                attacker_points: 100,
+                moves_made: 1,
+                hits: 0,
+                misses: 1,
+                game_started: true,
+                game_ended: false,
+                winner: None,
+                schema_version: GAME_SCHEMA_VERSION,
+                last_active_ledger: 120,
+                grid_size: 8,
+                trap_count: 10,
+            },
+            config: ContractConfig {
+                game_ttl_ledgers: 518_400,
+                emergency_delay_ledgers: 51_840,
+                proposal_expiry_ledgers: 120_960,
+                max_active_games: 0,
+                min_points: 1,
+                max_points: i128::MAX,
+                expiry_ledgers: 17_280,
+                expiry_policy: ExpiryPolicy::DefenderWins,
+                appeal_window_ledgers: 17_280,
+                claim_delay_ledgers: 0,
+                reject_reused_roots: true,
+                trap_count: 10,
+                move_gap_ledgers: 0,
+                grid_size: 8,
+                progressive_payout_bps: 0,
+                bonus_curve: PayoutCurve {
+                    max_bonus_bps: 0,
+                    threshold_bps: 0,
+                    exponent: 1,
+                },
+                streak_bonus: StreakBonus {
+                    min_streak: 0,
+                    fee_rebate_bps: 0,
+                },
+                min_games_completed: 0,
+            },
+            pending_move: Some(PendingMove { x: 4, y: 2 }),
+            deadlines: Deadlines {
+                expires_after: Some(17_400),
+                emergency_withdraw_from: Some(51_960),
+                ..Deadlines::default()
+            },
+            recent_moves: vec![Move {
+                x: 0,
+                y: 0,
+                is_hit: false,
+                verified: true,
+            }],
+        };
+        let val = ScVal::try_from(&state).unwrap();
+        assert_eq!(FullState::try_from(&val).unwrap(), state);
+
+        let idle = FullState {
+            pending_move: None,
+            recent_moves: Vec::new(),
+            ..state
+        };
+        let val = ScVal::try_from(&idle).unwrap();
+        assert_eq!(FullState::try_from(&val).unwrap(), idle);
+    }
+
+    #[test]
+    fn test_rules_round_trip() {
+        let rules = Rules {
+            grid_size: 12,
+            trap_count: 20,
+            win_policy: WinPolicy::MajorityHits,
+            expiry_policy: ExpiryPolicy::Refund,
+            expiry_ledgers: 0,
+            move_gap_ledgers: 5,
+            appeal_window_ledgers: 17_280,
+            claim_delay_ledgers: 0,
+            emergency_delay_ledgers: 51_840,
+            channel_challenge_ledgers: 17_280,
+            claim_challenge_ledgers: 720,
+            min_points: 1,
+            max_points: i128::MAX,
+            protocol_fee_bps: 250,
+            referral_fee_bps: 0,
+            progressive_payout_bps: 100,
+            bonus_curve: PayoutCurve {
+                max_bonus_bps: 2_500,
+                threshold_bps: 5_000,
+                exponent: 2,
+            },
+            streak_bonus: StreakBonus {
+                min_streak: 3,
+                fee_rebate_bps: 5_000,
+            },
+            min_games_completed: 5,
+            verifier: ACCOUNT.into(),
+            setup_verifier: ACCOUNT.into(),
+        };
+        let val = ScVal::try_from(&rules).unwrap();
+        assert_eq!(Rules::try_from(&val).unwrap(), rules);
+    }
+
+    #[test]
+    fn test_optimistic_round_trip() {
+        let optimistic = Optimistic {
+            claims: BTreeMap::from([(0, 17_000), (1, 17_100)]),
+            challenged: Some(1),
+            prove_until: 17_820,
+            token: "CB4VZAT2U3UC6XFK3N23SKRF2NDCMP3QHJYMCHHFMZO7MRQO6DQ2EMYG".into(),
+            bond: 10,
+            bonded: 20,
+        };
+        let val = ScVal::try_from(&optimistic).unwrap();
+        assert_eq!(Optimistic::try_from(&val).unwrap(), optimistic);
+        let unchallenged = Optimistic {
+            claims: BTreeMap::new(),
+            challenged: None,
+            prove_until: 0,
+            bonded: 0,
+            ..optimistic
+        };
+        let val = ScVal::try_from(&unchallenged).unwrap();
+        assert_eq!(Optimistic::try_from(&val).unwrap(), unchallenged);
+    }
+
+    #[test]
+    fn test_data_key_round_trip() {
+        for key in [
+            DataKey::Game(7),
+            DataKey::Moves(7),
+            DataKey::Admin,
+            DataKey::Sponsorships(7),
+            DataKey::Fees,
+            DataKey::Referrer(ACCOUNT.into()),
+            DataKey::ReferralRewards(ACCOUNT.into(), ACCOUNT.into()),
+            DataKey::EmergencyWithdraw(7),
+            DataKey::AdminSet,
+            DataKey::Proposal(7),
+            DataKey::Operator(ACCOUNT.into()),
+            DataKey::Paused,
+            DataKey::Config,
+            DataKey::ActiveGames,
+            DataKey::GlobalStats,
+            DataKey::Heatmap,
+            DataKey::Arbitration(7),
+            DataKey::Claim(7),
+            DataKey::GridRoot(7),
+            DataKey::UsedRoot(ACCOUNT.into(), [1; 32]),
+            DataKey::SetupVerifierAddress,
+            DataKey::GridSetup(7),
+            DataKey::UsedSetup([1; 32]),
+            DataKey::ArchiveAddress,
+            DataKey::LastMoves(7),
+            DataKey::AbortVotes(7),
+            DataKey::Channel(7),
+            DataKey::Optimistic(7),
+            DataKey::Frozen(7),
+            DataKey::BonusPool(ACCOUNT.into()),
+            DataKey::Practice(ACCOUNT.into()),
+            DataKey::PracticeStats(ACCOUNT.into()),
+            DataKey::Daily(7),
+            DataKey::DailyEntry(7, ACCOUNT.into()),
+            DataKey::DailyLeaderboard(7),
+            DataKey::Streak(ACCOUNT.into()),
+            DataKey::GamesCompleted(ACCOUNT.into()),
+        ] {
+            let val = ScVal::try_from(key.clone()).unwrap();
+            assert_eq!(DataKey::try_from(&val).unwrap(), key);
+        }
+    }
+}
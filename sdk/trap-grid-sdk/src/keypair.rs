@@ -0,0 +1,63 @@
+//! Stellar ed25519 keypairs
+
+use anyhow::{anyhow, Result};
+use ed25519_dalek::{Signer, SigningKey};
+use zeroize::Zeroizing;
+
+/// An ed25519 Stellar keypair
+pub struct Keypair {
+    signing_key: SigningKey,
+}
+
+impl Keypair {
+    /// Generate a new random keypair
+    pub fn generate() -> Result<Self> {
+        let mut seed = Zeroizing::new([0u8; 32]);
+        getrandom::getrandom(seed.as_mut()).map_err(|e| anyhow!("no system randomness: {e}"))?;
+        Ok(Self {
+            signing_key: SigningKey::from_bytes(&seed),
+        })
+    }
+
+    /// Parse an `S...` secret key
+    pub fn from_secret(secret: &str) -> Result<Self> {
+        let key = stellar_strkey::ed25519::PrivateKey::from_string(secret.trim())
+            .map_err(|_| anyhow!("invalid secret key; expected an S... strkey"))?;
+        let seed = Zeroizing::new(key.0);
+        Ok(Self {
+            signing_key: SigningKey::from_bytes(&seed),
+        })
+    }
+
+    pub fn public_key(&self) -> [u8; 32] {
+        self.signing_key.verifying_key().to_bytes()
+    }
+
+    /// `G...` account address
+    pub fn address(&self) -> String {
+        stellar_strkey::ed25519::PublicKey(self.public_key()).to_string()
+    }
+
+    /// `S...` secret key
+    pub fn secret(&self) -> Zeroizing<String> {
+        Zeroizing::new(stellar_strkey::ed25519::PrivateKey(self.signing_key.to_bytes()).to_string())
+    }
+
+    pub fn sign(&self, payload: &[u8]) -> [u8; 64] {
+        self.signing_key.sign(payload).to_bytes()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_secret_round_trip() {
+        let keypair = Keypair::generate().unwrap();
+        let restored = Keypair::from_secret(&keypair.secret()).unwrap();
+        assert_eq!(restored.address(), keypair.address());
+        assert!(keypair.address().starts_with('G'));
+        assert!(Keypair::from_secret(&keypair.address()).is_err());
+    }
+}
@@ -0,0 +1,126 @@
+//! Trap layouts
+//!
+//! Layouts use the same JSON format as the dApp's grid export:
+//! `{ "trapValues": [0, 1, ...] }` with a cell per cell of the square grid,
+//! indexed by `x * grid_size + y` (64 cells on the default 8x8 grid), plus an
+//! optional `salt` (0x-prefixed field element) when one was derived.
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use trap_grid_encoding::{MAX_GRID_SIZE, MIN_GRID_SIZE};
+
+use crate::GRID_SIZE;
+
+/// Cells of the default grid
+pub const NUM_CELLS: usize = (GRID_SIZE * GRID_SIZE) as usize;
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct TrapLayout {
+    #[serde(rename = "trapValues")]
+    pub trap_values: Vec<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub salt: Option<String>,
+}
+
+impl TrapLayout {
+    pub fn load(path: &Path) -> Result<Self> {
+        let data = fs::read_to_string(path)
+            .with_context(|| format!("failed to read layout file {}", path.display()))?;
+        let layout: TrapLayout = serde_json::from_str(&data)
+            .with_context(|| format!("invalid layout file {}", path.display()))?;
+        layout.validate()?;
+        Ok(layout)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let data = serde_json::to_string(self)?;
+        fs::write(path, data + "\n")
+            .with_context(|| format!("failed to write layout file {}", path.display()))
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        grid_size(self.trap_values.len())?;
+        if let Some(index) = self.trap_values.iter().position(|v| *v > 1) {
+            bail!("trap value at cell {index} must be 0 or 1");
+        }
+        Ok(())
+    }
+
+    /// Side of the layout's grid; the layout must be valid
+    pub fn grid_size(&self) -> u32 {
+        grid_size(self.trap_values.len()).expect("layout of a square grid")
+    }
+
+    pub fn is_trap(&self, x: u32, y: u32) -> bool {
+        self.trap_values[cell_index(self.grid_size(), x, y)] == 1
+    }
+
+    pub fn trap_count(&self) -> usize {
+        self.trap_values.iter().filter(|v| **v == 1).count()
+    }
+}
+
+/// Side of the square grid with `cells` cells, which the circuits must support
+pub fn grid_size(cells: usize) -> Result<u32> {
+    let size = (MIN_GRID_SIZE..=MAX_GRID_SIZE).find(|size| (size * size) as usize == cells);
+    match size {
+        Some(size) => Ok(size),
+        None => bail!(
+            "trap layout must have a cell per cell of a {MIN_GRID_SIZE}x{MIN_GRID_SIZE} \
+             to {MAX_GRID_SIZE}x{MAX_GRID_SIZE} grid, found {cells} cells"
+        ),
+    }
+}
+
+/// Flattened cell index, matching the circuits' leaf index (`x * grid_size + y`)
+pub fn cell_index(grid_size: u32, x: u32, y: u32) -> usize {
+    (x * grid_size + y) as usize
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn layout_with_traps(traps: &[(u32, u32)]) -> TrapLayout {
+        let mut trap_values = vec![0; NUM_CELLS];
+        for (x, y) in traps {
+            trap_values[cell_index(GRID_SIZE, *x, *y)] = 1;
+        }
+        TrapLayout {
+            trap_values,
+            salt: None,
+        }
+    }
+
+    #[test]
+    fn test_layout_validation() {
+        assert!(layout_with_traps(&[(0, 0)]).validate().is_ok());
+        assert!(TrapLayout {
+            trap_values: vec![0; 63],
+            salt: None,
+        }
+        .validate()
+        .is_err());
+
+        let mut layout = layout_with_traps(&[]);
+        layout.trap_values[5] = 2;
+        assert!(layout.validate().is_err());
+
+        // Any supported square grid
+        let large = TrapLayout {
+            trap_values: vec![0; 256],
+            salt: None,
+        };
+        assert!(large.validate().is_ok());
+        assert_eq!(large.grid_size(), 16);
+        assert!(TrapLayout {
+            trap_values: vec![0; 289],
+            salt: None,
+        }
+        .validate()
+        .is_err());
+    }
+}
@@ -0,0 +1,94 @@
+//! # Trap Grid SDK
+//!
+//! Off-chain Rust mirror of the trap-grid contract's types, for bots,
+//! indexers and backend services:
+//! - [`Game`], [`Move`] and [`GameConfig`] - contract state
+//! - [`ContractError`] - the contract's error codes
+//! - [`GameEvent`] - event payloads published by the contract
+//! - [`GameClient`] - typed calls to a deployed contract over RPC, signed by any
+//!   [`TransactionSigner`]: a local key, a Ledger or a browser wallet
+//! - [`GameWatcher`] - a session's events folded into a local [`GameState`]
+//! - [`TrapLayout`] and [`Prover`] - the defender's grid, its setup proof and its
+//!   hit/miss proofs, with an optional on-disk [`ProofCache`]
+//! - [`AttackerStrategy`] - pluggable move selection for attacker bots
+//! - [`simulator`] - offline games between strategies and layouts, with statistics
+//! - [`channel`] - states, signatures and transcripts of games played off-chain
+//! - [`analysis`] - heatmaps and player statistics from finished games
+//! - [`network`] - named network profiles from built-ins, `config.toml` and the environment
+//! - [`commitment`] - trap commitments and the trap Merkle tree, as the circuits hash them
+//!
+//! Every type derives serde and converts to and from its `ScVal` encoding, so
+//! no contract crate or hand-written XDR decoding is needed.
+//!
+//! The RPC, signing, proving and on-disk pieces sit behind the default
+//! `client` feature. Without it the crate builds for `wasm32-unknown-unknown`,
+//! and the `wasm` feature adds the [`wasm`] exports used by the dApp.
+
+#[cfg(feature = "client")]
+pub mod analysis;
+#[cfg(feature = "client")]
+pub mod channel;
+#[cfg(feature = "client")]
+pub mod client;
+pub mod commitment;
+mod error;
+mod events;
+mod game;
+#[cfg(feature = "client")]
+mod keypair;
+pub mod layout;
+#[cfg(feature = "client")]
+pub mod network;
+#[cfg(feature = "client")]
+pub mod proof_cache;
+pub mod proof_format;
+#[cfg(feature = "client")]
+pub mod prover;
+#[cfg(feature = "client")]
+pub mod retry;
+#[cfg(feature = "client")]
+pub mod rpc;
+mod scval;
+#[cfg(feature = "client")]
+pub mod signer;
+pub mod simulator;
+pub mod strategy;
+#[cfg(feature = "client")]
+pub mod tx;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "client")]
+pub mod watcher;
+
+#[cfg(feature = "client")]
+pub use client::{GameClient, Interface, ProvenMove};
+pub use error::ContractError;
+pub use events::{
+    player_topics, session_topics, GameEnded, GameEvent, GameStarted, MoveMade, MoveSubmitted,
+};
+pub use game::{
+    ContractConfig, DataKey, Deadlines, ExpiryPolicy, FullState, Game, GameConfig, GridSetup, Move,
+    Optimistic, PayoutCurve, PendingMove, Rules, Sponsorship, StreakBonus, WinPolicy,
+    GAME_SCHEMA_VERSION,
+};
+#[cfg(feature = "client")]
+pub use keypair::Keypair;
+pub use layout::TrapLayout;
+#[cfg(feature = "client")]
+pub use network::{Config, Network};
+#[cfg(feature = "client")]
+pub use proof_cache::ProofCache;
+#[cfg(feature = "client")]
+pub use prover::Prover;
+#[cfg(feature = "client")]
+pub use signer::{Passthrough, TransactionSigner};
+pub use strategy::AttackerStrategy;
+#[cfg(feature = "client")]
+pub use watcher::{GameHandler, GameState, GameWatcher};
+
+/// Width and height of the grid a deployment plays on unless configured
+/// otherwise (`ContractConfig::grid_size`)
+pub const GRID_SIZE: u32 = trap_grid_encoding::DEFAULT_GRID_SIZE;
+
+/// Number of cells of the default grid, and the most moves a game on it can have
+pub const MAX_MOVES: u32 = GRID_SIZE * GRID_SIZE;
@@ -0,0 +1,284 @@
+//! Named network profiles
+//!
+//! Each profile holds a network's RPC endpoint, passphrase and deployed
+//! contract ids. The built-in `localnet`, `testnet`, `futurenet` and
+//! `mainnet` profiles are completed or overridden by `config.toml` under
+//! `$TRAP_GRID_HOME` (default: `trap-grid` under the platform config
+//! directory), and that in turn by environment variables:
+//!
+//! ```toml
+//! default_network = "testnet"
+//!
+//! [networks.testnet]
+//! contract_id = "C..."
+//!
+//! [networks.staging]
+//! rpc_url = "https://rpc.example.org"
+//! passphrase = "Test SDF Network ; September 2015"
+//! contract_id = "C..."
+//! ```
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use stellar_xdr::curr::ScAddress;
+
+use crate::rpc::{RpcClient, DEFAULT_RPC_URL, LOCAL_RPC_URL};
+
+/// Profile used when neither the caller nor the config file names one
+pub const DEFAULT_NETWORK: &str = "testnet";
+
+/// Names of the built-in profiles
+pub const BUILTIN_NETWORKS: [&str; 4] = ["localnet", "testnet", "futurenet", "mainnet"];
+
+/// Environment variables that override a profile's fields
+const ENV_OVERRIDES: [(&str, Field); 5] = [
+    ("STELLAR_RPC_URL", Field::RpcUrl),
+    ("STELLAR_NETWORK_PASSPHRASE", Field::Passphrase),
+    ("TRAP_GRID_CONTRACT", Field::ContractId),
+    ("GAME_HUB_CONTRACT", Field::HubId),
+    ("TRAP_GRID_VERIFIER", Field::VerifierId),
+];
+
+#[derive(Clone, Copy)]
+enum Field {
+    RpcUrl,
+    Passphrase,
+    ContractId,
+    HubId,
+    VerifierId,
+}
+
+/// A network as written in the config file; unset fields fall back
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    pub rpc_url: Option<String>,
+    pub passphrase: Option<String>,
+    /// Trap-grid contract
+    pub contract_id: Option<String>,
+    /// Game Hub contract
+    pub hub_id: Option<String>,
+    /// UltraHonk verifier contract
+    pub verifier_id: Option<String>,
+}
+
+impl Profile {
+    fn field(&mut self, field: Field) -> &mut Option<String> {
+        match field {
+            Field::RpcUrl => &mut self.rpc_url,
+            Field::Passphrase => &mut self.passphrase,
+            Field::ContractId => &mut self.contract_id,
+            Field::HubId => &mut self.hub_id,
+            Field::VerifierId => &mut self.verifier_id,
+        }
+    }
+
+    /// Take every field `other` sets
+    fn merge(&mut self, other: &Profile) {
+        let other = other.clone();
+        self.rpc_url = other.rpc_url.or(self.rpc_url.take());
+        self.passphrase = other.passphrase.or(self.passphrase.take());
+        self.contract_id = other.contract_id.or(self.contract_id.take());
+        self.hub_id = other.hub_id.or(self.hub_id.take());
+        self.verifier_id = other.verifier_id.or(self.verifier_id.take());
+    }
+
+    fn builtin(name: &str) -> Option<Self> {
+        let profile = |rpc_url: Option<&str>, passphrase: &str| Profile {
+            rpc_url: rpc_url.map(str::to_string),
+            passphrase: Some(passphrase.to_string()),
+            ..Profile::default()
+        };
+        match name {
+            "localnet" => Some(profile(
+                Some(LOCAL_RPC_URL),
+                "Standalone Network ; February 2017",
+            )),
+            "testnet" => Some(Profile {
+                hub_id: Some("CB4VZAT2U3UC6XFK3N23SKRF2NDCMP3QHJYMCHHFMZO7MRQO6DQ2EMYG".into()),
+                verifier_id: Some(
+                    "CAMRMEFTAFKUOVNFXX4BE2FD66SK2LLENREMNKOWDUNLKFVYJVG36QO7".into(),
+                ),
+                ..profile(Some(DEFAULT_RPC_URL), "Test SDF Network ; September 2015")
+            }),
+            "futurenet" => Some(profile(
+                Some("https://rpc-futurenet.stellar.org"),
+                "Test SDF Future Network ; October 2022",
+            )),
+            // SDF runs no public mainnet RPC; bring your own provider
+            "mainnet" => Some(profile(
+                None,
+                "Public Global Stellar Network ; September 2015",
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// A fully resolved network
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct Network {
+    pub name: String,
+    pub rpc_url: String,
+    pub passphrase: String,
+    pub contract_id: Option<String>,
+    pub hub_id: Option<String>,
+    pub verifier_id: Option<String>,
+}
+
+impl Network {
+    pub fn rpc(&self) -> RpcClient {
+        RpcClient::new(&self.rpc_url)
+    }
+
+    /// The trap-grid contract, which must be configured
+    pub fn contract(&self) -> Result<ScAddress> {
+        let id = self.contract_id.as_deref().with_context(|| {
+            format!(
+                "no trap-grid contract for {}; pass --contract-id, set TRAP_GRID_CONTRACT \
+                 or add contract_id under [networks.{}] in the config file",
+                self.name, self.name
+            )
+        })?;
+        id.parse()
+            .map_err(|e| anyhow!("invalid contract id {id}: {e}"))
+    }
+}
+
+/// The profiles of `config.toml`
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub default_network: Option<String>,
+    #[serde(default)]
+    pub networks: BTreeMap<String, Profile>,
+}
+
+impl Config {
+    /// `$TRAP_GRID_HOME/config.toml` or `trap-grid/config.toml` under the
+    /// platform config directory
+    pub fn default_path() -> Result<PathBuf> {
+        let home = match env::var_os("TRAP_GRID_HOME") {
+            Some(home) => PathBuf::from(home),
+            None => dirs::config_dir()
+                .context("no config directory; set TRAP_GRID_HOME")?
+                .join("trap-grid"),
+        };
+        Ok(home.join("config.toml"))
+    }
+
+    /// The config file at the default path; empty if there is none
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::default_path()?)
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(text) => {
+                toml::from_str(&text).with_context(|| format!("invalid config {}", path.display()))
+            }
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err).with_context(|| format!("failed to read {}", path.display())),
+        }
+    }
+
+    /// Built-in and configured profile names
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = BUILTIN_NETWORKS.iter().map(|n| n.to_string()).collect();
+        names.extend(
+            self.networks
+                .keys()
+                .filter(|name| !BUILTIN_NETWORKS.contains(&name.as_str()))
+                .cloned(),
+        );
+        names
+    }
+
+    /// Resolve `name`, or the default network, with environment overrides
+    pub fn network(&self, name: Option<&str>) -> Result<Network> {
+        self.resolve(name, |var| env::var(var).ok().filter(|v| !v.is_empty()))
+    }
+
+    fn resolve(&self, name: Option<&str>, env: impl Fn(&str) -> Option<String>) -> Result<Network> {
+        let name = name
+            .or(self.default_network.as_deref())
+            .unwrap_or(DEFAULT_NETWORK);
+        let configured = self.networks.get(name);
+        let mut profile = match (Profile::builtin(name), configured) {
+            (None, None) => bail!(
+                "unknown network {name}; expected one of {}",
+                self.names().join(", ")
+            ),
+            (builtin, _) => builtin.unwrap_or_default(),
+        };
+        if let Some(configured) = configured {
+            profile.merge(configured);
+        }
+        for (var, field) in ENV_OVERRIDES {
+            if let Some(value) = env(var) {
+                *profile.field(field) = Some(value);
+            }
+        }
+
+        let missing = |what: &str, key: &str| {
+            anyhow!("network {name} has no {what}; set {key} under [networks.{name}] in the config file")
+        };
+        Ok(Network {
+            name: name.to_string(),
+            rpc_url: profile
+                .rpc_url
+                .ok_or_else(|| missing("RPC URL", "rpc_url"))?,
+            passphrase: profile
+                .passphrase
+                .ok_or_else(|| missing("passphrase", "passphrase"))?,
+            contract_id: profile.contract_id,
+            hub_id: profile.hub_id,
+            verifier_id: profile.verifier_id,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_resolve_layers() {
+        let config: Config = toml::from_str(
+            r#"
+            default_network = "localnet"
+
+            [networks.localnet]
+            contract_id = "CFILE"
+
+            [networks.staging]
+            rpc_url = "https://rpc.example.org"
+            passphrase = "Staging"
+            "#,
+        )
+        .unwrap();
+        let no_env = |_: &str| None;
+
+        let local = config.resolve(None, no_env).unwrap();
+        assert_eq!(local.rpc_url, LOCAL_RPC_URL);
+        assert_eq!(local.contract_id.as_deref(), Some("CFILE"));
+
+        let env = |var: &str| (var == "TRAP_GRID_CONTRACT").then(|| "CENV".to_string());
+        let local = config.resolve(Some("localnet"), env).unwrap();
+        assert_eq!(local.contract_id.as_deref(), Some("CENV"));
+
+        let staging = config.resolve(Some("staging"), no_env).unwrap();
+        assert_eq!(staging.passphrase, "Staging");
+        assert!(staging.contract().is_err());
+
+        assert!(config.resolve(Some("mainnet"), no_env).is_err());
+        assert!(config.resolve(Some("devnet"), no_env).is_err());
+        assert_eq!(config.names().last().map(String::as_str), Some("staging"));
+    }
+}
@@ -0,0 +1,173 @@
+//! Content-addressed proof cache
+//!
+//! Proving is by far the slowest step, and a proof only depends on the
+//! circuit and its inputs, so the same move can reuse the same proof on a
+//! retry, a resubmission or a replayed game. Entries are keyed by the SHA-256
+//! of the circuit id and the `Prover.toml` inputs and hold the raw
+//! `proof.with_public_inputs` bytes, one file per entry.
+
+use std::env;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+
+#[derive(Clone, Debug)]
+pub struct ProofCache {
+    dir: PathBuf,
+}
+
+impl ProofCache {
+    /// Cache under `$TRAP_GRID_HOME/proofs` or the platform cache directory
+    pub fn open() -> Result<Self> {
+        let dir = match env::var_os("TRAP_GRID_HOME") {
+            Some(home) => PathBuf::from(home).join("proofs"),
+            None => dirs::cache_dir()
+                .context("no cache directory; set TRAP_GRID_HOME")?
+                .join("trap-grid")
+                .join("proofs"),
+        };
+        Ok(Self::at(&dir))
+    }
+
+    pub fn at(dir: &Path) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Cache key of a proof of `inputs` (the Prover.toml contents) for `circuit_id`
+    pub fn key(circuit_id: &str, inputs: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(circuit_id.as_bytes());
+        hasher.update([0]);
+        hasher.update(inputs.as_bytes());
+        hex::encode(hasher.finalize())
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{key}.proof"))
+    }
+
+    pub fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let path = self.path(key);
+        match fs::read(&path) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err).with_context(|| format!("failed to read {}", path.display())),
+        }
+    }
+
+    /// Store an entry; written to a temporary file and renamed into place, so
+    /// concurrent provers never see a partial proof
+    pub fn put(&self, key: &str, proof: &[u8]) -> Result<()> {
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("failed to create {}", self.dir.display()))?;
+        // Unique per process and per call, for workers proving the same move
+        static WRITES: AtomicU64 = AtomicU64::new(0);
+        let tmp = self.dir.join(format!(
+            ".{key}.{}.{}.tmp",
+            std::process::id(),
+            WRITES.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::write(&tmp, proof).with_context(|| format!("failed to write {}", tmp.display()))?;
+        fs::rename(&tmp, self.path(key)).with_context(|| format!("failed to store proof {key}"))
+    }
+
+    /// Remove every entry; returns how many were removed
+    pub fn clear(&self) -> Result<usize> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(0),
+            Err(err) => return Err(err).context("failed to read the proof cache"),
+        };
+        let mut removed = 0;
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "proof") {
+                fs::remove_file(&path)?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+}
+
+/// Hash of a circuit's sources (`Nargo.toml` and everything under `src/`), so
+/// editing the circuit invalidates its cached proofs
+pub fn circuit_id(circuit_dir: &Path) -> Result<String> {
+    fn collect(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+        for entry in
+            fs::read_dir(dir).with_context(|| format!("failed to read {}", dir.display()))?
+        {
+            let path = entry?.path();
+            if path.is_dir() {
+                collect(&path, files)?;
+            } else {
+                files.push(path);
+            }
+        }
+        Ok(())
+    }
+
+    let mut files = vec![circuit_dir.join("Nargo.toml")];
+    let src = circuit_dir.join("src");
+    if src.is_dir() {
+        collect(&src, &mut files)?;
+    }
+    files[1..].sort();
+
+    let mut hasher = Sha256::new();
+    for file in &files {
+        let relative = file.strip_prefix(circuit_dir).unwrap_or(file);
+        hasher.update(relative.to_string_lossy().as_bytes());
+        hasher.update([0]);
+        hasher
+            .update(fs::read(file).with_context(|| format!("failed to read {}", file.display()))?);
+        hasher.update([0]);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_cache_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ProofCache::at(&dir.path().join("proofs"));
+        let key = ProofCache::key("circuit", "move_x = \"1\"");
+        assert_ne!(key, ProofCache::key("circuit", "move_x = \"2\""));
+        assert_ne!(key, ProofCache::key("other", "move_x = \"1\""));
+
+        assert_eq!(cache.get(&key).unwrap(), None);
+        cache.put(&key, b"proof").unwrap();
+        assert_eq!(cache.get(&key).unwrap(), Some(b"proof".to_vec()));
+        assert_eq!(cache.clear().unwrap(), 1);
+        assert_eq!(cache.get(&key).unwrap(), None);
+    }
+
+    #[test]
+    fn test_circuit_id_follows_sources() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("Nargo.toml"), "[package]\n").unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src/main.nr"), "fn main() {}\n").unwrap();
+        let before = circuit_id(dir.path()).unwrap();
+
+        // Build output does not count, source edits do
+        fs::create_dir(dir.path().join("target")).unwrap();
+        fs::write(dir.path().join("target/out.json"), "{}").unwrap();
+        assert_eq!(circuit_id(dir.path()).unwrap(), before);
+        fs::write(dir.path().join("src/main.nr"), "fn main() { }\n").unwrap();
+        assert_ne!(circuit_id(dir.path()).unwrap(), before);
+    }
+}
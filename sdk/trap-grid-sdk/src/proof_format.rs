@@ -0,0 +1,522 @@
+//! Proof byte layouts of the prover toolchains
+//!
+//! Verifier contracts read proofs in one canonical layout per proof system:
+//! - UltraHonk (the trap-grid verifier): the proof's field elements as
+//!   32-byte big-endian words, without public inputs, as `bb` writes them
+//! - Groth16: 256 bytes, `A.x A.y | B.x.c1 B.x.c0 B.y.c1 B.y.c0 | C.x C.y`
+//!   with every coordinate 32 big-endian bytes (the EIP-197 order)
+//!
+//! Each toolchain serializes proofs its own way. [`normalize`] detects the
+//! layout of a proof and rewrites it into the canonical one, checking every
+//! Groth16 point on the way. A Groth16 proof only verifies against a Groth16
+//! verifier; the layout is normalized, not the proof system.
+
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{bail, Context, Result};
+use ark_bn254::{Fq, Fq2, G1Affine, G2Affine};
+use ark_ff::{BigInteger, PrimeField};
+use serde::{Deserialize, Serialize};
+
+/// Size of a canonical Groth16 proof
+pub const GROTH16_PROOF_BYTES: usize = 256;
+
+const WORD: usize = 32;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ProofSystem {
+    UltraHonk,
+    Groth16,
+}
+
+/// Serializations of a proof this module reads
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProofFormat {
+    /// `bb` or bb.js proof bytes: already canonical
+    Barretenberg,
+    /// `bb --output_format fields`: a JSON array of `0x` hex field elements
+    BarretenbergFields,
+    /// snarkjs `proof.json`: Groth16 points as decimal strings
+    Snarkjs,
+    /// arkworks `serialize_uncompressed` of a Groth16 proof: little-endian
+    /// coordinates, `c0` before `c1`
+    Arkworks,
+    /// arkworks `serialize_compressed`: x coordinates with the y sign in the
+    /// top bits of their last byte
+    ArkworksCompressed,
+    /// gnark `WriteRawTo` of a Groth16 proof without commitments:
+    /// big-endian, already canonical
+    Gnark,
+    /// gnark `WriteTo`: x coordinates with the y sign in the top bits of
+    /// their first byte
+    GnarkCompressed,
+}
+
+impl ProofFormat {
+    pub const ALL: [ProofFormat; 7] = [
+        ProofFormat::Barretenberg,
+        ProofFormat::BarretenbergFields,
+        ProofFormat::Snarkjs,
+        ProofFormat::Arkworks,
+        ProofFormat::ArkworksCompressed,
+        ProofFormat::Gnark,
+        ProofFormat::GnarkCompressed,
+    ];
+
+    pub fn id(self) -> &'static str {
+        match self {
+            ProofFormat::Barretenberg => "barretenberg",
+            ProofFormat::BarretenbergFields => "barretenberg-fields",
+            ProofFormat::Snarkjs => "snarkjs",
+            ProofFormat::Arkworks => "arkworks",
+            ProofFormat::ArkworksCompressed => "arkworks-compressed",
+            ProofFormat::Gnark => "gnark",
+            ProofFormat::GnarkCompressed => "gnark-compressed",
+        }
+    }
+
+    pub fn system(self) -> ProofSystem {
+        match self {
+            ProofFormat::Barretenberg | ProofFormat::BarretenbergFields => ProofSystem::UltraHonk,
+            _ => ProofSystem::Groth16,
+        }
+    }
+
+    /// The layout of `bytes`
+    ///
+    /// JSON is told apart by its shape. Binary Groth16 proofs have a fixed
+    /// size, and big- and little-endian ones are told apart by which reading
+    /// yields valid curve points; any other run of 32-byte words is taken for
+    /// Barretenberg's.
+    pub fn detect(bytes: &[u8]) -> Result<Self> {
+        match bytes.iter().find(|b| !b.is_ascii_whitespace()) {
+            Some(b'{') => return Ok(ProofFormat::Snarkjs),
+            Some(b'[') => return Ok(ProofFormat::BarretenbergFields),
+            _ => {}
+        }
+        let candidates: &[ProofFormat] = match bytes.len() {
+            GROTH16_PROOF_BYTES => &[ProofFormat::Gnark, ProofFormat::Arkworks],
+            128 => &[
+                ProofFormat::GnarkCompressed,
+                ProofFormat::ArkworksCompressed,
+            ],
+            0 => bail!("proof is empty"),
+            len if len.is_multiple_of(WORD) => return Ok(ProofFormat::Barretenberg),
+            len => bail!("unknown proof format: {len} bytes of binary data"),
+        };
+        for &format in candidates {
+            if format.normalize(bytes).is_ok() {
+                return Ok(format);
+            }
+        }
+        bail!(
+            "unknown proof format: {} bytes that are no valid Groth16 proof",
+            bytes.len()
+        )
+    }
+
+    /// Rewrite a proof in this layout into the canonical one
+    pub fn normalize(self, bytes: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            ProofFormat::Barretenberg => {
+                if bytes.is_empty() || !bytes.len().is_multiple_of(WORD) {
+                    bail!("{} bytes are not a run of 32-byte words", bytes.len());
+                }
+                Ok(bytes.to_vec())
+            }
+            ProofFormat::BarretenbergFields => barretenberg_fields(bytes),
+            ProofFormat::Snarkjs => Ok(snarkjs(bytes)?.canonical()),
+            ProofFormat::Arkworks => Ok(arkworks(bytes)?.canonical()),
+            ProofFormat::ArkworksCompressed => Ok(arkworks_compressed(bytes)?.canonical()),
+            ProofFormat::Gnark => Ok(gnark(bytes)?.canonical()),
+            ProofFormat::GnarkCompressed => Ok(gnark_compressed(bytes)?.canonical()),
+        }
+        .with_context(|| format!("invalid {} proof", self.id()))
+    }
+}
+
+impl fmt::Display for ProofFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.id())
+    }
+}
+
+impl FromStr for ProofFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match ProofFormat::ALL.into_iter().find(|format| format.id() == s) {
+            Some(format) => Ok(format),
+            None => bail!(
+                "unknown proof format {s}; expected one of {}",
+                ProofFormat::ALL.map(ProofFormat::id).join(", ")
+            ),
+        }
+    }
+}
+
+/// Detect the layout of `bytes` and rewrite it into the canonical one
+pub fn normalize(bytes: &[u8]) -> Result<(ProofFormat, Vec<u8>)> {
+    let format = ProofFormat::detect(bytes)?;
+    Ok((format, format.normalize(bytes)?))
+}
+
+/// A Groth16 proof over BN254
+struct Groth16 {
+    a: G1Affine,
+    b: G2Affine,
+    c: G1Affine,
+}
+
+impl Groth16 {
+    fn new(a: G1Affine, b: G2Affine, c: G1Affine) -> Result<Self> {
+        if !a.is_on_curve() || !c.is_on_curve() {
+            bail!("G1 point is not on the curve");
+        }
+        if !b.is_on_curve() || !b.is_in_correct_subgroup_assuming_on_curve() {
+            bail!("G2 point is not in the subgroup");
+        }
+        Ok(Self { a, b, c })
+    }
+
+    fn canonical(&self) -> Vec<u8> {
+        [
+            self.a.x,
+            self.a.y,
+            self.b.x.c1,
+            self.b.x.c0,
+            self.b.y.c1,
+            self.b.y.c0,
+            self.c.x,
+            self.c.y,
+        ]
+        .iter()
+        .flat_map(|fq| fq.into_bigint().to_bytes_be())
+        .collect()
+    }
+}
+
+/// A base-field element from 32 big-endian bytes, below the modulus
+fn fq_be(bytes: &[u8]) -> Result<Fq> {
+    let fq = Fq::from_be_bytes_mod_order(bytes);
+    if fq.into_bigint().to_bytes_be() != bytes {
+        bail!("coordinate is not below the field modulus");
+    }
+    Ok(fq)
+}
+
+fn fq_le(bytes: &[u8]) -> Result<Fq> {
+    let mut be = bytes.to_vec();
+    be.reverse();
+    fq_be(&be)
+}
+
+fn words(bytes: &[u8], count: usize) -> Result<Vec<[u8; WORD]>> {
+    if bytes.len() != count * WORD {
+        bail!("expected {} bytes, found {}", count * WORD, bytes.len());
+    }
+    Ok(bytes
+        .chunks(WORD)
+        .map(|chunk| chunk.try_into().expect("32-byte chunk"))
+        .collect())
+}
+
+/// Clear the two flag bits arkworks keeps in the top of a coordinate's last
+/// byte, failing on the point at infinity; returns whether y is the larger
+/// root
+fn arkworks_flags(word: &mut [u8; WORD]) -> Result<bool> {
+    let flags = word[WORD - 1] >> 6;
+    word[WORD - 1] &= 0x3f;
+    if flags & 0b01 != 0 {
+        bail!("point at infinity");
+    }
+    Ok(flags & 0b10 != 0)
+}
+
+/// Clear gnark's flag bits in the top of a coordinate's first byte; returns
+/// whether y is the larger root of a compressed point
+fn gnark_flags(word: &mut [u8; WORD], compressed: bool) -> Result<bool> {
+    let flags = word[0] >> 6;
+    word[0] &= 0x3f;
+    match (compressed, flags) {
+        (false, 0b00) => Ok(false),
+        (true, 0b10) => Ok(false),
+        (true, 0b11) => Ok(true),
+        (_, 0b01) => bail!("point at infinity"),
+        _ => bail!("unexpected point flags {flags:#04b}"),
+    }
+}
+
+fn g1_from_x(x: Fq, greatest: bool) -> Result<G1Affine> {
+    G1Affine::get_point_from_x_unchecked(x, greatest).context("x is not on the G1 curve")
+}
+
+fn g2_from_x(x: Fq2, greatest: bool) -> Result<G2Affine> {
+    G2Affine::get_point_from_x_unchecked(x, greatest).context("x is not on the G2 curve")
+}
+
+fn arkworks(bytes: &[u8]) -> Result<Groth16> {
+    let mut w = words(bytes, 8)?;
+    for i in [1, 5, 7] {
+        arkworks_flags(&mut w[i])?;
+    }
+    let g1 = |x: &[u8], y: &[u8]| -> Result<G1Affine> {
+        Ok(G1Affine::new_unchecked(fq_le(x)?, fq_le(y)?))
+    };
+    let b = G2Affine::new_unchecked(
+        Fq2::new(fq_le(&w[2])?, fq_le(&w[3])?),
+        Fq2::new(fq_le(&w[4])?, fq_le(&w[5])?),
+    );
+    Groth16::new(g1(&w[0], &w[1])?, b, g1(&w[6], &w[7])?)
+}
+
+fn arkworks_compressed(bytes: &[u8]) -> Result<Groth16> {
+    let mut w = words(bytes, 4)?;
+    let a = arkworks_flags(&mut w[0])?;
+    let b = arkworks_flags(&mut w[2])?;
+    let c = arkworks_flags(&mut w[3])?;
+    Groth16::new(
+        g1_from_x(fq_le(&w[0])?, a)?,
+        g2_from_x(Fq2::new(fq_le(&w[1])?, fq_le(&w[2])?), b)?,
+        g1_from_x(fq_le(&w[3])?, c)?,
+    )
+}
+
+fn gnark(bytes: &[u8]) -> Result<Groth16> {
+    let mut w = words(bytes, 8)?;
+    for i in [0, 2, 6] {
+        gnark_flags(&mut w[i], false)?;
+    }
+    let g1 = |x: &[u8], y: &[u8]| -> Result<G1Affine> {
+        Ok(G1Affine::new_unchecked(fq_be(x)?, fq_be(y)?))
+    };
+    // gnark writes the imaginary part of each Fq2 first
+    let b = G2Affine::new_unchecked(
+        Fq2::new(fq_be(&w[3])?, fq_be(&w[2])?),
+        Fq2::new(fq_be(&w[5])?, fq_be(&w[4])?),
+    );
+    Groth16::new(g1(&w[0], &w[1])?, b, g1(&w[6], &w[7])?)
+}
+
+fn gnark_compressed(bytes: &[u8]) -> Result<Groth16> {
+    let mut w = words(bytes, 4)?;
+    let a = gnark_flags(&mut w[0], true)?;
+    let b = gnark_flags(&mut w[1], true)?;
+    let c = gnark_flags(&mut w[3], true)?;
+    Groth16::new(
+        g1_from_x(fq_be(&w[0])?, a)?,
+        g2_from_x(Fq2::new(fq_be(&w[2])?, fq_be(&w[1])?), b)?,
+        g1_from_x(fq_be(&w[3])?, c)?,
+    )
+}
+
+#[derive(Deserialize)]
+struct SnarkjsProof {
+    pi_a: Vec<String>,
+    pi_b: Vec<Vec<String>>,
+    pi_c: Vec<String>,
+    #[serde(default)]
+    protocol: Option<String>,
+}
+
+fn fq_decimal(s: &str) -> Result<Fq> {
+    // `from_str` reduces numbers past the modulus, which would hide a bad proof
+    match Fq::from_str(s) {
+        Ok(fq) if fq.to_string() == s => Ok(fq),
+        _ => bail!("{s} is not a field element"),
+    }
+}
+
+/// An affine point from snarkjs' projective `[x, y, z]`, where z must be 1
+fn snarkjs_g1(point: &[String]) -> Result<G1Affine> {
+    let [x, y, z] = point else {
+        bail!("G1 point has {} coordinates, expected 3", point.len());
+    };
+    if z != "1" {
+        bail!("G1 point is not affine");
+    }
+    Ok(G1Affine::new_unchecked(fq_decimal(x)?, fq_decimal(y)?))
+}
+
+fn snarkjs(bytes: &[u8]) -> Result<Groth16> {
+    let proof: SnarkjsProof = serde_json::from_slice(bytes).context("not a snarkjs proof")?;
+    if let Some(protocol) = proof.protocol.as_deref().filter(|p| *p != "groth16") {
+        bail!("snarkjs {protocol} proofs are not supported, only groth16");
+    }
+    let fq2 = |c: &[String]| -> Result<Fq2> {
+        let [c0, c1] = c else {
+            bail!("Fq2 element has {} parts, expected 2", c.len());
+        };
+        Ok(Fq2::new(fq_decimal(c0)?, fq_decimal(c1)?))
+    };
+    let [x, y, z] = proof.pi_b.as_slice() else {
+        bail!("G2 point has {} coordinates, expected 3", proof.pi_b.len());
+    };
+    if z.as_slice() != ["1", "0"] {
+        bail!("G2 point is not affine");
+    }
+    Groth16::new(
+        snarkjs_g1(&proof.pi_a)?,
+        G2Affine::new_unchecked(fq2(x)?, fq2(y)?),
+        snarkjs_g1(&proof.pi_c)?,
+    )
+}
+
+fn barretenberg_fields(bytes: &[u8]) -> Result<Vec<u8>> {
+    let fields: Vec<String> =
+        serde_json::from_slice(bytes).context("not a JSON array of fields")?;
+    if fields.is_empty() {
+        bail!("proof has no fields");
+    }
+    let mut out = Vec::with_capacity(fields.len() * WORD);
+    for (i, field) in fields.iter().enumerate() {
+        let digits = field.strip_prefix("0x").unwrap_or(field);
+        let digits = if digits.len() % 2 == 1 {
+            format!("0{digits}")
+        } else {
+            digits.to_string()
+        };
+        let bytes = hex::decode(&digits).with_context(|| format!("field {i} is not hex"))?;
+        if bytes.len() > WORD {
+            bail!("field {i} is longer than 32 bytes");
+        }
+        out.extend(std::iter::repeat_n(0, WORD - bytes.len()));
+        out.extend(bytes);
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_bn254::Fr;
+    use ark_ec::{AffineRepr, CurveGroup};
+    use ark_serialize::CanonicalSerialize;
+
+    /// A proof made of multiples of the generators; not one a verifier
+    /// accepts, but with points like a real proof's
+    fn proof() -> (G1Affine, G2Affine, G1Affine) {
+        (
+            (G1Affine::generator() * Fr::from(3u64)).into_affine(),
+            (G2Affine::generator() * Fr::from(5u64)).into_affine(),
+            (G1Affine::generator() * Fr::from(7u64)).into_affine(),
+        )
+    }
+
+    fn be(fq: Fq) -> Vec<u8> {
+        fq.into_bigint().to_bytes_be()
+    }
+
+    fn canonical() -> Vec<u8> {
+        let (a, b, c) = proof();
+        [a.x, a.y, b.x.c1, b.x.c0, b.y.c1, b.y.c0, c.x, c.y]
+            .into_iter()
+            .flat_map(be)
+            .collect()
+    }
+
+    fn arkworks_bytes(compressed: bool) -> Vec<u8> {
+        let (a, b, c) = proof();
+        let mut bytes = Vec::new();
+        if compressed {
+            a.serialize_compressed(&mut bytes).unwrap();
+            b.serialize_compressed(&mut bytes).unwrap();
+            c.serialize_compressed(&mut bytes).unwrap();
+        } else {
+            a.serialize_uncompressed(&mut bytes).unwrap();
+            b.serialize_uncompressed(&mut bytes).unwrap();
+            c.serialize_uncompressed(&mut bytes).unwrap();
+        }
+        bytes
+    }
+
+    /// gnark's compressed encoding, from its `Bytes()` rules
+    fn gnark_compressed_bytes() -> Vec<u8> {
+        let (a, b, c) = proof();
+        let flag = |largest: bool| if largest { 0b11 << 6 } else { 0b10 << 6 };
+        let mut a_x = be(a.x);
+        a_x[0] |= flag(a.y > -a.y);
+        let mut b_x = [be(b.x.c1), be(b.x.c0)].concat();
+        b_x[0] |= flag(b.y > -b.y);
+        let mut c_x = be(c.x);
+        c_x[0] |= flag(c.y > -c.y);
+        [a_x, b_x, c_x].concat()
+    }
+
+    fn snarkjs_json() -> Vec<u8> {
+        let (a, b, c) = proof();
+        serde_json::to_vec(&serde_json::json!({
+            "pi_a": [a.x.to_string(), a.y.to_string(), "1"],
+            "pi_b": [
+                [b.x.c0.to_string(), b.x.c1.to_string()],
+                [b.y.c0.to_string(), b.y.c1.to_string()],
+                ["1", "0"]
+            ],
+            "pi_c": [c.x.to_string(), c.y.to_string(), "1"],
+            "protocol": "groth16",
+            "curve": "bn128"
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_groth16_formats() {
+        let expected = canonical();
+        assert_eq!(expected.len(), GROTH16_PROOF_BYTES);
+        for (format, bytes) in [
+            (ProofFormat::Gnark, expected.clone()),
+            (ProofFormat::GnarkCompressed, gnark_compressed_bytes()),
+            (ProofFormat::Arkworks, arkworks_bytes(false)),
+            (ProofFormat::ArkworksCompressed, arkworks_bytes(true)),
+            (ProofFormat::Snarkjs, snarkjs_json()),
+        ] {
+            assert_eq!(normalize(&bytes).unwrap(), (format, expected.clone()));
+            assert_eq!(format.system(), ProofSystem::Groth16);
+        }
+    }
+
+    #[test]
+    fn test_barretenberg_formats() {
+        let mut proof: Vec<u8> = (1..=64u8).collect();
+        proof[32] = 0;
+        assert_eq!(
+            normalize(&proof).unwrap(),
+            (ProofFormat::Barretenberg, proof.clone())
+        );
+        let fields = format!(
+            "[\"0x{}\", \"0x{}\"]",
+            hex::encode(&proof[..32]),
+            // bb drops leading zeros of small fields
+            hex::encode(&proof[33..])
+        );
+        assert_eq!(
+            normalize(fields.as_bytes()).unwrap(),
+            (ProofFormat::BarretenbergFields, proof)
+        );
+    }
+
+    #[test]
+    fn test_invalid_proofs() {
+        // A point off the curve, a bad length, another snarkjs protocol
+        let mut bad = canonical();
+        bad[63] ^= 1;
+        assert!(ProofFormat::Gnark.normalize(&bad).is_err());
+        assert!(normalize(&bad).is_err());
+        assert!(normalize(&[1; 33]).is_err());
+        assert!(normalize(&[]).is_err());
+        let plonk = String::from_utf8(snarkjs_json())
+            .unwrap()
+            .replace("groth16", "plonk");
+        assert!(normalize(plonk.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_format_ids() {
+        for format in ProofFormat::ALL {
+            assert_eq!(format.id().parse::<ProofFormat>().unwrap(), format);
+        }
+        assert!("groth16".parse::<ProofFormat>().is_err());
+    }
+}
@@ -0,0 +1,440 @@
+//! Proving driver for the position-movement and grid-setup circuits
+//!
+//! Mirrors the steps of the e2e scripts: write `Prover.toml`, run
+//! `nargo execute`, prove with bb.js (UltraHonk, keccak transcript) and split
+//! `proof.with_public_inputs` into the public inputs and the proof bytes.
+//! With a [`ProofCache`] attached, proofs of inputs seen before are reused.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::str::FromStr;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use trap_grid_encoding::{
+    key_fields, FieldBytes, GridSetup as SetupInputs, PositionMovement, TrapCommitment,
+    TrapMerkleRoot, MAX_GRID_SIZE,
+};
+
+use trap_grid_encoding::domain;
+
+use crate::commitment::{defender_field, defender_key, grid_commitment, hash_to_field, TrapTree};
+use crate::layout::cell_index;
+use crate::proof_cache::{self, ProofCache};
+use crate::{GridSetup, ProvenMove, TrapLayout};
+
+/// bb.js entrypoint relative to the circuit directory
+pub const DEFAULT_BBJS: &str = "node_modules/@aztec/bb.js/dest/node/main.js";
+
+/// The Noir circuits under `circuits/`
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Circuit {
+    PositionMovement,
+    TrapCommitment,
+    TrapMerkleRoot,
+    GridSetup,
+}
+
+impl Circuit {
+    pub const ALL: [Circuit; 4] = [
+        Circuit::PositionMovement,
+        Circuit::TrapCommitment,
+        Circuit::TrapMerkleRoot,
+        Circuit::GridSetup,
+    ];
+
+    /// Directory name under `circuits/`
+    pub fn id(self) -> &'static str {
+        match self {
+            Circuit::PositionMovement => "position-movement",
+            Circuit::TrapCommitment => "trap-commitment",
+            Circuit::TrapMerkleRoot => "trap-merkle-root",
+            Circuit::GridSetup => "grid-setup",
+        }
+    }
+
+    /// Package name (`name` in Nargo.toml), which names the build artifacts
+    pub fn package(self) -> &'static str {
+        match self {
+            Circuit::PositionMovement => "position_movement",
+            Circuit::TrapCommitment => "trap_commitment",
+            Circuit::TrapMerkleRoot => "trap_merkle_root",
+            Circuit::GridSetup => "grid_setup",
+        }
+    }
+
+    /// Length of the serialized public inputs at the start of
+    /// `proof.with_public_inputs`
+    pub fn public_inputs_len(self) -> usize {
+        match self {
+            Circuit::PositionMovement => PositionMovement::LEN,
+            Circuit::TrapCommitment => TrapCommitment::LEN,
+            Circuit::TrapMerkleRoot => TrapMerkleRoot::LEN,
+            Circuit::GridSetup => SetupInputs::LEN,
+        }
+    }
+}
+
+impl FromStr for Circuit {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match Circuit::ALL.into_iter().find(|circuit| circuit.id() == s) {
+            Some(circuit) => Ok(circuit),
+            None => bail!(
+                "unknown circuit {s}; expected one of {}",
+                Circuit::ALL.map(Circuit::id).join(", ")
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Prover {
+    circuit_dir: PathBuf,
+    bbjs: PathBuf,
+    /// The cache and this circuit's id in it
+    cache: Option<(ProofCache, String)>,
+}
+
+impl Prover {
+    pub fn new(circuit_dir: &Path, bbjs: &Path) -> Result<Self> {
+        if !circuit_dir.join("Nargo.toml").is_file() {
+            bail!(
+                "{} is not a Noir circuit directory (no Nargo.toml)",
+                circuit_dir.display()
+            );
+        }
+        // Commands run inside the circuit directory, so every path must be absolute
+        let circuit_dir = &circuit_dir.canonicalize()?;
+        let bbjs = if bbjs.is_absolute() {
+            bbjs.to_path_buf()
+        } else {
+            circuit_dir.join(bbjs)
+        };
+        if !bbjs.is_file() {
+            bail!(
+                "bb.js not found at {}; run `npm install` in {}",
+                bbjs.display(),
+                circuit_dir.display()
+            );
+        }
+        Ok(Self {
+            circuit_dir: circuit_dir.to_path_buf(),
+            bbjs,
+            cache: None,
+        })
+    }
+
+    /// Reuse proofs from `cache`, and store new ones there
+    pub fn with_cache(mut self, cache: ProofCache) -> Result<Self> {
+        let id = proof_cache::circuit_id(&self.circuit_dir)?;
+        self.cache = Some((cache, id));
+        Ok(self)
+    }
+
+    pub fn circuit_dir(&self) -> &Path {
+        &self.circuit_dir
+    }
+
+    /// Absolute path of the bb.js entrypoint
+    pub fn bbjs(&self) -> &Path {
+        &self.bbjs
+    }
+
+    fn run(&self, program: &str, args: &[&str]) -> Result<()> {
+        let output = Command::new(program)
+            .args(args)
+            .current_dir(&self.circuit_dir)
+            .output()
+            .with_context(|| format!("failed to run {program}; is it installed and on PATH?"))?;
+        if !output.status.success() {
+            bail!(
+                "{program} {} failed: {}",
+                args.first().unwrap_or(&""),
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(())
+    }
+
+    /// Prove what the cell at `(x, y)` of `layout` holds, opening the
+    /// commitment [`Prover::prove_setup`] made of `layout` with `salt` for
+    /// `defender`
+    ///
+    /// The contract only accepts the proof for the game started with that
+    /// commitment, submitted by its defender.
+    pub fn prove_move(
+        &self,
+        session_id: u32,
+        layout: &TrapLayout,
+        salt: &FieldBytes,
+        defender: &str,
+        x: u32,
+        y: u32,
+    ) -> Result<ProvenMove> {
+        let inputs = prover_toml(layout, salt, &defender_key(defender)?, x, y)?;
+        let bytes = self.cached_prove(Circuit::PositionMovement, &inputs)?;
+        let (public_inputs, proof) = split_proof(Circuit::PositionMovement, &bytes)?;
+
+        Ok(ProvenMove {
+            session_id,
+            x,
+            y,
+            is_hit: layout.is_trap(x, y),
+            proof: proof.to_vec(),
+            public_inputs: public_inputs.to_vec(),
+        })
+    }
+
+    /// Prove that `layout` holds only 0s and 1s, with as many traps as it
+    /// has, under the commitment
+    /// `commitment::grid_commitment(trap Merkle root, salt, defender)`
+    ///
+    /// The prover must run in a grid-setup circuit directory. The contract
+    /// only accepts the proof when the layout's trap count and grid size
+    /// match its configured `trap_count` and `grid_size`, and in a game
+    /// `defender` starts.
+    pub fn prove_setup(
+        &self,
+        layout: &TrapLayout,
+        salt: &FieldBytes,
+        defender: &str,
+    ) -> Result<GridSetup> {
+        let key = defender_key(defender)?;
+        let root = TrapTree::new(&layout.trap_values)?.root();
+        let commitment = grid_commitment(&root, salt, &defender_field(defender)?);
+        let inputs = setup_prover_toml(layout, &commitment, salt, &key);
+        let bytes = self.cached_prove(Circuit::GridSetup, &inputs)?;
+        let (_, proof) = split_proof(Circuit::GridSetup, &bytes)?;
+        Ok(GridSetup {
+            grid_commitment: commitment,
+            proof: proof.to_vec(),
+        })
+    }
+
+    /// [`Prover::prove`] through the cache, when one is attached
+    fn cached_prove(&self, circuit: Circuit, inputs: &str) -> Result<Vec<u8>> {
+        let Some((cache, circuit_id)) = &self.cache else {
+            return self.prove(circuit, inputs);
+        };
+        let key = ProofCache::key(circuit_id, inputs);
+        match cache.get(&key)? {
+            Some(bytes) => Ok(bytes),
+            None => {
+                let bytes = self.prove(circuit, inputs)?;
+                cache.put(&key, &bytes)?;
+                Ok(bytes)
+            }
+        }
+    }
+
+    /// Run the circuit on `inputs` and return `proof.with_public_inputs`
+    fn prove(&self, circuit: Circuit, inputs: &str) -> Result<Vec<u8>> {
+        fs::write(self.circuit_dir.join("Prover.toml"), inputs)
+            .context("failed to write Prover.toml")?;
+
+        self.run("nargo", &["execute"])?;
+
+        let target = self.circuit_dir.join("target");
+        let package = circuit.package();
+        let bytecode = target.join(format!("{package}.json"));
+        let witness = target.join(format!("{package}.gz"));
+        let output = target.join("proof.with_public_inputs");
+        self.run(
+            "node",
+            &[
+                &self.bbjs.to_string_lossy(),
+                "prove_ultra_keccak_honk",
+                "-b",
+                &bytecode.to_string_lossy(),
+                "-w",
+                &witness.to_string_lossy(),
+                "-o",
+                &output.to_string_lossy(),
+            ],
+        )?;
+
+        fs::read(&output).with_context(|| format!("failed to read {}", output.display()))
+    }
+}
+
+/// Split `proof.with_public_inputs` into the public inputs and the proof
+fn split_proof(circuit: Circuit, bytes: &[u8]) -> Result<(&[u8], &[u8])> {
+    if bytes.len() <= circuit.public_inputs_len() {
+        bail!("proof output is only {} bytes", bytes.len());
+    }
+    Ok(bytes.split_at(circuit.public_inputs_len()))
+}
+
+/// Copy a circuit directory without its build output and npm packages
+///
+/// `nargo execute` writes `Prover.toml` and `target/` inside the circuit
+/// directory, so provers running in parallel each need their own copy.
+pub fn copy_circuit(from: &Path, to: &Path) -> Result<()> {
+    fs::create_dir_all(to).with_context(|| format!("failed to create {}", to.display()))?;
+    for entry in fs::read_dir(from).with_context(|| format!("failed to read {}", from.display()))? {
+        let entry = entry?;
+        let name = entry.file_name();
+        if name == "target" || name == "node_modules" {
+            continue;
+        }
+        let dest = to.join(&name);
+        if entry.file_type()?.is_dir() {
+            copy_circuit(&entry.path(), &dest)?;
+        } else {
+            fs::copy(entry.path(), &dest)
+                .with_context(|| format!("failed to copy {}", entry.path().display()))?;
+        }
+    }
+    Ok(())
+}
+
+/// Prover.toml for the position-movement circuit
+///
+/// Opens cell (`x`, `y`) of `layout` from its grid commitment: the Merkle
+/// proof of the cell's leaf, and the `salt` and [`defender_key`] the
+/// commitment was made with.
+pub fn prover_toml(
+    layout: &TrapLayout,
+    salt: &FieldBytes,
+    defender: &[u8; 32],
+    x: u32,
+    y: u32,
+) -> Result<String> {
+    let tree = TrapTree::new(&layout.trap_values)?;
+    let grid_size = tree.grid_size();
+    if x >= grid_size || y >= grid_size {
+        bail!("move ({x}, {y}) is outside the {grid_size}x{grid_size} grid");
+    }
+    let commitment = grid_commitment(
+        &tree.root(),
+        salt,
+        &hash_to_field(&domain::DEFENDER, defender)?,
+    );
+    let (indices, siblings) = tree.proof(cell_index(grid_size, x, y));
+    let trap_value = u8::from(layout.is_trap(x, y));
+    let [head, last] = key_fields(defender);
+    let indices = indices
+        .iter()
+        .map(|bit| format!("\"{bit}\""))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let siblings = siblings
+        .iter()
+        .map(|sibling| format!("\"0x{}\"", hex::encode(sibling)))
+        .collect::<Vec<_>>()
+        .join(", ");
+    Ok(format!(
+        "[public_inputs]\nmove_x = \"{x}\"\nmove_y = \"{y}\"\nis_hit = \"{trap_value}\"\ngrid_size = \"{grid_size}\"\ngrid_commitment = \"0x{}\"\n\n[private_inputs]\ntrap_value = \"{trap_value}\"\nsalt = \"0x{}\"\ndefender = [\"0x{}\", \"0x{}\"]\ntrap_merkle_proof_indices = [{indices}]\ntrap_merkle_proof_siblings = [{siblings}]\n",
+        hex::encode(commitment),
+        hex::encode(salt),
+        hex::encode(head),
+        hex::encode(last)
+    ))
+}
+
+/// Prover.toml for the grid-setup circuit
+///
+/// The circuit takes a trap value per cell of the largest grid; the entries
+/// past the layout's cells are 0. `defender` is the [`defender_key`] of the
+/// defender, which the circuit takes as its [`key_fields`].
+pub fn setup_prover_toml(
+    layout: &TrapLayout,
+    grid_commitment: &FieldBytes,
+    salt: &FieldBytes,
+    defender: &[u8; 32],
+) -> String {
+    let [head, last] = key_fields(defender);
+    let max_cells = (MAX_GRID_SIZE * MAX_GRID_SIZE) as usize;
+    let trap_values = layout
+        .trap_values
+        .iter()
+        .chain(std::iter::repeat(&0))
+        .take(max_cells)
+        .map(|value| format!("\"{value}\""))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!(
+        "[public_inputs]\ngrid_commitment = \"0x{}\"\ntrap_count = \"{}\"\ngrid_size = \"{}\"\ndefender = [\"0x{}\", \"0x{}\"]\n\n[private_inputs]\ntrap_values = [{trap_values}]\nsalt = \"0x{}\"\n",
+        hex::encode(grid_commitment),
+        layout.trap_count(),
+        layout.grid_size(),
+        hex::encode(head),
+        hex::encode(last),
+        hex::encode(salt)
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use trap_grid_encoding::encode_u64;
+
+    #[test]
+    fn test_circuit_ids() {
+        for circuit in Circuit::ALL {
+            assert_eq!(circuit.id().parse::<Circuit>().unwrap(), circuit);
+        }
+        assert!("position_movement".parse::<Circuit>().is_err());
+    }
+
+    #[test]
+    fn test_prover_toml() {
+        // circuits/position-movement/Prover.toml: the grid-setup sample grid,
+        // its commitment and the defender it is bound to
+        let mut trap_values = vec![0; 64];
+        for x in 0..8 {
+            trap_values[cell_index(8, x, x)] = 1;
+        }
+        trap_values[cell_index(8, 0, 7)] = 1;
+        trap_values[cell_index(8, 7, 0)] = 1;
+        let layout = TrapLayout {
+            trap_values,
+            salt: None,
+        };
+        let toml = prover_toml(&layout, &encode_u64(12345), &[0x11; 32], 2, 2).unwrap();
+        assert!(toml.starts_with(
+            "[public_inputs]\nmove_x = \"2\"\nmove_y = \"2\"\nis_hit = \"1\"\ngrid_size = \"8\"\n\
+             grid_commitment = \"0x2ee5032b9c54a99742b33bb21516e00e4872bd72a4b0ee205189c5a365e65f85\"\n\n\
+             [private_inputs]\ntrap_value = \"1\"\n"
+        ));
+        // Leaf 18 = 0b00010010, padded past the tree's 6 levels
+        assert!(toml.contains(
+            "trap_merkle_proof_indices = [\"0\", \"1\", \"0\", \"0\", \"1\", \"0\", \"0\", \"0\"]\n"
+        ));
+        assert!(toml.contains(&format!(
+            "defender = [\"0x00{}\", \"0x{}11\"]\n",
+            "11".repeat(31),
+            "00".repeat(31)
+        )));
+        assert!(toml.ends_with(&format!(", \"0x{0}\", \"0x{0}\"]\n", "00".repeat(32))));
+        assert!(prover_toml(&layout, &encode_u64(12345), &[0x11; 32], 8, 0).is_err());
+    }
+
+    #[test]
+    fn test_setup_prover_toml() {
+        let mut trap_values = vec![0; 64];
+        trap_values[1] = 1;
+        let layout = TrapLayout {
+            trap_values,
+            salt: None,
+        };
+        let toml = setup_prover_toml(&layout, &[0xab; 32], &[1; 32], &[0xcd; 32]);
+        assert!(toml.starts_with(&format!(
+            "[public_inputs]\ngrid_commitment = \"0x{}\"\ntrap_count = \"1\"\ngrid_size = \"8\"\n",
+            "ab".repeat(32)
+        )));
+        assert!(toml.contains(&format!(
+            "defender = [\"0x00{}\", \"0x{}cd\"]\n",
+            "cd".repeat(31),
+            "00".repeat(31)
+        )));
+        assert!(toml.contains("trap_values = [\"0\", \"1\", \"0\","));
+        // Padded to the 256 cells of the largest grid
+        assert_eq!(toml.matches("\"0\"").count(), 255);
+        assert!(toml.ends_with(&format!("salt = \"0x{}\"\n", "01".repeat(32))));
+    }
+}
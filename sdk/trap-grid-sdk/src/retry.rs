@@ -0,0 +1,184 @@
+//! Retrying rejected submissions
+//!
+//! Bots that send many transactions from one account race their own sequence
+//! numbers, outrun resource estimates and meet a busy RPC server. The
+//! [`Submitter`](crate::tx::Submitter) classifies each rejection and sends
+//! again, rebuilt or re-simulated as needed, backing off exponentially.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use stellar_xdr::curr::{
+    DiagnosticEvent, InvokeHostFunctionResult, OperationResult, OperationResultTr,
+    TransactionResultResult,
+};
+
+/// How often and how patiently a transaction is sent again
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RetryPolicy {
+    /// Attempts in total, including the first
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(16),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Send every transaction once
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Wait before attempt `attempt + 1`, doubling from `initial_backoff`
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_backoff)
+    }
+}
+
+/// What a rejected transaction needs before it is sent again
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Retry {
+    /// The RPC server is busy (`TRY_AGAIN_LATER`); send it unchanged
+    Later,
+    /// `txBAD_SEQ`: another transaction used the sequence number; rebuild
+    Sequence,
+    /// `txSOROBAN_INVALID` or resources ran out; simulate again
+    Resimulate,
+}
+
+/// A transaction the network refused or failed to apply
+#[derive(Debug)]
+pub struct Rejected {
+    /// `None` when sending it again cannot help, e.g. a contract error
+    pub retry: Option<Retry>,
+    pub message: String,
+    /// The host's diagnostic events, oldest first; empty unless the RPC
+    /// server returned them
+    pub events: Vec<DiagnosticEvent>,
+}
+
+impl fmt::Display for Rejected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for Rejected {}
+
+/// Whether a transaction with this result is worth sending again
+pub fn classify(result: &TransactionResultResult) -> Option<Retry> {
+    match result {
+        TransactionResultResult::TxBadSeq => Some(Retry::Sequence),
+        TransactionResultResult::TxSorobanInvalid => Some(Retry::Resimulate),
+        TransactionResultResult::TxFailed(operations) => {
+            operations.iter().find_map(|operation| match operation {
+                OperationResult::OpInner(OperationResultTr::InvokeHostFunction(
+                    InvokeHostFunctionResult::ResourceLimitExceeded
+                    | InvokeHostFunctionResult::InsufficientRefundableFee,
+                )) => Some(Retry::Resimulate),
+                _ => None,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Next sequence number of each source account
+///
+/// The RPC server only knows applied transactions, so back-to-back
+/// submissions would reuse a number; this remembers the ones already sent.
+#[derive(Debug, Default)]
+pub(crate) struct Sequences {
+    next: Mutex<HashMap<[u8; 32], i64>>,
+}
+
+impl Sequences {
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<[u8; 32], i64>> {
+        self.next.lock().expect("sequence lock poisoned")
+    }
+
+    /// Number for a new transaction, given the account's applied `current` one
+    pub fn next(&self, account: [u8; 32], current: i64) -> i64 {
+        let sent = self.lock().get(&account).copied().unwrap_or(0);
+        sent.max(current + 1)
+    }
+
+    /// Record that a transaction with `sequence` was accepted
+    pub fn sent(&self, account: [u8; 32], sequence: i64) {
+        let mut next = self.lock();
+        let entry = next.entry(account).or_insert(0);
+        *entry = (*entry).max(sequence + 1);
+    }
+
+    /// Trust the RPC server again after a `txBAD_SEQ`
+    pub fn forget(&self, account: [u8; 32]) {
+        self.lock().remove(&account);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles_up_to_the_cap() {
+        let policy = RetryPolicy::default();
+        let waits: Vec<_> = (1..=6).map(|n| policy.backoff(n).as_secs()).collect();
+        assert_eq!(waits, [1, 2, 4, 8, 16, 16]);
+    }
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(
+            classify(&TransactionResultResult::TxBadSeq),
+            Some(Retry::Sequence)
+        );
+        let exceeded = TransactionResultResult::TxFailed(
+            vec![OperationResult::OpInner(
+                OperationResultTr::InvokeHostFunction(
+                    InvokeHostFunctionResult::ResourceLimitExceeded,
+                ),
+            )]
+            .try_into()
+            .unwrap(),
+        );
+        assert_eq!(classify(&exceeded), Some(Retry::Resimulate));
+        let trapped = TransactionResultResult::TxFailed(
+            vec![OperationResult::OpInner(
+                OperationResultTr::InvokeHostFunction(InvokeHostFunctionResult::Trapped),
+            )]
+            .try_into()
+            .unwrap(),
+        );
+        assert_eq!(classify(&trapped), None);
+    }
+
+    #[test]
+    fn test_sequences_stay_ahead_of_the_rpc() {
+        let sequences = Sequences::default();
+        let account = [1; 32];
+        assert_eq!(sequences.next(account, 10), 11);
+        sequences.sent(account, 11);
+        // The RPC server has not seen 11 applied yet
+        assert_eq!(sequences.next(account, 10), 12);
+        assert_eq!(sequences.next(account, 20), 21);
+        sequences.forget(account);
+        assert_eq!(sequences.next(account, 10), 11);
+    }
+}
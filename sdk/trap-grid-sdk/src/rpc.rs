@@ -0,0 +1,326 @@
+//! Minimal Soroban RPC client
+//!
+//! Only the JSON-RPC methods the SDK and CLI need are implemented. Contract state is
+//! read straight from ledger entries so no source account is required;
+//! transactions are simulated and submitted for [`crate::tx`].
+
+use anyhow::{anyhow, Context, Result};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use stellar_xdr::curr::{
+    AccountId, ContractDataDurability, LedgerEntryData, LedgerKey, LedgerKeyAccount,
+    LedgerKeyContractData, Limits, ReadXdr, ScAddress, ScVal, TransactionEnvelope, WriteXdr,
+};
+
+/// Default RPC endpoint (Stellar testnet)
+pub const DEFAULT_RPC_URL: &str = "https://soroban-testnet.stellar.org";
+
+/// RPC endpoint of the local quickstart network (see contracts/deploy-local.sh)
+pub const LOCAL_RPC_URL: &str = "http://localhost:8000/soroban/rpc";
+
+pub struct RpcClient {
+    url: String,
+    agent: ureq::Agent,
+}
+
+#[derive(Deserialize)]
+struct RpcResponse<T> {
+    result: Option<T>,
+    error: Option<RpcError>,
+}
+
+#[derive(Deserialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct GetLedgerEntriesResult {
+    #[serde(default)]
+    entries: Option<Vec<LedgerEntryResult>>,
+}
+
+#[derive(Deserialize)]
+struct LedgerEntryResult {
+    xdr: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetLatestLedgerResult {
+    pub sequence: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetEventsResult {
+    #[serde(default)]
+    pub events: Vec<EventInfo>,
+    #[serde(default)]
+    pub cursor: Option<String>,
+    pub latest_ledger: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventInfo {
+    pub id: String,
+    pub ledger: u32,
+    pub ledger_closed_at: String,
+    pub tx_hash: String,
+    pub topic: Vec<String>,
+    pub value: String,
+}
+
+impl EventInfo {
+    /// Decoded topics and value
+    pub fn decode(&self) -> Result<(Vec<ScVal>, ScVal)> {
+        let topics = self
+            .topic
+            .iter()
+            .map(|topic| ScVal::from_xdr_base64(topic, Limits::none()))
+            .collect::<Result<_, _>>()?;
+        Ok((topics, ScVal::from_xdr_base64(&self.value, Limits::none())?))
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetNetworkResult {
+    pub passphrase: String,
+    #[serde(default)]
+    pub friendbot_url: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulateTransactionResult {
+    #[serde(default)]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub transaction_data: Option<String>,
+    #[serde(default)]
+    pub min_resource_fee: Option<String>,
+    #[serde(default)]
+    pub results: Vec<SimulateHostFunctionResult>,
+    /// Base64 `DiagnosticEvent`s
+    #[serde(default)]
+    pub events: Vec<String>,
+    pub latest_ledger: u32,
+}
+
+#[derive(Deserialize)]
+pub struct SimulateHostFunctionResult {
+    #[serde(default)]
+    pub auth: Vec<String>,
+    /// Base64 `ScVal` the host function returned
+    #[serde(default)]
+    pub xdr: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SendTransactionResult {
+    pub status: String,
+    pub hash: String,
+    #[serde(default)]
+    pub error_result_xdr: Option<String>,
+    #[serde(default)]
+    pub diagnostic_events_xdr: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetTransactionResult {
+    pub status: String,
+    #[serde(default)]
+    pub result_xdr: Option<String>,
+    #[serde(default)]
+    pub result_meta_xdr: Option<String>,
+    #[serde(default)]
+    pub diagnostic_events_xdr: Vec<String>,
+}
+
+impl RpcClient {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            agent: ureq::Agent::new_with_defaults(),
+        }
+    }
+
+    fn request<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T> {
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
+        let response: RpcResponse<T> = self
+            .agent
+            .post(&self.url)
+            .send_json(&body)
+            .with_context(|| format!("RPC request {method} to {} failed", self.url))?
+            .body_mut()
+            .read_json()
+            .with_context(|| format!("invalid RPC response for {method}"))?;
+
+        if let Some(error) = response.error {
+            return Err(anyhow!("RPC error {}: {}", error.code, error.message));
+        }
+        response
+            .result
+            .ok_or_else(|| anyhow!("RPC response for {method} has no result"))
+    }
+
+    /// Read a contract data entry, returning `None` if it does not exist (or has expired)
+    pub fn get_contract_data(
+        &self,
+        contract: &ScAddress,
+        key: ScVal,
+        durability: ContractDataDurability,
+    ) -> Result<Option<ScVal>> {
+        let ledger_key = LedgerKey::ContractData(LedgerKeyContractData {
+            contract: contract.clone(),
+            key,
+            durability,
+        });
+        let result: GetLedgerEntriesResult = self.request(
+            "getLedgerEntries",
+            json!({ "keys": [ledger_key.to_xdr_base64(Limits::none())?] }),
+        )?;
+
+        let Some(entry) = result.entries.unwrap_or_default().into_iter().next() else {
+            return Ok(None);
+        };
+        match LedgerEntryData::from_xdr_base64(&entry.xdr, Limits::none())? {
+            LedgerEntryData::ContractData(data) => Ok(Some(data.val)),
+            other => Err(anyhow!("unexpected ledger entry type {}", other.name())),
+        }
+    }
+
+    /// Whether the server reports itself healthy
+    pub fn is_healthy(&self) -> bool {
+        self.request::<Value>("getHealth", json!({}))
+            .is_ok_and(|health| health["status"] == "healthy")
+    }
+
+    pub fn get_network(&self) -> Result<GetNetworkResult> {
+        self.request("getNetwork", json!({}))
+    }
+
+    /// Current sequence number of an account
+    pub fn get_account_sequence(&self, account: &AccountId) -> Result<i64> {
+        let ledger_key = LedgerKey::Account(LedgerKeyAccount {
+            account_id: account.clone(),
+        });
+        let result: GetLedgerEntriesResult = self.request(
+            "getLedgerEntries",
+            json!({ "keys": [ledger_key.to_xdr_base64(Limits::none())?] }),
+        )?;
+
+        let entry = result
+            .entries
+            .unwrap_or_default()
+            .into_iter()
+            .next()
+            .with_context(|| format!("account {account} does not exist; fund it first"))?;
+        match LedgerEntryData::from_xdr_base64(&entry.xdr, Limits::none())? {
+            LedgerEntryData::Account(account) => Ok(account.seq_num.0),
+            other => Err(anyhow!("unexpected ledger entry type {}", other.name())),
+        }
+    }
+
+    pub fn simulate_transaction(
+        &self,
+        envelope: &TransactionEnvelope,
+    ) -> Result<SimulateTransactionResult> {
+        self.request(
+            "simulateTransaction",
+            json!({ "transaction": envelope.to_xdr_base64(Limits::none())? }),
+        )
+    }
+
+    pub fn send_transaction(
+        &self,
+        envelope: &TransactionEnvelope,
+    ) -> Result<SendTransactionResult> {
+        self.request(
+            "sendTransaction",
+            json!({ "transaction": envelope.to_xdr_base64(Limits::none())? }),
+        )
+    }
+
+    pub fn get_transaction(&self, hash: &str) -> Result<GetTransactionResult> {
+        self.request("getTransaction", json!({ "hash": hash }))
+    }
+
+    pub fn get_latest_ledger(&self) -> Result<u32> {
+        let result: GetLatestLedgerResult = self.request("getLatestLedger", json!({}))?;
+        Ok(result.sequence)
+    }
+
+    /// Contract events matching one topic filter (`None` segments match any topic,
+    /// no segments match every event), starting at `start_ledger` or continuing
+    /// after `cursor`
+    pub fn get_events(
+        &self,
+        contract: &ScAddress,
+        topics: &[Option<ScVal>],
+        start: EventsStart<'_>,
+    ) -> Result<GetEventsResult> {
+        if topics.is_empty() {
+            self.get_events_matching(contract, &[], start)
+        } else {
+            self.get_events_matching(contract, &[topics], start)
+        }
+    }
+
+    /// Contract events matching any of up to five topic filters, as in
+    /// [`Self::get_events`]; no filters match every event
+    pub fn get_events_matching(
+        &self,
+        contract: &ScAddress,
+        filters: &[&[Option<ScVal>]],
+        start: EventsStart<'_>,
+    ) -> Result<GetEventsResult> {
+        let filters = filters
+            .iter()
+            .map(|topics| {
+                topics
+                    .iter()
+                    .map(|topic| match topic {
+                        Some(val) => val.to_xdr_base64(Limits::none()),
+                        None => Ok("*".to_string()),
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut filter = json!({
+            "type": "contract",
+            "contractIds": [contract.to_string()],
+        });
+        if !filters.is_empty() {
+            filter["topics"] = json!(filters);
+        }
+        let mut params = json!({ "filters": [filter] });
+        match start {
+            EventsStart::Ledger(ledger) => {
+                params["startLedger"] = json!(ledger);
+                params["pagination"] = json!({ "limit": 100 });
+            }
+            EventsStart::Cursor(cursor) => {
+                params["pagination"] = json!({ "cursor": cursor, "limit": 100 });
+            }
+        }
+        self.request("getEvents", params)
+    }
+}
+
+/// Where a `getEvents` query starts
+pub enum EventsStart<'a> {
+    Ledger(u32),
+    Cursor(&'a str),
+}
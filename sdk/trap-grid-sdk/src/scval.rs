@@ -0,0 +1,78 @@
+//! `ScVal` encoding helpers shared by the mirrored types
+
+use anyhow::{anyhow, bail, Result};
+use stellar_xdr::curr::{ScAddress, ScMap, ScSymbol, ScVal, ScVec};
+
+pub fn symbol(name: &str) -> Result<ScVal> {
+    let symbol: ScSymbol = name
+        .try_into()
+        .map_err(|_| anyhow!("invalid symbol `{name}`"))?;
+    Ok(ScVal::Symbol(symbol))
+}
+
+/// Encode a `#[contracttype]` struct: a map keyed by field name
+pub fn map(fields: Vec<(&str, ScVal)>) -> Result<ScVal> {
+    let pairs = fields
+        .into_iter()
+        .map(|(name, val)| Ok((symbol(name)?, val)))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(ScVal::Map(Some(ScMap::sorted_from_pairs(
+        pairs.into_iter(),
+    )?)))
+}
+
+/// Encode a `#[contracttype]` enum variant: a vec of its name and payload
+pub fn variant(name: &str, payload: Option<ScVal>) -> Result<ScVal> {
+    let mut items = vec![symbol(name)?];
+    items.extend(payload);
+    Ok(ScVal::Vec(Some(ScVec(items.try_into()?))))
+}
+
+pub fn as_map<'a>(val: &'a ScVal, what: &str) -> Result<&'a ScMap> {
+    match val {
+        ScVal::Map(Some(map)) => Ok(map),
+        other => bail!("expected {what} map, found {}", other.name()),
+    }
+}
+
+pub fn map_field<'a>(map: &'a ScMap, name: &str) -> Result<&'a ScVal> {
+    map.iter()
+        .find(|entry| matches!(&entry.key, ScVal::Symbol(s) if s.as_vec() == name.as_bytes()))
+        .map(|entry| &entry.val)
+        .ok_or_else(|| anyhow!("missing field `{name}`"))
+}
+
+pub fn as_u32(val: &ScVal) -> Result<u32> {
+    match val {
+        ScVal::U32(v) => Ok(*v),
+        other => bail!("expected u32, found {}", other.name()),
+    }
+}
+
+pub fn as_bool(val: &ScVal) -> Result<bool> {
+    match val {
+        ScVal::Bool(v) => Ok(*v),
+        other => bail!("expected bool, found {}", other.name()),
+    }
+}
+
+pub fn as_i128(val: &ScVal) -> Result<i128> {
+    match val {
+        ScVal::I128(parts) => Ok(((parts.hi as i128) << 64) | parts.lo as i128),
+        other => bail!("expected i128, found {}", other.name()),
+    }
+}
+
+pub fn as_address(val: &ScVal) -> Result<String> {
+    match val {
+        ScVal::Address(address) => Ok(address.to_string()),
+        other => bail!("expected address, found {}", other.name()),
+    }
+}
+
+pub fn address(strkey: &str) -> Result<ScVal> {
+    let address: ScAddress = strkey
+        .parse()
+        .map_err(|e| anyhow!("invalid address {strkey}: {e}"))?;
+    Ok(ScVal::Address(address))
+}
@@ -0,0 +1,217 @@
+//! Transaction signers
+//!
+//! [`Submitter`](crate::tx::Submitter) and [`GameClient`](crate::GameClient)
+//! build transactions the same way whoever holds the key:
+//! - [`Keypair`] - a local secret key
+//! - [`Ledger`] - the Stellar app on a Ledger device (`ledger` feature)
+//! - [`Passthrough`] - no key at all; transactions come back unsigned, for a
+//!   browser wallet such as Freighter to sign
+
+use anyhow::{bail, Result};
+use sha2::{Digest, Sha256};
+
+use crate::Keypair;
+
+/// Signs transactions and Soroban authorization entries for one account
+///
+/// Both methods get the unhashed signature base, since hardware wallets parse
+/// it to show what is being signed; the signature is over its SHA-256.
+pub trait TransactionSigner {
+    /// The account's ed25519 public key
+    fn public_key(&self) -> [u8; 32];
+
+    /// Sign `signature_base`, the XDR of a `TransactionSignaturePayload`;
+    /// `None` leaves the transaction unsigned
+    fn sign_transaction(&self, signature_base: &[u8]) -> Result<Option<[u8; 64]>>;
+
+    /// Sign `preimage`, the XDR of an authorization entry's `HashIdPreimage`
+    fn sign_authorization(&self, preimage: &[u8]) -> Result<[u8; 64]>;
+
+    /// `G...` account address
+    fn address(&self) -> String {
+        stellar_strkey::ed25519::PublicKey(self.public_key()).to_string()
+    }
+}
+
+impl TransactionSigner for Keypair {
+    fn public_key(&self) -> [u8; 32] {
+        Keypair::public_key(self)
+    }
+
+    fn sign_transaction(&self, signature_base: &[u8]) -> Result<Option<[u8; 64]>> {
+        Ok(Some(self.sign(&Sha256::digest(signature_base))))
+    }
+
+    fn sign_authorization(&self, preimage: &[u8]) -> Result<[u8; 64]> {
+        Ok(self.sign(&Sha256::digest(preimage)))
+    }
+}
+
+/// An account whose key lives in a browser wallet
+///
+/// Transactions built for it are returned unsigned; hand their base64 XDR to
+/// the wallet (Freighter's `signTransaction`) and send the signed result with
+/// [`Submitter::send_xdr`](crate::tx::Submitter::send_xdr). Authorization
+/// entries for other accounts are signed by them through
+/// [`AuthRequest`](crate::tx::AuthRequest).
+pub struct Passthrough {
+    public_key: [u8; 32],
+}
+
+impl Passthrough {
+    /// Passthrough for the `G...` account `address`
+    pub fn new(address: &str) -> Result<Self> {
+        let Ok(key) = stellar_strkey::ed25519::PublicKey::from_string(address.trim()) else {
+            bail!("invalid account {address}; expected a G... strkey");
+        };
+        Ok(Self { public_key: key.0 })
+    }
+}
+
+impl TransactionSigner for Passthrough {
+    fn public_key(&self) -> [u8; 32] {
+        self.public_key
+    }
+
+    fn sign_transaction(&self, _signature_base: &[u8]) -> Result<Option<[u8; 64]>> {
+        Ok(None)
+    }
+
+    fn sign_authorization(&self, _preimage: &[u8]) -> Result<[u8; 64]> {
+        bail!(
+            "{} signs in its wallet; collect its authorization with an AuthRequest",
+            self.address()
+        )
+    }
+}
+
+#[cfg(feature = "ledger")]
+pub use self::ledger::Ledger;
+
+#[cfg(feature = "ledger")]
+mod ledger {
+    use anyhow::{anyhow, bail, ensure, Context, Result};
+    use ledger_transport::APDUCommand;
+    use ledger_transport_hid::hidapi::HidApi;
+    use ledger_transport_hid::TransportNativeHID;
+
+    use super::TransactionSigner;
+
+    const CLA: u8 = 0xe0;
+    const INS_GET_PUBLIC_KEY: u8 = 0x02;
+    const INS_SIGN_TX: u8 = 0x04;
+    const INS_SIGN_SOROBAN_AUTHORIZATION: u8 = 0x0a;
+    const P1_FIRST: u8 = 0x00;
+    const P1_MORE: u8 = 0x80;
+    const P2_LAST: u8 = 0x00;
+    const P2_MORE: u8 = 0x80;
+    const RETURN_OK: u16 = 0x9000;
+    const RETURN_REJECTED: u16 = 0x6985;
+    /// Largest APDU payload the Stellar app accepts
+    const CHUNK_SIZE: usize = 150;
+    const HARDENED: u32 = 0x8000_0000;
+
+    /// The Stellar app on a Ledger device, for account `m/44'/148'/{index}'`
+    ///
+    /// Every signature is confirmed on the device.
+    pub struct Ledger {
+        transport: TransportNativeHID,
+        path: Vec<u8>,
+        public_key: [u8; 32],
+    }
+
+    impl Ledger {
+        /// Connect to the first Ledger found, with the Stellar app open
+        pub fn connect(index: u32) -> Result<Self> {
+            let hid = HidApi::new().context("failed to open the USB HID API")?;
+            let transport =
+                TransportNativeHID::new(&hid).map_err(|e| anyhow!("no Ledger found: {e}"))?;
+            let mut path = vec![3];
+            for component in [44, 148, index] {
+                path.extend((HARDENED | component).to_be_bytes());
+            }
+            let mut ledger = Self {
+                transport,
+                path,
+                public_key: [0; 32],
+            };
+            let answer = ledger.exchange(INS_GET_PUBLIC_KEY, 0x00, 0x00, ledger.path.clone())?;
+            ledger.public_key = answer
+                .get(..32)
+                .context("short public key from the Ledger")?
+                .try_into()?;
+            Ok(ledger)
+        }
+
+        fn exchange(&self, ins: u8, p1: u8, p2: u8, data: Vec<u8>) -> Result<Vec<u8>> {
+            let command = APDUCommand {
+                cla: CLA,
+                ins,
+                p1,
+                p2,
+                data,
+            };
+            let answer = self
+                .transport
+                .exchange(&command)
+                .map_err(|e| anyhow!("Ledger communication failed: {e}"))?;
+            match answer.retcode() {
+                RETURN_OK => Ok(answer.data().to_vec()),
+                RETURN_REJECTED => bail!("rejected on the Ledger"),
+                code => bail!("Ledger error {code:#06x}; is the Stellar app open?"),
+            }
+        }
+
+        /// Send the derivation path and `payload` in chunks; the last answer
+        /// holds the signature
+        fn sign(&self, ins: u8, payload: &[u8]) -> Result<[u8; 64]> {
+            let mut data = self.path.clone();
+            data.extend_from_slice(payload);
+            let chunks: Vec<_> = data.chunks(CHUNK_SIZE).collect();
+            let mut answer = Vec::new();
+            for (index, chunk) in chunks.iter().enumerate() {
+                let p1 = if index == 0 { P1_FIRST } else { P1_MORE };
+                let p2 = if index + 1 == chunks.len() {
+                    P2_LAST
+                } else {
+                    P2_MORE
+                };
+                answer = self.exchange(ins, p1, p2, chunk.to_vec())?;
+            }
+            ensure!(answer.len() >= 64, "short signature from the Ledger");
+            Ok(answer[..64].try_into()?)
+        }
+    }
+
+    impl TransactionSigner for Ledger {
+        fn public_key(&self) -> [u8; 32] {
+            self.public_key
+        }
+
+        fn sign_transaction(&self, signature_base: &[u8]) -> Result<Option<[u8; 64]>> {
+            self.sign(INS_SIGN_TX, signature_base).map(Some)
+        }
+
+        fn sign_authorization(&self, preimage: &[u8]) -> Result<[u8; 64]> {
+            self.sign(INS_SIGN_SOROBAN_AUTHORIZATION, preimage)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_passthrough_leaves_transactions_unsigned() {
+        let keypair = Keypair::generate().unwrap();
+        let wallet = Passthrough::new(&keypair.address()).unwrap();
+        assert_eq!(TransactionSigner::public_key(&wallet), keypair.public_key());
+        assert_eq!(wallet.sign_transaction(b"tx").unwrap(), None);
+        assert!(wallet.sign_authorization(b"entry").is_err());
+        assert!(Passthrough::new(&keypair.secret()).is_err());
+
+        let signature = keypair.sign_transaction(b"tx").unwrap().unwrap();
+        assert_eq!(signature, keypair.sign(&Sha256::digest(b"tx")));
+    }
+}
@@ -0,0 +1,231 @@
+//! Offline game simulator
+//!
+//! Plays complete games between an [`AttackerStrategy`] and a known trap
+//! layout without a chain or proofs, applying the contract's end-of-game
+//! rules, and aggregates the results into [`SimStats`]. Used to see how a
+//! win threshold or scoring change plays out before deploying it.
+
+use serde::Serialize;
+
+use crate::layout::{TrapLayout, NUM_CELLS};
+use crate::strategy::{AttackerStrategy, Rng, StrategyKind};
+use crate::{Move, MAX_MOVES};
+
+/// The contract's winner rule, both for the last cell and for `end_game`:
+/// the attacker wins with more than half of the moves made being hits
+pub fn attacker_wins(hits: u32, moves: u32) -> bool {
+    hits > moves / 2
+}
+
+/// When a simulated game stops
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct Ending {
+    /// Stop after this many moves (`end_game`); [`MAX_MOVES`] plays every cell
+    pub max_moves: u32,
+    /// The game ends by `end_game` as soon as the attacker would win
+    pub end_when_ahead: bool,
+}
+
+impl Default for Ending {
+    fn default() -> Self {
+        Self {
+            max_moves: MAX_MOVES,
+            end_when_ahead: false,
+        }
+    }
+}
+
+/// Result of one simulated game
+#[derive(Clone, Debug, Serialize)]
+pub struct GameOutcome {
+    pub moves: Vec<Move>,
+    pub hits: u32,
+    pub misses: u32,
+    pub attacker_wins: bool,
+}
+
+/// Play one game of `strategy` against `layout`
+pub fn play_game(
+    layout: &TrapLayout,
+    strategy: &mut dyn AttackerStrategy,
+    ending: Ending,
+) -> GameOutcome {
+    let mut moves: Vec<Move> = Vec::new();
+    let mut hits = 0;
+    while (moves.len() as u32) < ending.max_moves.min(MAX_MOVES) {
+        let Some((x, y)) = strategy.next_cell(&moves) else {
+            break;
+        };
+        let is_hit = layout.is_trap(x, y);
+        hits += u32::from(is_hit);
+        moves.push(Move {
+            x,
+            y,
+            is_hit,
+            verified: true,
+        });
+        if ending.end_when_ahead && attacker_wins(hits, moves.len() as u32) {
+            break;
+        }
+    }
+    let played = moves.len() as u32;
+    GameOutcome {
+        moves,
+        hits,
+        misses: played - hits,
+        attacker_wins: attacker_wins(hits, played),
+    }
+}
+
+/// A uniformly random layout with `traps` traps
+pub fn random_layout(rng: &mut Rng, traps: usize) -> TrapLayout {
+    let mut cells: Vec<usize> = (0..NUM_CELLS).collect();
+    for i in 0..traps.min(NUM_CELLS) {
+        let j = i + rng.below(NUM_CELLS - i);
+        cells.swap(i, j);
+    }
+    let mut trap_values = vec![0u8; NUM_CELLS];
+    for cell in &cells[..traps.min(NUM_CELLS)] {
+        trap_values[*cell] = 1;
+    }
+    TrapLayout {
+        trap_values,
+        salt: None,
+    }
+}
+
+/// Aggregated results of many games
+#[derive(Clone, Debug, Serialize)]
+pub struct SimStats {
+    pub strategy: String,
+    pub games: u32,
+    /// Hits over moves, across all games
+    pub hit_rate: f64,
+    pub mean_hits: f64,
+    /// Average number of moves per game
+    pub mean_length: f64,
+    pub attacker_win_rate: f64,
+    /// `hits_histogram[h]` is the number of games that ended with `h` hits
+    pub hits_histogram: Vec<u32>,
+    /// `first_hit_histogram[m]` is the number of games whose first hit was move `m + 1`
+    pub first_hit_histogram: Vec<u32>,
+}
+
+impl SimStats {
+    /// Share of games with at least `min_hits` hits, i.e. the attacker's
+    /// win rate under a fixed "find `min_hits` traps" threshold
+    pub fn win_rate_with_min_hits(&self, min_hits: u32) -> f64 {
+        let wins: u32 = self.hits_histogram.iter().skip(min_hits as usize).sum();
+        wins as f64 / self.games.max(1) as f64
+    }
+}
+
+/// How the layouts of a simulation are chosen
+#[derive(Clone, Debug)]
+pub enum Layouts {
+    /// The same known layout every game
+    Fixed(TrapLayout),
+    /// A fresh uniformly random layout with this many traps every game
+    Random { traps: u32 },
+}
+
+impl Layouts {
+    fn trap_count(&self) -> u32 {
+        match self {
+            Layouts::Fixed(layout) => layout.trap_count() as u32,
+            Layouts::Random { traps } => *traps,
+        }
+    }
+}
+
+/// Play `games` games of `kind` and aggregate them; the same seed gives the same stats
+pub fn simulate(
+    kind: StrategyKind,
+    layouts: &Layouts,
+    ending: Ending,
+    games: u32,
+    seed: u64,
+) -> SimStats {
+    let mut rng = Rng::new(seed);
+    let mut stats = SimStats {
+        strategy: kind.name().to_string(),
+        games,
+        hit_rate: 0.0,
+        mean_hits: 0.0,
+        mean_length: 0.0,
+        attacker_win_rate: 0.0,
+        hits_histogram: vec![0; NUM_CELLS + 1],
+        first_hit_histogram: vec![0; NUM_CELLS],
+    };
+    let (mut hits, mut moves, mut wins) = (0u64, 0u64, 0u32);
+    for _ in 0..games {
+        let layout = match layouts {
+            Layouts::Fixed(layout) => layout.clone(),
+            Layouts::Random { traps } => random_layout(&mut rng, *traps as usize),
+        };
+        let mut strategy = kind.build(Rng::new(rng.next_u64()), layouts.trap_count());
+        let outcome = play_game(&layout, strategy.as_mut(), ending);
+
+        hits += u64::from(outcome.hits);
+        moves += outcome.moves.len() as u64;
+        wins += u32::from(outcome.attacker_wins);
+        stats.hits_histogram[outcome.hits as usize] += 1;
+        if let Some(first) = outcome.moves.iter().position(|m| m.is_hit) {
+            stats.first_hit_histogram[first] += 1;
+        }
+    }
+    let games = f64::from(games.max(1));
+    stats.hit_rate = hits as f64 / moves.max(1) as f64;
+    stats.mean_hits = hits as f64 / games;
+    stats.mean_length = moves as f64 / games;
+    stats.attacker_win_rate = f64::from(wins) / games;
+    stats
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_full_games_find_every_trap() {
+        let stats = simulate(
+            StrategyKind::Random,
+            &Layouts::Random { traps: 10 },
+            Ending::default(),
+            20,
+            7,
+        );
+        // Playing every cell finds every trap, and 10 of 64 never wins
+        assert_eq!(stats.hits_histogram[10], 20);
+        assert_eq!(stats.mean_length, 64.0);
+        assert_eq!(stats.attacker_win_rate, 0.0);
+        assert_eq!(stats.win_rate_with_min_hits(10), 1.0);
+
+        let again = simulate(
+            StrategyKind::Random,
+            &Layouts::Random { traps: 10 },
+            Ending::default(),
+            20,
+            7,
+        );
+        assert_eq!(stats.first_hit_histogram, again.first_hit_histogram);
+    }
+
+    #[test]
+    fn test_end_when_ahead() {
+        let mut rng = Rng::new(3);
+        let layout = random_layout(&mut rng, 64);
+        let mut strategy = StrategyKind::Density.build(rng, 64);
+        let outcome = play_game(
+            &layout,
+            strategy.as_mut(),
+            Ending {
+                max_moves: MAX_MOVES,
+                end_when_ahead: true,
+            },
+        );
+        // One hit out of one move is already "more than half"
+        assert_eq!((outcome.moves.len(), outcome.hits), (1, 1));
+        assert!(outcome.attacker_wins);
+    }
+}
@@ -0,0 +1,308 @@
+//! Attacker strategies
+//!
+//! An [`AttackerStrategy`] looks at the moves recorded so far and picks the
+//! next cell to attack. The attacker wins by finding traps, so every
+//! strategy tries to maximise hits. The reference strategies back
+//! `attacker-bot`, which plays them against live games.
+
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Result};
+
+use crate::{Move, GRID_SIZE};
+
+/// A cell as `(x, y)`
+pub type Cell = (u32, u32);
+
+pub trait AttackerStrategy {
+    fn name(&self) -> &'static str;
+
+    /// Pick an unplayed cell given the moves so far; `None` once every cell is played
+    fn next_cell(&mut self, moves: &[Move]) -> Option<Cell>;
+}
+
+/// The reference strategies, by name
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StrategyKind {
+    Random,
+    HuntTarget,
+    Density,
+}
+
+impl StrategyKind {
+    pub const ALL: [StrategyKind; 3] = [
+        StrategyKind::Random,
+        StrategyKind::HuntTarget,
+        StrategyKind::Density,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            StrategyKind::Random => "random",
+            StrategyKind::HuntTarget => "hunt-target",
+            StrategyKind::Density => "density",
+        }
+    }
+
+    /// `expected_traps` is only used by [`ProbabilityDensity`]
+    pub fn build(self, rng: Rng, expected_traps: u32) -> Box<dyn AttackerStrategy + Send> {
+        match self {
+            StrategyKind::Random => Box::new(RandomStrategy::new(rng)),
+            StrategyKind::HuntTarget => Box::new(HuntTarget::new(rng)),
+            StrategyKind::Density => Box::new(ProbabilityDensity::new(rng, expected_traps)),
+        }
+    }
+}
+
+impl fmt::Display for StrategyKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for StrategyKind {
+    type Err = anyhow::Error;
+
+    fn from_str(name: &str) -> Result<Self> {
+        match Self::ALL.into_iter().find(|kind| kind.name() == name) {
+            Some(kind) => Ok(kind),
+            None => bail!("unknown strategy `{name}` (expected random, hunt-target or density)"),
+        }
+    }
+}
+
+/// SplitMix64: tiny, seedable and good enough for picking cells
+#[derive(Clone, Debug)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn from_entropy() -> Result<Self> {
+        let mut seed = [0u8; 8];
+        getrandom::getrandom(&mut seed).map_err(|e| anyhow!("failed to seed RNG: {e}"))?;
+        Ok(Self(u64::from_le_bytes(seed)))
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `0..n`
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+fn is_played(moves: &[Move], (x, y): Cell) -> bool {
+    moves.iter().any(|m| m.x == x && m.y == y)
+}
+
+/// Unplayed cells in row-major order
+pub fn unplayed_cells(moves: &[Move]) -> Vec<Cell> {
+    (0..GRID_SIZE)
+        .flat_map(|x| (0..GRID_SIZE).map(move |y| (x, y)))
+        .filter(|cell| !is_played(moves, *cell))
+        .collect()
+}
+
+/// The on-grid cells next to `(x, y)`, orthogonally or also diagonally
+fn neighbours((x, y): Cell, diagonal: bool) -> impl Iterator<Item = Cell> {
+    let offsets: &[(i32, i32)] = if diagonal {
+        &[
+            (-1, -1),
+            (-1, 0),
+            (-1, 1),
+            (0, -1),
+            (0, 1),
+            (1, -1),
+            (1, 0),
+            (1, 1),
+        ]
+    } else {
+        &[(-1, 0), (0, -1), (0, 1), (1, 0)]
+    };
+    offsets.iter().filter_map(move |(dx, dy)| {
+        let nx = x.checked_add_signed(*dx).filter(|v| *v < GRID_SIZE)?;
+        let ny = y.checked_add_signed(*dy).filter(|v| *v < GRID_SIZE)?;
+        Some((nx, ny))
+    })
+}
+
+/// Uniformly random unplayed cell
+pub struct RandomStrategy {
+    rng: Rng,
+}
+
+impl RandomStrategy {
+    pub fn new(rng: Rng) -> Self {
+        Self { rng }
+    }
+}
+
+impl AttackerStrategy for RandomStrategy {
+    fn name(&self) -> &'static str {
+        StrategyKind::Random.name()
+    }
+
+    fn next_cell(&mut self, moves: &[Move]) -> Option<Cell> {
+        let cells = unplayed_cells(moves);
+        (!cells.is_empty()).then(|| cells[self.rng.below(cells.len())])
+    }
+}
+
+/// Random hunting until a hit, then the hit's unplayed neighbours
+///
+/// Pays off when defenders cluster their traps; against uniformly placed
+/// traps it plays like [`RandomStrategy`].
+pub struct HuntTarget {
+    rng: Rng,
+}
+
+impl HuntTarget {
+    pub fn new(rng: Rng) -> Self {
+        Self { rng }
+    }
+}
+
+impl AttackerStrategy for HuntTarget {
+    fn name(&self) -> &'static str {
+        StrategyKind::HuntTarget.name()
+    }
+
+    fn next_cell(&mut self, moves: &[Move]) -> Option<Cell> {
+        // Most recent hits first
+        let target = moves
+            .iter()
+            .rev()
+            .filter(|m| m.is_hit)
+            .flat_map(|m| neighbours((m.x, m.y), false))
+            .find(|cell| !is_played(moves, *cell));
+        if target.is_some() {
+            return target;
+        }
+        let cells = unplayed_cells(moves);
+        (!cells.is_empty()).then(|| cells[self.rng.below(cells.len())])
+    }
+}
+
+/// Picks the cell with the highest estimated trap probability
+///
+/// Each unplayed cell starts from the remaining trap density
+/// (`expected_traps - hits` over the unplayed cells), blended with the hit
+/// rate among its already played neighbours, so areas with hits are
+/// preferred and areas of misses are avoided. Ties are broken at random.
+pub struct ProbabilityDensity {
+    rng: Rng,
+    expected_traps: u32,
+}
+
+impl ProbabilityDensity {
+    /// Weight of the prior density against the observed neighbours
+    const PRIOR_WEIGHT: f64 = 2.0;
+
+    pub fn new(rng: Rng, expected_traps: u32) -> Self {
+        Self {
+            rng,
+            expected_traps,
+        }
+    }
+
+    /// Estimated trap probability of every unplayed cell
+    pub fn densities(&self, moves: &[Move]) -> Vec<(Cell, f64)> {
+        let cells = unplayed_cells(moves);
+        let hits = moves.iter().filter(|m| m.is_hit).count() as u32;
+        let prior = self.expected_traps.saturating_sub(hits) as f64 / cells.len().max(1) as f64;
+        cells
+            .into_iter()
+            .map(|cell| {
+                let (mut played, mut hit) = (0.0, 0.0);
+                for neighbour in neighbours(cell, true) {
+                    if let Some(m) = moves.iter().find(|m| (m.x, m.y) == neighbour) {
+                        played += 1.0;
+                        if m.is_hit {
+                            hit += 1.0;
+                        }
+                    }
+                }
+                let density = (hit + Self::PRIOR_WEIGHT * prior) / (played + Self::PRIOR_WEIGHT);
+                (cell, density)
+            })
+            .collect()
+    }
+}
+
+impl AttackerStrategy for ProbabilityDensity {
+    fn name(&self) -> &'static str {
+        StrategyKind::Density.name()
+    }
+
+    fn next_cell(&mut self, moves: &[Move]) -> Option<Cell> {
+        let densities = self.densities(moves);
+        let best = densities
+            .iter()
+            .map(|(_, density)| *density)
+            .fold(f64::NEG_INFINITY, f64::max);
+        let candidates: Vec<Cell> = densities
+            .into_iter()
+            .filter(|(_, density)| best - density < 1e-9)
+            .map(|(cell, _)| cell)
+            .collect();
+        (!candidates.is_empty()).then(|| candidates[self.rng.below(candidates.len())])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::MAX_MOVES;
+
+    fn record(moves: &mut Vec<Move>, (x, y): Cell, is_hit: bool) {
+        moves.push(Move {
+            x,
+            y,
+            is_hit,
+            verified: true,
+        });
+    }
+
+    #[test]
+    fn test_strategies_cover_the_grid() {
+        for kind in StrategyKind::ALL {
+            let mut strategy = kind.build(Rng::new(1), 10);
+            assert_eq!(kind.name().parse::<StrategyKind>().unwrap(), kind);
+            let mut moves = Vec::new();
+            while let Some(cell) = strategy.next_cell(&moves) {
+                assert!(
+                    !is_played(&moves, cell),
+                    "{} repeated {cell:?}",
+                    strategy.name()
+                );
+                record(&mut moves, cell, (cell.0 + cell.1) % 5 == 0);
+            }
+            assert_eq!(moves.len(), MAX_MOVES as usize);
+        }
+    }
+
+    #[test]
+    fn test_hits_attract_neighbours() {
+        let mut moves = Vec::new();
+        record(&mut moves, (0, 0), false);
+        record(&mut moves, (4, 4), true);
+
+        let next = HuntTarget::new(Rng::new(0)).next_cell(&moves).unwrap();
+        assert!(neighbours((4, 4), false).any(|cell| cell == next));
+
+        let next = ProbabilityDensity::new(Rng::new(0), 10)
+            .next_cell(&moves)
+            .unwrap();
+        assert!(neighbours((4, 4), true).any(|cell| cell == next));
+    }
+}
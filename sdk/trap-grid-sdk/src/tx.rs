@@ -0,0 +1,1091 @@
+//! Native Soroban transaction submission
+//!
+//! Builds an `InvokeHostFunction` transaction, simulates it to obtain the
+//! footprint, resource fee and authorization entries, signs the entries and the
+//! transaction with a [`TransactionSigner`] and submits it over RPC, waiting
+//! for the result. Rejected transactions are rebuilt or re-simulated and sent
+//! again according to a [`RetryPolicy`]. This replaces shelling out to
+//! `stellar contract invoke`.
+//!
+//! A relayer can pay for players' calls: players sign only their
+//! authorization entries ([`Submitter::sign_preimage`]), and the relayer
+//! wraps the transaction in a fee bump ([`Submitter::send_prepared_sponsored`]).
+
+use std::fmt;
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
+use sha2::{Digest, Sha256};
+use stellar_xdr::curr::{
+    AccountId, BytesM, ContractExecutable, ContractIdPreimage, ContractIdPreimageFromAddress,
+    CreateContractArgsV2, DecoratedSignature, DiagnosticEvent, FeeBumpTransaction,
+    FeeBumpTransactionEnvelope, FeeBumpTransactionExt, FeeBumpTransactionInnerTx, Hash,
+    HashIdPreimage, HashIdPreimageSorobanAuthorization, HostFunction, InvokeContractArgs,
+    InvokeHostFunctionOp, Limits, Memo, MuxedAccount, Operation, OperationBody, Preconditions,
+    PublicKey, ReadXdr, ScAddress, ScBytes, ScMap, ScSymbol, ScVal, ScVec, SequenceNumber,
+    Signature, SignatureHint, SorobanAuthorizationEntry, SorobanCredentials,
+    SorobanTransactionData, Transaction, TransactionEnvelope, TransactionExt, TransactionMeta,
+    TransactionResult, TransactionSignaturePayload, TransactionSignaturePayloadTaggedTransaction,
+    TransactionV1Envelope, Uint256, VecM, WriteXdr,
+};
+
+use crate::retry::{self, Rejected, Retry, RetryPolicy, Sequences};
+use crate::rpc::{RpcClient, SimulateTransactionResult};
+use crate::signer::TransactionSigner;
+
+/// Inclusion fee in stroops, on top of the simulated resource fee
+const BASE_FEE: u32 = 100;
+
+/// Ledgers an address authorization signature stays valid for (~8 minutes)
+const AUTH_VALIDITY_LEDGERS: u32 = 100;
+
+/// Source account of read-only simulations, which need not exist
+const READ_SOURCE: [u8; 32] = [0; 32];
+
+/// How long to wait for a submitted transaction to be included
+const CONFIRM_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A simulation the host failed, e.g. because the contract returned an error
+#[derive(Debug)]
+pub struct SimulationFailed {
+    pub message: String,
+    /// The host's diagnostic events, oldest first
+    pub events: Vec<DiagnosticEvent>,
+}
+
+impl fmt::Display for SimulationFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for SimulationFailed {}
+
+/// Diagnostic events attached to a failed simulation or transaction in
+/// `err`'s chain
+pub fn diagnostic_events(err: &anyhow::Error) -> &[DiagnosticEvent] {
+    if let Some(failed) = err.downcast_ref::<SimulationFailed>() {
+        return &failed.events;
+    }
+    err.downcast_ref::<Rejected>()
+        .map(|rejected| rejected.events.as_slice())
+        .unwrap_or_default()
+}
+
+/// Decode base64 `DiagnosticEvent`s, skipping any this XDR version cannot read
+fn decode_events(events_xdr: &[String]) -> Vec<DiagnosticEvent> {
+    events_xdr
+        .iter()
+        .filter_map(|xdr| DiagnosticEvent::from_xdr_base64(xdr, Limits::none()).ok())
+        .collect()
+}
+
+/// Account address of a signer as an `ScAddress`
+pub fn account_address(signer: &dyn TransactionSigner) -> ScAddress {
+    ScAddress::Account(account_id(signer))
+}
+
+fn account_id(signer: &dyn TransactionSigner) -> AccountId {
+    account(signer.public_key())
+}
+
+fn account(public_key: [u8; 32]) -> AccountId {
+    AccountId(PublicKey::PublicKeyTypeEd25519(Uint256(public_key)))
+}
+
+/// `Bytes` contract argument
+pub fn bytes_arg(bytes: &[u8]) -> Result<ScVal> {
+    Ok(ScVal::Bytes(ScBytes(bytes.to_vec().try_into()?)))
+}
+
+fn invoke_contract(contract: &ScAddress, function: &str, args: Vec<ScVal>) -> Result<HostFunction> {
+    Ok(HostFunction::InvokeContract(InvokeContractArgs {
+        contract_address: contract.clone(),
+        function_name: ScSymbol(function.try_into()?),
+        args: args.try_into()?,
+    }))
+}
+
+fn sha256(bytes: &[u8]) -> [u8; 32] {
+    Sha256::digest(bytes).into()
+}
+
+/// Footprint and fee from a simulation
+#[derive(Clone, Debug)]
+struct Resources {
+    transaction_data: Option<String>,
+    min_resource_fee: Option<String>,
+}
+
+impl Resources {
+    fn of(simulation: SimulateTransactionResult) -> Self {
+        Self {
+            transaction_data: simulation.transaction_data,
+            min_resource_fee: simulation.min_resource_fee,
+        }
+    }
+}
+
+/// A simulated invocation waiting for its address authorizations
+///
+/// Each address signs the payload hash of its own entry with its ed25519 key,
+/// here with [`AuthRequest::sign_with`] or remotely with
+/// [`AuthRequest::add_signature`];
+/// [`Submitter::finish`] then signs and returns the transaction. Signatures
+/// stay valid for about 8 minutes after preparation.
+#[derive(Clone, Debug)]
+pub struct AuthRequest {
+    tx: Transaction,
+    entries: Vec<SorobanAuthorizationEntry>,
+    /// `HashIdPreimage` XDR of each entry with address credentials
+    preimages: Vec<Option<Vec<u8>>>,
+    signed: Vec<bool>,
+    label: String,
+    resources: Resources,
+}
+
+impl AuthRequest {
+    /// Addresses that still have to sign, with the hash each one signs
+    pub fn required(&self) -> Vec<(ScAddress, [u8; 32])> {
+        self.entries
+            .iter()
+            .zip(&self.preimages)
+            .zip(&self.signed)
+            .filter_map(
+                |((entry, preimage), signed)| match (&entry.credentials, preimage) {
+                    (SorobanCredentials::Address(credentials), Some(preimage)) if !signed => {
+                        Some((credentials.address.clone(), sha256(preimage)))
+                    }
+                    _ => None,
+                },
+            )
+            .collect()
+    }
+
+    /// Attach the signature of the account with `public_key` to its entry
+    pub fn add_signature(&mut self, public_key: [u8; 32], signature: [u8; 64]) -> Result<()> {
+        let index = self.entry_of(public_key)?;
+        let payload = sha256(
+            self.preimages[index]
+                .as_ref()
+                .expect("address entries have a preimage"),
+        );
+        let address = ScAddress::Account(account(public_key));
+        ed25519_dalek::VerifyingKey::from_bytes(&public_key)
+            .context("invalid public key")?
+            .verify_strict(&payload, &ed25519_dalek::Signature::from_bytes(&signature))
+            .map_err(|_| anyhow!("signature from {address} does not match its payload"))?;
+
+        let SorobanCredentials::Address(credentials) = &mut self.entries[index].credentials else {
+            unreachable!("address entries have address credentials");
+        };
+        credentials.signature = auth_signature(&public_key, &signature)?;
+        self.signed[index] = true;
+        Ok(())
+    }
+
+    /// Addresses that still have to sign, with the `HashIdPreimage` XDR of
+    /// their entry; a signer elsewhere checks it with
+    /// [`Submitter::sign_preimage`] before signing
+    pub fn preimages(&self) -> Vec<(ScAddress, Vec<u8>)> {
+        self.entries
+            .iter()
+            .zip(&self.preimages)
+            .zip(&self.signed)
+            .filter_map(
+                |((entry, preimage), signed)| match (&entry.credentials, preimage) {
+                    (SorobanCredentials::Address(credentials), Some(preimage)) if !signed => {
+                        Some((credentials.address.clone(), preimage.clone()))
+                    }
+                    _ => None,
+                },
+            )
+            .collect()
+    }
+
+    /// Sign the entry of `signer`'s address
+    pub fn sign_with(&mut self, signer: &dyn TransactionSigner) -> Result<()> {
+        let index = self.entry_of(signer.public_key())?;
+        let preimage = self.preimages[index]
+            .as_ref()
+            .expect("address entries have a preimage");
+        let signature = signer.sign_authorization(preimage)?;
+        self.add_signature(signer.public_key(), signature)
+    }
+
+    /// Index of the unsigned entry of the account with `public_key`
+    fn entry_of(&self, public_key: [u8; 32]) -> Result<usize> {
+        let address = ScAddress::Account(account(public_key));
+        self.entries
+            .iter()
+            .zip(&self.signed)
+            .position(|(entry, signed)| {
+                !signed
+                    && matches!(&entry.credentials,
+                        SorobanCredentials::Address(credentials) if credentials.address == address)
+            })
+            .with_context(|| format!("{} needs no signature from {address}", self.label))
+    }
+}
+
+/// Signature value for address credentials (`Vec<AccountEd25519Signature>`)
+fn auth_signature(public_key: &[u8; 32], signature: &[u8; 64]) -> Result<ScVal> {
+    let entry = ScMap::sorted_from_pairs(
+        [
+            (
+                ScVal::Symbol(ScSymbol("public_key".try_into()?)),
+                bytes_arg(public_key)?,
+            ),
+            (
+                ScVal::Symbol(ScSymbol("signature".try_into()?)),
+                bytes_arg(signature)?,
+            ),
+        ]
+        .into_iter(),
+    )?;
+    Ok(ScVal::Vec(Some(ScVec(
+        vec![ScVal::Map(Some(entry))].try_into()?,
+    ))))
+}
+
+pub struct Submitter<'a> {
+    rpc: &'a RpcClient,
+    network_id: [u8; 32],
+    retry: RetryPolicy,
+    sequences: Sequences,
+}
+
+impl<'a> Submitter<'a> {
+    /// Submitter for the network the RPC server is connected to
+    pub fn new(rpc: &'a RpcClient) -> Result<Self> {
+        let passphrase = rpc.get_network()?.passphrase;
+        Ok(Self::for_network(rpc, sha256(passphrase.as_bytes())))
+    }
+
+    fn for_network(rpc: &'a RpcClient, network_id: [u8; 32]) -> Self {
+        Self {
+            rpc,
+            network_id,
+            retry: RetryPolicy::default(),
+            sequences: Sequences::default(),
+        }
+    }
+
+    /// Retry rejected transactions according to `policy` instead of the default
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// Build, simulate and sign a contract invocation without sending it
+    pub fn build(
+        &self,
+        source: &dyn TransactionSigner,
+        contract: &ScAddress,
+        function: &str,
+        args: Vec<ScVal>,
+    ) -> Result<TransactionEnvelope> {
+        self.build_authorized(source, &[], contract, function, args)
+    }
+
+    /// Like [`Submitter::build`], with `cosigners` signing the authorization
+    /// entries for their addresses (e.g. a player other than the fee payer)
+    pub fn build_authorized(
+        &self,
+        source: &dyn TransactionSigner,
+        cosigners: &[&dyn TransactionSigner],
+        contract: &ScAddress,
+        function: &str,
+        args: Vec<ScVal>,
+    ) -> Result<TransactionEnvelope> {
+        let host_function = invoke_contract(contract, function, args)?;
+        self.build_signed(source, cosigners, host_function, function)
+    }
+
+    /// Simulate a read-only contract call and return its result; nothing is
+    /// signed or sent
+    pub fn read(&self, contract: &ScAddress, function: &str, args: Vec<ScVal>) -> Result<ScVal> {
+        let tx = Transaction {
+            source_account: MuxedAccount::Ed25519(Uint256(READ_SOURCE)),
+            fee: BASE_FEE,
+            seq_num: SequenceNumber(0),
+            cond: Preconditions::None,
+            memo: Memo::None,
+            operations: vec![Operation {
+                source_account: None,
+                body: OperationBody::InvokeHostFunction(InvokeHostFunctionOp {
+                    host_function: invoke_contract(contract, function, args)?,
+                    auth: VecM::default(),
+                }),
+            }]
+            .try_into()?,
+            ext: TransactionExt::V0,
+        };
+        let simulation = self.simulate(&tx, function)?;
+        let result = simulation
+            .results
+            .first()
+            .and_then(|r| r.xdr.as_deref())
+            .with_context(|| format!("{function} simulation returned no result"))?;
+        Ok(ScVal::from_xdr_base64(result, Limits::none())?)
+    }
+
+    /// Build, simulate and sign any host function; `label` names it in errors
+    pub fn build_host_function(
+        &self,
+        source: &dyn TransactionSigner,
+        host_function: HostFunction,
+        label: &str,
+    ) -> Result<TransactionEnvelope> {
+        self.build_signed(source, &[], host_function, label)
+    }
+
+    fn build_signed(
+        &self,
+        source: &dyn TransactionSigner,
+        cosigners: &[&dyn TransactionSigner],
+        host_function: HostFunction,
+        label: &str,
+    ) -> Result<TransactionEnvelope> {
+        let mut request = self.prepare(source, host_function, label)?;
+        for (address, _) in request.required() {
+            let Some(signer) = std::iter::once(source)
+                .chain(cosigners.iter().copied())
+                .find(|signer| address == account_address(*signer))
+            else {
+                bail!("{label} needs authorization from {address}, which is not a signer");
+            };
+            request.sign_with(signer)?;
+        }
+        self.finish(source, request)
+    }
+
+    /// Sign an authorization entry prepared elsewhere, e.g. by a relayer,
+    /// given its `HashIdPreimage` XDR
+    ///
+    /// Only an entry for this network that authorizes `function` of
+    /// `contract`, and no other call beneath it, is signed, so a relayer
+    /// cannot have the signer authorize anything else.
+    pub fn sign_preimage(
+        &self,
+        signer: &dyn TransactionSigner,
+        preimage: &[u8],
+        contract: &ScAddress,
+        function: &str,
+    ) -> Result<[u8; 64]> {
+        let HashIdPreimage::SorobanAuthorization(auth) =
+            HashIdPreimage::from_xdr(preimage, Limits::none())
+                .context("invalid authorization preimage")?
+        else {
+            bail!("preimage is not a Soroban authorization");
+        };
+        if auth.network_id.0 != self.network_id {
+            bail!("authorization is for another network");
+        }
+        let invocation = &auth.invocation;
+        let stellar_xdr::curr::SorobanAuthorizedFunction::ContractFn(call) = &invocation.function
+        else {
+            bail!("authorization is not for a contract call");
+        };
+        if call.contract_address != *contract
+            || call.function_name.0.as_slice() != function.as_bytes()
+        {
+            bail!(
+                "authorization is for {} of {}, not {function} of {contract}",
+                call.function_name.0,
+                call.contract_address
+            );
+        }
+        if !invocation.sub_invocations.is_empty() {
+            bail!("authorization covers further calls beneath {function}");
+        }
+        signer.sign_authorization(preimage)
+    }
+
+    /// Build and simulate a contract invocation whose authorizations are
+    /// signed elsewhere, e.g. by players of a match arranged by a server
+    pub fn prepare_authorized(
+        &self,
+        source: &dyn TransactionSigner,
+        contract: &ScAddress,
+        function: &str,
+        args: Vec<ScVal>,
+    ) -> Result<AuthRequest> {
+        let host_function = invoke_contract(contract, function, args)?;
+        self.prepare(source, host_function, function)
+    }
+
+    fn prepare(
+        &self,
+        source: &dyn TransactionSigner,
+        host_function: HostFunction,
+        label: &str,
+    ) -> Result<AuthRequest> {
+        let sequence = self.next_sequence(source)?;
+        let operation = InvokeHostFunctionOp {
+            host_function,
+            auth: VecM::default(),
+        };
+        let tx = Transaction {
+            source_account: MuxedAccount::Ed25519(Uint256(source.public_key())),
+            fee: BASE_FEE,
+            seq_num: SequenceNumber(sequence),
+            cond: Preconditions::None,
+            memo: Memo::None,
+            operations: vec![Operation {
+                source_account: None,
+                body: OperationBody::InvokeHostFunction(operation),
+            }]
+            .try_into()?,
+            ext: TransactionExt::V0,
+        };
+
+        let simulation = self.simulate(&tx, label)?;
+        let mut entries = Vec::new();
+        let mut preimages = Vec::new();
+        for entry in simulation.results.iter().flat_map(|r| &r.auth) {
+            let mut entry = SorobanAuthorizationEntry::from_xdr_base64(entry, Limits::none())?;
+            let preimage = match &mut entry.credentials {
+                SorobanCredentials::Address(credentials) => {
+                    credentials.signature_expiration_ledger =
+                        simulation.latest_ledger + AUTH_VALIDITY_LEDGERS;
+                    Some(self.auth_preimage(
+                        credentials.nonce,
+                        credentials.signature_expiration_ledger,
+                        &entry.root_invocation,
+                    )?)
+                }
+                SorobanCredentials::SourceAccount => None,
+            };
+            entries.push(entry);
+            preimages.push(preimage);
+        }
+        Ok(AuthRequest {
+            tx,
+            entries,
+            signed: vec![false; preimages.len()],
+            preimages,
+            label: label.to_string(),
+            resources: Resources::of(simulation),
+        })
+    }
+
+    /// Sign a prepared invocation once every authorization is signed
+    pub fn finish(
+        &self,
+        source: &dyn TransactionSigner,
+        request: AuthRequest,
+    ) -> Result<TransactionEnvelope> {
+        self.complete(source, request, false)
+    }
+
+    /// Sign and send a prepared invocation, retrying with a fresh sequence
+    /// number and simulation if it is rejected
+    pub fn send_prepared(
+        &self,
+        source: &dyn TransactionSigner,
+        request: AuthRequest,
+    ) -> Result<ScVal> {
+        let label = request.label.clone();
+        self.retrying(|retry| {
+            let envelope = self.complete(source, request.clone(), retry)?;
+            self.send(&envelope)
+        })
+        .with_context(|| format!("{label} failed"))
+    }
+
+    /// Like [`Submitter::send_prepared`], with `fee_source` paying the fee
+    /// through a fee bump; `source` only provides the sequence number
+    pub fn send_prepared_sponsored(
+        &self,
+        source: &dyn TransactionSigner,
+        fee_source: &dyn TransactionSigner,
+        request: AuthRequest,
+    ) -> Result<ScVal> {
+        let label = request.label.clone();
+        self.retrying(|retry| {
+            let inner = self.complete(source, request.clone(), retry)?;
+            self.send(&self.fee_bump(fee_source, inner)?)
+        })
+        .with_context(|| format!("{label} failed"))
+    }
+
+    /// Wrap a signed transaction in a fee bump paid and signed by
+    /// `fee_source`, offering the inner fee plus one more inclusion fee
+    pub fn fee_bump(
+        &self,
+        fee_source: &dyn TransactionSigner,
+        inner: TransactionEnvelope,
+    ) -> Result<TransactionEnvelope> {
+        let TransactionEnvelope::Tx(inner) = inner else {
+            bail!("only v1 transactions can be fee-bumped");
+        };
+        let tx = FeeBumpTransaction {
+            fee_source: MuxedAccount::Ed25519(Uint256(fee_source.public_key())),
+            fee: i64::from(inner.tx.fee) + i64::from(BASE_FEE),
+            inner_tx: FeeBumpTransactionInnerTx::Tx(inner),
+            ext: FeeBumpTransactionExt::V0,
+        };
+        let payload = TransactionSignaturePayload {
+            network_id: Hash(self.network_id),
+            tagged_transaction: TransactionSignaturePayloadTaggedTransaction::TxFeeBump(tx.clone()),
+        };
+        Ok(TransactionEnvelope::TxFeeBump(FeeBumpTransactionEnvelope {
+            tx,
+            signatures: signatures(fee_source, &payload)?.try_into()?,
+        }))
+    }
+
+    /// [`Submitter::finish`]; a `retry` takes a new sequence number and
+    /// simulates again, keeping the authorization signatures
+    fn complete(
+        &self,
+        source: &dyn TransactionSigner,
+        request: AuthRequest,
+        retry: bool,
+    ) -> Result<TransactionEnvelope> {
+        if let Some((address, _)) = request.required().first() {
+            bail!(
+                "{} is missing the authorization of {address}",
+                request.label
+            );
+        }
+        let AuthRequest {
+            mut tx,
+            entries,
+            preimages,
+            label,
+            mut resources,
+            ..
+        } = request;
+        set_auth(&mut tx, entries.try_into()?);
+        if retry {
+            tx.seq_num = SequenceNumber(self.next_sequence(source)?);
+        }
+
+        // Signature checks cost resources the recording simulation did not see
+        if retry || preimages.iter().any(Option::is_some) {
+            resources = Resources::of(self.simulate(&tx, &label)?);
+        }
+        let data = resources
+            .transaction_data
+            .as_deref()
+            .context("simulation returned no transaction data")?;
+        let resource_fee: u32 = resources
+            .min_resource_fee
+            .as_deref()
+            .unwrap_or("0")
+            .parse()
+            .context("invalid minResourceFee")?;
+        tx.ext = TransactionExt::V1(SorobanTransactionData::from_xdr_base64(
+            data,
+            Limits::none(),
+        )?);
+        tx.fee = BASE_FEE + resource_fee;
+
+        self.sign(source, tx)
+    }
+
+    /// Build, sign and send a contract invocation and return its result
+    pub fn invoke(
+        &self,
+        source: &dyn TransactionSigner,
+        contract: &ScAddress,
+        function: &str,
+        args: Vec<ScVal>,
+    ) -> Result<ScVal> {
+        self.invoke_authorized(source, &[], contract, function, args)
+    }
+
+    /// Like [`Submitter::invoke`], with `cosigners` authorizing for their addresses
+    pub fn invoke_authorized(
+        &self,
+        source: &dyn TransactionSigner,
+        cosigners: &[&dyn TransactionSigner],
+        contract: &ScAddress,
+        function: &str,
+        args: Vec<ScVal>,
+    ) -> Result<ScVal> {
+        self.retrying(|_| {
+            let envelope =
+                self.build_authorized(source, cosigners, contract, function, args.clone())?;
+            self.send(&envelope)
+        })
+        .with_context(|| format!("{function} failed"))
+    }
+
+    /// Upload a contract wasm and return its hash
+    pub fn upload_wasm(&self, source: &dyn TransactionSigner, wasm: &[u8]) -> Result<[u8; 32]> {
+        let host_function = HostFunction::UploadContractWasm(wasm.to_vec().try_into()?);
+        let uploaded = self.retrying(|_| {
+            let envelope = self.build_host_function(source, host_function.clone(), "upload")?;
+            self.send(&envelope)
+        });
+        match uploaded.context("wasm upload failed")? {
+            ScVal::Bytes(hash) => Ok(hash
+                .as_slice()
+                .try_into()
+                .context("wasm hash is not 32 bytes")?),
+            other => bail!("upload returned {}, expected the wasm hash", other.name()),
+        }
+    }
+
+    /// Create a contract from an uploaded wasm, passing `constructor_args` to `__constructor`
+    pub fn deploy(
+        &self,
+        source: &dyn TransactionSigner,
+        wasm_hash: [u8; 32],
+        constructor_args: Vec<ScVal>,
+    ) -> Result<ScAddress> {
+        let mut salt = [0u8; 32];
+        getrandom::getrandom(&mut salt).map_err(|e| anyhow!("no system randomness: {e}"))?;
+        let host_function = HostFunction::CreateContractV2(CreateContractArgsV2 {
+            contract_id_preimage: ContractIdPreimage::Address(ContractIdPreimageFromAddress {
+                address: account_address(source),
+                salt: Uint256(salt),
+            }),
+            executable: ContractExecutable::Wasm(Hash(wasm_hash)),
+            constructor_args: constructor_args.try_into()?,
+        });
+        let deployed = self.retrying(|_| {
+            let envelope = self.build_host_function(source, host_function.clone(), "deploy")?;
+            self.send(&envelope)
+        });
+        match deployed.context("contract deployment failed")? {
+            ScVal::Address(address) => Ok(address),
+            other => bail!("deploy returned {}, expected an address", other.name()),
+        }
+    }
+
+    /// Send a base64 transaction envelope signed elsewhere, e.g. by a wallet
+    pub fn send_xdr(&self, envelope: &str) -> Result<ScVal> {
+        let envelope = TransactionEnvelope::from_xdr_base64(envelope.trim(), Limits::none())
+            .context("invalid transaction envelope XDR")?;
+        self.send(&envelope)
+    }
+
+    /// Send a signed transaction and wait for its return value
+    ///
+    /// A busy RPC server is retried with backoff. Other rejections fail with
+    /// a [`Rejected`] error telling whether a rebuilt transaction may succeed.
+    pub fn send(&self, envelope: &TransactionEnvelope) -> Result<ScVal> {
+        // A fee bump's inner transaction holds the sequence number
+        let (tx, signatures) = match envelope {
+            TransactionEnvelope::Tx(TransactionV1Envelope { tx, signatures }) => (tx, signatures),
+            TransactionEnvelope::TxFeeBump(FeeBumpTransactionEnvelope { tx, signatures }) => {
+                let FeeBumpTransactionInnerTx::Tx(inner) = &tx.inner_tx;
+                if inner.signatures.is_empty() {
+                    bail!("inner transaction is unsigned");
+                }
+                (&inner.tx, signatures)
+            }
+            TransactionEnvelope::TxV0(_) => bail!("only v1 transaction envelopes are supported"),
+        };
+        if signatures.is_empty() {
+            bail!("transaction is unsigned; have the wallet sign its XDR first");
+        }
+        let MuxedAccount::Ed25519(Uint256(source)) = tx.source_account else {
+            bail!("muxed source accounts are not supported");
+        };
+        let mut attempt = 1;
+        let sent = loop {
+            let sent = self.rpc.send_transaction(envelope)?;
+            match sent.status.as_str() {
+                "PENDING" | "DUPLICATE" => break sent,
+                "TRY_AGAIN_LATER" if attempt < self.retry.max_attempts => {
+                    thread::sleep(self.retry.backoff(attempt));
+                    attempt += 1;
+                }
+                "TRY_AGAIN_LATER" => {
+                    return Err(Rejected {
+                        retry: Some(Retry::Later),
+                        message: format!("RPC server still busy after {attempt} attempt(s)"),
+                        events: Vec::new(),
+                    }
+                    .into())
+                }
+                "ERROR" => {
+                    let rejected = rejection(
+                        "transaction rejected",
+                        sent.error_result_xdr.as_deref(),
+                        decode_events(&sent.diagnostic_events_xdr),
+                    );
+                    if rejected.retry == Some(Retry::Sequence) {
+                        self.sequences.forget(source);
+                    }
+                    return Err(rejected.into());
+                }
+                status => bail!("transaction not accepted: {status}"),
+            }
+        };
+        self.sequences.sent(source, tx.seq_num.0);
+
+        let mut waited = Duration::ZERO;
+        loop {
+            let result = self.rpc.get_transaction(&sent.hash)?;
+            match result.status.as_str() {
+                "SUCCESS" => return return_value(result.result_meta_xdr.as_deref()),
+                "FAILED" => {
+                    let mut events = decode_events(&result.diagnostic_events_xdr);
+                    if events.is_empty() {
+                        events = meta_events(result.result_meta_xdr.as_deref());
+                    }
+                    return Err(rejection(
+                        &format!("transaction {} failed", sent.hash),
+                        result.result_xdr.as_deref(),
+                        events,
+                    )
+                    .into());
+                }
+                _ if waited >= CONFIRM_TIMEOUT => {
+                    bail!("transaction {} not confirmed after {waited:?}", sent.hash)
+                }
+                _ => {
+                    thread::sleep(Duration::from_secs(1));
+                    waited += Duration::from_secs(1);
+                }
+            }
+        }
+    }
+
+    /// Run `attempt` until it succeeds or fails for good; its argument tells
+    /// whether it is a retry
+    fn retrying(&self, mut attempt: impl FnMut(bool) -> Result<ScVal>) -> Result<ScVal> {
+        let mut attempts = 1;
+        loop {
+            let err = match attempt(attempts > 1) {
+                Ok(value) => return Ok(value),
+                Err(err) => err,
+            };
+            let retry = err.downcast_ref::<Rejected>().and_then(|r| r.retry);
+            if !matches!(retry, Some(Retry::Sequence | Retry::Resimulate))
+                || attempts >= self.retry.max_attempts
+            {
+                return Err(err);
+            }
+            thread::sleep(self.retry.backoff(attempts));
+            attempts += 1;
+        }
+    }
+
+    /// Sequence number for a new transaction from `source`
+    fn next_sequence(&self, source: &dyn TransactionSigner) -> Result<i64> {
+        let current = self.rpc.get_account_sequence(&account_id(source))?;
+        Ok(self.sequences.next(source.public_key(), current))
+    }
+
+    fn simulate(&self, tx: &Transaction, label: &str) -> Result<SimulateTransactionResult> {
+        let envelope = TransactionEnvelope::Tx(TransactionV1Envelope {
+            tx: tx.clone(),
+            signatures: VecM::default(),
+        });
+        let simulation = self.rpc.simulate_transaction(&envelope)?;
+        if let Some(error) = simulation.error {
+            return Err(SimulationFailed {
+                message: format!("{label} simulation failed: {error}"),
+                events: decode_events(&simulation.events),
+            }
+            .into());
+        }
+        Ok(simulation)
+    }
+
+    /// XDR whose hash an address authorization entry's signer signs
+    fn auth_preimage(
+        &self,
+        nonce: i64,
+        signature_expiration_ledger: u32,
+        invocation: &stellar_xdr::curr::SorobanAuthorizedInvocation,
+    ) -> Result<Vec<u8>> {
+        let preimage = HashIdPreimage::SorobanAuthorization(HashIdPreimageSorobanAuthorization {
+            network_id: Hash(self.network_id),
+            nonce,
+            signature_expiration_ledger,
+            invocation: invocation.clone(),
+        });
+        Ok(preimage.to_xdr(Limits::none())?)
+    }
+
+    /// Envelope signed by `signer`, or unsigned if it leaves signing to a wallet
+    fn sign(&self, signer: &dyn TransactionSigner, tx: Transaction) -> Result<TransactionEnvelope> {
+        let payload = TransactionSignaturePayload {
+            network_id: Hash(self.network_id),
+            tagged_transaction: TransactionSignaturePayloadTaggedTransaction::Tx(tx.clone()),
+        };
+        Ok(TransactionEnvelope::Tx(TransactionV1Envelope {
+            tx,
+            signatures: signatures(signer, &payload)?.try_into()?,
+        }))
+    }
+}
+
+/// `signer`'s signature of `payload`, or none if it leaves signing to a wallet
+fn signatures(
+    signer: &dyn TransactionSigner,
+    payload: &TransactionSignaturePayload,
+) -> Result<Vec<DecoratedSignature>> {
+    let mut signatures = Vec::new();
+    if let Some(signature) = signer.sign_transaction(&payload.to_xdr(Limits::none())?)? {
+        let public_key = signer.public_key();
+        signatures.push(DecoratedSignature {
+            hint: SignatureHint(public_key[28..].try_into().expect("4-byte hint")),
+            signature: Signature(BytesM::try_from(signature.to_vec())?),
+        });
+    }
+    Ok(signatures)
+}
+
+fn set_auth(tx: &mut Transaction, auth: VecM<SorobanAuthorizationEntry>) {
+    for operation in tx.operations.iter_mut() {
+        if let OperationBody::InvokeHostFunction(op) = &mut operation.body {
+            op.auth = auth.clone();
+        }
+    }
+}
+
+/// Rejection described by a base64 `TransactionResult`
+fn rejection(what: &str, result_xdr: Option<&str>, events: Vec<DiagnosticEvent>) -> Rejected {
+    match result_xdr.and_then(|xdr| TransactionResult::from_xdr_base64(xdr, Limits::none()).ok()) {
+        Some(result) => Rejected {
+            retry: retry::classify(&result.result),
+            message: format!("{what}: {:?}", result.result),
+            events,
+        },
+        None => Rejected {
+            retry: None,
+            message: format!("{what}: unknown error"),
+            events,
+        },
+    }
+}
+
+/// Diagnostic events recorded in a base64 `TransactionMeta`, for RPC servers
+/// that do not return them separately
+fn meta_events(meta_xdr: Option<&str>) -> Vec<DiagnosticEvent> {
+    let meta = meta_xdr.and_then(|xdr| TransactionMeta::from_xdr_base64(xdr, Limits::none()).ok());
+    match meta {
+        Some(TransactionMeta::V3(meta)) => meta
+            .soroban_meta
+            .map(|m| m.diagnostic_events.to_vec())
+            .unwrap_or_default(),
+        Some(TransactionMeta::V4(meta)) => meta.diagnostic_events.to_vec(),
+        _ => Vec::new(),
+    }
+}
+
+/// Contract return value from a base64 `TransactionMeta`
+fn return_value(meta_xdr: Option<&str>) -> Result<ScVal> {
+    let meta = TransactionMeta::from_xdr_base64(
+        meta_xdr.context("transaction result has no metadata")?,
+        Limits::none(),
+    )?;
+    match meta {
+        TransactionMeta::V3(meta) => meta.soroban_meta.map(|m| m.return_value),
+        TransactionMeta::V4(meta) => meta.soroban_meta.and_then(|m| m.return_value),
+        _ => None,
+    }
+    .ok_or_else(|| anyhow!("transaction metadata has no return value"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::signer::Passthrough;
+    use crate::Keypair;
+
+    #[test]
+    fn test_signature_verifies() {
+        let keypair = Keypair::generate().unwrap();
+        let rpc = RpcClient::new("http://localhost:0");
+        let submitter = Submitter::for_network(&rpc, sha256(b"Standalone Network ; February 2017"));
+        let tx = Transaction {
+            source_account: MuxedAccount::Ed25519(Uint256(keypair.public_key())),
+            fee: BASE_FEE,
+            seq_num: SequenceNumber(1),
+            cond: Preconditions::None,
+            memo: Memo::None,
+            operations: VecM::default(),
+            ext: TransactionExt::V0,
+        };
+
+        let TransactionEnvelope::Tx(envelope) = submitter.sign(&keypair, tx.clone()).unwrap()
+        else {
+            panic!("expected a v1 envelope");
+        };
+        let payload = TransactionSignaturePayload {
+            network_id: Hash(submitter.network_id),
+            tagged_transaction: TransactionSignaturePayloadTaggedTransaction::Tx(tx),
+        };
+        let hash = sha256(&payload.to_xdr(Limits::none()).unwrap());
+        let verifying_key = ed25519_dalek::VerifyingKey::from_bytes(&keypair.public_key()).unwrap();
+        let signature =
+            ed25519_dalek::Signature::from_slice(&envelope.signatures[0].signature.0).unwrap();
+        assert!(verifying_key.verify_strict(&hash, &signature).is_ok());
+        assert_eq!(envelope.signatures[0].hint.0, keypair.public_key()[28..]);
+
+        // A wallet's transaction comes back unsigned and is not sent as is
+        let wallet = Passthrough::new(&keypair.address()).unwrap();
+        let unsigned = submitter.sign(&wallet, envelope.tx).unwrap();
+        let TransactionEnvelope::Tx(unsigned_v1) = &unsigned else {
+            panic!("expected a v1 envelope");
+        };
+        assert!(unsigned_v1.signatures.is_empty());
+        assert!(submitter.send(&unsigned).is_err());
+    }
+
+    #[test]
+    fn test_auth_request_signatures() {
+        let player = Keypair::generate().unwrap();
+        let other = Keypair::generate().unwrap();
+        let entry = SorobanAuthorizationEntry {
+            credentials: SorobanCredentials::Address(
+                stellar_xdr::curr::SorobanAddressCredentials {
+                    address: account_address(&player),
+                    nonce: 7,
+                    signature_expiration_ledger: 100,
+                    signature: ScVal::Void,
+                },
+            ),
+            root_invocation: stellar_xdr::curr::SorobanAuthorizedInvocation {
+                function: stellar_xdr::curr::SorobanAuthorizedFunction::ContractFn(
+                    InvokeContractArgs {
+                        contract_address: account_address(&other),
+                        function_name: ScSymbol("start_game".try_into().unwrap()),
+                        args: VecM::default(),
+                    },
+                ),
+                sub_invocations: VecM::default(),
+            },
+        };
+        let preimage = vec![3u8; 16];
+        let payload = sha256(&preimage);
+        let mut request = AuthRequest {
+            tx: Transaction {
+                source_account: MuxedAccount::Ed25519(Uint256(other.public_key())),
+                fee: BASE_FEE,
+                seq_num: SequenceNumber(1),
+                cond: Preconditions::None,
+                memo: Memo::None,
+                operations: VecM::default(),
+                ext: TransactionExt::V0,
+            },
+            entries: vec![entry],
+            preimages: vec![Some(preimage.clone())],
+            signed: vec![false],
+            label: "start_game".into(),
+            resources: Resources {
+                transaction_data: None,
+                min_resource_fee: None,
+            },
+        };
+
+        assert_eq!(
+            request.required(),
+            vec![(account_address(&player), payload)]
+        );
+        assert_eq!(
+            request.preimages(),
+            vec![(account_address(&player), preimage)]
+        );
+        // Only the entry's own account, over its own payload
+        assert!(request.sign_with(&other).is_err());
+        assert!(request
+            .add_signature(player.public_key(), player.sign(&[4u8; 32]))
+            .is_err());
+        request
+            .add_signature(player.public_key(), player.sign(&payload))
+            .unwrap();
+        assert!(request.required().is_empty());
+        assert!(request.preimages().is_empty());
+    }
+
+    #[test]
+    fn test_fee_bump() {
+        let player = Keypair::generate().unwrap();
+        let relayer = Keypair::generate().unwrap();
+        let rpc = RpcClient::new("http://localhost:0");
+        let submitter = Submitter::for_network(&rpc, sha256(b"Standalone Network ; February 2017"));
+        let tx = Transaction {
+            source_account: MuxedAccount::Ed25519(Uint256(player.public_key())),
+            fee: BASE_FEE + 5_000,
+            seq_num: SequenceNumber(1),
+            cond: Preconditions::None,
+            memo: Memo::None,
+            operations: VecM::default(),
+            ext: TransactionExt::V0,
+        };
+        let inner = submitter.sign(&player, tx).unwrap();
+
+        let TransactionEnvelope::TxFeeBump(envelope) = submitter.fee_bump(&relayer, inner).unwrap()
+        else {
+            panic!("expected a fee-bump envelope");
+        };
+        assert_eq!(envelope.tx.fee, i64::from(2 * BASE_FEE + 5_000));
+        let payload = TransactionSignaturePayload {
+            network_id: Hash(submitter.network_id),
+            tagged_transaction: TransactionSignaturePayloadTaggedTransaction::TxFeeBump(
+                envelope.tx.clone(),
+            ),
+        };
+        let hash = sha256(&payload.to_xdr(Limits::none()).unwrap());
+        let verifying_key = ed25519_dalek::VerifyingKey::from_bytes(&relayer.public_key()).unwrap();
+        let signature =
+            ed25519_dalek::Signature::from_slice(&envelope.signatures[0].signature.0).unwrap();
+        assert!(verifying_key.verify_strict(&hash, &signature).is_ok());
+    }
+
+    #[test]
+    fn test_sign_preimage() {
+        let player = Keypair::generate().unwrap();
+        let contract = account_address(&Keypair::generate().unwrap());
+        let rpc = RpcClient::new("http://localhost:0");
+        let network_id = sha256(b"Standalone Network ; February 2017");
+        let submitter = Submitter::for_network(&rpc, network_id);
+        let preimage = |network_id: [u8; 32], function: &str, sub_invocations: usize| {
+            let invocation = |function: &str| stellar_xdr::curr::SorobanAuthorizedInvocation {
+                function: stellar_xdr::curr::SorobanAuthorizedFunction::ContractFn(
+                    InvokeContractArgs {
+                        contract_address: contract.clone(),
+                        function_name: ScSymbol(function.try_into().unwrap()),
+                        args: VecM::default(),
+                    },
+                ),
+                sub_invocations: VecM::default(),
+            };
+            let mut root = invocation(function);
+            root.sub_invocations = vec![invocation("transfer"); sub_invocations]
+                .try_into()
+                .unwrap();
+            HashIdPreimage::SorobanAuthorization(
+                stellar_xdr::curr::HashIdPreimageSorobanAuthorization {
+                    network_id: Hash(network_id),
+                    nonce: 7,
+                    signature_expiration_ledger: 100,
+                    invocation: root,
+                },
+            )
+            .to_xdr(Limits::none())
+            .unwrap()
+        };
+
+        let good = preimage(network_id, "submit_move", 0);
+        assert_eq!(
+            submitter
+                .sign_preimage(&player, &good, &contract, "submit_move")
+                .unwrap(),
+            player.sign(&sha256(&good))
+        );
+        // Another call, another network, or a call beneath it is refused
+        for (preimage, function) in [
+            (good.clone(), "end_game"),
+            (preimage([0; 32], "submit_move", 0), "submit_move"),
+            (preimage(network_id, "submit_move", 1), "submit_move"),
+            (vec![1, 2, 3], "submit_move"),
+        ] {
+            assert!(submitter
+                .sign_preimage(&player, &preimage, &contract, function)
+                .is_err());
+        }
+    }
+}
@@ -0,0 +1,179 @@
+//! wasm-bindgen exports for the dApp
+//!
+//! The frontend builds its commitments, Merkle proofs and public inputs with
+//! the same code as the bots and the CLI. Field elements cross the boundary as
+//! 0x-prefixed hex strings and trap layouts as their trap values, one per
+//! cell of the square grid (64 on the default 8x8 grid).
+//!
+//! ```sh
+//! wasm-pack build trap-grid-sdk --target web -- --no-default-features --features wasm
+//! ```
+
+use serde::Serialize;
+use trap_grid_encoding::{
+    FieldBytes, PositionMovement, TrapCommitment, TrapMerkleRoot, MAX_MERKLE_TREE_DEPTH,
+};
+use wasm_bindgen::prelude::*;
+
+use crate::commitment::{self, TrapTree};
+use crate::layout::{self, TrapLayout};
+use crate::proof_format;
+
+fn js_error(err: anyhow::Error) -> JsError {
+    JsError::new(&format!("{err:#}"))
+}
+
+fn field_hex(field: &FieldBytes) -> String {
+    format!("0x{}", hex::encode(field))
+}
+
+fn layout(trap_values: Vec<u8>) -> Result<TrapLayout, JsError> {
+    let layout = TrapLayout {
+        trap_values,
+        salt: None,
+    };
+    layout.validate().map_err(js_error)?;
+    Ok(layout)
+}
+
+fn check_cell(grid_size: u32, x: u32, y: u32) -> Result<(), JsError> {
+    if x >= grid_size || y >= grid_size {
+        return Err(JsError::new(&format!(
+            "cell ({x}, {y}) is outside the {grid_size}x{grid_size} grid"
+        )));
+    }
+    Ok(())
+}
+
+/// `Prover.toml` of the trap-merkle-root circuit
+#[derive(Serialize)]
+struct TrapMerkleRootInputs {
+    trap_merkle_root: String,
+    move_x: u32,
+    move_y: u32,
+    trap_merkle_proof_length: u32,
+    trap_merkle_proof_indices: [u8; MAX_MERKLE_TREE_DEPTH],
+    trap_merkle_proof_siblings: Vec<String>,
+    grid_size: u32,
+    trap_value: u8,
+}
+
+/// Throw unless the layout has a cell of 0 or 1 per cell of a supported grid
+#[wasm_bindgen(js_name = validateLayout)]
+pub fn validate_layout(trap_values: Vec<u8>) -> Result<(), JsError> {
+    layout(trap_values).map(|_| ())
+}
+
+/// Leaf index of a cell (`x * grid_size + y`)
+#[wasm_bindgen(js_name = cellIndex)]
+pub fn cell_index(grid_size: u32, x: u32, y: u32) -> Result<u32, JsError> {
+    check_cell(grid_size, x, y)?;
+    Ok(layout::cell_index(grid_size, x, y) as u32)
+}
+
+/// Hash of a defender's address, as the trap-commitment circuit binds it
+#[wasm_bindgen(js_name = defenderField)]
+pub fn defender_field(defender: &str) -> Result<String, JsError> {
+    Ok(field_hex(
+        &commitment::defender_field(defender).map_err(js_error)?,
+    ))
+}
+
+/// Commitment to one trap value by `defender`, as the trap-commitment circuit computes it
+#[wasm_bindgen(js_name = trapCommitment)]
+pub fn trap_commitment(trap_value: u32, secret: &str, defender: &str) -> Result<String, JsError> {
+    if trap_value > 1 {
+        return Err(JsError::new("trap value must be 0 or 1"));
+    }
+    let secret = commitment::parse_field(secret).map_err(js_error)?;
+    let defender = commitment::defender_field(defender).map_err(js_error)?;
+    Ok(field_hex(&commitment::trap_commitment(
+        trap_value, &secret, &defender,
+    )))
+}
+
+/// Root of the trap Merkle tree over a layout
+#[wasm_bindgen(js_name = trapMerkleRoot)]
+pub fn trap_merkle_root(trap_values: Vec<u8>) -> Result<String, JsError> {
+    let layout = layout(trap_values)?;
+    let tree = TrapTree::new(&layout.trap_values).map_err(js_error)?;
+    Ok(field_hex(&tree.root()))
+}
+
+fn opening(trap_values: Vec<u8>, x: u32, y: u32) -> Result<(TrapMerkleRoot, u8), JsError> {
+    let layout = layout(trap_values)?;
+    let grid_size = layout.grid_size();
+    check_cell(grid_size, x, y)?;
+    let tree = TrapTree::new(&layout.trap_values).map_err(js_error)?;
+    Ok((
+        tree.opening(x, y),
+        layout.trap_values[layout::cell_index(grid_size, x, y)],
+    ))
+}
+
+/// Trap-merkle-root circuit inputs opening cell (`x`, `y`), keyed as in its `Prover.toml`
+#[wasm_bindgen(js_name = trapMerkleProof)]
+pub fn trap_merkle_proof(trap_values: Vec<u8>, x: u32, y: u32) -> Result<JsValue, JsError> {
+    let (opening, trap_value) = opening(trap_values, x, y)?;
+    let inputs = TrapMerkleRootInputs {
+        trap_merkle_root: field_hex(&opening.trap_merkle_root),
+        move_x: opening.move_x,
+        move_y: opening.move_y,
+        trap_merkle_proof_length: opening.proof_length,
+        trap_merkle_proof_indices: opening.indices,
+        trap_merkle_proof_siblings: opening.siblings.iter().map(field_hex).collect(),
+        grid_size: opening.grid_size,
+        trap_value,
+    };
+    Ok(serde_wasm_bindgen::to_value(&inputs)?)
+}
+
+/// Public inputs `make_move` verifies, as the verifier contract expects them,
+/// for a game on a `grid_size` x `grid_size` grid started with `grid_commitment`
+#[wasm_bindgen(js_name = encodePositionMovement)]
+pub fn encode_position_movement(
+    move_x: u32,
+    move_y: u32,
+    is_hit: bool,
+    grid_size: u32,
+    grid_commitment: &str,
+) -> Result<Vec<u8>, JsError> {
+    let grid_commitment = commitment::parse_field(grid_commitment).map_err(js_error)?;
+    Ok(PositionMovement {
+        move_x,
+        move_y,
+        is_hit,
+        grid_size,
+        grid_commitment,
+    }
+    .encode()
+    .to_vec())
+}
+
+/// Public inputs of the trap-commitment circuit for `defender`'s commitment
+#[wasm_bindgen(js_name = encodeTrapCommitment)]
+pub fn encode_trap_commitment(trap_commitment: &str, defender: &str) -> Result<Vec<u8>, JsError> {
+    let trap_commitment = commitment::parse_field(trap_commitment).map_err(js_error)?;
+    let defender = commitment::defender_field(defender).map_err(js_error)?;
+    Ok(TrapCommitment {
+        trap_commitment,
+        defender,
+    }
+    .encode()
+    .to_vec())
+}
+
+/// Public inputs of the trap-merkle-root circuit opening cell (`x`, `y`)
+#[wasm_bindgen(js_name = encodeTrapMerkleRoot)]
+pub fn encode_trap_merkle_root(trap_values: Vec<u8>, x: u32, y: u32) -> Result<Vec<u8>, JsError> {
+    let (opening, _) = opening(trap_values, x, y)?;
+    Ok(opening.encode().to_vec())
+}
+
+/// A proof from any supported prover toolchain in the verifier's canonical
+/// layout; bb.js proofs come back unchanged
+#[wasm_bindgen(js_name = normalizeProof)]
+pub fn normalize_proof(proof: Vec<u8>) -> Result<Vec<u8>, JsError> {
+    let (_, canonical) = proof_format::normalize(&proof).map_err(js_error)?;
+    Ok(canonical)
+}
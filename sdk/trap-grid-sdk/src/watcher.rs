@@ -0,0 +1,285 @@
+//! Event-driven view of a session
+//!
+//! [`GameWatcher`] polls `getEvents` for one session, folds the events into a
+//! locally reconstructed [`GameState`] and calls a [`GameHandler`] as things
+//! happen. Failed proofs revert `make_move`, so every move a handler sees has
+//! been verified on-chain.
+
+use std::thread;
+use std::time::Duration;
+
+use anyhow::Result;
+use serde::Serialize;
+use stellar_xdr::curr::ScAddress;
+
+use crate::client::{fetch_game, fetch_moves, fetch_pending_move};
+use crate::rpc::{EventsStart, RpcClient};
+use crate::{session_topics, GameEnded, GameEvent, GameStarted, Move, PendingMove, MAX_MOVES};
+
+/// Session state rebuilt from events (or seeded from contract storage)
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct GameState {
+    pub session_id: u32,
+    pub defender: Option<String>,
+    pub attacker: Option<String>,
+    pub moves: Vec<Move>,
+    /// Submitted by the attacker and not answered yet
+    pub pending: Option<PendingMove>,
+    pub hits: u32,
+    pub misses: u32,
+    pub ended: bool,
+    pub winner: Option<String>,
+}
+
+impl GameState {
+    pub fn new(session_id: u32) -> Self {
+        Self {
+            session_id,
+            ..Self::default()
+        }
+    }
+
+    /// Fold an event into the state; `false` if it was already applied
+    pub fn apply(&mut self, event: &GameEvent) -> bool {
+        match event {
+            GameEvent::GameStarted(started) => {
+                let changed = self.defender.as_ref() != Some(&started.defender);
+                self.defender = Some(started.defender.clone());
+                self.attacker = Some(started.attacker.clone());
+                changed
+            }
+            GameEvent::MoveSubmitted(submitted) => {
+                if self.is_played(submitted.x, submitted.y) {
+                    return false;
+                }
+                let pending = PendingMove {
+                    x: submitted.x,
+                    y: submitted.y,
+                };
+                let changed = self.pending != Some(pending);
+                self.pending = Some(pending);
+                changed
+            }
+            GameEvent::MoveMade(made) => {
+                if self.is_played(made.x, made.y) {
+                    return false;
+                }
+                self.pending = None;
+                self.moves.push(Move {
+                    x: made.x,
+                    y: made.y,
+                    is_hit: made.is_hit,
+                    verified: made.verified,
+                });
+                if made.is_hit {
+                    self.hits += 1;
+                } else {
+                    self.misses += 1;
+                }
+                true
+            }
+            GameEvent::GameEnded(ended) => {
+                let changed = !self.ended;
+                self.ended = true;
+                self.pending = None;
+                self.winner = Some(ended.winner.clone());
+                changed
+            }
+        }
+    }
+
+    pub fn is_played(&self, x: u32, y: u32) -> bool {
+        self.moves.iter().any(|m| m.x == x && m.y == y)
+    }
+
+    /// Cells not played yet
+    pub fn remaining(&self) -> u32 {
+        MAX_MOVES - self.moves.len() as u32
+    }
+}
+
+/// An event with the ledger and transaction it was published in
+#[derive(Clone, Debug, Serialize)]
+pub struct WatchedEvent {
+    pub ledger: u32,
+    pub ledger_closed_at: String,
+    pub tx_hash: String,
+    #[serde(flatten)]
+    pub event: GameEvent,
+}
+
+/// Callbacks for [`GameWatcher`]; `state` already includes the event
+#[allow(unused_variables)]
+pub trait GameHandler {
+    /// Every new event, before the specific callback
+    fn on_event(&mut self, state: &GameState, event: &WatchedEvent) {}
+
+    fn on_start(&mut self, state: &GameState, started: &GameStarted) {}
+
+    /// The attacker picked a cell that now waits for the defender's proof
+    fn on_move_submitted(&mut self, state: &GameState, pending: &PendingMove) {}
+
+    /// A move was recorded together with its verified hit/miss proof
+    fn on_move(&mut self, state: &GameState, recorded: &Move) {}
+
+    fn on_end(&mut self, state: &GameState, ended: &GameEnded) {}
+}
+
+pub struct GameWatcher<'a> {
+    rpc: &'a RpcClient,
+    contract: ScAddress,
+    state: GameState,
+    start_ledger: u32,
+    cursor: Option<String>,
+    interval: Duration,
+}
+
+impl<'a> GameWatcher<'a> {
+    /// Watch a session from `from_ledger`, replaying its events from there
+    ///
+    /// Without a start ledger the state is seeded from contract storage and
+    /// only newer events are read, which also works once older events have
+    /// left the RPC server's retention window.
+    pub fn new(
+        rpc: &'a RpcClient,
+        contract: ScAddress,
+        session_id: u32,
+        from_ledger: Option<u32>,
+    ) -> Result<Self> {
+        let mut state = GameState::new(session_id);
+        let start_ledger = match from_ledger {
+            Some(ledger) => ledger,
+            None => {
+                let latest = rpc.get_latest_ledger()?;
+                if let Some(game) = fetch_game(rpc, &contract, session_id)? {
+                    state.defender = Some(game.defender);
+                    state.attacker = Some(game.attacker);
+                    state.hits = game.hits;
+                    state.misses = game.misses;
+                    state.ended = game.game_ended;
+                    state.winner = game.winner;
+                    state.moves = fetch_moves(rpc, &contract, session_id)?;
+                    state.pending = fetch_pending_move(rpc, &contract, session_id)?;
+                }
+                latest
+            }
+        };
+        Ok(Self {
+            rpc,
+            contract,
+            state,
+            start_ledger,
+            cursor: None,
+            interval: Duration::from_secs(2),
+        })
+    }
+
+    /// Delay between polls in [`GameWatcher::run`]
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn state(&self) -> &GameState {
+        &self.state
+    }
+
+    pub fn start_ledger(&self) -> u32 {
+        self.start_ledger
+    }
+
+    /// Read the events published since the last poll and dispatch the new ones
+    pub fn poll(&mut self, handler: &mut impl GameHandler) -> Result<Vec<WatchedEvent>> {
+        let topics = session_topics(self.state.session_id);
+        let start = match &self.cursor {
+            Some(cursor) => EventsStart::Cursor(cursor),
+            None => EventsStart::Ledger(self.start_ledger),
+        };
+        let result = self.rpc.get_events(&self.contract, &topics, start)?;
+
+        let mut new_events = Vec::new();
+        for info in &result.events {
+            let (topics, value) = info.decode()?;
+            let Some(event) = GameEvent::decode(&topics, &value)? else {
+                continue;
+            };
+            if !self.state.apply(&event) {
+                continue;
+            }
+            let watched = WatchedEvent {
+                ledger: info.ledger,
+                ledger_closed_at: info.ledger_closed_at.clone(),
+                tx_hash: info.tx_hash.clone(),
+                event,
+            };
+            handler.on_event(&self.state, &watched);
+            match &watched.event {
+                GameEvent::GameStarted(started) => handler.on_start(&self.state, started),
+                GameEvent::MoveSubmitted(_) => {
+                    let pending = self.state.pending.expect("move was just submitted");
+                    handler.on_move_submitted(&self.state, &pending)
+                }
+                GameEvent::MoveMade(_) => {
+                    let recorded = self.state.moves.last().expect("move was just applied");
+                    handler.on_move(&self.state, recorded)
+                }
+                GameEvent::GameEnded(ended) => handler.on_end(&self.state, ended),
+            }
+            new_events.push(watched);
+        }
+
+        match result.cursor {
+            Some(next) => self.cursor = Some(next),
+            None => self.start_ledger = result.latest_ledger,
+        }
+        Ok(new_events)
+    }
+
+    /// Poll until the game ends (or forever with `follow`)
+    pub fn run(&mut self, handler: &mut impl GameHandler, follow: bool) -> Result<()> {
+        loop {
+            self.poll(handler)?;
+            if self.state.ended && !follow {
+                return Ok(());
+            }
+            thread::sleep(self.interval);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::MoveMade;
+
+    const ZERO_ACCOUNT: &str = "GAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAWHF";
+
+    #[test]
+    fn test_apply_is_idempotent() {
+        let mut state = GameState::new(1);
+        let made = GameEvent::MoveMade(MoveMade {
+            session_id: 1,
+            defender: ZERO_ACCOUNT.into(),
+            attacker: ZERO_ACCOUNT.into(),
+            x: 2,
+            y: 3,
+            is_hit: true,
+            verified: true,
+        });
+        assert!(state.apply(&made));
+        assert!(!state.apply(&made));
+        assert_eq!((state.hits, state.misses, state.remaining()), (1, 0, 63));
+
+        let ended = GameEvent::GameEnded(GameEnded {
+            session_id: 1,
+            defender: ZERO_ACCOUNT.into(),
+            attacker: ZERO_ACCOUNT.into(),
+            winner: ZERO_ACCOUNT.into(),
+            hits: 1,
+            misses: 0,
+        });
+        assert!(state.apply(&ended));
+        assert!(!state.apply(&ended));
+        assert!(state.ended);
+    }
+}